@@ -1,6 +1,26 @@
+pub mod alert_page_commands;
+pub mod anomaly_commands;
+pub mod api_token_commands;
+pub mod ban_commands;
+pub mod chat_ack_commands;
+pub mod cluster_commands;
+pub mod config_change_commands;
+pub mod daily_quota_commands;
+pub mod dead_letter_commands;
+pub mod event_source_commands;
 pub mod ingest_commands;
 pub mod item_registry_commands;
 pub mod key_item_commands;
+pub mod maintenance_commands;
+pub mod ml_export_commands;
 pub mod mod_config_commands;
 pub mod op_token_commands;
+pub mod origin_whitelist_commands;
+pub mod player_team_commands;
+pub mod quarantine_commands;
+pub mod replay_commands;
+pub mod report_commands;
+pub mod selftest_commands;
+pub mod server_identity_commands;
+pub mod suppression_commands;
 pub mod task_progress_commands;
@@ -0,0 +1,31 @@
+use crate::AppState;
+use backend_domain::MORE_ALERTS_COMMAND;
+
+/// Whether a group message asks for the next page of the latest alert batch (`/更多`, or `更多`).
+pub fn is_more_alerts_command(text: &str) -> bool {
+    let text = text.trim();
+    text == MORE_ALERTS_COMMAND || Some(text) == MORE_ALERTS_COMMAND.strip_prefix('/')
+}
+
+/// Reply to `/更多` in `group_id`: the next page, or a note that nothing is left to show.
+pub async fn next_alert_page_reply(state: &AppState, group_id: i64) -> String {
+    match state.alert_service.next_alert_page(group_id).await {
+        Some(page) => page,
+        None => format!(
+            "没有更多告警：最近一批已全部展示，或已超过 {} 分钟",
+            state.config.alert_page_ttl_minutes
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_command_accepts_bare_form() {
+        assert!(is_more_alerts_command(" /更多 "));
+        assert!(is_more_alerts_command("更多"));
+        assert!(!is_more_alerts_command("/更多告警"));
+    }
+}
@@ -0,0 +1,183 @@
+use tracing::{error, info};
+
+use crate::{AppError, ErrorCode};
+use crate::AppState;
+use backend_domain::{
+    anomaly_id_event_ms, current_millis, AnomalyAckRequest, AnomalyAckResult, AnomalySeenRequest,
+    AnomalySeenResult,
+};
+
+const MAX_ACK_NOTE_CHARS: usize = 500;
+/// Ids one read-receipt call may carry: a few list pages.
+const MAX_SEEN_IDS: usize = 500;
+
+/// Acknowledges every anomaly of one day that matches the filters, so false-positive storms
+/// can be cleared in one call. At least one filter besides `date` is required.
+pub async fn bulk_ack_anomalies(
+    state: &AppState,
+    request: AnomalyAckRequest,
+) -> Result<AnomalyAckResult, AppError> {
+    let request = normalize_ack_request(request)?;
+    let acked_at_ms = current_millis();
+    let matched = state
+        .anomaly_repo
+        .ack_anomalies(&request, acked_at_ms)
+        .await
+        .map_err(|err| {
+            error!("failed to acknowledge anomalies: {}", err);
+            AppError::Internal(err.into())
+        })?;
+    info!(
+        "acknowledged {} anomalies on {} (rule={:?}, player={:?}, server={:?}, item={:?}, by={:?})",
+        matched,
+        request.date,
+        request.rule_id,
+        request.player,
+        request.server_id,
+        request.item_id,
+        request.acked_by
+    );
+    Ok(AnomalyAckResult {
+        date: request.date,
+        matched,
+        acked_at_ms,
+    })
+}
+
+/// Records that the caller has had these anomalies on screen, so other moderators can skip
+/// them. Repeated receipts for an id are harmless.
+pub async fn mark_anomalies_seen(
+    state: &AppState,
+    request: AnomalySeenRequest,
+) -> Result<AnomalySeenResult, AppError> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in request.ids {
+        let id = id.trim().to_string();
+        if anomaly_id_event_ms(&id).is_none() {
+            return Err(AppError::BadRequest(format!("invalid anomaly id: {}", id)));
+        }
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() {
+        return Err(AppError::BadRequest("ids must not be empty".to_string()));
+    }
+    if ids.len() > MAX_SEEN_IDS {
+        return Err(AppError::BadRequest(format!(
+            "at most {} ids per call",
+            MAX_SEEN_IDS
+        )));
+    }
+    let seen_at_ms = current_millis();
+    state
+        .anomaly_repo
+        .mark_anomalies_seen(
+            &ids,
+            seen_at_ms,
+            request.seen_by.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map_err(|err| {
+            error!("failed to record anomaly read receipts: {}", err);
+            AppError::Internal(err.into())
+        })?;
+    Ok(AnomalySeenResult {
+        recorded: ids.len(),
+        seen_at_ms,
+    })
+}
+
+fn normalize_ack_request(request: AnomalyAckRequest) -> Result<AnomalyAckRequest, AppError> {
+    let date = request.date.trim().to_string();
+    if let Err(err) = backend_domain::parse_date(&date) {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid date: {}", err),
+        ));
+    }
+    let clean = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let normalized = AnomalyAckRequest {
+        date,
+        rule_id: clean(request.rule_id).map(|value| value.to_uppercase()),
+        player: clean(request.player),
+        server_id: clean(request.server_id),
+        item_id: clean(request.item_id).map(|value| value.to_lowercase()),
+        note: clean(request.note),
+        acked_by: clean(request.acked_by),
+    };
+    if normalized.rule_id.is_none()
+        && normalized.player.is_none()
+        && normalized.server_id.is_none()
+        && normalized.item_id.is_none()
+    {
+        return Err(AppError::BadRequest(
+            "at least one of rule_id, player, server_id, item_id is required".to_string(),
+        ));
+    }
+    if normalized
+        .note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_ACK_NOTE_CHARS)
+    {
+        return Err(AppError::BadRequest(format!(
+            "note must be at most {} characters",
+            MAX_ACK_NOTE_CHARS
+        )));
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+
+    fn request(rule_id: Option<&str>) -> AnomalyAckRequest {
+        AnomalyAckRequest {
+            date: " 2026-03-01 ".to_string(),
+            rule_id: rule_id.map(ToString::to_string),
+            player: Some("  ".to_string()),
+            server_id: None,
+            item_id: None,
+            note: None,
+            acked_by: None,
+        }
+    }
+
+    #[test]
+    fn ack_request_requires_a_narrowing_filter() {
+        assert!(normalize_ack_request(request(None)).is_err());
+        let normalized = normalize_ack_request(request(Some("r12"))).unwrap();
+        assert_eq!(normalized.date, "2026-03-01");
+        assert_eq!(normalized.rule_id.as_deref(), Some("R12"));
+        assert!(normalized.player.is_none());
+    }
+
+    #[tokio::test]
+    async fn read_receipts_are_deduplicated_and_validated() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let seen = |ids: &[&str]| AnomalySeenRequest {
+            ids: ids.iter().map(ToString::to_string).collect(),
+            seen_by: Some("alice".to_string()),
+        };
+        let id = "1772382600000-00000000000000ff";
+        let result = mark_anomalies_seen(&app.state, seen(&[id, &format!(" {} ", id)]))
+            .await
+            .unwrap();
+        assert_eq!(result.recorded, 1);
+        assert_eq!(
+            app.anomalies.seen(),
+            vec![(id.to_string(), "alice".to_string())]
+        );
+        assert!(mark_anomalies_seen(&app.state, seen(&[])).await.is_err());
+        assert!(mark_anomalies_seen(&app.state, seen(&[id, "not-an-id"]))
+            .await
+            .is_err());
+        assert_eq!(app.anomalies.seen().len(), 1);
+    }
+}
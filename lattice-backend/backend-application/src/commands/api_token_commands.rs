@@ -0,0 +1,80 @@
+use crate::commands::config_change_commands::notify_config_change;
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, ApiTokenIssueRequest, ApiTokenIssued, ApiTokenView};
+
+/// Issues a named token; its secret is only in the answer, the store keeps a digest.
+pub async fn issue_api_token(
+    state: &AppState,
+    request: ApiTokenIssueRequest,
+    actor: &str,
+) -> Result<ApiTokenIssued, AppError> {
+    let now = current_millis();
+    let (token, secret) = state
+        .api_tokens
+        .issue(&request.name, request.scope, request.expires_at_ms, now)
+        .await?;
+    persist_api_tokens(state).await?;
+    notify_config_change(
+        state,
+        actor,
+        &format!("新增 API 令牌 {}（{}）", token.name, token.scope.as_str()),
+    );
+    Ok(ApiTokenIssued {
+        token: secret,
+        info: ApiTokenView::new(&token, now),
+    })
+}
+
+/// Returns false when no active token has this name.
+pub async fn revoke_api_token(state: &AppState, name: &str, actor: &str) -> Result<bool, AppError> {
+    if !state.api_tokens.revoke(name, current_millis()).await {
+        return Ok(false);
+    }
+    persist_api_tokens(state).await?;
+    notify_config_change(state, actor, &format!("吊销 API 令牌 {}", name.trim()));
+    Ok(true)
+}
+
+/// A token change that is not saved would come back (or vanish) on restart, so it fails the call.
+async fn persist_api_tokens(state: &AppState) -> Result<(), AppError> {
+    let tokens = state.api_tokens.snapshot().await;
+    state
+        .config_repo
+        .save_api_tokens(&tokens)
+        .await
+        .map_err(AppError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::{ApiTokenScope, ConfigRepository};
+
+    #[tokio::test]
+    async fn issued_and_revoked_tokens_are_persisted() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let request = ApiTokenIssueRequest {
+            name: "mod-survival".to_string(),
+            scope: ApiTokenScope::Ingest,
+            expires_at_ms: None,
+        };
+        let issued = issue_api_token(&app.state, request, "desktop")
+            .await
+            .unwrap();
+        assert!(issued.info.active);
+        let stored = app.configs.load_api_tokens().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_ne!(stored[0].token_sha256, issued.token);
+
+        assert!(revoke_api_token(&app.state, "mod-survival", "desktop")
+            .await
+            .unwrap());
+        assert!(!revoke_api_token(&app.state, "mod-survival", "desktop")
+            .await
+            .unwrap());
+        let stored = app.configs.load_api_tokens().await.unwrap();
+        assert!(stored[0].revoked_at_ms.is_some());
+    }
+}
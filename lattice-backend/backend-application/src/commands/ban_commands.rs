@@ -0,0 +1,93 @@
+use tracing::{info, warn};
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, BanEventRequest, PlayerBan};
+
+const MAX_TEXT_CHARS: usize = 500;
+
+/// Applies a ban or unban from an external system. Returns the stored ban, or None for an unban.
+pub async fn record_ban_event(
+    state: &AppState,
+    request: BanEventRequest,
+) -> Result<Option<PlayerBan>, AppError> {
+    let now = current_millis();
+    let action = request
+        .action
+        .as_deref()
+        .map(|action| action.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "ban".to_string());
+    let ban = build_ban(request, now)?;
+    match action.as_str() {
+        "ban" => {
+            state.bans.ban(ban.clone(), now).await;
+            persist_bans(state).await;
+            info!(
+                "ban recorded for uuid={:?} name={:?} (source={:?}, until={:?})",
+                ban.player_uuid, ban.player_name, ban.source, ban.expires_at_ms
+            );
+            Ok(Some(ban))
+        }
+        "unban" => {
+            if state.bans.unban(&ban, now).await {
+                persist_bans(state).await;
+                info!(
+                    "ban lifted for uuid={:?} name={:?}",
+                    ban.player_uuid, ban.player_name
+                );
+            }
+            Ok(None)
+        }
+        other => Err(AppError::BadRequest(format!(
+            "action must be ban or unban, got '{}'",
+            other
+        ))),
+    }
+}
+
+pub async fn persist_bans(state: &AppState) {
+    let bans = state.bans.snapshot().await;
+    if let Err(err) = state.config_repo.save_bans(&bans).await {
+        warn!("failed to save bans: {}", err);
+    }
+}
+
+fn build_ban(request: BanEventRequest, now: i64) -> Result<PlayerBan, AppError> {
+    let text = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let player_uuid = text(request.player_uuid);
+    let player_name = text(request.player_name);
+    if player_uuid.is_none() && player_name.is_none() {
+        return Err(AppError::BadRequest(
+            "player_uuid or player_name is required".to_string(),
+        ));
+    }
+    let reason = text(request.reason);
+    let source = text(request.source);
+    if [&reason, &source].iter().any(|value| {
+        value
+            .as_ref()
+            .is_some_and(|value| value.chars().count() > MAX_TEXT_CHARS)
+    }) {
+        return Err(AppError::BadRequest(format!(
+            "reason and source must be at most {} characters",
+            MAX_TEXT_CHARS
+        )));
+    }
+    if request.expires_at_ms.is_some_and(|expires| expires <= now) {
+        return Err(AppError::BadRequest(
+            "expires_at_ms must be in the future".to_string(),
+        ));
+    }
+    Ok(PlayerBan {
+        player_uuid,
+        player_name,
+        reason,
+        source,
+        banned_at_ms: now,
+        expires_at_ms: request.expires_at_ms,
+    })
+}
@@ -0,0 +1,161 @@
+use chrono::Local;
+use tracing::{info, warn};
+
+use crate::commands::anomaly_commands;
+use crate::queries::anomaly_queries;
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    anomaly_id_event_ms, current_millis, AnomalyAckKey, AnomalyAckRequest, AnomalyLookupQuery,
+    RuntimeConfig, ACK_COMMAND,
+};
+
+/// Ack note that tells chat acknowledgements apart from the desktop's.
+const CHAT_ACK_NOTE: &str = "群聊 /处理";
+
+/// The target of `/处理 <anomaly_id|player>` (empty when missing), or `None` for other messages.
+/// The slash is required so ordinary chat about "处理" is never taken as a command.
+pub fn parse_ack_command(text: &str) -> Option<&str> {
+    let rest = text.trim().strip_prefix(ACK_COMMAND)?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim())
+}
+
+/// Groups whose members count as staff: the ones alerts are delivered to.
+fn is_alert_group(config: &RuntimeConfig, group_id: i64) -> bool {
+    config.alert_group_id == Some(group_id)
+        || config
+            .alert_team_routes
+            .iter()
+            .any(|route| route.group_id == Some(group_id))
+        || config
+            .alert_server_groups
+            .values()
+            .any(|server_group| *server_group == group_id)
+        || config
+            .alert_routing
+            .targets
+            .values()
+            .any(|target| target.group_id == Some(group_id))
+}
+
+/// Reply to `/处理 <target>` sent by `user_id` in `group_id`. An anomaly id acknowledges that
+/// anomaly, anything else is a player name whose anomalies of today are acknowledged; the QQ
+/// user id is kept as the reviewer (`acked_by = qq:<user id>`).
+pub async fn chat_ack_reply(
+    state: &AppState,
+    group_id: i64,
+    user_id: Option<i64>,
+    target: &str,
+) -> String {
+    if !is_alert_group(&state.config, group_id) {
+        return "处理失败：当前群不是告警群".to_string();
+    }
+    let Some(user_id) = user_id else {
+        return "处理失败：无法识别发送者 QQ".to_string();
+    };
+    if target.is_empty() {
+        return format!("用法：{} <异常ID|玩家名>", ACK_COMMAND);
+    }
+    let acked_by = format!("qq:{}", user_id);
+    let result = if anomaly_id_event_ms(target).is_some() {
+        ack_anomaly_by_id(state, target, &acked_by).await
+    } else {
+        ack_player_today(state, target, &acked_by).await
+    };
+    match result {
+        Ok(reply) => reply,
+        Err(err) => {
+            warn!("chat ack of {} by {} failed: {}", target, acked_by, err);
+            "处理失败：后端暂不可用，请稍后重试".to_string()
+        }
+    }
+}
+
+async fn ack_anomaly_by_id(state: &AppState, id: &str, acked_by: &str) -> Result<String, AppError> {
+    let query = AnomalyLookupQuery {
+        id: id.to_string(),
+        lang: None,
+    };
+    let Some(view) = anomaly_queries::get_anomaly(state, query).await? else {
+        return Ok(format!("未找到异常 {}", id));
+    };
+    let row = &view.row;
+    let summary = format!(
+        "{} {} {} x{}",
+        row.player_name, row.rule_id, row.item_id, row.count
+    );
+    if view.acknowledged {
+        return Ok(format!("异常 {}（{}）已处理过", view.id, summary));
+    }
+    let key = AnomalyAckKey {
+        event_time: row.event_time,
+        player_uuid: row.player_uuid.clone(),
+        item_id: row.item_id.clone(),
+        rule_id: row.rule_id.clone(),
+    };
+    state
+        .anomaly_repo
+        .ack_anomaly(&key, current_millis(), CHAT_ACK_NOTE, acked_by)
+        .await?;
+    info!("acknowledged anomaly {} (by={})", view.id, acked_by);
+    Ok(format!(
+        "已处理异常 {}（{}），处理人 {}",
+        view.id, summary, acked_by
+    ))
+}
+
+async fn ack_player_today(
+    state: &AppState,
+    player: &str,
+    acked_by: &str,
+) -> Result<String, AppError> {
+    let request = AnomalyAckRequest {
+        date: Local::now().format("%Y-%m-%d").to_string(),
+        rule_id: None,
+        player: Some(player.to_string()),
+        server_id: None,
+        item_id: None,
+        note: Some(CHAT_ACK_NOTE.to_string()),
+        acked_by: Some(acked_by.to_string()),
+    };
+    let result = anomaly_commands::bulk_ack_anomalies(state, request).await?;
+    if result.matched == 0 {
+        return Ok(format!("今日没有玩家 {} 的异常", player));
+    }
+    Ok(format!(
+        "已将玩家 {} 今日 {} 条异常标记为已处理，处理人 {}",
+        player, result.matched, acked_by
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_alert_groups_accept_acks() {
+        let mut config = backend_domain::testing::runtime_config();
+        config.alert_group_id = Some(100);
+        config
+            .alert_server_groups
+            .insert("survival".to_string(), 200);
+        assert!(is_alert_group(&config, 100));
+        assert!(is_alert_group(&config, 200));
+        assert!(!is_alert_group(&config, 300));
+    }
+
+    #[test]
+    fn ack_command_needs_the_slash_and_a_separator() {
+        assert_eq!(parse_ack_command(" /处理 Steve "), Some("Steve"));
+        assert_eq!(parse_ack_command("/处理"), Some(""));
+        assert_eq!(
+            parse_ack_command("/处理 1767225600000-00112233aabbccdd"),
+            Some("1767225600000-00112233aabbccdd")
+        );
+        assert_eq!(parse_ack_command("/处理中"), None);
+        assert_eq!(parse_ack_command("处理 Steve"), None);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::queries::config_queries;
+use crate::{AppError, AppState};
+use backend_domain::{
+    AnomalyRow, ClusterAnalyzeRequest, IngestEvent, ItemRegistryEntry, KeyItemRule, ThresholdUnit,
+    TransferMatching,
+};
+
+/// Packs one enriched batch with the current rule snapshot and strictness settings.
+pub async fn analyze_request(
+    state: &AppState,
+    events: Vec<IngestEvent>,
+    custom_events: Vec<IngestEvent>,
+) -> ClusterAnalyzeRequest {
+    let mut rules = state.key_rules.read().await.clone();
+    fill_stack_sizes(&mut rules, &state.item_registry.read().await);
+    let origin_whitelist = state.origin_whitelist.snapshot().await;
+    let strictness = config_queries::current_strictness(state);
+    ClusterAnalyzeRequest {
+        events,
+        custom_events,
+        rules,
+        transfer_window_ms: (state.config.transfer_window_seconds * 1000) as i64,
+        key_item_window_ms: (state.config.key_item_window_minutes * 60_000) as i64,
+        strict_pickup_window_ms: if strictness.enabled {
+            (strictness.pickup_window_seconds * 1000) as i64
+        } else {
+            0
+        },
+        strict_pickup_threshold: if strictness.enabled {
+            strictness.pickup_threshold as i64
+        } else {
+            0
+        },
+        origin_whitelist,
+        risk_overrides: state.config.rule_risk_overrides.clone(),
+        detection_rules: state.detection_rules.read().await.clone(),
+        composite_rules: state.composite_rules.read().await.clone(),
+        strict_transfer_fingerprint: state.config.strict_transfer_fingerprint,
+        transfer_matching: TransferMatching {
+            count_tolerance: state.config.transfer_count_tolerance,
+            count_tolerance_percent: state.config.transfer_count_tolerance_percent,
+            aggregate: state.config.transfer_aggregate,
+        },
+    }
+}
+
+/// Gives `stacks` rules without a `stack_size` the registry's `max_stack_size` for their item,
+/// so the snapshot sent to the analyzer is self-contained.
+fn fill_stack_sizes(rules: &mut HashMap<String, KeyItemRule>, registry: &[ItemRegistryEntry]) {
+    let mut missing = rules
+        .values_mut()
+        .filter(|rule| rule.threshold_unit() == ThresholdUnit::Stacks && rule.stack_size.is_none())
+        .peekable();
+    if missing.peek().is_none() {
+        return;
+    }
+    let stack_sizes: HashMap<&str, u32> = registry
+        .iter()
+        .filter_map(|entry| Some((entry.item_id.as_str(), entry.max_stack_size?)))
+        .collect();
+    for rule in missing {
+        rule.stack_size = stack_sizes.get(rule.item_id.as_str()).copied();
+    }
+}
+
+/// Runs the windowed detectors for a batch: on the shared state instance when this replica has
+/// one, otherwise in this process. A replica that cannot reach the state instance falls back to
+/// its own windows, so detection keeps running with per-replica counts until it is back.
+pub async fn analyze(state: &AppState, request: &ClusterAnalyzeRequest) -> Vec<AnomalyRow> {
+    if let Some(cluster_state) = &state.cluster_state {
+        match cluster_state.analyze(request).await {
+            Ok(anomalies) => return anomalies,
+            Err(err) => warn!(
+                "cluster state at {} unavailable, analyzing locally: {}",
+                state.config.cluster_state_url, err
+            ),
+        }
+    }
+    analyze_locally(state, request).await
+}
+
+pub async fn analyze_locally(state: &AppState, request: &ClusterAnalyzeRequest) -> Vec<AnomalyRow> {
+    let (mut anomalies, mut timings) = {
+        let mut analyzer = state.analyzer.lock().await;
+        analyzer.set_origin_whitelist(request.origin_whitelist.clone());
+        analyzer.set_risk_overrides(request.risk_overrides.clone());
+        analyzer.set_detection_rules(request.detection_rules.clone());
+        analyzer.set_composite_rules(request.composite_rules.clone());
+        analyzer.set_strict_transfer_fingerprint(request.strict_transfer_fingerprint);
+        analyzer.set_transfer_matching(request.transfer_matching);
+        let anomalies = analyzer.analyze_batch(
+            &request.events,
+            &request.rules,
+            request.transfer_window_ms,
+            request.key_item_window_ms,
+            request.strict_pickup_window_ms,
+            request.strict_pickup_threshold,
+        );
+        let timings: Vec<(&'static str, Duration)> = analyzer.rule_timings().iter().collect();
+        (anomalies, timings)
+    };
+    if !request.custom_events.is_empty() {
+        let started = Instant::now();
+        let mut detectors = state.custom_detectors.lock().await;
+        anomalies.extend(detectors.analyze(&request.custom_events));
+        timings.push(("R13", started.elapsed()));
+    }
+    record_rule_timings(
+        state,
+        &timings,
+        request.events.len() + request.custom_events.len(),
+    );
+    anomalies
+}
+
+/// Feeds the per-rule histograms and warns about rules over `slow_rule_budget_ms`.
+fn record_rule_timings(state: &AppState, timings: &[(&'static str, Duration)], events: usize) {
+    let budget = Duration::from_millis(state.config.slow_rule_budget_ms);
+    for (rule, elapsed) in timings {
+        state.metrics.record_rule_eval(rule, *elapsed);
+        if !budget.is_zero() && *elapsed > budget {
+            warn!(
+                "slow rule {}: {} ms for a batch of {} events (budget {} ms)",
+                rule,
+                elapsed.as_millis(),
+                events,
+                budget.as_millis()
+            );
+        }
+    }
+}
+
+/// Serves a replica's batch; only the instance that holds the shared windows accepts them, so a
+/// misconfigured replica cannot silently split the state.
+pub async fn serve_analyze(
+    state: &AppState,
+    request: ClusterAnalyzeRequest,
+) -> Result<Vec<AnomalyRow>, AppError> {
+    if !state.config.cluster_mode || !state.config.cluster_state_url.is_empty() {
+        return Err(AppError::BadRequest(
+            "this instance does not hold the shared analyzer state".to_string(),
+        ));
+    }
+    Ok(analyze_locally(state, &request).await)
+}
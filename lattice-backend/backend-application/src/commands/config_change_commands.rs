@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use tracing::{error, info, warn};
+
+use crate::AppState;
+use backend_domain::{current_millis, KeyItemRule};
+
+/// Item ids listed per kind of change before the rest are only counted.
+const LISTED_ITEMS: usize = 5;
+
+/// Announces a config or rule change to the alert group in the background, when
+/// `config_change_alert_enabled` is on.
+pub fn notify_config_change(state: &AppState, actor: &str, summary: &str) {
+    info!("config change by {}: {}", actor, summary);
+    if !state.config.config_change_alert_enabled {
+        return;
+    }
+    let message = format!("[Lattice 配置变更] {}\n操作人: {}", summary, actor);
+    let alert_service = state.alert_service.clone();
+    let config = state.config.clone();
+    tokio::spawn(async move {
+        if let Err(err) = alert_service.send_system_alert(&config, &message).await {
+            error!("config change alert failed: {}", err);
+        }
+    });
+}
+
+/// Records a change of the active key item rules as the next rule revision, then announces it
+/// like any other config change.
+pub async fn record_rule_change(state: &AppState, actor: &str, summary: &str) {
+    let revision = state
+        .rule_revisions
+        .record(actor, summary, current_millis())
+        .await;
+    info!("rule revision {} by {}", revision.revision, actor);
+    let revisions = state.rule_revisions.snapshot().await;
+    if let Err(err) = state.config_repo.save_rule_revisions(&revisions).await {
+        warn!("failed to save rule revisions: {}", err);
+    }
+    notify_config_change(state, actor, summary);
+}
+
+/// `新增 …；删除 …；修改 …` for the item ids that differ between two rule sets, or `None` when
+/// they are the same.
+pub fn describe_rule_changes(
+    before: &HashMap<String, KeyItemRule>,
+    after: &HashMap<String, KeyItemRule>,
+) -> Option<String> {
+    let mut added: Vec<&str> = Vec::new();
+    let mut changed: Vec<&str> = Vec::new();
+    for (item_id, rule) in after {
+        match before.get(item_id) {
+            None => added.push(item_id),
+            Some(old) if !same_rule(old, rule) => changed.push(item_id),
+            Some(_) => {}
+        }
+    }
+    let removed: Vec<&str> = before
+        .keys()
+        .filter(|item_id| !after.contains_key(*item_id))
+        .map(String::as_str)
+        .collect();
+    let mut parts = Vec::new();
+    for (label, mut items) in [("新增", added), ("删除", removed), ("修改", changed)] {
+        if items.is_empty() {
+            continue;
+        }
+        items.sort_unstable();
+        let mut text = format!(
+            "{} {} 条: {}",
+            label,
+            items.len(),
+            items[..items.len().min(LISTED_ITEMS)].join(", ")
+        );
+        if items.len() > LISTED_ITEMS {
+            text.push_str(" 等");
+        }
+        parts.push(text);
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("；"))
+    }
+}
+
+fn same_rule(a: &KeyItemRule, b: &KeyItemRule) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::KeyItemRuleApi;
+
+    fn rules(entries: &[(&str, u64)]) -> HashMap<String, KeyItemRule> {
+        entries
+            .iter()
+            .map(|(item_id, threshold)| {
+                (
+                    item_id.to_string(),
+                    KeyItemRule::from(KeyItemRuleApi {
+                        item_id: item_id.to_string(),
+                        threshold: *threshold,
+                        risk_level: "HIGH".to_string(),
+                        daily_quota: None,
+                        unit: None,
+                        stack_size: None,
+                        unit_value: None,
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rule_changes_are_summarized_by_kind() {
+        let before = rules(&[("minecraft:diamond", 64), ("minecraft:elytra", 1)]);
+        let after = rules(&[("minecraft:diamond", 32), ("minecraft:beacon", 1)]);
+        assert_eq!(
+            describe_rule_changes(&before, &after).as_deref(),
+            Some("新增 1 条: minecraft:beacon；删除 1 条: minecraft:elytra；修改 1 条: minecraft:diamond")
+        );
+        assert_eq!(describe_rule_changes(&after, &after), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use tracing::warn;
+
+use crate::AppState;
+use backend_domain::{
+    build_daily_quota_anomaly, daily_quota_candidates, AnomalyRow, IngestEvent, ItemPatternSet,
+    KeyItemRule,
+};
+
+/// Checks stored `ACQUIRE` totals for today against `daily_quota` rules; one R14 anomaly per
+/// player and item per day. Runs after the batch is inserted so the totals include it.
+pub async fn evaluate_daily_quotas(
+    state: &AppState,
+    events: &[IngestEvent],
+    rules: &HashMap<String, KeyItemRule>,
+) -> Vec<AnomalyRow> {
+    let patterns = ItemPatternSet::from_rules(rules);
+    let candidates = daily_quota_candidates(events, rules, &patterns);
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let pending = state
+        .daily_quotas
+        .unflagged(&date, candidates.keys().cloned())
+        .await;
+    if pending.is_empty() {
+        return Vec::new();
+    }
+    let mut player_uuids: Vec<String> = pending.iter().map(|(uuid, _)| uuid.clone()).collect();
+    let mut item_ids: Vec<String> = pending.iter().map(|(_, item)| item.clone()).collect();
+    player_uuids.sort();
+    player_uuids.dedup();
+    item_ids.sort();
+    item_ids.dedup();
+    let totals = match state
+        .event_repo
+        .fetch_daily_acquired_totals(&date, &player_uuids, &item_ids)
+        .await
+    {
+        Ok(totals) => totals,
+        Err(err) => {
+            warn!("failed to fetch daily quota totals: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut anomalies = Vec::new();
+    for total in totals {
+        let key = (total.player_uuid.clone(), total.item_id.clone());
+        let (Some(latest), Some(rule)) = (candidates.get(&key), patterns.find(rules, &total.item_id)) else {
+            continue;
+        };
+        let Some(quota) = rule.daily_quota else {
+            continue;
+        };
+        if total.total <= quota as i64
+            || !state
+                .daily_quotas
+                .flag(&date, &total.player_uuid, &total.item_id)
+                .await
+        {
+            continue;
+        }
+        anomalies.push(build_daily_quota_anomaly(
+            &total, quota, rule, &date, latest,
+        ));
+    }
+    anomalies
+}
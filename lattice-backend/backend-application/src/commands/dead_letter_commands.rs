@@ -0,0 +1,205 @@
+use tracing::{info, warn};
+
+use crate::AppState;
+use backend_domain::{current_millis, DeadLetterBatch, HealthTransition, ReadyStatus};
+
+/// Records a failed ClickHouse write and parks `batch` in the dead-letter queue. Returns false
+/// when the queue is disabled, in which case the caller must surface the error.
+pub async fn dead_letter(state: &AppState, batch: DeadLetterBatch, error: &anyhow::Error) -> bool {
+    record_storage_failure(state, error).await;
+    if !state.dead_letters.enabled() {
+        return false;
+    }
+    let rows = batch.len();
+    let _log = state.dead_letters.lock_log().await;
+    let appended = match state.config_repo.append_dead_letters(&batch).await {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("failed to append dead letters: {}", err);
+            false
+        }
+    };
+    let dropped = state.dead_letters.push(batch).await;
+    if dropped > 0 {
+        warn!(
+            "dead-letter queue full ({} events), dropped {} oldest rows",
+            state.config.dead_letter_max_events, dropped
+        );
+    }
+    if dropped > 0 || !appended {
+        persist_dead_letters(state).await;
+    }
+    warn!(
+        "queued {} rows for replay after write failure: {}",
+        rows, error
+    );
+    true
+}
+
+pub async fn record_storage_failure(state: &AppState, error: &anyhow::Error) {
+    if state
+        .degraded
+        .record_failure(current_millis(), &error.to_string())
+        .await
+    {
+        warn!("ClickHouse unavailable, entering degraded mode: {}", error);
+        publish_health(state, "degraded", Some(error.to_string()));
+    }
+}
+
+pub async fn record_storage_success(state: &AppState) {
+    if state
+        .degraded
+        .record_success(
+            current_millis(),
+            (state.config.degraded_recovery_seconds * 1000) as i64,
+        )
+        .await
+    {
+        info!("ClickHouse recovered, leaving degraded mode");
+        publish_health(state, "ready", None);
+    }
+}
+
+fn publish_health(state: &AppState, status: &str, detail: Option<String>) {
+    if let Some(publisher) = &state.event_publisher {
+        publisher.publish_health(&HealthTransition {
+            component: "clickhouse".to_string(),
+            status: status.to_string(),
+            at_ms: current_millis(),
+            detail,
+        });
+    }
+}
+
+/// Reports `ready`, `degraded` (writes are being queued) or `down` (nothing can absorb writes
+/// because the dead-letter queue is disabled or full). ClickHouse is pinged here only when the
+/// watchdog task is disabled; otherwise its last results decide.
+pub async fn check_readiness(state: &AppState) -> ReadyStatus {
+    let clickhouse_failures = if state.config.clickhouse_watchdog_interval_seconds > 0 {
+        let watchdog = state.clickhouse_watchdog.status().await;
+        Some(watchdog.consecutive_failures)
+    } else {
+        match ping_storage(state).await {
+            Ok(()) => record_storage_success(state).await,
+            Err(err) => record_storage_failure(state, &err).await,
+        }
+        None
+    };
+    let dead_letter_events = state.dead_letters.len_events().await;
+    let Some(degraded) = state.degraded.status().await else {
+        return ReadyStatus {
+            status: "ready".to_string(),
+            degraded_since_ms: None,
+            last_error: None,
+            dead_letter_events,
+            clickhouse_failures,
+        };
+    };
+    let status = if state.dead_letters.enabled()
+        && dead_letter_events < state.config.dead_letter_max_events
+    {
+        "degraded"
+    } else {
+        "down"
+    };
+    ReadyStatus {
+        status: status.to_string(),
+        degraded_since_ms: Some(degraded.since_ms),
+        last_error: degraded.last_error,
+        dead_letter_events,
+        clickhouse_failures,
+    }
+}
+
+/// Pings ClickHouse, giving up after `request_timeout_seconds`.
+pub async fn ping_storage(state: &AppState) -> anyhow::Result<()> {
+    let timeout_secs = state.config.request_timeout_seconds.max(1);
+    let timeout_duration = std::time::Duration::from_secs(timeout_secs);
+    match tokio::time::timeout(timeout_duration, state.event_repo.ping()).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow::anyhow!("ping timeout after {}s", timeout_secs)),
+    }
+}
+
+/// Re-inserts queued batches in order once ClickHouse answers again; stops at the first failure
+/// and keeps it and everything after it queued. Returns the number of replayed rows.
+pub async fn replay_dead_letters(state: &AppState) -> usize {
+    if state.dead_letters.len_events().await == 0 {
+        return 0;
+    }
+    if let Err(err) = state.event_repo.ping().await {
+        record_storage_failure(state, &err).await;
+        return 0;
+    }
+    // Held through the replay: batches in flight are in neither the queue nor a rewritten log
+    // until they are requeued.
+    let _log = state.dead_letters.lock_log().await;
+    let mut batches = state.dead_letters.take_all().await.into_iter();
+    let mut replayed = 0;
+    let mut failed = Vec::new();
+    for batch in batches.by_ref() {
+        let result = match &batch {
+            DeadLetterBatch::Events(rows) => state.event_repo.insert_events(rows).await,
+            DeadLetterBatch::CustomEvents(rows) => {
+                state.event_repo.insert_custom_events(rows).await
+            }
+            DeadLetterBatch::Anomalies(rows) => state.anomaly_repo.insert_anomalies(rows).await,
+        };
+        match result {
+            Ok(()) => replayed += batch.len(),
+            Err(err) => {
+                warn!("dead-letter replay stopped: {}", err);
+                record_storage_failure(state, &err).await;
+                failed.push(batch);
+                break;
+            }
+        }
+    }
+    failed.extend(batches);
+    if !failed.is_empty() {
+        state.dead_letters.requeue(failed).await;
+    }
+    persist_dead_letters(state).await;
+    if replayed > 0 {
+        info!("replayed {} dead-letter rows", replayed);
+    }
+    replayed
+}
+
+/// Rewrites the log from the queue; the caller holds `lock_log`.
+async fn persist_dead_letters(state: &AppState) {
+    let batches = state.dead_letters.snapshot().await;
+    if let Err(err) = state.config_repo.save_dead_letters(&batches).await {
+        warn!("failed to save dead letters: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::{runtime_config, Scenario};
+
+    #[tokio::test]
+    async fn failed_writes_are_appended_and_the_log_is_rewritten_on_overflow() {
+        let mut config = runtime_config();
+        config.dead_letter_max_events = 3;
+        let app = InMemoryApp::new(config);
+        let events: Vec<_> = Scenario::new()
+            .acquires_without_origin("minecraft:diamond", 1)
+            .acquires_without_origin("minecraft:emerald", 1)
+            .events()
+            .cloned()
+            .collect();
+        let error = anyhow::anyhow!("connection refused");
+
+        assert!(dead_letter(&app.state, DeadLetterBatch::Events(events.clone()), &error).await);
+        assert!(dead_letter(&app.state, DeadLetterBatch::CustomEvents(events), &error).await);
+        let stored = app.state.config_repo.load_dead_letters().await.unwrap();
+        assert!(
+            matches!(stored.as_slice(), [DeadLetterBatch::CustomEvents(rows)] if rows.len() == 2)
+        );
+        assert_eq!(app.state.dead_letters.len_events().await, 2);
+    }
+}
@@ -0,0 +1,29 @@
+use tracing::{info, warn};
+
+use crate::commands::ingest_commands;
+use crate::AppState;
+use backend_domain::ports::EventSource;
+
+/// Feeds every batch from `source` through the same validation and pipeline as HTTP ingest until
+/// the source closes. Failed batches are logged; a source has no caller to report them to.
+pub async fn consume_event_source(state: AppState, mut source: Box<dyn EventSource>, name: &str) {
+    info!("{} ingest started", name);
+    while let Some(mut events) = source.next_batch().await {
+        let original_len = events.len();
+        events.retain(ingest_commands::is_valid_event);
+        if events.len() != original_len {
+            warn!(
+                "{} ingest dropped {} invalid events (empty item_id/air/<=0 count/no custom_type)",
+                name,
+                original_len - events.len()
+            );
+        }
+        if events.is_empty() {
+            continue;
+        }
+        if let Err(err) = ingest_commands::process_ingest_events(&state, events).await {
+            warn!("{} ingest batch failed: {}", name, err);
+        }
+    }
+    info!("{} ingest stopped", name);
+}
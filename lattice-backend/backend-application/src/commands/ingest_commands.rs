@@ -1,46 +1,268 @@
-use tracing::warn;
+use std::time::Instant;
+
+use tracing::{error, warn};
+use crate::commands::cluster_commands;
+use crate::commands::daily_quota_commands::evaluate_daily_quotas;
+use crate::commands::origin_whitelist_commands::learn_origin_types;
+use crate::commands::dead_letter_commands::{dead_letter, record_storage_success};
+use crate::commands::player_team_commands::spawn_routed_alerts;
+use crate::ops::{ModVersionCheck, ModVersionGate};
 use crate::AppState;
-use backend_domain::IngestEvent;
+use backend_domain::{
+    current_millis, custom_type, is_persisting_finding, is_quarantined, ClusterAnalyzeRequest,
+    DeadLetterBatch, EnrichmentContext, IngestEvent, ServerHeartbeat, DAILY_QUOTA_RULE_ID,
+};
 use crate::AppError;
 
 pub async fn process_ingest_events(
     state: &AppState,
     events: Vec<IngestEvent>,
 ) -> Result<(), AppError> {
-    if let Err(err) = state.event_repo.insert_events(&events).await {
-        state.metrics.record_ingest_error();
-        return Err(AppError::Internal(err.into()));
-    }
-
-    let rules_snapshot = { state.key_rules.read().await.clone() };
-    let anomalies = {
-        let mut analyzer = state.analyzer.lock().await;
-        analyzer.analyze_batch(
-            &events,
-            &rules_snapshot,
-            (state.config.transfer_window_seconds * 1000) as i64,
-            (state.config.key_item_window_minutes * 60_000) as i64,
-            if state.config.strict_enabled {
-                (state.config.strict_pickup_window_seconds * 1000) as i64
-            } else {
-                0
-            },
-            if state.config.strict_enabled {
-                state.config.strict_pickup_threshold as i64
-            } else {
-                0
-            },
+    let started = Instant::now();
+    let result = process_batch(state, events).await;
+    state.metrics.record_ingest_latency(started.elapsed());
+    result
+}
+
+async fn process_batch(state: &AppState, mut events: Vec<IngestEvent>) -> Result<(), AppError> {
+    let duplicates = state.event_dedup.retain_new(&mut events);
+    if duplicates > 0 {
+        state.metrics.record_ingest_duplicates(duplicates);
+        if events.is_empty() {
+            state.metrics.record_ingest(0);
+            return Ok(());
+        }
+    }
+    let total = events.len();
+    if let Some(recorder) = &state.ingest_recorder {
+        recorder.record(current_millis(), &events);
+    }
+    enrich_events(state, &mut events).await;
+    let (custom_events, events): (Vec<IngestEvent>, Vec<IngestEvent>) =
+        events.into_iter().partition(IngestEvent::is_custom);
+    // While ClickHouse is down, rejected batches are queued for replay and analysis still runs,
+    // so the mod does not retry and alerts keep flowing.
+    let mut storage_ok = true;
+    if !events.is_empty() {
+        let inserting = Instant::now();
+        let inserted = state.event_repo.insert_events(&events).await;
+        state
+            .metrics
+            .record_clickhouse_insert("item_events", inserting.elapsed());
+        if let Err(err) = inserted {
+            state.metrics.record_ingest_error();
+            storage_ok = false;
+            if !dead_letter(state, DeadLetterBatch::Events(events.clone()), &err).await {
+                // The mod retries a rejected batch; it must not be taken for a duplicate.
+                state.event_dedup.forget(&events);
+                state.event_dedup.forget(&custom_events);
+                return Err(AppError::Unavailable(err));
+            }
+        }
+    }
+    if !custom_events.is_empty() {
+        let inserting = Instant::now();
+        let inserted = state.event_repo.insert_custom_events(&custom_events).await;
+        state
+            .metrics
+            .record_clickhouse_insert("custom_events", inserting.elapsed());
+        if let Err(err) = inserted {
+            state.metrics.record_ingest_error();
+            storage_ok = false;
+            if !dead_letter(
+                state,
+                DeadLetterBatch::CustomEvents(custom_events.clone()),
+                &err,
+            )
+            .await
+            {
+                state.event_dedup.forget(&events);
+                state.event_dedup.forget(&custom_events);
+                return Err(AppError::Unavailable(err));
+            }
+        }
+    }
+    state
+        .ingest_tracker
+        .record_success(
+            events
+                .iter()
+                .chain(&custom_events)
+                .filter_map(|event| event.server_id.as_deref()),
+            current_millis(),
         )
-    };
+        .await;
+
+    let request = cluster_commands::analyze_request(state, events, custom_events).await;
+    let mut anomalies = cluster_commands::analyze(state, &request).await;
+    learn_origin_types(state, &request.events).await;
+    let ClusterAnalyzeRequest {
+        events,
+        rules: rules_snapshot,
+        ..
+    } = request;
+    // Quota totals come from ClickHouse, so they are only meaningful once this batch is stored.
+    if storage_ok {
+        anomalies.extend(evaluate_daily_quotas(state, &events, &rules_snapshot).await);
+    }
 
     if !anomalies.is_empty() {
-        if let Err(err) = state.anomaly_repo.insert_anomalies(&anomalies).await {
+        if state
+            .storage_findings
+            .classify(&mut anomalies, current_millis())
+            .await
+        {
+            persist_storage_findings(state).await;
+        }
+        state.quarantines.tag(&mut anomalies).await;
+        state.recent_anomalies.push(&anomalies).await;
+        state.anomaly_stream.publish(&anomalies);
+        let inserting = Instant::now();
+        let inserted = state.anomaly_repo.insert_anomalies(&anomalies).await;
+        state
+            .metrics
+            .record_clickhouse_insert("anomalies", inserting.elapsed());
+        if let Err(err) = inserted {
             warn!("failed to insert anomalies: {}", err);
+            storage_ok = false;
+            dead_letter(state, DeadLetterBatch::Anomalies(anomalies.clone()), &err).await;
+        }
+        state.metrics.record_anomalies(&anomalies);
+        if let Some(publisher) = &state.event_publisher {
+            publisher.publish_anomalies(&anomalies);
         }
-        state.metrics.record_anomalies(anomalies.len());
-        state.alert_service.spawn_alerts(state.config.clone(), anomalies.clone());
+        // Unchanged storage findings from earlier scans, suppressed anomalies and anomalies of
+        // banned or quarantined players stay in the report but do not alert.
+        let now = current_millis();
+        let mut alerts = Vec::new();
+        for row in anomalies {
+            if row.rule_id == DAILY_QUOTA_RULE_ID && !state.config.daily_quota_alert_enabled {
+                continue;
+            }
+            if !is_persisting_finding(&row)
+                && !is_quarantined(&row)
+                && !state.suppressions.is_suppressed(&row, now).await
+                && !state.bans.is_banned(&row, now).await
+            {
+                alerts.push(row);
+            }
+        }
+        if !alerts.is_empty() {
+            spawn_routed_alerts(state, alerts).await;
+        }
+    }
+
+    if storage_ok {
+        record_storage_success(state).await;
+    }
+    state.metrics.record_ingest(total);
+    Ok(())
+}
+
+/// Whether an event can be analyzed: item events need a non-air item and a positive count,
+/// custom events a `custom_type`.
+pub fn is_valid_event(event: &IngestEvent) -> bool {
+    if event.is_custom() {
+        return !custom_type(event).is_empty();
     }
+    !(event.item_id.trim().is_empty() || event.item_id == "minecraft:air" || event.count <= 0)
+}
 
-    state.metrics.record_ingest(events.len());
+/// Runs the configured enrichers so stored rows and the analyzers see the same values.
+pub(crate) async fn enrich_events(state: &AppState, events: &mut [IngestEvent]) {
+    let mut chain = state.enrichment.lock().await;
+    if chain.is_empty() {
+        return;
+    }
+    let item_registry = state.item_registry.read().await;
+    let key_rules = state.key_rules.read().await;
+    chain.enrich(
+        events,
+        &EnrichmentContext {
+            item_registry: &item_registry,
+            key_rules: &key_rules,
+        },
+    );
+}
+
+pub async fn record_heartbeat(
+    state: &AppState,
+    mut heartbeat: ServerHeartbeat,
+) -> Result<(), AppError> {
+    heartbeat.server_id = heartbeat.server_id.trim().to_lowercase();
+    if heartbeat.server_id.is_empty() {
+        return Err(AppError::BadRequest(
+            "server_id must not be empty".to_string(),
+        ));
+    }
+    heartbeat.mod_version = heartbeat
+        .mod_version
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(tps) = heartbeat.tps {
+        if !tps.is_finite() || tps < 0.0 {
+            return Err(AppError::BadRequest(
+                "tps must be a non-negative number".to_string(),
+            ));
+        }
+    }
+    heartbeat.received_at_ms = current_millis();
+    state.heartbeats.record(heartbeat).await;
     Ok(())
 }
+
+/// Rejects outdated mods when enforcement is on; otherwise the caller only flags the response.
+pub async fn check_mod_version(
+    state: &AppState,
+    server_id: Option<&str>,
+    mod_version: Option<&str>,
+) -> Result<ModVersionCheck, AppError> {
+    let check = ModVersionGate::check(state.config.min_mod_version.as_deref(), mod_version);
+    let ModVersionCheck::Outdated { minimum } = &check else {
+        return Ok(check);
+    };
+    let server_id = server_id.unwrap_or("unknown");
+    let version = mod_version.unwrap_or_default();
+    if state
+        .mod_version_gate
+        .first_report(server_id, version)
+        .await
+    {
+        warn!(
+            "outdated mod version: server={} version={} minimum={}",
+            server_id, version, minimum
+        );
+        let message = format!(
+            "[Lattice 版本告警] server={} mod 版本 {} 低于最低要求 {}{}",
+            server_id,
+            version,
+            minimum,
+            if state.config.mod_version_enforce {
+                "，上报已被拒绝"
+            } else {
+                ""
+            }
+        );
+        let alert_service = state.alert_service.clone();
+        let config = state.config.clone();
+        tokio::spawn(async move {
+            if let Err(err) = alert_service.send_system_alert(&config, &message).await {
+                error!("mod version alert failed: {}", err);
+            }
+        });
+    }
+    if state.config.mod_version_enforce {
+        return Err(AppError::BadRequest(format!(
+            "mod version {} is older than minimum supported {}",
+            version, minimum
+        )));
+    }
+    Ok(check)
+}
+
+pub(crate) async fn persist_storage_findings(state: &AppState) {
+    let findings = state.storage_findings.snapshot().await;
+    if let Err(err) = state.config_repo.save_storage_findings(&findings).await {
+        warn!("failed to save storage findings: {}", err);
+    }
+}
@@ -1,7 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
 
 use crate::AppState;
-use backend_domain::{ItemRegistryPayload, ItemRegistryUpdateQuery};
+use backend_domain::{
+    ItemRegistryDeleteQuery, ItemRegistryDeleteResult, ItemRegistryEntry, ItemRegistryPayload,
+    ItemRegistryUpdateQuery,
+};
 use crate::AppError;
 
 pub async fn update_item_registry(
@@ -75,3 +80,122 @@ pub async fn update_item_registry(
     *state.item_registry.write().await = merged;
     Ok(())
 }
+
+/// Removes registry entries in `namespace` and/or not seen in `item_events` for `unseen_days`
+/// days; a dry run only lists them.
+pub async fn delete_item_registry(
+    state: &AppState,
+    query: ItemRegistryDeleteQuery,
+) -> Result<ItemRegistryDeleteResult, AppError> {
+    let namespace = query
+        .namespace
+        .map(|namespace| namespace.trim().to_lowercase())
+        .filter(|namespace| !namespace.is_empty());
+    if query.unseen_days == Some(0) {
+        return Err(AppError::BadRequest("unseen_days must be at least 1".to_string()));
+    }
+    if namespace.is_none() && query.unseen_days.is_none() {
+        return Err(AppError::BadRequest(
+            "namespace or unseen_days is required".to_string(),
+        ));
+    }
+    let seen = match query.unseen_days {
+        Some(days) => {
+            let since_ms =
+                Utc::now().timestamp_millis() - i64::from(days) * 24 * 60 * 60 * 1000;
+            let ids = state
+                .event_repo
+                .fetch_item_ids_seen_since(since_ms)
+                .await
+                .map_err(AppError::Unavailable)?;
+            Some(ids.into_iter().collect::<HashSet<_>>())
+        }
+        None => None,
+    };
+
+    let mut items = state.item_registry.write().await;
+    let removed = select_removals(&items, namespace.as_deref(), seen.as_ref());
+    if query.dry_run || removed.is_empty() {
+        return Ok(ItemRegistryDeleteResult {
+            dry_run: query.dry_run,
+            remaining: items.len() - removed.len(),
+            removed,
+        });
+    }
+    let removed_ids: HashSet<&str> = removed.iter().map(String::as_str).collect();
+    let kept: Vec<ItemRegistryEntry> = items
+        .iter()
+        .filter(|entry| !removed_ids.contains(entry.item_id.as_str()))
+        .cloned()
+        .collect();
+    state
+        .config_repo
+        .save_item_registry(&state.config.item_registry_path, &kept)
+        .await
+        .map_err(AppError::Internal)?;
+    *items = kept;
+    Ok(ItemRegistryDeleteResult {
+        dry_run: false,
+        remaining: items.len(),
+        removed,
+    })
+}
+
+/// Item ids matching every given filter: the namespace, and absence from `seen`.
+fn select_removals(
+    items: &[ItemRegistryEntry],
+    namespace: Option<&str>,
+    seen: Option<&HashSet<String>>,
+) -> Vec<String> {
+    items
+        .iter()
+        .filter(|entry| {
+            namespace.is_none_or(|namespace| entry.item_id.split(':').next() == Some(namespace))
+        })
+        .filter(|entry| seen.is_none_or(|seen| !seen.contains(&entry.item_id)))
+        .map(|entry| entry.item_id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(item_id: &str) -> ItemRegistryEntry {
+        ItemRegistryEntry {
+            item_id: item_id.to_string(),
+            name: None,
+            names: None,
+            namespace: None,
+            path: None,
+            max_stack_size: None,
+        }
+    }
+
+    #[test]
+    fn removals_match_namespace_and_unseen_filters_together() {
+        let items = vec![
+            entry("create:cogwheel"),
+            entry("create:shaft"),
+            entry("minecraft:diamond"),
+            entry("minecraft:stone"),
+        ];
+        let seen: HashSet<String> = ["create:shaft", "minecraft:stone"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            select_removals(&items, Some("create"), None),
+            ["create:cogwheel", "create:shaft"]
+        );
+        assert_eq!(
+            select_removals(&items, None, Some(&seen)),
+            ["create:cogwheel", "minecraft:diamond"]
+        );
+        assert_eq!(
+            select_removals(&items, Some("minecraft"), Some(&seen)),
+            ["minecraft:diamond"]
+        );
+    }
+}
@@ -1,35 +1,56 @@
+use tracing::info;
+
+use crate::commands::config_change_commands::{describe_rule_changes, record_rule_change};
 use crate::AppState;
-use backend_domain::{KeyItemRule, KeyItemRuleApi};
-use crate::AppError;
+use backend_domain::{
+    compile_item_pattern, find_rule_preset, is_item_pattern, is_risk_level, merge_rule_preset, KeyItemRule, KeyItemRuleApi, RulePresetApplyRequest,
+    RulePresetApplyResult,
+};
+use crate::{AppError, ErrorCode};
 
+/// Replaces the key item rules; `actor` names who did it in the config change notification.
 pub async fn update_key_items(
     state: &AppState,
     incoming_rules: Vec<KeyItemRuleApi>,
+    actor: &str,
 ) -> Result<(), AppError> {
     let mut rules = Vec::new();
     for rule in incoming_rules.into_iter() {
         let normalized = rule.normalized();
         if normalized.item_id.is_empty() {
-            return Err(AppError::BadRequest("item_id is required".to_string()));
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidItemId,
+                "item_id is required".to_string(),
+            ));
         }
         if !normalized.item_id.contains(':') {
-            return Err(AppError::BadRequest(format!(
-                "invalid item_id '{}'",
-                normalized.item_id
-            )));
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidItemId,
+                format!("invalid item_id '{}'", normalized.item_id),
+            ));
+        }
+        if is_item_pattern(&normalized.item_id) {
+            if let Err(err) = compile_item_pattern(&normalized.item_id) {
+                return Err(AppError::Invalid(ErrorCode::InvalidItemId, err));
+            }
         }
-        if normalized.threshold == 0 {
-            return Err(AppError::BadRequest(format!(
-                "threshold must be > 0 for '{}'",
-                normalized.item_id
-            )));
+        if normalized.threshold == 0 && normalized.daily_quota.is_none() {
+            return Err(AppError::Invalid(
+                ErrorCode::RuleThresholdZero,
+                format!(
+                    "threshold or daily_quota must be > 0 for '{}'",
+                    normalized.item_id
+                ),
+            ));
         }
-        let risk = normalized.risk_level.as_str();
-        if risk != "LOW" && risk != "MEDIUM" && risk != "HIGH" {
-            return Err(AppError::BadRequest(format!(
-                "invalid risk_level '{}' for '{}'",
-                normalized.risk_level, normalized.item_id
-            )));
+        if !is_risk_level(&normalized.risk_level) {
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidRiskLevel,
+                format!(
+                    "invalid risk_level '{}' for '{}'",
+                    normalized.risk_level, normalized.item_id
+                ),
+            ));
         }
         rules.push(KeyItemRule::from(normalized));
     }
@@ -40,6 +61,54 @@ pub async fn update_key_items(
         .into_iter()
         .map(|rule| (rule.item_id.clone(), rule))
         .collect();
-    *state.key_rules.write().await = map;
+    let changes = {
+        let mut key_rules = state.key_rules.write().await;
+        let before = std::mem::replace(&mut *key_rules, map);
+        describe_rule_changes(&before, &key_rules)
+    };
+    if let Some(changes) = changes {
+        record_rule_change(state, actor, &format!("关键物品规则更新：{}", changes)).await;
+    }
     Ok(())
 }
+
+/// Merges the built-in preset `id` into the active rules and saves them; `None` when there is no
+/// such preset.
+pub async fn apply_rule_preset(
+    state: &AppState,
+    id: &str,
+    request: RulePresetApplyRequest,
+    actor: &str,
+) -> Result<Option<RulePresetApplyResult>, AppError> {
+    let Some(preset) = find_rule_preset(id) else {
+        return Ok(None);
+    };
+    let mut key_rules = state.key_rules.write().await;
+    let mut merged = key_rules.clone();
+    let result = merge_rule_preset(&mut merged, &preset, request.on_conflict);
+    if !result.added.is_empty() || !result.replaced.is_empty() {
+        let mut rules: Vec<KeyItemRule> = merged.values().cloned().collect();
+        rules.sort_by(|a, b| a.item_id.cmp(&b.item_id));
+        state
+            .config_repo
+            .save_key_items(&state.config.key_items_path, &rules)
+            .await?;
+        if let Some(changes) = describe_rule_changes(&key_rules, &merged) {
+            record_rule_change(
+                state,
+                actor,
+                &format!("应用规则预设 {}：{}", preset.id, changes),
+            )
+            .await;
+        }
+        *key_rules = merged;
+    }
+    info!(
+        "applied rule preset {}: {} added, {} replaced, {} kept",
+        preset.id,
+        result.added.len(),
+        result.replaced.len(),
+        result.kept.len()
+    );
+    Ok(Some(result))
+}
@@ -0,0 +1,338 @@
+use chrono::{Local, NaiveDate};
+use tracing::{error, info, warn};
+
+use crate::commands::config_change_commands::notify_config_change;
+use crate::{AppError, AppState, ErrorCode};
+use backend_domain::{
+    current_millis, DataDropQuery, DataDropResult, MaintenanceRun, PartitionStat, StorageUsage,
+};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+/// Tables `DELETE /v2/ops/data` may drop a day of.
+pub const DROPPABLE_TABLES: [&str; 2] = ["item_events", "anomalies"];
+
+/// Compacts finished daily partitions that took a heavy write load (typically storage scans)
+/// and checks storage usage; the result is kept for `/v2/ops/maintenance`.
+pub async fn run_maintenance(state: &AppState) -> MaintenanceRun {
+    let started_at_ms = current_millis();
+    let mut errors = Vec::new();
+    let mut optimized = Vec::new();
+
+    match state.maintenance_repo.fetch_partition_stats().await {
+        Ok(stats) => {
+            let today_id = Local::now().format("%Y%m%d").to_string();
+            let candidates = select_optimize_candidates(
+                stats,
+                &today_id,
+                state.config.maintenance_optimize_min_rows,
+            );
+            for partition in candidates {
+                match state
+                    .maintenance_repo
+                    .optimize_partition(&partition.table, &partition.partition_id)
+                    .await
+                {
+                    Ok(()) => {
+                        info!(
+                            "optimized {} partition {} ({} parts, {} rows)",
+                            partition.table,
+                            partition.partition_id,
+                            partition.parts,
+                            partition.rows
+                        );
+                        optimized.push(partition);
+                    }
+                    Err(err) => errors.push(format!(
+                        "optimize {} {}: {}",
+                        partition.table, partition.partition_id, err
+                    )),
+                }
+            }
+        }
+        Err(err) => errors.push(format!("partition stats: {}", err)),
+    }
+
+    let storage = match state.maintenance_repo.fetch_storage_usage().await {
+        Ok(usage) => Some(usage),
+        Err(err) => {
+            errors.push(format!("storage usage: {}", err));
+            None
+        }
+    };
+    let storage_alert = match &storage {
+        Some(usage) => check_storage_threshold(state, usage).await,
+        None => false,
+    };
+
+    let status = if errors.is_empty() {
+        "success"
+    } else if !optimized.is_empty() || storage.is_some() {
+        "partial"
+    } else {
+        "failed"
+    };
+    for err in &errors {
+        error!("maintenance: {}", err);
+    }
+
+    let run = MaintenanceRun {
+        started_at_ms,
+        finished_at_ms: current_millis(),
+        status: status.to_string(),
+        optimized,
+        storage,
+        storage_alert,
+        errors,
+    };
+    *state.maintenance_status.write().await = Some(run.clone());
+    run
+}
+
+/// Drops one day of `item_events` or `anomalies` in two steps: without `confirm` it only
+/// describes the partition and issues a token, and the drop itself needs that token back.
+/// Returns `None` when the table has no partition for the day.
+pub async fn drop_data_partition(
+    state: &AppState,
+    query: &DataDropQuery,
+    actor: &str,
+) -> Result<Option<DataDropResult>, AppError> {
+    if !DROPPABLE_TABLES.contains(&query.table.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "table must be one of: {}",
+            DROPPABLE_TABLES.join(", ")
+        )));
+    }
+    let day = NaiveDate::parse_from_str(&query.date, "%Y-%m-%d").map_err(|_| {
+        AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid date: {}", query.date),
+        )
+    })?;
+    let partition_id = day.format("%Y%m%d").to_string();
+    let stats = state
+        .maintenance_repo
+        .fetch_partition_stats()
+        .await
+        .map_err(AppError::Unavailable)?;
+    let Some(stat) = stats
+        .into_iter()
+        .find(|stat| stat.table == query.table && stat.partition_id == partition_id)
+    else {
+        return Ok(None);
+    };
+    let mut result = DataDropResult {
+        table: stat.table,
+        date: query.date.clone(),
+        partition_id,
+        rows: stat.rows,
+        bytes_on_disk: stat.bytes_on_disk,
+        dropped: false,
+        confirm_token: None,
+        confirm_expires_at_ms: None,
+    };
+    let now = current_millis();
+    let Some(token) = &query.confirm else {
+        let (token, expires_at_ms) = state
+            .data_drops
+            .issue(&result.table, &result.partition_id, now)
+            .await;
+        result.confirm_token = Some(token);
+        result.confirm_expires_at_ms = Some(expires_at_ms);
+        return Ok(Some(result));
+    };
+    if !state
+        .data_drops
+        .confirm(token, &result.table, &result.partition_id, now)
+        .await
+    {
+        return Err(AppError::BadRequest(
+            "confirm token is unknown, expired or for another partition".to_string(),
+        ));
+    }
+    state
+        .maintenance_repo
+        .drop_partition(&result.table, &result.partition_id)
+        .await
+        .map_err(AppError::Unavailable)?;
+    warn!(
+        "data drop by {}: {} partition {} ({} rows, {} bytes)",
+        actor, result.table, result.partition_id, result.rows, result.bytes_on_disk
+    );
+    // The trend rollup still counts the dropped anomalies until the day is rolled up again.
+    if result.table == "anomalies" {
+        if let Err(err) = state.anomaly_repo.rollup_daily_summary(&result.date).await {
+            error!(
+                "failed to re-roll anomaly summary for {}: {}",
+                result.date, err
+            );
+        }
+    }
+    notify_config_change(
+        state,
+        actor,
+        &format!(
+            "删除数据 {} {}（{} 行）",
+            result.table, result.date, result.rows
+        ),
+    );
+    result.dropped = true;
+    Ok(Some(result))
+}
+
+fn select_optimize_candidates(
+    stats: Vec<PartitionStat>,
+    today_id: &str,
+    min_rows: u64,
+) -> Vec<PartitionStat> {
+    // Today's partition is still being written, and single-part partitions are already merged.
+    stats
+        .into_iter()
+        .filter(|item| item.partition_id != today_id && item.parts > 1 && item.rows >= min_rows)
+        .collect()
+}
+
+async fn check_storage_threshold(state: &AppState, usage: &StorageUsage) -> bool {
+    let threshold_mb = state.config.storage_alert_threshold_mb;
+    if threshold_mb == 0 || usage.database_bytes < threshold_mb.saturating_mul(BYTES_PER_MB) {
+        return false;
+    }
+    let message = format!(
+        "[Lattice 存储告警] 数据库占用 {} MB，超过阈值 {} MB（磁盘剩余 {} MB / 共 {} MB）",
+        usage.database_bytes / BYTES_PER_MB,
+        threshold_mb,
+        usage.disk_free_bytes / BYTES_PER_MB,
+        usage.disk_total_bytes / BYTES_PER_MB
+    );
+    warn!("{}", message);
+    if let Err(err) = state
+        .alert_service
+        .send_system_alert(&state.config, &message)
+        .await
+    {
+        error!("storage alert failed: {}", err);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+
+    fn stat(table: &str, partition_id: &str, parts: u64, rows: u64) -> PartitionStat {
+        PartitionStat {
+            table: table.to_string(),
+            partition_id: partition_id.to_string(),
+            parts,
+            rows,
+            bytes_on_disk: 0,
+        }
+    }
+
+    #[test]
+    fn optimize_candidates_skip_today_small_and_merged_partitions() {
+        let stats = vec![
+            stat("item_events", "20261015", 12, 5_000_000),
+            stat("item_events", "20261016", 30, 9_000_000),
+            stat("item_events", "20261014", 1, 5_000_000),
+            stat("anomalies", "20261015", 4, 100),
+        ];
+        let selected = select_optimize_candidates(stats, "20261016", 1_000_000);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].partition_id, "20261015");
+    }
+
+    #[tokio::test]
+    async fn data_drop_needs_the_token_issued_for_that_partition() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        app.maintenance.set_partition_stats(vec![
+            stat("item_events", "20261015", 3, 500),
+            stat("anomalies", "20261015", 1, 20),
+        ]);
+        let query = |table: &str, confirm: Option<&str>| DataDropQuery {
+            date: "2026-10-15".to_string(),
+            table: table.to_string(),
+            confirm: confirm.map(str::to_string),
+        };
+
+        let preview = drop_data_partition(&app.state, &query("item_events", None), "ops")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!preview.dropped);
+        assert_eq!(preview.rows, 500);
+        let token = preview.confirm_token.unwrap();
+        assert!(app.maintenance.dropped().is_empty());
+
+        let wrong_table = query("anomalies", Some(&token));
+        assert!(drop_data_partition(&app.state, &wrong_table, "ops")
+            .await
+            .is_err());
+        let dropped = drop_data_partition(&app.state, &query("item_events", Some(&token)), "ops")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(dropped.dropped);
+        assert_eq!(
+            app.maintenance.dropped(),
+            vec![("item_events".to_string(), "20261015".to_string())]
+        );
+        // The partition is gone, and the token was single-use anyway.
+        let again = query("item_events", Some(&token));
+        assert!(drop_data_partition(&app.state, &again, "ops")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(
+            drop_data_partition(&app.state, &query("custom_events", None), "ops")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_anomalies_rerolls_their_daily_summary() {
+        use backend_domain::testing::anomaly_row;
+        use backend_domain::{AnomalyRepository, AnomalyRow};
+
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        app.maintenance
+            .set_partition_stats(vec![stat("anomalies", "20261015", 1, 1)]);
+        app.anomalies
+            .insert_anomalies(&[AnomalyRow {
+                // 2026-10-15T08:00:00Z
+                event_time: backend_domain::millis_to_utc(1_792_051_200_000),
+                ..anomaly_row()
+            }])
+            .await
+            .unwrap();
+        app.anomalies
+            .rollup_daily_summary("2026-10-15")
+            .await
+            .unwrap();
+        let summary = || {
+            app.anomalies
+                .fetch_daily_summary("2026-10-15", "2026-10-15", None, None)
+        };
+        assert_eq!(summary().await.unwrap().len(), 1);
+
+        let query = |confirm: Option<&str>| DataDropQuery {
+            date: "2026-10-15".to_string(),
+            table: "anomalies".to_string(),
+            confirm: confirm.map(str::to_string),
+        };
+        let preview = drop_data_partition(&app.state, &query(None), "ops")
+            .await
+            .unwrap()
+            .unwrap();
+        let token = preview.confirm_token.unwrap();
+        // The in-memory maintenance repo only records the drop; the rows go here.
+        app.anomalies.drop_date("2026-10-15");
+        let dropped = drop_data_partition(&app.state, &query(Some(&token)), "ops")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(dropped.dropped);
+        assert!(summary().await.unwrap().is_empty());
+    }
+}
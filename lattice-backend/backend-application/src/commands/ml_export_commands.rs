@@ -0,0 +1,510 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+use chrono::Duration;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    anomaly_id, csv_field, current_millis, parse_date, AnomalyAckKey, AnomalyRow, Job,
+    MlExportRequest, MlExportResult, PlayerItemDailyTotal,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const ML_EXPORT_JOB: &str = "ml_export";
+const MAX_EXPORT_DAYS: i64 = 31;
+const MAX_NORMAL_RATIO: f64 = 10.0;
+const CSV_HEADER: &str = "date,label,rule_id,risk_level,player,server_id,item_id,hour_utc,count,\
+day_acquired,player_day_anomalies,acked";
+
+/// A validated `MlExportRequest`.
+#[derive(Debug, Clone)]
+struct ExportPlan {
+    dates: Vec<String>,
+    anomaly_rate: f64,
+    normal_ratio: f64,
+    max_per_rule: Option<usize>,
+    seed: Option<String>,
+}
+
+/// One line of the export; `rule` is None for an unflagged sample.
+struct Sample {
+    date: String,
+    rule: Option<(String, String)>,
+    player: String,
+    server_id: String,
+    item_id: String,
+    hour_utc: Option<u8>,
+    count: i64,
+    day_acquired: i64,
+    player_day_anomalies: usize,
+    acked: Option<bool>,
+}
+
+/// Queues an ML export job that writes `exports/ml-export-{id}.csv` under `report_dir`.
+pub async fn submit_ml_export(
+    state: &AppState,
+    request: MlExportRequest,
+    actor: &str,
+) -> Result<Job, AppError> {
+    let plan = build_plan(request)?;
+    let job = state
+        .jobs
+        .submit(ML_EXPORT_JOB, actor, current_millis())
+        .await;
+    let state = state.clone();
+    let id = job.id.clone();
+    tokio::spawn(async move {
+        let _slot = state.jobs.run_slot().await;
+        state.jobs.start(&id, current_millis()).await;
+        let outcome = run_ml_export(&state, &id, &plan)
+            .await
+            .and_then(|result| serde_json::to_value(result).map_err(|err| err.to_string()));
+        if let Err(err) = &outcome {
+            warn!("ml export job {} failed: {}", id, err);
+        }
+        state.jobs.finish(&id, current_millis(), outcome).await;
+    });
+    Ok(job)
+}
+
+async fn run_ml_export(
+    state: &AppState,
+    job_id: &str,
+    plan: &ExportPlan,
+) -> Result<MlExportResult, String> {
+    let seed = plan.seed.as_deref().unwrap_or(job_id);
+    let pseudonym_key = pseudonym_key(state.config.ml_export_secret.as_deref());
+    let mut days = Vec::new();
+    for date in &plan.dates {
+        let anomalies = state
+            .anomaly_repo
+            .fetch_anomalies(date, None)
+            .await
+            .map_err(|err| format!("failed to fetch anomalies of {}: {}", date, err))?;
+        let acked: HashSet<AnomalyAckKey> = state
+            .anomaly_repo
+            .fetch_acked_keys(date)
+            .await
+            .map_err(|err| format!("failed to fetch anomaly acks of {}: {}", date, err))?
+            .into_iter()
+            .collect();
+        days.push((date.clone(), anomalies, acked));
+    }
+    let candidates = days
+        .iter()
+        .flat_map(|(date, anomalies, _)| anomalies.iter().map(move |row| (date.as_str(), row)))
+        .collect();
+    let sampled = sample_anomalies(candidates, plan, seed);
+
+    let mut samples = Vec::new();
+    let mut result = MlExportResult::default();
+    for (date, anomalies, acked) in &days {
+        let picked: Vec<&AnomalyRow> = sampled
+            .iter()
+            .filter(|(day, _)| *day == date.as_str())
+            .map(|(_, row)| *row)
+            .collect();
+        if picked.is_empty() {
+            continue;
+        }
+        let players: Vec<String> = distinct(anomalies.iter().map(|row| &row.player_uuid));
+        let items: Vec<String> = distinct(anomalies.iter().map(|row| &row.item_id));
+        let totals = state
+            .event_repo
+            .fetch_daily_acquired_totals(date, &players, &items)
+            .await
+            .map_err(|err| format!("failed to fetch acquired totals of {}: {}", date, err))?;
+        let acquired: HashMap<(&str, &str), i64> = totals
+            .iter()
+            .map(|total| {
+                (
+                    (total.player_uuid.as_str(), total.item_id.as_str()),
+                    total.total,
+                )
+            })
+            .collect();
+        let mut per_player: HashMap<&str, usize> = HashMap::new();
+        for row in anomalies {
+            *per_player.entry(row.player_uuid.as_str()).or_default() += 1;
+        }
+
+        for row in &picked {
+            let key = AnomalyAckKey {
+                event_time: row.event_time,
+                player_uuid: row.player_uuid.clone(),
+                item_id: row.item_id.clone(),
+                rule_id: row.rule_id.clone(),
+            };
+            let is_acked = acked.contains(&key);
+            result.acked += usize::from(is_acked);
+            samples.push(Sample {
+                date: date.clone(),
+                rule: Some((row.rule_id.clone(), row.risk_level.clone())),
+                player: pseudonym(pseudonym_key, &row.player_uuid)?,
+                server_id: row.server_id.clone(),
+                item_id: row.item_id.clone(),
+                hour_utc: Some(row.event_time.hour()),
+                count: row.count,
+                day_acquired: acquired
+                    .get(&(row.player_uuid.as_str(), row.item_id.as_str()))
+                    .copied()
+                    .unwrap_or(0),
+                player_day_anomalies: per_player
+                    .get(row.player_uuid.as_str())
+                    .copied()
+                    .unwrap_or(0),
+                acked: Some(is_acked),
+            });
+        }
+        result.anomalies += picked.len();
+
+        let flagged: HashSet<(&str, &str)> = anomalies
+            .iter()
+            .map(|row| (row.player_uuid.as_str(), row.item_id.as_str()))
+            .collect();
+        let wanted = (picked.len() as f64 * plan.normal_ratio).round() as usize;
+        for total in sample_normal(&totals, &flagged, wanted, date, seed) {
+            samples.push(Sample {
+                date: date.clone(),
+                rule: None,
+                player: pseudonym(pseudonym_key, &total.player_uuid)?,
+                server_id: String::new(),
+                item_id: total.item_id.clone(),
+                hour_utc: None,
+                count: total.total,
+                day_acquired: total.total,
+                player_day_anomalies: per_player
+                    .get(total.player_uuid.as_str())
+                    .copied()
+                    .unwrap_or(0),
+                acked: None,
+            });
+            result.normal += 1;
+        }
+    }
+
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for sample in &samples {
+        csv.push_str(&csv_line(sample));
+        csv.push('\n');
+    }
+    result.rows = samples.len();
+    result.file = format!("exports/ml-export-{}.csv", job_id);
+    state
+        .config_repo
+        .write_report_file(&state.config.report_dir, &result.file, &csv)
+        .await
+        .map_err(|err| format!("failed to write {}: {}", result.file, err))?;
+    info!(
+        "ml export job {} wrote {} rows ({} anomalies, {} unflagged) to {}",
+        job_id, result.rows, result.anomalies, result.normal, result.file
+    );
+    Ok(result)
+}
+
+fn build_plan(request: MlExportRequest) -> Result<ExportPlan, AppError> {
+    let from = parse_date(request.from_date.trim())
+        .map_err(|_| AppError::BadRequest("from_date must be YYYY-MM-DD".to_string()))?;
+    let to = parse_date(request.to_date.trim())
+        .map_err(|_| AppError::BadRequest("to_date must be YYYY-MM-DD".to_string()))?;
+    let days = (to - from).num_days() + 1;
+    if !(1..=MAX_EXPORT_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "to_date must be on or after from_date and at most {} days later",
+            MAX_EXPORT_DAYS - 1
+        )));
+    }
+    let anomaly_rate = request.anomaly_rate.unwrap_or(1.0);
+    if !(anomaly_rate > 0.0 && anomaly_rate <= 1.0) {
+        return Err(AppError::BadRequest(
+            "anomaly_rate must be greater than 0 and at most 1".to_string(),
+        ));
+    }
+    let normal_ratio = request.normal_ratio.unwrap_or(1.0);
+    if !(0.0..=MAX_NORMAL_RATIO).contains(&normal_ratio) {
+        return Err(AppError::BadRequest(format!(
+            "normal_ratio must be between 0 and {}",
+            MAX_NORMAL_RATIO
+        )));
+    }
+    if request.max_per_rule == Some(0) {
+        return Err(AppError::BadRequest(
+            "max_per_rule must be at least 1".to_string(),
+        ));
+    }
+    let format = request.format.as_deref().map(str::trim).unwrap_or_default();
+    if !format.is_empty() && !format.eq_ignore_ascii_case("csv") {
+        return Err(AppError::BadRequest("format must be csv".to_string()));
+    }
+    Ok(ExportPlan {
+        dates: (0..days)
+            .map(|offset| (from + Duration::days(offset)).to_string())
+            .collect(),
+        anomaly_rate,
+        normal_ratio,
+        max_per_rule: request.max_per_rule,
+        seed: request
+            .seed
+            .map(|seed| seed.trim().to_string())
+            .filter(|seed| !seed.is_empty()),
+    })
+}
+
+/// Anomalies kept by `anomaly_rate`, then at most `max_per_rule` of each rule, lowest sample
+/// point first, in their original order.
+fn sample_anomalies<'a>(
+    candidates: Vec<(&'a str, &'a AnomalyRow)>,
+    plan: &ExportPlan,
+    seed: &str,
+) -> Vec<(&'a str, &'a AnomalyRow)> {
+    let mut kept: Vec<(f64, usize, (&str, &AnomalyRow))> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            (
+                sample_point(seed, &anomaly_id(candidate.1)),
+                index,
+                candidate,
+            )
+        })
+        .filter(|(point, _, _)| *point < plan.anomaly_rate)
+        .collect();
+    if let Some(max_per_rule) = plan.max_per_rule {
+        kept.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut per_rule: BTreeMap<&str, usize> = BTreeMap::new();
+        kept.retain(|(_, _, (_, row))| {
+            let taken = per_rule.entry(row.rule_id.as_str()).or_default();
+            *taken += 1;
+            *taken <= max_per_rule
+        });
+        kept.sort_by_key(|(_, index, _)| *index);
+    }
+    kept.into_iter()
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+/// Up to `wanted` acquisitions of a (player, item) pair that was not flagged on `date`.
+fn sample_normal<'a>(
+    totals: &'a [PlayerItemDailyTotal],
+    flagged: &HashSet<(&str, &str)>,
+    wanted: usize,
+    date: &str,
+    seed: &str,
+) -> Vec<&'a PlayerItemDailyTotal> {
+    let mut candidates: Vec<(f64, &PlayerItemDailyTotal)> = totals
+        .iter()
+        .filter(|total| {
+            total.total > 0
+                && !flagged.contains(&(total.player_uuid.as_str(), total.item_id.as_str()))
+        })
+        .map(|total| {
+            let key = format!("{}|{}|{}", date, total.player_uuid, total.item_id);
+            (sample_point(seed, &key), total)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    candidates
+        .into_iter()
+        .take(wanted)
+        .map(|(_, total)| total)
+        .collect()
+}
+
+/// Deterministic point in `[0, 1)` for `key`.
+fn sample_point(seed: &str, key: &str) -> f64 {
+    let digest = Sha256::digest(format!("sample|{}|{}", seed, key).as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// `ml_export_secret`, else a random key held for the life of the process. Never part of a
+/// request or response, so pseudonyms cannot be recomputed from a UUID by API callers.
+fn pseudonym_key(configured: Option<&str>) -> &str {
+    static RANDOM_KEY: OnceLock<String> = OnceLock::new();
+    configured.unwrap_or_else(|| {
+        RANDOM_KEY.get_or_init(|| format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()))
+    })
+}
+
+/// Stands in for the player UUID; names are never exported.
+fn pseudonym(key: &str, player_uuid: &str) -> Result<String, String> {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes())
+        .map_err(|err| format!("hmac init failed: {}", err))?;
+    mac.update(player_uuid.as_bytes());
+    Ok(mac.finalize().into_bytes()[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn distinct<'a>(values: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut seen: Vec<String> = values.cloned().collect();
+    seen.sort();
+    seen.dedup();
+    seen
+}
+
+fn csv_line(sample: &Sample) -> String {
+    let (rule_id, risk_level) = match &sample.rule {
+        Some((rule_id, risk_level)) => (rule_id.as_str(), risk_level.as_str()),
+        None => ("", "NONE"),
+    };
+    [
+        sample.date.clone(),
+        u8::from(sample.rule.is_some()).to_string(),
+        csv_field(rule_id),
+        csv_field(risk_level),
+        sample.player.clone(),
+        csv_field(&sample.server_id),
+        csv_field(&sample.item_id),
+        sample
+            .hour_utc
+            .map(|hour| hour.to_string())
+            .unwrap_or_default(),
+        sample.count.to_string(),
+        sample.day_acquired.to_string(),
+        sample.player_day_anomalies.to_string(),
+        sample
+            .acked
+            .map(|acked| acked.to_string())
+            .unwrap_or_default(),
+    ]
+    .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::{anomaly_row, runtime_config, Scenario};
+    use backend_domain::{millis_to_utc, AnomalyRepository, ConfigRepository, EventRepository};
+
+    fn request(from_date: &str, to_date: &str) -> MlExportRequest {
+        MlExportRequest {
+            from_date: from_date.to_string(),
+            to_date: to_date.to_string(),
+            anomaly_rate: None,
+            normal_ratio: None,
+            max_per_rule: None,
+            seed: Some("seed".to_string()),
+            format: None,
+        }
+    }
+
+    fn anomaly(event_ms: i64, player: &str, item_id: &str, rule_id: &str) -> AnomalyRow {
+        AnomalyRow {
+            event_time: millis_to_utc(event_ms),
+            player_uuid: format!("uuid-{}", player),
+            player_name: player.to_string(),
+            item_id: item_id.to_string(),
+            rule_id: rule_id.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn ml_export_labels_anomalies_and_samples_unflagged_acquisitions() {
+        let app = InMemoryApp::new(runtime_config());
+        let events: Vec<_> = Scenario::new()
+            .player("steve")
+            .acquires_without_origin("minecraft:diamond", 5)
+            .acquires_without_origin("minecraft:gold_ingot", 3)
+            .player("alex")
+            .acquires_without_origin("minecraft:gold_ingot", 40)
+            .acquires_without_origin("minecraft:diamond", 2)
+            .events()
+            .cloned()
+            .collect();
+        let event_ms = events[0].event_time;
+        let date = millis_to_utc(event_ms).date().to_string();
+        app.events.insert_events(&events).await.unwrap();
+        let steve = anomaly(event_ms, "steve", "minecraft:diamond", "R4");
+        let alex = anomaly(event_ms, "alex", "minecraft:gold_ingot", "R4");
+        app.anomalies
+            .insert_anomalies(&[steve.clone(), alex])
+            .await
+            .unwrap();
+        let key = AnomalyAckKey {
+            event_time: steve.event_time,
+            player_uuid: steve.player_uuid.clone(),
+            item_id: steve.item_id.clone(),
+            rule_id: steve.rule_id.clone(),
+        };
+        app.anomalies
+            .ack_anomaly(&key, event_ms, "confirmed dupe", "ops")
+            .await
+            .unwrap();
+
+        let plan = build_plan(request(&date, &date)).unwrap();
+        let result = run_ml_export(&app.state, "job-1", &plan).await.unwrap();
+        assert_eq!((result.anomalies, result.normal, result.acked), (2, 2, 1));
+        let csv = app
+            .configs
+            .read_report_file("./reports", &result.file)
+            .await
+            .unwrap()
+            .unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines.len(), 5);
+        assert!(!csv.contains("steve") && !csv.contains("uuid-"));
+        let normal: Vec<&str> = lines
+            .iter()
+            .filter(|line| line.contains(",0,,NONE,"))
+            .copied()
+            .collect();
+        assert_eq!(normal.len(), 2);
+        assert!(normal
+            .iter()
+            .any(|line| line.contains(",minecraft:gold_ingot,,3,3,1,")));
+        assert_eq!(
+            lines.iter().filter(|line| line.ends_with(",true")).count(),
+            1
+        );
+
+        let mut capped = request(&date, &date);
+        capped.max_per_rule = Some(1);
+        capped.normal_ratio = Some(0.0);
+        let plan = build_plan(capped).unwrap();
+        let result = run_ml_export(&app.state, "job-2", &plan).await.unwrap();
+        assert_eq!((result.rows, result.anomalies, result.normal), (1, 1, 0));
+    }
+
+    #[test]
+    fn pseudonyms_depend_on_the_server_secret_not_the_seed() {
+        let configured = pseudonym_key(Some("0123456789abcdef"));
+        let steve = pseudonym(configured, "uuid-steve").unwrap();
+        assert_eq!(steve.len(), 16);
+        assert_eq!(pseudonym(configured, "uuid-steve").unwrap(), steve);
+        assert_ne!(pseudonym(configured, "uuid-alex").unwrap(), steve);
+        // Knowing the seed and the UUID is not enough to recompute a pseudonym.
+        assert_ne!(pseudonym("seed", "uuid-steve").unwrap(), steve);
+        let random = pseudonym_key(None);
+        assert_eq!(random.len(), 64);
+        assert_eq!(pseudonym_key(None), random);
+        assert_ne!(pseudonym(random, "uuid-steve").unwrap(), steve);
+    }
+
+    #[test]
+    fn ml_export_requests_are_validated() {
+        assert!(build_plan(request("2026-10-01", "2026-10-31")).is_ok());
+        assert!(build_plan(request("2026-10-01", "2026-11-01")).is_err());
+        assert!(build_plan(request("2026-10-02", "2026-10-01")).is_err());
+        let mut parquet = request("2026-10-01", "2026-10-01");
+        parquet.format = Some("parquet".to_string());
+        assert!(build_plan(parquet).is_err());
+        let mut rate = request("2026-10-01", "2026-10-01");
+        rate.anomaly_rate = Some(0.0);
+        assert!(build_plan(rate).is_err());
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -87,8 +87,12 @@ pub fn build_issue_failure_message(err: &AppError) -> String {
         AppError::Unauthorized => {
             "申请失败：当前群未授权，请联系管理员配置 op_token_allowed_group_ids".to_string()
         }
-        AppError::BadRequest(message) => format!("申请失败：{}", message),
-        AppError::Internal(_) => "申请失败：后端内部错误".to_string(),
+        AppError::Forbidden(message)
+        | AppError::BadRequest(message)
+        | AppError::Invalid(_, message) => {
+            format!("申请失败：{}", message)
+        }
+        AppError::Unavailable(_) | AppError::Internal(_) => "申请失败：后端内部错误".to_string(),
     }
 }
 
@@ -240,29 +244,9 @@ mod tests {
     #[test]
     fn authorize_issue_requires_group_id() {
         let config = backend_domain::RuntimeConfig {
-            bind_addr: "127.0.0.1:3234".to_string(),
-            api_token: None,
             op_token_admin_ids: vec!["admin_1".to_string()],
             op_token_allowed_group_ids: vec!["group_a".to_string()],
-            report_dir: "./reports".to_string(),
-            public_base_url: "http://127.0.0.1:3234".to_string(),
-            webhook_url: None,
-            webhook_template: None,
-            alert_webhook_url: None,
-            alert_webhook_template: None,
-            alert_webhook_token: None,
-            alert_group_id: None,
-            key_items_path: "./key_items.yaml".to_string(),
-            item_registry_path: "./item_registry.json".to_string(),
-            transfer_window_seconds: 2,
-            key_item_window_minutes: 10,
-            strict_enabled: false,
-            strict_pickup_window_seconds: 30,
-            strict_pickup_threshold: 256,
-            max_body_bytes: 1024,
-            request_timeout_seconds: 15,
-            report_hour: 0,
-            report_minute: 5,
+            ..backend_domain::testing::runtime_config()
         };
 
         let result_missing = authorize_issue(&config, None);
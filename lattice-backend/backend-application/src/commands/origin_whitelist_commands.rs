@@ -0,0 +1,122 @@
+use tracing::{info, warn};
+
+use crate::commands::config_change_commands::notify_config_change;
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    current_millis, IngestEvent, OriginLearningRequest, OriginWhitelist, OriginWhitelistUpdate,
+};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const MAX_LEARNING_DAYS: u32 = 90;
+
+/// Appends and removes origin types, then saves `origin_whitelist.json`. Removing a learned
+/// origin type makes it raise R2 again; adding one promotes it to the list.
+pub async fn update_origin_whitelist(
+    state: &AppState,
+    update: OriginWhitelistUpdate,
+    actor: &str,
+) -> Result<OriginWhitelist, AppError> {
+    let add = normalize_origin_types(update.add)?;
+    let remove = normalize_origin_types(update.remove)?;
+    if add.is_empty() && remove.is_empty() {
+        return Err(AppError::BadRequest(
+            "add or remove must name at least one origin_type".to_string(),
+        ));
+    }
+    let mut whitelist = state.origin_whitelist.snapshot().await;
+    for origin_type in &add {
+        if !whitelist.origin_types.contains(origin_type) {
+            whitelist.origin_types.push(origin_type.clone());
+        }
+    }
+    whitelist
+        .learned
+        .retain(|learned| !add.contains(&learned.origin_type));
+    whitelist
+        .origin_types
+        .retain(|origin_type| !remove.contains(origin_type));
+    whitelist
+        .learned
+        .retain(|learned| !remove.contains(&learned.origin_type));
+    save_origin_whitelist(state, &whitelist).await?;
+
+    let mut summary = Vec::new();
+    if !add.is_empty() {
+        summary.push(format!("加入 {}", add.join(", ")));
+    }
+    if !remove.is_empty() {
+        summary.push(format!("移除 {}", remove.join(", ")));
+    }
+    notify_config_change(
+        state,
+        actor,
+        &format!("来源类型白名单更新：{}", summary.join("；")),
+    );
+    Ok(whitelist)
+}
+
+/// Starts a learning period of `days` (default `origin_learning_days`), or ends it with 0.
+pub async fn set_origin_learning(
+    state: &AppState,
+    request: OriginLearningRequest,
+    actor: &str,
+) -> Result<OriginWhitelist, AppError> {
+    let days = request.days.unwrap_or(state.config.origin_learning_days);
+    if days > MAX_LEARNING_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 0 and {}",
+            MAX_LEARNING_DAYS
+        )));
+    }
+    let mut whitelist = state.origin_whitelist.snapshot().await;
+    whitelist.learning_until_ms = (days > 0).then(|| current_millis() + i64::from(days) * DAY_MS);
+    save_origin_whitelist(state, &whitelist).await?;
+    let summary = if days > 0 {
+        format!("来源类型学习模式开启 {} 天", days)
+    } else {
+        "来源类型学习模式关闭".to_string()
+    };
+    notify_config_change(state, actor, &summary);
+    Ok(whitelist)
+}
+
+/// Records the unknown origin types of an analyzed batch while learning is on.
+pub async fn learn_origin_types(state: &AppState, events: &[IngestEvent]) {
+    let Some(whitelist) = state.origin_whitelist.learn(events, current_millis()).await else {
+        return;
+    };
+    info!(
+        "origin whitelist learning: {} learned origin types",
+        whitelist.learned.len()
+    );
+    if let Err(err) = state.config_repo.save_origin_whitelist(&whitelist).await {
+        warn!("failed to save origin whitelist: {}", err);
+    }
+}
+
+async fn save_origin_whitelist(
+    state: &AppState,
+    whitelist: &OriginWhitelist,
+) -> Result<(), AppError> {
+    state.config_repo.save_origin_whitelist(whitelist).await?;
+    state.origin_whitelist.replace(whitelist.clone()).await;
+    Ok(())
+}
+
+fn normalize_origin_types(values: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized = Vec::with_capacity(values.len());
+    for value in values {
+        let origin_type = value.trim().to_lowercase();
+        if origin_type.is_empty() || origin_type.contains(char::is_whitespace) {
+            return Err(AppError::BadRequest(format!(
+                "invalid origin_type '{}'",
+                value
+            )));
+        }
+        if !normalized.contains(&origin_type) {
+            normalized.push(origin_type);
+        }
+    }
+    Ok(normalized)
+}
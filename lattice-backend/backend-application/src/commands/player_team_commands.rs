@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use tracing::info;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{AnomalyRow, PlayerTeam};
+
+/// Replaces the player-to-team assignments and saves them to `player_teams.json`.
+pub async fn update_player_teams(state: &AppState, teams: Vec<PlayerTeam>) -> Result<(), AppError> {
+    let text = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let mut normalized = Vec::with_capacity(teams.len());
+    for (index, team) in teams.into_iter().enumerate() {
+        let player_uuid = text(team.player_uuid);
+        let player_name = text(team.player_name);
+        if player_uuid.is_none() && player_name.is_none() {
+            return Err(AppError::BadRequest(format!(
+                "entry {}: player_uuid or player_name is required",
+                index + 1
+            )));
+        }
+        let team = team.team.trim().to_string();
+        if team.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "entry {}: team must not be empty",
+                index + 1
+            )));
+        }
+        normalized.push(PlayerTeam {
+            player_uuid,
+            player_name,
+            team,
+        });
+    }
+    state.config_repo.save_player_teams(&normalized).await?;
+    info!("player teams updated: {} assignments", normalized.len());
+    state.player_teams.replace(normalized).await;
+    Ok(())
+}
+
+/// Sends alerts about players of a team with an `alert_team_routes` entry to that team's target
+/// and the rest to the default one.
+pub async fn spawn_routed_alerts(state: &AppState, alerts: Vec<AnomalyRow>) {
+    if state.config.alert_team_routes.is_empty() {
+        state
+            .alert_service
+            .spawn_alerts(state.config.clone(), alerts);
+        return;
+    }
+    let mut default_alerts = Vec::new();
+    let mut routed: HashMap<usize, Vec<AnomalyRow>> = HashMap::new();
+    for row in alerts {
+        let route = match state.player_teams.team_of(&row).await {
+            Some(team) => state
+                .config
+                .alert_team_routes
+                .iter()
+                .position(|route| route.team.eq_ignore_ascii_case(&team)),
+            None => None,
+        };
+        match route {
+            Some(index) => routed.entry(index).or_default().push(row),
+            None => default_alerts.push(row),
+        }
+    }
+    for (index, rows) in routed {
+        let route = &state.config.alert_team_routes[index];
+        let mut config = state.config.clone();
+        if let Some(group_id) = route.group_id {
+            config.alert_group_id = Some(group_id);
+            config.alert_server_groups.clear();
+        }
+        if let Some(url) = &route.webhook_url {
+            config.alert_webhook_url = Some(url.clone());
+        }
+        if let Some(proxy) = &route.proxy {
+            config.alert_webhook_proxy = Some(proxy.clone());
+        }
+        state.alert_service.spawn_alerts(config, rows);
+    }
+    if !default_alerts.is_empty() {
+        state
+            .alert_service
+            .spawn_alerts(state.config.clone(), default_alerts);
+    }
+}
@@ -0,0 +1,125 @@
+use tracing::{info, warn};
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, PlayerQuarantine, QuarantineRequest};
+
+const MAX_REASON_CHARS: usize = 500;
+
+/// Quarantines a player, replacing an earlier entry of the same player.
+pub async fn quarantine_player(
+    state: &AppState,
+    request: QuarantineRequest,
+    actor: &str,
+) -> Result<PlayerQuarantine, AppError> {
+    let entry = build_quarantine(request, actor, current_millis())?;
+    state.quarantines.quarantine(entry.clone()).await;
+    persist_quarantines(state).await;
+    info!(
+        "player quarantined: uuid={:?} name={:?} by {} ({:?})",
+        entry.player_uuid, entry.player_name, entry.quarantined_by, entry.reason
+    );
+    Ok(entry)
+}
+
+/// Lifts the quarantine whose UUID or name equals `player`; false when there is none.
+pub async fn release_player(state: &AppState, player: &str, actor: &str) -> bool {
+    let Some(entry) = state.quarantines.release(player.trim()).await else {
+        return false;
+    };
+    persist_quarantines(state).await;
+    info!(
+        "player quarantine lifted: uuid={:?} name={:?} by {}",
+        entry.player_uuid, entry.player_name, actor
+    );
+    true
+}
+
+pub async fn persist_quarantines(state: &AppState) {
+    let quarantines = state.quarantines.snapshot().await;
+    if let Err(err) = state.config_repo.save_quarantines(&quarantines).await {
+        warn!("failed to save quarantines: {}", err);
+    }
+}
+
+fn build_quarantine(
+    request: QuarantineRequest,
+    actor: &str,
+    now_ms: i64,
+) -> Result<PlayerQuarantine, AppError> {
+    let text = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let player_uuid = text(request.player_uuid);
+    let player_name = text(request.player_name);
+    if player_uuid.is_none() && player_name.is_none() {
+        return Err(AppError::BadRequest(
+            "player_uuid or player_name is required".to_string(),
+        ));
+    }
+    let reason = text(request.reason);
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_CHARS)
+    {
+        return Err(AppError::BadRequest(format!(
+            "reason must be at most {} characters",
+            MAX_REASON_CHARS
+        )));
+    }
+    Ok(PlayerQuarantine {
+        player_uuid,
+        player_name,
+        reason,
+        quarantined_by: actor.to_string(),
+        quarantined_at_ms: now_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ingest_commands::process_ingest_events;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::{runtime_config, Scenario};
+    use backend_domain::{is_quarantined, AnomalyRepository, ConfigRepository};
+
+    #[tokio::test]
+    async fn quarantined_players_are_stored_and_tagged_but_not_alerted() {
+        let app = InMemoryApp::new(runtime_config());
+        let request = QuarantineRequest {
+            player_uuid: None,
+            player_name: Some(" Steve ".to_string()),
+            reason: Some("investigating dupes".to_string()),
+        };
+        quarantine_player(&app.state, request, "ops").await.unwrap();
+        assert_eq!(app.configs.load_quarantines().await.unwrap().len(), 1);
+
+        let events: Vec<_> = Scenario::new()
+            .acquires_without_origin("minecraft:diamond", 5)
+            .player("alex")
+            .acquires_without_origin("minecraft:diamond", 5)
+            .events()
+            .cloned()
+            .collect();
+        let date = backend_domain::millis_to_utc(events[0].event_time)
+            .date()
+            .to_string();
+        process_ingest_events(&app.state, events).await.unwrap();
+
+        let stored = app.anomalies.fetch_anomalies(&date, None).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        for row in &stored {
+            assert_eq!(is_quarantined(row), row.player_name == "steve");
+        }
+        let alerted = app.alerts.alerts();
+        assert_eq!(alerted.len(), 1);
+        assert_eq!(alerted[0].player_name, "alex");
+
+        assert!(release_player(&app.state, "STEVE", "ops").await);
+        assert!(!release_player(&app.state, "steve", "ops").await);
+        assert!(app.configs.load_quarantines().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use tracing::info;
+
+use crate::commands::cluster_commands;
+use crate::commands::ingest_commands::{enrich_events, is_valid_event};
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{Analyzer, CustomDetectorRegistry, IngestEvent, RecordedBatch, ReplayReport};
+
+/// Splits an ingest recording into its batches, in order, and counts the lines that are not one.
+pub fn parse_recording(text: &str) -> (Vec<RecordedBatch>, usize) {
+    let mut batches = Vec::new();
+    let mut skipped = 0;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match serde_json::from_str::<RecordedBatch>(line) {
+            Ok(batch) => batches.push(batch),
+            Err(_) => skipped += 1,
+        }
+    }
+    (batches, skipped)
+}
+
+/// Replays a recording through a fresh analyzer and fresh custom detectors with the current
+/// rules and config, each batch analyzed at the time it was recorded. Nothing is stored or
+/// alerted and the live analyzer windows are untouched.
+pub async fn replay(state: &AppState, text: &str) -> Result<ReplayReport, AppError> {
+    let (batches, skipped_lines) = parse_recording(text);
+    if batches.is_empty() {
+        return Err(AppError::BadRequest(
+            "recording contains no ingest batches".to_string(),
+        ));
+    }
+    let mut analyzer = Analyzer::default();
+    let mut detectors = CustomDetectorRegistry::with_builtins(&state.config);
+    let mut report = ReplayReport {
+        batches: batches.len(),
+        events: 0,
+        skipped_lines,
+        anomalies: Vec::new(),
+    };
+    for batch in batches {
+        let mut events: Vec<IngestEvent> =
+            batch.events.into_iter().filter(is_valid_event).collect();
+        report.events += events.len();
+        enrich_events(state, &mut events).await;
+        let (custom_events, events): (Vec<IngestEvent>, Vec<IngestEvent>) =
+            events.into_iter().partition(IngestEvent::is_custom);
+        let request = cluster_commands::analyze_request(state, events, custom_events).await;
+        analyzer.set_replay_clock(batch.recorded_at_ms);
+        analyzer.set_origin_whitelist(request.origin_whitelist.clone());
+        analyzer.set_risk_overrides(request.risk_overrides.clone());
+        analyzer.set_detection_rules(request.detection_rules.clone());
+        analyzer.set_composite_rules(request.composite_rules.clone());
+        analyzer.set_strict_transfer_fingerprint(request.strict_transfer_fingerprint);
+        analyzer.set_transfer_matching(request.transfer_matching);
+        report.anomalies.extend(analyzer.analyze_batch(
+            &request.events,
+            &request.rules,
+            request.transfer_window_ms,
+            request.key_item_window_ms,
+            request.strict_pickup_window_ms,
+            request.strict_pickup_threshold,
+        ));
+        if !request.custom_events.is_empty() {
+            report
+                .anomalies
+                .extend(detectors.analyze(&request.custom_events));
+        }
+    }
+    info!(
+        "replayed {} batches ({} events): {} anomalies",
+        report.batches,
+        report.events,
+        report.anomalies.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_lines_that_are_not_batches_are_skipped() {
+        let text = concat!(
+            "{\"recorded_at_ms\":1000,\"events\":[]}\n",
+            "\n",
+            "not json\n",
+            "{\"recorded_at_ms\":2000,\"events\":[{\"event_id\":\"e1\",\"event_time\":1999,",
+            "\"server_id\":\"s1\",\"event_type\":\"ACQUIRE\",\"player_uuid\":\"p1\",",
+            "\"player_name\":\"alice\",\"item_id\":\"minecraft:diamond\",\"count\":2}]}\n",
+        );
+        let (batches, skipped) = parse_recording(text);
+        assert_eq!(skipped, 1);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].recorded_at_ms, 2000);
+        assert_eq!(batches[1].events[0].item_id, "minecraft:diamond");
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{Local, NaiveDate};
+use tracing::{info, warn};
+
+use crate::{AppError, ErrorCode};
+use crate::AppState;
+use backend_domain::ReportFile;
+
+/// (Re)generates the report for `date` (`YYYY-MM-DD`, not in the future) from stored anomalies
+/// and returns its file entry.
+pub async fn generate_report(state: &AppState, date: &str) -> Result<ReportFile, AppError> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+        AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid report date: {}", date),
+        )
+    })?;
+    if day > Local::now().date_naive() {
+        return Err(AppError::BadRequest(format!(
+            "report date {} is in the future",
+            date
+        )));
+    }
+    state.report_service.generate_report(date).await?;
+    info!("generated report {}", date);
+    let reports = state
+        .config_repo
+        .list_reports(&state.config.report_dir)
+        .await?;
+    reports
+        .into_iter()
+        .find(|report| report.date == date)
+        .ok_or_else(|| anyhow::anyhow!("report {} was not written", date).into())
+}
+
+/// Deletes the report for `date` (`YYYY-MM-DD`) and its player pages. Returns false when there
+/// was no such report.
+pub async fn delete_report(state: &AppState, date: &str) -> Result<bool, AppError> {
+    if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid report date: {}", date),
+        ));
+    }
+    let deleted = state
+        .config_repo
+        .delete_report(&state.config.report_dir, date)
+        .await?;
+    if deleted {
+        info!("deleted report {}", date);
+    }
+    Ok(deleted)
+}
+
+/// Keeps the newest `report_retention_count` reports and deletes the rest; 0 keeps everything.
+/// Returns the number of deleted reports.
+pub async fn prune_reports(state: &AppState) -> Result<usize, AppError> {
+    let keep = state.config.report_retention_count;
+    if keep == 0 {
+        return Ok(0);
+    }
+    let reports = state
+        .config_repo
+        .list_reports(&state.config.report_dir)
+        .await?;
+    let mut pruned = 0;
+    for report in reports.iter().skip(keep) {
+        match state
+            .config_repo
+            .delete_report(&state.config.report_dir, &report.date)
+            .await
+        {
+            Ok(true) => pruned += 1,
+            Ok(false) => {}
+            Err(err) => warn!("failed to prune report {}: {}", report.date, err),
+        }
+    }
+    if pruned > 0 {
+        info!("pruned {} reports beyond retention of {}", pruned, keep);
+    }
+    Ok(pruned)
+}
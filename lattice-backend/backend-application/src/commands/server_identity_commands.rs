@@ -0,0 +1,168 @@
+use tracing::{error, warn};
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{constant_time_eq, current_millis, IngestEvent, ServerKey};
+
+/// An ingest batch whose claimed `server_id` does not belong to the key it was sent with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityMismatch {
+    /// `server_id` bound to the presented key; `None` when no valid key was sent.
+    pub authenticated_server_id: Option<String>,
+    pub claimed_server_id: Option<String>,
+}
+
+/// Checks a batch against `server_keys`. With a valid key every event must claim the bound
+/// `server_id` (events without one get it); without one, events may not claim a bound
+/// `server_id`, nor anything at all when `required`. No keys configured accepts everything.
+pub fn check_server_identity(
+    server_keys: &[ServerKey],
+    required: bool,
+    presented_key: Option<&str>,
+    events: &mut [IngestEvent],
+) -> Result<(), IdentityMismatch> {
+    if server_keys.is_empty() {
+        return Ok(());
+    }
+    let claimed = |event: &IngestEvent| {
+        event
+            .server_id
+            .as_deref()
+            .map(|server_id| server_id.trim().to_lowercase())
+            .filter(|server_id| !server_id.is_empty())
+    };
+    let presented_key = presented_key.map(str::trim).filter(|key| !key.is_empty());
+    let Some(presented_key) = presented_key else {
+        let forged = events.iter().map(claimed).find(|server_id| {
+            required
+                || server_id.as_ref().is_some_and(|server_id| {
+                    server_keys
+                        .iter()
+                        .any(|bound| &bound.server_id == server_id)
+                })
+        });
+        return match forged {
+            Some(claimed_server_id) => Err(IdentityMismatch {
+                authenticated_server_id: None,
+                claimed_server_id,
+            }),
+            None => Ok(()),
+        };
+    };
+    let Some(bound) = server_keys
+        .iter()
+        .find(|bound| constant_time_eq(&bound.key, presented_key))
+    else {
+        return Err(IdentityMismatch {
+            authenticated_server_id: None,
+            claimed_server_id: events.iter().find_map(claimed),
+        });
+    };
+    for event in events.iter_mut() {
+        match claimed(event) {
+            None => event.server_id = Some(bound.server_id.clone()),
+            Some(server_id) if server_id == bound.server_id => {}
+            Some(server_id) => {
+                return Err(IdentityMismatch {
+                    authenticated_server_id: Some(bound.server_id.clone()),
+                    claimed_server_id: Some(server_id),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Logs and counts a rejected batch, alerts the first time `source` claims that `server_id`,
+/// and returns the error to answer with.
+pub async fn report_identity_mismatch(
+    state: &AppState,
+    source: &str,
+    mismatch: IdentityMismatch,
+) -> AppError {
+    let claimed = mismatch.claimed_server_id.as_deref().unwrap_or("-");
+    warn!(
+        "ingest server identity mismatch: source={} server_id={} key_server_id={}",
+        source,
+        claimed,
+        mismatch.authenticated_server_id.as_deref().unwrap_or("-")
+    );
+    let first = state
+        .ingest_tracker
+        .record_identity_mismatch(
+            source,
+            mismatch.claimed_server_id.as_deref(),
+            mismatch.authenticated_server_id.as_deref(),
+            current_millis(),
+        )
+        .await;
+    if first {
+        let identity = match &mismatch.authenticated_server_id {
+            Some(server_id) => format!("其服务器密钥属于 {}", server_id),
+            None => "未携带有效服务器密钥".to_string(),
+        };
+        let message = format!(
+            "上报来源伪造告警: 来源 {} 以 server={} 上报事件，{}，批次已拒绝",
+            source, claimed, identity
+        );
+        if let Err(err) = state
+            .alert_service
+            .send_system_alert(&state.config, &message)
+            .await
+        {
+            error!("failed to send server identity alert: {}", err);
+        }
+    }
+    AppError::Forbidden(match mismatch.authenticated_server_id {
+        Some(server_id) => format!(
+            "server key is bound to {}, batch claims {}",
+            server_id, claimed
+        ),
+        None => format!("server_id {} requires a valid server key", claimed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(server_id: Option<&str>) -> IngestEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_id": "evt-1",
+            "event_time": 1_000,
+            "event_type": "ACQUIRE",
+            "server_id": server_id,
+            "item_id": "minecraft:diamond",
+            "count": 1,
+        }))
+        .expect("event")
+    }
+
+    #[test]
+    fn claimed_server_id_must_match_the_presented_key() {
+        let keys = [ServerKey {
+            server_id: "survival-01".to_string(),
+            key: "k-survival".to_string(),
+        }];
+        let mut events = [event(Some("Survival-01")), event(None)];
+        assert!(check_server_identity(&keys, false, Some("k-survival"), &mut events).is_ok());
+        assert_eq!(events[1].server_id.as_deref(), Some("survival-01"));
+
+        let mut forged = [event(Some("creative-02"))];
+        assert_eq!(
+            check_server_identity(&keys, false, Some("k-survival"), &mut forged),
+            Err(IdentityMismatch {
+                authenticated_server_id: Some("survival-01".to_string()),
+                claimed_server_id: Some("creative-02".to_string()),
+            })
+        );
+        assert!(check_server_identity(&keys, false, Some("k-other"), &mut forged).is_err());
+
+        let mut unbound = [event(Some("creative-02"))];
+        assert!(check_server_identity(&keys, false, None, &mut unbound).is_ok());
+        assert!(check_server_identity(&keys, true, None, &mut unbound).is_err());
+        let mut impersonated = [event(Some("survival-01"))];
+        assert!(check_server_identity(&keys, false, None, &mut impersonated).is_err());
+        assert!(check_server_identity(&[], true, None, &mut impersonated).is_ok());
+    }
+}
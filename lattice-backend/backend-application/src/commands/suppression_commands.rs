@@ -0,0 +1,84 @@
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, AnomalySuppression, SuppressionRequest};
+
+/// Suppressions are meant to be temporary; anything longer should be a rule change.
+const MAX_SUPPRESSION_MINUTES: u64 = 30 * 24 * 60;
+const MAX_REASON_CHARS: usize = 500;
+
+pub async fn create_suppression(
+    state: &AppState,
+    request: SuppressionRequest,
+) -> Result<AnomalySuppression, AppError> {
+    let now = current_millis();
+    let suppression = build_suppression(request, now)?;
+    state.suppressions.add(suppression.clone()).await;
+    persist_suppressions(state).await;
+    info!(
+        "suppression {} added until {} (rule={:?}, player={:?}, server={:?}, item={:?})",
+        suppression.id,
+        suppression.expires_at_ms,
+        suppression.rule_id,
+        suppression.player,
+        suppression.server_id,
+        suppression.item_id
+    );
+    Ok(suppression)
+}
+
+pub async fn persist_suppressions(state: &AppState) {
+    let suppressions = state.suppressions.snapshot().await;
+    if let Err(err) = state.config_repo.save_suppressions(&suppressions).await {
+        warn!("failed to save suppressions: {}", err);
+    }
+}
+
+fn build_suppression(
+    request: SuppressionRequest,
+    now_ms: i64,
+) -> Result<AnomalySuppression, AppError> {
+    let clean = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let rule_id = clean(request.rule_id).map(|value| value.to_uppercase());
+    let player = clean(request.player);
+    let server_id = clean(request.server_id);
+    let item_id = clean(request.item_id).map(|value| value.to_lowercase());
+    if rule_id.is_none() && player.is_none() && server_id.is_none() && item_id.is_none() {
+        return Err(AppError::BadRequest(
+            "at least one of rule_id, player, server_id, item_id is required".to_string(),
+        ));
+    }
+    if request.duration_minutes == 0 || request.duration_minutes > MAX_SUPPRESSION_MINUTES {
+        return Err(AppError::BadRequest(format!(
+            "duration_minutes must be between 1 and {}",
+            MAX_SUPPRESSION_MINUTES
+        )));
+    }
+    let reason = clean(request.reason);
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_CHARS)
+    {
+        return Err(AppError::BadRequest(format!(
+            "reason must be at most {} characters",
+            MAX_REASON_CHARS
+        )));
+    }
+    Ok(AnomalySuppression {
+        id: Uuid::new_v4().simple().to_string(),
+        rule_id,
+        player,
+        server_id,
+        item_id,
+        reason,
+        created_at_ms: now_ms,
+        expires_at_ms: now_ms + (request.duration_minutes * 60_000) as i64,
+        expiry_notified: false,
+    })
+}
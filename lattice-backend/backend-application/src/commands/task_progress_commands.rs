@@ -1,11 +1,13 @@
+use crate::commands::ingest_commands;
 use crate::AppState;
 use crate::AppError;
 use backend_domain::TaskProgressUpdate;
 
 pub async fn update_task_progress(
     state: &AppState,
-    payload: TaskProgressUpdate,
+    mut payload: TaskProgressUpdate,
 ) -> Result<(), AppError> {
+    migrate_legacy_update(&mut payload);
     let mut status = state.task_status.write().await;
     let now = chrono::Utc::now().timestamp_millis();
     let update = backend_domain::TaskProgress {
@@ -16,18 +18,62 @@ pub async fn update_task_progress(
         failure: normalize_failure(payload.failure),
         trace_id: normalize_optional_text(payload.trace_id),
         throughput_per_sec: normalize_optional_number(payload.throughput_per_sec),
+        stale: false,
     };
     let key = payload.task.trim().to_lowercase();
     if key == "audit" {
         status.audit = update;
     } else if key == "scan" {
+        let was_running = status.scan.state == "RUNNING";
+        let next_state = update.state.clone();
         status.scan = update;
+        drop(status);
+        track_scan_run(state, was_running, &next_state, now).await;
     } else {
         return Err(AppError::BadRequest("task must be audit or scan".to_string()));
     }
     Ok(())
 }
 
+/// A scan run spans RUNNING to SUCCEEDED; findings it did not see again are forgotten.
+async fn track_scan_run(state: &AppState, was_running: bool, next_state: &str, now: i64) {
+    match next_state {
+        "RUNNING" if !was_running => state.storage_findings.begin_scan(now).await,
+        "SUCCEEDED" if was_running => {
+            if state.storage_findings.finish_scan().await > 0 {
+                ingest_commands::persist_storage_findings(state).await;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Maps the monolith's `{ running, total, done }` payload onto `state` and `counters`, so
+/// mods that still report progress the old way keep working.
+fn migrate_legacy_update(payload: &mut TaskProgressUpdate) {
+    if !payload.state.trim().is_empty() {
+        return;
+    }
+    let Some(running) = payload.running else {
+        return;
+    };
+    if let Some(total) = payload.total {
+        payload.counters.total = total;
+    }
+    if let Some(done) = payload.done {
+        payload.counters.done = done;
+    }
+    let counters = &payload.counters;
+    payload.state = if running {
+        "RUNNING"
+    } else if counters.total > 0 && counters.done >= counters.total {
+        "SUCCEEDED"
+    } else {
+        "IDLE"
+    }
+    .to_string();
+}
+
 fn normalize_optional_text(value: Option<String>) -> Option<String> {
     match value {
         Some(raw) => {
@@ -87,3 +133,34 @@ fn normalize_failure(value: Option<backend_domain::TaskFailure>) -> Option<backe
         message: message.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(running: bool, total: u64, done: u64) -> TaskProgressUpdate {
+        serde_json::from_value(serde_json::json!({
+            "task": "scan",
+            "running": running,
+            "total": total,
+            "done": done,
+        }))
+        .expect("legacy payload parses")
+    }
+
+    #[test]
+    fn legacy_payloads_map_onto_state_and_counters() {
+        let mut running = legacy(true, 10, 3);
+        migrate_legacy_update(&mut running);
+        assert_eq!(running.state, "RUNNING");
+        assert_eq!((running.counters.total, running.counters.done), (10, 3));
+
+        let mut finished = legacy(false, 10, 10);
+        migrate_legacy_update(&mut finished);
+        assert_eq!(finished.state, "SUCCEEDED");
+
+        let mut idle = legacy(false, 0, 0);
+        migrate_legacy_update(&mut idle);
+        assert_eq!(idle.state, "IDLE");
+    }
+}
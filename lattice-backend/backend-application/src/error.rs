@@ -1,11 +1,55 @@
+use serde::Serialize;
 use thiserror::Error;
 
+/// Machine-readable error codes sent as `code` in HTTP error bodies, so clients do not have to
+/// match on message text. The catalog is documented in docs/http-v2-contract.md.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Internal,
+    InvalidDate,
+    InvalidPage,
+    InvalidItemId,
+    InvalidRiskLevel,
+    RuleThresholdZero,
+    ClickhouseUnavailable,
+    RateLimited,
+    /// A mutating request without a fresh timestamp and unused nonce (`replay_window_seconds`).
+    ReplayRejected,
+}
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("unauthorized")]
     Unauthorized,
+    /// Authenticated, but not allowed to do this (e.g. a forged ingest `server_id`).
+    #[error("forbidden: {0}")]
+    Forbidden(String),
     #[error("bad request: {0}")]
     BadRequest(String),
+    /// A rejected request with a specific code; reported like `BadRequest`.
+    #[error("bad request: {1}")]
+    Invalid(ErrorCode, String),
+    /// ClickHouse failed and nothing could absorb or replace the result.
+    #[error("clickhouse unavailable: {0}")]
+    Unavailable(anyhow::Error),
     #[error(transparent)]
     Internal(#[from] anyhow::Error),
 }
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Unauthorized => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Invalid(code, _) => *code,
+            AppError::Unavailable(_) => ErrorCode::ClickhouseUnavailable,
+            AppError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+}
@@ -10,7 +10,9 @@ pub mod ops;
 pub mod queries;
 pub mod query;
 pub mod state;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 
-pub use error::AppError;
+pub use error::{AppError, ErrorCode};
 pub use metrics::Metrics;
 pub use state::AppState;
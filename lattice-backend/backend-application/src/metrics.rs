@@ -1,11 +1,105 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use backend_domain::{
+    current_millis, is_quarantined, AlertDeliveryTotals, AnomalyRow, IngestRate,
+    ALERT_DELIVERY_BUCKETS,
+};
+
+/// Upper bounds, in seconds, of the per-rule evaluation time buckets.
+const RULE_EVAL_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+/// Upper bounds, in seconds, of the ingest batch and ClickHouse insert duration buckets.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+/// Full minutes `ingest_rate` averages over.
+const INGEST_RATE_MINUTES: i64 = 5;
+/// Longest anomaly baseline `meta_alert_baseline_minutes` may ask for.
+pub const MAX_ANOMALY_BASELINE_MINUTES: i64 = 1440;
+/// Clock hours of ingest kept, enough to compare an hour with the same hour a day earlier.
+const INGEST_HOURS_KEPT: i64 = 25;
 
 #[derive(Debug, Default)]
 pub struct Metrics {
     ingest_requests: AtomicU64,
     ingest_events: AtomicU64,
     ingest_errors: AtomicU64,
-    anomalies: AtomicU64,
+    /// Events dropped on ingest because their `event_id` was already accepted.
+    ingest_duplicates: AtomicU64,
+    /// Ingest requests refused with `429`, by the limit that ran out.
+    ingest_throttled_source: AtomicU64,
+    ingest_throttled_token: AtomicU64,
+    /// Anomalies raised since start, by `rule_id`.
+    anomalies: Mutex<BTreeMap<String, u64>>,
+    rule_eval: Mutex<BTreeMap<&'static str, Histogram>>,
+    /// Time from receiving an ingest batch to having stored, analyzed and alerted on it.
+    ingest_latency: Mutex<Histogram>,
+    /// ClickHouse insert durations by table, failed inserts included.
+    clickhouse_inserts: Mutex<BTreeMap<&'static str, Histogram>>,
+    /// `(minute, requests, events)` of the current and the last `INGEST_RATE_MINUTES` minutes.
+    recent_ingest: Mutex<VecDeque<(i64, u64, u64)>>,
+    /// `(minute, anomalies)` of minutes that had any, back to `MAX_ANOMALY_BASELINE_MINUTES`.
+    recent_anomalies: Mutex<VecDeque<(i64, u64)>>,
+    /// `(hour, events)` of clock hours that had ingest, back to `INGEST_HOURS_KEPT`.
+    ingest_hours: Mutex<VecDeque<(i64, u64)>>,
+}
+
+/// Anomalies of the last full minute against the per-minute average before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalySpike {
+    pub last_minute: u64,
+    pub baseline_per_minute: f64,
+}
+
+/// Cumulative bucket counts against the bucket bounds passed in, which stay the same per metric.
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum_seconds: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, bounds: &[f64], seconds: f64) {
+        self.buckets.resize(bounds.len(), 0);
+        for (bucket, bound) in self.buckets.iter_mut().zip(bounds) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_seconds += seconds;
+    }
+
+    /// Writes the `_bucket`, `_sum` and `_count` series; `labels` is `key="value"` or empty.
+    fn render(&self, out: &mut String, name: &str, bounds: &[f64], labels: &str) {
+        let prefix = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{},", labels)
+        };
+        for (index, bound) in bounds.iter().enumerate() {
+            let count = self.buckets.get(index).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}le=\"{}\"}} {}",
+                name, prefix, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}le=\"+Inf\"}} {}",
+            name, prefix, self.count
+        );
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels)
+        };
+        let _ = writeln!(out, "{}_sum{} {}", name, labels, self.sum_seconds);
+        let _ = writeln!(out, "{}_count{} {}", name, labels, self.count);
+    }
 }
 
 impl Metrics {
@@ -13,32 +107,403 @@ impl Metrics {
         self.ingest_requests.fetch_add(1, Ordering::Relaxed);
         self.ingest_events
             .fetch_add(event_count as u64, Ordering::Relaxed);
+        self.record_recent_ingest(current_millis(), event_count as u64);
+    }
+
+    fn record_recent_ingest(&self, now_ms: i64, events: u64) {
+        let minute = now_ms.div_euclid(60_000);
+        let mut recent = self
+            .recent_ingest
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match recent.back_mut() {
+            Some(last) if last.0 == minute => {
+                last.1 += 1;
+                last.2 += events;
+            }
+            _ => recent.push_back((minute, 1, events)),
+        }
+        while recent
+            .front()
+            .is_some_and(|first| first.0 < minute - INGEST_RATE_MINUTES)
+        {
+            recent.pop_front();
+        }
+        drop(recent);
+
+        let hour = now_ms.div_euclid(3_600_000);
+        let mut hours = self
+            .ingest_hours
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match hours.back_mut() {
+            Some(last) if last.0 == hour => last.1 += events,
+            _ => hours.push_back((hour, events)),
+        }
+        while hours
+            .front()
+            .is_some_and(|first| first.0 < hour - INGEST_HOURS_KEPT)
+        {
+            hours.pop_front();
+        }
+    }
+
+    /// Events ingested during clock hour `hour` (hours since the epoch); `0` once it is no
+    /// longer kept.
+    pub fn ingest_events_in_hour(&self, hour: i64) -> u64 {
+        self.ingest_hours
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .find(|bucket| bucket.0 == hour)
+            .map(|bucket| bucket.1)
+            .unwrap_or(0)
+    }
+
+    /// Totals since start and the per-minute average of the last full minutes before `now_ms`.
+    pub fn ingest_rate(&self, now_ms: i64) -> IngestRate {
+        let minute = now_ms.div_euclid(60_000);
+        let (requests, events) = self
+            .recent_ingest
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .filter(|bucket| bucket.0 < minute && bucket.0 >= minute - INGEST_RATE_MINUTES)
+            .fold((0, 0), |(requests, events), bucket| {
+                (requests + bucket.1, events + bucket.2)
+            });
+        IngestRate {
+            window_minutes: INGEST_RATE_MINUTES as u64,
+            events_per_minute: events as f64 / INGEST_RATE_MINUTES as f64,
+            requests_per_minute: requests as f64 / INGEST_RATE_MINUTES as f64,
+            events_total: self.ingest_events.load(Ordering::Relaxed),
+            requests_total: self.ingest_requests.load(Ordering::Relaxed),
+            errors_total: self.ingest_errors.load(Ordering::Relaxed),
+        }
     }
 
     pub fn record_ingest_error(&self) {
         self.ingest_errors.fetch_add(1, Ordering::Relaxed);
     }
 
-    pub fn record_anomalies(&self, count: usize) {
-        self.anomalies.fetch_add(count as u64, Ordering::Relaxed);
+    pub fn record_ingest_duplicates(&self, count: usize) {
+        self.ingest_duplicates
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// `by_token` when the request's API token ran out, else its source address.
+    pub fn record_ingest_throttled(&self, by_token: bool) {
+        let counter = if by_token {
+            &self.ingest_throttled_token
+        } else {
+            &self.ingest_throttled_source
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_anomalies(&self, anomalies: &[AnomalyRow]) {
+        let mut by_rule = self.anomalies.lock().unwrap_or_else(|err| err.into_inner());
+        for row in anomalies {
+            *by_rule.entry(row.rule_id.clone()).or_default() += 1;
+        }
+        drop(by_rule);
+        // Quarantined players must not trip the anomaly spike meta-alert either.
+        let counted = anomalies.iter().filter(|row| !is_quarantined(row)).count();
+        self.record_recent_anomalies(current_millis(), counted as u64);
+    }
+
+    pub fn record_ingest_latency(&self, elapsed: Duration) {
+        self.ingest_latency
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .observe(&LATENCY_BUCKETS, elapsed.as_secs_f64());
     }
 
-    pub fn render_prometheus(&self) -> String {
+    pub fn record_clickhouse_insert(&self, table: &'static str, elapsed: Duration) {
+        self.clickhouse_inserts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(table)
+            .or_default()
+            .observe(&LATENCY_BUCKETS, elapsed.as_secs_f64());
+    }
+
+    fn record_recent_anomalies(&self, now_ms: i64, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let minute = now_ms.div_euclid(60_000);
+        let mut recent = self
+            .recent_anomalies
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match recent.back_mut() {
+            Some(last) if last.0 == minute => last.1 += count,
+            _ => recent.push_back((minute, count)),
+        }
+        while recent
+            .front()
+            .is_some_and(|first| first.0 < minute - MAX_ANOMALY_BASELINE_MINUTES - 1)
+        {
+            recent.pop_front();
+        }
+    }
+
+    /// The last full minute before `now_ms` when its anomalies exceed `multiple` times the
+    /// per-minute average of the `baseline_minutes` before it. A quiet baseline counts as one
+    /// anomaly per minute, so a handful of anomalies after a silent hour is not a spike.
+    pub fn anomaly_spike(
+        &self,
+        now_ms: i64,
+        baseline_minutes: u64,
+        multiple: f64,
+    ) -> Option<AnomalySpike> {
+        if multiple <= 0.0 || baseline_minutes == 0 {
+            return None;
+        }
+        let last = now_ms.div_euclid(60_000) - 1;
+        let baseline_minutes = (baseline_minutes as i64).min(MAX_ANOMALY_BASELINE_MINUTES);
+        let (last_minute, baseline) = self
+            .recent_anomalies
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .fold((0, 0), |(last_minute, baseline), bucket| {
+                if bucket.0 == last {
+                    (last_minute + bucket.1, baseline)
+                } else if bucket.0 < last && bucket.0 >= last - baseline_minutes {
+                    (last_minute, baseline + bucket.1)
+                } else {
+                    (last_minute, baseline)
+                }
+            });
+        let baseline_per_minute = baseline as f64 / baseline_minutes as f64;
+        (last_minute as f64 > multiple * baseline_per_minute.max(1.0)).then_some(AnomalySpike {
+            last_minute,
+            baseline_per_minute,
+        })
+    }
+
+    /// Records one batch's evaluation time of `rule`; rules no event reached are skipped.
+    pub fn record_rule_eval(&self, rule: &'static str, elapsed: Duration) {
+        if elapsed.is_zero() {
+            return;
+        }
+        let mut rule_eval = self.rule_eval.lock().unwrap_or_else(|err| err.into_inner());
+        rule_eval
+            .entry(rule)
+            .or_default()
+            .observe(&RULE_EVAL_BUCKETS, elapsed.as_secs_f64());
+    }
+
+    /// `alerts` come from the alert service, which counts its own deliveries.
+    pub fn render_prometheus(&self, alerts: AlertDeliveryTotals) -> String {
         let requests = self.ingest_requests.load(Ordering::Relaxed);
         let events = self.ingest_events.load(Ordering::Relaxed);
         let errors = self.ingest_errors.load(Ordering::Relaxed);
-        let anomalies = self.anomalies.load(Ordering::Relaxed);
+        let duplicates = self.ingest_duplicates.load(Ordering::Relaxed);
+        let throttled_source = self.ingest_throttled_source.load(Ordering::Relaxed);
+        let throttled_token = self.ingest_throttled_token.load(Ordering::Relaxed);
 
-        format!(
+        let mut out = format!(
             "# TYPE lattice_ingest_requests_total counter\n\
 lattice_ingest_requests_total {}\n\
 # TYPE lattice_ingest_events_total counter\n\
 lattice_ingest_events_total {}\n\
 # TYPE lattice_ingest_errors_total counter\n\
 lattice_ingest_errors_total {}\n\
-# TYPE lattice_anomalies_total counter\n\
-lattice_anomalies_total {}\n",
-            requests, events, errors, anomalies
-        )
+# TYPE lattice_ingest_duplicates_total counter\n\
+lattice_ingest_duplicates_total {}\n\
+# TYPE lattice_ingest_throttled_total counter\n\
+lattice_ingest_throttled_total{{limit=\"source\"}} {}\n\
+lattice_ingest_throttled_total{{limit=\"token\"}} {}\n\
+# TYPE lattice_anomalies_total counter\n",
+            requests, events, errors, duplicates, throttled_source, throttled_token
+        );
+        for (rule_id, count) in self
+            .anomalies
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+        {
+            let _ = writeln!(
+                out,
+                "lattice_anomalies_total{{rule_id=\"{}\"}} {}",
+                rule_id, count
+            );
+        }
+        render_alert_deliveries(&mut out, &alerts);
+        out.push_str("# TYPE lattice_ingest_batch_seconds histogram\n");
+        self.ingest_latency
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .render(
+                &mut out,
+                "lattice_ingest_batch_seconds",
+                &LATENCY_BUCKETS,
+                "",
+            );
+        let inserts = self
+            .clickhouse_inserts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if !inserts.is_empty() {
+            out.push_str("# TYPE lattice_clickhouse_insert_seconds histogram\n");
+        }
+        for (table, histogram) in inserts.iter() {
+            histogram.render(
+                &mut out,
+                "lattice_clickhouse_insert_seconds",
+                &LATENCY_BUCKETS,
+                &format!("table=\"{}\"", table),
+            );
+        }
+        drop(inserts);
+        let rule_eval = self.rule_eval.lock().unwrap_or_else(|err| err.into_inner());
+        if !rule_eval.is_empty() {
+            out.push_str("# TYPE lattice_rule_eval_seconds histogram\n");
+        }
+        for (rule, histogram) in rule_eval.iter() {
+            histogram.render(
+                &mut out,
+                "lattice_rule_eval_seconds",
+                &RULE_EVAL_BUCKETS,
+                &format!("rule=\"{}\"", rule),
+            );
+        }
+        out
+    }
+}
+
+/// Delivery outcomes, retries and delivery time of each alert mode that delivered anything.
+fn render_alert_deliveries(out: &mut String, alerts: &AlertDeliveryTotals) {
+    out.push_str("# TYPE lattice_alert_deliveries_total counter\n");
+    for (mode, totals) in &alerts.modes {
+        for (status, count) in [("success", totals.succeeded), ("failed", totals.failed)] {
+            let _ = writeln!(
+                out,
+                "lattice_alert_deliveries_total{{mode=\"{}\",status=\"{}\"}} {}",
+                mode, status, count
+            );
+        }
+    }
+    out.push_str("# TYPE lattice_alert_delivery_retries_total counter\n");
+    for (mode, totals) in &alerts.modes {
+        let _ = writeln!(
+            out,
+            "lattice_alert_delivery_retries_total{{mode=\"{}\"}} {}",
+            mode, totals.retries
+        );
+    }
+    out.push_str("# TYPE lattice_alert_delivery_seconds histogram\n");
+    for (mode, totals) in &alerts.modes {
+        let histogram = Histogram {
+            buckets: totals.seconds_buckets.clone(),
+            count: totals.succeeded + totals.failed,
+            sum_seconds: totals.seconds_sum,
+        };
+        histogram.render(
+            out,
+            "lattice_alert_delivery_seconds",
+            &ALERT_DELIVERY_BUCKETS,
+            &format!("mode=\"{}\"", mode),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::Scenario;
+
+    #[test]
+    fn ingest_rate_averages_only_full_recent_minutes() {
+        let metrics = Metrics::default();
+        metrics.record_recent_ingest(0, 100);
+        metrics.record_recent_ingest(60_000, 50);
+        metrics.record_recent_ingest(61_000, 50);
+        metrics.record_recent_ingest(6 * 60_000, 999);
+        let rate = metrics.ingest_rate(6 * 60_000 + 1);
+        assert_eq!(rate.events_per_minute, 20.0);
+        assert_eq!(rate.requests_per_minute, 0.4);
+        assert_eq!(metrics.recent_ingest.lock().unwrap().len(), 2);
+        assert_eq!(metrics.ingest_events_in_hour(0), 1199);
+        assert_eq!(metrics.ingest_events_in_hour(1), 0);
+    }
+
+    #[test]
+    fn anomaly_spike_compares_last_minute_with_baseline() {
+        let metrics = Metrics::default();
+        for minute in 0..10 {
+            metrics.record_recent_anomalies(minute * 60_000, 4);
+        }
+        metrics.record_recent_anomalies(10 * 60_000 + 5_000, 30);
+        let now_ms = 11 * 60_000 + 1;
+
+        let spike = metrics.anomaly_spike(now_ms, 10, 5.0).unwrap();
+        assert_eq!(spike.last_minute, 30);
+        assert_eq!(spike.baseline_per_minute, 4.0);
+        assert!(metrics.anomaly_spike(now_ms, 10, 8.0).is_none());
+        assert!(metrics.anomaly_spike(now_ms, 10, 0.0).is_none());
+        // The minute in progress is never judged.
+        assert!(metrics
+            .anomaly_spike(10 * 60_000 + 6_000, 10, 5.0)
+            .is_none());
+
+        let quiet = Metrics::default();
+        quiet.record_recent_anomalies(60_000, 3);
+        assert!(quiet.anomaly_spike(2 * 60_000, 60, 5.0).is_none());
+        quiet.record_recent_anomalies(60_000, 3);
+        assert!(quiet.anomaly_spike(2 * 60_000, 60, 5.0).is_some());
+    }
+
+    #[test]
+    fn prometheus_output_counts_rules_alerts_and_latencies() {
+        let metrics = Metrics::default();
+        let outcome = Scenario::new()
+            .rule("minecraft:beacon", 1)
+            .acquires_without_origin("minecraft:beacon", 2)
+            .run();
+        metrics.record_anomalies(&outcome.anomalies);
+        let again: Vec<AnomalyRow> = outcome.of_rule("R4").into_iter().cloned().collect();
+        metrics.record_anomalies(&again);
+        metrics.record_ingest_latency(Duration::from_millis(30));
+        metrics.record_clickhouse_insert("item_events", Duration::from_secs(3));
+        metrics.record_ingest_throttled(true);
+        let mut alerts = AlertDeliveryTotals::default();
+        for _ in 0..5 {
+            alerts.record("ws", true, 1, 0.2);
+        }
+        alerts.record("ws", false, 3, 4.0);
+        alerts.record("ws", false, 3, 40.0);
+        alerts.record("http", true, 2, 1.0);
+
+        let out = metrics.render_prometheus(alerts);
+        for line in [
+            "lattice_anomalies_total{rule_id=\"R1\"} 1",
+            "lattice_anomalies_total{rule_id=\"R4\"} 2",
+            "lattice_alert_deliveries_total{mode=\"ws\",status=\"success\"} 5",
+            "lattice_alert_deliveries_total{mode=\"ws\",status=\"failed\"} 2",
+            "lattice_alert_deliveries_total{mode=\"http\",status=\"failed\"} 0",
+            "lattice_alert_delivery_retries_total{mode=\"ws\"} 4",
+            "lattice_alert_delivery_retries_total{mode=\"http\"} 1",
+            "lattice_alert_delivery_seconds_bucket{mode=\"ws\",le=\"0.25\"} 5",
+            "lattice_alert_delivery_seconds_bucket{mode=\"ws\",le=\"5\"} 6",
+            "lattice_alert_delivery_seconds_bucket{mode=\"ws\",le=\"+Inf\"} 7",
+            "lattice_alert_delivery_seconds_count{mode=\"http\"} 1",
+            "lattice_ingest_throttled_total{limit=\"source\"} 0",
+            "lattice_ingest_throttled_total{limit=\"token\"} 1",
+            "lattice_ingest_batch_seconds_bucket{le=\"0.025\"} 0",
+            "lattice_ingest_batch_seconds_bucket{le=\"0.05\"} 1",
+            "lattice_ingest_batch_seconds_count 1",
+            "lattice_clickhouse_insert_seconds_bucket{table=\"item_events\",le=\"2.5\"} 0",
+            "lattice_clickhouse_insert_seconds_bucket{table=\"item_events\",le=\"+Inf\"} 1",
+            "lattice_clickhouse_insert_seconds_sum{table=\"item_events\"} 3",
+        ] {
+            assert!(
+                out.lines().any(|got| got == line),
+                "missing {line} in\n{out}"
+            );
+        }
     }
 }
@@ -1,3 +1,43 @@
+pub mod admin_secret;
+pub mod anomaly_stream_hub;
+pub mod api_token_store;
+pub mod ban_registry;
+pub mod clickhouse_watchdog;
+pub mod daily_quota_tracker;
+pub mod data_drop_confirmations;
+pub mod dead_letter_queue;
+pub mod degraded_mode;
+pub mod event_dedup;
+pub mod ingest_source_tracker;
+pub mod job_queue;
 pub mod mod_config_stream_hub;
+pub mod mod_version_gate;
+pub mod origin_whitelist_registry;
+pub mod player_team_registry;
+pub mod quarantine_registry;
+pub mod rule_revision_log;
+pub mod server_heartbeat_registry;
+pub mod storage_finding_tracker;
+pub mod suppression_registry;
 
+pub use admin_secret::*;
+pub use anomaly_stream_hub::*;
+pub use api_token_store::*;
+pub use ban_registry::*;
+pub use clickhouse_watchdog::*;
+pub use daily_quota_tracker::*;
+pub use data_drop_confirmations::*;
+pub use dead_letter_queue::*;
+pub use degraded_mode::*;
+pub use event_dedup::*;
+pub use ingest_source_tracker::*;
+pub use job_queue::*;
 pub use mod_config_stream_hub::*;
+pub use mod_version_gate::*;
+pub use origin_whitelist_registry::*;
+pub use player_team_registry::*;
+pub use quarantine_registry::*;
+pub use rule_revision_log::*;
+pub use server_heartbeat_registry::*;
+pub use storage_finding_tracker::*;
+pub use suppression_registry::*;
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use backend_domain::constant_time_eq;
+
+/// One-time secret an embedded backend hands to the process that started it. Config-mutating
+/// endpoints require it on top of the API token, so a leaked token alone cannot rewrite config.
+#[derive(Clone)]
+pub struct AdminSecret(Arc<str>);
+
+impl AdminSecret {
+    /// 256 random bits as 64 hex characters; a new one on every start.
+    pub fn generate() -> Self {
+        let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        Self(Arc::from(secret))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Compares without returning early at the first differing byte.
+    pub fn matches(&self, presented: &str) -> bool {
+        constant_time_eq(&self.0, presented.trim())
+    }
+}
+
+impl std::fmt::Debug for AdminSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AdminSecret(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_matches_only_itself() {
+        let secret = AdminSecret::generate();
+        assert_eq!(secret.as_str().len(), 64);
+        assert!(secret.matches(secret.as_str()));
+        assert!(!secret.matches(&secret.as_str()[1..]));
+        assert!(!AdminSecret::generate().matches(secret.as_str()));
+        assert!(!secret.matches(""));
+        assert_eq!(format!("{:?}", secret), "AdminSecret(***)");
+    }
+}
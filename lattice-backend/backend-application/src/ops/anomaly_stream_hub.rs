@@ -0,0 +1,32 @@
+use backend_domain::AnomalyRow;
+use tokio::sync::broadcast;
+
+/// Anomalies a slow subscriber may fall behind by before it skips ahead.
+const CHANNEL_BUFFER: usize = 256;
+
+/// Fans newly produced anomalies out to `/v2/detect/anomalies/stream` connections.
+pub struct AnomalyStreamHub {
+    sender: broadcast::Sender<AnomalyRow>,
+}
+
+impl Default for AnomalyStreamHub {
+    fn default() -> Self {
+        let (sender, _receiver) = broadcast::channel(CHANNEL_BUFFER);
+        Self { sender }
+    }
+}
+
+impl AnomalyStreamHub {
+    pub fn subscribe(&self) -> broadcast::Receiver<AnomalyRow> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, anomalies: &[AnomalyRow]) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        for row in anomalies {
+            let _ = self.sender.send(row.clone());
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::AppError;
+use backend_domain::{constant_time_eq, ApiToken, ApiTokenScope};
+
+const MAX_NAME_CHARS: usize = 64;
+
+/// Named, scoped API tokens issued through `/v2/ops/api-tokens`. The configured `api_token`
+/// keeps working beside them with full access; with neither, the API stays open.
+pub struct ApiTokenStore {
+    tokens: RwLock<Vec<ApiToken>>,
+}
+
+impl ApiTokenStore {
+    pub fn new(tokens: Vec<ApiToken>) -> Self {
+        Self {
+            tokens: RwLock::new(tokens),
+        }
+    }
+
+    /// `Unauthorized` for a missing, unknown, expired or revoked token, `Forbidden` for a valid
+    /// token without `required` in its scope. Revoked tokens still count as issued, so revoking
+    /// the last one does not open the API.
+    pub async fn check(
+        &self,
+        api_token: Option<&str>,
+        presented: Option<&str>,
+        required: ApiTokenScope,
+        now_ms: i64,
+    ) -> Result<(), AppError> {
+        let tokens = self.tokens.read().await;
+        if api_token.is_none() && tokens.is_empty() {
+            return Ok(());
+        }
+        let presented = presented.map(str::trim).ok_or(AppError::Unauthorized)?;
+        if api_token.is_some_and(|expected| constant_time_eq(expected, presented)) {
+            return Ok(());
+        }
+        let digest = token_digest(presented);
+        let token = tokens
+            .iter()
+            .find(|token| constant_time_eq(&token.token_sha256, &digest))
+            .filter(|token| token.is_active(now_ms))
+            .ok_or(AppError::Unauthorized)?;
+        if token.scope.allows(required) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "token '{}' has scope {}, {} required",
+                token.name,
+                token.scope.as_str(),
+                required.as_str()
+            )))
+        }
+    }
+
+    /// True while neither `api_token` nor any issued token exists, so `check` lets every request in.
+    pub async fn is_open(&self, api_token: Option<&str>) -> bool {
+        api_token.is_none() && self.tokens.read().await.is_empty()
+    }
+
+    /// True when `presented` is `api_token` or an active issued token, whatever its scope. Unlike
+    /// `check`, an open API authenticates nobody.
+    pub async fn authenticates(
+        &self,
+        api_token: Option<&str>,
+        presented: &str,
+        now_ms: i64,
+    ) -> bool {
+        let presented = presented.trim();
+        if api_token.is_some_and(|expected| constant_time_eq(expected, presented)) {
+            return true;
+        }
+        let digest = token_digest(presented);
+        self.tokens
+            .read()
+            .await
+            .iter()
+            .any(|token| constant_time_eq(&token.token_sha256, &digest) && token.is_active(now_ms))
+    }
+
+    /// Issues a token and returns it with its secret. A revoked or expired token's name may be
+    /// reused; an active one's may not.
+    pub async fn issue(
+        &self,
+        name: &str,
+        scope: ApiTokenScope,
+        expires_at_ms: Option<i64>,
+        now_ms: i64,
+    ) -> Result<(ApiToken, String), AppError> {
+        let name = name.trim();
+        if name.is_empty()
+            || name.chars().count() > MAX_NAME_CHARS
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(AppError::BadRequest(format!(
+                "name must be 1-{} characters of letters, digits, '-', '_' or '.'",
+                MAX_NAME_CHARS
+            )));
+        }
+        if expires_at_ms.is_some_and(|expires| expires <= now_ms) {
+            return Err(AppError::BadRequest(
+                "expires_at_ms must be in the future".to_string(),
+            ));
+        }
+        let mut tokens = self.tokens.write().await;
+        if tokens
+            .iter()
+            .any(|token| token.name == name && token.is_active(now_ms))
+        {
+            return Err(AppError::BadRequest(format!(
+                "an active token named '{}' already exists",
+                name
+            )));
+        }
+        tokens.retain(|token| token.name != name);
+        let secret = format!("lat_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let token = ApiToken {
+            name: name.to_string(),
+            scope,
+            token_sha256: token_digest(&secret),
+            created_at_ms: now_ms,
+            expires_at_ms,
+            revoked_at_ms: None,
+        };
+        tokens.push(token.clone());
+        Ok((token, secret))
+    }
+
+    /// Returns false when no active token has this name.
+    pub async fn revoke(&self, name: &str, now_ms: i64) -> bool {
+        let mut tokens = self.tokens.write().await;
+        match tokens
+            .iter_mut()
+            .find(|token| token.name == name.trim() && token.is_active(now_ms))
+        {
+            Some(token) => {
+                token.revoked_at_ms = Some(now_ms);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn snapshot(&self) -> Vec<ApiToken> {
+        let mut tokens = self.tokens.read().await.clone();
+        tokens.sort_by(|a, b| a.name.cmp(&b.name));
+        tokens
+    }
+}
+
+fn token_digest(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scoped_tokens_reach_only_their_endpoints() {
+        let store = ApiTokenStore::new(Vec::new());
+        assert!(store
+            .check(None, None, ApiTokenScope::Admin, 0)
+            .await
+            .is_ok());
+
+        let (_, ingest) = store
+            .issue("mod-survival", ApiTokenScope::Ingest, None, 0)
+            .await
+            .unwrap();
+        let (_, read) = store
+            .issue("dashboard", ApiTokenScope::Read, Some(1_000), 0)
+            .await
+            .unwrap();
+        assert!(ingest.starts_with("lat_") && ingest.len() == 68);
+        assert!(store
+            .issue("dashboard", ApiTokenScope::Admin, None, 0)
+            .await
+            .is_err());
+        assert!(store
+            .issue("bad name", ApiTokenScope::Read, None, 0)
+            .await
+            .is_err());
+
+        let check = |token: Option<&String>, scope, now| {
+            let token = token.map(String::as_str).map(str::to_string);
+            let store = &store;
+            async move { store.check(None, token.as_deref(), scope, now).await }
+        };
+        assert!(check(Some(&ingest), ApiTokenScope::Ingest, 0).await.is_ok());
+        assert!(matches!(
+            check(Some(&ingest), ApiTokenScope::Admin, 0).await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(check(Some(&read), ApiTokenScope::Read, 999).await.is_ok());
+        assert!(matches!(
+            check(Some(&read), ApiTokenScope::Read, 1_000).await,
+            Err(AppError::Unauthorized)
+        ));
+        assert!(matches!(
+            check(None, ApiTokenScope::Read, 0).await,
+            Err(AppError::Unauthorized)
+        ));
+
+        assert!(store.revoke("mod-survival", 10).await);
+        assert!(!store.revoke("mod-survival", 10).await);
+        assert!(matches!(
+            check(Some(&ingest), ApiTokenScope::Ingest, 20).await,
+            Err(AppError::Unauthorized)
+        ));
+        assert!(store
+            .check(Some("legacy"), Some("legacy"), ApiTokenScope::Admin, 20)
+            .await
+            .is_ok());
+        assert!(store
+            .snapshot()
+            .await
+            .iter()
+            .all(|token| !token.token_sha256.contains("lat_")));
+    }
+}
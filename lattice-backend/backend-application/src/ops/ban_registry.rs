@@ -0,0 +1,125 @@
+use backend_domain::{AnomalyRow, PlayerBan};
+use tokio::sync::RwLock;
+
+/// Bans reported by external systems, matched by player UUID or, failing that, by name.
+pub struct BanRegistry {
+    items: RwLock<Vec<PlayerBan>>,
+}
+
+impl BanRegistry {
+    pub fn new(items: Vec<PlayerBan>) -> Self {
+        Self {
+            items: RwLock::new(items),
+        }
+    }
+
+    /// Replaces any earlier ban of the same player and drops expired entries.
+    pub async fn ban(&self, ban: PlayerBan, now_ms: i64) {
+        let mut items = self.items.write().await;
+        items.retain(|item| is_active(item, now_ms) && !same_player(item, &ban));
+        items.push(ban);
+    }
+
+    /// Returns false when no active ban matched.
+    pub async fn unban(&self, player: &PlayerBan, now_ms: i64) -> bool {
+        let mut items = self.items.write().await;
+        let before = items.len();
+        items.retain(|item| !same_player(item, player));
+        let removed = items.len() != before;
+        items.retain(|item| is_active(item, now_ms));
+        removed
+    }
+
+    pub async fn active(&self, now_ms: i64) -> Vec<PlayerBan> {
+        let mut items: Vec<PlayerBan> = self
+            .items
+            .read()
+            .await
+            .iter()
+            .filter(|item| is_active(item, now_ms))
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.banned_at_ms));
+        items
+    }
+
+    pub async fn is_banned(&self, row: &AnomalyRow, now_ms: i64) -> bool {
+        self.items.read().await.iter().any(|item| {
+            is_active(item, now_ms)
+                && (item
+                    .player_uuid
+                    .as_deref()
+                    .is_some_and(|uuid| uuid.eq_ignore_ascii_case(&row.player_uuid))
+                    || item
+                        .player_name
+                        .as_deref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&row.player_name)))
+        })
+    }
+
+    pub async fn snapshot(&self) -> Vec<PlayerBan> {
+        self.items.read().await.clone()
+    }
+}
+
+fn is_active(item: &PlayerBan, now_ms: i64) -> bool {
+    item.expires_at_ms.is_none_or(|expires| expires > now_ms)
+}
+
+fn same_player(a: &PlayerBan, b: &PlayerBan) -> bool {
+    let field = |left: &Option<String>, right: &Option<String>| match (left, right) {
+        (Some(left), Some(right)) => left.eq_ignore_ascii_case(right),
+        _ => false,
+    };
+    field(&a.player_uuid, &b.player_uuid) || field(&a.player_name, &b.player_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::anomaly_row;
+
+    fn row(player_uuid: &str, player_name: &str) -> AnomalyRow {
+        AnomalyRow {
+            player_uuid: player_uuid.to_string(),
+            player_name: player_name.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    fn ban(
+        player_uuid: Option<&str>,
+        player_name: Option<&str>,
+        expires: Option<i64>,
+    ) -> PlayerBan {
+        PlayerBan {
+            player_uuid: player_uuid.map(ToString::to_string),
+            player_name: player_name.map(ToString::to_string),
+            reason: None,
+            source: None,
+            banned_at_ms: 0,
+            expires_at_ms: expires,
+        }
+    }
+
+    #[tokio::test]
+    async fn bans_match_by_uuid_or_name_until_expiry_or_unban() {
+        let registry = BanRegistry::new(Vec::new());
+        registry
+            .ban(ban(Some("uuid-1"), None, Some(1_000)), 0)
+            .await;
+        registry.ban(ban(None, Some("PlayerX"), None), 0).await;
+
+        assert!(registry.is_banned(&row("UUID-1", "Someone"), 500).await);
+        assert!(!registry.is_banned(&row("uuid-1", "Someone"), 1_000).await);
+        assert!(registry.is_banned(&row("uuid-2", "playerx"), 5_000).await);
+
+        assert!(
+            registry
+                .unban(&ban(None, Some("playerx"), None), 5_000)
+                .await
+        );
+        assert!(!registry.is_banned(&row("uuid-2", "PlayerX"), 5_000).await);
+        assert!(registry.active(5_000).await.is_empty());
+    }
+}
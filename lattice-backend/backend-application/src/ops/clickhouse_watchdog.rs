@@ -0,0 +1,94 @@
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Default)]
+struct WatchdogState {
+    consecutive_failures: u32,
+    unhealthy_since_ms: Option<i64>,
+    last_error: Option<String>,
+}
+
+/// What the ClickHouse watchdog's latest ping changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogTransition {
+    /// The failure threshold was just reached.
+    Unhealthy,
+    /// The first successful ping after being unhealthy.
+    Recovered,
+}
+
+/// Snapshot for `health_ready`.
+#[derive(Debug, Clone, Default)]
+pub struct WatchdogStatus {
+    pub consecutive_failures: u32,
+    pub unhealthy_since_ms: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Consecutive ClickHouse ping failures of the watchdog task; it turns unhealthy after
+/// `clickhouse_watchdog_failures` of them in a row and healthy again on the next success.
+#[derive(Default)]
+pub struct ClickhouseWatchdog {
+    state: RwLock<WatchdogState>,
+}
+
+impl ClickhouseWatchdog {
+    pub async fn record_failure(
+        &self,
+        now_ms: i64,
+        error: &str,
+        threshold: u32,
+    ) -> Option<WatchdogTransition> {
+        let mut state = self.state.write().await;
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.last_error = Some(error.to_string());
+        if state.unhealthy_since_ms.is_some() || state.consecutive_failures < threshold.max(1) {
+            return None;
+        }
+        state.unhealthy_since_ms = Some(now_ms);
+        Some(WatchdogTransition::Unhealthy)
+    }
+
+    pub async fn record_success(&self) -> Option<WatchdogTransition> {
+        let mut state = self.state.write().await;
+        let was_unhealthy = state.unhealthy_since_ms.is_some();
+        *state = WatchdogState::default();
+        was_unhealthy.then_some(WatchdogTransition::Recovered)
+    }
+
+    pub async fn status(&self) -> WatchdogStatus {
+        let state = self.state.read().await;
+        WatchdogStatus {
+            consecutive_failures: state.consecutive_failures,
+            unhealthy_since_ms: state.unhealthy_since_ms,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn watchdog_turns_unhealthy_at_threshold_and_recovers_once() {
+        let watchdog = ClickhouseWatchdog::default();
+        assert_eq!(watchdog.record_failure(1_000, "refused", 3).await, None);
+        assert_eq!(watchdog.record_failure(2_000, "refused", 3).await, None);
+        assert_eq!(
+            watchdog.record_failure(3_000, "timeout", 3).await,
+            Some(WatchdogTransition::Unhealthy)
+        );
+        assert_eq!(watchdog.record_failure(4_000, "timeout", 3).await, None);
+        let status = watchdog.status().await;
+        assert_eq!(status.consecutive_failures, 4);
+        assert_eq!(status.unhealthy_since_ms, Some(3_000));
+        assert_eq!(status.last_error.as_deref(), Some("timeout"));
+
+        assert_eq!(
+            watchdog.record_success().await,
+            Some(WatchdogTransition::Recovered)
+        );
+        assert_eq!(watchdog.record_success().await, None);
+        assert_eq!(watchdog.status().await.consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use tokio::sync::Mutex;
+
+#[derive(Default)]
+struct QuotaDay {
+    date: String,
+    flagged: HashSet<(String, String)>,
+}
+
+/// Remembers which (player uuid, item) pairs already broke their daily quota today, so R14 fires
+/// once per player and item per day; resets when the local date changes.
+#[derive(Default)]
+pub struct DailyQuotaTracker {
+    day: Mutex<QuotaDay>,
+}
+
+impl DailyQuotaTracker {
+    pub async fn unflagged(
+        &self,
+        date: &str,
+        pairs: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<(String, String)> {
+        let mut day = self.day.lock().await;
+        if day.date != date {
+            day.date = date.to_string();
+            day.flagged.clear();
+        }
+        pairs
+            .into_iter()
+            .filter(|pair| !day.flagged.contains(pair))
+            .collect()
+    }
+
+    /// Returns false when another batch flagged the pair first.
+    pub async fn flag(&self, date: &str, player_uuid: &str, item_id: &str) -> bool {
+        let mut day = self.day.lock().await;
+        if day.date != date {
+            day.date = date.to_string();
+            day.flagged.clear();
+        }
+        day.flagged
+            .insert((player_uuid.to_string(), item_id.to_string()))
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// How long a data drop confirmation token stays valid.
+pub const DATA_DROP_CONFIRM_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone)]
+struct PendingDrop {
+    table: String,
+    partition_id: String,
+    expires_at_ms: i64,
+}
+
+/// Tokens handed out by an unconfirmed `DELETE /v2/ops/data`; each confirms one drop of one
+/// partition, once, within `DATA_DROP_CONFIRM_MS`.
+#[derive(Debug, Default)]
+pub struct DataDropConfirmations {
+    pending: Mutex<HashMap<String, PendingDrop>>,
+}
+
+impl DataDropConfirmations {
+    /// A fresh token for dropping `partition_id` of `table` and when it expires.
+    pub async fn issue(&self, table: &str, partition_id: &str, now_ms: i64) -> (String, i64) {
+        let token = Uuid::new_v4().simple().to_string();
+        let expires_at_ms = now_ms + DATA_DROP_CONFIRM_MS;
+        let mut pending = self.pending.lock().await;
+        pending.retain(|_, drop| drop.expires_at_ms > now_ms);
+        pending.insert(
+            token.clone(),
+            PendingDrop {
+                table: table.to_string(),
+                partition_id: partition_id.to_string(),
+                expires_at_ms,
+            },
+        );
+        (token, expires_at_ms)
+    }
+
+    /// Consumes `token` when it was issued for this partition and has not expired.
+    pub async fn confirm(&self, token: &str, table: &str, partition_id: &str, now_ms: i64) -> bool {
+        let mut pending = self.pending.lock().await;
+        match pending.get(token) {
+            Some(drop)
+                if drop.table == table
+                    && drop.partition_id == partition_id
+                    && drop.expires_at_ms > now_ms =>
+            {
+                pending.remove(token);
+                true
+            }
+            _ => false,
+        }
+    }
+}
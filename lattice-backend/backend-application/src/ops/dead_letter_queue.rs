@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+
+use backend_domain::DeadLetterBatch;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Writes that ClickHouse rejected, oldest first, capped at `max_events` rows in total.
+pub struct DeadLetterQueue {
+    max_events: usize,
+    batches: Mutex<VecDeque<DeadLetterBatch>>,
+    log: Mutex<()>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(batches: Vec<DeadLetterBatch>, max_events: usize) -> Self {
+        Self {
+            max_events,
+            batches: Mutex::new(batches.into()),
+            log: Mutex::new(()),
+        }
+    }
+
+    /// Held while the write-ahead log is appended to or rewritten, together with the matching
+    /// change to the queue, so a rewrite never misses a batch that was logged but not yet queued.
+    pub async fn lock_log(&self) -> MutexGuard<'_, ()> {
+        self.log.lock().await
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_events > 0
+    }
+
+    /// Queues `batch`, dropping the oldest batches when over capacity; returns how many rows were
+    /// dropped.
+    pub async fn push(&self, batch: DeadLetterBatch) -> usize {
+        if batch.is_empty() {
+            return 0;
+        }
+        let mut batches = self.batches.lock().await;
+        batches.push_back(batch);
+        let mut total: usize = batches.iter().map(DeadLetterBatch::len).sum();
+        let mut dropped = 0;
+        while total > self.max_events {
+            let Some(oldest) = batches.pop_front() else {
+                break;
+            };
+            total -= oldest.len();
+            dropped += oldest.len();
+        }
+        dropped
+    }
+
+    pub async fn take_all(&self) -> Vec<DeadLetterBatch> {
+        self.batches.lock().await.drain(..).collect()
+    }
+
+    /// Puts batches that could not be replayed back in front of anything queued meanwhile.
+    pub async fn requeue(&self, failed: Vec<DeadLetterBatch>) {
+        let mut batches = self.batches.lock().await;
+        for batch in failed.into_iter().rev() {
+            batches.push_front(batch);
+        }
+    }
+
+    pub async fn len_events(&self) -> usize {
+        self.batches
+            .lock()
+            .await
+            .iter()
+            .map(DeadLetterBatch::len)
+            .sum()
+    }
+
+    pub async fn snapshot(&self) -> Vec<DeadLetterBatch> {
+        self.batches.lock().await.iter().cloned().collect()
+    }
+}
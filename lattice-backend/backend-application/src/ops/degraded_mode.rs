@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use backend_domain::AnomalyRow;
+use chrono::Local;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Default)]
+struct DegradedState {
+    since_ms: Option<i64>,
+    last_failure_ms: i64,
+    last_error: Option<String>,
+}
+
+/// Snapshot of an ongoing ClickHouse outage.
+#[derive(Debug, Clone)]
+pub struct DegradedStatus {
+    pub since_ms: i64,
+    pub last_error: Option<String>,
+}
+
+/// Tracks ClickHouse availability with hysteresis: one failure enters degraded mode, and it is
+/// only left after `recovery_ms` without failures, so readiness does not flap on a shaky link.
+#[derive(Default)]
+pub struct DegradedMode {
+    state: RwLock<DegradedState>,
+}
+
+impl DegradedMode {
+    /// Returns true when this failure started a new degraded period.
+    pub async fn record_failure(&self, now_ms: i64, error: &str) -> bool {
+        let mut state = self.state.write().await;
+        state.last_failure_ms = now_ms;
+        state.last_error = Some(error.to_string());
+        if state.since_ms.is_some() {
+            return false;
+        }
+        state.since_ms = Some(now_ms);
+        true
+    }
+
+    /// Returns true when this success ended the degraded period.
+    pub async fn record_success(&self, now_ms: i64, recovery_ms: i64) -> bool {
+        let mut state = self.state.write().await;
+        if state.since_ms.is_none() || now_ms - state.last_failure_ms < recovery_ms {
+            return false;
+        }
+        *state = DegradedState::default();
+        true
+    }
+
+    pub async fn status(&self) -> Option<DegradedStatus> {
+        let state = self.state.read().await;
+        state.since_ms.map(|since_ms| DegradedStatus {
+            since_ms,
+            last_error: state.last_error.clone(),
+        })
+    }
+}
+
+/// Bounded ring buffer of the newest anomalies, served while ClickHouse is unreachable.
+pub struct RecentAnomalyBuffer {
+    capacity: usize,
+    rows: RwLock<VecDeque<AnomalyRow>>,
+}
+
+impl RecentAnomalyBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            rows: RwLock::new(VecDeque::with_capacity(capacity.min(4096))),
+        }
+    }
+
+    pub async fn push(&self, anomalies: &[AnomalyRow]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut rows = self.rows.write().await;
+        for row in anomalies {
+            if rows.len() == self.capacity {
+                rows.pop_front();
+            }
+            rows.push_back(row.clone());
+        }
+    }
+
+    /// Cached anomalies on local `date` (`YYYY-MM-DD`), newest first, optionally for one player.
+    pub async fn matching(&self, date: &str, player: Option<&str>) -> Vec<AnomalyRow> {
+        self.rows
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|row| player.is_none_or(|player| row.player_name == player))
+            .filter(|row| local_date(row) == date)
+            .cloned()
+            .collect()
+    }
+}
+
+fn local_date(row: &AnomalyRow) -> String {
+    let millis = (row.event_time.unix_timestamp_nanos() / 1_000_000) as i64;
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::millis_to_utc;
+    use backend_domain::testing::anomaly_row;
+
+    fn row(player_name: &str, event_ms: i64) -> AnomalyRow {
+        AnomalyRow {
+            event_time: millis_to_utc(event_ms),
+            player_name: player_name.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn degraded_mode_waits_for_recovery_window() {
+        let mode = DegradedMode::default();
+        assert!(mode.record_failure(1_000, "connection refused").await);
+        assert!(!mode.record_failure(2_000, "connection refused").await);
+        assert!(!mode.record_success(30_000, 60_000).await);
+        assert_eq!(mode.status().await.map(|status| status.since_ms), Some(1_000));
+        assert!(mode.record_success(62_000, 60_000).await);
+        assert!(mode.status().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn recent_buffer_drops_oldest_rows() {
+        let buffer = RecentAnomalyBuffer::new(2);
+        let now = backend_domain::current_millis();
+        buffer
+            .push(&[row("A", now), row("B", now + 1), row("C", now + 2)])
+            .await;
+        let date = local_date(&row("A", now));
+        let names: Vec<String> = buffer
+            .matching(&date, None)
+            .await
+            .into_iter()
+            .map(|row| row.player_name)
+            .collect();
+        assert_eq!(names, vec!["C".to_string(), "B".to_string()]);
+        assert_eq!(buffer.matching(&date, Some("B")).await.len(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+use backend_domain::IngestEvent;
+
+#[derive(Debug, Default)]
+struct SeenIds {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+/// The last `capacity` event ids accepted on ingest, so a batch the mod re-sends after a
+/// network retry is not stored and analyzed twice. Events without an id always pass.
+#[derive(Debug, Default)]
+pub struct EventDedup {
+    capacity: usize,
+    seen: Mutex<SeenIds>,
+}
+
+impl EventDedup {
+    /// A `capacity` of 0 turns dedup off.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: Mutex::new(SeenIds::default()),
+        }
+    }
+
+    /// Drops events whose id was already accepted, in an earlier batch or earlier in this one,
+    /// and remembers the rest. Returns how many were dropped.
+    pub fn retain_new(&self, events: &mut Vec<IngestEvent>) -> usize {
+        if self.capacity == 0 {
+            return 0;
+        }
+        let before = events.len();
+        let mut seen = self.seen.lock().unwrap_or_else(|err| err.into_inner());
+        events.retain(|event| {
+            if event.event_id.is_empty() {
+                return true;
+            }
+            if !seen.ids.insert(event.event_id.clone()) {
+                return false;
+            }
+            seen.order.push_back(event.event_id.clone());
+            if seen.order.len() > self.capacity {
+                if let Some(oldest) = seen.order.pop_front() {
+                    seen.ids.remove(&oldest);
+                }
+            }
+            true
+        });
+        before - events.len()
+    }
+
+    /// Forgets the ids of a batch that was not accepted after all, so the mod's retry goes
+    /// through.
+    pub fn forget(&self, events: &[IngestEvent]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(|err| err.into_inner());
+        for event in events {
+            seen.ids.remove(&event.event_id);
+        }
+        let SeenIds { ids, order } = &mut *seen;
+        order.retain(|id| ids.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::Scenario;
+
+    fn events(ids: &[&str]) -> Vec<IngestEvent> {
+        let template = Scenario::new()
+            .acquires_without_origin("minecraft:diamond", 1)
+            .events()
+            .next()
+            .cloned()
+            .unwrap();
+        ids.iter()
+            .map(|id| IngestEvent {
+                event_id: id.to_string(),
+                ..template.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resent_events_are_dropped_until_evicted_or_forgotten() {
+        let dedup = EventDedup::new(3);
+        let mut first = events(&["a", "b", "a", ""]);
+        assert_eq!(dedup.retain_new(&mut first), 1);
+        assert_eq!(first.len(), 3);
+
+        let mut retry = events(&["a", "b", "c", ""]);
+        assert_eq!(dedup.retain_new(&mut retry), 2);
+        assert_eq!(retry[0].event_id, "c");
+
+        // "d" evicts "a", the oldest id.
+        assert_eq!(dedup.retain_new(&mut events(&["d"])), 0);
+        assert_eq!(dedup.retain_new(&mut events(&["a"])), 0);
+
+        dedup.forget(&events(&["a"]));
+        assert_eq!(dedup.retain_new(&mut events(&["a", "d"])), 1);
+    }
+}
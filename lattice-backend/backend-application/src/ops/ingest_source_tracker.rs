@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use backend_domain::{IngestAuthFailure, IngestIdentityMismatch, StaleServerStatus};
+use tokio::sync::RwLock;
+
+const MAX_AUTH_FAILURE_SOURCES: usize = 256;
+
+#[derive(Debug, Clone)]
+struct ServerIngestState {
+    last_success_ms: i64,
+    stale_alerted: bool,
+    /// Where the server last ingested from; auth failures from there are counted against it.
+    last_source: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct StaleTransitions {
+    pub newly_stale: Vec<StaleServerStatus>,
+    pub recovered: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct IngestSourceTracker {
+    servers: RwLock<HashMap<String, ServerIngestState>>,
+    auth_failures: RwLock<HashMap<String, IngestAuthFailure>>,
+    identity_mismatches: RwLock<HashMap<String, IngestIdentityMismatch>>,
+}
+
+impl IngestSourceTracker {
+    pub async fn record_success<'a>(
+        &self,
+        server_ids: impl IntoIterator<Item = &'a str>,
+        now_ms: i64,
+    ) {
+        let mut servers = self.servers.write().await;
+        for server_id in server_ids {
+            let server_id = server_id.trim();
+            if server_id.is_empty() {
+                continue;
+            }
+            let entry = servers
+                .entry(server_id.to_string())
+                .or_insert(ServerIngestState {
+                    last_success_ms: now_ms,
+                    stale_alerted: false,
+                    last_source: None,
+                });
+            entry.last_success_ms = entry.last_success_ms.max(now_ms);
+        }
+    }
+
+    /// Remembers `source` as where `server_ids` ingest from; only an authenticated batch may set
+    /// it, so a failing request cannot pin its failures on a server of its choosing.
+    pub async fn record_source<'a>(
+        &self,
+        server_ids: impl IntoIterator<Item = &'a str>,
+        source: &str,
+    ) {
+        let mut servers = self.servers.write().await;
+        for server_id in server_ids {
+            if let Some(state) = servers.get_mut(server_id.trim()) {
+                state.last_source = Some(source.to_string());
+            }
+        }
+    }
+
+    /// Counts an unauthenticated ingest request by source alone; its body is never read.
+    pub async fn record_auth_failure(&self, source: &str, now_ms: i64) {
+        let key = source.to_string();
+        let mut failures = self.auth_failures.write().await;
+        if !failures.contains_key(&key) && failures.len() >= MAX_AUTH_FAILURE_SOURCES {
+            if let Some(oldest) = failures
+                .iter()
+                .min_by_key(|(_, item)| item.last_failure_ms)
+                .map(|(key, _)| key.clone())
+            {
+                failures.remove(&oldest);
+            }
+        }
+        let entry = failures.entry(key).or_insert_with(|| IngestAuthFailure {
+            source: source.to_string(),
+            count: 0,
+            first_failure_ms: now_ms,
+            last_failure_ms: now_ms,
+        });
+        entry.count += 1;
+        entry.last_failure_ms = now_ms;
+    }
+
+    pub async fn auth_failures(&self) -> Vec<IngestAuthFailure> {
+        let mut items: Vec<IngestAuthFailure> =
+            self.auth_failures.read().await.values().cloned().collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.last_failure_ms));
+        items
+    }
+
+    /// Counts a batch rejected for claiming a `server_id` its key is not bound to; returns true
+    /// the first time a source claims that `server_id`, so it is alerted once.
+    pub async fn record_identity_mismatch(
+        &self,
+        source: &str,
+        claimed_server_id: Option<&str>,
+        authenticated_server_id: Option<&str>,
+        now_ms: i64,
+    ) -> bool {
+        let key = format!("{}|{}", source, claimed_server_id.unwrap_or_default());
+        let mut mismatches = self.identity_mismatches.write().await;
+        if !mismatches.contains_key(&key) && mismatches.len() >= MAX_AUTH_FAILURE_SOURCES {
+            if let Some(oldest) = mismatches
+                .iter()
+                .min_by_key(|(_, item)| item.last_seen_ms)
+                .map(|(key, _)| key.clone())
+            {
+                mismatches.remove(&oldest);
+            }
+        }
+        let entry = mismatches
+            .entry(key)
+            .or_insert_with(|| IngestIdentityMismatch {
+                source: source.to_string(),
+                claimed_server_id: claimed_server_id.map(ToString::to_string),
+                authenticated_server_id: authenticated_server_id.map(ToString::to_string),
+                count: 0,
+                first_seen_ms: now_ms,
+                last_seen_ms: now_ms,
+            });
+        entry.count += 1;
+        entry.last_seen_ms = now_ms;
+        entry.count == 1
+    }
+
+    pub async fn identity_mismatches(&self) -> Vec<IngestIdentityMismatch> {
+        let mut items: Vec<IngestIdentityMismatch> = self
+            .identity_mismatches
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.last_seen_ms));
+        items
+    }
+
+    pub async fn stale_servers(&self, now_ms: i64, stale_after_ms: i64) -> Vec<StaleServerStatus> {
+        let servers = self.servers.read().await;
+        let failures = self.auth_failures.read().await;
+        let mut items: Vec<StaleServerStatus> = servers
+            .iter()
+            .filter(|(_, state)| now_ms - state.last_success_ms >= stale_after_ms)
+            .map(|(server_id, state)| build_status(server_id, state, &failures, now_ms))
+            .collect();
+        items.sort_by_key(|item| item.last_success_ms);
+        items
+    }
+
+    /// Flips the per-server alert flag so each stale period is reported once, plus once on recovery.
+    pub async fn take_stale_transitions(
+        &self,
+        now_ms: i64,
+        stale_after_ms: i64,
+    ) -> StaleTransitions {
+        let mut servers = self.servers.write().await;
+        let failures = self.auth_failures.read().await;
+        let mut transitions = StaleTransitions::default();
+        for (server_id, state) in servers.iter_mut() {
+            let stale = now_ms - state.last_success_ms >= stale_after_ms;
+            if stale && !state.stale_alerted {
+                state.stale_alerted = true;
+                transitions
+                    .newly_stale
+                    .push(build_status(server_id, state, &failures, now_ms));
+            } else if !stale && state.stale_alerted {
+                state.stale_alerted = false;
+                transitions.recovered.push(server_id.clone());
+            }
+        }
+        transitions
+            .newly_stale
+            .sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        transitions.recovered.sort();
+        transitions
+    }
+}
+
+/// Auth failures count against a server when they come from the source it last ingested from.
+fn build_status(
+    server_id: &str,
+    state: &ServerIngestState,
+    failures: &HashMap<String, IngestAuthFailure>,
+    now_ms: i64,
+) -> StaleServerStatus {
+    let related = failures
+        .values()
+        .filter(|item| state.last_source.as_deref() == Some(item.source.as_str()))
+        .filter(|item| item.last_failure_ms >= state.last_success_ms);
+    let mut auth_failures = 0;
+    let mut last: Option<&IngestAuthFailure> = None;
+    for item in related {
+        auth_failures += item.count;
+        if last.is_none_or(|prev| item.last_failure_ms > prev.last_failure_ms) {
+            last = Some(item);
+        }
+    }
+    StaleServerStatus {
+        server_id: server_id.to_string(),
+        last_success_ms: state.last_success_ms,
+        stale_for_seconds: ((now_ms - state.last_success_ms).max(0) / 1000) as u64,
+        auth_failures,
+        last_auth_failure_ms: last.map(|item| item.last_failure_ms),
+        last_auth_failure_source: last.map(|item| item.source.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stale_transition_fires_once_and_recovers() {
+        let tracker = IngestSourceTracker::default();
+        tracker.record_success(["server-a"], 0).await;
+        tracker.record_source(["server-a"], "10.0.0.2").await;
+        tracker.record_auth_failure("10.0.0.2", 5_000).await;
+
+        let first = tracker.take_stale_transitions(60_000, 30_000).await;
+        assert_eq!(first.newly_stale.len(), 1);
+        assert_eq!(first.newly_stale[0].auth_failures, 1);
+        assert_eq!(
+            first.newly_stale[0].last_auth_failure_source.as_deref(),
+            Some("10.0.0.2")
+        );
+
+        let second = tracker.take_stale_transitions(90_000, 30_000).await;
+        assert!(second.newly_stale.is_empty());
+
+        tracker.record_success(["server-a"], 95_000).await;
+        let third = tracker.take_stale_transitions(96_000, 30_000).await;
+        assert_eq!(third.recovered, vec!["server-a".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn auth_failures_count_against_the_server_that_last_ingested_from_their_source() {
+        let tracker = IngestSourceTracker::default();
+        tracker.record_success(["server-a", "server-b"], 0).await;
+        tracker.record_source(["server-a"], "10.0.0.2").await;
+        tracker.record_source(["server-b"], "10.0.0.3").await;
+        tracker.record_auth_failure("10.0.0.2", 5_000).await;
+        tracker.record_auth_failure("10.0.0.2", 6_000).await;
+        tracker.record_auth_failure("203.0.113.9", 7_000).await;
+
+        let stale = tracker.stale_servers(60_000, 30_000).await;
+        let failures: Vec<(&str, u64)> = stale
+            .iter()
+            .map(|status| (status.server_id.as_str(), status.auth_failures))
+            .collect();
+        assert!(failures.contains(&("server-a", 2)));
+        assert!(failures.contains(&("server-b", 0)));
+        let sources: Vec<String> = tracker
+            .auth_failures()
+            .await
+            .into_iter()
+            .map(|item| item.source)
+            .collect();
+        assert_eq!(sources, vec!["203.0.113.9", "10.0.0.2"]);
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use backend_domain::Job;
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use uuid::Uuid;
+
+/// Finished jobs beyond this are forgotten, oldest first.
+const MAX_JOBS: usize = 100;
+
+/// Background jobs of `/v2/ops/jobs`. Each job's task waits for `run_slot`, which hands out one
+/// slot at a time in request order, so jobs run one after another.
+#[derive(Default)]
+pub struct JobQueue {
+    jobs: RwLock<VecDeque<Job>>,
+    slot: Mutex<()>,
+}
+
+impl JobQueue {
+    /// Records a QUEUED job; the caller then spawns its task.
+    pub async fn submit(&self, kind: &str, submitted_by: &str, now_ms: i64) -> Job {
+        let job = Job {
+            id: Uuid::new_v4().simple().to_string(),
+            kind: kind.to_string(),
+            state: "QUEUED".to_string(),
+            submitted_by: submitted_by.to_string(),
+            submitted_at_ms: now_ms,
+            started_at_ms: None,
+            finished_at_ms: None,
+            error: None,
+            result: None,
+        };
+        let mut jobs = self.jobs.write().await;
+        jobs.push_back(job.clone());
+        while jobs.len() > MAX_JOBS {
+            let Some(index) = jobs.iter().position(is_finished) else {
+                break;
+            };
+            jobs.remove(index);
+        }
+        job
+    }
+
+    /// Held while a job runs.
+    pub async fn run_slot(&self) -> MutexGuard<'_, ()> {
+        self.slot.lock().await
+    }
+
+    pub async fn start(&self, id: &str, now_ms: i64) {
+        self.update(id, |job| {
+            job.state = "RUNNING".to_string();
+            job.started_at_ms = Some(now_ms);
+        })
+        .await;
+    }
+
+    pub async fn finish(&self, id: &str, now_ms: i64, outcome: Result<serde_json::Value, String>) {
+        self.update(id, |job| {
+            job.finished_at_ms = Some(now_ms);
+            match outcome {
+                Ok(result) => {
+                    job.state = "SUCCEEDED".to_string();
+                    job.result = Some(result);
+                }
+                Err(error) => {
+                    job.state = "FAILED".to_string();
+                    job.error = Some(error);
+                }
+            }
+        })
+        .await;
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .read()
+            .await
+            .iter()
+            .find(|job| job.id == id)
+            .cloned()
+    }
+
+    /// Newest first.
+    pub async fn list(&self) -> Vec<Job> {
+        self.jobs.read().await.iter().rev().cloned().collect()
+    }
+
+    async fn update(&self, id: &str, apply: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.write().await.iter_mut().find(|job| job.id == id) {
+            apply(job);
+        }
+    }
+}
+
+fn is_finished(job: &Job) -> bool {
+    job.state == "SUCCEEDED" || job.state == "FAILED"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn jobs_move_through_their_states_and_finished_ones_are_trimmed() {
+        let queue = JobQueue::default();
+        let first = queue.submit("ml_export", "ops", 1).await;
+        assert_eq!(first.state, "QUEUED");
+        queue.start(&first.id, 2).await;
+        queue
+            .finish(&first.id, 3, Ok(serde_json::json!({ "rows": 4 })))
+            .await;
+        let done = queue.get(&first.id).await.unwrap();
+        assert_eq!(done.state, "SUCCEEDED");
+        assert_eq!(done.started_at_ms, Some(2));
+        assert_eq!(done.result.unwrap()["rows"], 4);
+
+        let second = queue.submit("ml_export", "ops", 4).await;
+        queue.finish(&second.id, 5, Err("boom".to_string())).await;
+        assert_eq!(queue.list().await[0].error.as_deref(), Some("boom"));
+
+        for at in 0..MAX_JOBS as i64 {
+            queue.submit("ml_export", "ops", 10 + at).await;
+        }
+        let jobs = queue.list().await;
+        assert_eq!(jobs.len(), MAX_JOBS);
+        assert!(jobs.iter().all(|job| job.state == "QUEUED"));
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+
+use backend_domain::ModVersion;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModVersionCheck {
+    Accepted,
+    Outdated { minimum: String },
+}
+
+/// Remembers which (server, version) pairs were already reported so alerts fire once per rollout.
+#[derive(Default)]
+pub struct ModVersionGate {
+    alerted: Mutex<HashSet<(String, String)>>,
+}
+
+impl ModVersionGate {
+    pub fn check(minimum: Option<&str>, version: Option<&str>) -> ModVersionCheck {
+        let (Some(minimum), Some(version)) = (minimum, version) else {
+            return ModVersionCheck::Accepted;
+        };
+        let (Some(required), Some(reported)) =
+            (ModVersion::parse(minimum), ModVersion::parse(version))
+        else {
+            return ModVersionCheck::Accepted;
+        };
+        if reported < required {
+            ModVersionCheck::Outdated {
+                minimum: minimum.to_string(),
+            }
+        } else {
+            ModVersionCheck::Accepted
+        }
+    }
+
+    pub async fn first_report(&self, server_id: &str, version: &str) -> bool {
+        self.alerted
+            .lock()
+            .await
+            .insert((server_id.to_string(), version.to_string()))
+    }
+}
@@ -0,0 +1,92 @@
+use backend_domain::{IngestEvent, LearnedOriginType, OriginWhitelist};
+use tokio::sync::RwLock;
+
+/// The active R2 origin type whitelist, including what a learning period has recorded.
+pub struct OriginWhitelistRegistry {
+    whitelist: RwLock<OriginWhitelist>,
+}
+
+impl OriginWhitelistRegistry {
+    pub fn new(whitelist: OriginWhitelist) -> Self {
+        Self {
+            whitelist: RwLock::new(whitelist),
+        }
+    }
+
+    pub async fn snapshot(&self) -> OriginWhitelist {
+        self.whitelist.read().await.clone()
+    }
+
+    pub async fn replace(&self, whitelist: OriginWhitelist) {
+        *self.whitelist.write().await = whitelist;
+    }
+
+    /// Records the origin types of `events` the whitelist does not know yet, when learning at
+    /// `now_ms`; returns the updated whitelist to persist, or `None` when nothing was new.
+    pub async fn learn(&self, events: &[IngestEvent], now_ms: i64) -> Option<OriginWhitelist> {
+        if !self.whitelist.read().await.is_learning(now_ms) {
+            return None;
+        }
+        let mut whitelist = self.whitelist.write().await;
+        let mut changed = false;
+        for event in events.iter().filter(|event| event.event_type == "ACQUIRE") {
+            let Some(origin_type) = event
+                .origin_type
+                .as_deref()
+                .map(str::trim)
+                .filter(|origin_type| !origin_type.is_empty())
+            else {
+                continue;
+            };
+            if whitelist.allows(origin_type) {
+                continue;
+            }
+            whitelist.learned.push(LearnedOriginType {
+                origin_type: origin_type.to_string(),
+                first_seen_ms: event.event_time,
+                server_id: event.server_id.clone(),
+                item_id: event.item_id.clone(),
+            });
+            changed = true;
+        }
+        changed.then(|| whitelist.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquire(origin_type: &str) -> IngestEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_id": "evt-1",
+            "event_time": 1_000,
+            "event_type": "ACQUIRE",
+            "server_id": "server-01",
+            "item_id": "botania:rune_fire",
+            "count": 1,
+            "origin_type": origin_type,
+        }))
+        .expect("event")
+    }
+
+    #[tokio::test]
+    async fn learning_records_unseen_origin_types_once() {
+        let registry = OriginWhitelistRegistry::new(OriginWhitelist::default());
+        let events = [acquire("botania_rune_altar"), acquire("craft")];
+        assert!(registry.learn(&events, 0).await.is_none());
+
+        let mut whitelist = registry.snapshot().await;
+        whitelist.learning_until_ms = Some(10_000);
+        registry.replace(whitelist).await;
+        let learned = registry.learn(&events, 0).await.expect("learned");
+        assert_eq!(learned.learned.len(), 1);
+        assert_eq!(learned.learned[0].origin_type, "botania_rune_altar");
+        assert!(learned.allows("botania_rune_altar"));
+        assert!(registry.learn(&events, 0).await.is_none());
+        assert!(registry
+            .learn(&[acquire("mana_pool")], 10_000)
+            .await
+            .is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use backend_domain::{AnomalyRow, PlayerTeam};
+use tokio::sync::RwLock;
+
+/// Player-to-team assignments used for alert routing, matched by player UUID or, failing that,
+/// by name.
+pub struct PlayerTeamRegistry {
+    items: RwLock<Vec<PlayerTeam>>,
+}
+
+impl PlayerTeamRegistry {
+    pub fn new(items: Vec<PlayerTeam>) -> Self {
+        Self {
+            items: RwLock::new(items),
+        }
+    }
+
+    pub async fn replace(&self, items: Vec<PlayerTeam>) {
+        *self.items.write().await = items;
+    }
+
+    pub async fn team_of(&self, row: &AnomalyRow) -> Option<String> {
+        let items = self.items.read().await;
+        let by_uuid = items.iter().find(|item| {
+            item.player_uuid
+                .as_deref()
+                .is_some_and(|uuid| uuid.eq_ignore_ascii_case(&row.player_uuid))
+        });
+        by_uuid
+            .or_else(|| {
+                items.iter().find(|item| {
+                    item.player_name
+                        .as_deref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&row.player_name))
+                })
+            })
+            .map(|item| item.team.clone())
+    }
+
+    pub async fn snapshot(&self) -> Vec<PlayerTeam> {
+        self.items.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::anomaly_row;
+
+    fn row(player_uuid: &str, player_name: &str) -> AnomalyRow {
+        AnomalyRow {
+            player_uuid: player_uuid.to_string(),
+            player_name: player_name.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn uuid_assignments_win_over_name_assignments() {
+        let registry = PlayerTeamRegistry::new(vec![
+            PlayerTeam {
+                player_uuid: None,
+                player_name: Some("Alice".to_string()),
+                team: "north".to_string(),
+            },
+            PlayerTeam {
+                player_uuid: Some("UUID-1".to_string()),
+                player_name: None,
+                team: "south".to_string(),
+            },
+        ]);
+
+        assert_eq!(
+            registry.team_of(&row("uuid-1", "alice")).await.as_deref(),
+            Some("south")
+        );
+        assert_eq!(
+            registry.team_of(&row("uuid-2", "ALICE")).await.as_deref(),
+            Some("north")
+        );
+        assert_eq!(registry.team_of(&row("uuid-3", "bob")).await, None);
+    }
+}
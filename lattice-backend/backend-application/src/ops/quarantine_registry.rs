@@ -0,0 +1,131 @@
+use backend_domain::{mark_quarantined, AnomalyRow, PlayerQuarantine};
+use tokio::sync::RwLock;
+
+/// Players under investigation, matched by UUID or, failing that, by name.
+pub struct QuarantineRegistry {
+    items: RwLock<Vec<PlayerQuarantine>>,
+}
+
+impl QuarantineRegistry {
+    pub fn new(items: Vec<PlayerQuarantine>) -> Self {
+        Self {
+            items: RwLock::new(items),
+        }
+    }
+
+    /// Replaces an earlier entry of the same player.
+    pub async fn quarantine(&self, entry: PlayerQuarantine) {
+        let mut items = self.items.write().await;
+        items.retain(|item| !same_player(item, &entry));
+        items.push(entry);
+    }
+
+    /// Releases the entry whose UUID or name equals `player`; None when there is none.
+    pub async fn release(&self, player: &str) -> Option<PlayerQuarantine> {
+        let mut items = self.items.write().await;
+        let index = items
+            .iter()
+            .position(|item| matches(item, player, player))?;
+        Some(items.remove(index))
+    }
+
+    /// Newest first.
+    pub async fn list(&self) -> Vec<PlayerQuarantine> {
+        let mut items = self.items.read().await.clone();
+        items.sort_by_key(|item| std::cmp::Reverse(item.quarantined_at_ms));
+        items
+    }
+
+    /// Tags the anomalies of quarantined players and returns how many were tagged.
+    pub async fn tag(&self, anomalies: &mut [AnomalyRow]) -> usize {
+        let items = self.items.read().await;
+        if items.is_empty() {
+            return 0;
+        }
+        let mut tagged = 0;
+        for row in anomalies.iter_mut() {
+            if items
+                .iter()
+                .any(|item| matches(item, &row.player_uuid, &row.player_name))
+            {
+                mark_quarantined(row);
+                tagged += 1;
+            }
+        }
+        tagged
+    }
+
+    pub async fn snapshot(&self) -> Vec<PlayerQuarantine> {
+        self.items.read().await.clone()
+    }
+}
+
+fn matches(item: &PlayerQuarantine, player_uuid: &str, player_name: &str) -> bool {
+    item.player_uuid
+        .as_deref()
+        .is_some_and(|uuid| !player_uuid.is_empty() && uuid.eq_ignore_ascii_case(player_uuid))
+        || item
+            .player_name
+            .as_deref()
+            .is_some_and(|name| !player_name.is_empty() && name.eq_ignore_ascii_case(player_name))
+}
+
+fn same_player(a: &PlayerQuarantine, b: &PlayerQuarantine) -> bool {
+    let field = |left: &Option<String>, right: &Option<String>| match (left, right) {
+        (Some(left), Some(right)) => left.eq_ignore_ascii_case(right),
+        _ => false,
+    };
+    field(&a.player_uuid, &b.player_uuid) || field(&a.player_name, &b.player_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::is_quarantined;
+    use backend_domain::testing::anomaly_row;
+
+    fn row(player_uuid: &str, player_name: &str) -> AnomalyRow {
+        AnomalyRow {
+            player_uuid: player_uuid.to_string(),
+            player_name: player_name.to_string(),
+            evidence_json: r#"{"threshold":32}"#.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    fn entry(player_uuid: Option<&str>, player_name: Option<&str>, at_ms: i64) -> PlayerQuarantine {
+        PlayerQuarantine {
+            player_uuid: player_uuid.map(ToString::to_string),
+            player_name: player_name.map(ToString::to_string),
+            reason: None,
+            quarantined_by: "ops".to_string(),
+            quarantined_at_ms: at_ms,
+        }
+    }
+
+    #[tokio::test]
+    async fn quarantined_players_are_tagged_until_released() {
+        let registry = QuarantineRegistry::new(Vec::new());
+        registry.quarantine(entry(Some("uuid-1"), None, 1)).await;
+        registry.quarantine(entry(None, Some("PlayerX"), 2)).await;
+
+        let mut rows = vec![
+            row("UUID-1", "Someone"),
+            row("uuid-2", "playerx"),
+            row("uuid-3", ""),
+        ];
+        assert_eq!(registry.tag(&mut rows).await, 2);
+        assert!(is_quarantined(&rows[0]));
+        assert!(rows[1].evidence_json.contains(r#""threshold":32"#));
+        assert!(!is_quarantined(&rows[2]));
+        assert_eq!(
+            registry.list().await[0].player_name.as_deref(),
+            Some("PlayerX")
+        );
+
+        assert!(registry.release("playerx").await.is_some());
+        assert!(registry.release("playerx").await.is_none());
+        let mut rows = vec![row("uuid-2", "PlayerX")];
+        assert_eq!(registry.tag(&mut rows).await, 0);
+    }
+}
@@ -0,0 +1,72 @@
+use backend_domain::RuleRevision;
+use tokio::sync::RwLock;
+
+/// Revisions kept; older ones are dropped once a report could no longer need them.
+const MAX_RULE_REVISIONS: usize = 1000;
+
+/// History of key item rule changes, oldest first, so a report can mark when the rules it was
+/// evaluated under changed.
+pub struct RuleRevisionLog {
+    items: RwLock<Vec<RuleRevision>>,
+}
+
+impl RuleRevisionLog {
+    pub fn new(mut items: Vec<RuleRevision>) -> Self {
+        items.sort_by_key(|item| item.revision);
+        Self {
+            items: RwLock::new(items),
+        }
+    }
+
+    /// Appends the next revision and returns it.
+    pub async fn record(&self, actor: &str, summary: &str, now_ms: i64) -> RuleRevision {
+        let mut items = self.items.write().await;
+        let revision = RuleRevision {
+            revision: items.last().map(|item| item.revision + 1).unwrap_or(1),
+            changed_at_ms: now_ms,
+            actor: actor.to_string(),
+            summary: summary.to_string(),
+        };
+        items.push(revision.clone());
+        if items.len() > MAX_RULE_REVISIONS {
+            let excess = items.len() - MAX_RULE_REVISIONS;
+            items.drain(..excess);
+        }
+        revision
+    }
+
+    /// Revisions with `from_ms <= changed_at_ms < to_ms`, oldest first.
+    pub async fn between(&self, from_ms: i64, to_ms: i64) -> Vec<RuleRevision> {
+        self.items
+            .read()
+            .await
+            .iter()
+            .filter(|item| item.changed_at_ms >= from_ms && item.changed_at_ms < to_ms)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn snapshot(&self) -> Vec<RuleRevision> {
+        self.items.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn revisions_are_numbered_and_selected_by_time() {
+        let log = RuleRevisionLog::new(Vec::new());
+        assert_eq!(log.record("desktop", "a", 1_000).await.revision, 1);
+        assert_eq!(log.record("desktop", "b", 2_000).await.revision, 2);
+        log.record("desktop", "c", 3_000).await;
+
+        let day = log.between(2_000, 3_000).await;
+        assert_eq!(day.len(), 1);
+        assert_eq!(day[0].summary, "b");
+
+        let reloaded = RuleRevisionLog::new(log.snapshot().await);
+        assert_eq!(reloaded.record("desktop", "d", 4_000).await.revision, 4);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::{HashMap, VecDeque};
+
+use backend_domain::{ServerHeartbeat, ServerStatus};
+use tokio::sync::RwLock;
+
+const MAX_RECENT_HEARTBEATS: usize = 60;
+
+#[derive(Default)]
+struct HeartbeatTrack {
+    recent: VecDeque<ServerHeartbeat>,
+    missed_alerted: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct HeartbeatTransitions {
+    pub missed: Vec<ServerStatus>,
+    pub recovered: Vec<ServerStatus>,
+}
+
+#[derive(Default)]
+pub struct ServerHeartbeatRegistry {
+    servers: RwLock<HashMap<String, HeartbeatTrack>>,
+}
+
+impl ServerHeartbeatRegistry {
+    pub async fn record(&self, heartbeat: ServerHeartbeat) {
+        let mut servers = self.servers.write().await;
+        let track = servers.entry(heartbeat.server_id.clone()).or_default();
+        if track.recent.len() >= MAX_RECENT_HEARTBEATS {
+            track.recent.pop_front();
+        }
+        track.recent.push_back(heartbeat);
+    }
+
+    pub async fn list_status(
+        &self,
+        now_ms: i64,
+        interval_ms: i64,
+        missed_threshold: u64,
+    ) -> Vec<ServerStatus> {
+        let servers = self.servers.read().await;
+        let mut items: Vec<ServerStatus> = servers
+            .iter()
+            .filter_map(|(server_id, track)| {
+                build_status(
+                    server_id,
+                    track,
+                    now_ms,
+                    interval_ms,
+                    missed_threshold,
+                    true,
+                )
+            })
+            .collect();
+        items.sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        items
+    }
+
+    /// Reports each server once when it goes offline and once when heartbeats resume.
+    pub async fn take_transitions(
+        &self,
+        now_ms: i64,
+        interval_ms: i64,
+        missed_threshold: u64,
+    ) -> HeartbeatTransitions {
+        let mut servers = self.servers.write().await;
+        let mut transitions = HeartbeatTransitions::default();
+        for (server_id, track) in servers.iter_mut() {
+            let Some(status) = build_status(
+                server_id,
+                track,
+                now_ms,
+                interval_ms,
+                missed_threshold,
+                false,
+            ) else {
+                continue;
+            };
+            if !status.online && !track.missed_alerted {
+                track.missed_alerted = true;
+                transitions.missed.push(status);
+            } else if status.online && track.missed_alerted {
+                track.missed_alerted = false;
+                transitions.recovered.push(status);
+            }
+        }
+        transitions
+            .missed
+            .sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        transitions
+            .recovered
+            .sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        transitions
+    }
+}
+
+fn build_status(
+    server_id: &str,
+    track: &HeartbeatTrack,
+    now_ms: i64,
+    interval_ms: i64,
+    missed_threshold: u64,
+    include_recent: bool,
+) -> Option<ServerStatus> {
+    let last = track.recent.back()?;
+    let elapsed_ms = (now_ms - last.received_at_ms).max(0);
+    let missed_heartbeats = (elapsed_ms / interval_ms.max(1)) as u64;
+    let online = missed_threshold == 0 || missed_heartbeats < missed_threshold;
+    Some(ServerStatus {
+        server_id: server_id.to_string(),
+        online,
+        last_heartbeat_ms: last.received_at_ms,
+        missed_heartbeats,
+        mod_version: last.mod_version.clone(),
+        player_count: last.player_count,
+        tps: last.tps,
+        mod_version_outdated: false,
+        recent: if include_recent {
+            track.recent.iter().cloned().collect()
+        } else {
+            Vec::new()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat(received_at_ms: i64) -> ServerHeartbeat {
+        ServerHeartbeat {
+            server_id: "server-01".to_string(),
+            mod_version: Some("2.1.0".to_string()),
+            player_count: Some(12),
+            tps: Some(19.8),
+            received_at_ms,
+        }
+    }
+
+    #[tokio::test]
+    async fn missed_heartbeats_alert_once_and_recover() {
+        let registry = ServerHeartbeatRegistry::default();
+        registry.record(heartbeat(0)).await;
+
+        let early = registry.take_transitions(120_000, 60_000, 3).await;
+        assert!(early.missed.is_empty());
+
+        let missed = registry.take_transitions(180_000, 60_000, 3).await;
+        assert_eq!(missed.missed.len(), 1);
+        assert_eq!(missed.missed[0].missed_heartbeats, 3);
+        assert!(registry
+            .take_transitions(240_000, 60_000, 3)
+            .await
+            .missed
+            .is_empty());
+
+        registry.record(heartbeat(250_000)).await;
+        let recovered = registry.take_transitions(260_000, 60_000, 3).await;
+        assert_eq!(recovered.recovered.len(), 1);
+        let status = registry.list_status(260_000, 60_000, 3).await;
+        assert_eq!(status[0].recent.len(), 2);
+    }
+}
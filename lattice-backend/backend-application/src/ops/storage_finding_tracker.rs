@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use backend_domain::{
+    AnomalyRow, StorageFinding, SCAN_STATUS_GROWN, SCAN_STATUS_NEW, SCAN_STATUS_PERSISTING,
+    STORAGE_SCAN_RULE_ID,
+};
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+#[derive(Default)]
+pub struct StorageFindingTracker {
+    findings: RwLock<HashMap<String, StorageFinding>>,
+    scan_started_ms: RwLock<Option<i64>>,
+}
+
+impl StorageFindingTracker {
+    pub fn new(findings: Vec<StorageFinding>) -> Self {
+        Self {
+            findings: RwLock::new(
+                findings
+                    .into_iter()
+                    .map(|finding| {
+                        let key =
+                            finding_key(&finding.server_id, &finding.storage_id, &finding.item_id);
+                        (key, finding)
+                    })
+                    .collect(),
+            ),
+            scan_started_ms: RwLock::new(None),
+        }
+    }
+
+    /// Compares every R12 row with the last finding for its location and stamps `scan_status`
+    /// (plus `previous_count` when it grew) into the evidence. Returns whether any finding changed.
+    pub async fn classify(&self, anomalies: &mut [AnomalyRow], now_ms: i64) -> bool {
+        let mut findings = self.findings.write().await;
+        let mut changed = false;
+        for row in anomalies
+            .iter_mut()
+            .filter(|row| row.rule_id == STORAGE_SCAN_RULE_ID)
+        {
+            let Ok(Value::Object(mut evidence)) = serde_json::from_str::<Value>(&row.evidence_json)
+            else {
+                continue;
+            };
+            let storage_id = evidence
+                .get("storage_id")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if storage_id.is_empty() {
+                continue;
+            }
+            let key = finding_key(&row.server_id, &storage_id, &row.item_id);
+            let status = match findings.get(&key) {
+                None => SCAN_STATUS_NEW,
+                Some(previous) if row.count > previous.count => {
+                    evidence.insert("previous_count".to_string(), previous.count.into());
+                    SCAN_STATUS_GROWN
+                }
+                Some(_) => SCAN_STATUS_PERSISTING,
+            };
+            evidence.insert("scan_status".to_string(), status.into());
+            row.evidence_json = Value::Object(evidence).to_string();
+
+            let finding = findings.entry(key).or_insert_with(|| StorageFinding {
+                server_id: row.server_id.clone(),
+                storage_id,
+                item_id: row.item_id.clone(),
+                count: row.count,
+                first_seen_ms: now_ms,
+                last_seen_ms: now_ms,
+            });
+            finding.count = row.count;
+            finding.last_seen_ms = now_ms;
+            changed = true;
+        }
+        changed
+    }
+
+    pub async fn begin_scan(&self, now_ms: i64) {
+        *self.scan_started_ms.write().await = Some(now_ms);
+    }
+
+    /// Drops findings the finished scan did not see again, so a chest that was emptied and
+    /// refilled alerts as new. Returns how many were dropped.
+    pub async fn finish_scan(&self) -> usize {
+        let Some(started_ms) = self.scan_started_ms.write().await.take() else {
+            return 0;
+        };
+        let mut findings = self.findings.write().await;
+        let before = findings.len();
+        findings.retain(|_, finding| finding.last_seen_ms >= started_ms);
+        before - findings.len()
+    }
+
+    pub async fn snapshot(&self) -> Vec<StorageFinding> {
+        let mut items: Vec<StorageFinding> = self.findings.read().await.values().cloned().collect();
+        items.sort_by(|a, b| {
+            (&a.server_id, &a.storage_id, &a.item_id).cmp(&(
+                &b.server_id,
+                &b.storage_id,
+                &b.item_id,
+            ))
+        });
+        items
+    }
+}
+
+fn finding_key(server_id: &str, storage_id: &str, item_id: &str) -> String {
+    format!("{}|{}|{}", server_id, storage_id, item_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::storage_scan_status;
+    use backend_domain::testing::anomaly_row;
+
+    fn r12(count: i64) -> AnomalyRow {
+        AnomalyRow {
+            player_uuid: String::new(),
+            player_name: String::new(),
+            item_id: "minecraft:netherite_ingot".to_string(),
+            count,
+            rule_id: "R12".to_string(),
+            reason: "Storage snapshot exceeds threshold".to_string(),
+            evidence_json: r#"{"storage_id":"chest@0,64,0"}"#.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn only_new_or_growing_findings_are_fresh() {
+        let tracker = StorageFindingTracker::default();
+        let mut rows = vec![r12(100)];
+        assert!(tracker.classify(&mut rows, 1_000).await);
+        assert_eq!(
+            storage_scan_status(&rows[0]).as_deref(),
+            Some(SCAN_STATUS_NEW)
+        );
+
+        let mut rows = vec![r12(100), r12(150)];
+        tracker.classify(&mut rows, 2_000).await;
+        assert_eq!(
+            storage_scan_status(&rows[0]).as_deref(),
+            Some(SCAN_STATUS_PERSISTING)
+        );
+        assert_eq!(
+            storage_scan_status(&rows[1]).as_deref(),
+            Some(SCAN_STATUS_GROWN)
+        );
+
+        tracker.begin_scan(3_000).await;
+        assert_eq!(tracker.finish_scan().await, 1);
+        let mut rows = vec![r12(150)];
+        tracker.classify(&mut rows, 4_000).await;
+        assert_eq!(
+            storage_scan_status(&rows[0]).as_deref(),
+            Some(SCAN_STATUS_NEW)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use backend_domain::{AnomalyRow, AnomalySuppression};
+use tokio::sync::RwLock;
+
+/// Expired suppressions stay listed this long before they are dropped from disk.
+pub const EXPIRED_SUPPRESSION_RETENTION_MS: i64 = 7 * 24 * 3_600_000;
+
+pub struct SuppressionRegistry {
+    items: RwLock<Vec<AnomalySuppression>>,
+}
+
+impl SuppressionRegistry {
+    pub fn new(items: Vec<AnomalySuppression>) -> Self {
+        Self {
+            items: RwLock::new(items),
+        }
+    }
+
+    pub async fn add(&self, suppression: AnomalySuppression) {
+        self.items.write().await.push(suppression);
+    }
+
+    pub async fn active(&self, now_ms: i64) -> Vec<AnomalySuppression> {
+        let mut items: Vec<AnomalySuppression> = self
+            .items
+            .read()
+            .await
+            .iter()
+            .filter(|item| item.expires_at_ms > now_ms)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| item.expires_at_ms);
+        items
+    }
+
+    /// Suppressions that expired at or after `since_ms`, most recent first.
+    pub async fn expired_since(&self, now_ms: i64, since_ms: i64) -> Vec<AnomalySuppression> {
+        let mut items: Vec<AnomalySuppression> = self
+            .items
+            .read()
+            .await
+            .iter()
+            .filter(|item| item.expires_at_ms <= now_ms && item.expires_at_ms >= since_ms)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.expires_at_ms));
+        items
+    }
+
+    pub async fn is_suppressed(&self, row: &AnomalyRow, now_ms: i64) -> bool {
+        self.items
+            .read()
+            .await
+            .iter()
+            .any(|item| item.expires_at_ms > now_ms && matches(item, row))
+    }
+
+    /// Marks newly expired suppressions as notified and returns them; also drops entries past
+    /// the retention window. The caller persists when anything changed.
+    pub async fn take_expired(&self, now_ms: i64) -> (Vec<AnomalySuppression>, bool) {
+        let mut items = self.items.write().await;
+        let before = items.len();
+        items.retain(|item| now_ms - item.expires_at_ms <= EXPIRED_SUPPRESSION_RETENTION_MS);
+        let mut expired = Vec::new();
+        for item in items.iter_mut() {
+            if item.expires_at_ms <= now_ms && !item.expiry_notified {
+                item.expiry_notified = true;
+                expired.push(item.clone());
+            }
+        }
+        let changed = !expired.is_empty() || items.len() != before;
+        (expired, changed)
+    }
+
+    pub async fn snapshot(&self) -> Vec<AnomalySuppression> {
+        self.items.read().await.clone()
+    }
+}
+
+fn matches(item: &AnomalySuppression, row: &AnomalyRow) -> bool {
+    let field = |filter: &Option<String>, value: &str| {
+        filter
+            .as_deref()
+            .is_none_or(|filter| filter.eq_ignore_ascii_case(value))
+    };
+    field(&item.rule_id, &row.rule_id)
+        && field(&item.server_id, &row.server_id)
+        && field(&item.item_id, &row.item_id)
+        && item.player.as_deref().is_none_or(|player| {
+            player.eq_ignore_ascii_case(&row.player_name)
+                || player.eq_ignore_ascii_case(&row.player_uuid)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::anomaly_row;
+
+    fn row(player_name: &str, rule_id: &str) -> AnomalyRow {
+        AnomalyRow {
+            player_name: player_name.to_string(),
+            rule_id: rule_id.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn suppression_matches_until_expiry_and_notifies_once() {
+        let registry = SuppressionRegistry::new(vec![AnomalySuppression {
+            id: "s1".to_string(),
+            rule_id: Some("R10".to_string()),
+            player: Some("playerx".to_string()),
+            server_id: None,
+            item_id: None,
+            reason: Some("event weekend".to_string()),
+            created_at_ms: 0,
+            expires_at_ms: 1_000,
+            expiry_notified: false,
+        }]);
+
+        assert!(registry.is_suppressed(&row("PlayerX", "R10"), 500).await);
+        assert!(!registry.is_suppressed(&row("PlayerX", "R4"), 500).await);
+        assert!(!registry.is_suppressed(&row("PlayerX", "R10"), 1_000).await);
+
+        let (expired, changed) = registry.take_expired(1_500).await;
+        assert_eq!(expired.len(), 1);
+        assert!(changed);
+        let (again, _) = registry.take_expired(2_000).await;
+        assert!(again.is_empty());
+        assert_eq!(registry.expired_since(2_000, 0).await.len(), 1);
+    }
+}
@@ -1,6 +1,24 @@
+pub mod alert_queries;
+pub mod analyzer_queries;
 pub mod anomaly_queries;
+pub mod api_token_queries;
+pub mod ban_queries;
+pub mod config_queries;
+pub mod event_queries;
+pub mod ingest_queries;
 pub mod item_registry_queries;
+pub mod item_trace_queries;
+pub mod job_queries;
 pub mod key_item_queries;
+pub mod maintenance_queries;
 pub mod mod_config_queries;
+pub mod origin_whitelist_queries;
+pub mod overview_queries;
+pub mod player_profile_queries;
+pub mod player_team_queries;
+pub mod preflight_queries;
+pub mod quarantine_queries;
+pub mod report_queries;
 pub mod storage_scan_queries;
+pub mod suppression_queries;
 pub mod task_progress_queries;
@@ -0,0 +1,89 @@
+use chrono::Local;
+use tracing::warn;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    current_millis, is_alerting_anomaly, millis_to_utc, AlertPreview, AlertPreviewRequest,
+    AlertPreviewSample, AnomalyRow,
+};
+
+const MAX_PREVIEW_SAMPLES: usize = 50;
+
+/// Renders alerts exactly as delivery would, without sending; falls back to today's anomalies,
+/// then to a placeholder row so template edits always have something to show.
+pub async fn preview_alerts(
+    state: &AppState,
+    request: AlertPreviewRequest,
+) -> Result<AlertPreview, AppError> {
+    if request.samples.len() > MAX_PREVIEW_SAMPLES {
+        return Err(AppError::BadRequest(format!(
+            "samples must not exceed {}",
+            MAX_PREVIEW_SAMPLES
+        )));
+    }
+
+    let mut config = state.config.clone();
+    if let Some(template) = request.alert_webhook_template {
+        if !template.trim().is_empty() {
+            config.alert_webhook_template = Some(template);
+        }
+    }
+
+    let (sample_source, anomalies) = if !request.samples.is_empty() {
+        let rows = request.samples.into_iter().map(sample_to_row).collect();
+        ("supplied", rows)
+    } else {
+        let date = Local::now().format("%Y-%m-%d").to_string();
+        let recent = match state.anomaly_repo.fetch_anomalies(&date, None).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                warn!("alert preview could not load recent anomalies: {}", err);
+                Vec::new()
+            }
+        };
+        let recent = recent
+            .into_iter()
+            .filter(is_alerting_anomaly)
+            .take(MAX_PREVIEW_SAMPLES)
+            .collect::<Vec<_>>();
+        if recent.is_empty() {
+            ("placeholder", vec![placeholder_row()])
+        } else {
+            ("recent", recent)
+        }
+    };
+
+    let mut preview = state.alert_service.preview_alerts(&config, anomalies);
+    preview.sample_source = sample_source.to_string();
+    Ok(preview)
+}
+
+fn sample_to_row(sample: AlertPreviewSample) -> AnomalyRow {
+    AnomalyRow {
+        event_time: millis_to_utc(current_millis()),
+        server_id: sample.server_id.unwrap_or_else(|| "server-01".to_string()),
+        player_uuid: String::new(),
+        player_name: sample.player_name.unwrap_or_else(|| "Steve".to_string()),
+        item_id: sample.item_id,
+        count: sample.count,
+        risk_level: sample
+            .risk_level
+            .map(|value| value.trim().to_uppercase())
+            .unwrap_or_else(|| "HIGH".to_string()),
+        rule_id: sample.rule_id.trim().to_uppercase(),
+        reason: String::new(),
+        evidence_json: "{}".to_string(),
+    }
+}
+
+fn placeholder_row() -> AnomalyRow {
+    sample_to_row(AlertPreviewSample {
+        server_id: None,
+        player_name: None,
+        item_id: "minecraft:netherite_ingot".to_string(),
+        count: 64,
+        risk_level: None,
+        rule_id: "R4".to_string(),
+    })
+}
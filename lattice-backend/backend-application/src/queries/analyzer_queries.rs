@@ -0,0 +1,10 @@
+use crate::AppState;
+use backend_domain::AnalyzerStatus;
+
+pub async fn analyzer_status(state: &AppState) -> AnalyzerStatus {
+    let servers = state.analyzer.lock().await.server_status();
+    AnalyzerStatus {
+        forwarded: state.cluster_state.is_some(),
+        servers,
+    }
+}
@@ -1,51 +1,88 @@
+use std::collections::HashSet;
+
 use chrono::Local;
-use tracing::error;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
 
+use crate::commands::dead_letter_commands::record_storage_failure;
 use crate::AppState;
-use crate::AppError;
-use backend_domain::{AnomalyQuery, AnomalyRow, PagedResult};
+use crate::{AppError, ErrorCode};
+use backend_domain::{
+    anomaly_id, anomaly_id_event_ms, is_risk_level, rule_description, AnomalyAckKey,
+    AnomalyDailySummaryRow, AnomalyLookupQuery, AnomalyQuery, AnomalyRow, AnomalyStreamQuery,
+    AnomalyTrendQuery, AnomalyView, FieldSelection, PagedResult, TimeDisplay, DEFAULT_RULE_LANG,
+    RISK_LEVEL_NAMES,
+};
 
 const DEFAULT_PAGE: usize = 1;
 const DEFAULT_PAGE_SIZE: usize = 50;
 const ALLOWED_PAGE_SIZES: [usize; 4] = [25, 50, 100, 200];
+const EXPORT_CHUNK_SIZE: usize = 1000;
+const DEFAULT_TREND_DAYS: u32 = 30;
+const MAX_TREND_DAYS: u32 = 365;
 
+/// Lists one page of anomalies; `fields` trims the heavy text columns read from storage.
 pub async fn list_anomalies(
     state: &AppState,
     query: AnomalyQuery,
-) -> Result<PagedResult<AnomalyRow>, AppError> {
-    let date = query
-        .date
-        .unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
-    if let Err(err) = backend_domain::parse_date(&date) {
-        return Err(AppError::BadRequest(format!("invalid date: {}", err)));
-    }
-
+    fields: &FieldSelection,
+) -> Result<PagedResult<AnomalyView>, AppError> {
+    let date = query_date(query.date)?;
     let (page, page_size) = normalize_page(query.page, query.page_size)?;
     let offset = (page - 1).saturating_mul(page_size);
 
-    let total_items_u64 = state
-        .anomaly_repo
-        .count_anomalies(&date, query.player.as_deref())
-        .await
-        .map_err(|err| {
-            error!("failed to count anomalies: {}", err);
-            AppError::Internal(err.into())
-        })?;
-    let total_items = usize::try_from(total_items_u64).unwrap_or(usize::MAX);
+    let (items, total_items, degraded) = match fetch_anomaly_page(
+        state,
+        &date,
+        query.player.as_deref(),
+        offset,
+        page_size,
+        fields,
+    )
+    .await
+    {
+        Ok((items, total_items)) => (items, total_items, false),
+        Err(err) if state.config.degraded_cache_size > 0 => {
+            // Serve recent anomalies from memory rather than failing the whole page.
+            warn!("failed to fetch anomalies, serving cached rows: {}", err);
+            record_storage_failure(state, &err).await;
+            let cached = state
+                .recent_anomalies
+                .matching(&date, query.player.as_deref())
+                .await;
+            let total_items = cached.len();
+            let items = cached.into_iter().skip(offset).take(page_size).collect();
+            (items, total_items, true)
+        }
+        Err(err) => {
+            error!("failed to fetch anomalies: {}", err);
+            return Err(AppError::Unavailable(err));
+        }
+    };
     let total_pages = if total_items == 0 {
         1
     } else {
         (total_items + page_size - 1) / page_size
     };
 
-    let items = state
-        .anomaly_repo
-        .fetch_anomalies_page(&date, query.player.as_deref(), offset, page_size)
-        .await
-        .map_err(|err| {
-            error!("failed to fetch anomalies: {}", err);
-            AppError::Internal(err.into())
-        })?;
+    let (acked, seen) = if items.is_empty() || degraded {
+        (HashSet::new(), HashSet::new())
+    } else {
+        let acked = match state.anomaly_repo.fetch_acked_keys(&date).await {
+            Ok(keys) => keys.into_iter().collect(),
+            Err(err) => {
+                warn!("failed to fetch anomaly acks: {}", err);
+                HashSet::new()
+            }
+        };
+        (acked, fetch_seen_ids(state, &date).await)
+    };
+    let lang = query.lang.as_deref().unwrap_or(DEFAULT_RULE_LANG);
+    let display = TimeDisplay::from_config(&state.config);
+    let items = items
+        .into_iter()
+        .map(|row| anomaly_view(row, lang, &acked, &seen, &display))
+        .collect();
 
     Ok(PagedResult {
         items,
@@ -53,20 +90,488 @@ pub async fn list_anomalies(
         page_size,
         total_items,
         total_pages,
+        degraded,
     })
 }
 
-fn normalize_page(page: Option<usize>, page_size: Option<usize>) -> Result<(usize, usize), AppError> {
+/// Every anomaly of one day for a `format=csv|xlsx` download, read newest first a chunk at a
+/// time. Each chunk continues after the last row of the one before, so anomalies recorded while
+/// the download runs neither repeat nor push rows out of it. Unlike the listing it fails rather
+/// than serving cached rows while ClickHouse is down, so a download is never silently partial.
+pub struct AnomalyExport {
+    state: AppState,
+    date: String,
+    player: Option<String>,
+    lang: String,
+    acked: HashSet<AnomalyAckKey>,
+    seen: HashSet<String>,
+    display: TimeDisplay,
+    after: Option<AnomalyAckKey>,
+    done: bool,
+}
+
+impl AnomalyExport {
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// The next rows of the day; empty once every row was read.
+    pub async fn next_chunk(&mut self) -> Result<Vec<AnomalyView>, AppError> {
+        if self.done {
+            return Ok(Vec::new());
+        }
+        let rows = self
+            .state
+            .anomaly_repo
+            .fetch_anomalies_after(
+                &self.date,
+                self.player.as_deref(),
+                self.after.as_ref(),
+                EXPORT_CHUNK_SIZE,
+            )
+            .await
+            .map_err(|err| {
+                error!("failed to fetch anomalies for export: {}", err);
+                AppError::Unavailable(err)
+            })?;
+        self.done = rows.len() < EXPORT_CHUNK_SIZE;
+        self.after = rows.last().map(|row| AnomalyAckKey {
+            event_time: row.event_time,
+            player_uuid: row.player_uuid.clone(),
+            item_id: row.item_id.clone(),
+            rule_id: row.rule_id.clone(),
+        });
+        Ok(rows
+            .into_iter()
+            .map(|row| anomaly_view(row, &self.lang, &self.acked, &self.seen, &self.display))
+            .collect())
+    }
+}
+
+/// Starts a download of the queried day; acks and read receipts are read once, up front.
+pub async fn export_anomalies(
+    state: &AppState,
+    query: AnomalyQuery,
+) -> Result<AnomalyExport, AppError> {
+    let date = query_date(query.date)?;
+    let acked = match state.anomaly_repo.fetch_acked_keys(&date).await {
+        Ok(keys) => keys.into_iter().collect(),
+        Err(err) => {
+            warn!("failed to fetch anomaly acks: {}", err);
+            HashSet::new()
+        }
+    };
+    let seen = fetch_seen_ids(state, &date).await;
+    Ok(AnomalyExport {
+        state: state.clone(),
+        date,
+        player: query.player,
+        lang: query.lang.unwrap_or_else(|| DEFAULT_RULE_LANG.to_string()),
+        acked,
+        seen,
+        display: TimeDisplay::from_config(&state.config),
+        after: None,
+        done: false,
+    })
+}
+
+/// `date`, or today when absent.
+fn query_date(date: Option<String>) -> Result<String, AppError> {
+    let date = date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
+    if let Err(err) = backend_domain::parse_date(&date) {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid date: {}", err),
+        ));
+    }
+    Ok(date)
+}
+
+async fn fetch_anomaly_page(
+    state: &AppState,
+    date: &str,
+    player: Option<&str>,
+    offset: usize,
+    page_size: usize,
+    fields: &FieldSelection,
+) -> anyhow::Result<(Vec<AnomalyRow>, usize)> {
+    let total_items = state.anomaly_repo.count_anomalies(date, player).await?;
+    let items = state
+        .anomaly_repo
+        .fetch_anomalies_page(date, player, offset, page_size, fields)
+        .await?;
+    Ok((items, usize::try_from(total_items).unwrap_or(usize::MAX)))
+}
+
+/// Resolves a deep-link id back to its anomaly; `Ok(None)` when no row carries that id.
+pub async fn get_anomaly(
+    state: &AppState,
+    query: AnomalyLookupQuery,
+) -> Result<Option<AnomalyView>, AppError> {
+    let Some(event_ms) = anomaly_id_event_ms(&query.id) else {
+        return Err(AppError::BadRequest("invalid anomaly id".to_string()));
+    };
+    let rows = state
+        .anomaly_repo
+        .fetch_anomalies_at(event_ms)
+        .await
+        .map_err(|err| {
+            error!("failed to fetch anomaly: {}", err);
+            AppError::Internal(err.into())
+        })?;
+    let id = query.id.trim();
+    let Some(row) = rows.into_iter().find(|row| anomaly_id(row) == id) else {
+        return Ok(None);
+    };
+    let date = chrono::DateTime::from_timestamp_millis(event_ms)
+        .map(|time| time.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    let acked: HashSet<AnomalyAckKey> = match state.anomaly_repo.fetch_acked_keys(&date).await {
+        Ok(keys) => keys.into_iter().collect(),
+        Err(err) => {
+            warn!("failed to fetch anomaly acks: {}", err);
+            HashSet::new()
+        }
+    };
+    let seen = fetch_seen_ids(state, &date).await;
+    let lang = query.lang.as_deref().unwrap_or(DEFAULT_RULE_LANG);
+    let display = TimeDisplay::from_config(&state.config);
+    let mut view = anomaly_view(row, lang, &acked, &seen, &display);
+    view.explain = serde_json::from_str::<serde_json::Value>(&view.row.evidence_json)
+        .ok()
+        .and_then(|mut evidence| evidence.get_mut("explain").map(serde_json::Value::take))
+        .filter(|explain| !explain.is_null());
+    Ok(Some(view))
+}
+
+/// Ids with a read receipt on `date`; empty when they cannot be read, as receipts only help.
+async fn fetch_seen_ids(state: &AppState, date: &str) -> HashSet<String> {
+    match state.anomaly_repo.fetch_seen_ids(date).await {
+        Ok(ids) => ids.into_iter().collect(),
+        Err(err) => {
+            warn!("failed to fetch anomaly read receipts: {}", err);
+            HashSet::new()
+        }
+    }
+}
+
+fn anomaly_view(
+    row: AnomalyRow,
+    lang: &str,
+    acked: &HashSet<AnomalyAckKey>,
+    seen: &HashSet<String>,
+    display: &TimeDisplay,
+) -> AnomalyView {
+    let id = anomaly_id(&row);
+    let key = AnomalyAckKey {
+        event_time: row.event_time,
+        player_uuid: row.player_uuid.clone(),
+        item_id: row.item_id.clone(),
+        rule_id: row.rule_id.clone(),
+    };
+    AnomalyView {
+        seen: seen.contains(&id),
+        id,
+        display_time: display.format(row.event_time),
+        rule_description: rule_description(&row.rule_id, lang).to_string(),
+        acknowledged: acked.contains(&key),
+        row,
+        explain: None,
+    }
+}
+
+/// Next item of an anomaly stream subscription.
+pub enum AnomalyStreamItem {
+    Anomaly(Box<AnomalyView>),
+    /// The connection fell this many anomalies behind and skipped them.
+    Lagged(u64),
+}
+
+/// One `/v2/detect/anomalies/stream` connection: new anomalies matching its filters.
+pub struct AnomalySubscription {
+    receiver: broadcast::Receiver<AnomalyRow>,
+    risk_levels: Vec<String>,
+    server_ids: Vec<String>,
+    lang: String,
+    display: TimeDisplay,
+}
+
+impl AnomalySubscription {
+    /// Waits for the next matching anomaly; `None` once the backend shuts the feed down.
+    pub async fn next(&mut self) -> Option<AnomalyStreamItem> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(row) if self.matches(&row) => {
+                    let view = anomaly_view(
+                        row,
+                        &self.lang,
+                        &HashSet::new(),
+                        &HashSet::new(),
+                        &self.display,
+                    );
+                    return Some(AnomalyStreamItem::Anomaly(Box::new(view)));
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    return Some(AnomalyStreamItem::Lagged(skipped));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn matches(&self, row: &AnomalyRow) -> bool {
+        (self.risk_levels.is_empty() || self.risk_levels.contains(&row.risk_level))
+            && (self.server_ids.is_empty()
+                || self
+                    .server_ids
+                    .iter()
+                    .any(|server_id| server_id.eq_ignore_ascii_case(&row.server_id)))
+    }
+}
+
+/// Subscribes to anomalies produced from now on; nothing is replayed, so clients load the
+/// current page from the list endpoint first.
+pub fn subscribe_anomalies(
+    state: &AppState,
+    query: AnomalyStreamQuery,
+) -> Result<AnomalySubscription, AppError> {
+    let risk_levels = split_filter(query.risk)
+        .into_iter()
+        .map(|level| level.to_uppercase())
+        .collect::<Vec<_>>();
+    if let Some(level) = risk_levels.iter().find(|level| !is_risk_level(level)) {
+        return Err(AppError::BadRequest(format!(
+            "risk must be {}, got {}",
+            RISK_LEVEL_NAMES, level
+        )));
+    }
+    Ok(AnomalySubscription {
+        receiver: state.anomaly_stream.subscribe(),
+        risk_levels,
+        server_ids: split_filter(query.server_id),
+        lang: query.lang.unwrap_or_else(|| DEFAULT_RULE_LANG.to_string()),
+        display: TimeDisplay::from_config(&state.config),
+    })
+}
+
+fn split_filter(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+pub async fn anomaly_trend(
+    state: &AppState,
+    query: AnomalyTrendQuery,
+) -> Result<Vec<AnomalyDailySummaryRow>, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_TREND_DAYS);
+    if days == 0 || days > MAX_TREND_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_TREND_DAYS
+        )));
+    }
+    let to_date = Local::now().date_naive();
+    let from_date = to_date - chrono::Duration::days(i64::from(days - 1));
+    let server_id = normalize_filter(query.server_id);
+    let rule_id = normalize_filter(query.rule_id).map(|value| value.to_uppercase());
+
+    state
+        .anomaly_repo
+        .fetch_daily_summary(
+            &from_date.format("%Y-%m-%d").to_string(),
+            &to_date.format("%Y-%m-%d").to_string(),
+            server_id.as_deref(),
+            rule_id.as_deref(),
+        )
+        .await
+        .map_err(|err| {
+            error!("failed to fetch anomaly trend: {}", err);
+            AppError::Unavailable(err)
+        })
+}
+
+fn normalize_filter(value: Option<String>) -> Option<String> {
+    value
+        .map(|raw| raw.trim().to_string())
+        .filter(|raw| !raw.is_empty())
+}
+
+fn normalize_page(
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<(usize, usize), AppError> {
     let current_page = page.unwrap_or(DEFAULT_PAGE);
     if current_page == 0 {
-        return Err(AppError::BadRequest("page must be >= 1".to_string()));
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
+            "page must be >= 1".to_string(),
+        ));
     }
 
     let size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
     if !ALLOWED_PAGE_SIZES.contains(&size) {
-        return Err(AppError::BadRequest(
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
             "page_size must be one of: 25, 50, 100, 200".to_string(),
         ));
     }
     Ok((current_page, size))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::AnomalyStreamHub;
+    use backend_domain::testing::anomaly_row;
+    use backend_domain::{current_millis, millis_to_utc, AnomalyRepository};
+
+    fn row(server_id: &str, risk_level: &str) -> AnomalyRow {
+        AnomalyRow {
+            event_time: millis_to_utc(1_000),
+            server_id: server_id.to_string(),
+            risk_level: risk_level.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_subscription_applies_its_filters() {
+        let hub = AnomalyStreamHub::default();
+        let mut subscription = AnomalySubscription {
+            receiver: hub.subscribe(),
+            risk_levels: vec!["HIGH".to_string()],
+            server_ids: split_filter(Some(" Survival-01, ,creative".to_string())),
+            lang: DEFAULT_RULE_LANG.to_string(),
+            display: TimeDisplay::default(),
+        };
+        hub.publish(&[
+            row("survival-01", "LOW"),
+            row("lobby", "HIGH"),
+            row("survival-01", "HIGH"),
+        ]);
+        let Some(AnomalyStreamItem::Anomaly(view)) = subscription.next().await else {
+            panic!("expected an anomaly");
+        };
+        assert_eq!(view.row.server_id, "survival-01");
+        assert_eq!(view.row.risk_level, "HIGH");
+        assert!(!view.display_time.is_empty());
+    }
+
+    #[tokio::test]
+    async fn export_chunks_neither_repeat_nor_skip_rows_recorded_meanwhile() {
+        let app = crate::testing::InMemoryApp::new(backend_domain::testing::runtime_config());
+        // Two anomalies share each event time, so the cursor has to break ties.
+        let rows = |from: usize, count: usize| -> Vec<AnomalyRow> {
+            (from..from + count)
+                .map(|i| AnomalyRow {
+                    event_time: millis_to_utc(1_000 + (i / 2) as i64),
+                    player_uuid: format!("uuid-{}", i),
+                    ..row("survival-01", "HIGH")
+                })
+                .collect()
+        };
+        app.anomalies
+            .insert_anomalies(&rows(0, EXPORT_CHUNK_SIZE + 500))
+            .await
+            .unwrap();
+        let query = AnomalyQuery {
+            date: Some("1970-01-01".to_string()),
+            player: None,
+            page: None,
+            page_size: None,
+            lang: None,
+        };
+        let mut export = export_anomalies(&app.state, query).await.unwrap();
+        let mut ids = Vec::new();
+        let first = export.next_chunk().await.unwrap();
+        assert_eq!(first.len(), EXPORT_CHUNK_SIZE);
+        ids.extend(first.into_iter().map(|view| view.row.player_uuid));
+        // Newer anomalies land at the top; an offset would now repeat the last 20 rows.
+        app.anomalies
+            .insert_anomalies(&rows(EXPORT_CHUNK_SIZE + 500, 20))
+            .await
+            .unwrap();
+        loop {
+            let chunk = export.next_chunk().await.unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            ids.extend(chunk.into_iter().map(|view| view.row.player_uuid));
+        }
+        assert_eq!(ids.len(), EXPORT_CHUNK_SIZE + 500);
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), EXPORT_CHUNK_SIZE + 500);
+    }
+
+    #[tokio::test]
+    async fn trend_follows_a_day_rolled_up_again() {
+        let app = crate::testing::InMemoryApp::new(backend_domain::testing::runtime_config());
+        // A day back, so the day is in range whatever the local time zone.
+        let event_time = millis_to_utc(current_millis() - 86_400_000);
+        let date = event_time.date().to_string();
+        let anomaly = |server_id: &str, rule_id: &str| AnomalyRow {
+            event_time,
+            server_id: server_id.to_string(),
+            rule_id: rule_id.to_string(),
+            ..anomaly_row()
+        };
+        app.anomalies
+            .insert_anomalies(&[
+                anomaly("server-01", "R4"),
+                anomaly("server-01", "R4"),
+                anomaly("server-02", "R1"),
+            ])
+            .await
+            .expect("insert");
+        app.anomalies
+            .rollup_daily_summary(&date)
+            .await
+            .expect("rollup");
+        let trend = |server_id: Option<&str>| AnomalyTrendQuery {
+            days: Some(3),
+            server_id: server_id.map(str::to_string),
+            rule_id: None,
+        };
+        let counts = |rows: Vec<AnomalyDailySummaryRow>| -> Vec<(String, String, u64)> {
+            rows.into_iter()
+                .map(|row| (row.server_id, row.rule_id, row.count))
+                .collect()
+        };
+
+        let rows = anomaly_trend(&app.state, trend(None)).await.expect("trend");
+        assert!(rows.iter().all(|row| row.date == date));
+        assert_eq!(
+            counts(rows),
+            vec![
+                ("server-01".to_string(), "R4".to_string(), 2),
+                ("server-02".to_string(), "R1".to_string(), 1),
+            ]
+        );
+
+        // The day is rebuilt without server-02's anomaly; its old row must not linger.
+        app.anomalies.drop_date(&date);
+        app.anomalies
+            .insert_anomalies(&[anomaly("server-01", "R4")])
+            .await
+            .expect("insert");
+        app.anomalies
+            .rollup_daily_summary(&date)
+            .await
+            .expect("rollup");
+        let rows = anomaly_trend(&app.state, trend(None)).await.expect("trend");
+        assert_eq!(
+            counts(rows),
+            vec![("server-01".to_string(), "R4".to_string(), 1)]
+        );
+        let rows = anomaly_trend(&app.state, trend(Some("server-02")))
+            .await
+            .expect("trend");
+        assert!(rows.is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+use crate::AppState;
+use backend_domain::{current_millis, ApiTokenView};
+
+/// Issued tokens by name, revoked and expired ones included; digests are left out.
+pub async fn list_api_tokens(state: &AppState) -> Vec<ApiTokenView> {
+    let now = current_millis();
+    state
+        .api_tokens
+        .snapshot()
+        .await
+        .iter()
+        .map(|token| ApiTokenView::new(token, now))
+        .collect()
+}
@@ -0,0 +1,6 @@
+use crate::AppState;
+use backend_domain::{current_millis, PlayerBan};
+
+pub async fn list_bans(state: &AppState) -> Vec<PlayerBan> {
+    state.bans.active(current_millis()).await
+}
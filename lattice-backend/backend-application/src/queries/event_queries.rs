@@ -0,0 +1,158 @@
+use tracing::error;
+
+use crate::AppState;
+use crate::{AppError, ErrorCode};
+use backend_domain::{FieldSelection, ItemEventFilter, ItemEventQuery, ItemEventRow, PagedResult};
+
+const DEFAULT_PAGE: usize = 1;
+const DEFAULT_PAGE_SIZE: usize = 50;
+const ALLOWED_PAGE_SIZES: [usize; 4] = [25, 50, 100, 200];
+/// Deepest row a query may page to; anything further needs narrower filters.
+pub const MAX_EVENT_QUERY_ROWS: usize = 10_000;
+
+/// One page of raw `item_events` rows, newest first; `fields` skips unrequested columns.
+pub async fn query_item_events(
+    state: &AppState,
+    query: ItemEventQuery,
+    fields: &FieldSelection,
+) -> Result<PagedResult<ItemEventRow>, AppError> {
+    let (filter, page, page_size) = normalize_event_query(query)?;
+    let total = state
+        .event_repo
+        .count_item_events(&filter)
+        .await
+        .map_err(|err| {
+            error!("failed to count item events: {}", err);
+            AppError::Internal(err.into())
+        })?;
+    let total_items = usize::try_from(total).unwrap_or(usize::MAX);
+    let total_pages = total_items
+        .min(MAX_EVENT_QUERY_ROWS)
+        .div_ceil(page_size)
+        .max(1);
+    let offset = (page - 1) * page_size;
+    let items = if offset >= total_items {
+        Vec::new()
+    } else {
+        state
+            .event_repo
+            .fetch_item_events_page(&filter, offset, page_size, fields)
+            .await
+            .map_err(|err| {
+                error!("failed to fetch item events: {}", err);
+                AppError::Internal(err.into())
+            })?
+    };
+    Ok(PagedResult {
+        items,
+        page,
+        page_size,
+        total_items,
+        total_pages,
+        degraded: false,
+    })
+}
+
+fn normalize_event_query(
+    query: ItemEventQuery,
+) -> Result<(ItemEventFilter, usize, usize), AppError> {
+    let clean = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let Some(date) = clean(query.date) else {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            "date is required".to_string(),
+        ));
+    };
+    if let Err(err) = backend_domain::parse_date(&date) {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid date: {}", err),
+        ));
+    }
+    let item_id = clean(query.item).map(|value| value.to_lowercase());
+    if item_id
+        .as_deref()
+        .is_some_and(|item_id| !item_id.contains(':'))
+    {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidItemId,
+            "item must be namespace:path".to_string(),
+        ));
+    }
+    let filter = ItemEventFilter {
+        date,
+        player: clean(query.player),
+        item_id,
+        storage_id: clean(query.storage),
+        server_id: clean(query.server_id),
+        event_type: clean(query.event_type).map(|value| value.to_uppercase()),
+    };
+    if filter.player.is_none() && filter.item_id.is_none() && filter.storage_id.is_none() {
+        return Err(AppError::BadRequest(
+            "at least one of player, item, storage is required".to_string(),
+        ));
+    }
+
+    let page = query.page.unwrap_or(DEFAULT_PAGE);
+    if page == 0 {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
+            "page must be >= 1".to_string(),
+        ));
+    }
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !ALLOWED_PAGE_SIZES.contains(&page_size) {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
+            "page_size must be one of: 25, 50, 100, 200".to_string(),
+        ));
+    }
+    if page.saturating_mul(page_size) > MAX_EVENT_QUERY_ROWS {
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
+            format!(
+                "only the first {} rows can be paged; narrow the filters",
+                MAX_EVENT_QUERY_ROWS
+            ),
+        ));
+    }
+    Ok((filter, page, page_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(player: Option<&str>, page: Option<usize>) -> ItemEventQuery {
+        ItemEventQuery {
+            date: Some("2026-03-01".to_string()),
+            player: player.map(ToString::to_string),
+            item: Some(" ".to_string()),
+            storage: None,
+            server_id: None,
+            event_type: Some("acquire".to_string()),
+            page,
+            page_size: Some(200),
+        }
+    }
+
+    #[test]
+    fn event_query_needs_a_narrowing_filter_and_a_shallow_page() {
+        assert!(normalize_event_query(query(None, None)).is_err());
+        let (filter, page, page_size) = normalize_event_query(query(Some("Steve"), None)).unwrap();
+        assert_eq!(filter.player.as_deref(), Some("Steve"));
+        assert!(filter.item_id.is_none());
+        assert_eq!(filter.event_type.as_deref(), Some("ACQUIRE"));
+        assert_eq!((page, page_size), (1, 200));
+        assert!(normalize_event_query(query(Some("Steve"), Some(50))).is_ok());
+        assert!(normalize_event_query(query(Some("Steve"), Some(51))).is_err());
+
+        let mut undated = query(Some("Steve"), None);
+        undated.date = None;
+        assert!(normalize_event_query(undated).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use tracing::error;
+
+use crate::ops::{ModVersionCheck, ModVersionGate};
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    current_millis, FingerprintStatsQuery, FingerprintStatsReport, IngestStaleReport, ModVersion,
+    ModVersionCount, ServerFingerprintStats, ServerStatusReport,
+};
+
+const DEFAULT_FINGERPRINT_HOURS: u32 = 24;
+/// Events are kept for 7 days.
+const MAX_FINGERPRINT_HOURS: u32 = 168;
+const DEFAULT_FINGERPRINT_TOP: usize = 10;
+const MAX_FINGERPRINT_TOP: usize = 100;
+
+pub async fn get_stale_servers(state: &AppState) -> IngestStaleReport {
+    let stale_after_minutes = state.config.ingest_stale_after_minutes;
+    let servers = if stale_after_minutes == 0 {
+        Vec::new()
+    } else {
+        state
+            .ingest_tracker
+            .stale_servers(current_millis(), (stale_after_minutes * 60_000) as i64)
+            .await
+    };
+    IngestStaleReport {
+        stale_after_minutes,
+        servers,
+        auth_failures: state.ingest_tracker.auth_failures().await,
+        identity_mismatches: state.ingest_tracker.identity_mismatches().await,
+    }
+}
+
+pub async fn list_server_status(state: &AppState) -> ServerStatusReport {
+    let min_mod_version = state.config.min_mod_version.clone();
+    let mut servers = state
+        .heartbeats
+        .list_status(
+            current_millis(),
+            (state.config.heartbeat_interval_seconds * 1000) as i64,
+            state.config.heartbeat_missed_threshold,
+        )
+        .await;
+
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for server in &mut servers {
+        let Some(version) = server.mod_version.clone() else {
+            continue;
+        };
+        server.mod_version_outdated = matches!(
+            ModVersionGate::check(min_mod_version.as_deref(), Some(&version)),
+            ModVersionCheck::Outdated { .. }
+        );
+        versions
+            .entry(version)
+            .or_default()
+            .push(server.server_id.clone());
+    }
+    let latest_mod_version = versions
+        .keys()
+        .filter_map(|version| ModVersion::parse(version).map(|parsed| (parsed, version)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, version)| version.clone());
+
+    ServerStatusReport {
+        min_mod_version,
+        latest_mod_version,
+        version_skew: versions.len() > 1,
+        versions: versions
+            .into_iter()
+            .map(|(mod_version, servers)| ModVersionCount {
+                mod_version,
+                servers,
+            })
+            .collect(),
+        servers,
+    }
+}
+
+/// How often `TRANSFER` / `ACQUIRE` events arrive without an `item_fingerprint`, and which
+/// fingerprints several players or items shared, per server over the last `hours`.
+pub async fn fingerprint_stats(
+    state: &AppState,
+    query: FingerprintStatsQuery,
+) -> Result<FingerprintStatsReport, AppError> {
+    let hours = query.hours.unwrap_or(DEFAULT_FINGERPRINT_HOURS);
+    if hours == 0 || hours > MAX_FINGERPRINT_HOURS {
+        return Err(AppError::BadRequest(format!(
+            "hours must be between 1 and {}",
+            MAX_FINGERPRINT_HOURS
+        )));
+    }
+    let top = query.top.unwrap_or(DEFAULT_FINGERPRINT_TOP);
+    if top > MAX_FINGERPRINT_TOP {
+        return Err(AppError::BadRequest(format!(
+            "top must be at most {}",
+            MAX_FINGERPRINT_TOP
+        )));
+    }
+    let since_ms = current_millis() - i64::from(hours) * 3_600_000;
+    let internal = |what: &str, err: anyhow::Error| {
+        error!("failed to fetch fingerprint {}: {}", what, err);
+        AppError::Internal(err)
+    };
+    let usage = state
+        .event_repo
+        .fetch_fingerprint_usage(since_ms)
+        .await
+        .map_err(|err| internal("usage", err))?;
+    let mut collisions = if top == 0 {
+        Vec::new()
+    } else {
+        state
+            .event_repo
+            .fetch_fingerprint_collisions(since_ms, top)
+            .await
+            .map_err(|err| internal("collisions", err))?
+    };
+
+    let servers = usage
+        .into_iter()
+        .map(|usage| {
+            let (top_collisions, rest) = collisions
+                .drain(..)
+                .partition(|collision| collision.server_id == usage.server_id);
+            collisions = rest;
+            ServerFingerprintStats {
+                missing_ratio: if usage.events == 0 {
+                    0.0
+                } else {
+                    usage.missing as f64 / usage.events as f64
+                },
+                server_id: usage.server_id,
+                events: usage.events,
+                missing: usage.missing,
+                top_collisions,
+            }
+        })
+        .collect();
+    Ok(FingerprintStatsReport {
+        hours,
+        since_ms,
+        strict_transfer_fingerprint: state.config.strict_transfer_fingerprint,
+        servers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::Scenario;
+    use backend_domain::{EventRepository, IngestEvent};
+
+    #[tokio::test]
+    async fn fingerprint_stats_report_missing_ratio_and_shared_fingerprints() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let now_ms = current_millis();
+        let tag = |fingerprint: &'static str| {
+            move |event: &mut IngestEvent| event.item_fingerprint = Some(fingerprint.to_string())
+        };
+        let mut events: Vec<IngestEvent> = Scenario::new()
+            .transfers("minecraft:diamond", 1)
+            .acquires_without_origin("minecraft:diamond", 1)
+            .player("alex")
+            .acquires_without_origin("minecraft:diamond", 1)
+            .acquires("minecraft:elytra", 1, "loot")
+            .with(tag("fp-elytra"))
+            .picks_up("minecraft:dirt", 3)
+            .events()
+            .cloned()
+            .collect();
+        for event in &mut events {
+            event.event_time = now_ms;
+        }
+        app.events.insert_events(&events).await.unwrap();
+
+        let report = fingerprint_stats(&app.state, FingerprintStatsQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(report.hours, 24);
+        assert_eq!(report.servers.len(), 1);
+        let server = &report.servers[0];
+        assert_eq!((server.events, server.missing), (5, 4));
+        assert_eq!(server.missing_ratio, 0.8);
+        let collisions: Vec<(&str, bool, u64)> = server
+            .top_collisions
+            .iter()
+            .map(|collision| {
+                (
+                    collision.fingerprint.as_str(),
+                    collision.synthesized,
+                    collision.players,
+                )
+            })
+            .collect();
+        assert_eq!(collisions, [("minecraft:diamond:*", true, 2)]);
+
+        let query = FingerprintStatsQuery {
+            hours: Some(169),
+            ..Default::default()
+        };
+        assert!(fingerprint_stats(&app.state, query).await.is_err());
+    }
+}
@@ -1,6 +1,6 @@
+use crate::AppError;
 use crate::AppState;
 use backend_domain::{ItemRegistryEntry, ItemRegistryQuery};
-use crate::AppError;
 
 pub async fn list_item_registry(
     state: &AppState,
@@ -8,7 +8,10 @@ pub async fn list_item_registry(
 ) -> Result<Vec<ItemRegistryEntry>, AppError> {
     let limit = query.limit.unwrap_or(50).clamp(1, 200);
     let query_text = query.query.unwrap_or_default().trim().to_lowercase();
-    let lang = query.lang.unwrap_or_else(|| "zh_cn".to_string()).to_lowercase();
+    let lang = query
+        .lang
+        .unwrap_or_else(|| "zh_cn".to_string())
+        .to_lowercase();
     let items = state.item_registry.read().await;
     let mut results = Vec::new();
     for entry in items.iter() {
@@ -0,0 +1,210 @@
+use std::collections::BTreeSet;
+
+use tracing::error;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{ItemEventRow, ItemTrace, ItemTraceHop, ItemTraceNode, ItemTraceQuery};
+
+const DEFAULT_TRACE_HOPS: usize = 500;
+const MAX_TRACE_HOPS: usize = 5_000;
+
+/// Rebuilds the `TRANSFER` / `ACQUIRE` chain sharing a trace id or item fingerprint. A lone
+/// `trace_id` whose events all carry one fingerprint is widened to that fingerprint, so hops
+/// recorded under later traces stay in the chain.
+pub async fn item_trace(state: &AppState, query: ItemTraceQuery) -> Result<ItemTrace, AppError> {
+    let trace_id = query.trace_id.as_deref().map(str::trim).unwrap_or_default();
+    let mut item_fingerprint = query
+        .item_fingerprint
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string();
+    if trace_id.is_empty() && item_fingerprint.is_empty() {
+        return Err(AppError::BadRequest(
+            "trace_id or item_fingerprint is required".to_string(),
+        ));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_TRACE_HOPS);
+    if limit == 0 || limit > MAX_TRACE_HOPS {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_TRACE_HOPS
+        )));
+    }
+    let fetch = |fingerprint: String| async move {
+        state
+            .event_repo
+            .fetch_events_by_trace(trace_id, &fingerprint, limit)
+            .await
+            .map_err(|err| {
+                error!("failed to fetch item trace {}: {}", trace_id, err);
+                AppError::Internal(err)
+            })
+    };
+
+    let mut rows = fetch(item_fingerprint.clone()).await?;
+    if item_fingerprint.is_empty() {
+        let fingerprints: BTreeSet<&str> = rows
+            .iter()
+            .map(|row| row.item_fingerprint.as_str())
+            .filter(|fingerprint| !fingerprint.is_empty())
+            .collect();
+        if fingerprints.len() == 1 {
+            item_fingerprint = fingerprints.into_iter().next().unwrap().to_string();
+            rows = fetch(item_fingerprint.clone()).await?;
+        }
+    }
+
+    let truncated = rows.len() >= limit;
+    let (nodes, hops) = link_hops(&rows);
+    Ok(ItemTrace {
+        trace_id: (!trace_id.is_empty()).then(|| trace_id.to_string()),
+        item_fingerprint: (!item_fingerprint.is_empty()).then_some(item_fingerprint),
+        nodes,
+        hops,
+        truncated,
+    })
+}
+
+/// Turns time-ordered rows into edges between holders, in order of first appearance.
+fn link_hops(rows: &[ItemEventRow]) -> (Vec<ItemTraceNode>, Vec<ItemTraceHop>) {
+    let mut nodes: Vec<ItemTraceNode> = Vec::new();
+    let mut hops: Vec<ItemTraceHop> = Vec::new();
+    for row in rows {
+        let from = source_node(row);
+        let to = ItemTraceNode {
+            id: format!("player:{}", row.player_uuid),
+            kind: "player".to_string(),
+            label: if row.player_name.is_empty() {
+                row.player_uuid.clone()
+            } else {
+                row.player_name.clone()
+            },
+        };
+        let previous = hops
+            .iter()
+            .rev()
+            .find(|hop| hop.to == from.id)
+            .or_else(|| {
+                (row.event_type == "ACQUIRE")
+                    .then(|| {
+                        hops.iter().rev().find(|hop| {
+                            hop.event_type == "TRANSFER"
+                                && hop.to == to.id
+                                && hop.count == row.count
+                        })
+                    })
+                    .flatten()
+            })
+            .map(|hop| hop.event_id.clone());
+        hops.push(ItemTraceHop {
+            event_id: row.event_id.clone(),
+            event_time_ms: (row.event_time.unix_timestamp_nanos() / 1_000_000) as i64,
+            event_type: row.event_type.clone(),
+            server_id: row.server_id.clone(),
+            item_id: row.item_id.clone(),
+            count: row.count,
+            from: from.id.clone(),
+            to: to.id.clone(),
+            previous_event_id: previous,
+        });
+        for node in [from, to] {
+            if !nodes.iter().any(|known| known.id == node.id) {
+                nodes.push(node);
+            }
+        }
+    }
+    (nodes, hops)
+}
+
+/// The storage a `TRANSFER` left, or the origin an `ACQUIRE` reports; `origin:unknown` when
+/// the event names neither.
+fn source_node(row: &ItemEventRow) -> ItemTraceNode {
+    if row.event_type == "TRANSFER" && !row.storage_id.is_empty() {
+        return ItemTraceNode {
+            id: format!("storage:{}:{}", row.storage_mod, row.storage_id),
+            kind: "storage".to_string(),
+            label: row.storage_id.clone(),
+        };
+    }
+    if row.origin_type.is_empty() && row.origin_id.is_empty() {
+        return ItemTraceNode {
+            id: "origin:unknown".to_string(),
+            kind: "origin".to_string(),
+            label: "unknown".to_string(),
+        };
+    }
+    ItemTraceNode {
+        id: format!("origin:{}:{}", row.origin_type, row.origin_id),
+        kind: "origin".to_string(),
+        label: row.origin_type.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::Scenario;
+    use backend_domain::{EventRepository, IngestEvent};
+
+    #[tokio::test]
+    async fn trace_links_transfers_and_acquisitions_of_one_item() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let tag = |trace: &'static str| {
+            move |event: &mut IngestEvent| {
+                event.trace_id = Some(trace.to_string());
+                event.item_fingerprint = Some("fp-sword".to_string());
+            }
+        };
+        let events: Vec<IngestEvent> = Scenario::new()
+            .acquires("minecraft:diamond_sword", 1, "craft")
+            .with(tag("trace-a"))
+            .at_secs(5)
+            .transfers("minecraft:diamond_sword", 1)
+            .with(tag("trace-b"))
+            .acquires_without_origin("minecraft:diamond_sword", 1)
+            .with(tag("trace-b"))
+            .picks_up("minecraft:dirt", 3)
+            .events()
+            .cloned()
+            .collect();
+        app.events.insert_events(&events).await.unwrap();
+
+        let query = ItemTraceQuery {
+            trace_id: Some(" trace-b ".to_string()),
+            ..Default::default()
+        };
+        let trace = item_trace(&app.state, query).await.unwrap();
+        assert_eq!(trace.item_fingerprint.as_deref(), Some("fp-sword"));
+        assert!(!trace.truncated);
+        let hops: Vec<(&str, &str, &str)> = trace
+            .hops
+            .iter()
+            .map(|hop| (hop.event_type.as_str(), hop.from.as_str(), hop.to.as_str()))
+            .collect();
+        assert_eq!(
+            hops,
+            [
+                ("ACQUIRE", "origin:craft:origin-1", "player:uuid-steve"),
+                (
+                    "TRANSFER",
+                    "storage:minecraft:minecraft:chest@0,64,0",
+                    "player:uuid-steve"
+                ),
+                ("ACQUIRE", "origin:unknown", "player:uuid-steve"),
+            ]
+        );
+        assert_eq!(trace.hops[0].previous_event_id, None);
+        assert_eq!(
+            trace.hops[2].previous_event_id.as_deref(),
+            Some(trace.hops[1].event_id.as_str())
+        );
+        assert_eq!(trace.nodes.len(), 4);
+
+        assert!(item_trace(&app.state, ItemTraceQuery::default())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::commands::ml_export_commands::ML_EXPORT_JOB;
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{Job, MlExportResult};
+
+pub async fn list_jobs(state: &AppState) -> Vec<Job> {
+    state.jobs.list().await
+}
+
+pub async fn get_job(state: &AppState, id: &str) -> Option<Job> {
+    state.jobs.get(id.trim()).await
+}
+
+/// File name and contents of a finished export job; None for an unknown, unfinished or failed
+/// job and for an export file that has since been removed.
+pub async fn read_job_export(
+    state: &AppState,
+    id: &str,
+) -> Result<Option<(String, String)>, AppError> {
+    let Some(job) = get_job(state, id).await else {
+        return Ok(None);
+    };
+    if job.kind != ML_EXPORT_JOB || job.state != "SUCCEEDED" {
+        return Ok(None);
+    }
+    let Some(result) = job
+        .result
+        .and_then(|result| serde_json::from_value::<MlExportResult>(result).ok())
+    else {
+        return Ok(None);
+    };
+    let contents = state
+        .config_repo
+        .read_report_file(&state.config.report_dir, &result.file)
+        .await?;
+    let name = result
+        .file
+        .rsplit('/')
+        .next()
+        .unwrap_or(&result.file)
+        .to_string();
+    Ok(contents.map(|contents| (name, contents)))
+}
@@ -1,6 +1,6 @@
-use crate::AppState;
-use backend_domain::KeyItemRuleApi;
 use crate::AppError;
+use crate::AppState;
+use backend_domain::{rule_presets, CompositeRule, DetectionRule, KeyItemRuleApi, RulePreset};
 
 pub async fn list_key_items(state: &AppState) -> Result<Vec<KeyItemRuleApi>, AppError> {
     let rules = state.key_rules.read().await;
@@ -8,3 +8,17 @@ pub async fn list_key_items(state: &AppState) -> Result<Vec<KeyItemRuleApi>, App
     list.sort_by(|a, b| a.item_id.cmp(&b.item_id));
     Ok(list)
 }
+
+pub fn list_rule_presets() -> Vec<RulePreset> {
+    rule_presets()
+}
+
+/// The user detection rules loaded at startup, disabled ones included.
+pub async fn list_detection_rules(state: &AppState) -> Vec<DetectionRule> {
+    state.detection_rules.read().await.clone()
+}
+
+/// The composite rules loaded at startup, disabled ones included.
+pub async fn list_composite_rules(state: &AppState) -> Vec<CompositeRule> {
+    state.composite_rules.read().await.clone()
+}
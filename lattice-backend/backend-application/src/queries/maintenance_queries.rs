@@ -0,0 +1,12 @@
+use crate::AppState;
+use backend_domain::MaintenanceStatus;
+
+pub async fn get_maintenance_status(state: &AppState) -> MaintenanceStatus {
+    MaintenanceStatus {
+        enabled: state.config.maintenance_enabled,
+        maintenance_hour: state.config.maintenance_hour,
+        optimize_min_rows: state.config.maintenance_optimize_min_rows,
+        storage_alert_threshold_mb: state.config.storage_alert_threshold_mb,
+        last_run: state.maintenance_status.read().await.clone(),
+    }
+}
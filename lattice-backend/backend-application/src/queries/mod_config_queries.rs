@@ -7,7 +7,9 @@ pub async fn get_mod_config(
 ) -> Result<Option<ModConfigEnvelope>, AppError> {
     let server_id = normalize_server_id(server_id);
     if server_id.is_empty() {
-        return Err(AppError::BadRequest("server_id must not be empty".to_string()));
+        return Err(AppError::BadRequest(
+            "server_id must not be empty".to_string(),
+        ));
     }
     let cached = {
         let cache = state.mod_configs.read().await;
@@ -50,7 +52,9 @@ pub async fn get_mod_config_ack(
 ) -> Result<Option<ModConfigAck>, AppError> {
     let server_id = normalize_server_id(server_id);
     if server_id.is_empty() {
-        return Err(AppError::BadRequest("server_id must not be empty".to_string()));
+        return Err(AppError::BadRequest(
+            "server_id must not be empty".to_string(),
+        ));
     }
     let cached = {
         let cache = state.mod_config_acks.read().await;
@@ -0,0 +1,6 @@
+use crate::AppState;
+use backend_domain::OriginWhitelist;
+
+pub async fn get_origin_whitelist(state: &AppState) -> OriginWhitelist {
+    state.origin_whitelist.snapshot().await
+}
@@ -0,0 +1,85 @@
+use chrono::Local;
+use tracing::warn;
+
+use crate::queries::{ingest_queries, task_progress_queries};
+use crate::AppState;
+use backend_domain::{current_millis, OpsOverview, ServerOverview};
+
+/// Everything the desktop home screen shows. Each part degrades on its own: a ClickHouse or
+/// report directory failure leaves that part empty instead of failing the overview.
+pub async fn ops_overview(state: &AppState) -> OpsOverview {
+    let now_ms = current_millis();
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let anomalies = match state.anomaly_repo.fetch_summary(&date).await {
+        Ok(summary) => Some(summary),
+        Err(err) => {
+            warn!("overview: failed to fetch anomaly summary: {}", err);
+            None
+        }
+    };
+    let unseen_anomalies = match &anomalies {
+        Some(summary) => match state.anomaly_repo.fetch_seen_ids(&date).await {
+            Ok(seen) => Some(summary.total().saturating_sub(seen.len() as u64)),
+            Err(err) => {
+                warn!("overview: failed to fetch anomaly read receipts: {}", err);
+                None
+            }
+        },
+        None => None,
+    };
+    let last_report = match state
+        .config_repo
+        .list_reports(&state.config.report_dir)
+        .await
+    {
+        Ok(reports) => reports.into_iter().next(),
+        Err(err) => {
+            warn!("overview: failed to list reports: {}", err);
+            None
+        }
+    };
+
+    let status = ingest_queries::list_server_status(state).await;
+    let stale = ingest_queries::get_stale_servers(state).await;
+    let (online, offline): (Vec<_>, Vec<_>) =
+        status.servers.iter().partition(|server| server.online);
+    let servers = ServerOverview {
+        total: status.servers.len(),
+        online: online
+            .iter()
+            .map(|server| server.server_id.clone())
+            .collect(),
+        offline: offline
+            .iter()
+            .map(|server| server.server_id.clone())
+            .collect(),
+        stale_ingest: stale
+            .servers
+            .into_iter()
+            .map(|server| server.server_id)
+            .collect(),
+        outdated_mod: status
+            .servers
+            .iter()
+            .filter(|server| server.mod_version_outdated)
+            .map(|server| server.server_id.clone())
+            .collect(),
+    };
+
+    OpsOverview {
+        date,
+        generated_at_ms: now_ms,
+        anomalies,
+        unseen_anomalies,
+        ingest: state.metrics.ingest_rate(now_ms),
+        servers,
+        last_report,
+        last_alert_delivery: state.alert_service.last_alert_delivery().await,
+        task_progress: task_progress_queries::mark_stalled(
+            state.task_status.read().await.clone(),
+            now_ms,
+            state.config.task_stale_after_seconds,
+        ),
+        degraded: state.degraded.status().await.is_some(),
+    }
+}
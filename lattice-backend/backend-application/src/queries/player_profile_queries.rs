@@ -0,0 +1,158 @@
+use tracing::error;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, PlayerProfile, PlayerProfileQuery};
+
+const DEFAULT_PROFILE_DAYS: u32 = 30;
+const MAX_PROFILE_DAYS: u32 = 365;
+const DEFAULT_RECENT_EVENTS: usize = 20;
+const MAX_RECENT_EVENTS: usize = 200;
+/// Items listed under `acquired_items`; the rest of a busy player's inventory is left out.
+const PROFILE_ITEM_LIMIT: usize = 50;
+
+/// A player's first/last seen, anomalies per rule and acquisitions over the last `days`, plus
+/// their latest events. `None` when the player has neither events nor anomalies.
+pub async fn player_profile(
+    state: &AppState,
+    player_uuid: &str,
+    query: PlayerProfileQuery,
+) -> Result<Option<PlayerProfile>, AppError> {
+    let player_uuid = player_uuid.trim();
+    if player_uuid.is_empty() {
+        return Err(AppError::BadRequest("player uuid is required".to_string()));
+    }
+    let days = query.days.unwrap_or(DEFAULT_PROFILE_DAYS);
+    if days == 0 || days > MAX_PROFILE_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_PROFILE_DAYS
+        )));
+    }
+    let recent = query.recent.unwrap_or(DEFAULT_RECENT_EVENTS);
+    if recent > MAX_RECENT_EVENTS {
+        return Err(AppError::BadRequest(format!(
+            "recent must be at most {}",
+            MAX_RECENT_EVENTS
+        )));
+    }
+    let since_ms = current_millis() - i64::from(days) * 86_400_000;
+    let internal = |what: &str, err: anyhow::Error| {
+        error!(
+            "failed to fetch player {} for {}: {}",
+            what, player_uuid, err
+        );
+        AppError::Internal(err)
+    };
+
+    let span = state
+        .event_repo
+        .fetch_player_event_span(player_uuid)
+        .await
+        .map_err(|err| internal("event span", err))?;
+    let anomalies_by_rule = state
+        .anomaly_repo
+        .fetch_player_rule_counts(player_uuid, since_ms)
+        .await
+        .map_err(|err| internal("anomaly counts", err))?;
+    if span.is_none() && anomalies_by_rule.is_empty() {
+        return Ok(None);
+    }
+    let acquired_items = state
+        .event_repo
+        .fetch_player_acquired_items(player_uuid, since_ms, PROFILE_ITEM_LIMIT)
+        .await
+        .map_err(|err| internal("acquisitions", err))?;
+    let recent_events = if recent == 0 {
+        Vec::new()
+    } else {
+        state
+            .event_repo
+            .fetch_player_recent_events(player_uuid, recent)
+            .await
+            .map_err(|err| internal("recent events", err))?
+    };
+    Ok(Some(PlayerProfile {
+        player_uuid: player_uuid.to_string(),
+        player_name: span
+            .as_ref()
+            .map(|span| span.player_name.clone())
+            .unwrap_or_default(),
+        first_seen_ms: span.as_ref().map(|span| span.first_seen_ms),
+        last_seen_ms: span.as_ref().map(|span| span.last_seen_ms),
+        total_events: span.as_ref().map_or(0, |span| span.events),
+        days,
+        anomalies: anomalies_by_rule.iter().map(|rule| rule.anomalies).sum(),
+        anomalies_by_rule,
+        acquired_items,
+        recent_events,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::testing::InMemoryApp;
+    use backend_domain::testing::{Scenario, SCENARIO_START_MS};
+    use backend_domain::{Analyzer, AnomalyRepository, EventRepository, IngestEvent};
+
+    #[tokio::test]
+    async fn profile_aggregates_events_and_anomalies_of_one_player() {
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let shift = current_millis() - 60_000 - SCENARIO_START_MS;
+        let events: Vec<IngestEvent> = Scenario::new()
+            .acquires_without_origin("minecraft:diamond", 5)
+            .at_secs(10)
+            .acquires("minecraft:diamond", 3, "craft")
+            .acquires("minecraft:iron_ingot", 9, "craft")
+            .player("alex")
+            .acquires_without_origin("minecraft:diamond", 64)
+            .events()
+            .map(|event| IngestEvent {
+                event_time: event.event_time + shift,
+                ..event.clone()
+            })
+            .collect();
+        app.events.insert_events(&events).await.unwrap();
+        let anomalies =
+            Analyzer::default().analyze_batch(&events, &HashMap::new(), 2_000, 600_000, 0, 0);
+        app.anomalies.insert_anomalies(&anomalies).await.unwrap();
+
+        let profile = player_profile(&app.state, " uuid-steve ", PlayerProfileQuery::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.player_name, "steve");
+        assert_eq!(profile.total_events, 3);
+        assert_eq!(profile.first_seen_ms, Some(SCENARIO_START_MS + shift));
+        assert_eq!(
+            profile.last_seen_ms,
+            Some(SCENARIO_START_MS + shift + 10_000)
+        );
+        assert_eq!(profile.anomalies, 1);
+        assert_eq!(profile.anomalies_by_rule[0].rule_id, "R1");
+        let items: Vec<(&str, u64, i64)> = profile
+            .acquired_items
+            .iter()
+            .map(|item| (item.item_id.as_str(), item.acquisitions, item.total))
+            .collect();
+        assert_eq!(
+            items,
+            [("minecraft:iron_ingot", 1, 9), ("minecraft:diamond", 2, 8)]
+        );
+        assert_eq!(profile.recent_events.len(), 3);
+        assert_eq!(profile.recent_events[0].item_id, "minecraft:diamond");
+
+        let unknown = player_profile(&app.state, "uuid-herobrine", PlayerProfileQuery::default());
+        assert!(unknown.await.unwrap().is_none());
+        let no_days = PlayerProfileQuery {
+            days: Some(0),
+            recent: None,
+        };
+        assert!(player_profile(&app.state, "uuid-steve", no_days)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,6 @@
+use crate::AppState;
+use backend_domain::PlayerTeam;
+
+pub async fn list_player_teams(state: &AppState) -> Vec<PlayerTeam> {
+    state.player_teams.snapshot().await
+}
@@ -0,0 +1,7 @@
+use crate::AppError;
+use crate::AppState;
+use backend_domain::ClickhousePreflight;
+
+pub async fn clickhouse_preflight(state: &AppState) -> Result<ClickhousePreflight, AppError> {
+    Ok(state.event_repo.check_permissions().await?)
+}
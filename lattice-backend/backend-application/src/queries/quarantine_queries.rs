@@ -0,0 +1,6 @@
+use crate::AppState;
+use backend_domain::PlayerQuarantine;
+
+pub async fn list_quarantines(state: &AppState) -> Vec<PlayerQuarantine> {
+    state.quarantines.list().await
+}
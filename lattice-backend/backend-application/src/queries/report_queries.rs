@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{
+    constant_time_eq, current_millis, ReportArchiveEntry, ReportFile, ReportSummary, RuntimeConfig,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A file under `report_dir` that `/reports/...` may serve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportPage {
+    /// `YYYY-MM-DD` or `rule-hygiene-YYYY-MM`; a signed link covers the report and its player
+    /// pages.
+    pub report: String,
+    /// Relative to `report_dir`.
+    pub file: String,
+}
+
+/// The `s/{expires}/{signature}/` prefix of a signed report link, `expires` in unix seconds.
+/// It sits in the path rather than the query, so the relative links between a report and its
+/// player pages keep it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSignature {
+    pub expires: i64,
+    pub signature: String,
+}
+
+pub async fn list_reports(state: &AppState) -> Result<Vec<ReportFile>, AppError> {
+    Ok(state
+        .config_repo
+        .list_reports(&state.config.report_dir)
+        .await?)
+}
+
+/// Reports in `report_dir`, newest first, with their link and the day's anomaly counts. The
+/// counts are left out while ClickHouse is unreachable.
+pub async fn list_report_archive(state: &AppState) -> Result<Vec<ReportArchiveEntry>, AppError> {
+    let reports = list_reports(state).await?;
+    let (Some(newest), Some(oldest)) = (reports.first(), reports.last()) else {
+        return Ok(Vec::new());
+    };
+    let summaries = match state
+        .anomaly_repo
+        .fetch_daily_summary(&oldest.date, &newest.date, None, None)
+        .await
+    {
+        Ok(rows) => {
+            let mut summaries: BTreeMap<String, ReportSummary> = BTreeMap::new();
+            for row in rows {
+                let summary = summaries.entry(row.date).or_default();
+                match row.risk_level.as_str() {
+                    "CRITICAL" => summary.critical += row.count,
+                    "HIGH" => summary.high += row.count,
+                    "MEDIUM" => summary.medium += row.count,
+                    _ => summary.low += row.count,
+                }
+            }
+            summaries
+        }
+        Err(err) => {
+            warn!("report archive listed without summaries: {}", err);
+            BTreeMap::new()
+        }
+    };
+    let now_ms = current_millis();
+    Ok(reports
+        .into_iter()
+        .map(|report| ReportArchiveEntry {
+            url: report_url(&state.config, &report.date, now_ms),
+            summary: summaries.get(&report.date).cloned(),
+            date: report.date,
+            size_bytes: report.size_bytes,
+            player_pages: report.player_pages,
+            modified_ms: report.modified_ms,
+        })
+        .collect())
+}
+
+pub async fn read_report_page(
+    state: &AppState,
+    page: &ReportPage,
+) -> Result<Option<String>, AppError> {
+    Ok(state
+        .config_repo
+        .read_report_file(&state.config.report_dir, &page.file)
+        .await?)
+}
+
+/// Splits the path after `/reports/` into the page and, for a signed link, its signature; None
+/// for anything that is not a report, a rule hygiene report or a player page.
+pub fn parse_report_path(path: &str) -> Option<(ReportPage, Option<ReportSignature>)> {
+    let (signature, path) = match path.strip_prefix("s/") {
+        Some(signed) => {
+            let mut parts = signed.splitn(3, '/');
+            let expires = parts.next()?.parse().ok()?;
+            let signature = parts.next()?.to_string();
+            let rest = parts.next()?;
+            (Some(ReportSignature { expires, signature }), rest)
+        }
+        None => (None, path),
+    };
+    let segments: Vec<&str> = path.split('/').collect();
+    let page = match segments.as_slice() {
+        [name] => {
+            let report = name.strip_suffix(".html").unwrap_or(name);
+            if !is_report_date(report) && !is_rule_hygiene_report(report) {
+                return None;
+            }
+            ReportPage {
+                report: report.to_string(),
+                file: format!("{}.html", report),
+            }
+        }
+        [date, "players", file] => {
+            let stem = file.strip_suffix(".html")?;
+            let valid = !stem.is_empty()
+                && stem
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+            if !is_report_date(date) || !valid {
+                return None;
+            }
+            ReportPage {
+                report: date.to_string(),
+                file: format!("{}/players/{}", date, file),
+            }
+        }
+        _ => return None,
+    };
+    Some((page, signature))
+}
+
+/// Link to `report` under `public_base_url`, signed for `report_link_ttl_hours` when
+/// `report_link_secret` is set.
+pub fn report_url(config: &RuntimeConfig, report: &str, now_ms: i64) -> String {
+    let base = config.public_base_url.trim_end_matches('/');
+    let expires = now_ms / 1000 + config.report_link_ttl_hours as i64 * 3600;
+    let signature = config
+        .report_link_secret
+        .as_deref()
+        .and_then(|secret| sign_report(secret, report, expires));
+    match signature {
+        Some(signature) => format!("{}/reports/s/{}/{}/{}", base, expires, signature, report),
+        None => format!("{}/reports/{}", base, report),
+    }
+}
+
+/// True for an unexpired signature made with `report_link_secret` for `report`.
+pub fn verify_report_signature(
+    config: &RuntimeConfig,
+    report: &str,
+    signature: &ReportSignature,
+    now_ms: i64,
+) -> bool {
+    let Some(secret) = &config.report_link_secret else {
+        return false;
+    };
+    if signature.expires < now_ms / 1000 {
+        return false;
+    }
+    let Some(expected) = sign_report(secret, report, signature.expires) else {
+        return false;
+    };
+    constant_time_eq(&expected, &signature.signature)
+}
+
+fn sign_report(secret: &str, report: &str, expires: i64) -> Option<String> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(format!("{}|{}", report, expires).as_bytes());
+    Some(
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+fn is_report_date(value: &str) -> bool {
+    value.len() == 10 && backend_domain::parse_date(value).is_ok()
+}
+
+fn is_rule_hygiene_report(value: &str) -> bool {
+    value
+        .strip_prefix("rule-hygiene-")
+        .is_some_and(|month| month.len() == 7 && is_report_date(&format!("{}-01", month)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::runtime_config;
+
+    #[test]
+    fn signed_report_links_cover_their_player_pages_until_they_expire() {
+        let mut config = runtime_config();
+        config.public_base_url = "https://lattice.example/".to_string();
+        assert_eq!(
+            report_url(&config, "2026-10-15", 0),
+            "https://lattice.example/reports/2026-10-15"
+        );
+
+        config.report_link_secret = Some("0123456789abcdef".to_string());
+        config.report_link_ttl_hours = 1;
+        let url = report_url(&config, "2026-10-15", 1_000_000);
+        let path = url
+            .strip_prefix("https://lattice.example/reports/")
+            .unwrap();
+        let (page, signature) = parse_report_path(path).unwrap();
+        assert_eq!(page.file, "2026-10-15.html");
+        let signature = signature.unwrap();
+        assert_eq!(signature.expires, 1_000 + 3_600);
+        assert!(verify_report_signature(
+            &config,
+            "2026-10-15",
+            &signature,
+            1_000_000
+        ));
+        assert!(!verify_report_signature(
+            &config,
+            "2026-10-16",
+            &signature,
+            1_000_000
+        ));
+        assert!(!verify_report_signature(
+            &config,
+            "2026-10-15",
+            &signature,
+            4_601_000
+        ));
+
+        let player_path = path.replace("2026-10-15", "2026-10-15/players/Steve.html");
+        let (page, _) = parse_report_path(&player_path).unwrap();
+        assert_eq!(page.report, "2026-10-15");
+        assert_eq!(page.file, "2026-10-15/players/Steve.html");
+        assert!(parse_report_path("2026-10-15/players/..%2F.html").is_none());
+        assert!(parse_report_path("../config.toml").is_none());
+        assert_eq!(
+            parse_report_path("rule-hygiene-2026-09").unwrap().0.file,
+            "rule-hygiene-2026-09.html"
+        );
+    }
+}
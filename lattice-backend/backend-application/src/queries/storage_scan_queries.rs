@@ -1,32 +1,162 @@
+use std::collections::HashMap;
+
 use chrono::Local;
 use tracing::error;
 
 use crate::AppState;
-use crate::AppError;
-use backend_domain::{KeyItemRule, PagedResult, StorageScanEventRow, StorageScanQuery, StorageScanRow};
+use crate::{AppError, ErrorCode};
+use backend_domain::{
+    rule_description, FieldSelection, ItemPatternSet, KeyItemRule, PagedResult,
+    StorageScanEventRow, StorageScanQuery, StorageScanRow, DEFAULT_RULE_LANG,
+};
 
 const DEFAULT_PAGE: usize = 1;
 const DEFAULT_PAGE_SIZE: usize = 50;
 const ALLOWED_PAGE_SIZES: [usize; 4] = [25, 50, 100, 200];
+/// Snapshots read per storage query.
+const CHUNK_SIZE: usize = 200;
 
+/// Lists one page of storage-scan findings; `fields` skips unrequested location columns.
 pub async fn list_storage_scan(
     state: &AppState,
     query: StorageScanQuery,
+    fields: &FieldSelection,
 ) -> Result<PagedResult<StorageScanRow>, AppError> {
+    let (date, item) = scan_filters(&query)?;
+    let (page, page_size) = normalize_page(query.page, query.page_size)?;
+    let lang = query.lang.as_deref().unwrap_or(DEFAULT_RULE_LANG);
+    let filtered_rows = fetch_findings(state, &date, item.as_deref(), lang, fields).await?;
+
+    let total_items = filtered_rows.len();
+    let total_pages = if total_items == 0 {
+        1
+    } else {
+        (total_items + page_size - 1) / page_size
+    };
+    let start = (page - 1).saturating_mul(page_size);
+    let items = if start >= total_items {
+        Vec::new()
+    } else {
+        filtered_rows
+            .into_iter()
+            .skip(start)
+            .take(page_size)
+            .collect()
+    };
+
+    Ok(PagedResult {
+        items,
+        page,
+        page_size,
+        total_items,
+        total_pages,
+        degraded: false,
+    })
+}
+
+/// Every finding of one day for a `format=csv|xlsx` download, read newest first a chunk of
+/// snapshots at a time, each continuing after the last snapshot of the one before.
+pub struct StorageScanExport {
+    state: AppState,
+    date: String,
+    item: Option<String>,
+    lang: String,
+    rules: HashMap<String, KeyItemRule>,
+    patterns: ItemPatternSet,
+    after: Option<StorageScanEventRow>,
+    done: bool,
+}
+
+impl StorageScanExport {
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// The next findings of the day; empty once every snapshot was read. Snapshots under their
+    /// rule's threshold are skipped, reading on until a chunk has a finding.
+    pub async fn next_chunk(&mut self) -> Result<Vec<StorageScanRow>, AppError> {
+        while !self.done {
+            let events = self
+                .state
+                .event_repo
+                .fetch_storage_scan_events_after(
+                    &self.date,
+                    self.item.as_deref(),
+                    self.after
+                        .as_ref()
+                        .map(|event| (event.event_time, event.event_id.as_str())),
+                    CHUNK_SIZE,
+                )
+                .await
+                .map_err(|err| {
+                    error!("failed to fetch storage scan events: {}", err);
+                    AppError::Internal(err.into())
+                })?;
+            self.done = events.len() < CHUNK_SIZE;
+            let rows: Vec<StorageScanRow> = events
+                .iter()
+                .filter_map(|event| {
+                    to_storage_scan_row(event, &self.rules, &self.patterns, &self.lang)
+                })
+                .collect();
+            self.after = events.into_iter().last();
+            if !rows.is_empty() {
+                return Ok(rows);
+            }
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Starts a download of the queried day under the key item rules loaded now.
+pub async fn export_storage_scan(
+    state: &AppState,
+    query: StorageScanQuery,
+) -> Result<StorageScanExport, AppError> {
+    let (date, item) = scan_filters(&query)?;
+    let rules = state.key_rules.read().await.clone();
+    let patterns = ItemPatternSet::from_rules(&rules);
+    Ok(StorageScanExport {
+        state: state.clone(),
+        date,
+        item,
+        lang: query.lang.unwrap_or_else(|| DEFAULT_RULE_LANG.to_string()),
+        rules,
+        patterns,
+        after: None,
+        done: false,
+    })
+}
+
+/// The validated `date` (today when absent) and lowercased `item` of a query.
+fn scan_filters(query: &StorageScanQuery) -> Result<(String, Option<String>), AppError> {
     let date = query
         .date
+        .clone()
         .unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
     if let Err(err) = backend_domain::parse_date(&date) {
-        return Err(AppError::BadRequest(format!("invalid date: {}", err)));
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            format!("invalid date: {}", err),
+        ));
     }
 
-    let item = query.item.as_deref().map(|value| value.trim().to_lowercase());
+    let item = query
+        .item
+        .as_deref()
+        .map(|value| value.trim().to_lowercase());
     if let Some(item_id) = item.as_deref() {
         if item_id.is_empty() {
-            return Err(AppError::BadRequest("item is empty".to_string()));
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidItemId,
+                "item is empty".to_string(),
+            ));
         }
         if !item_id.contains(':') {
-            return Err(AppError::BadRequest("item must be namespace:path".to_string()));
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidItemId,
+                "item must be namespace:path".to_string(),
+            ));
         }
         if !item_id.chars().all(|c| {
             c.is_ascii_lowercase()
@@ -37,14 +167,25 @@ pub async fn list_storage_scan(
                 || c == '.'
                 || c == '/'
         }) {
-            return Err(AppError::BadRequest("item contains invalid characters".to_string()));
+            return Err(AppError::Invalid(
+                ErrorCode::InvalidItemId,
+                "item contains invalid characters".to_string(),
+            ));
         }
     }
+    Ok((date, item))
+}
 
-    let (page, page_size) = normalize_page(query.page, query.page_size)?;
+async fn fetch_findings(
+    state: &AppState,
+    date: &str,
+    item: Option<&str>,
+    lang: &str,
+    fields: &FieldSelection,
+) -> Result<Vec<StorageScanRow>, AppError> {
     let total_raw_u64 = state
         .event_repo
-        .count_storage_scan_events(&date, item.as_deref())
+        .count_storage_scan_events(date, item)
         .await
         .map_err(|err| {
             error!("failed to count storage scan events: {}", err);
@@ -52,25 +193,19 @@ pub async fn list_storage_scan(
         })?;
     let total_raw = usize::try_from(total_raw_u64).unwrap_or(usize::MAX);
     if total_raw == 0 {
-        return Ok(PagedResult {
-            items: Vec::new(),
-            page,
-            page_size,
-            total_items: 0,
-            total_pages: 1,
-        });
+        return Ok(Vec::new());
     }
 
     // Storage scan threshold is rule-dependent, so we materialize filtered rows first,
     // then apply stable paging on the filtered result set.
     let rules = state.key_rules.read().await.clone();
+    let patterns = ItemPatternSet::from_rules(&rules);
     let mut filtered_rows = Vec::new();
     let mut current_offset = 0usize;
-    const CHUNK_SIZE: usize = 200;
     while current_offset < total_raw {
         let events = state
             .event_repo
-            .fetch_storage_scan_events_page(&date, item.as_deref(), current_offset, CHUNK_SIZE)
+            .fetch_storage_scan_events_page(date, item, current_offset, CHUNK_SIZE, fields)
             .await
             .map_err(|err| {
                 error!("failed to fetch storage scan events: {}", err);
@@ -80,44 +215,22 @@ pub async fn list_storage_scan(
             break;
         }
         for event in events.iter() {
-            if let Some(row) = to_storage_scan_row(event, &rules) {
+            if let Some(row) = to_storage_scan_row(event, &rules, &patterns, lang) {
                 filtered_rows.push(row);
             }
         }
         current_offset = current_offset.saturating_add(events.len());
     }
-
-    let total_items = filtered_rows.len();
-    let total_pages = if total_items == 0 {
-        1
-    } else {
-        (total_items + page_size - 1) / page_size
-    };
-    let start = (page - 1).saturating_mul(page_size);
-    let items = if start >= total_items {
-        Vec::new()
-    } else {
-        filtered_rows
-            .into_iter()
-            .skip(start)
-            .take(page_size)
-            .collect()
-    };
-
-    Ok(PagedResult {
-        items,
-        page,
-        page_size,
-        total_items,
-        total_pages,
-    })
+    Ok(filtered_rows)
 }
 
 fn to_storage_scan_row(
     event: &StorageScanEventRow,
-    rules: &std::collections::HashMap<String, KeyItemRule>,
+    rules: &HashMap<String, KeyItemRule>,
+    patterns: &ItemPatternSet,
+    lang: &str,
 ) -> Option<StorageScanRow> {
-    let rule = rules.get(&event.item_id)?;
+    let rule = patterns.find(rules, &event.item_id)?;
     let threshold = rule.effective_threshold();
     if threshold == 0 {
         return None;
@@ -140,6 +253,7 @@ fn to_storage_scan_row(
         y: event.y,
         z: event.z,
         rule_id: "R12".to_string(),
+        rule_description: rule_description("R12", lang).to_string(),
         threshold,
         risk_level,
         reason: format!(
@@ -149,15 +263,22 @@ fn to_storage_scan_row(
     })
 }
 
-fn normalize_page(page: Option<usize>, page_size: Option<usize>) -> Result<(usize, usize), AppError> {
+fn normalize_page(
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<(usize, usize), AppError> {
     let current_page = page.unwrap_or(DEFAULT_PAGE);
     if current_page == 0 {
-        return Err(AppError::BadRequest("page must be >= 1".to_string()));
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
+            "page must be >= 1".to_string(),
+        ));
     }
 
     let size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
     if !ALLOWED_PAGE_SIZES.contains(&size) {
-        return Err(AppError::BadRequest(
+        return Err(AppError::Invalid(
+            ErrorCode::InvalidPage,
             "page_size must be one of: 25, 50, 100, 200".to_string(),
         ));
     }
@@ -0,0 +1,28 @@
+use crate::AppError;
+use crate::AppState;
+use backend_domain::{current_millis, AnomalySuppression, ExpiredSuppressionQuery};
+
+const DEFAULT_EXPIRED_HOURS: u32 = 72;
+const MAX_EXPIRED_HOURS: u32 = 168;
+
+pub async fn list_suppressions(state: &AppState) -> Vec<AnomalySuppression> {
+    state.suppressions.active(current_millis()).await
+}
+
+pub async fn list_expired_suppressions(
+    state: &AppState,
+    query: ExpiredSuppressionQuery,
+) -> Result<Vec<AnomalySuppression>, AppError> {
+    let hours = query.hours.unwrap_or(DEFAULT_EXPIRED_HOURS);
+    if hours == 0 || hours > MAX_EXPIRED_HOURS {
+        return Err(AppError::BadRequest(format!(
+            "hours must be between 1 and {}",
+            MAX_EXPIRED_HOURS
+        )));
+    }
+    let now = current_millis();
+    Ok(state
+        .suppressions
+        .expired_since(now, now - i64::from(hours) * 3_600_000)
+        .await)
+}
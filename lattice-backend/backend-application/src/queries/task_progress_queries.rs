@@ -1,6 +1,77 @@
 use crate::AppState;
-use backend_domain::TaskStatus;
+use backend_domain::{current_millis, TaskProgress, TaskStatus};
 
 pub async fn get_task_progress(state: &AppState) -> TaskStatus {
-    state.task_status.read().await.clone()
+    let status = state.task_status.read().await.clone();
+    mark_stalled(
+        status,
+        current_millis(),
+        state.config.task_stale_after_seconds,
+    )
+}
+
+/// Exposes every stalled task as STALLED with `stale` set; the stored progress keeps RUNNING, so
+/// the next update of the mod resumes the task.
+pub fn mark_stalled(mut status: TaskStatus, now_ms: i64, stale_after_seconds: u64) -> TaskStatus {
+    for progress in [&mut status.audit, &mut status.scan] {
+        if is_stalled(progress, now_ms, stale_after_seconds) {
+            progress.state = "STALLED".to_string();
+            progress.stale = true;
+        }
+    }
+    status
+}
+
+/// Tasks that stayed RUNNING for `stale_after_seconds` without an update, e.g. because the mod
+/// crashed mid-scan; none while `stale_after_seconds` is 0.
+pub fn stalled_tasks(
+    status: &TaskStatus,
+    now_ms: i64,
+    stale_after_seconds: u64,
+) -> Vec<(&'static str, &TaskProgress)> {
+    [("audit", &status.audit), ("scan", &status.scan)]
+        .into_iter()
+        .filter(|(_, progress)| is_stalled(progress, now_ms, stale_after_seconds))
+        .collect()
+}
+
+fn is_stalled(progress: &TaskProgress, now_ms: i64, stale_after_seconds: u64) -> bool {
+    stale_after_seconds > 0
+        && progress.state == "RUNNING"
+        && now_ms.saturating_sub(progress.updated_at) >= stale_after_seconds as i64 * 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(state: &str, updated_at: i64) -> TaskProgress {
+        TaskProgress {
+            state: state.to_string(),
+            updated_at,
+            ..TaskProgress::default()
+        }
+    }
+
+    #[test]
+    fn running_tasks_without_updates_are_exposed_as_stalled() {
+        let status = TaskStatus {
+            audit: progress("RUNNING", 1_000),
+            scan: progress("SUCCEEDED", 1_000),
+        };
+        assert!(stalled_tasks(&status, 600_999, 600).is_empty());
+        let stalled = stalled_tasks(&status, 601_000, 600);
+        assert_eq!(stalled.len(), 1);
+        assert_eq!(stalled[0].0, "audit");
+        assert!(stalled_tasks(&status, 10_000_000, 0).is_empty());
+
+        let exposed = mark_stalled(status, 601_000, 600);
+        assert_eq!(exposed.audit.state, "STALLED");
+        assert!(exposed.audit.stale);
+        assert_eq!(exposed.scan.state, "SUCCEEDED");
+        assert!(!exposed.scan.stale);
+        let json = serde_json::to_value(&exposed).unwrap();
+        assert_eq!(json["audit"]["stale"], true);
+        assert_eq!(json["scan"]["stale"], false);
+    }
 }
@@ -1,10 +1,20 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::ops::ModConfigStreamHub;
-use backend_domain::ports::{AlertService, AnomalyRepository, ConfigRepository, EventRepository};
-use backend_domain::services::Analyzer;
-use backend_domain::{ItemRegistryEntry, KeyItemRule, ModConfigAck, ModConfigEnvelope, RuntimeConfig, TaskStatus};
+use crate::ops::{
+    AdminSecret, AnomalyStreamHub, ApiTokenStore, BanRegistry, ClickhouseWatchdog, DailyQuotaTracker, DataDropConfirmations, DeadLetterQueue, DegradedMode, EventDedup, IngestSourceTracker, JobQueue,
+    ModConfigStreamHub, ModVersionGate, OriginWhitelistRegistry, PlayerTeamRegistry, QuarantineRegistry, RecentAnomalyBuffer,
+    RuleRevisionLog, ServerHeartbeatRegistry, StorageFindingTracker, SuppressionRegistry,
+};
+use backend_domain::ports::{
+    AlertService, AnalyzerStateService, AnomalyRepository, ConfigRepository, EventPublisher,
+    EventRepository, IngestRecorder, MaintenanceRepository, ReportService,
+};
+use backend_domain::services::{Analyzer, CustomDetectorRegistry, EnrichmentChain};
+use backend_domain::{
+    CompositeRule, DetectionRule, ItemRegistryEntry, KeyItemRule, MaintenanceRun, ModConfigAck,
+    ModConfigEnvelope, RuntimeConfig, TaskStatus,
+};
 use tokio::sync::{Mutex, RwLock};
 
 use crate::Metrics;
@@ -15,13 +25,61 @@ pub struct AppState {
     pub event_repo: Arc<dyn EventRepository>,
     pub anomaly_repo: Arc<dyn AnomalyRepository>,
     pub config_repo: Arc<dyn ConfigRepository>,
+    pub maintenance_repo: Arc<dyn MaintenanceRepository>,
     pub alert_service: Arc<dyn AlertService>,
+    pub report_service: Arc<dyn ReportService>,
+    /// Mirrors anomalies and health transitions, e.g. to MQTT; `None` when no bus is configured.
+    pub event_publisher: Option<Arc<dyn EventPublisher>>,
+    /// Appends accepted batches to `ingest_record_path` for offline replay; `None` when off.
+    pub ingest_recorder: Option<Arc<dyn IngestRecorder>>,
     pub analyzer: Arc<Mutex<Analyzer>>,
+    /// Set on `cluster_mode` replicas that forward analysis to `cluster_state_url`; `analyzer`
+    /// is then only used while that instance is unreachable.
+    pub cluster_state: Option<Arc<dyn AnalyzerStateService>>,
+    /// Detectors for `custom` family events; embedders may register their own next to the built-ins.
+    pub custom_detectors: Arc<Mutex<CustomDetectorRegistry>>,
+    /// Ingest enrichers from `enrichers`; embedders may register their own (e.g. geodata).
+    pub enrichment: Arc<Mutex<EnrichmentChain>>,
     pub key_rules: Arc<RwLock<HashMap<String, KeyItemRule>>>,
+    /// User rules from `detection_rules_path`, validated at startup.
+    pub detection_rules: Arc<RwLock<Vec<DetectionRule>>>,
+    /// Rules from `composite_rules_path`, validated at startup.
+    pub composite_rules: Arc<RwLock<Vec<CompositeRule>>>,
     pub item_registry: Arc<RwLock<Vec<ItemRegistryEntry>>>,
     pub metrics: Arc<Metrics>,
     pub task_status: Arc<RwLock<TaskStatus>>,
+    pub maintenance_status: Arc<RwLock<Option<MaintenanceRun>>>,
     pub mod_configs: Arc<RwLock<HashMap<String, ModConfigEnvelope>>>,
     pub mod_config_acks: Arc<RwLock<HashMap<String, ModConfigAck>>>,
     pub mod_config_stream_hub: Arc<ModConfigStreamHub>,
+    pub ingest_tracker: Arc<IngestSourceTracker>,
+    /// Recent event ids, so batches the mod re-sends are stored and analyzed once.
+    pub event_dedup: Arc<EventDedup>,
+    pub heartbeats: Arc<ServerHeartbeatRegistry>,
+    pub mod_version_gate: Arc<ModVersionGate>,
+    pub storage_findings: Arc<StorageFindingTracker>,
+    pub suppressions: Arc<SuppressionRegistry>,
+    pub degraded: Arc<DegradedMode>,
+    /// Ping results of the ClickHouse watchdog task, read by `health_ready`.
+    pub clickhouse_watchdog: Arc<ClickhouseWatchdog>,
+    pub recent_anomalies: Arc<RecentAnomalyBuffer>,
+    /// New anomalies for `/v2/detect/anomalies/stream` subscribers.
+    pub anomaly_stream: Arc<AnomalyStreamHub>,
+    pub dead_letters: Arc<DeadLetterQueue>,
+    /// Pending `DELETE /v2/ops/data` confirmations.
+    pub data_drops: Arc<DataDropConfirmations>,
+    pub daily_quotas: Arc<DailyQuotaTracker>,
+    pub bans: Arc<BanRegistry>,
+    /// Players whose anomalies are stored tagged but not alerted, managed via `/v2/ops/quarantine`.
+    pub quarantines: Arc<QuarantineRegistry>,
+    /// Background jobs of `/v2/ops/jobs`, such as ML exports.
+    pub jobs: Arc<JobQueue>,
+    /// Named, scoped tokens beside the configured `api_token`.
+    pub api_tokens: Arc<ApiTokenStore>,
+    pub player_teams: Arc<PlayerTeamRegistry>,
+    pub origin_whitelist: Arc<OriginWhitelistRegistry>,
+    /// Key item rule changes, marked in the daily report of the day they happened.
+    pub rule_revisions: Arc<RuleRevisionLog>,
+    /// Set by an embedded backend; config-mutating endpoints then also require it.
+    pub admin_secret: Option<AdminSecret>,
 }
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use backend_domain::services::{Analyzer, CustomDetectorRegistry, EnrichmentChain};
+use backend_domain::testing::{
+    InMemoryAnomalyRepository, InMemoryConfigRepository, InMemoryEventRepository,
+    InMemoryMaintenanceRepository, RecordingAlertService, RecordingReportService,
+};
+use backend_domain::{OriginWhitelist, RuntimeConfig, TaskStatus};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::ops::{
+    AnomalyStreamHub, ApiTokenStore, BanRegistry, ClickhouseWatchdog, DailyQuotaTracker,
+    DataDropConfirmations, DeadLetterQueue, DegradedMode, EventDedup, IngestSourceTracker,
+    JobQueue, ModConfigStreamHub, ModVersionGate, OriginWhitelistRegistry, PlayerTeamRegistry,
+    QuarantineRegistry, RecentAnomalyBuffer, RuleRevisionLog, ServerHeartbeatRegistry,
+    StorageFindingTracker, SuppressionRegistry,
+};
+use crate::{AppState, Metrics};
+
+/// An `AppState` over the in-memory ports of `backend_domain::testing`, with handles on each so
+/// a test can seed data and inspect what the application stored or alerted.
+///
+/// ```ignore
+/// let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+/// app.anomalies.insert_anomalies(&rows).await?;
+/// let router = backend_interfaces_http::build_router(app.state.clone());
+/// ```
+pub struct InMemoryApp {
+    pub state: AppState,
+    pub events: Arc<InMemoryEventRepository>,
+    pub anomalies: Arc<InMemoryAnomalyRepository>,
+    pub configs: Arc<InMemoryConfigRepository>,
+    pub maintenance: Arc<InMemoryMaintenanceRepository>,
+    pub alerts: Arc<RecordingAlertService>,
+    pub reports: Arc<RecordingReportService>,
+}
+
+impl InMemoryApp {
+    /// Starts empty: no key item rules, no registries and the built-in origin whitelist. Unknown
+    /// `enrichers` are skipped rather than failing the setup.
+    pub fn new(config: RuntimeConfig) -> Self {
+        let events = Arc::new(InMemoryEventRepository::default());
+        let anomalies = Arc::new(InMemoryAnomalyRepository::default());
+        let configs = Arc::new(InMemoryConfigRepository::default());
+        let maintenance = Arc::new(InMemoryMaintenanceRepository::default());
+        let alerts = Arc::new(RecordingAlertService::default());
+        let reports = Arc::new(RecordingReportService::default());
+
+        let state = AppState {
+            event_repo: events.clone(),
+            anomaly_repo: anomalies.clone(),
+            config_repo: configs.clone(),
+            maintenance_repo: maintenance.clone(),
+            alert_service: alerts.clone(),
+            report_service: reports.clone(),
+            event_publisher: None,
+            ingest_recorder: None,
+            analyzer: Arc::new(Mutex::new(Analyzer::default())),
+            cluster_state: None,
+            custom_detectors: Arc::new(Mutex::new(CustomDetectorRegistry::with_builtins(&config))),
+            enrichment: Arc::new(Mutex::new(
+                EnrichmentChain::from_names(&config.enrichers).unwrap_or_default(),
+            )),
+            key_rules: Arc::new(RwLock::new(HashMap::new())),
+            detection_rules: Arc::new(RwLock::new(Vec::new())),
+            composite_rules: Arc::new(RwLock::new(Vec::new())),
+            item_registry: Arc::new(RwLock::new(Vec::new())),
+            metrics: Arc::new(Metrics::default()),
+            task_status: Arc::new(RwLock::new(TaskStatus::default())),
+            maintenance_status: Arc::new(RwLock::new(None)),
+            mod_configs: Arc::new(RwLock::new(HashMap::new())),
+            mod_config_acks: Arc::new(RwLock::new(HashMap::new())),
+            mod_config_stream_hub: Arc::new(ModConfigStreamHub::default()),
+            ingest_tracker: Arc::new(IngestSourceTracker::default()),
+            event_dedup: Arc::new(EventDedup::new(config.ingest_dedup_capacity)),
+            heartbeats: Arc::new(ServerHeartbeatRegistry::default()),
+            mod_version_gate: Arc::new(ModVersionGate::default()),
+            storage_findings: Arc::new(StorageFindingTracker::new(Vec::new())),
+            suppressions: Arc::new(SuppressionRegistry::new(Vec::new())),
+            degraded: Arc::new(DegradedMode::default()),
+            clickhouse_watchdog: Arc::new(ClickhouseWatchdog::default()),
+            recent_anomalies: Arc::new(RecentAnomalyBuffer::new(config.degraded_cache_size)),
+            anomaly_stream: Arc::new(AnomalyStreamHub::default()),
+            dead_letters: Arc::new(DeadLetterQueue::new(
+                Vec::new(),
+                config.dead_letter_max_events,
+            )),
+            data_drops: Arc::new(DataDropConfirmations::default()),
+            daily_quotas: Arc::new(DailyQuotaTracker::default()),
+            bans: Arc::new(BanRegistry::new(Vec::new())),
+            quarantines: Arc::new(QuarantineRegistry::new(Vec::new())),
+            jobs: Arc::new(JobQueue::default()),
+            api_tokens: Arc::new(ApiTokenStore::new(Vec::new())),
+            player_teams: Arc::new(PlayerTeamRegistry::new(Vec::new())),
+            origin_whitelist: Arc::new(OriginWhitelistRegistry::new(OriginWhitelist::default())),
+            rule_revisions: Arc::new(RuleRevisionLog::new(Vec::new())),
+            admin_secret: None,
+            config,
+        };
+
+        Self {
+            state,
+            events,
+            anomalies,
+            configs,
+            maintenance,
+            alerts,
+            reports,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Pings ClickHouse in the background, so an outage shows up in `health_ready` and as a system
+//! alert even while nothing is being written.
+
+use std::time::Duration;
+
+use backend_application::commands::dead_letter_commands;
+use backend_application::ops::WatchdogTransition;
+use backend_application::AppState;
+use backend_domain::current_millis;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info, warn};
+
+/// Runs every `clickhouse_watchdog_interval_seconds`. Once `clickhouse_watchdog_failures` pings
+/// in a row failed, degraded mode is entered and stays held by every further failure; the first
+/// success starts its usual recovery window. Both transitions send one system alert.
+pub async fn monitor_clickhouse(state: AppState) {
+    let interval_seconds = state.config.clickhouse_watchdog_interval_seconds;
+    if interval_seconds == 0 {
+        return;
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let message = match dead_letter_commands::ping_storage(&state).await {
+            Ok(()) => {
+                dead_letter_commands::record_storage_success(&state).await;
+                match state.clickhouse_watchdog.record_success().await {
+                    Some(WatchdogTransition::Recovered) => {
+                        info!("ClickHouse watchdog ping succeeded again");
+                        "[Lattice 数据库恢复] ClickHouse 已恢复连接".to_string()
+                    }
+                    _ => continue,
+                }
+            }
+            Err(err) => {
+                let threshold = state.config.clickhouse_watchdog_failures;
+                let transition = state
+                    .clickhouse_watchdog
+                    .record_failure(current_millis(), &err.to_string(), threshold)
+                    .await;
+                let status = state.clickhouse_watchdog.status().await;
+                if status.unhealthy_since_ms.is_none() {
+                    warn!(
+                        "ClickHouse watchdog ping failed ({}/{}): {}",
+                        status.consecutive_failures, threshold, err
+                    );
+                    continue;
+                }
+                dead_letter_commands::record_storage_failure(&state, &err).await;
+                if transition != Some(WatchdogTransition::Unhealthy) {
+                    continue;
+                }
+                format!(
+                    "[Lattice 数据库告警] ClickHouse 已连续 {} 次无法连接：{}",
+                    status.consecutive_failures, err
+                )
+            }
+        };
+        if let Err(err) = state
+            .alert_service
+            .send_system_alert(&state.config, &message)
+            .await
+        {
+            error!("failed to send ClickHouse watchdog alert: {}", err);
+        }
+    }
+}
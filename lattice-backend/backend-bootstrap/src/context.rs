@@ -1,15 +1,22 @@
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::path::Path;
 
 use anyhow::Result;
 use clickhouse::Client;
 use tokio::sync::{Mutex, RwLock};
-use tracing::warn;
+use tracing::{info, warn};
 
+use backend_application::commands::event_source_commands::consume_event_source;
 use backend_application::{AppState, Metrics};
-use backend_domain::{Analyzer, ConfigRepository, TaskStatus};
+use backend_domain::{
+    validate_composite_rules, validate_detection_rules, AlertService, Analyzer,
+    AnalyzerStateService, ConfigRepository, CustomDetectorRegistry, DbConfig, EnrichmentChain,
+    IngestRecorder, OriginWhitelist, TaskStatus,
+};
 use backend_infrastructure::{
-    AppConfig, ClickhouseRepo, ConfigFileRepository, DefaultAlertService,
+    AppConfig, ClickhouseRepo, ConfigFileRepository, DefaultAlertService, FileIngestRecorder,
+    HtmlReportService, HttpAnalyzerStateService, MqttBridge,
 };
 
 pub struct AppContext {
@@ -17,55 +24,316 @@ pub struct AppContext {
 }
 
 impl AppContext {
+    /// Builds the context from `LATTICE_CONFIG`, as the standalone server does.
     pub async fn new() -> Result<Self> {
         let config = AppConfig::load().await?;
+        Self::from_config(
+            config,
+            Arc::new(ConfigFileRepository::new()),
+            Arc::new(DefaultAlertService::new()),
+        )
+        .await
+    }
+
+    /// Builds the context from the given config file only, so several instances can coexist in
+    /// one process.
+    pub async fn from_config_path(path: impl AsRef<Path>) -> Result<Self> {
+        let config = AppConfig::load_from(path.as_ref()).await?;
+        Self::from_config(
+            config,
+            Arc::new(ConfigFileRepository::for_config_path(path)),
+            Arc::new(DefaultAlertService::new()),
+        )
+        .await
+    }
+
+    /// Builds the context from an already loaded config with the given config store and alert
+    /// delivery.
+    pub async fn from_config(
+        config: AppConfig,
+        config_repo: Arc<dyn ConfigRepository>,
+        alert_service: Arc<dyn AlertService>,
+    ) -> Result<Self> {
         let runtime_config = config.to_runtime_config();
         let db_config = config.to_db_config();
 
-        let mut clickhouse = Client::default()
-            .with_url(&db_config.clickhouse_url)
-            .with_database(&db_config.clickhouse_database);
-        if let Some(user) = &db_config.clickhouse_user {
-            clickhouse = clickhouse.with_user(user);
-        }
-        if let Some(password) = &db_config.clickhouse_password {
-            clickhouse = clickhouse.with_password(password);
+        let mut repo = clickhouse_repo(&db_config);
+        if let Some(shadow_config) = config.to_shadow_db_config() {
+            let shadow = clickhouse_repo(&shadow_config);
+            if let Err(err) = shadow.ensure_schema().await {
+                warn!("shadow clickhouse schema ensure failed at startup: {}", err);
+            }
+            info!(
+                "dual-writing to shadow clickhouse {} database {}",
+                shadow_config.clickhouse_url, shadow_config.clickhouse_database
+            );
+            repo = repo.with_shadow(shadow);
         }
-
-        let repo = Arc::new(ClickhouseRepo::new(
-            clickhouse,
-            db_config.clickhouse_database.clone(),
-        ));
+        let repo = Arc::new(repo);
         if let Err(err) = repo.ensure_schema().await {
             warn!("clickhouse schema ensure failed at startup: {}", err);
         }
+        match repo.check_permissions().await {
+            Ok(preflight) => {
+                for check in preflight.checks.iter().filter(|check| check.status != "granted") {
+                    warn!(
+                        "clickhouse preflight: {} on database {} is {}: {}",
+                        check.privilege,
+                        preflight.database,
+                        check.status,
+                        check.error.as_deref().unwrap_or_default()
+                    );
+                }
+            }
+            Err(err) => warn!("clickhouse permission preflight skipped: {}", err),
+        }
 
-        let config_repo = Arc::new(ConfigFileRepository::new());
         let key_rules = config_repo
             .load_key_items(&runtime_config.key_items_path)
             .await
             .unwrap_or_default();
+        let detection_rules = match config_repo
+            .load_detection_rules(&runtime_config.detection_rules_path)
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|rules| validate_detection_rules(&rules).map(|_| rules))
+        {
+            Ok(rules) => rules,
+            Err(err) => {
+                warn!(
+                    "ignoring detection rules at {}: {}",
+                    runtime_config.detection_rules_path, err
+                );
+                Vec::new()
+            }
+        };
+        let composite_rules = match config_repo
+            .load_composite_rules(&runtime_config.composite_rules_path)
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|rules| validate_composite_rules(&rules).map(|_| rules))
+        {
+            Ok(rules) => rules,
+            Err(err) => {
+                warn!(
+                    "ignoring composite rules at {}: {}",
+                    runtime_config.composite_rules_path, err
+                );
+                Vec::new()
+            }
+        };
         let item_registry = config_repo
             .load_item_registry(&runtime_config.item_registry_path)
             .await
             .unwrap_or_default();
+        let storage_findings = config_repo
+            .load_storage_findings()
+            .await
+            .unwrap_or_else(|err| {
+                warn!("failed to load storage findings: {}", err);
+                Vec::new()
+            });
+        let suppressions = config_repo.load_suppressions().await.unwrap_or_else(|err| {
+            warn!("failed to load suppressions: {}", err);
+            Vec::new()
+        });
+        let bans = config_repo.load_bans().await.unwrap_or_else(|err| {
+            warn!("failed to load bans: {}", err);
+            Vec::new()
+        });
+        let quarantines = config_repo.load_quarantines().await.unwrap_or_else(|err| {
+            warn!("failed to load quarantines: {}", err);
+            Vec::new()
+        });
+        // Starting without the issued tokens could leave the API open, so a broken file is fatal.
+        let api_tokens = config_repo
+            .load_api_tokens()
+            .await
+            .map_err(|err| anyhow::anyhow!("failed to load api tokens: {}", err))?;
+        let player_teams = config_repo.load_player_teams().await.unwrap_or_else(|err| {
+            warn!("failed to load player teams: {}", err);
+            Vec::new()
+        });
+        let origin_whitelist = config_repo
+            .load_origin_whitelist()
+            .await
+            .unwrap_or_else(|err| {
+                warn!("failed to load origin whitelist: {}", err);
+                OriginWhitelist::default()
+            });
+        let rule_revisions = config_repo.load_rule_revisions().await.unwrap_or_else(|err| {
+            warn!("failed to load rule revisions: {}", err);
+            Vec::new()
+        });
+        let rule_revisions = Arc::new(backend_application::ops::RuleRevisionLog::new(
+            rule_revisions,
+        ));
+        let dead_letters = config_repo.load_dead_letters().await.unwrap_or_else(|err| {
+            warn!("failed to load dead letters: {}", err);
+            Vec::new()
+        });
+
+        let custom_detectors = CustomDetectorRegistry::with_builtins(&runtime_config);
+
+        let enrichment =
+            EnrichmentChain::from_names(&runtime_config.enrichers).map_err(anyhow::Error::msg)?;
+
+        let cluster_state: Option<Arc<dyn AnalyzerStateService>> = if runtime_config.cluster_mode
+            && !runtime_config.cluster_state_url.is_empty()
+        {
+            Some(Arc::new(HttpAnalyzerStateService::new(
+                &runtime_config.cluster_state_url,
+                runtime_config.api_token.clone(),
+                runtime_config.request_timeout_seconds,
+            )?))
+        } else {
+            None
+        };
+
+        let (event_publisher, mqtt_source) = match MqttBridge::start(&runtime_config)? {
+            Some(bridge) => (bridge.publisher, bridge.source),
+            None => (None, None),
+        };
+
+        let ingest_recorder = FileIngestRecorder::start(&runtime_config)
+            .await?
+            .map(|recorder| Arc::new(recorder) as Arc<dyn IngestRecorder>);
+
+        let event_dedup =
+            backend_application::ops::EventDedup::new(runtime_config.ingest_dedup_capacity);
+        let recent_anomalies =
+            backend_application::ops::RecentAnomalyBuffer::new(runtime_config.degraded_cache_size);
+        let dead_letters = backend_application::ops::DeadLetterQueue::new(
+            dead_letters,
+            runtime_config.dead_letter_max_events,
+        );
+
+        let report_service = Arc::new(HtmlReportService::new(
+            runtime_config.clone(),
+            repo.clone(),
+            repo.clone(),
+            rule_revisions.clone(),
+        ));
 
         let state = AppState {
             config: runtime_config,
             event_repo: repo.clone(),
-            anomaly_repo: repo,
+            anomaly_repo: repo.clone(),
+            maintenance_repo: repo,
             config_repo,
-            alert_service: Arc::new(DefaultAlertService::new()),
+            alert_service,
+            report_service,
+            event_publisher,
+            ingest_recorder,
             analyzer: Arc::new(Mutex::new(Analyzer::default())),
+            cluster_state,
+            custom_detectors: Arc::new(Mutex::new(custom_detectors)),
+            enrichment: Arc::new(Mutex::new(enrichment)),
             key_rules: Arc::new(RwLock::new(key_rules)),
+            detection_rules: Arc::new(RwLock::new(detection_rules)),
+            composite_rules: Arc::new(RwLock::new(composite_rules)),
             item_registry: Arc::new(RwLock::new(item_registry)),
             metrics: Arc::new(Metrics::default()),
             task_status: Arc::new(RwLock::new(TaskStatus::default())),
+            maintenance_status: Arc::new(RwLock::new(None)),
             mod_configs: Arc::new(RwLock::new(HashMap::new())),
             mod_config_acks: Arc::new(RwLock::new(HashMap::new())),
             mod_config_stream_hub: Arc::new(backend_application::ops::ModConfigStreamHub::default()),
+            ingest_tracker: Arc::new(backend_application::ops::IngestSourceTracker::default()),
+            event_dedup: Arc::new(event_dedup),
+            heartbeats: Arc::new(backend_application::ops::ServerHeartbeatRegistry::default()),
+            mod_version_gate: Arc::new(backend_application::ops::ModVersionGate::default()),
+            storage_findings: Arc::new(backend_application::ops::StorageFindingTracker::new(
+                storage_findings,
+            )),
+            suppressions: Arc::new(backend_application::ops::SuppressionRegistry::new(
+                suppressions,
+            )),
+            degraded: Arc::new(backend_application::ops::DegradedMode::default()),
+            clickhouse_watchdog: Arc::new(backend_application::ops::ClickhouseWatchdog::default()),
+            recent_anomalies: Arc::new(recent_anomalies),
+            anomaly_stream: Arc::new(backend_application::ops::AnomalyStreamHub::default()),
+            dead_letters: Arc::new(dead_letters),
+            data_drops: Arc::new(backend_application::ops::DataDropConfirmations::default()),
+            daily_quotas: Arc::new(backend_application::ops::DailyQuotaTracker::default()),
+            bans: Arc::new(backend_application::ops::BanRegistry::new(bans)),
+            quarantines: Arc::new(backend_application::ops::QuarantineRegistry::new(
+                quarantines,
+            )),
+            jobs: Arc::new(backend_application::ops::JobQueue::default()),
+            api_tokens: Arc::new(backend_application::ops::ApiTokenStore::new(api_tokens)),
+            player_teams: Arc::new(backend_application::ops::PlayerTeamRegistry::new(
+                player_teams,
+            )),
+            origin_whitelist: Arc::new(backend_application::ops::OriginWhitelistRegistry::new(
+                origin_whitelist,
+            )),
+            rule_revisions,
+            admin_secret: None,
         };
 
+        if let Some(source) = mqtt_source {
+            tokio::spawn(consume_event_source(state.clone(), Box::new(source), "mqtt"));
+        }
+
         Ok(Self { state })
     }
 }
+
+fn clickhouse_repo(db_config: &DbConfig) -> ClickhouseRepo {
+    let mut client = Client::default()
+        .with_url(&db_config.clickhouse_url)
+        .with_database(&db_config.clickhouse_database);
+    if let Some(user) = &db_config.clickhouse_user {
+        client = client.with_user(user);
+    }
+    if let Some(password) = &db_config.clickhouse_password {
+        client = client.with_password(password);
+    }
+    ClickhouseRepo::new(client, db_config.clickhouse_database.clone())
+        .with_insert_settings(db_config)
+        .with_dedup_events(db_config.clickhouse_dedup_events)
+}
+
+#[cfg(test)]
+mod tests {
+    use backend_application::testing::InMemoryApp;
+    use backend_domain::ports::AnalyzerStateService;
+    use backend_domain::testing::runtime_config;
+    use backend_domain::{ClusterAnalyzeRequest, RuntimeConfig};
+    use backend_infrastructure::HttpAnalyzerStateService;
+
+    #[tokio::test]
+    async fn replicas_pass_the_replay_check_of_the_state_instance() {
+        let app = InMemoryApp::new(RuntimeConfig {
+            cluster_mode: true,
+            replay_window_seconds: 300,
+            ..runtime_config()
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let router = backend_interfaces_http::build_router(app.state.clone());
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .await
+        });
+
+        let client =
+            HttpAnalyzerStateService::new(&format!("http://{}", addr), None, 5).expect("client");
+        let request: ClusterAnalyzeRequest = serde_json::from_value(serde_json::json!({
+            "transfer_window_ms": 60_000,
+            "key_item_window_ms": 60_000
+        }))
+        .expect("request");
+        // A second batch carries a fresh nonce, so it is not taken for a replay of the first.
+        for _ in 0..2 {
+            let anomalies = client.analyze(&request).await.expect("analyze");
+            assert!(anomalies.is_empty());
+        }
+    }
+}
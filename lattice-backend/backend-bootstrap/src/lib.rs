@@ -1,8 +1,18 @@
+mod clickhouse_watchdog;
 pub mod context;
 pub mod lifecycle;
+mod local_socket;
 mod napcat_bridge;
+mod tls;
 
-pub use lifecycle::{run_standalone, start_embedded, BackendHandle};
+pub use backend_domain::{AlertService, ConfigRepository};
+pub use backend_infrastructure::AppConfig;
+pub use backend_interfaces_http::{
+    ADMIN_SECRET_HEADER, REPLAY_NONCE_HEADER, REPLAY_TIMESTAMP_HEADER,
+};
+pub use lifecycle::{
+    run_standalone, start_embedded, BackendBuilder, BackendEndpoint, BackendHandle,
+};
 
 pub async fn run() -> anyhow::Result<()> {
     run_standalone().await
@@ -1,28 +1,96 @@
 use anyhow::{anyhow, Result};
+use axum::http::header;
 use axum::Router;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::mpsc;
+use std::sync::Arc;
 use std::time::Duration as StdDuration;
 use tokio::net::TcpListener;
 use tokio::sync::oneshot;
+use tower_http::compression::predicate::{And, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{error, info, warn};
 
+use backend_application::ops::AdminSecret;
+use backend_application::queries::config_queries;
 use backend_application::AppState;
-use backend_infrastructure::schedule_reports;
-use backend_interfaces_http::build_router;
+use backend_domain::{AlertService, ConfigRepository};
+use backend_infrastructure::{
+    monitor_config_files, monitor_dead_letters, monitor_ingest_staleness,
+    monitor_server_heartbeats, monitor_shadow_consistency, monitor_suppression_expiry,
+    monitor_system_rates, monitor_task_progress, schedule_maintenance, schedule_reports, AppConfig,
+    ConfigFileRepository, DefaultAlertService,
+};
+use backend_interfaces_grpc::serve_grpc;
+use backend_interfaces_http::{build_router, ENVELOPE_MEDIA_TYPE};
 
+use crate::clickhouse_watchdog::monitor_clickhouse;
 use crate::context::AppContext;
+use crate::local_socket::{LocalSocketListener, LOCAL_SOCKET_SCHEME};
 use crate::napcat_bridge::spawn_napcat_ws_bridge;
+use crate::tls::TlsListener;
+
+/// Where an embedded backend accepts requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendEndpoint {
+    Tcp(SocketAddr),
+    /// TCP with `tls_cert_path` / `tls_key_path` set.
+    Https(SocketAddr),
+    /// Unix socket path or Windows pipe name from `bind_socket`.
+    LocalSocket(String),
+}
+
+impl BackendEndpoint {
+    /// `http://host:port` or `https://host:port` for TCP, `unix:<path>` or `pipe:<name>` for a
+    /// local socket.
+    pub fn base_url(&self) -> String {
+        match self {
+            BackendEndpoint::Tcp(addr) => format!("http://{}", addr),
+            BackendEndpoint::Https(addr) => format!("https://{}", addr),
+            BackendEndpoint::LocalSocket(path) => format!("{}:{}", LOCAL_SOCKET_SCHEME, path),
+        }
+    }
+}
+
+/// What the worker thread reports once the embedded backend is listening.
+struct EmbeddedStartup {
+    endpoint: BackendEndpoint,
+    admin_secret: AdminSecret,
+}
 
 pub struct BackendHandle {
+    endpoint: BackendEndpoint,
+    admin_secret: AdminSecret,
     shutdown_tx: Option<oneshot::Sender<()>>,
     worker: Option<std::thread::JoinHandle<()>>,
 }
 
 impl BackendHandle {
+    pub fn endpoint(&self) -> &BackendEndpoint {
+        &self.endpoint
+    }
+
+    /// Value of the `X-Lattice-Admin-Secret` header that config-mutating endpoints of this
+    /// backend require besides the API token. Only the starting process learns it, and a new
+    /// one is issued on every start.
+    pub fn admin_secret(&self) -> &str {
+        self.admin_secret.as_str()
+    }
+
+    /// TCP address the backend is listening on, with the actual port when `bind_addr` used port 0;
+    /// `None` when it serves `bind_socket` instead.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.endpoint {
+            BackendEndpoint::Tcp(addr) | BackendEndpoint::Https(addr) => Some(*addr),
+            BackendEndpoint::LocalSocket(_) => None,
+        }
+    }
+
     pub fn stop(mut self) {
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
@@ -33,8 +101,47 @@ impl BackendHandle {
     }
 }
 
+/// Only compresses responses whose content type starts with one of the configured prefixes.
+#[derive(Clone)]
+struct ContentTypeAllowList(Arc<Vec<String>>);
+
+impl Predicate for ContentTypeAllowList {
+    fn should_compress<B>(&self, response: &axum::http::Response<B>) -> bool
+    where
+        B: axum::body::HttpBody,
+    {
+        let Some(content_type) = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return false;
+        };
+        let content_type = content_type.to_ascii_lowercase();
+        self.0.iter().any(|prefix| content_type.starts_with(prefix))
+    }
+}
+
+fn compression_layer(state: &AppState) -> CompressionLayer<And<SizeAbove, ContentTypeAllowList>> {
+    let mut content_types = state.config.response_compression_content_types.clone();
+    // Versioned detect/query responses are JSON under a vendor media type.
+    if content_types.iter().any(|prefix| prefix == "application/json") {
+        content_types.push(ENVELOPE_MEDIA_TYPE.to_string());
+    }
+    CompressionLayer::new().compress_when(
+        SizeAbove::new(state.config.response_compression_min_bytes)
+            .and(ContentTypeAllowList(Arc::new(content_types))),
+    )
+}
+
 fn build_router_with_layers(state: AppState) -> Router {
-    build_router(state.clone())
+    let router = build_router(state.clone());
+    let router = if state.config.response_compression_enabled {
+        router.layer(compression_layer(&state))
+    } else {
+        router
+    };
+    router
         .layer(CorsLayer::permissive())
         .layer(RequestBodyLimitLayer::new(
             usize::try_from(state.config.max_body_bytes).unwrap_or(usize::MAX),
@@ -45,32 +152,217 @@ fn build_router_with_layers(state: AppState) -> Router {
         .layer(TraceLayer::new_for_http())
 }
 
+/// Logs the effective configuration as one structured line, then each config warning.
+async fn log_startup_summary(state: AppState) {
+    let config = &state.config;
+    let bind = if config.bind_socket.is_empty() {
+        &config.bind_addr
+    } else {
+        &config.bind_socket
+    };
+    let key_item_rules = state.key_rules.read().await.len();
+    info!(
+        version = env!("CARGO_PKG_VERSION"),
+        config_path = config.config_path.as_deref().unwrap_or("<defaults>"),
+        bind = %bind,
+        tls = !config.tls_cert_path.is_empty(),
+        api_token = config.api_token.is_some(),
+        strict_enabled = config.strict_enabled,
+        alert_webhook = config.alert_webhook_url.is_some(),
+        report_dir = %config.report_dir,
+        cluster_mode = config.cluster_mode,
+        key_item_rules,
+        "lattice backend starting"
+    );
+    for warning in config_queries::config_warnings(&state).await {
+        warn!(code = %warning.code, key = %warning.key, "{}", warning.message);
+    }
+}
+
+fn spawn_background_tasks(state: &AppState) {
+    tokio::spawn(log_startup_summary(state.clone()));
+    tokio::spawn(schedule_reports(state.clone()));
+    tokio::spawn(schedule_maintenance(state.clone()));
+    tokio::spawn(monitor_shadow_consistency(state.clone()));
+    tokio::spawn(monitor_ingest_staleness(state.clone()));
+    tokio::spawn(monitor_server_heartbeats(state.clone()));
+    tokio::spawn(monitor_task_progress(state.clone()));
+    tokio::spawn(monitor_system_rates(state.clone()));
+    tokio::spawn(monitor_suppression_expiry(state.clone()));
+    tokio::spawn(monitor_dead_letters(state.clone()));
+    tokio::spawn(monitor_clickhouse(state.clone()));
+    tokio::spawn(monitor_config_files(state.clone()));
+    spawn_napcat_ws_bridge(state.clone());
+    if let Ok(addr) = state.config.grpc_bind_addr.parse::<SocketAddr>() {
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(err) = serve_grpc(state, addr).await {
+                error!("grpc server on {} stopped: {}", addr, err);
+            }
+        });
+    }
+}
+
 pub async fn run_standalone() -> Result<()> {
     let context = AppContext::new().await?;
     let state = context.state;
 
-    tokio::spawn(schedule_reports(state.clone()));
-    spawn_napcat_ws_bridge(state.clone());
+    spawn_background_tasks(&state);
 
     let app = build_router_with_layers(state.clone());
+    if !state.config.bind_socket.is_empty() {
+        let listener = LocalSocketListener::bind(&state.config.bind_socket)?;
+        info!("listening on {}", state.config.bind_socket);
+        return listener.serve(app, shutdown_signal()).await;
+    }
     let addr: std::net::SocketAddr = state.config.bind_addr.parse()?;
     let listener = TcpListener::bind(addr).await?;
+    if !state.config.tls_cert_path.is_empty() {
+        let listener = TlsListener::new(
+            listener,
+            &state.config.tls_cert_path,
+            &state.config.tls_key_path,
+        )?;
+        info!("listening on https://{}", addr);
+        return listener.serve(app, shutdown_signal()).await;
+    }
     info!("listening on {}", addr);
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
     Ok(())
 }
 
+/// Where an embedded backend takes its configuration from. Nothing here touches process-wide
+/// environment variables, so several embedded backends can run side by side in one process.
+enum ConfigSource {
+    Path(PathBuf),
+    Config(Box<AppConfig>),
+}
+
+/// Public entry point for running Lattice inside another Rust application.
+///
+/// The backend runs on its own thread with its own tokio runtime and is stopped through the
+/// returned [`BackendHandle`]. Configuration comes either from a config file or from an
+/// [`AppConfig`] built in code, so nothing has to be written to disk. Setting `bind_addr` to port
+/// `0` lets the OS pick a free port; [`BackendHandle::local_addr`] reports it. Setting
+/// `bind_socket` serves a Unix socket (named pipe on Windows) instead of TCP.
+///
+/// ```no_run
+/// use backend_bootstrap::{AppConfig, BackendBuilder};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let config = AppConfig {
+///     bind_addr: "127.0.0.1:0".to_string(),
+///     clickhouse_url: "http://127.0.0.1:8123".to_string(),
+///     ..AppConfig::default()
+/// };
+/// let handle = BackendBuilder::from_config(config)
+///     .config_dir("/var/lib/my-app/lattice")
+///     .start()?;
+/// println!("lattice listening on {}", handle.endpoint().base_url());
+/// handle.stop();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`BackendBuilder::alert_service`] and [`BackendBuilder::config_repository`] swap in custom
+/// implementations of the domain ports, e.g. to route alerts into the host application.
+pub struct BackendBuilder {
+    source: ConfigSource,
+    config_dir: Option<PathBuf>,
+    config_repo: Option<Arc<dyn ConfigRepository>>,
+    alert_service: Option<Arc<dyn AlertService>>,
+}
+
+impl BackendBuilder {
+    /// Reads the config file at `path` (plus `LATTICE_*` value overrides); sibling files (rcon,
+    /// mod-config, suppressions, ...) are kept in the same directory.
+    pub fn from_config_path(path: impl Into<PathBuf>) -> Self {
+        Self::with_source(ConfigSource::Path(path.into()))
+    }
+
+    /// Uses a config built in code. It is normalized and validated like a loaded file, but
+    /// environment overrides are not applied. Sibling files go next to `config.config_path` when
+    /// set and into the current directory otherwise.
+    pub fn from_config(config: AppConfig) -> Self {
+        Self::with_source(ConfigSource::Config(Box::new(config)))
+    }
+
+    fn with_source(source: ConfigSource) -> Self {
+        Self {
+            source,
+            config_dir: None,
+            config_repo: None,
+            alert_service: None,
+        }
+    }
+
+    /// Overrides the directory for rcon, mod-config, storage-finding and suppression files.
+    /// Ignored when a custom [`ConfigRepository`] is set.
+    pub fn config_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config_dir = Some(dir.into());
+        self
+    }
+
+    /// Replaces the file-backed config store.
+    pub fn config_repository(mut self, repo: Arc<dyn ConfigRepository>) -> Self {
+        self.config_repo = Some(repo);
+        self
+    }
+
+    /// Replaces the built-in webhook/NapCat alert delivery.
+    pub fn alert_service(mut self, service: Arc<dyn AlertService>) -> Self {
+        self.alert_service = Some(service);
+        self
+    }
+
+    /// Starts the backend and waits until it is listening (or failed to start).
+    pub fn start(self) -> Result<BackendHandle> {
+        start_with_builder(self)
+    }
+
+    async fn build_context(self) -> Result<AppContext> {
+        let (config, config_files) = match self.source {
+            ConfigSource::Path(path) => (
+                AppConfig::load_from(&path).await?,
+                ConfigFileRepository::for_config_path(&path),
+            ),
+            ConfigSource::Config(config) => {
+                let mut config = *config;
+                config.normalize();
+                config.validate()?;
+                let config_files = match &config.config_path {
+                    Some(path) => ConfigFileRepository::for_config_path(path),
+                    None => ConfigFileRepository::with_config_dir("."),
+                };
+                (config, config_files)
+            }
+        };
+        let config_files = match self.config_dir {
+            Some(dir) => ConfigFileRepository::with_config_dir(dir),
+            None => config_files,
+        };
+        let config_repo = self.config_repo.unwrap_or_else(|| Arc::new(config_files));
+        let alert_service = self
+            .alert_service
+            .unwrap_or_else(|| Arc::new(DefaultAlertService::new()));
+        AppContext::from_config(config, config_repo, alert_service).await
+    }
+}
+
+/// Starts an embedded backend from the config file at `config_path`.
 pub fn start_embedded(config_path: impl AsRef<std::path::Path>) -> Result<BackendHandle> {
-    std::env::set_var(
-        "LATTICE_CONFIG",
-        config_path.as_ref().to_string_lossy().to_string(),
-    );
+    BackendBuilder::from_config_path(config_path.as_ref()).start()
+}
 
+fn start_with_builder(builder: BackendBuilder) -> Result<BackendHandle> {
     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
-    let (startup_tx, startup_rx) = mpsc::channel::<std::result::Result<(), String>>();
+    let (startup_tx, startup_rx) = mpsc::channel::<std::result::Result<EmbeddedStartup, String>>();
     let worker = std::thread::Builder::new()
         .name("lattice-backend".to_string())
         .spawn(move || {
@@ -91,14 +383,17 @@ pub fn start_embedded(config_path: impl AsRef<std::path::Path>) -> Result<Backen
             };
 
             runtime.block_on(async move {
-                if let Err(err) = run_embedded_with_shutdown(shutdown_rx, startup_tx).await {
+                if let Err(err) = run_embedded_with_shutdown(builder, shutdown_rx, startup_tx).await
+                {
                     eprintln!("embedded backend exited: {err}");
                 }
             });
         })?;
 
     match startup_rx.recv_timeout(StdDuration::from_secs(10)) {
-        Ok(Ok(())) => Ok(BackendHandle {
+        Ok(Ok(startup)) => Ok(BackendHandle {
+            endpoint: startup.endpoint,
+            admin_secret: startup.admin_secret,
             shutdown_tx: Some(shutdown_tx),
             worker: Some(worker),
         }),
@@ -121,22 +416,45 @@ pub fn start_embedded(config_path: impl AsRef<std::path::Path>) -> Result<Backen
 }
 
 async fn run_embedded_with_shutdown(
+    builder: BackendBuilder,
     mut shutdown_rx: oneshot::Receiver<()>,
-    startup_tx: mpsc::Sender<std::result::Result<(), String>>,
+    startup_tx: mpsc::Sender<std::result::Result<EmbeddedStartup, String>>,
 ) -> Result<()> {
-    let context = match AppContext::new().await {
+    let context = match builder.build_context().await {
         Ok(context) => context,
         Err(err) => {
             let _ = startup_tx.send(Err(format!("context init failed: {}", err)));
             return Err(err);
         }
     };
-    let state = context.state;
+    let mut state = context.state;
+    let admin_secret = AdminSecret::generate();
+    state.admin_secret = Some(admin_secret.clone());
 
-    tokio::spawn(schedule_reports(state.clone()));
-    spawn_napcat_ws_bridge(state.clone());
+    spawn_background_tasks(&state);
 
     let app = build_router_with_layers(state.clone());
+    if !state.config.bind_socket.is_empty() {
+        let socket = state.config.bind_socket.clone();
+        let listener = match LocalSocketListener::bind(&socket) {
+            Ok(listener) => listener,
+            Err(err) => {
+                let message = format!("failed to bind socket {}: {}", socket, err);
+                let _ = startup_tx.send(Err(message.clone()));
+                return Err(anyhow!(message));
+            }
+        };
+        let _ = startup_tx.send(Ok(EmbeddedStartup {
+            endpoint: BackendEndpoint::LocalSocket(socket.clone()),
+            admin_secret,
+        }));
+        info!("embedded backend listening on {}", socket);
+        return listener
+            .serve(app, async move {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+    }
     let addr: std::net::SocketAddr = match state.config.bind_addr.parse() {
         Ok(addr) => addr,
         Err(err) => {
@@ -153,14 +471,42 @@ async fn run_embedded_with_shutdown(
             return Err(anyhow!(message));
         }
     };
-    let _ = startup_tx.send(Ok(()));
-    info!("embedded backend listening on {}", addr);
-
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async move {
-            let _ = (&mut shutdown_rx).await;
-        })
-        .await?;
+    let local_addr = listener.local_addr().unwrap_or(addr);
+    if !state.config.tls_cert_path.is_empty() {
+        let config = &state.config;
+        let listener = match TlsListener::new(listener, &config.tls_cert_path, &config.tls_key_path)
+        {
+            Ok(listener) => listener,
+            Err(err) => {
+                let _ = startup_tx.send(Err(err.to_string()));
+                return Err(err);
+            }
+        };
+        let _ = startup_tx.send(Ok(EmbeddedStartup {
+            endpoint: BackendEndpoint::Https(local_addr),
+            admin_secret,
+        }));
+        info!("embedded backend listening on https://{}", local_addr);
+        return listener
+            .serve(app, async move {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+    }
+    let _ = startup_tx.send(Ok(EmbeddedStartup {
+        endpoint: BackendEndpoint::Tcp(local_addr),
+        admin_secret,
+    }));
+    info!("embedded backend listening on {}", local_addr);
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        let _ = (&mut shutdown_rx).await;
+    })
+    .await?;
     Ok(())
 }
 
@@ -0,0 +1,134 @@
+//! Serves the router over a Unix domain socket (named pipe on Windows) instead of a TCP port, so
+//! an embedded backend neither competes for ports nor is reachable by other local users.
+
+use std::future::Future;
+
+use anyhow::Result;
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder as ConnectionBuilder;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::debug;
+
+/// Base URL scheme the desktop shell uses to address a socket-bound backend.
+#[cfg(windows)]
+pub const LOCAL_SOCKET_SCHEME: &str = "pipe";
+#[cfg(not(windows))]
+pub const LOCAL_SOCKET_SCHEME: &str = "unix";
+
+pub(crate) fn serve_connection<S>(app: &Router, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let service = TowerToHyperService::new(app.clone());
+    tokio::spawn(async move {
+        if let Err(err) = ConnectionBuilder::new(TokioExecutor::new())
+            .serve_connection_with_upgrades(TokioIo::new(stream), service)
+            .await
+        {
+            debug!("local socket connection closed with error: {}", err);
+        }
+    });
+}
+
+#[cfg(unix)]
+pub struct LocalSocketListener {
+    listener: tokio::net::UnixListener,
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl LocalSocketListener {
+    /// Binds `path`, replacing a stale socket left by a previous run, and restricts it to the
+    /// current user.
+    pub fn bind(path: &str) -> Result<Self> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+        let path = std::path::PathBuf::from(path);
+        if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+            if !metadata.file_type().is_socket() {
+                anyhow::bail!("{} exists and is not a socket", path.display());
+            }
+            std::fs::remove_file(&path)?;
+        }
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let listener = tokio::net::UnixListener::bind(&path)?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        Ok(Self { listener, path })
+    }
+
+    pub async fn serve(self, app: Router, shutdown: impl Future<Output = ()>) -> Result<()> {
+        tokio::pin!(shutdown);
+        let result = loop {
+            let stream = tokio::select! {
+                accepted = self.listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(err) => break Err(err.into()),
+                },
+                _ = &mut shutdown => break Ok(()),
+            };
+            serve_connection(&app, stream);
+        };
+        let _ = std::fs::remove_file(&self.path);
+        result
+    }
+}
+
+#[cfg(windows)]
+pub struct LocalSocketListener {
+    server: tokio::net::windows::named_pipe::NamedPipeServer,
+    name: String,
+}
+
+#[cfg(windows)]
+impl LocalSocketListener {
+    /// Creates the first instance of pipe `name`; fails when another process already owns it.
+    pub fn bind(name: &str) -> Result<Self> {
+        let server = tokio::net::windows::named_pipe::ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(name)?;
+        Ok(Self {
+            server,
+            name: name.to_string(),
+        })
+    }
+
+    pub async fn serve(mut self, app: Router, shutdown: impl Future<Output = ()>) -> Result<()> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                connected = self.server.connect() => connected?,
+                _ = &mut shutdown => return Ok(()),
+            }
+            // A pipe instance serves one client, so open the next one before handing this off.
+            let next = ServerOptions::new()
+                .reject_remote_clients(true)
+                .create(&self.name)?;
+            let stream = std::mem::replace(&mut self.server, next);
+            serve_connection(&app, stream);
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub struct LocalSocketListener;
+
+#[cfg(not(any(unix, windows)))]
+impl LocalSocketListener {
+    pub fn bind(_path: &str) -> Result<Self> {
+        anyhow::bail!("bind_socket is not supported on this platform")
+    }
+
+    pub async fn serve(self, _app: Router, _shutdown: impl Future<Output = ()>) -> Result<()> {
+        Ok(())
+    }
+}
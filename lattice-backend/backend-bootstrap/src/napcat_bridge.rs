@@ -1,8 +1,9 @@
 use anyhow::Result;
 use axum::http::header::AUTHORIZATION;
-use backend_application::commands::op_token_commands;
+use backend_application::commands::{alert_page_commands, chat_ack_commands, op_token_commands};
 use backend_application::AppState;
 use backend_domain::OpTokenIssueRequest;
+use backend_infrastructure::{connect_alert_ws, AlertWebSocket};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
 use std::time::Duration;
@@ -19,10 +20,11 @@ pub fn spawn_napcat_ws_bridge(state: AppState) {
         return;
     };
     let ws_token = state.config.alert_webhook_token.clone();
+    let ws_proxy = state.config.alert_webhook_proxy.clone();
 
     tokio::spawn(async move {
         loop {
-            match connect_ws(&ws_url, ws_token.as_deref()).await {
+            match connect_ws(&ws_url, ws_token.as_deref(), ws_proxy.as_deref()).await {
                 Ok((mut ws, mode)) => {
                     info!("napcat ws bridge connected: url={}, mode={}", ws_url, mode);
                     if let Err(err) = run_bridge_loop(&state, &mut ws).await {
@@ -41,12 +43,8 @@ pub fn spawn_napcat_ws_bridge(state: AppState) {
 async fn connect_ws(
     ws_url: &str,
     token: Option<&str>,
-) -> Result<(
-    tokio_tungstenite::WebSocketStream<
-        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-    >,
-    &'static str,
-)> {
+    proxy: Option<&str>,
+) -> Result<(AlertWebSocket, &'static str)> {
     let mut request = ws_url.into_client_request()?;
     if let Some(value) = token.filter(|raw| !raw.trim().is_empty()) {
         request
@@ -54,45 +52,45 @@ async fn connect_ws(
             .insert(AUTHORIZATION, format!("Bearer {}", value).parse()?);
     }
 
-    if let Ok((socket, _)) = tokio_tungstenite::connect_async(request).await {
+    if let Ok((socket, _)) = connect_alert_ws(request, proxy).await {
         return Ok((socket, "header"));
     }
 
     let with_query = add_access_token_query(ws_url, token);
     let query_request = with_query.into_client_request()?;
-    if let Ok((socket, _)) = tokio_tungstenite::connect_async(query_request).await {
+    if let Ok((socket, _)) = connect_alert_ws(query_request, proxy).await {
         return Ok((socket, "query"));
     }
 
     let plain_request = ws_url.into_client_request()?;
-    let (socket, _) = tokio_tungstenite::connect_async(plain_request).await?;
+    let (socket, _) = connect_alert_ws(plain_request, proxy).await?;
     Ok((socket, "plain"))
 }
 
-async fn run_bridge_loop(
-    state: &AppState,
-    ws: &mut tokio_tungstenite::WebSocketStream<
-        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-    >,
-) -> Result<()> {
+async fn run_bridge_loop(state: &AppState, ws: &mut AlertWebSocket) -> Result<()> {
     while let Some(next) = ws.next().await {
         match next {
             Ok(Message::Text(text)) => {
                 let Some(event) = parse_group_message_event(text.as_ref()) else {
                     continue;
                 };
-                if !is_issue_token_command(&event.command_text) {
+                let reply = if is_issue_token_command(&event.command_text) {
+                    let request = OpTokenIssueRequest {
+                        server_id: None,
+                        operator_id: event.user_id.map(|value| value.to_string()),
+                        group_id: Some(event.group_id.to_string()),
+                    };
+                    match op_token_commands::issue_op_token(state, request).await {
+                        Ok(issued) => op_token_commands::build_issue_success_message(&issued),
+                        Err(err) => op_token_commands::build_issue_failure_message(&err),
+                    }
+                } else if alert_page_commands::is_more_alerts_command(&event.command_text) {
+                    alert_page_commands::next_alert_page_reply(state, event.group_id).await
+                } else if let Some(target) = chat_ack_commands::parse_ack_command(&event.command_text) {
+                    chat_ack_commands::chat_ack_reply(state, event.group_id, event.user_id, target)
+                        .await
+                } else {
                     continue;
-                }
-
-                let request = OpTokenIssueRequest {
-                    server_id: None,
-                    operator_id: event.user_id.map(|value| value.to_string()),
-                    group_id: Some(event.group_id.to_string()),
-                };
-                let reply = match op_token_commands::issue_op_token(state, request).await {
-                    Ok(issued) => op_token_commands::build_issue_success_message(&issued),
-                    Err(err) => op_token_commands::build_issue_failure_message(&err),
                 };
 
                 let action_echo = format!(
@@ -0,0 +1,168 @@
+//! Serves the router over HTTPS on the TCP listener when `tls_cert_path` and `tls_key_path` are
+//! set, so a backend reachable from other hosts needs no reverse proxy in front of it.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use axum::extract::ConnectInfo;
+use axum::{Extension, Router};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+use crate::local_socket::serve_connection;
+
+/// How often the certificate files are checked for replacement.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A client that has not finished its handshake by then is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The PEM files a certificate is loaded from, and reloaded when either changes.
+struct CertificateFiles {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    provider: Arc<CryptoProvider>,
+}
+
+impl CertificateFiles {
+    fn modified(&self) -> Option<(SystemTime, SystemTime)> {
+        let cert = std::fs::metadata(&self.cert_path).and_then(|meta| meta.modified());
+        let key = std::fs::metadata(&self.key_path).and_then(|meta| meta.modified());
+        Some((cert.ok()?, key.ok()?))
+    }
+
+    /// Reads the chain and key, rejecting an empty chain or a key that does not match it.
+    fn load(&self) -> Result<Arc<CertifiedKey>> {
+        let chain = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| {
+                anyhow!(
+                    "failed to read tls_cert_path {}: {}",
+                    self.cert_path.display(),
+                    err
+                )
+            })?;
+        if chain.is_empty() {
+            anyhow::bail!(
+                "tls_cert_path {} holds no certificate",
+                self.cert_path.display()
+            );
+        }
+        let key = PrivateKeyDer::from_pem_file(&self.key_path).map_err(|err| {
+            anyhow!(
+                "failed to read tls_key_path {}: {}",
+                self.key_path.display(),
+                err
+            )
+        })?;
+        let certified = CertifiedKey::from_der(chain, key, &self.provider)
+            .map_err(|err| anyhow!("tls_key_path does not fit tls_cert_path: {}", err))?;
+        Ok(Arc::new(certified))
+    }
+}
+
+/// Hands every handshake the certificate loaded last.
+#[derive(Debug)]
+struct ReloadingCertResolver(RwLock<Arc<CertifiedKey>>);
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.0.read().ok().map(|key| Arc::clone(&key))
+    }
+}
+
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    files: CertificateFiles,
+    resolver: Arc<ReloadingCertResolver>,
+}
+
+impl TlsListener {
+    /// Loads the certificate up front, so a bad `tls_cert_path` or `tls_key_path` fails startup
+    /// instead of every handshake.
+    pub fn new(listener: TcpListener, cert_path: &str, key_path: &str) -> Result<Self> {
+        let files = CertificateFiles {
+            cert_path: PathBuf::from(cert_path),
+            key_path: PathBuf::from(key_path),
+            provider: Arc::new(ring::default_provider()),
+        };
+        let resolver = Arc::new(ReloadingCertResolver(RwLock::new(files.load()?)));
+        let mut config = ServerConfig::builder_with_provider(Arc::clone(&files.provider))
+            .with_safe_default_protocol_versions()
+            .map_err(|err| anyhow!("tls setup failed: {}", err))?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver.clone());
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Self {
+            listener,
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            files,
+            resolver,
+        })
+    }
+
+    pub async fn serve(self, app: Router, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let reload = tokio::spawn(watch_certificate(self.files, self.resolver));
+        tokio::pin!(shutdown);
+        loop {
+            let (stream, peer) = tokio::select! {
+                accepted = self.listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        // Mostly descriptor exhaustion; give connections a moment to close.
+                        warn!("tls accept failed: {}", err);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                },
+                _ = &mut shutdown => break,
+            };
+            let acceptor = self.acceptor.clone();
+            let app = app.clone().layer(Extension(ConnectInfo(peer)));
+            tokio::spawn(async move {
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => serve_connection(&app, stream),
+                    Ok(Err(err)) => debug!("tls handshake with {} failed: {}", peer, err),
+                    Err(_) => debug!("tls handshake with {} timed out", peer),
+                }
+            });
+        }
+        reload.abort();
+        Ok(())
+    }
+}
+
+/// Swaps in the certificate whenever its files change. A load that fails (e.g. the key is not
+/// replaced yet) keeps the current certificate and is retried on the next check.
+async fn watch_certificate(files: CertificateFiles, resolver: Arc<ReloadingCertResolver>) {
+    let mut loaded = files.modified();
+    let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = files.modified();
+        if current.is_none() || current == loaded {
+            continue;
+        }
+        match files.load() {
+            Ok(key) => {
+                if let Ok(mut slot) = resolver.0.write() {
+                    *slot = key;
+                }
+                loaded = current;
+                info!("reloaded TLS certificate {}", files.cert_path.display());
+            }
+            Err(err) => warn!("keeping the current TLS certificate: {}", err),
+        }
+    }
+}
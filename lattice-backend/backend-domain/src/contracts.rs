@@ -0,0 +1,4 @@
+// Wire contracts published for clients
+pub mod ingest_samples;
+
+pub use ingest_samples::*;
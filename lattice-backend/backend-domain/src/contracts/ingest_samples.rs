@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::entities::{IngestEnvelope, IngestEvent, CUSTOM_EVENT_FAMILY, INGEST_SCHEMA_VERSION};
+
+/// 2026-03-01T16:30:00Z, so the samples never change between runs.
+const SAMPLE_EVENT_TIME: i64 = 1_772_382_600_000;
+
+/// One example body for `POST /v2/ingest/events` and the status the backend answers it with:
+/// `200` accepted, `204` every event filtered as invalid, `400` rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngestContractSample {
+    pub name: String,
+    pub description: String,
+    pub status: u16,
+    pub envelope: Value,
+}
+
+/// `GET /v2/ingest/contract-samples`, also checked in as `contracts/ingest-v2.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngestContractSamples {
+    pub schema_version: String,
+    pub samples: Vec<IngestContractSample>,
+}
+
+/// Canonical ingest envelopes built from the domain types, so a mod build can check that what
+/// it sends is what this backend accepts.
+pub fn ingest_contract_samples() -> IngestContractSamples {
+    let acquire = sample_acquire();
+    let samples = vec![
+        sample(
+            "acquire_full",
+            "ACQUIRE with every field the mod fills in",
+            200,
+            envelope(Some("survival-01"), vec![acquire.clone()]),
+        ),
+        sample(
+            "acquire_minimal",
+            "event_id, event_time and event_type are required; item_id and count make it analyzable",
+            200,
+            json!({
+                "schema_version": INGEST_SCHEMA_VERSION,
+                "server_id": "survival-01",
+                "events": [{
+                    "event_id": "",
+                    "event_time": SAMPLE_EVENT_TIME,
+                    "event_type": "ACQUIRE",
+                    "item_id": "minecraft:diamond",
+                    "count": 1,
+                }],
+            }),
+        ),
+        sample(
+            "transfer",
+            "TRANSFER into a storage block",
+            200,
+            envelope(
+                Some("survival-01"),
+                vec![IngestEvent {
+                    event_type: "TRANSFER".to_string(),
+                    origin_type: None,
+                    origin_ref: None,
+                    source_type: Some("container".to_string()),
+                    source_ref: Some("minecraft:chest".to_string()),
+                    ..acquire.clone()
+                }],
+            ),
+        ),
+        sample(
+            "server_id_per_event",
+            "no envelope server_id; each event carries its own",
+            200,
+            envelope(None, vec![acquire.clone()]),
+        ),
+        sample(
+            "custom_event",
+            "custom family event with custom_type and payload instead of an item",
+            200,
+            envelope(
+                Some("survival-01"),
+                vec![IngestEvent {
+                    event_type: "CUSTOM".to_string(),
+                    item_id: String::new(),
+                    count: 0,
+                    family: Some(CUSTOM_EVENT_FAMILY.to_string()),
+                    custom_type: Some("economy.trade".to_string()),
+                    payload: Some(json!({ "price": 120, "currency": "emerald" })),
+                    ..acquire.clone()
+                }],
+            ),
+        ),
+        sample(
+            "air_item",
+            "minecraft:air is dropped as invalid",
+            204,
+            envelope(
+                Some("survival-01"),
+                vec![IngestEvent {
+                    item_id: "minecraft:air".to_string(),
+                    ..acquire.clone()
+                }],
+            ),
+        ),
+        sample(
+            "zero_count",
+            "item events need a positive count",
+            204,
+            envelope(
+                Some("survival-01"),
+                vec![IngestEvent {
+                    count: 0,
+                    ..acquire.clone()
+                }],
+            ),
+        ),
+        sample(
+            "custom_without_type",
+            "custom events need a custom_type",
+            204,
+            envelope(
+                Some("survival-01"),
+                vec![IngestEvent {
+                    family: Some(CUSTOM_EVENT_FAMILY.to_string()),
+                    ..acquire.clone()
+                }],
+            ),
+        ),
+        sample(
+            "wrong_schema_version",
+            "only schema_version v2 is accepted",
+            400,
+            json!({
+                "schema_version": "v1",
+                "server_id": "survival-01",
+                "events": [to_value(&acquire)],
+            }),
+        ),
+        sample(
+            "missing_event_time",
+            "event_time is required",
+            400,
+            without_field(
+                envelope(Some("survival-01"), vec![acquire.clone()]),
+                "event_time",
+            ),
+        ),
+        sample(
+            "count_as_string",
+            "count must be a JSON number",
+            400,
+            with_field(
+                envelope(Some("survival-01"), vec![acquire]),
+                "count",
+                json!("64"),
+            ),
+        ),
+    ];
+    IngestContractSamples {
+        schema_version: INGEST_SCHEMA_VERSION.to_string(),
+        samples,
+    }
+}
+
+fn sample_acquire() -> IngestEvent {
+    IngestEvent {
+        event_id: String::new(),
+        event_time: SAMPLE_EVENT_TIME,
+        server_id: Some("survival-01".to_string()),
+        event_type: "ACQUIRE".to_string(),
+        player_uuid: Some("8667ba71-b85a-4004-af54-457a9734eed7".to_string()),
+        player_name: Some("Steve".to_string()),
+        item_id: "minecraft:diamond".to_string(),
+        count: 64,
+        nbt_hash: Some("0".to_string()),
+        origin_id: Some("0b6c2a56-2f1e-4d3c-9a8b-7c6d5e4f3a2b".to_string()),
+        origin_type: Some("mob_drop".to_string()),
+        origin_ref: Some("minecraft:zombie".to_string()),
+        source_type: Some("pickup".to_string()),
+        source_ref: Some(String::new()),
+        storage_mod: None,
+        storage_id: None,
+        actor_type: Some("player".to_string()),
+        trace_id: Some("trace-0001".to_string()),
+        item_fingerprint: Some("minecraft:diamond|0|0b6c2a56".to_string()),
+        dim: Some("minecraft:overworld".to_string()),
+        x: Some(120),
+        y: Some(64),
+        z: Some(-35),
+        family: None,
+        custom_type: None,
+        payload: None,
+        item_name: None,
+        rule_risk_level: None,
+    }
+}
+
+/// Gives every event an id of its own, so posting all samples to one backend does not trip
+/// event id dedup.
+fn sample(name: &str, description: &str, status: u16, mut envelope: Value) -> IngestContractSample {
+    if let Some(events) = envelope["events"].as_array_mut() {
+        for (index, event) in events.iter_mut().enumerate() {
+            if event.get("event_id").is_some() {
+                event["event_id"] = format!("sample-{}-{}", name, index).into();
+            }
+        }
+    }
+    IngestContractSample {
+        name: name.to_string(),
+        description: description.to_string(),
+        status,
+        envelope,
+    }
+}
+
+fn envelope(server_id: Option<&str>, events: Vec<IngestEvent>) -> Value {
+    to_value(&IngestEnvelope {
+        schema_version: INGEST_SCHEMA_VERSION.to_string(),
+        server_id: server_id.map(ToString::to_string),
+        events: events
+            .into_iter()
+            .map(|event| IngestEvent {
+                server_id: if server_id.is_some() {
+                    None
+                } else {
+                    event.server_id
+                },
+                ..event
+            })
+            .collect(),
+    })
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("ingest types serialize to JSON")
+}
+
+fn first_event(envelope: &mut Value) -> &mut serde_json::Map<String, Value> {
+    envelope["events"][0]
+        .as_object_mut()
+        .expect("sample envelopes carry an event")
+}
+
+fn without_field(mut envelope: Value, field: &str) -> Value {
+    first_event(&mut envelope).remove(field);
+    envelope
+}
+
+fn with_field(mut envelope: Value, field: &str, value: Value) -> Value {
+    first_event(&mut envelope).insert(field.to_string(), value);
+    envelope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_fixture_matches_the_generated_samples() {
+        let published: IngestContractSamples =
+            serde_json::from_str(include_str!("../../../contracts/ingest-v2.json"))
+                .expect("contracts/ingest-v2.json parses");
+        assert_eq!(
+            published,
+            ingest_contract_samples(),
+            "ingest samples changed: regenerate contracts/ingest-v2.json from GET /v2/ingest/contract-samples"
+        );
+    }
+
+    #[test]
+    fn samples_parse_the_way_their_status_says() {
+        for sample in ingest_contract_samples().samples {
+            let parsed = serde_json::from_value::<IngestEnvelope>(sample.envelope)
+                .map_err(|err| err.to_string())
+                .and_then(IngestEnvelope::into_events);
+            assert_eq!(parsed.is_err(), sample.status == 400, "{}", sample.name);
+            if let Ok(events) = parsed {
+                assert!(events.iter().all(|event| event.server_id.is_some()));
+            }
+        }
+    }
+}
@@ -13,6 +13,44 @@ pub struct KeyItemRule {
     pub risk_level: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub weight: Option<u8>,
+    /// Hard cap on how many of this item one player may acquire per local day (R14).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_quota: Option<u64>,
+    /// What `threshold` counts; `items` when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<ThresholdUnit>,
+    /// Items per stack for `stacks`; the registry's `max_stack_size`, then 64, when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_size: Option<u32>,
+    /// Value units one item is worth for `value`; 1 when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_value: Option<f64>,
+}
+
+/// Stack size assumed for items the rule and the registry say nothing about.
+pub const DEFAULT_STACK_SIZE: u32 = 64;
+
+/// What a key item rule's windowed threshold (R4) counts.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ThresholdUnit {
+    #[default]
+    Items,
+    /// Full stacks of `stack_size` items.
+    Stacks,
+    /// Items weighted by `unit_value`.
+    Value,
+}
+
+impl ThresholdUnit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThresholdUnit::Items => "items",
+            ThresholdUnit::Stacks => "stacks",
+            ThresholdUnit::Value => "value",
+        }
+    }
 }
 
 impl KeyItemRule {
@@ -20,6 +58,43 @@ impl KeyItemRule {
         self.threshold.or(self.max_per_10m).unwrap_or_default()
     }
 
+    pub fn threshold_unit(&self) -> ThresholdUnit {
+        self.unit.unwrap_or_default()
+    }
+
+    pub fn effective_stack_size(&self) -> u32 {
+        self.stack_size
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_STACK_SIZE)
+    }
+
+    pub fn effective_unit_value(&self) -> f64 {
+        self.unit_value
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// `items` of this rule's item in the rule's threshold unit.
+    pub fn units(&self, items: i64) -> f64 {
+        match self.threshold_unit() {
+            ThresholdUnit::Items => items as f64,
+            ThresholdUnit::Stacks => items as f64 / f64::from(self.effective_stack_size()),
+            ThresholdUnit::Value => items as f64 * self.effective_unit_value(),
+        }
+    }
+
+    /// What one item counts for against a threshold in plain items (R10): a full stack weighs
+    /// like 64 items whatever its size, a `value` item its unit value.
+    pub fn item_weight(&self) -> f64 {
+        match self.threshold_unit() {
+            ThresholdUnit::Items => 1.0,
+            ThresholdUnit::Stacks => {
+                f64::from(DEFAULT_STACK_SIZE) / f64::from(self.effective_stack_size())
+            }
+            ThresholdUnit::Value => self.effective_unit_value(),
+        }
+    }
+
     pub fn effective_risk_level(&self) -> String {
         if let Some(level) = &self.risk_level {
             let upper = level.trim().to_uppercase();
@@ -38,18 +113,39 @@ impl KeyItemRule {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct KeyItemRuleApi {
     pub item_id: String,
     pub threshold: u64,
     pub risk_level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_quota: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<ThresholdUnit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_value: Option<f64>,
 }
 
 impl KeyItemRuleApi {
+    /// Lowercases the item id, except for `re:` patterns where case carries meaning.
     pub fn normalized(&self) -> Self {
+        let item_id = self.item_id.trim();
         Self {
-            item_id: self.item_id.trim().to_lowercase(),
+            item_id: if item_id.starts_with(crate::services::ITEM_REGEX_PREFIX) {
+                item_id.to_string()
+            } else {
+                item_id.to_lowercase()
+            },
             threshold: self.threshold,
             risk_level: self.risk_level.trim().to_uppercase(),
+            daily_quota: self.daily_quota.filter(|quota| *quota > 0),
+            unit: self.unit.filter(|unit| *unit != ThresholdUnit::Items),
+            stack_size: self.stack_size.filter(|size| *size > 0),
+            unit_value: self
+                .unit_value
+                .filter(|value| value.is_finite() && *value > 0.0),
         }
     }
 }
@@ -60,6 +156,10 @@ impl From<&KeyItemRule> for KeyItemRuleApi {
             item_id: rule.item_id.clone(),
             threshold: rule.effective_threshold(),
             risk_level: rule.effective_risk_level(),
+            daily_quota: rule.daily_quota,
+            unit: rule.unit,
+            stack_size: rule.stack_size,
+            unit_value: rule.unit_value,
         }
     }
 }
@@ -72,11 +172,55 @@ impl From<KeyItemRuleApi> for KeyItemRule {
             max_per_10m: None,
             risk_level: Some(rule.risk_level),
             weight: None,
+            daily_quota: rule.daily_quota,
+            unit: rule.unit,
+            stack_size: rule.stack_size,
+            unit_value: rule.unit_value,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// A curated rule set shipped with the backend, listed by `GET /v2/detect/rules/presets`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RulePreset {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub rules: Vec<KeyItemRuleApi>,
+}
+
+/// Which rule wins when a preset names an item that already has a rule.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetConflictPolicy {
+    /// The existing rule stays as it is.
+    #[default]
+    Keep,
+    /// The preset rule overwrites the existing one.
+    Replace,
+    /// The lower threshold, higher risk level and lower daily quota of the two.
+    Stricter,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RulePresetApplyRequest {
+    #[serde(default)]
+    pub on_conflict: PresetConflictPolicy,
+}
+
+/// Item ids of a preset by what applying it did to the active rule set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RulePresetApplyResult {
+    pub preset: String,
+    pub added: Vec<String>,
+    pub replaced: Vec<String>,
+    pub kept: Vec<String>,
+}
+
+pub const CUSTOM_EVENT_FAMILY: &str = "custom";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct IngestEvent {
     pub event_id: String,
     pub event_time: i64,
@@ -84,7 +228,9 @@ pub struct IngestEvent {
     pub event_type: String,
     pub player_uuid: Option<String>,
     pub player_name: Option<String>,
+    #[serde(default)]
     pub item_id: String,
+    #[serde(default)]
     pub count: i64,
     pub nbt_hash: Option<String>,
     pub origin_id: Option<String>,
@@ -101,9 +247,34 @@ pub struct IngestEvent {
     pub x: Option<i32>,
     pub y: Option<i32>,
     pub z: Option<i32>,
+    /// `item` when absent; `custom` events carry `custom_type` and `payload` instead of an item.
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub custom_type: Option<String>,
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// Display name from the item registry, set by the `item_name` enricher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_name: Option<String>,
+    /// Risk level of the item's key item rule, set by the `rule_metadata` enricher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_risk_level: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl IngestEvent {
+    pub fn is_custom(&self) -> bool {
+        self.family
+            .as_deref()
+            .is_some_and(|family| family.eq_ignore_ascii_case(CUSTOM_EVENT_FAMILY))
+    }
+}
+
+/// The only `schema_version` ingest accepts.
+pub const INGEST_SCHEMA_VERSION: &str = "v2";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct IngestEnvelope {
     #[serde(default)]
     pub schema_version: String,
@@ -113,7 +284,179 @@ pub struct IngestEnvelope {
     pub events: Vec<IngestEvent>,
 }
 
+impl IngestEnvelope {
+    /// Checks the schema version and hands the envelope `server_id` down to events without one.
+    pub fn into_events(self) -> Result<Vec<IngestEvent>, String> {
+        if self.schema_version.trim() != INGEST_SCHEMA_VERSION {
+            return Err(format!(
+                "unsupported schema_version '{}', expected '{}'",
+                self.schema_version, INGEST_SCHEMA_VERSION
+            ));
+        }
+        let inherited_server_id = self.server_id;
+        let mut events = self.events;
+        for event in &mut events {
+            if event.server_id.is_none() {
+                event.server_id = inherited_server_id.clone();
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// One line of an ingest recording (`ingest_record_path`): an accepted batch as it arrived,
+/// before enrichment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedBatch {
+    pub recorded_at_ms: i64,
+    pub events: Vec<IngestEvent>,
+}
+
+/// `POST /v2/ops/replay` body: what a recording produced in a fresh analyzer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub batches: usize,
+    pub events: usize,
+    /// Lines that were not a recorded batch.
+    pub skipped_lines: usize,
+    pub anomalies: Vec<AnomalyRow>,
+}
+
+/// A user-defined detection rule from `detection_rules_path`, evaluated by the analyzer next to
+/// the built-in rules. List conditions match anything when empty; item ids may end in `*` to
+/// match a prefix (e.g. `mekanism:*`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DetectionRule {
+    /// Becomes the anomaly `rule_id`; must not look like a built-in id (`R` and digits).
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_detection_risk_level")]
+    pub risk_level: String,
+    /// Pushes the rule's anomalies to the alert channel; otherwise they only reach reports.
+    #[serde(default)]
+    pub alert: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Item event types (`ACQUIRE`, `TRANSFER`, snapshots); `ACQUIRE` when empty.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub origin_types: Vec<String>,
+    #[serde(default)]
+    pub item_ids: Vec<String>,
+    /// Events with a smaller `count` are ignored.
+    #[serde(default)]
+    pub min_count: i64,
+    /// With a window, the rule fires once the counts of matching events in the window, per
+    /// `group_by` key, exceed `threshold`; without one every matching event fires.
+    #[serde(default)]
+    pub window_seconds: u64,
+    #[serde(default)]
+    pub threshold: i64,
+    /// Event fields the window is kept per: `player_uuid`, `item_id`, `server_id`,
+    /// `origin_type`, `storage_id` or `dim`. `player_uuid` when empty.
+    #[serde(default)]
+    pub group_by: Vec<String>,
+}
+
+fn default_detection_risk_level() -> String {
+    "MEDIUM".to_string()
+}
+
+/// A rule from `composite_rules_path` that fires when several signals hold for the same event,
+/// e.g. `R4` fired, the acquisition matched no transfer and the item's key item rule is `HIGH`.
+/// Evaluated after the built-in and user rules, over the anomalies they raised for the event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CompositeRule {
+    /// Becomes the anomaly `rule_id`; must be `C` and digits (e.g. `C1`).
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_composite_risk_level")]
+    pub risk_level: String,
+    /// Pushes the rule's anomalies to the alert channel; on unless turned off.
+    #[serde(default = "default_enabled")]
+    pub alert: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Built-in or user rule ids that must all have fired on the event; at least one.
+    pub all_of: Vec<String>,
+    /// Only when the event matched no transfer.
+    #[serde(default)]
+    pub no_transfer: bool,
+    /// Only items whose key item rule has at least this risk level.
+    #[serde(default)]
+    pub key_item_risk: Option<String>,
+}
+
+fn default_composite_risk_level() -> String {
+    "HIGH".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// One enriched ingest batch sent by a cluster replica to the instance that owns the shared
+/// analyzer windows, together with the replica's rule snapshot and strictness settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterAnalyzeRequest {
+    #[serde(default)]
+    pub events: Vec<IngestEvent>,
+    #[serde(default)]
+    pub custom_events: Vec<IngestEvent>,
+    #[serde(default)]
+    pub rules: std::collections::HashMap<String, KeyItemRule>,
+    pub transfer_window_ms: i64,
+    pub key_item_window_ms: i64,
+    /// 0 when strict pickup detection is off.
+    #[serde(default)]
+    pub strict_pickup_window_ms: i64,
+    #[serde(default)]
+    pub strict_pickup_threshold: i64,
+    /// Missing from older replicas, which then get the built-in list.
+    #[serde(default)]
+    pub origin_whitelist: OriginWhitelist,
+    /// `rule_risk_overrides` of the replica; older replicas send none.
+    #[serde(default)]
+    pub risk_overrides: std::collections::BTreeMap<String, String>,
+    /// The replica's user-defined rules; older replicas send none.
+    #[serde(default)]
+    pub detection_rules: Vec<DetectionRule>,
+    /// The replica's composite rules; older replicas send none.
+    #[serde(default)]
+    pub composite_rules: Vec<CompositeRule>,
+    /// The replica's `strict_transfer_fingerprint`; off for older replicas.
+    #[serde(default)]
+    pub strict_transfer_fingerprint: bool,
+    /// The replica's transfer count tolerance; older replicas match exact counts only.
+    #[serde(default)]
+    pub transfer_matching: TransferMatching,
+}
+
 #[derive(Debug, Clone, Serialize, Row)]
+pub struct CustomEventRow {
+    #[serde(with = "clickhouse::serde::time::datetime64::millis")]
+    pub event_time: OffsetDateTime,
+    pub event_id: String,
+    pub server_id: String,
+    pub custom_type: String,
+    pub player_uuid: String,
+    pub player_name: String,
+    pub count: i64,
+    pub payload_json: String,
+    pub dim: String,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub z: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
 pub struct ItemEventRow {
     #[serde(with = "clickhouse::serde::time::datetime64::millis")]
     pub event_time: OffsetDateTime,
@@ -155,34 +498,943 @@ pub struct AnomalyRow {
     pub evidence_json: String,
 }
 
+/// Anomaly as served by the API, with the rule explained in the caller's language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyView {
+    /// Stable id used by deep links, see `anomaly_id`.
+    pub id: String,
+    #[serde(flatten)]
+    pub row: AnomalyRow,
+    /// `event_time` in `display_timezone` / `display_time_format`.
+    pub display_time: String,
+    pub rule_description: String,
+    pub acknowledged: bool,
+    /// Some moderator has already had it on screen, see `POST /v2/detect/anomalies/seen`.
+    pub seen: bool,
+    /// The `explain` section of `evidence_json`: the rule inputs that made it fire. Only the
+    /// lookup endpoint fills it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<serde_json::Value>,
+}
+
+/// `fields=` names accepted by the anomaly listing, in `AnomalyView` key order.
+pub const ANOMALY_FIELDS: [&str; 15] = [
+    "id",
+    "event_time",
+    "server_id",
+    "player_uuid",
+    "player_name",
+    "item_id",
+    "count",
+    "risk_level",
+    "rule_id",
+    "reason",
+    "evidence_json",
+    "display_time",
+    "rule_description",
+    "acknowledged",
+    "seen",
+];
+
+/// Writes that failed while ClickHouse was unavailable, kept until they can be replayed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "rows", rename_all = "snake_case")]
+pub enum DeadLetterBatch {
+    Events(Vec<IngestEvent>),
+    CustomEvents(Vec<IngestEvent>),
+    Anomalies(Vec<AnomalyRow>),
+}
+
+impl DeadLetterBatch {
+    pub fn len(&self) -> usize {
+        match self {
+            DeadLetterBatch::Events(rows) | DeadLetterBatch::CustomEvents(rows) => rows.len(),
+            DeadLetterBatch::Anomalies(rows) => rows.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One privilege probed by the ClickHouse preflight: `granted`, `missing` (ClickHouse answered
+/// `ACCESS_DENIED`) or `unknown` (failed for another reason, e.g. the probe table was never created).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionCheck {
+    pub privilege: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `GET /v2/ops/clickhouse/preflight` body; `missing` lists the privileges that were denied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickhousePreflight {
+    pub database: String,
+    pub ok: bool,
+    pub missing: Vec<String>,
+    pub checks: Vec<PermissionCheck>,
+}
+
+/// One check of `POST /v2/ops/selftest`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestCheck {
+    pub name: String,
+    pub ok: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `POST /v2/ops/selftest` body; `ok` when every check passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestReport {
+    pub ok: bool,
+    pub checks: Vec<SelftestCheck>,
+}
+
+/// `GET /v2/ops/health/ready` body; `degraded` while ClickHouse is failing or recently recovered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyStatus {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded_since_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub dead_letter_events: usize,
+    /// Consecutive failed pings of the ClickHouse watchdog; absent when it is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clickhouse_failures: Option<u32>,
+}
+
+/// A component entering or leaving a failed state, mirrored to event publishers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthTransition {
+    /// `clickhouse`.
+    pub component: String,
+    /// `degraded` or `ready`.
+    pub status: String,
+    pub at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Temporary exception for noisy anomalies: matches are still stored and reported, but not
+/// alerted until `expires_at_ms`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalySuppression {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+    /// Set once the expiry alert went out, so a restart does not repeat it.
+    #[serde(default)]
+    pub expiry_notified: bool,
+}
+
+/// Ban reported by an external system; the player's anomalies are still stored and reported but
+/// no longer alerted while the ban is active.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerBan {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub banned_at_ms: i64,
+    /// None for permanent bans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_ms: Option<i64>,
+}
+
+/// Player under investigation: their events are still stored and analyzed, but their anomalies
+/// are tagged `"quarantined": true` in the evidence and never alert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerQuarantine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub quarantined_by: String,
+    pub quarantined_at_ms: i64,
+}
+
+/// One change of the active key item rules, kept so reports can tell which rules applied when.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleRevision {
+    /// Increases by one per change, starting at 1.
+    pub revision: u64,
+    pub changed_at_ms: i64,
+    pub actor: String,
+    /// Same text as the config change notification, e.g. `关键物品规则更新：修改 1 条: minecraft:diamond`.
+    pub summary: String,
+}
+
+/// Assigns a player, by UUID or name, to a team whose alerts may go to their own chat group
+/// (`alert_team_routes`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerTeam {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_name: Option<String>,
+    pub team: String,
+}
+
+/// ACQUIRE `origin_type`s of vanilla Minecraft that never raise R2; the starting point of
+/// `origin_whitelist.json`.
+pub const DEFAULT_ORIGIN_WHITELIST: [&str; 19] = [
+    "world_pickup",
+    "container_click",
+    "storage_transfer",
+    "craft",
+    "smelt",
+    "trade",
+    "loot",
+    "barter",
+    "fishing",
+    "smithing",
+    "stonecutting",
+    "grindstone",
+    "anvil",
+    "brewing",
+    "loom",
+    "cartography",
+    "enchant",
+    "inventory_audit",
+    "command",
+];
+
+/// ACQUIRE `origin_type`s that do not raise R2, kept in `origin_whitelist.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OriginWhitelist {
+    pub origin_types: Vec<String>,
+    /// While learning, origin types outside the list are recorded in `learned` instead of
+    /// raising R2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub learning_until_ms: Option<i64>,
+    /// Origin types first seen during a learning period; allowed until removed.
+    #[serde(default)]
+    pub learned: Vec<LearnedOriginType>,
+}
+
+impl Default for OriginWhitelist {
+    fn default() -> Self {
+        Self {
+            origin_types: DEFAULT_ORIGIN_WHITELIST
+                .iter()
+                .map(|origin_type| origin_type.to_string())
+                .collect(),
+            learning_until_ms: None,
+            learned: Vec::new(),
+        }
+    }
+}
+
+impl OriginWhitelist {
+    pub fn allows(&self, origin_type: &str) -> bool {
+        self.origin_types.iter().any(|allowed| allowed == origin_type)
+            || self
+                .learned
+                .iter()
+                .any(|learned| learned.origin_type == origin_type)
+    }
+
+    pub fn is_learning(&self, now_ms: i64) -> bool {
+        self.learning_until_ms.is_some_and(|until| now_ms < until)
+    }
+}
+
+/// An origin type recorded in learning mode, with the first event that carried it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LearnedOriginType {
+    pub origin_type: String,
+    pub first_seen_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    pub item_id: String,
+}
+
+/// `POST /v2/detect/origin-whitelist` body; removals also drop learned origin types.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OriginWhitelistUpdate {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// `POST /v2/detect/origin-whitelist/learning` body; `days: 0` ends learning.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OriginLearningRequest {
+    pub days: Option<u32>,
+}
+
+/// `POST /v2/ops/quarantine` body; `player_uuid` or `player_name` is required.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuarantineRequest {
+    pub player_uuid: Option<String>,
+    pub player_name: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// A background job of `/v2/ops/jobs`. `state` moves from QUEUED to RUNNING to SUCCEEDED or
+/// FAILED; jobs run one at a time, in the order they were submitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    /// `ml_export`.
+    pub kind: String,
+    pub state: String,
+    pub submitted_by: String,
+    pub submitted_at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Kind specific, e.g. an `MlExportResult`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+}
+
+/// `POST /v2/ops/jobs/ml-export` body: a labeled, anonymized training set of the anomalies in
+/// `from_date..=to_date` and unflagged acquisitions of the same players.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MlExportRequest {
+    pub from_date: String,
+    pub to_date: String,
+    /// Share of the anomalies sampled, in `(0, 1]`; default 1.
+    pub anomaly_rate: Option<f64>,
+    /// Unflagged samples per sampled anomaly of the same day, in `[0, 10]`; default 1.
+    pub normal_ratio: Option<f64>,
+    /// Caps the sampled anomalies of each rule, so frequent rules do not drown out rare ones.
+    pub max_per_rule: Option<usize>,
+    /// Drives sampling only; exports with the same seed pick the same rows. Defaults to the job
+    /// id.
+    pub seed: Option<String>,
+    /// `csv`, the default and only format.
+    pub format: Option<String>,
+}
+
+/// `result` of a finished `ml_export` job.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MlExportResult {
+    /// Relative to `report_dir`; downloaded via `GET /v2/ops/jobs/{id}/download`.
+    pub file: String,
+    pub rows: usize,
+    pub anomalies: usize,
+    pub normal: usize,
+    /// Sampled anomalies a moderator acknowledged.
+    pub acked: usize,
+}
+
+/// `POST /v2/ops/integrations/ban-events` body; `action` is `ban` (default) or `unban`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BanEventRequest {
+    pub action: Option<String>,
+    pub player_uuid: Option<String>,
+    pub player_name: Option<String>,
+    pub reason: Option<String>,
+    pub source: Option<String>,
+    pub expires_at_ms: Option<i64>,
+}
+
+/// What an API token may call: `ingest` the endpoints a mod or replica uses (ingest,
+/// heartbeats, mod config sync and the mod's own reports), `read` the read-only detect, query and
+/// ops endpoints, `admin` everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTokenScope {
+    Ingest,
+    Read,
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiTokenScope::Ingest => "ingest",
+            ApiTokenScope::Read => "read",
+            ApiTokenScope::Admin => "admin",
+        }
+    }
+
+    /// `admin` covers every scope; `ingest` and `read` only themselves.
+    pub fn allows(self, required: ApiTokenScope) -> bool {
+        self == ApiTokenScope::Admin || self == required
+    }
+}
+
+/// A named API token; only the SHA-256 digest of its secret is stored. Revoked tokens are kept
+/// so their names stay on record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    pub scope: ApiTokenScope,
+    pub token_sha256: String,
+    pub created_at_ms: i64,
+    /// None for tokens that never expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at_ms: Option<i64>,
+}
+
+impl ApiToken {
+    pub fn is_active(&self, now_ms: i64) -> bool {
+        self.revoked_at_ms.is_none() && self.expires_at_ms.is_none_or(|expires| expires > now_ms)
+    }
+}
+
+/// `POST /v2/ops/api-tokens` body.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenIssueRequest {
+    pub name: String,
+    pub scope: ApiTokenScope,
+    #[serde(default)]
+    pub expires_at_ms: Option<i64>,
+}
+
+/// An API token as listed, without its digest.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenView {
+    pub name: String,
+    pub scope: ApiTokenScope,
+    pub created_at_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revoked_at_ms: Option<i64>,
+    pub active: bool,
+}
+
+impl ApiTokenView {
+    pub fn new(token: &ApiToken, now_ms: i64) -> Self {
+        Self {
+            name: token.name.clone(),
+            scope: token.scope,
+            created_at_ms: token.created_at_ms,
+            expires_at_ms: token.expires_at_ms,
+            revoked_at_ms: token.revoked_at_ms,
+            active: token.is_active(now_ms),
+        }
+    }
+}
+
+/// Answer to `POST /v2/ops/api-tokens`; the only time the secret is shown.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenIssued {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenView,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuppressionRequest {
+    pub rule_id: Option<String>,
+    pub player: Option<String>,
+    pub server_id: Option<String>,
+    pub item_id: Option<String>,
+    pub reason: Option<String>,
+    pub duration_minutes: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct ExpiredSuppressionQuery {
+    pub hours: Option<u32>,
+}
+
+/// Identifies one anomaly row; acknowledgements are stored by this key next to the anomalies table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Row)]
+pub struct AnomalyAckKey {
+    #[serde(with = "clickhouse::serde::time::datetime64::millis")]
+    pub event_time: OffsetDateTime,
+    pub player_uuid: String,
+    pub item_id: String,
+    pub rule_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyAckRequest {
+    pub date: String,
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub player: Option<String>,
+    #[serde(default)]
+    pub server_id: Option<String>,
+    #[serde(default)]
+    pub item_id: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Reviewer kept with each ack: the HTTP caller's `X-Lattice-Actor`, or `qq:<user id>` for
+    /// `/处理` in chat. Set by the server, not read from the body.
+    #[serde(skip_deserializing)]
+    pub acked_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyAckResult {
+    pub date: String,
+    pub matched: u64,
+    pub acked_at_ms: i64,
+}
+
+/// Anomaly ids the desktop has shown a moderator, so others can skip them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalySeenRequest {
+    pub ids: Vec<String>,
+    /// The HTTP caller's `X-Lattice-Actor`. Set by the server, not read from the body.
+    #[serde(skip_deserializing)]
+    pub seen_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalySeenResult {
+    pub recorded: usize,
+    pub seen_at_ms: i64,
+}
+
+/// Last R12 finding per storage location and item, kept across scan runs so unchanged chests
+/// are not re-alerted every scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageFinding {
+    pub server_id: String,
+    pub storage_id: String,
+    pub item_id: String,
+    pub count: i64,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+}
+
+/// How loosely an `ACQUIRE` may match earlier transfers; the default only accepts a single
+/// transfer of exactly the acquired count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransferMatching {
+    /// Counts this far apart still match.
+    #[serde(default)]
+    pub count_tolerance: u64,
+    /// Counts within this percentage of the acquired count still match; the larger of the two
+    /// tolerances applies.
+    #[serde(default)]
+    pub count_tolerance_percent: f64,
+    /// Several transfers whose counts add up to the acquired count match together.
+    #[serde(default)]
+    pub aggregate: bool,
+}
+
+impl TransferMatching {
+    /// The count difference accepted for an acquisition of `count`.
+    pub fn tolerance_for(&self, count: i64) -> i64 {
+        let percent = (count.unsigned_abs() as f64 * self.count_tolerance_percent / 100.0) as i64;
+        (self.count_tolerance as i64).max(percent)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRecord {
+    pub time_ms: i64,
+    pub player_uuid: String,
+    pub player_name: String,
+    pub item_fingerprint: String,
+    pub count: i64,
+    pub storage_mod: String,
+    pub storage_id: String,
+    pub trace_id: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ReportSummary {
+    pub critical: u64,
+    pub high: u64,
+    pub medium: u64,
+    pub low: u64,
+}
+
+impl ReportSummary {
+    pub fn total(&self) -> u64 {
+        self.critical + self.high + self.medium + self.low
+    }
+}
+
+/// Per-player anomaly totals for one day, used to pick the report's top offenders.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct PlayerAnomalyCount {
+    pub player_uuid: String,
+    pub player_name: String,
+    pub anomalies: u64,
+    pub critical: u64,
+    pub high: u64,
+}
+
+/// A generated daily report in `report_dir`; sizes include its player pages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportFile {
+    pub date: String,
+    pub size_bytes: u64,
+    pub player_pages: usize,
+    pub modified_ms: i64,
+}
+
+/// Entry of `GET /v2/query/reports`: a report, where to open it and that day's anomaly counts.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportArchiveEntry {
+    pub date: String,
+    pub url: String,
+    pub size_bytes: u64,
+    pub player_pages: usize,
+    pub modified_ms: i64,
+    /// None while ClickHouse is unreachable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ReportSummary>,
+}
+
+/// One player's acquisitions of one item on a local day, summed in ClickHouse.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct PlayerItemDailyTotal {
+    pub player_uuid: String,
+    pub player_name: String,
+    pub item_id: String,
+    pub total: i64,
+}
+
+/// Anomalies on one item over a date range; `rule_hits` only counts key item rules.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct ItemAnomalyStat {
+    pub item_id: String,
+    pub anomalies: u64,
+    pub rule_hits: u64,
+    pub players: u64,
+}
+
+/// All of a player's `item_events`: latest name, event count and first/last event time.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct PlayerEventSpan {
+    pub player_name: String,
+    pub events: u64,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+}
+
+/// One item's `ACQUIRE` events of a player over a profile window.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct PlayerItemAcquired {
+    pub item_id: String,
+    pub acquisitions: u64,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct RuleAnomalyCount {
+    pub rule_id: String,
+    pub anomalies: u64,
+}
+
+/// `GET /v2/query/players/{uuid}/profile`: `days` bounds the anomaly and acquisition
+/// aggregates, `recent` the number of latest events listed.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct PlayerProfileQuery {
+    pub days: Option<u32>,
+    pub recent: Option<usize>,
+}
+
+/// One player's activity and risk at a glance; first/last seen cover all stored events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub player_uuid: String,
+    pub player_name: String,
+    pub first_seen_ms: Option<i64>,
+    pub last_seen_ms: Option<i64>,
+    pub total_events: u64,
+    pub days: u32,
+    pub anomalies: u64,
+    /// Most anomalies first.
+    pub anomalies_by_rule: Vec<RuleAnomalyCount>,
+    /// Largest total first.
+    pub acquired_items: Vec<PlayerItemAcquired>,
+    /// Newest first.
+    pub recent_events: Vec<ItemEventRow>,
+}
+
+/// `GET /v2/query/item-trace`: at least one of `trace_id` and `item_fingerprint` is required.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct ItemTraceQuery {
+    pub trace_id: Option<String>,
+    pub item_fingerprint: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A holder an item passed through: `player:<uuid>`, `storage:<mod>:<id>` or
+/// `origin:<type>:<id>`, the last being where an `ACQUIRE` says the item came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemTraceNode {
+    pub id: String,
+    pub kind: String,
+    pub label: String,
+}
+
+/// One `TRANSFER` or `ACQUIRE` event as an edge between two nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemTraceHop {
+    pub event_id: String,
+    pub event_time_ms: i64,
+    pub event_type: String,
+    pub server_id: String,
+    pub item_id: String,
+    pub count: i64,
+    pub from: String,
+    pub to: String,
+    /// The earlier hop this one continues: the latest one ending at `from`, or for an `ACQUIRE`
+    /// the `TRANSFER` of the same count to the same player that it completes.
+    pub previous_event_id: Option<String>,
+}
+
+/// The provenance chain of an item, hops oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ItemTrace {
+    pub trace_id: Option<String>,
+    pub item_fingerprint: Option<String>,
+    pub nodes: Vec<ItemTraceNode>,
+    pub hops: Vec<ItemTraceHop>,
+    /// Set when `limit` cut the chain short.
+    pub truncated: bool,
+}
+
+/// Quantiles of per-player daily `ACQUIRE` totals for one item.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct ItemCountDistribution {
+    pub item_id: String,
+    pub samples: u64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoisyRule {
+    pub item_id: String,
+    pub rule_hits: u64,
+    pub per_day: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleThresholdFit {
+    pub item_id: String,
+    pub threshold: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_quota: Option<u64>,
+    pub distribution: ItemCountDistribution,
+}
+
+/// Monthly review of the key item rules against what was observed in `from_date..=to_date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleHygieneReport {
+    pub from_date: String,
+    pub to_date: String,
+    pub never_fired: Vec<String>,
+    pub noisy: Vec<NoisyRule>,
+    /// Items without a rule that still show up in many anomalies.
+    pub uncovered: Vec<ItemAnomalyStat>,
+    pub thresholds: Vec<RuleThresholdFit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct AnomalyDailySummaryRow {
+    pub date: String,
+    pub server_id: String,
+    pub rule_id: String,
+    pub risk_level: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct PartitionStat {
+    pub table: String,
+    pub partition_id: String,
+    pub parts: u64,
+    pub rows: u64,
+    pub bytes_on_disk: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub database_bytes: u64,
+    pub disk_free_bytes: u64,
+    pub disk_total_bytes: u64,
+}
+
+/// Rows of one table on one day in the primary ClickHouse and in the dual-write shadow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowTableCount {
+    pub table: String,
+    pub primary: u64,
+    pub shadow: u64,
+}
+
+/// Daily dual-write consistency check; the failure counts cover mirror inserts since start.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowConsistency {
+    pub date: String,
+    pub tables: Vec<ShadowTableCount>,
+    /// Batches the primary stored but the shadow rejected; they are not retried.
+    pub failed_batches: u64,
+    pub last_error: Option<String>,
+}
+
+impl ShadowConsistency {
+    pub fn mismatched(&self) -> impl Iterator<Item = &ShadowTableCount> {
+        self.tables
+            .iter()
+            .filter(|count| count.primary != count.shadow)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceRun {
+    pub started_at_ms: i64,
+    pub finished_at_ms: i64,
+    pub status: String,
+    pub optimized: Vec<PartitionStat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<StorageUsage>,
+    pub storage_alert: bool,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub maintenance_hour: u32,
+    pub optimize_min_rows: u64,
+    pub storage_alert_threshold_mb: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<MaintenanceRun>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct DataDropQuery {
+    /// Local day (`YYYY-MM-DD`) whose partition is dropped.
+    pub date: String,
+    pub table: String,
+    /// Token from the first, unconfirmed call; without it nothing is dropped.
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+/// A daily partition about to be dropped (with the token that confirms it) or just dropped.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransferRecord {
-    pub time_ms: i64,
-    pub player_uuid: String,
-    pub player_name: String,
-    pub item_fingerprint: String,
-    pub count: i64,
-    pub storage_mod: String,
-    pub storage_id: String,
-    pub trace_id: String,
+pub struct DataDropResult {
+    pub table: String,
+    pub date: String,
+    pub partition_id: String,
+    pub rows: u64,
+    pub bytes_on_disk: u64,
+    pub dropped: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_expires_at_ms: Option<i64>,
 }
 
-#[derive(Default, Clone)]
-pub struct ReportSummary {
-    pub high: u64,
-    pub medium: u64,
-    pub low: u64,
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AnomalyTrendQuery {
+    pub days: Option<u32>,
+    pub server_id: Option<String>,
+    pub rule_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
 pub struct AnomalyQuery {
     pub date: Option<String>,
     pub player: Option<String>,
     pub page: Option<usize>,
     pub page_size: Option<usize>,
+    pub lang: Option<String>,
+}
+
+/// Filters of `/v2/detect/anomalies/stream`: comma-separated risk levels and server ids, each
+/// matching everything when absent.
+#[derive(Debug, Deserialize, Default)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AnomalyStreamQuery {
+    pub risk: Option<String>,
+    pub server_id: Option<String>,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AnomalyLookupQuery {
+    pub id: String,
+    pub lang: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ItemRegistryEntry {
     pub item_id: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -193,6 +1445,9 @@ pub struct ItemRegistryEntry {
     pub namespace: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
+    /// Fills `stack_size` of `stacks` key item rules that leave it out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stack_size: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -201,6 +1456,11 @@ pub struct ItemRegistryPayload {
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
 pub struct ItemRegistryQuery {
     pub query: Option<String>,
     pub limit: Option<usize>,
@@ -208,10 +1468,38 @@ pub struct ItemRegistryQuery {
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
 pub struct ItemRegistryUpdateQuery {
     pub mode: Option<String>,
 }
 
+/// Filters for `DELETE /v2/query/item-registry`; set filters must all match, at least one is
+/// required.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct ItemRegistryDeleteQuery {
+    pub namespace: Option<String>,
+    /// Only entries without an `item_events` row in the last `unseen_days` days.
+    pub unseen_days: Option<u32>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ItemRegistryDeleteResult {
+    pub dry_run: bool,
+    pub removed: Vec<String>,
+    pub remaining: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TaskProgress {
     pub state: String,
@@ -226,6 +1514,10 @@ pub struct TaskProgress {
     pub trace_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub throughput_per_sec: Option<f64>,
+    /// Set when a RUNNING task went without an update for `task_stale_after_seconds`; `state`
+    /// then reads STALLED.
+    #[serde(default)]
+    pub stale: bool,
 }
 
 impl Default for TaskProgress {
@@ -238,6 +1530,7 @@ impl Default for TaskProgress {
             failure: None,
             trace_id: None,
             throughput_per_sec: None,
+            stale: false,
         }
     }
 }
@@ -287,6 +1580,8 @@ impl Default for RconConfig {
 #[derive(Debug, Deserialize)]
 pub struct TaskProgressUpdate {
     pub task: String,
+    /// Required unless the legacy `running` flag is sent instead.
+    #[serde(default)]
     pub state: String,
     #[serde(default)]
     pub stage: Option<String>,
@@ -298,6 +1593,13 @@ pub struct TaskProgressUpdate {
     pub throughput_per_sec: Option<f64>,
     #[serde(default)]
     pub failure: Option<TaskFailure>,
+    /// Legacy monolith fields, mapped onto `state` and `counters` when `state` is empty.
+    #[serde(default)]
+    pub running: Option<bool>,
+    #[serde(default)]
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub done: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -347,6 +1649,12 @@ pub struct ModConfigAck {
     pub changed_keys: Vec<String>,
 }
 
+/// Chat command that pages through the rest of a group's latest alert batch.
+pub const MORE_ALERTS_COMMAND: &str = "/更多";
+
+/// Chat command that marks an anomaly (by id) or a player's anomalies of today as reviewed.
+pub const ACK_COMMAND: &str = "/处理";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AlertDeliveryRecord {
     pub timestamp_ms: i64,
@@ -357,14 +1665,320 @@ pub struct AlertDeliveryRecord {
     pub rule_ids: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Sent to `alert_webhook_fallback_url` because the primary target failed.
+    #[serde(default)]
+    pub failover: bool,
+    /// Last primary target error when `failover` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_error: Option<String>,
+}
+
+/// Upper bounds, in seconds, of the alert delivery time buckets; retries and failover included.
+pub const ALERT_DELIVERY_BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Alert batches delivered and failed since start by delivery `mode` (`http`, `ws`, `unset`),
+/// for the Prometheus export.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AlertDeliveryTotals {
+    pub modes: std::collections::BTreeMap<String, AlertModeTotals>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AlertModeTotals {
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Attempts after the first of each batch, fallback attempts included.
+    pub retries: u64,
+    /// Batches per `ALERT_DELIVERY_BUCKETS` bound, cumulative.
+    pub seconds_buckets: Vec<u64>,
+    pub seconds_sum: f64,
+}
+
+impl AlertDeliveryTotals {
+    /// Counts one batch under the mode it was finally sent with.
+    pub fn record(&mut self, mode: &str, succeeded: bool, attempts: u8, seconds: f64) {
+        let totals = self.modes.entry(mode.to_string()).or_default();
+        if succeeded {
+            totals.succeeded += 1;
+        } else {
+            totals.failed += 1;
+        }
+        totals.retries += u64::from(attempts.saturating_sub(1));
+        totals
+            .seconds_buckets
+            .resize(ALERT_DELIVERY_BUCKETS.len(), 0);
+        for (bucket, bound) in totals
+            .seconds_buckets
+            .iter_mut()
+            .zip(ALERT_DELIVERY_BUCKETS)
+        {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        totals.seconds_sum += seconds;
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AlertPreviewSample {
+    #[serde(default)]
+    pub server_id: Option<String>,
+    #[serde(default)]
+    pub player_name: Option<String>,
+    pub item_id: String,
+    pub count: i64,
+    #[serde(default)]
+    pub risk_level: Option<String>,
+    pub rule_id: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct AlertPreviewRequest {
+    pub alert_webhook_template: Option<String>,
+    pub samples: Vec<AlertPreviewSample>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AlertPreview {
+    pub mode: String,
+    pub sample_source: String,
+    pub alert_count: usize,
+    pub filtered_count: usize,
+    pub text: String,
+    pub payload: String,
+    pub payload_valid_json: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaleServerStatus {
+    pub server_id: String,
+    pub last_success_ms: i64,
+    pub stale_for_seconds: u64,
+    pub auth_failures: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_auth_failure_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_auth_failure_source: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestAuthFailure {
+    pub source: String,
+    pub count: u64,
+    pub first_failure_ms: i64,
+    pub last_failure_ms: i64,
+}
+
+/// Ingest batches from one source whose claimed `server_id` did not match its server key.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestIdentityMismatch {
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_server_id: Option<String>,
+    /// `server_id` bound to the presented key; unset when no valid key was sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authenticated_server_id: Option<String>,
+    pub count: u64,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestStaleReport {
+    pub stale_after_minutes: u64,
+    pub servers: Vec<StaleServerStatus>,
+    pub auth_failures: Vec<IngestAuthFailure>,
+    #[serde(default)]
+    pub identity_mismatches: Vec<IngestIdentityMismatch>,
+}
+
+/// `GET /v2/ops/ingest/fingerprint-stats`: `hours` of `TRANSFER` / `ACQUIRE` events to look at,
+/// `top` colliding fingerprints listed per server.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct FingerprintStatsQuery {
+    pub hours: Option<u32>,
+    pub top: Option<usize>,
+}
+
+/// A server's `TRANSFER` / `ACQUIRE` events and how many came without an `item_fingerprint`.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct FingerprintUsage {
+    pub server_id: String,
+    pub events: u64,
+    pub missing: u64,
+}
+
+/// A fingerprint carried by the events of several players or items. Synthesized fingerprints
+/// read `item_id:*`, since the `nbt_hash` behind them is not stored.
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct FingerprintCollision {
+    pub server_id: String,
+    pub fingerprint: String,
+    pub synthesized: bool,
+    pub events: u64,
+    pub players: u64,
+    pub item_ids: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerFingerprintStats {
+    pub server_id: String,
+    pub events: u64,
+    pub missing: u64,
+    /// `missing / events`, 0 without events.
+    pub missing_ratio: f64,
+    /// Most players first.
+    pub top_collisions: Vec<FingerprintCollision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintStatsReport {
+    pub hours: u32,
+    pub since_ms: i64,
+    pub strict_transfer_fingerprint: bool,
+    pub servers: Vec<ServerFingerprintStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ServerHeartbeat {
+    pub server_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tps: Option<f64>,
+    #[serde(default)]
+    pub received_at_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerStatus {
+    pub server_id: String,
+    pub online: bool,
+    pub last_heartbeat_ms: i64,
+    pub missed_heartbeats: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tps: Option<f64>,
+    #[serde(default)]
+    pub mod_version_outdated: bool,
+    #[serde(default)]
+    pub recent: Vec<ServerHeartbeat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModVersionCount {
+    pub mod_version: String,
+    pub servers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerStatusReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_mod_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_mod_version: Option<String>,
+    pub version_skew: bool,
+    pub versions: Vec<ModVersionCount>,
+    pub servers: Vec<ServerStatus>,
+}
+
+/// Ingest throughput since start, plus the average of the last `window_minutes` full minutes.
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestRate {
+    pub window_minutes: u64,
+    pub events_per_minute: f64,
+    pub requests_per_minute: f64,
+    pub events_total: u64,
+    pub requests_total: u64,
+    pub errors_total: u64,
+}
+
+/// Heartbeat and ingest state of the known servers, by `server_id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerOverview {
+    pub total: usize,
+    pub online: Vec<String>,
+    pub offline: Vec<String>,
+    /// Servers whose ingest went quiet for `ingest_stale_after_minutes`.
+    pub stale_ingest: Vec<String>,
+    pub outdated_mod: Vec<String>,
+}
+
+/// `GET /v2/ops/overview`: what the desktop home screen shows, in one response.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpsOverview {
+    pub date: String,
+    pub generated_at_ms: i64,
+    /// Today's anomalies by risk level; `None` when ClickHouse could not be read.
+    pub anomalies: Option<ReportSummary>,
+    /// Today's anomalies no moderator has seen yet; `None` when ClickHouse could not be read.
+    pub unseen_anomalies: Option<u64>,
+    pub ingest: IngestRate,
+    pub servers: ServerOverview,
+    pub last_report: Option<ReportFile>,
+    pub last_alert_delivery: Option<AlertDeliveryRecord>,
+    pub task_progress: TaskStatus,
+    /// ClickHouse is unreachable (see `/v2/ops/health/ready`).
+    pub degraded: bool,
 }
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
 pub struct StorageScanQuery {
     pub date: Option<String>,
     pub item: Option<String>,
     pub page: Option<usize>,
     pub page_size: Option<usize>,
+    pub lang: Option<String>,
+}
+
+/// `GET /v2/query/events`: raw `item_events` rows of one day. `date` and at least one of
+/// `player`, `item`, `storage` are required so a lookup never reads a whole day.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct ItemEventQuery {
+    pub date: Option<String>,
+    /// Player name or UUID.
+    pub player: Option<String>,
+    pub item: Option<String>,
+    /// Exact `storage_id`, e.g. `minecraft:chest@0,64,0`.
+    pub storage: Option<String>,
+    pub server_id: Option<String>,
+    pub event_type: Option<String>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// Validated `ItemEventQuery` filters, combined with AND.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemEventFilter {
+    pub date: String,
+    pub player: Option<String>,
+    pub item_id: Option<String>,
+    pub storage_id: Option<String>,
+    pub server_id: Option<String>,
+    pub event_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -374,6 +1988,20 @@ pub struct PagedResult<T> {
     pub page_size: usize,
     pub total_items: usize,
     pub total_pages: usize,
+    /// Set when ClickHouse is unavailable and items come from the in-memory recent-anomaly cache.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+}
+
+/// Response schema version of the detect/query DTOs; bump it whenever one of them changes shape.
+pub const API_VERSION: &str = "2.1";
+
+/// Versioned wrapper for detect/query responses, sent to clients that accept
+/// `application/vnd.lattice.v2.1+json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEnvelope<T> {
+    pub api_version: String,
+    pub data: T,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -406,6 +2034,7 @@ pub struct OpTokenMisuseAlertRequest {
 pub struct StorageScanEventRow {
     #[serde(with = "clickhouse::serde::time::datetime64::millis")]
     pub event_time: OffsetDateTime,
+    pub event_id: String,
     pub item_id: String,
     pub count: i64,
     pub storage_mod: String,
@@ -429,12 +2058,229 @@ pub struct StorageScanRow {
     pub y: Option<i32>,
     pub z: Option<i32>,
     pub rule_id: String,
+    #[serde(default)]
+    pub rule_description: String,
     pub threshold: u64,
     pub risk_level: String,
     pub reason: String,
 }
 
-#[derive(Debug, Clone)]
+/// `fields=` names accepted by the storage-scan listing, in `StorageScanRow` key order.
+pub const STORAGE_SCAN_FIELDS: [&str; 14] = [
+    "event_time",
+    "item_id",
+    "count",
+    "storage_mod",
+    "storage_id",
+    "dim",
+    "x",
+    "y",
+    "z",
+    "rule_id",
+    "rule_description",
+    "threshold",
+    "risk_level",
+    "reason",
+];
+
+/// `fields=` names accepted by the raw event query, in `ItemEventRow` key order.
+pub const ITEM_EVENT_FIELDS: [&str; 22] = [
+    "event_time",
+    "event_id",
+    "server_id",
+    "event_type",
+    "player_uuid",
+    "player_name",
+    "item_id",
+    "count",
+    "origin_id",
+    "origin_type",
+    "origin_ref",
+    "source_type",
+    "source_ref",
+    "storage_mod",
+    "storage_id",
+    "actor_type",
+    "trace_id",
+    "item_fingerprint",
+    "dim",
+    "x",
+    "y",
+    "z",
+];
+
+/// Scheduled strictness for strict pickup mode (R10), e.g. stricter while staff is asleep.
+/// `hours` (0-23, local time) and `days` (0-6 or `sun`..`sat`) are cron-like fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrictProfile {
+    pub name: String,
+    pub hours: String,
+    pub days: String,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pickup_window_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pickup_threshold: Option<u64>,
+}
+
+/// Sends alerts about a team's players to that team instead of the default target; unset fields
+/// keep the default `alert_group_id` / `alert_webhook_url` / `alert_webhook_proxy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertTeamRoute {
+    pub team: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Replaces `alert_webhook_proxy` for this team's target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+/// Target name of `[alert_routing]` routes for the batch's usual target: the team route, server
+/// group or default `alert_group_id` / `alert_webhook_url` it would go to without routing.
+pub const DEFAULT_ALERT_TARGET: &str = "default";
+
+/// `[alert_routing]`: sends anomalies to named targets by rule id and risk level, e.g. `HIGH` to
+/// an on-call group and `MEDIUM` to a review channel.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRouting {
+    /// Targets by name (lowercase); `default` is reserved for the usual target.
+    pub targets: std::collections::BTreeMap<String, AlertRoutingTarget>,
+    pub routes: Vec<AlertRoute>,
+}
+
+/// Where a routed alert goes; unset fields keep the usual target's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertRoutingTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Replaces `alert_webhook_proxy` for this target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+/// Matches anomalies whose rule is in `rule_ids` and whose level is in `risk_levels`; an empty
+/// list matches any, but a route needs at least one of them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertRoute {
+    #[serde(default)]
+    pub rule_ids: Vec<String>,
+    #[serde(default)]
+    pub risk_levels: Vec<String>,
+    /// Names in `targets`, or `default`.
+    pub targets: Vec<String>,
+}
+
+impl AlertRouting {
+    /// Targets of every route `row` matches, in route order without repeats; empty when none
+    /// matches.
+    pub fn targets_for(&self, row: &AnomalyRow) -> Vec<&str> {
+        let mut targets: Vec<&str> = Vec::new();
+        for route in &self.routes {
+            let listed = |values: &[String], value: &str| {
+                values.is_empty() || values.iter().any(|item| item.eq_ignore_ascii_case(value))
+            };
+            if !listed(&route.rule_ids, &row.rule_id)
+                || !listed(&route.risk_levels, &row.risk_level)
+            {
+                continue;
+            }
+            for target in &route.targets {
+                if !targets.contains(&target.as_str()) {
+                    targets.push(target);
+                }
+            }
+        }
+        targets
+    }
+}
+
+/// Binds a game server's `server_id` to the enrollment key its mod sends as
+/// `X-Lattice-Server-Key`; events under that key may only claim this `server_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerKey {
+    pub server_id: String,
+    pub key: String,
+}
+
+/// Hides content before it leaves the backend in reports or alerts; the API keeps the raw rows.
+/// `field` names `player_name`, `reason` or an evidence key (matched at any depth); `pattern` is
+/// a regex. With both, only matches inside that field are replaced; with only `pattern`, matches
+/// in `reason` and evidence strings are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub replacement: String,
+    /// `report` and/or `alert`; empty applies to both.
+    pub targets: Vec<String>,
+}
+
+impl Default for RedactionRule {
+    fn default() -> Self {
+        Self {
+            field: None,
+            pattern: None,
+            replacement: "[redacted]".to_string(),
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl Default for StrictProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            hours: "*".to_string(),
+            days: "*".to_string(),
+            enabled: true,
+            pickup_window_seconds: None,
+            pickup_threshold: None,
+        }
+    }
+}
+
+/// Strict pickup settings in effect right now; `profile` is None when the global keys apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrictnessStatus {
+    pub profile: Option<String>,
+    pub enabled: bool,
+    pub pickup_window_seconds: u64,
+    pub pickup_threshold: u64,
+}
+
+/// State the live analyzer holds for one `server_id` (`""` for events without one).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerServerStatus {
+    pub server_id: String,
+    /// Events analyzed since startup.
+    pub events_analyzed: u64,
+    pub last_event_ms: Option<i64>,
+    pub transfer_cache: usize,
+    pub origin_seen: usize,
+    pub key_item_windows: usize,
+    pub pickup_windows: usize,
+    pub audit_windows: usize,
+    pub strict_pickup_windows: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerStatus {
+    /// True on `cluster_mode` replicas that forward analysis to `cluster_state_url`; `servers`
+    /// then only covers batches analyzed locally while it was unreachable.
+    pub forwarded: bool,
+    pub servers: Vec<AnalyzerServerStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct RuntimeConfig {
     pub bind_addr: String,
     pub api_token: Option<String>,
@@ -444,6 +2290,9 @@ pub struct RuntimeConfig {
     pub public_base_url: String,
     pub webhook_url: Option<String>,
     pub webhook_template: Option<String>,
+    /// Language of the built-in report webhook template (`zh-CN` or `en`), used when
+    /// `webhook_template` is unset.
+    pub report_webhook_locale: String,
     pub alert_webhook_url: Option<String>,
     pub alert_webhook_template: Option<String>,
     pub alert_webhook_token: Option<String>,
@@ -451,6 +2300,15 @@ pub struct RuntimeConfig {
     pub key_items_path: String,
     pub item_registry_path: String,
     pub transfer_window_seconds: u64,
+    /// ACQUIRE and TRANSFER events without an `item_fingerprint` are never matched to a
+    /// transfer, instead of matching on the synthesized `item_id:nbt_hash`.
+    pub strict_transfer_fingerprint: bool,
+    /// Counts this far from a transfer's still match it; 0 requires the exact count.
+    pub transfer_count_tolerance: u64,
+    /// The same as a percentage of the acquired count; the larger tolerance applies.
+    pub transfer_count_tolerance_percent: f64,
+    /// Lets several transfers whose counts add up to an acquisition match it together.
+    pub transfer_aggregate: bool,
     pub key_item_window_minutes: u64,
     pub strict_enabled: bool,
     pub strict_pickup_window_seconds: u64,
@@ -459,6 +2317,189 @@ pub struct RuntimeConfig {
     pub request_timeout_seconds: u64,
     pub report_hour: u32,
     pub report_minute: u32,
+    pub ingest_stale_after_minutes: u64,
+    /// A RUNNING task whose progress is older than this is exposed as STALLED; 0 disables.
+    pub task_stale_after_seconds: u64,
+    pub heartbeat_interval_seconds: u64,
+    pub heartbeat_missed_threshold: u64,
+    pub min_mod_version: Option<String>,
+    pub mod_version_enforce: bool,
+    pub maintenance_enabled: bool,
+    pub maintenance_hour: u32,
+    pub maintenance_optimize_min_rows: u64,
+    pub storage_alert_threshold_mb: u64,
+    pub response_compression_enabled: bool,
+    pub response_compression_min_bytes: u16,
+    pub response_compression_content_types: Vec<String>,
+    pub alert_group_by_player: bool,
+    pub alert_group_window_seconds: u64,
+    pub anomaly_link_target: String,
+    pub custom_burst_types: Vec<String>,
+    pub custom_burst_threshold: u64,
+    pub custom_burst_window_seconds: u64,
+    /// Unix socket path (named pipe on Windows) served instead of `bind_addr` when set.
+    pub bind_socket: String,
+    /// PEM certificate chain; with `tls_key_path` the TCP listener serves HTTPS and picks up
+    /// replaced files without a restart. Empty serves plain HTTP.
+    pub tls_cert_path: String,
+    /// PEM private key matching `tls_cert_path`.
+    pub tls_key_path: String,
+    pub degraded_cache_size: usize,
+    pub dead_letter_max_events: usize,
+    pub degraded_recovery_seconds: u64,
+    /// How often a background task pings ClickHouse; `health_ready` then reports its last
+    /// result instead of pinging per request. 0 disables the watchdog.
+    pub clickhouse_watchdog_interval_seconds: u64,
+    /// Consecutive failed watchdog pings before ClickHouse counts as down and a system alert
+    /// is sent.
+    pub clickhouse_watchdog_failures: u32,
+    pub strict_profiles: Vec<StrictProfile>,
+    pub daily_quota_alert_enabled: bool,
+    /// Top offenders listed on the daily report; 0 leaves the list out. Every player in the
+    /// report's tables gets a drill-down page either way.
+    pub report_player_pages: usize,
+    /// Newest daily reports kept in `report_dir` after each run; 0 keeps them all.
+    pub report_retention_count: usize,
+    /// Signs the report links of webhook messages, so `/reports/...` opens them without an API
+    /// token until they expire; None sends plain links.
+    pub report_link_secret: Option<String>,
+    /// How long a signed report link stays valid.
+    pub report_link_ttl_hours: u64,
+    /// Keys the player pseudonyms of ML exports, so they stay stable across exports and restarts;
+    /// None keys them with a random secret per start.
+    pub ml_export_secret: Option<String>,
+    /// Ingest enrichers run in this order before events are stored and analyzed.
+    pub enrichers: Vec<String>,
+    /// Run as one of several replicas sharing analyzer state.
+    pub cluster_mode: bool,
+    /// Instance holding the shared analyzer windows; empty when this instance holds them.
+    pub cluster_state_url: String,
+    /// Address of the optional gRPC ingest server; empty disables it.
+    pub grpc_bind_addr: String,
+    /// `mqtt://host:port` of the MQTT broker; empty disables the MQTT bridge.
+    pub mqtt_broker_url: String,
+    pub mqtt_client_id: String,
+    pub mqtt_username: Option<String>,
+    pub mqtt_password: Option<String>,
+    /// Topic anomalies are published to; empty disables anomaly publishing.
+    pub mqtt_anomaly_topic: String,
+    /// Topic ClickHouse health transitions are published to; empty disables them.
+    pub mqtt_health_topic: String,
+    /// Topic filter ingest envelopes are read from; empty disables MQTT ingest.
+    pub mqtt_ingest_topic: String,
+    /// Applied to report and alert content, in order.
+    pub redaction_rules: Vec<RedactionRule>,
+    /// File accepted ingest batches are appended to as JSON lines; empty disables recording.
+    pub ingest_record_path: String,
+    /// Share of batches recorded, from 0 to 1.
+    pub ingest_record_sample_rate: f64,
+    /// Recording stops once the file reaches this size.
+    pub ingest_record_max_mb: u64,
+    /// Per-team alert targets, matched through the player teams in `player_teams.json`.
+    pub alert_team_routes: Vec<AlertTeamRoute>,
+    /// Announces rule changes (API or on disk) and `config.toml` edits to the alert group.
+    pub config_change_alert_enabled: bool,
+    /// A rule taking longer than this for one batch is logged as slow; 0 disables the warning.
+    pub slow_rule_budget_ms: u64,
+    /// Day of the month the rule hygiene report for the previous month is written; 0 disables it.
+    pub rule_hygiene_report_day: u32,
+    /// Default length of an origin type learning period started without `days`.
+    pub origin_learning_days: u32,
+    /// Enrollment keys binding ingest sources to their `server_id`, sent as `X-Lattice-Server-Key`.
+    pub server_keys: Vec<ServerKey>,
+    /// Rejects ingest without a valid server key; otherwise only bound `server_id`s need theirs.
+    pub server_identity_required: bool,
+    /// Alert lines per chat message; the rest of a batch is paged with `/更多`.
+    pub alert_max_lines: usize,
+    /// How long `/更多` can page through the latest alert batch of a group; 0 disables paging.
+    pub alert_page_ttl_minutes: u64,
+    /// Risk level forced on every anomaly of an analyzer rule (`R1 = "MEDIUM"`), whatever the
+    /// rule itself computed; the original level is kept in the evidence as `risk_override`.
+    pub rule_risk_overrides: std::collections::BTreeMap<String, String>,
+    /// Secondary alert target (http(s) or ws(s)) used when `alert_webhook_url` fails all retries; the
+    /// primary is tried again and a recovery notice is sent once it accepts messages again.
+    pub alert_webhook_fallback_url: Option<String>,
+    /// Anomalies in the last full minute above this multiple of the trailing per-minute average raise a
+    /// system alert; `0` disables.
+    pub meta_alert_spike_multiple: f64,
+    /// Minutes of anomaly history the spike alarm averages as its baseline.
+    pub meta_alert_baseline_minutes: u64,
+    /// Raise a system alert when a full hour passes without ingested events although the same hour
+    /// a day earlier had some.
+    pub meta_alert_zero_ingest: bool,
+    /// Proxy (`http://`, `socks5://` or `socks5h://`) for the alert webhook and the NapCat
+    /// bridge only; team routes may set their own.
+    pub alert_webhook_proxy: Option<String>,
+    /// Timezone for times shown in alerts, reports and `display_time`: `local`, `UTC`, an offset
+    /// such as `+08:00` or an IANA name such as `Asia/Shanghai`.
+    pub display_timezone: String,
+    /// strftime pattern for those times.
+    pub display_time_format: String,
+    /// User detection rules (YAML, or JSON when the path ends in `.json`) evaluated next to the
+    /// built-in rules; a missing file means none.
+    pub detection_rules_path: String,
+    /// Composite rules (YAML, or JSON when the path ends in `.json`) combining the signals of
+    /// other rules; a missing file means none.
+    pub composite_rules_path: String,
+    /// Event ids remembered to drop events the mod re-sends after a network retry; 0 turns
+    /// ingest dedup off.
+    pub ingest_dedup_capacity: usize,
+    /// QQ group per `server_id` (`survival = 123456`) that gets that server's anomaly alerts
+    /// instead of `alert_group_id`; a team route still wins for its players.
+    pub alert_server_groups: std::collections::BTreeMap<String, i64>,
+    /// Sustained `/v2/ingest/events` requests per second allowed per source IP and per API
+    /// token; 0 disables rate limiting.
+    pub ingest_rate_limit_per_second: f64,
+    /// Requests a source or token may send at once before the sustained rate applies.
+    pub ingest_rate_limit_burst: u64,
+    /// Reverse proxies whose `X-Forwarded-For` is believed when keying ingest rate limits; any
+    /// other peer is limited by its own address.
+    pub trusted_proxies: Vec<String>,
+    /// When above 0, mutating requests must carry an `X-Lattice-Timestamp` at most this many
+    /// seconds off and an `X-Lattice-Nonce` not seen within that window; 0 turns the check off.
+    pub replay_window_seconds: u64,
+    /// Sends anomalies matching a route to its targets instead of the usual one, including rules
+    /// that would not alert on their own; unmatched anomalies alert as before.
+    pub alert_routing: AlertRouting,
+    #[serde(skip)]
+    pub config_path: Option<String>,
+    /// Where each config key's value came from, keyed by the TOML key name.
+    #[serde(skip)]
+    pub config_origins: std::collections::BTreeMap<String, ConfigOrigin>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigOrigin {
+    File,
+    Env,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfigEntry {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub origin: ConfigOrigin,
+    pub secret: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_path: Option<String>,
+    pub entries: Vec<EffectiveConfigEntry>,
+}
+
+/// A setting that works but is likely a mistake, logged at startup and listed for the desktop's
+/// post-start checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigWarning {
+    /// Stable identifier, e.g. `api_token_unset`.
+    pub code: String,
+    /// Config key to change.
+    pub key: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone)]
@@ -467,4 +2508,258 @@ pub struct DbConfig {
     pub clickhouse_database: String,
     pub clickhouse_user: Option<String>,
     pub clickhouse_password: Option<String>,
+    /// Lets ClickHouse buffer small inserts server-side (`async_insert`) instead of creating a
+    /// part per batch.
+    pub clickhouse_async_insert: bool,
+    /// With `clickhouse_async_insert`, waits until the buffered rows are flushed so insert
+    /// failures still reach the dead letter queue; off acknowledges on receipt.
+    pub clickhouse_wait_for_async_insert: bool,
+    /// `max_insert_block_size` for inserts; 0 keeps the server setting.
+    pub clickhouse_max_insert_block_size: u64,
+    /// Creates `item_events` and `custom_events` as `ReplacingMergeTree` keyed on `event_id`,
+    /// so re-sent events that slip past the ingest dedup are merged away. Only applies to
+    /// tables created after it is turned on.
+    pub clickhouse_dedup_events: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::anomaly_row;
+    use serde_json::Value;
+
+    /// Top-level keys of `value` once serialized, sorted. Changing any list below changes the API
+    /// contract and needs an `API_VERSION` bump.
+    fn keys<T: Serialize>(value: &T) -> Vec<String> {
+        let Value::Object(map) = serde_json::to_value(value).expect("serialize") else {
+            panic!("expected a JSON object");
+        };
+        let mut keys: Vec<String> = map.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn anomaly_schemas_are_stable() {
+        let view = AnomalyView {
+            id: "id".to_string(),
+            row: anomaly_row(),
+            display_time: String::new(),
+            rule_description: String::new(),
+            acknowledged: false,
+            seen: false,
+            explain: None,
+        };
+        assert_eq!(
+            keys(&view),
+            [
+                "acknowledged",
+                "count",
+                "display_time",
+                "event_time",
+                "evidence_json",
+                "id",
+                "item_id",
+                "player_name",
+                "player_uuid",
+                "reason",
+                "risk_level",
+                "rule_description",
+                "rule_id",
+                "seen",
+                "server_id",
+            ]
+        );
+        let mut fields = ANOMALY_FIELDS.to_vec();
+        fields.sort();
+        assert_eq!(keys(&view), fields);
+        let page = PagedResult {
+            items: vec![view],
+            page: 1,
+            page_size: 50,
+            total_items: 1,
+            total_pages: 1,
+            degraded: false,
+        };
+        assert_eq!(
+            keys(&page),
+            ["items", "page", "page_size", "total_items", "total_pages"]
+        );
+        let ack = AnomalyAckResult {
+            date: "2026-10-16".to_string(),
+            matched: 1,
+            acked_at_ms: 0,
+        };
+        assert_eq!(keys(&ack), ["acked_at_ms", "date", "matched"]);
+        let trend = AnomalyDailySummaryRow {
+            date: "2026-10-16".to_string(),
+            server_id: "server-01".to_string(),
+            rule_id: "R4".to_string(),
+            risk_level: "HIGH".to_string(),
+            count: 1,
+        };
+        assert_eq!(
+            keys(&trend),
+            ["count", "date", "risk_level", "rule_id", "server_id"]
+        );
+        let envelope = ApiEnvelope {
+            api_version: API_VERSION.to_string(),
+            data: ack,
+        };
+        assert_eq!(keys(&envelope), ["api_version", "data"]);
+    }
+
+    #[test]
+    fn rule_and_registry_schemas_are_stable() {
+        let suppression = AnomalySuppression {
+            id: "s1".to_string(),
+            rule_id: Some("R4".to_string()),
+            player: Some("Steve".to_string()),
+            server_id: Some("server-01".to_string()),
+            item_id: Some("minecraft:diamond".to_string()),
+            reason: Some("event".to_string()),
+            created_at_ms: 0,
+            expires_at_ms: 1,
+            expiry_notified: false,
+        };
+        assert_eq!(
+            keys(&suppression),
+            [
+                "created_at_ms",
+                "expires_at_ms",
+                "expiry_notified",
+                "id",
+                "item_id",
+                "player",
+                "reason",
+                "rule_id",
+                "server_id",
+            ]
+        );
+        let scan = StorageScanRow {
+            event_time: OffsetDateTime::UNIX_EPOCH,
+            item_id: "minecraft:diamond".to_string(),
+            count: 64,
+            storage_mod: "minecraft".to_string(),
+            storage_id: "chest".to_string(),
+            dim: "minecraft:overworld".to_string(),
+            x: Some(0),
+            y: Some(64),
+            z: Some(0),
+            rule_id: "R12".to_string(),
+            rule_description: String::new(),
+            threshold: 32,
+            risk_level: "HIGH".to_string(),
+            reason: String::new(),
+        };
+        assert_eq!(
+            keys(&scan),
+            [
+                "count",
+                "dim",
+                "event_time",
+                "item_id",
+                "reason",
+                "risk_level",
+                "rule_description",
+                "rule_id",
+                "storage_id",
+                "storage_mod",
+                "threshold",
+                "x",
+                "y",
+                "z",
+            ]
+        );
+        let mut fields = STORAGE_SCAN_FIELDS.to_vec();
+        fields.sort();
+        assert_eq!(keys(&scan), fields);
+        let event = ItemEventRow {
+            event_time: OffsetDateTime::UNIX_EPOCH,
+            event_id: "evt-1".to_string(),
+            server_id: "server-01".to_string(),
+            event_type: "ACQUIRE".to_string(),
+            player_uuid: "uuid-1".to_string(),
+            player_name: "Steve".to_string(),
+            item_id: "minecraft:diamond".to_string(),
+            count: 1,
+            origin_id: String::new(),
+            origin_type: "craft".to_string(),
+            origin_ref: String::new(),
+            source_type: String::new(),
+            source_ref: String::new(),
+            storage_mod: String::new(),
+            storage_id: String::new(),
+            actor_type: "player".to_string(),
+            trace_id: String::new(),
+            item_fingerprint: String::new(),
+            dim: "minecraft:overworld".to_string(),
+            x: Some(0),
+            y: Some(64),
+            z: Some(0),
+        };
+        let mut fields = ITEM_EVENT_FIELDS.to_vec();
+        fields.sort();
+        assert_eq!(keys(&event), fields);
+        let rule = KeyItemRuleApi {
+            item_id: "minecraft:diamond".to_string(),
+            threshold: 64,
+            risk_level: "HIGH".to_string(),
+            daily_quota: Some(256),
+            unit: None,
+            stack_size: None,
+            unit_value: None,
+        };
+        assert_eq!(
+            keys(&rule),
+            ["daily_quota", "item_id", "risk_level", "threshold"]
+        );
+        let item = ItemRegistryEntry {
+            item_id: "minecraft:diamond".to_string(),
+            name: Some("Diamond".to_string()),
+            names: None,
+            namespace: Some("minecraft".to_string()),
+            path: Some("diamond".to_string()),
+            max_stack_size: None,
+        };
+        assert_eq!(keys(&item), ["item_id", "name", "namespace", "path"]);
+    }
+
+    #[test]
+    fn alert_routes_collect_the_targets_of_every_matching_route() {
+        let route = |rule_ids: &[&str], risk_levels: &[&str], targets: &[&str]| AlertRoute {
+            rule_ids: rule_ids.iter().map(|id| id.to_string()).collect(),
+            risk_levels: risk_levels.iter().map(|level| level.to_string()).collect(),
+            targets: targets.iter().map(|target| target.to_string()).collect(),
+        };
+        let routing = AlertRouting {
+            targets: Default::default(),
+            routes: vec![
+                route(&[], &["HIGH", "CRITICAL"], &["oncall", "default"]),
+                route(&["R4"], &[], &["review", "oncall"]),
+                route(&["R12"], &["MEDIUM"], &["review"]),
+            ],
+        };
+        let row = |rule_id: &str, risk_level: &str| AnomalyRow {
+            event_time: OffsetDateTime::UNIX_EPOCH,
+            server_id: String::new(),
+            player_uuid: String::new(),
+            player_name: String::new(),
+            item_id: String::new(),
+            count: 1,
+            risk_level: risk_level.to_string(),
+            rule_id: rule_id.to_string(),
+            reason: String::new(),
+            evidence_json: String::new(),
+        };
+        assert_eq!(
+            routing.targets_for(&row("R4", "high")),
+            ["oncall", "default", "review"]
+        );
+        assert_eq!(routing.targets_for(&row("R12", "MEDIUM")), ["review"]);
+        assert!(routing.targets_for(&row("R12", "LOW")).is_empty());
+        assert!(AlertRouting::default()
+            .targets_for(&row("R4", "HIGH"))
+            .is_empty());
+    }
 }
@@ -1,11 +1,15 @@
 // Backend Domain Layer
 
+pub mod contracts;
 pub mod entities;
 pub mod ports;
 pub mod services;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 pub mod utils;
 pub mod value_objects;
 
+pub use contracts::*;
 pub use entities::*;
 pub use ports::*;
 pub use services::*;
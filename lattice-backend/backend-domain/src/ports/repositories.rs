@@ -1,22 +1,56 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
+use time::OffsetDateTime;
 
 use crate::entities::{
+    ApiToken,
+    CompositeRule,
+    DetectionRule,
+    FingerprintCollision,
+    FingerprintUsage,
     ModConfigAck,
     ModConfigEnvelope,
+    OriginWhitelist,
+    AnomalyAckKey,
+    AnomalyAckRequest,
+    AnomalyDailySummaryRow,
     AnomalyRow,
+    AnomalySuppression,
+    ClickhousePreflight,
+    DeadLetterBatch,
+    PlayerBan,
+    PlayerTeam,
     IngestEvent,
+    ItemAnomalyStat,
+    ItemCountDistribution,
+    ItemEventFilter,
+    ItemEventRow,
     ItemRegistryEntry,
     KeyItemRule,
+    PartitionStat,
+    PlayerAnomalyCount,
+    PlayerEventSpan,
+    PlayerQuarantine,
+    PlayerItemAcquired,
+    PlayerItemDailyTotal,
     RconConfig,
+    ReportFile,
     ReportSummary,
+    RuleAnomalyCount,
+    RuleRevision,
+    ShadowConsistency,
+    StorageFinding,
     StorageScanEventRow,
+    StorageUsage,
 };
+use crate::value_objects::FieldSelection;
 
 #[async_trait]
 pub trait EventRepository: Send + Sync {
     async fn ensure_schema(&self) -> anyhow::Result<()>;
     async fn insert_events(&self, events: &[IngestEvent]) -> anyhow::Result<()>;
+    /// Stores `custom` family events; their payload is kept as JSON next to `custom_type`.
+    async fn insert_custom_events(&self, events: &[IngestEvent]) -> anyhow::Result<()>;
     async fn fetch_storage_scan_events(
         &self,
         date: &str,
@@ -28,14 +62,104 @@ pub trait EventRepository: Send + Sync {
         date: &str,
         item: Option<&str>,
     ) -> anyhow::Result<u64>;
+    /// Location columns outside `fields` come back empty.
     async fn fetch_storage_scan_events_page(
         &self,
         date: &str,
         item: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
     ) -> anyhow::Result<Vec<StorageScanEventRow>>;
+    /// Newest first by `(event_time, event_id)`, starting after `after`. Unlike an offset, the
+    /// cursor does not shift while snapshots are ingested, so reading every chunk of a day
+    /// neither repeats nor skips a row.
+    async fn fetch_storage_scan_events_after(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        after: Option<(OffsetDateTime, &str)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<StorageScanEventRow>>;
+    async fn count_item_events(&self, filter: &ItemEventFilter) -> anyhow::Result<u64>;
+    /// Newest first; columns outside `fields` come back empty, `event_time` and `event_id`
+    /// are always read.
+    async fn fetch_item_events_page(
+        &self,
+        filter: &ItemEventFilter,
+        offset: usize,
+        limit: usize,
+        fields: &FieldSelection,
+    ) -> anyhow::Result<Vec<ItemEventRow>>;
     async fn ping(&self) -> anyhow::Result<()>;
+    /// Probes CREATE, INSERT, SELECT and ALTER on the configured database; fails only when
+    /// ClickHouse is unreachable.
+    async fn check_permissions(&self) -> anyhow::Result<ClickhousePreflight>;
+    /// Writes a marker row to the preflight scratch table and reads it back.
+    async fn round_trip_check(&self) -> anyhow::Result<()>;
+    /// `ACQUIRE` totals on `date` for every (player, item) pair among the given ids.
+    async fn fetch_daily_acquired_totals(
+        &self,
+        date: &str,
+        player_uuids: &[String],
+        item_ids: &[String],
+    ) -> anyhow::Result<Vec<PlayerItemDailyTotal>>;
+    /// Distinct item ids with an event at or after `since_ms`.
+    async fn fetch_item_ids_seen_since(&self, since_ms: i64) -> anyhow::Result<Vec<String>>;
+    async fn fetch_item_count_distributions(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        item_ids: &[String],
+    ) -> anyhow::Result<Vec<ItemCountDistribution>>;
+    /// `None` when the player has no stored events.
+    async fn fetch_player_event_span(
+        &self,
+        player_uuid: &str,
+    ) -> anyhow::Result<Option<PlayerEventSpan>>;
+    /// The player's `ACQUIRE` totals per item since `since_ms`, largest first.
+    async fn fetch_player_acquired_items(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<PlayerItemAcquired>>;
+    /// The player's latest events, newest first.
+    async fn fetch_player_recent_events(
+        &self,
+        player_uuid: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ItemEventRow>>;
+    /// `TRANSFER` and `ACQUIRE` events whose `trace_id` or `item_fingerprint` equals the given
+    /// value, oldest first; an empty argument matches nothing.
+    async fn fetch_events_by_trace(
+        &self,
+        trace_id: &str,
+        item_fingerprint: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ItemEventRow>>;
+    /// `TRANSFER` / `ACQUIRE` events at or after `since_ms` per server, ordered by `server_id`.
+    async fn fetch_fingerprint_usage(&self, since_ms: i64)
+        -> anyhow::Result<Vec<FingerprintUsage>>;
+    /// Per server, up to `per_server` fingerprints of `TRANSFER` / `ACQUIRE` events at or after
+    /// `since_ms` that more than one player or item carried, most players first.
+    async fn fetch_fingerprint_collisions(
+        &self,
+        since_ms: i64,
+        per_server: usize,
+    ) -> anyhow::Result<Vec<FingerprintCollision>>;
+}
+
+#[async_trait]
+pub trait MaintenanceRepository: Send + Sync {
+    async fn fetch_partition_stats(&self) -> anyhow::Result<Vec<PartitionStat>>;
+    async fn optimize_partition(&self, table: &str, partition_id: &str) -> anyhow::Result<()>;
+    /// Deletes a whole daily partition; callers confirm with the operator first.
+    async fn drop_partition(&self, table: &str, partition_id: &str) -> anyhow::Result<()>;
+    async fn fetch_storage_usage(&self) -> anyhow::Result<StorageUsage>;
+    /// Rows per event table on `date` in the primary and the dual-write shadow; `None` when no
+    /// shadow is configured.
+    async fn compare_shadow_counts(&self, date: &str) -> anyhow::Result<Option<ShadowConsistency>>;
 }
 
 #[async_trait]
@@ -51,20 +175,94 @@ pub trait AnomalyRepository: Send + Sync {
         date: &str,
         player: Option<&str>,
     ) -> anyhow::Result<u64>;
+    /// Text columns outside `fields` come back empty; the id and ack key columns are always read.
     async fn fetch_anomalies_page(
         &self,
         date: &str,
         player: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
+    ) -> anyhow::Result<Vec<AnomalyRow>>;
+    /// Newest first by ack key, starting after `after`; a cursor that stays put while
+    /// anomalies are recorded, so reading every chunk of a day neither repeats nor skips a row.
+    async fn fetch_anomalies_after(
+        &self,
+        date: &str,
+        player: Option<&str>,
+        after: Option<&AnomalyAckKey>,
+        limit: usize,
     ) -> anyhow::Result<Vec<AnomalyRow>>;
+    /// Anomalies recorded at exactly this event time, used to resolve an anomaly id.
+    async fn fetch_anomalies_at(&self, event_time_ms: i64) -> anyhow::Result<Vec<AnomalyRow>>;
     async fn fetch_summary(&self, date: &str) -> anyhow::Result<ReportSummary>;
+    /// Risk level counts of anomalies with `from_ms <= event_time < to_ms`.
+    async fn fetch_summary_between(
+        &self,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> anyhow::Result<ReportSummary>;
+    /// Players with the most HIGH anomalies on `date`, then the most anomalies overall.
+    async fn fetch_top_players(
+        &self,
+        date: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<PlayerAnomalyCount>>;
+    async fn rollup_daily_summary(&self, date: &str) -> anyhow::Result<()>;
+    async fn fetch_daily_summary(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        server_id: Option<&str>,
+        rule_id: Option<&str>,
+    ) -> anyhow::Result<Vec<AnomalyDailySummaryRow>>;
+    /// Acknowledges every anomaly matching the request filters and returns how many matched.
+    async fn ack_anomalies(
+        &self,
+        request: &AnomalyAckRequest,
+        acked_at_ms: i64,
+    ) -> anyhow::Result<u64>;
+    /// Acknowledges the one anomaly identified by `key`.
+    async fn ack_anomaly(
+        &self,
+        key: &AnomalyAckKey,
+        acked_at_ms: i64,
+        note: &str,
+        acked_by: &str,
+    ) -> anyhow::Result<()>;
+    async fn fetch_acked_keys(&self, date: &str) -> anyhow::Result<Vec<AnomalyAckKey>>;
+    /// Records read receipts for well-formed anomaly ids, each on the day its id encodes.
+    async fn mark_anomalies_seen(
+        &self,
+        ids: &[String],
+        seen_at_ms: i64,
+        seen_by: &str,
+    ) -> anyhow::Result<()>;
+    /// Distinct ids with a read receipt among the anomalies of `date`.
+    async fn fetch_seen_ids(&self, date: &str) -> anyhow::Result<Vec<String>>;
+    /// Per-item anomaly counts in `from_date..=to_date`; `rule_hits` counts only `rule_ids`.
+    async fn fetch_item_anomaly_stats(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        rule_ids: &[String],
+    ) -> anyhow::Result<Vec<ItemAnomalyStat>>;
+    /// The player's anomalies per rule since `since_ms`, most first.
+    async fn fetch_player_rule_counts(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+    ) -> anyhow::Result<Vec<RuleAnomalyCount>>;
 }
 
 #[async_trait]
 pub trait ConfigRepository: Send + Sync {
     async fn load_key_items(&self, path: &str) -> anyhow::Result<HashMap<String, KeyItemRule>>;
     async fn save_key_items(&self, path: &str, rules: &[KeyItemRule]) -> anyhow::Result<()>;
+    /// User detection rules; a missing file means none.
+    async fn load_detection_rules(&self, path: &str) -> anyhow::Result<Vec<DetectionRule>>;
+    /// Composite rules; a missing file means none.
+    async fn load_composite_rules(&self, path: &str) -> anyhow::Result<Vec<CompositeRule>>;
 
     async fn load_item_registry(&self, path: &str) -> anyhow::Result<Vec<ItemRegistryEntry>>;
     async fn save_item_registry(&self, path: &str, items: &[ItemRegistryEntry]) -> anyhow::Result<()>;
@@ -76,4 +274,49 @@ pub trait ConfigRepository: Send + Sync {
     async fn save_mod_config(&self, envelope: &ModConfigEnvelope) -> anyhow::Result<()>;
     async fn load_mod_config_ack(&self, server_id: &str) -> anyhow::Result<Option<ModConfigAck>>;
     async fn save_mod_config_ack(&self, ack: &ModConfigAck) -> anyhow::Result<()>;
+
+    async fn load_storage_findings(&self) -> anyhow::Result<Vec<StorageFinding>>;
+    async fn save_storage_findings(&self, findings: &[StorageFinding]) -> anyhow::Result<()>;
+    async fn load_suppressions(&self) -> anyhow::Result<Vec<AnomalySuppression>>;
+    async fn save_suppressions(&self, suppressions: &[AnomalySuppression]) -> anyhow::Result<()>;
+    async fn load_dead_letters(&self) -> anyhow::Result<Vec<DeadLetterBatch>>;
+    /// Adds one batch to the end of the stored queue, durably, before the write is answered.
+    async fn append_dead_letters(&self, batch: &DeadLetterBatch) -> anyhow::Result<()>;
+    /// Replaces the stored queue, e.g. after a replay or after dropping the oldest rows.
+    async fn save_dead_letters(&self, batches: &[DeadLetterBatch]) -> anyhow::Result<()>;
+    async fn load_bans(&self) -> anyhow::Result<Vec<PlayerBan>>;
+    async fn save_bans(&self, bans: &[PlayerBan]) -> anyhow::Result<()>;
+    async fn load_quarantines(&self) -> anyhow::Result<Vec<PlayerQuarantine>>;
+    async fn save_quarantines(&self, quarantines: &[PlayerQuarantine]) -> anyhow::Result<()>;
+    async fn load_api_tokens(&self) -> anyhow::Result<Vec<ApiToken>>;
+    async fn save_api_tokens(&self, tokens: &[ApiToken]) -> anyhow::Result<()>;
+    async fn load_player_teams(&self) -> anyhow::Result<Vec<PlayerTeam>>;
+    async fn save_player_teams(&self, teams: &[PlayerTeam]) -> anyhow::Result<()>;
+    async fn load_rule_revisions(&self) -> anyhow::Result<Vec<RuleRevision>>;
+    async fn save_rule_revisions(&self, revisions: &[RuleRevision]) -> anyhow::Result<()>;
+    /// The built-in whitelist when `origin_whitelist.json` does not exist yet.
+    async fn load_origin_whitelist(&self) -> anyhow::Result<OriginWhitelist>;
+    async fn save_origin_whitelist(&self, whitelist: &OriginWhitelist) -> anyhow::Result<()>;
+
+    /// Saves, reloads and removes a scratch file next to the config file.
+    async fn round_trip_check(&self) -> anyhow::Result<()>;
+    /// Creates `report_dir` if needed and writes and removes a probe file in it.
+    async fn check_report_dir(&self, report_dir: &str) -> anyhow::Result<()>;
+    /// Reports in `report_dir`, newest first.
+    async fn list_reports(&self, report_dir: &str) -> anyhow::Result<Vec<ReportFile>>;
+    /// Removes `{date}.html` and `{date}/`; returns false when neither existed.
+    async fn delete_report(&self, report_dir: &str, date: &str) -> anyhow::Result<bool>;
+    /// Contents of `file`, a validated path relative to `report_dir`; None when it is missing.
+    async fn read_report_file(
+        &self,
+        report_dir: &str,
+        file: &str,
+    ) -> anyhow::Result<Option<String>>;
+    /// Writes `file`, a path relative to `report_dir` chosen by the backend, e.g. a job export.
+    async fn write_report_file(
+        &self,
+        report_dir: &str,
+        file: &str,
+        contents: &str,
+    ) -> anyhow::Result<()>;
 }
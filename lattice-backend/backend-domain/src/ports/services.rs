@@ -1,10 +1,14 @@
 use async_trait::async_trait;
 
-use crate::entities::{AlertDeliveryRecord, AnomalyRow, RuntimeConfig};
+use crate::entities::{
+    AlertDeliveryRecord, AlertDeliveryTotals, AlertPreview, AnomalyRow, ClusterAnalyzeRequest,
+    HealthTransition, IngestEvent, RuntimeConfig,
+};
 
 #[async_trait]
 pub trait AlertService: Send + Sync {
     fn spawn_alerts(&self, config: RuntimeConfig, anomalies: Vec<AnomalyRow>);
+    fn preview_alerts(&self, config: &RuntimeConfig, anomalies: Vec<AnomalyRow>) -> AlertPreview;
     async fn send_system_alert(&self, config: &RuntimeConfig, message: &str) -> anyhow::Result<()>;
     async fn send_group_text(
         &self,
@@ -13,8 +17,12 @@ pub trait AlertService: Send + Sync {
         message: &str,
     ) -> anyhow::Result<()>;
     async fn check_alert_target(&self, config: &RuntimeConfig) -> anyhow::Result<()>;
+    /// Next page of the latest alert batch sent to `group_id`, while it has lines left and has
+    /// not expired (`alert_page_ttl_minutes`).
+    async fn next_alert_page(&self, group_id: i64) -> Option<String>;
     async fn list_alert_deliveries(&self, limit: usize) -> Vec<AlertDeliveryRecord>;
     async fn last_alert_delivery(&self) -> Option<AlertDeliveryRecord>;
+    async fn alert_delivery_totals(&self) -> AlertDeliveryTotals;
 }
 
 #[async_trait]
@@ -22,3 +30,36 @@ pub trait HealthCheckService: Send + Sync {
     async fn check_database(&self) -> anyhow::Result<bool>;
     async fn check_alert_target(&self) -> anyhow::Result<bool>;
 }
+
+/// Shared analyzer state for `cluster_mode`: replicas hand their batches to the one instance that
+/// keeps the sliding windows, so every replica sees the same detections.
+#[async_trait]
+pub trait AnalyzerStateService: Send + Sync {
+    async fn analyze(&self, request: &ClusterAnalyzeRequest) -> anyhow::Result<Vec<AnomalyRow>>;
+}
+
+/// Mirrors anomalies and health transitions to an external bus (e.g. MQTT). Publishing must not
+/// block ingest, so implementations queue or drop instead of waiting.
+pub trait EventPublisher: Send + Sync {
+    fn publish_anomalies(&self, anomalies: &[AnomalyRow]);
+    fn publish_health(&self, transition: &HealthTransition);
+}
+
+/// Ingest events arriving from somewhere other than the HTTP API (e.g. an MQTT subscription).
+#[async_trait]
+pub trait EventSource: Send {
+    /// Next batch of parsed events; `None` once the source is closed for good.
+    async fn next_batch(&mut self) -> Option<Vec<IngestEvent>>;
+}
+
+/// Keeps a replayable log of accepted ingest batches. Recording must not block ingest, so
+/// implementations sample, queue or drop instead of waiting.
+pub trait IngestRecorder: Send + Sync {
+    fn record(&self, recorded_at_ms: i64, events: &[IngestEvent]);
+}
+
+/// Renders the HTML report of a past or current day into `report_dir`.
+#[async_trait]
+pub trait ReportService: Send + Sync {
+    async fn generate_report(&self, date: &str) -> anyhow::Result<()>;
+}
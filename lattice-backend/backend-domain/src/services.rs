@@ -1,4 +1,32 @@
 // Domain services
 pub mod analyzer;
+pub mod anomaly_links;
+pub mod composite_rules;
+pub mod custom_detectors;
+pub mod daily_quota;
+pub mod enrichment;
+pub mod item_patterns;
+pub mod quarantine;
+pub mod rule_catalog;
+pub mod rule_engine;
+pub mod rule_hygiene;
+pub mod rule_presets;
+pub mod storage_findings;
+pub mod strictness;
+pub mod time_display;
 
 pub use analyzer::*;
+pub use anomaly_links::*;
+pub use composite_rules::*;
+pub use custom_detectors::*;
+pub use daily_quota::*;
+pub use enrichment::*;
+pub use item_patterns::*;
+pub use quarantine::*;
+pub use rule_catalog::*;
+pub use rule_engine::*;
+pub use rule_hygiene::*;
+pub use rule_presets::*;
+pub use storage_findings::*;
+pub use strictness::*;
+pub use time_display::*;
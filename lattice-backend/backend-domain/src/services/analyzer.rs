@@ -1,19 +1,172 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
-use crate::entities::{AnomalyRow, IngestEvent, KeyItemRule, TransferRecord};
+use serde_json::{json, Value};
+
+use crate::entities::{
+    AnalyzerServerStatus, AnomalyRow, CompositeRule, DetectionRule, IngestEvent, KeyItemRule,
+    OriginWhitelist, ThresholdUnit, TransferMatching, TransferRecord,
+};
+use crate::services::{evaluate_composite_rules, DetectionRuleSet, ItemPatternSet};
 use crate::utils::{current_millis, millis_to_utc};
 
+/// Timing labels of `RuleTimings`; rules decided in one pass over the origin cache share a label.
+/// User detection rules share the `user` label, composite rules the `composite` label.
+pub const TIMED_RULES: [&str; 12] = [
+    "R0",
+    "R1",
+    "R2",
+    "R3/R5/R8",
+    "R4",
+    "R6",
+    "R7",
+    "R9",
+    "R10",
+    "R12",
+    "user",
+    "composite",
+];
+const TIME_R0: usize = 0;
+const TIME_R1: usize = 1;
+const TIME_R2: usize = 2;
+const TIME_ORIGIN_REUSE: usize = 3;
+const TIME_R4: usize = 4;
+const TIME_R6: usize = 5;
+const TIME_R7: usize = 6;
+const TIME_R9: usize = 7;
+const TIME_R10: usize = 8;
+const TIME_R12: usize = 9;
+const TIME_DETECTION_RULES: usize = 10;
+const TIME_COMPOSITE_RULES: usize = 11;
+/// Most recent window records listed under `matched` in an anomaly's `explain`.
+const EXPLAIN_MATCHED_LIMIT: usize = 20;
+/// How far back R8 looks for an origin id the same player used before; no built-in rule looks
+/// further.
+const ORIGIN_REUSE_LONG_WINDOW_MS: i64 = 6 * 60 * 60 * 1000;
+
+/// Time each rule took in the last `analyze_batch`, summed over the batch's events. Transfer
+/// bookkeeping and matching count towards R0.
+#[derive(Debug, Clone, Default)]
+pub struct RuleTimings {
+    totals: [Duration; TIMED_RULES.len()],
+}
+
+impl RuleTimings {
+    /// Adds the time since `mark` to rule `index` and moves `mark` to now.
+    fn lap(&mut self, index: usize, mark: &mut Instant) {
+        let now = Instant::now();
+        self.totals[index] += now - *mark;
+        *mark = now;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Duration)> + '_ {
+        TIMED_RULES.iter().copied().zip(self.totals.iter().copied())
+    }
+}
+
+/// Transfer cache, origin history and rule windows of one `server_id`, so players and items on
+/// different servers never match each other's transfers or share a window.
 #[derive(Debug, Default)]
-pub struct Analyzer {
+struct ServerWindows {
     transfer_cache: VecDeque<TransferRecord>,
     origin_seen: HashMap<String, (String, i64)>,
     key_item_windows: HashMap<(String, String), VecDeque<i64>>,
     pickup_windows: HashMap<(String, String, String), VecDeque<i64>>,
     audit_windows: HashMap<(String, String, String), VecDeque<AuditRecord>>,
     strict_pickup_windows: HashMap<(String, String), VecDeque<CountRecord>>,
+    events_analyzed: u64,
+    last_event_ms: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Analyzer {
+    /// Keyed by `server_id`; events without one share the `""` partition.
+    servers: HashMap<String, ServerWindows>,
+    replay_now_ms: Option<i64>,
+    rule_timings: RuleTimings,
+    /// Pattern rules compiled once and reused until the rule set's patterns change.
+    item_patterns: ItemPatternSet,
+    origin_whitelist: OriginWhitelist,
+    /// Rule id to the risk level its anomalies get instead of the computed one.
+    risk_overrides: BTreeMap<String, String>,
+    /// User rules from `detection_rules_path`, evaluated on every event before the built-ins.
+    detection_rules: DetectionRuleSet,
+    /// Enabled rules from `composite_rules_path`, evaluated per event after all the others.
+    composite_rules: Vec<CompositeRule>,
+    /// `strict_transfer_fingerprint`: only explicit fingerprints are matched to transfers.
+    strict_transfer_fingerprint: bool,
+    /// `transfer_count_tolerance`, `transfer_count_tolerance_percent` and `transfer_aggregate`.
+    transfer_matching: TransferMatching,
 }
 
 impl Analyzer {
+    /// Expires windows against `now_ms` instead of the wall clock, so a recorded log replays
+    /// the same way it was analyzed live.
+    pub fn set_replay_clock(&mut self, now_ms: i64) {
+        self.replay_now_ms = Some(now_ms);
+    }
+
+    /// Origin types that do not raise R2 from the next batch on; none raise it while learning.
+    pub fn set_origin_whitelist(&mut self, whitelist: OriginWhitelist) {
+        self.origin_whitelist = whitelist;
+    }
+
+    /// Risk levels forced per rule id (`rule_risk_overrides`) from the next batch on.
+    pub fn set_risk_overrides(&mut self, overrides: BTreeMap<String, String>) {
+        self.risk_overrides = overrides;
+    }
+
+    /// User detection rules from the next batch on; their windows restart when the rules change.
+    pub fn set_detection_rules(&mut self, rules: Vec<DetectionRule>) {
+        self.detection_rules.set_rules(rules);
+    }
+
+    /// Composite rules from the next batch on; they keep no state of their own.
+    pub fn set_composite_rules(&mut self, rules: Vec<CompositeRule>) {
+        self.composite_rules = rules.into_iter().filter(|rule| rule.enabled).collect();
+    }
+
+    /// Whether an `ACQUIRE` without an `item_fingerprint` may still match a transfer on its
+    /// synthesized `item_id:nbt_hash`, which collides across every plain stack of the item.
+    pub fn set_strict_transfer_fingerprint(&mut self, strict: bool) {
+        self.strict_transfer_fingerprint = strict;
+    }
+
+    /// How far an `ACQUIRE` count may drift from the transfers it matches, from the next batch on.
+    pub fn set_transfer_matching(&mut self, matching: TransferMatching) {
+        self.transfer_matching = matching;
+    }
+
+    /// Per-rule evaluation time of the last `analyze_batch`.
+    pub fn rule_timings(&self) -> &RuleTimings {
+        &self.rule_timings
+    }
+
+    /// Size of each server's partition, ordered by `server_id`.
+    pub fn server_status(&self) -> Vec<AnalyzerServerStatus> {
+        let mut servers: Vec<AnalyzerServerStatus> = self
+            .servers
+            .iter()
+            .map(|(server_id, windows)| AnalyzerServerStatus {
+                server_id: server_id.clone(),
+                events_analyzed: windows.events_analyzed,
+                last_event_ms: windows.last_event_ms,
+                transfer_cache: windows.transfer_cache.len(),
+                origin_seen: windows.origin_seen.len(),
+                key_item_windows: windows.key_item_windows.len(),
+                pickup_windows: windows.pickup_windows.len(),
+                audit_windows: windows.audit_windows.len(),
+                strict_pickup_windows: windows.strict_pickup_windows.len(),
+            })
+            .collect();
+        servers.sort_by(|a, b| a.server_id.cmp(&b.server_id));
+        servers
+    }
+
+    fn server(&mut self, server_id: &str) -> &mut ServerWindows {
+        self.servers.entry(server_id.to_string()).or_default()
+    }
+
     pub fn analyze_batch(
         &mut self,
         events: &[IngestEvent],
@@ -23,16 +176,41 @@ impl Analyzer {
         strict_pickup_window_ms: i64,
         strict_pickup_threshold: i64,
     ) -> Vec<AnomalyRow> {
-        let now = current_millis();
+        let now = self.replay_now_ms.unwrap_or_else(current_millis);
         self.cleanup(now, transfer_window_ms, key_item_window_ms, strict_pickup_window_ms);
+        self.detection_rules.cleanup(now);
+        self.item_patterns.refresh(rules);
 
         let mut anomalies = Vec::new();
+        let mut timings = RuleTimings::default();
+        // Each analyzed event with the index of its first anomaly and whether it matched a
+        // transfer, for the composite rules.
+        let mut spans: Vec<(&IngestEvent, usize, bool)> = Vec::new();
         for event in events {
             if event.item_id.trim().is_empty() || event.item_id == "minecraft:air" || event.count <= 0 {
                 continue;
             }
+            spans.push((event, anomalies.len(), false));
+            let server_id = event.server_id.clone().unwrap_or_default();
+            let server = self.server(&server_id);
+            server.events_analyzed += 1;
+            server.last_event_ms = server.last_event_ms.max(Some(event.event_time));
+            let mut mark = Instant::now();
+            if !self.detection_rules.is_empty() {
+                for hit in self.detection_rules.evaluate(event) {
+                    anomalies.push(self.build_anomaly(
+                        event,
+                        &hit.risk_level,
+                        &hit.rule_id,
+                        &hit.reason,
+                        &None,
+                        hit.explain,
+                    ));
+                }
+                timings.lap(TIME_DETECTION_RULES, &mut mark);
+            }
             if event.event_type == "INVENTORY_SNAPSHOT" || event.event_type == "STORAGE_SNAPSHOT" {
-                if let Some(rule) = rules.get(&event.item_id) {
+                if let Some(rule) = self.item_patterns.find(rules, &event.item_id) {
                     let threshold = rule.effective_threshold();
                     if threshold > 0 && (event.count as u64) > threshold {
                         let risk = rule.effective_risk_level();
@@ -41,19 +219,32 @@ impl Analyzer {
                         } else {
                             ("R12", "Storage snapshot exceeds threshold")
                         };
+                        let explain = json!({
+                            "threshold": threshold,
+                            "count": event.count,
+                            "rule_item_id": rule.item_id,
+                        });
                         anomalies.push(self.build_anomaly(
                             event,
                             &risk,
                             rule_id,
                             reason,
                             &None,
+                            explain,
                         ));
                     }
                 }
+                let index = if event.event_type == "INVENTORY_SNAPSHOT" {
+                    TIME_R9
+                } else {
+                    TIME_R12
+                };
+                timings.lap(index, &mut mark);
                 continue;
             }
             if event.event_type == "TRANSFER" {
-                self.record_transfer(event);
+                self.server(&server_id).record_transfer(event);
+                timings.lap(TIME_R0, &mut mark);
                 continue;
             }
             if event.event_type != "ACQUIRE" {
@@ -69,14 +260,30 @@ impl Analyzer {
             let origin_id = event.origin_id.clone().unwrap_or_default();
             let origin_type = event.origin_type.clone().unwrap_or_default();
 
-            let transfer_match = self.find_transfer(
-                &player_uuid,
-                &item_fingerprint,
-                count,
-                transfer_window_ms,
-                event.event_time,
-            );
+            let fingerprint_refused = self.strict_transfer_fingerprint
+                && event
+                    .item_fingerprint
+                    .as_deref()
+                    .is_none_or(|fingerprint| fingerprint.trim().is_empty());
+            let matched = if fingerprint_refused {
+                None
+            } else {
+                let matching = self.transfer_matching;
+                self.server(&server_id).find_transfer(
+                    &player_uuid,
+                    &item_fingerprint,
+                    count,
+                    transfer_window_ms,
+                    event.event_time,
+                    &matching,
+                )
+            };
+            let transfer_match = matched.as_ref().map(|matched| matched.record.clone());
             let has_transfer = transfer_match.is_some();
+            if let Some(span) = spans.last_mut() {
+                span.2 = has_transfer;
+            }
+            timings.lap(TIME_R0, &mut mark);
 
             if origin_id.is_empty() && !has_transfer {
                 anomalies.push(self.build_anomaly(
@@ -85,43 +292,48 @@ impl Analyzer {
                     "R1",
                     "ACQUIRE missing origin and no transfer match",
                     &transfer_match,
+                    json!({
+                        "origin_id": null,
+                        "transfer_window_ms": transfer_window_ms,
+                        "fingerprint_refused": fingerprint_refused,
+                    }),
                 ));
             }
+            timings.lap(TIME_R1, &mut mark);
 
-            let whitelist = [
-                "world_pickup",
-                "container_click",
-                "storage_transfer",
-                "craft",
-                "smelt",
-                "trade",
-                "loot",
-                "barter",
-                "fishing",
-                "smithing",
-                "stonecutting",
-                "grindstone",
-                "anvil",
-                "brewing",
-                "loom",
-                "cartography",
-                "enchant",
-                "inventory_audit",
-                "command",
-            ];
-            if !origin_type.is_empty() && !whitelist.contains(&origin_type.as_str()) && !has_transfer {
+            if !origin_type.is_empty()
+                && !self.origin_whitelist.allows(&origin_type)
+                && !self.origin_whitelist.is_learning(now)
+                && !has_transfer
+            {
                 anomalies.push(self.build_anomaly(
                     event,
                     "HIGH",
                     "R2",
                     "ACQUIRE origin_type not in whitelist",
                     &transfer_match,
+                    json!({
+                        "origin_type": origin_type,
+                        "whitelisted_types": self.origin_whitelist.origin_types.len(),
+                        "learned_types": self.origin_whitelist.learned.len(),
+                    }),
                 ));
             }
+            timings.lap(TIME_R2, &mut mark);
 
             if !origin_id.is_empty() {
-                if let Some((prev_player, prev_time)) = self.origin_seen.get(&origin_id) {
+                let previous = self.servers[&server_id].origin_seen.get(&origin_id);
+                if let Some((prev_player, prev_time)) = previous {
                     let delta = (event.event_time - *prev_time).abs();
+                    let explain = |window_ms: i64| {
+                        json!({
+                            "origin_id": origin_id,
+                            "previous_player_uuid": prev_player,
+                            "previous_time_ms": prev_time,
+                            "delta_ms": delta,
+                            "window_ms": window_ms,
+                        })
+                    };
                     if prev_player != &player_uuid && delta < 10_000 {
                         anomalies.push(self.build_anomaly(
                             event,
@@ -129,6 +341,7 @@ impl Analyzer {
                             "R3",
                             "Duplicate origin_id across players",
                             &transfer_match,
+                            explain(10_000),
                         ));
                     } else if prev_player == &player_uuid
                         && !has_transfer
@@ -141,28 +354,32 @@ impl Analyzer {
                                 "R5",
                                 "Origin id reused by same player (possible duplication)",
                                 &transfer_match,
+                                explain(30_000),
                             ));
-                        } else if delta < 6 * 60 * 60 * 1000 {
+                        } else if delta < ORIGIN_REUSE_LONG_WINDOW_MS {
                             anomalies.push(self.build_anomaly(
                                 event,
                                 "MEDIUM",
                                 "R8",
                                 "Origin id reused by same player (long window)",
                                 &transfer_match,
+                                explain(ORIGIN_REUSE_LONG_WINDOW_MS),
                             ));
                         }
                     }
                 }
-                self.origin_seen
+                self.server(&server_id)
+                    .origin_seen
                     .insert(origin_id, (player_uuid.clone(), event.event_time));
             }
+            timings.lap(TIME_ORIGIN_REUSE, &mut mark);
 
             if !has_transfer && is_world_pickup(event, &origin_type) {
                 const DUP_PICKUP_WINDOW_MS: i64 = 15_000;
                 const DUP_PICKUP_THRESHOLD: usize = 2;
                 let nbt_hash = event.nbt_hash.clone().unwrap_or_default();
                 let key = (player_uuid.clone(), event.item_id.clone(), nbt_hash);
-                let window = self.pickup_windows.entry(key).or_default();
+                let window = self.server(&server_id).pickup_windows.entry(key).or_default();
                 window.push_back(event.event_time);
                 while let Some(front) = window.front() {
                     if event.event_time - *front > DUP_PICKUP_WINDOW_MS {
@@ -172,20 +389,32 @@ impl Analyzer {
                     }
                 }
                 if window.len() == DUP_PICKUP_THRESHOLD {
+                    let records: Vec<(i64, i64)> = window.iter().map(|time| (*time, 1)).collect();
                     anomalies.push(self.build_anomaly(
                         event,
                         "MEDIUM",
                         "R6",
                         "Rapid repeated world pickup of identical item",
                         &transfer_match,
+                        explain_window(DUP_PICKUP_WINDOW_MS, DUP_PICKUP_THRESHOLD as u64, &records),
                     ));
                 }
             }
+            timings.lap(TIME_R6, &mut mark);
 
             if strict_pickup_window_ms > 0 && strict_pickup_threshold > 0 && !has_transfer && is_world_pickup(event, &origin_type) {
                 let key = (player_uuid.clone(), event.item_id.clone());
-                let should_alert = {
-                    let window = self.strict_pickup_windows.entry(key.clone()).or_default();
+                let pickup_rule = self
+                    .item_patterns
+                    .find(rules, &event.item_id)
+                    .filter(|rule| rule.threshold_unit() != ThresholdUnit::Items);
+                let weight = pickup_rule.map_or(1.0, KeyItemRule::item_weight);
+                let explain = {
+                    let window = self
+                        .server(&server_id)
+                        .strict_pickup_windows
+                        .entry(key.clone())
+                        .or_default();
                     window.push_back(CountRecord {
                         time_ms: event.event_time,
                         count: event.count,
@@ -198,28 +427,44 @@ impl Analyzer {
                         }
                     }
                     let sum: i64 = window.iter().map(|entry| entry.count).sum();
-                    sum >= strict_pickup_threshold
+                    let weighted_sum = sum as f64 * weight;
+                    (weighted_sum >= strict_pickup_threshold as f64).then(|| {
+                        let records: Vec<(i64, i64)> =
+                            window.iter().map(|entry| (entry.time_ms, entry.count)).collect();
+                        let mut explain = explain_window(
+                            strict_pickup_window_ms,
+                            strict_pickup_threshold as u64,
+                            &records,
+                        );
+                        if let Some(rule) = pickup_rule {
+                            explain_unit(&mut explain, rule, weighted_sum);
+                            explain["item_weight"] = weight.into();
+                        }
+                        explain
+                    })
                 };
-                if should_alert {
+                if let Some(explain) = explain {
                     anomalies.push(self.build_anomaly(
                         event,
                         "HIGH",
                         "R10",
                         "Large world pickup volume in short window",
                         &transfer_match,
+                        explain,
                     ));
-                    if let Some(window) = self.strict_pickup_windows.get_mut(&key) {
+                    if let Some(window) = self.server(&server_id).strict_pickup_windows.get_mut(&key) {
                         window.clear();
                     }
                 }
             }
+            timings.lap(TIME_R10, &mut mark);
 
             if origin_type == "inventory_audit" && !has_transfer {
                 const AUDIT_WINDOW_MS: i64 = 30_000;
                 const AUDIT_THRESHOLD: i64 = 16;
                 let nbt_hash = event.nbt_hash.clone().unwrap_or_default();
                 let key = (player_uuid.clone(), event.item_id.clone(), nbt_hash);
-                let window = self.audit_windows.entry(key).or_default();
+                let window = self.server(&server_id).audit_windows.entry(key).or_default();
                 let sum_before: i64 = window.iter().map(|entry| entry.count).sum();
                 window.push_back(AuditRecord {
                     time_ms: event.event_time,
@@ -234,23 +479,31 @@ impl Analyzer {
                 }
                 let sum_after: i64 = window.iter().map(|entry| entry.count).sum();
                 if sum_before < AUDIT_THRESHOLD && sum_after >= AUDIT_THRESHOLD {
+                    let records: Vec<(i64, i64)> =
+                        window.iter().map(|entry| (entry.time_ms, entry.count)).collect();
+                    let mut explain =
+                        explain_window(AUDIT_WINDOW_MS, AUDIT_THRESHOLD as u64, &records);
+                    explain["sum_before"] = sum_before.into();
                     anomalies.push(self.build_anomaly(
                         event,
                         "HIGH",
                         "R7",
                         "Inventory gain without source (rapid increase)",
                         &transfer_match,
+                        explain,
                     ));
                 }
             }
+            timings.lap(TIME_R7, &mut mark);
 
-            if let Some(rule) = rules.get(&event.item_id) {
+            if let Some(rule) = self.item_patterns.find(rules, &event.item_id) {
                 let threshold = rule.effective_threshold();
                 if threshold == 0 {
+                    timings.lap(TIME_R4, &mut mark);
                     continue;
                 }
                 let key = (player_uuid.clone(), event.item_id.clone());
-                let window = self.key_item_windows.entry(key).or_default();
+                let window = self.server(&server_id).key_item_windows.entry(key).or_default();
                 for _ in 0..count.max(0) {
                     window.push_back(event.event_time);
                 }
@@ -261,31 +514,145 @@ impl Analyzer {
                         break;
                     }
                 }
-                if window.len() as u64 > threshold {
+                let units = rule.units(window.len() as i64);
+                if units > threshold as f64 {
                     let risk = rule.effective_risk_level();
+                    let mut explain =
+                        explain_window(key_item_window_ms, threshold, &timestamp_runs(window));
+                    explain["rule_item_id"] = rule.item_id.clone().into();
+                    if rule.threshold_unit() != ThresholdUnit::Items {
+                        explain_unit(&mut explain, rule, units);
+                    }
                     anomalies.push(self.build_anomaly(
                         event,
                         &risk,
                         "R4",
                         "Rare item threshold exceeded",
                         &transfer_match,
+                        explain,
                     ));
                 }
             }
+            timings.lap(TIME_R4, &mut mark);
 
-            if has_transfer {
+            if let Some(matched) = &matched {
+                let mut explain = json!({
+                    "transfer_window_ms": transfer_window_ms,
+                    "delta_ms": event.event_time - matched.record.time_ms,
+                    "strategy": matched.strategy,
+                });
+                match matched.strategy {
+                    "tolerance" => explain["count_delta"] = json!(count - matched.record.count),
+                    "aggregate" => explain["transfers"] = json!(matched.transfers),
+                    _ => {}
+                }
                 anomalies.push(self.build_anomaly(
                     event,
                     "LOW",
                     "R0",
                     "Matched transfer chain",
                     &transfer_match,
+                    explain,
                 ));
             }
+            timings.lap(TIME_R0, &mut mark);
+        }
+        if !self.composite_rules.is_empty() {
+            let mut mark = Instant::now();
+            let base_len = anomalies.len();
+            for (index, (event, start, has_transfer)) in spans.iter().enumerate() {
+                let end = spans.get(index + 1).map_or(base_len, |next| next.1);
+                if *start == end {
+                    continue;
+                }
+                let key_item_risk = self
+                    .item_patterns
+                    .find(rules, &event.item_id)
+                    .map(KeyItemRule::effective_risk_level);
+                let hits = evaluate_composite_rules(
+                    &self.composite_rules,
+                    &anomalies[*start..end],
+                    *has_transfer,
+                    key_item_risk.as_deref(),
+                );
+                for hit in hits {
+                    anomalies.push(self.build_anomaly(
+                        event,
+                        &hit.risk_level,
+                        &hit.rule_id,
+                        &hit.reason,
+                        &None,
+                        hit.explain,
+                    ));
+                }
+            }
+            timings.lap(TIME_COMPOSITE_RULES, &mut mark);
         }
+        self.rule_timings = timings;
         anomalies
     }
 
+    fn build_anomaly(
+        &self,
+        event: &IngestEvent,
+        risk: &str,
+        rule_id: &str,
+        reason: &str,
+        transfer: &Option<TransferRecord>,
+        explain: Value,
+    ) -> AnomalyRow {
+        let mut evidence = json!({
+            "transfer": transfer,
+            "origin_id": event.origin_id,
+            "origin_type": event.origin_type,
+            "origin_ref": event.origin_ref,
+            "trace_id": event.trace_id,
+            "storage_mod": event.storage_mod,
+            "storage_id": event.storage_id,
+            "explain": explain,
+        });
+        let risk = match self.risk_overrides.get(rule_id) {
+            Some(level) if level != risk => {
+                evidence["risk_override"] = json!({
+                    "from": risk,
+                    "to": level,
+                    "source": "rule_risk_overrides",
+                });
+                level.as_str()
+            }
+            _ => risk,
+        };
+        let evidence_json = evidence.to_string();
+        AnomalyRow {
+            event_time: millis_to_utc(event.event_time),
+            server_id: event.server_id.clone().unwrap_or_default(),
+            player_uuid: event.player_uuid.clone().unwrap_or_default(),
+            player_name: event.player_name.clone().unwrap_or_default(),
+            item_id: event.item_id.clone(),
+            count: event.count,
+            risk_level: risk.to_string(),
+            rule_id: rule_id.to_string(),
+            reason: reason.to_string(),
+            evidence_json,
+        }
+    }
+
+    /// Also drops the partitions of servers quiet for longer than every window, which hold
+    /// nothing a rule still reads, so servers that went away do not pile up.
+    fn cleanup(&mut self, now: i64, transfer_window_ms: i64, key_item_window_ms: i64, strict_pickup_window_ms: i64) {
+        let idle_ms = ORIGIN_REUSE_LONG_WINDOW_MS
+            .max(transfer_window_ms)
+            .max(key_item_window_ms)
+            .max(strict_pickup_window_ms);
+        self.servers
+            .retain(|_, server| server.last_event_ms.is_some_and(|last| now - last <= idle_ms));
+        for server in self.servers.values_mut() {
+            server.cleanup(now, transfer_window_ms, key_item_window_ms, strict_pickup_window_ms);
+        }
+    }
+}
+
+impl ServerWindows {
     fn record_transfer(&mut self, event: &IngestEvent) {
         let record = TransferRecord {
             time_ms: event.event_time,
@@ -303,6 +670,8 @@ impl Analyzer {
         self.transfer_cache.push_back(record);
     }
 
+    /// Tries an exact count first, then the closest count within tolerance, then (with
+    /// `aggregate`) the newest transfers whose counts add up to `count` within tolerance.
     fn find_transfer(
         &self,
         player_uuid: &str,
@@ -310,47 +679,62 @@ impl Analyzer {
         count: i64,
         window_ms: i64,
         event_time: i64,
-    ) -> Option<TransferRecord> {
-        self.transfer_cache
+        matching: &TransferMatching,
+    ) -> Option<TransferMatch> {
+        let candidates: Vec<&TransferRecord> = self
+            .transfer_cache
             .iter()
             .rev()
-            .find(|record| {
+            .filter(|record| {
                 record.player_uuid == player_uuid
                     && record.item_fingerprint == item_fingerprint
-                    && record.count == count
                     && (event_time - record.time_ms).abs() <= window_ms
             })
-            .cloned()
-    }
-
-    fn build_anomaly(
-        &self,
-        event: &IngestEvent,
-        risk: &str,
-        rule_id: &str,
-        reason: &str,
-        transfer: &Option<TransferRecord>,
-    ) -> AnomalyRow {
-        let evidence_json = serde_json::json!({
-            "transfer": transfer,
-            "origin_id": event.origin_id,
-            "origin_type": event.origin_type,
-            "origin_ref": event.origin_ref,
-            "trace_id": event.trace_id,
-        })
-        .to_string();
-        AnomalyRow {
-            event_time: millis_to_utc(event.event_time),
-            server_id: event.server_id.clone().unwrap_or_default(),
-            player_uuid: event.player_uuid.clone().unwrap_or_default(),
-            player_name: event.player_name.clone().unwrap_or_default(),
-            item_id: event.item_id.clone(),
-            count: event.count,
-            risk_level: risk.to_string(),
-            rule_id: rule_id.to_string(),
-            reason: reason.to_string(),
-            evidence_json,
+            .collect();
+        let single = |record: &TransferRecord, strategy| TransferMatch {
+            record: record.clone(),
+            strategy,
+            transfers: vec![json!({ "time_ms": record.time_ms, "count": record.count })],
+        };
+        if let Some(record) = candidates.iter().find(|record| record.count == count) {
+            return Some(single(record, "exact"));
+        }
+        let tolerance = matching.tolerance_for(count);
+        if tolerance > 0 {
+            let closest = candidates
+                .iter()
+                .filter(|record| (record.count - count).abs() <= tolerance)
+                .min_by_key(|record| (record.count - count).abs());
+            if let Some(record) = closest {
+                return Some(single(record, "tolerance"));
+            }
         }
+        if !matching.aggregate {
+            return None;
+        }
+        let mut sum = 0;
+        let mut parts = Vec::new();
+        for record in candidates
+            .iter()
+            .filter(|record| record.count <= count + tolerance)
+        {
+            sum += record.count;
+            parts.push(*record);
+            if sum > count + tolerance {
+                return None;
+            }
+            if parts.len() > 1 && (sum - count).abs() <= tolerance {
+                return Some(TransferMatch {
+                    record: parts[0].clone(),
+                    strategy: "aggregate",
+                    transfers: parts
+                        .iter()
+                        .map(|record| json!({ "time_ms": record.time_ms, "count": record.count }))
+                        .collect(),
+                });
+            }
+        }
+        None
     }
 
     fn cleanup(&mut self, now: i64, transfer_window_ms: i64, key_item_window_ms: i64, strict_pickup_window_ms: i64) {
@@ -370,6 +754,7 @@ impl Analyzer {
                 }
             }
         }
+        self.key_item_windows.retain(|_, window| !window.is_empty());
         const DUP_PICKUP_WINDOW_MS: i64 = 15_000;
         let mut empty_keys = Vec::new();
         for (key, window) in self.pickup_windows.iter_mut() {
@@ -427,6 +812,49 @@ impl Analyzer {
     }
 }
 
+/// `explain` of a windowed rule: its window, threshold and the `(time_ms, count)` records inside
+/// the window when it fired, oldest first (only the latest `EXPLAIN_MATCHED_LIMIT` are listed).
+pub fn explain_window(window_ms: i64, threshold: u64, records: &[(i64, i64)]) -> Value {
+    let window_sum: i64 = records.iter().map(|(_, count)| count).sum();
+    let skipped = records.len().saturating_sub(EXPLAIN_MATCHED_LIMIT);
+    let matched: Vec<Value> = records[skipped..]
+        .iter()
+        .map(|(time_ms, count)| json!({ "time_ms": time_ms, "count": count }))
+        .collect();
+    json!({
+        "window_ms": window_ms,
+        "threshold": threshold,
+        "window_sum": window_sum,
+        "window_start_ms": records.first().map(|(time_ms, _)| time_ms),
+        "matched": matched,
+        "matched_total": records.len(),
+    })
+}
+
+/// Adds how a weighted rule turned item counts into the amount compared with the threshold.
+fn explain_unit(explain: &mut Value, rule: &KeyItemRule, weighted_sum: f64) {
+    let unit = rule.threshold_unit();
+    explain["unit"] = unit.as_str().into();
+    explain["weighted_sum"] = weighted_sum.into();
+    match unit {
+        ThresholdUnit::Stacks => explain["stack_size"] = rule.effective_stack_size().into(),
+        ThresholdUnit::Value => explain["unit_value"] = rule.effective_unit_value().into(),
+        ThresholdUnit::Items => {}
+    }
+}
+
+/// The key item window keeps one timestamp per unit; folds equal neighbours into records.
+fn timestamp_runs(window: &VecDeque<i64>) -> Vec<(i64, i64)> {
+    let mut runs: Vec<(i64, i64)> = Vec::new();
+    for time_ms in window {
+        match runs.last_mut() {
+            Some((last, count)) if last == time_ms => *count += 1,
+            _ => runs.push((*time_ms, 1)),
+        }
+    }
+    runs
+}
+
 fn is_world_pickup(event: &IngestEvent, origin_type: &str) -> bool {
     if origin_type == "world_pickup" {
         return true;
@@ -434,6 +862,15 @@ fn is_world_pickup(event: &IngestEvent, origin_type: &str) -> bool {
     matches!(event.storage_id.as_deref(), Some("world"))
 }
 
+/// Transfers an `ACQUIRE` was matched to; `record` is the newest of them.
+#[derive(Debug)]
+struct TransferMatch {
+    record: TransferRecord,
+    /// `exact`, `tolerance` or `aggregate`.
+    strategy: &'static str,
+    transfers: Vec<Value>,
+}
+
 #[derive(Clone, Copy, Debug)]
 struct AuditRecord {
     time_ms: i64,
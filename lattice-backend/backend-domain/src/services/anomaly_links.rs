@@ -0,0 +1,74 @@
+use crate::entities::{AnomalyRow, RuntimeConfig};
+
+pub const DESKTOP_LINK_SCHEME: &str = "lattice";
+
+/// Stable id of an anomaly row: `<event time ms>-<hash of server, player, item and rule>`.
+/// The timestamp prefix lets a lookup go straight to the row without a date.
+pub fn anomaly_id(row: &AnomalyRow) -> String {
+    let event_ms = (row.event_time.unix_timestamp_nanos() / 1_000_000) as i64;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in [&row.server_id, &row.player_uuid, &row.item_id, &row.rule_id] {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{}-{:016x}", event_ms, hash)
+}
+
+/// Event time in epoch millis encoded in an anomaly id, or `None` when the id is malformed.
+pub fn anomaly_id_event_ms(id: &str) -> Option<i64> {
+    let (event_ms, hash) = id.trim().split_once('-')?;
+    if hash.len() != 16 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    event_ms.parse().ok().filter(|value: &i64| *value >= 0)
+}
+
+/// Deep link to the anomaly's evidence view for the configured `anomaly_link_target`.
+pub fn anomaly_link(config: &RuntimeConfig, row: &AnomalyRow) -> Option<String> {
+    let id = anomaly_id(row);
+    match config.anomaly_link_target.as_str() {
+        "desktop" => Some(format!("{}://anomaly/{}", DESKTOP_LINK_SCHEME, id)),
+        "web" => Some(format!(
+            "{}/ui/anomalies?id={}",
+            config.public_base_url.trim_end_matches('/'),
+            id
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn anomaly_id_round_trips_event_time() {
+        let row = AnomalyRow {
+            event_time: OffsetDateTime::from_unix_timestamp(1_771_000_000).unwrap(),
+            server_id: "server-01".to_string(),
+            player_uuid: "uuid-1".to_string(),
+            player_name: "Steve".to_string(),
+            item_id: "minecraft:diamond".to_string(),
+            count: 64,
+            risk_level: "HIGH".to_string(),
+            rule_id: "R4".to_string(),
+            reason: String::new(),
+            evidence_json: "{}".to_string(),
+        };
+        let id = anomaly_id(&row);
+        assert_eq!(id, anomaly_id(&row));
+        assert_eq!(anomaly_id_event_ms(&id), Some(1_771_000_000_000));
+        assert_ne!(
+            id,
+            anomaly_id(&AnomalyRow {
+                rule_id: "R12".to_string(),
+                ..row
+            })
+        );
+        assert_eq!(anomaly_id_event_ms("1771000000000-xyz"), None);
+        assert_eq!(anomaly_id_event_ms("not-an-id"), None);
+    }
+}
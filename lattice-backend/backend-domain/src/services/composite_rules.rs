@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+
+use serde_json::json;
+
+use crate::entities::{AnomalyRow, CompositeRule};
+use crate::services::DetectionHit;
+use crate::value_objects::{is_risk_level, risk_rank, RISK_LEVEL_NAMES};
+
+/// `C` followed by digits; only composite rules may take these ids.
+pub fn is_composite_rule_id(rule_id: &str) -> bool {
+    rule_id
+        .strip_prefix('C')
+        .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Checks a loaded composite rule file, so a typo fails at load instead of silently never firing.
+pub fn validate_composite_rules(rules: &[CompositeRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        let id = rule.id.trim();
+        if !is_composite_rule_id(id) {
+            return Err(format!(
+                "composite rule {}: id must be C followed by digits",
+                id
+            ));
+        }
+        if !ids.insert(id) {
+            return Err(format!("composite rule {}: duplicate id", id));
+        }
+        if !is_risk_level(&rule.risk_level) {
+            return Err(format!(
+                "composite rule {}: risk_level must be {}",
+                id, RISK_LEVEL_NAMES
+            ));
+        }
+        if rule.all_of.is_empty() {
+            return Err(format!("composite rule {}: all_of must name a rule", id));
+        }
+        if let Some(signal) = rule
+            .all_of
+            .iter()
+            .find(|signal| signal.trim().is_empty() || is_composite_rule_id(signal.trim()))
+        {
+            return Err(format!(
+                "composite rule {}: all_of entry {:?} must be a built-in or user rule id",
+                id, signal
+            ));
+        }
+        if let Some(level) = &rule.key_item_risk {
+            if !is_risk_level(level) {
+                return Err(format!(
+                    "composite rule {}: key_item_risk must be {}",
+                    id, RISK_LEVEL_NAMES
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The composite rules that hold for one event, given the anomalies the other rules raised for
+/// it, whether it matched a transfer and the risk level of its key item rule, if any.
+pub fn evaluate_composite_rules(
+    rules: &[CompositeRule],
+    fired: &[AnomalyRow],
+    has_transfer: bool,
+    key_item_risk: Option<&str>,
+) -> Vec<DetectionHit> {
+    let mut hits = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        let matched: Vec<&AnomalyRow> = rule
+            .all_of
+            .iter()
+            .filter_map(|rule_id| fired.iter().find(|row| row.rule_id == rule_id.trim()))
+            .collect();
+        if matched.len() < rule.all_of.len() || (rule.no_transfer && has_transfer) {
+            continue;
+        }
+        let risk_met = rule.key_item_risk.as_deref().is_none_or(|required| {
+            key_item_risk.is_some_and(|level| risk_rank(level) >= risk_rank(required))
+        });
+        if !risk_met {
+            continue;
+        }
+        let reason = if rule.description.is_empty() {
+            format!("Composite rule {} matched", rule.id)
+        } else {
+            rule.description.clone()
+        };
+        let explain = json!({
+            "composite_rule": rule.id,
+            "alert": rule.alert,
+            "matched": matched
+                .iter()
+                .map(|row| json!({ "rule_id": row.rule_id, "risk_level": row.risk_level }))
+                .collect::<Vec<_>>(),
+            "no_transfer": rule.no_transfer,
+            "key_item_risk": key_item_risk,
+        });
+        hits.push(DetectionHit {
+            rule_id: rule.id.clone(),
+            risk_level: rule.risk_level.clone(),
+            reason,
+            explain,
+        });
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::is_alerting_anomaly;
+    use crate::testing::Scenario;
+    use serde_json::Value;
+
+    fn rule(value: Value) -> CompositeRule {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn untraced_high_value() -> CompositeRule {
+        rule(json!({
+            "id": "C1",
+            "description": "Key item over threshold without a transfer",
+            "all_of": ["R4"],
+            "no_transfer": true,
+            "key_item_risk": "HIGH",
+        }))
+    }
+
+    #[test]
+    fn rules_are_validated_on_load() {
+        let ok = untraced_high_value();
+        assert!(validate_composite_rules(std::slice::from_ref(&ok)).is_ok());
+        assert!(validate_composite_rules(&[ok.clone(), ok]).is_err());
+        assert!(
+            validate_composite_rules(&[rule(json!({ "id": "R15", "all_of": ["R4"] }))]).is_err()
+        );
+        assert!(validate_composite_rules(&[rule(json!({ "id": "C2", "all_of": [] }))]).is_err());
+        assert!(
+            validate_composite_rules(&[rule(json!({ "id": "C2", "all_of": ["C1"] }))]).is_err()
+        );
+        assert!(validate_composite_rules(&[rule(json!({
+            "id": "C2",
+            "all_of": ["R4"],
+            "key_item_risk": "SEVERE",
+        }))])
+        .is_err());
+        assert!(serde_json::from_value::<CompositeRule>(json!({ "id": "C2" })).is_err());
+    }
+
+    #[test]
+    fn composite_fires_only_when_every_signal_holds() {
+        let outcome = Scenario::new()
+            .rule("minecraft:nether_star", 2)
+            .composite_rule(untraced_high_value())
+            .acquires("minecraft:nether_star", 3, "craft")
+            .run();
+        outcome.assert_rules(&["R4", "C1"]);
+        let hit = outcome.of_rule("C1")[0];
+        assert_eq!(hit.risk_level, "HIGH");
+        assert_eq!(hit.reason, "Key item over threshold without a transfer");
+        assert!(is_alerting_anomaly(hit));
+        let evidence: Value = serde_json::from_str(&hit.evidence_json).unwrap();
+        assert_eq!(evidence["explain"]["matched"][0]["rule_id"], "R4");
+        assert_eq!(evidence["explain"]["key_item_risk"], "HIGH");
+
+        Scenario::new()
+            .rule("minecraft:nether_star", 2)
+            .composite_rule(untraced_high_value())
+            .transfers("minecraft:nether_star", 3)
+            .acquires("minecraft:nether_star", 3, "craft")
+            .run()
+            .assert_rules(&["R4", "R0"]);
+        Scenario::new()
+            .rule_with_risk("minecraft:nether_star", 2, "MEDIUM")
+            .composite_rule(untraced_high_value())
+            .acquires("minecraft:nether_star", 3, "craft")
+            .run()
+            .assert_rules(&["R4"]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::entities::{AnomalyRow, IngestEvent, RuntimeConfig};
+use crate::services::explain_window;
+use crate::utils::millis_to_utc;
+
+pub const CUSTOM_BURST_RULE_ID: &str = "R13";
+/// Subscribing to this type delivers every custom event to the detector.
+pub const ANY_CUSTOM_TYPE: &str = "*";
+
+/// Detector for `custom` family events. Item rules never see these events and custom detectors
+/// never see item events, so plugins can be added without touching the analyzer.
+pub trait CustomEventDetector: Send + Sync {
+    fn name(&self) -> &str;
+    /// Custom types this detector subscribes to (lowercase), or `*` for all of them.
+    fn event_types(&self) -> Vec<String>;
+    fn detect(&mut self, events: &[&IngestEvent]) -> Vec<AnomalyRow>;
+}
+
+#[derive(Default)]
+pub struct CustomDetectorRegistry {
+    detectors: Vec<Box<dyn CustomEventDetector>>,
+}
+
+impl CustomDetectorRegistry {
+    /// The built-in detectors enabled by `config`; currently the burst detector when
+    /// `custom_burst_types` is set.
+    pub fn with_builtins(config: &RuntimeConfig) -> Self {
+        let mut registry = Self::default();
+        if !config.custom_burst_types.is_empty() {
+            registry.register(Box::new(CustomBurstDetector::new(
+                config.custom_burst_types.clone(),
+                config.custom_burst_threshold,
+                (config.custom_burst_window_seconds * 1000) as i64,
+            )));
+        }
+        registry
+    }
+
+    pub fn register(&mut self, detector: Box<dyn CustomEventDetector>) {
+        self.detectors.push(detector);
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.detectors
+            .iter()
+            .map(|detector| detector.name().to_string())
+            .collect()
+    }
+
+    /// Hands each detector the custom events of the types it subscribed to, in batch order.
+    pub fn analyze(&mut self, events: &[IngestEvent]) -> Vec<AnomalyRow> {
+        let mut anomalies = Vec::new();
+        for detector in &mut self.detectors {
+            let types = detector.event_types();
+            let subscribed = events
+                .iter()
+                .filter(|event| event.is_custom())
+                .filter(|event| {
+                    let custom_type = custom_type(event);
+                    types
+                        .iter()
+                        .any(|item| item == ANY_CUSTOM_TYPE || *item == custom_type)
+                })
+                .collect::<Vec<_>>();
+            if !subscribed.is_empty() {
+                anomalies.extend(detector.detect(&subscribed));
+            }
+        }
+        anomalies
+    }
+}
+
+/// Normalized `custom_type` of an event; empty when the sender left it out.
+pub fn custom_type(event: &IngestEvent) -> String {
+    event
+        .custom_type
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Built-in detector: too many events of one custom type from one player inside a sliding window,
+/// e.g. block-break bursts. Each event counts `count` times (at least once).
+pub struct CustomBurstDetector {
+    types: Vec<String>,
+    threshold: i64,
+    window_ms: i64,
+    windows: HashMap<(String, String), VecDeque<(i64, i64)>>,
+}
+
+impl CustomBurstDetector {
+    pub fn new(types: Vec<String>, threshold: u64, window_ms: i64) -> Self {
+        Self {
+            types: types
+                .into_iter()
+                .map(|item| item.trim().to_ascii_lowercase())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            threshold: threshold as i64,
+            window_ms,
+            windows: HashMap::new(),
+        }
+    }
+}
+
+impl CustomEventDetector for CustomBurstDetector {
+    fn name(&self) -> &str {
+        "custom_burst"
+    }
+
+    fn event_types(&self) -> Vec<String> {
+        self.types.clone()
+    }
+
+    fn detect(&mut self, events: &[&IngestEvent]) -> Vec<AnomalyRow> {
+        let mut anomalies = Vec::new();
+        if self.threshold <= 0 || self.window_ms <= 0 {
+            return anomalies;
+        }
+        for event in events {
+            let custom_type = custom_type(event);
+            let player = event.player_uuid.clone().unwrap_or_default();
+            let window = self
+                .windows
+                .entry((player, custom_type.clone()))
+                .or_default();
+            while window
+                .front()
+                .is_some_and(|(time_ms, _)| event.event_time - time_ms > self.window_ms)
+            {
+                window.pop_front();
+            }
+            window.push_back((event.event_time, event.count.max(1)));
+            let total: i64 = window.iter().map(|(_, count)| count).sum();
+            if total <= self.threshold {
+                continue;
+            }
+            let records: Vec<(i64, i64)> = window.iter().copied().collect();
+            window.clear();
+            let evidence_json = serde_json::json!({
+                "family": "custom",
+                "custom_type": custom_type,
+                "window_ms": self.window_ms,
+                "threshold": self.threshold,
+                "event_id": event.event_id,
+                "payload": event.payload,
+                "explain": explain_window(self.window_ms, self.threshold as u64, &records),
+            })
+            .to_string();
+            anomalies.push(AnomalyRow {
+                event_time: millis_to_utc(event.event_time),
+                server_id: event.server_id.clone().unwrap_or_default(),
+                player_uuid: event.player_uuid.clone().unwrap_or_default(),
+                player_name: event.player_name.clone().unwrap_or_default(),
+                item_id: format!("custom:{}", custom_type),
+                count: total,
+                risk_level: "MEDIUM".to_string(),
+                rule_id: CUSTOM_BURST_RULE_ID.to_string(),
+                reason: format!(
+                    "{} {} events within {}s",
+                    total,
+                    custom_type,
+                    self.window_ms / 1000
+                ),
+                evidence_json,
+            });
+        }
+        if let Some(latest) = events.iter().map(|event| event.event_time).max() {
+            let window_ms = self.window_ms;
+            self.windows.retain(|_, window| {
+                window
+                    .back()
+                    .is_some_and(|(time_ms, _)| latest - time_ms <= window_ms)
+            });
+        }
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_event(custom_type: &str, event_time: i64, count: i64) -> IngestEvent {
+        IngestEvent {
+            event_id: format!("evt-{}", event_time),
+            event_time,
+            server_id: Some("server-01".to_string()),
+            event_type: "CUSTOM".to_string(),
+            player_uuid: Some("uuid-1".to_string()),
+            player_name: Some("Steve".to_string()),
+            item_id: String::new(),
+            count,
+            nbt_hash: None,
+            origin_id: None,
+            origin_type: None,
+            origin_ref: None,
+            source_type: None,
+            source_ref: None,
+            storage_mod: None,
+            storage_id: None,
+            actor_type: None,
+            trace_id: None,
+            item_fingerprint: None,
+            dim: None,
+            x: None,
+            y: None,
+            z: None,
+            family: Some("custom".to_string()),
+            custom_type: Some(custom_type.to_string()),
+            payload: Some(serde_json::json!({ "block": "minecraft:stone" })),
+            item_name: None,
+            rule_risk_level: None,
+        }
+    }
+
+    #[test]
+    fn burst_detector_only_sees_subscribed_types() {
+        let mut registry = CustomDetectorRegistry::default();
+        registry.register(Box::new(CustomBurstDetector::new(
+            vec!["Block_Break".to_string()],
+            10,
+            60_000,
+        )));
+
+        let quiet = registry.analyze(&[
+            custom_event("block_break", 0, 6),
+            custom_event("command", 1_000, 50),
+        ]);
+        assert!(quiet.is_empty());
+
+        let burst = registry.analyze(&[custom_event("block_break", 30_000, 5)]);
+        assert_eq!(burst.len(), 1);
+        assert_eq!(burst[0].rule_id, CUSTOM_BURST_RULE_ID);
+        assert_eq!(burst[0].item_id, "custom:block_break");
+        assert_eq!(burst[0].count, 11);
+
+        let expired = registry.analyze(&[custom_event("block_break", 200_000, 5)]);
+        assert!(expired.is_empty());
+    }
+}
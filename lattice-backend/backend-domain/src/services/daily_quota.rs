@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::entities::{AnomalyRow, IngestEvent, KeyItemRule, PlayerItemDailyTotal};
+use crate::services::ItemPatternSet;
+use crate::utils::millis_to_utc;
+
+pub const DAILY_QUOTA_RULE_ID: &str = "R14";
+
+/// Latest `ACQUIRE` event per (player uuid, item) among items that carry a daily quota; these
+/// are the pairs whose daily totals need checking after a batch is stored.
+pub fn daily_quota_candidates<'a>(
+    events: &'a [IngestEvent],
+    rules: &HashMap<String, KeyItemRule>,
+    patterns: &ItemPatternSet,
+) -> HashMap<(String, String), &'a IngestEvent> {
+    let mut candidates: HashMap<(String, String), &IngestEvent> = HashMap::new();
+    for event in events {
+        if event.event_type != "ACQUIRE" {
+            continue;
+        }
+        let Some(player_uuid) = event.player_uuid.as_deref().filter(|uuid| !uuid.is_empty()) else {
+            continue;
+        };
+        if patterns
+            .find(rules, &event.item_id)
+            .is_none_or(|rule| rule.daily_quota.is_none())
+        {
+            continue;
+        }
+        let key = (player_uuid.to_string(), event.item_id.clone());
+        match candidates.get(&key) {
+            Some(latest) if latest.event_time >= event.event_time => {}
+            _ => {
+                candidates.insert(key, event);
+            }
+        }
+    }
+    candidates
+}
+
+/// R14 anomaly for a player over quota; `count` carries the accumulated daily total so alerts
+/// show it directly.
+pub fn build_daily_quota_anomaly(
+    total: &PlayerItemDailyTotal,
+    quota: u64,
+    rule: &KeyItemRule,
+    date: &str,
+    latest: &IngestEvent,
+) -> AnomalyRow {
+    let evidence_json = serde_json::json!({
+        "date": date,
+        "daily_total": total.total,
+        "daily_quota": quota,
+        "trace_id": latest.trace_id,
+        "explain": {
+            "threshold": quota,
+            "window_sum": total.total,
+            "date": date,
+            "rule_item_id": rule.item_id,
+        },
+    })
+    .to_string();
+    AnomalyRow {
+        event_time: millis_to_utc(latest.event_time),
+        server_id: latest.server_id.clone().unwrap_or_default(),
+        player_uuid: total.player_uuid.clone(),
+        player_name: latest
+            .player_name
+            .clone()
+            .unwrap_or_else(|| total.player_name.clone()),
+        item_id: total.item_id.clone(),
+        count: total.total,
+        risk_level: rule.effective_risk_level(),
+        rule_id: DAILY_QUOTA_RULE_ID.to_string(),
+        reason: format!("Daily quota exceeded: {} / {}", total.total, quota),
+        evidence_json,
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use crate::entities::{IngestEvent, ItemRegistryEntry, KeyItemRule};
+use crate::services::{ItemPatternSet, DEFAULT_RULE_LANG};
+
+pub const PLAYER_NAME_ENRICHER: &str = "player_name";
+pub const ITEM_NAME_ENRICHER: &str = "item_name";
+pub const RULE_METADATA_ENRICHER: &str = "rule_metadata";
+/// Built-in enrichers in their default order.
+pub const BUILTIN_ENRICHERS: [&str; 3] = [
+    PLAYER_NAME_ENRICHER,
+    ITEM_NAME_ENRICHER,
+    RULE_METADATA_ENRICHER,
+];
+
+/// Lookups shared by every enricher of one batch.
+pub struct EnrichmentContext<'a> {
+    pub item_registry: &'a [ItemRegistryEntry],
+    pub key_rules: &'a HashMap<String, KeyItemRule>,
+}
+
+/// One step of the ingest enrichment stage, run after parsing and before events are stored and
+/// analyzed, so both see the same values.
+pub trait EventEnricher: Send + Sync {
+    fn name(&self) -> &str;
+    fn enrich(&mut self, events: &mut [IngestEvent], context: &EnrichmentContext);
+}
+
+#[derive(Default)]
+pub struct EnrichmentChain {
+    enrichers: Vec<Box<dyn EventEnricher>>,
+}
+
+impl EnrichmentChain {
+    /// Builds the built-in enrichers named in `names`, in that order.
+    pub fn from_names(names: &[String]) -> Result<Self, String> {
+        let mut chain = Self::default();
+        for name in names {
+            chain.register(builtin_enricher(name)?);
+        }
+        Ok(chain)
+    }
+
+    pub fn register(&mut self, enricher: Box<dyn EventEnricher>) {
+        self.enrichers.push(enricher);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.enrichers.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.enrichers
+            .iter()
+            .map(|enricher| enricher.name().to_string())
+            .collect()
+    }
+
+    pub fn enrich(&mut self, events: &mut [IngestEvent], context: &EnrichmentContext) {
+        for enricher in &mut self.enrichers {
+            enricher.enrich(events, context);
+        }
+    }
+}
+
+pub fn builtin_enricher(name: &str) -> Result<Box<dyn EventEnricher>, String> {
+    match name {
+        PLAYER_NAME_ENRICHER => Ok(Box::new(PlayerNameEnricher::default())),
+        ITEM_NAME_ENRICHER => Ok(Box::new(ItemNameEnricher)),
+        RULE_METADATA_ENRICHER => Ok(Box::new(RuleMetadataEnricher::default())),
+        other => Err(format!(
+            "unknown enricher '{}', expected one of {}",
+            other,
+            BUILTIN_ENRICHERS.join(", ")
+        )),
+    }
+}
+
+/// Rewrites player names to the spelling first seen for them, so `steve` and `Steve` end up as
+/// one player in stored rows, per-player windows and reports.
+#[derive(Default)]
+pub struct PlayerNameEnricher {
+    canonical: HashMap<String, String>,
+}
+
+impl PlayerNameEnricher {
+    /// Bounds memory on servers with many one-off visitors; spellings are re-learned afterwards.
+    const MAX_NAMES: usize = 100_000;
+}
+
+impl EventEnricher for PlayerNameEnricher {
+    fn name(&self) -> &str {
+        PLAYER_NAME_ENRICHER
+    }
+
+    fn enrich(&mut self, events: &mut [IngestEvent], _context: &EnrichmentContext) {
+        for event in events.iter_mut() {
+            let Some(name) = event.player_name.as_mut() else {
+                continue;
+            };
+            let trimmed = name.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let key = trimmed.to_lowercase();
+            if self.canonical.len() >= Self::MAX_NAMES && !self.canonical.contains_key(&key) {
+                self.canonical.clear();
+            }
+            *name = self
+                .canonical
+                .entry(key)
+                .or_insert_with(|| trimmed.to_string())
+                .clone();
+        }
+    }
+}
+
+/// Fills `item_name` from the item registry (`name`, else the default-language entry of `names`).
+pub struct ItemNameEnricher;
+
+impl EventEnricher for ItemNameEnricher {
+    fn name(&self) -> &str {
+        ITEM_NAME_ENRICHER
+    }
+
+    fn enrich(&mut self, events: &mut [IngestEvent], context: &EnrichmentContext) {
+        if context.item_registry.is_empty() {
+            return;
+        }
+        let names: HashMap<&str, &str> = context
+            .item_registry
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.name.as_deref().or_else(|| {
+                    entry
+                        .names
+                        .as_ref()
+                        .and_then(|names| names.get(DEFAULT_RULE_LANG))
+                        .map(String::as_str)
+                })?;
+                Some((entry.item_id.as_str(), name))
+            })
+            .collect();
+        for event in events.iter_mut().filter(|event| !event.is_custom()) {
+            event.item_name = names
+                .get(event.item_id.as_str())
+                .map(|name| name.to_string());
+        }
+    }
+}
+
+/// Fills `rule_risk_level` with the key item rule's risk level for items that have one.
+#[derive(Default)]
+pub struct RuleMetadataEnricher {
+    item_patterns: ItemPatternSet,
+}
+
+impl EventEnricher for RuleMetadataEnricher {
+    fn name(&self) -> &str {
+        RULE_METADATA_ENRICHER
+    }
+
+    fn enrich(&mut self, events: &mut [IngestEvent], context: &EnrichmentContext) {
+        self.item_patterns.refresh(context.key_rules);
+        for event in events.iter_mut().filter(|event| !event.is_custom()) {
+            event.rule_risk_level = self
+                .item_patterns
+                .find(context.key_rules, &event.item_id)
+                .and_then(|rule| rule.risk_level.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(player_name: &str, item_id: &str) -> IngestEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_id": "evt-1",
+            "event_time": 0,
+            "event_type": "ACQUIRE",
+            "player_name": player_name,
+            "item_id": item_id,
+            "count": 1,
+        }))
+        .expect("event")
+    }
+
+    #[test]
+    fn chain_runs_enrichers_in_configured_order() {
+        let names: Vec<String> = BUILTIN_ENRICHERS
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let mut chain = EnrichmentChain::from_names(&names).expect("chain");
+        assert_eq!(chain.names(), names);
+        assert!(EnrichmentChain::from_names(&["geoip".to_string()]).is_err());
+
+        let registry = vec![ItemRegistryEntry {
+            item_id: "minecraft:diamond".to_string(),
+            name: None,
+            names: Some(HashMap::from([(
+                DEFAULT_RULE_LANG.to_string(),
+                "钻石".to_string(),
+            )])),
+            namespace: None,
+            path: None,
+            max_stack_size: None,
+        }];
+        let key_rules = HashMap::from([(
+            "minecraft:diamond".to_string(),
+            KeyItemRule {
+                item_id: "minecraft:diamond".to_string(),
+                threshold: Some(64),
+                max_per_10m: None,
+                risk_level: Some("HIGH".to_string()),
+                weight: None,
+                daily_quota: None,
+                unit: None,
+                stack_size: None,
+                unit_value: None,
+            },
+        )]);
+        let context = EnrichmentContext {
+            item_registry: &registry,
+            key_rules: &key_rules,
+        };
+        let mut events = vec![
+            event("Steve", "minecraft:diamond"),
+            event("steve ", "minecraft:dirt"),
+        ];
+        chain.enrich(&mut events, &context);
+        assert_eq!(events[1].player_name.as_deref(), Some("Steve"));
+        assert_eq!(events[0].item_name.as_deref(), Some("钻石"));
+        assert_eq!(events[0].rule_risk_level.as_deref(), Some("HIGH"));
+        assert!(events[1].item_name.is_none());
+        assert!(events[1].rule_risk_level.is_none());
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::entities::KeyItemRule;
+
+/// `item_id` prefix of a key item rule matched as a regular expression, anchored to the whole id.
+pub const ITEM_REGEX_PREFIX: &str = "re:";
+
+/// Whether a rule's `item_id` is a pattern (`re:` regex, or a glob with `*` / `?`) rather than
+/// an exact item id.
+pub fn is_item_pattern(item_id: &str) -> bool {
+    item_id.starts_with(ITEM_REGEX_PREFIX) || item_id.contains(['*', '?'])
+}
+
+/// Compiles a rule pattern. Globs match the whole item id, `*` any run of characters and `?` one
+/// character; `re:` regexes are anchored the same way.
+pub fn compile_item_pattern(item_id: &str) -> Result<Regex, String> {
+    let source = match item_id.strip_prefix(ITEM_REGEX_PREFIX) {
+        Some(regex) => format!("^(?:{})$", regex),
+        None => {
+            let mut source = String::from("^");
+            for c in item_id.chars() {
+                match c {
+                    '*' => source.push_str(".*"),
+                    '?' => source.push('.'),
+                    c => source.push_str(&regex::escape(&c.to_string())),
+                }
+            }
+            source.push('$');
+            source
+        }
+    };
+    Regex::new(&source).map_err(|err| format!("invalid item pattern '{}': {}", item_id, err))
+}
+
+/// Compiled pattern rules of a rule set. Exact rules always win; among patterns the longest
+/// (then alphabetically first) one that matches is used.
+#[derive(Debug, Default)]
+pub struct ItemPatternSet {
+    sources: Vec<String>,
+    patterns: Vec<(String, Regex)>,
+}
+
+impl ItemPatternSet {
+    /// Compiles the pattern rules of `rules`; patterns that fail to compile never match.
+    pub fn from_rules(rules: &HashMap<String, KeyItemRule>) -> Self {
+        let mut set = Self::default();
+        set.refresh(rules);
+        set
+    }
+
+    /// Recompiles only when the rule set's patterns changed since the last call.
+    pub fn refresh(&mut self, rules: &HashMap<String, KeyItemRule>) {
+        let mut keys: Vec<&String> = rules.keys().filter(|key| is_item_pattern(key)).collect();
+        keys.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        if keys.iter().copied().eq(self.sources.iter()) {
+            return;
+        }
+        self.sources = keys.iter().map(|key| key.to_string()).collect();
+        self.patterns = keys
+            .into_iter()
+            .filter_map(|key| {
+                compile_item_pattern(key)
+                    .ok()
+                    .map(|regex| (key.clone(), regex))
+            })
+            .collect();
+    }
+
+    /// The rule that applies to `item_id`: its exact rule, else the first matching pattern rule.
+    pub fn find<'r>(
+        &self,
+        rules: &'r HashMap<String, KeyItemRule>,
+        item_id: &str,
+    ) -> Option<&'r KeyItemRule> {
+        if let Some(rule) = rules.get(item_id) {
+            return Some(rule);
+        }
+        self.patterns
+            .iter()
+            .find(|(_, regex)| regex.is_match(item_id))
+            .and_then(|(key, _)| rules.get(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::KeyItemRuleApi;
+
+    fn rules(entries: &[(&str, u64)]) -> HashMap<String, KeyItemRule> {
+        entries
+            .iter()
+            .map(|(item_id, threshold)| {
+                (
+                    item_id.to_string(),
+                    KeyItemRule::from(KeyItemRuleApi {
+                        item_id: item_id.to_string(),
+                        threshold: *threshold,
+                        risk_level: "HIGH".to_string(),
+                        daily_quota: None,
+                        unit: None,
+                        stack_size: None,
+                        unit_value: None,
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_rules_win_over_longest_matching_pattern() {
+        let rules = rules(&[
+            ("botania:*_rune", 4),
+            ("botania:*", 64),
+            ("botania:fire_rune", 1),
+            ("re:mekanism:(basic|elite)_.+", 8),
+        ]);
+        let set = ItemPatternSet::from_rules(&rules);
+        let threshold = |item_id| {
+            set.find(&rules, item_id)
+                .map(|rule| rule.effective_threshold())
+        };
+        assert_eq!(threshold("botania:fire_rune"), Some(1));
+        assert_eq!(threshold("botania:water_rune"), Some(4));
+        assert_eq!(threshold("botania:manasteel_ingot"), Some(64));
+        assert_eq!(threshold("mekanism:elite_tank"), Some(8));
+        assert_eq!(threshold("mekanism:ultimate_tank"), None);
+        assert_eq!(threshold("xbotania:fire_rune"), None);
+        assert!(compile_item_pattern("re:botania:(rune").is_err());
+        assert!(!is_item_pattern("minecraft:diamond"));
+    }
+}
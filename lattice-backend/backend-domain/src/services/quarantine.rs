@@ -0,0 +1,26 @@
+use serde_json::Value;
+
+use crate::entities::AnomalyRow;
+
+/// Evidence key set on anomalies of quarantined players.
+pub const QUARANTINED_EVIDENCE_KEY: &str = "quarantined";
+
+/// Stamps `"quarantined": true` into the row's evidence; evidence that is not a JSON object is
+/// wrapped as `{"evidence": ..., "quarantined": true}`.
+pub fn mark_quarantined(row: &mut AnomalyRow) {
+    let mut evidence = match serde_json::from_str::<Value>(&row.evidence_json) {
+        Ok(Value::Object(evidence)) => evidence,
+        Ok(other) => serde_json::Map::from_iter([("evidence".to_string(), other)]),
+        Err(_) => serde_json::Map::new(),
+    };
+    evidence.insert(QUARANTINED_EVIDENCE_KEY.to_string(), Value::Bool(true));
+    row.evidence_json = Value::Object(evidence).to_string();
+}
+
+/// Anomalies of a quarantined player are stored and reported, but never alerted.
+pub fn is_quarantined(row: &AnomalyRow) -> bool {
+    serde_json::from_str::<Value>(&row.evidence_json)
+        .ok()
+        .and_then(|evidence| evidence.get(QUARANTINED_EVIDENCE_KEY)?.as_bool())
+        .unwrap_or(false)
+}
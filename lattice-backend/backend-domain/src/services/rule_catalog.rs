@@ -0,0 +1,145 @@
+use crate::entities::AnomalyRow;
+
+/// Rules whose anomalies are pushed to the alert channel as they happen; the rest only show up in reports.
+pub const ALERTING_RULE_IDS: [&str; 4] = ["R4", "R10", "R12", "R14"];
+
+/// Rules driven by the key item rule set: window threshold, snapshots and daily quota.
+pub const KEY_ITEM_RULE_IDS: [&str; 4] = ["R4", "R9", "R12", "R14"];
+
+/// Rules decided by the analyzer, the ones `rule_risk_overrides` can re-rank.
+pub const ANALYZER_RULE_IDS: [&str; 12] = [
+    "R0", "R1", "R2", "R3", "R4", "R5", "R6", "R7", "R8", "R9", "R10", "R12",
+];
+
+pub const DEFAULT_RULE_LANG: &str = "zh_cn";
+
+struct RuleDoc {
+    rule_id: &'static str,
+    zh_cn: &'static str,
+    en_us: &'static str,
+}
+
+const RULE_DOCS: [RuleDoc; 14] = [
+    RuleDoc {
+        rule_id: "R0",
+        zh_cn: "物品来源可追溯到一次转移记录，仅作留痕",
+        en_us: "Item traced back to a matching transfer; recorded for audit only",
+    },
+    RuleDoc {
+        rule_id: "R1",
+        zh_cn: "获得物品时缺少来源，且找不到匹配的转移记录",
+        en_us: "Item acquired without an origin and no matching transfer was found",
+    },
+    RuleDoc {
+        rule_id: "R2",
+        zh_cn: "获得物品的来源类型不在白名单内",
+        en_us: "Item acquired from an origin type outside the whitelist",
+    },
+    RuleDoc {
+        rule_id: "R3",
+        zh_cn: "同一来源 ID 出现在多名玩家身上，疑似复制",
+        en_us: "Same origin id seen on several players, possible duplication",
+    },
+    RuleDoc {
+        rule_id: "R4",
+        zh_cn: "关键物品数量超过配置阈值",
+        en_us: "Key item count exceeded its configured threshold",
+    },
+    RuleDoc {
+        rule_id: "R5",
+        zh_cn: "同一玩家短时间内重复使用同一来源 ID，疑似复制",
+        en_us: "Same player reused an origin id within a short window, possible duplication",
+    },
+    RuleDoc {
+        rule_id: "R6",
+        zh_cn: "短时间内反复从世界拾取相同物品",
+        en_us: "Identical item picked up from the world repeatedly in a short window",
+    },
+    RuleDoc {
+        rule_id: "R7",
+        zh_cn: "背包物品快速增加且没有对应来源",
+        en_us: "Inventory grew rapidly without a matching source",
+    },
+    RuleDoc {
+        rule_id: "R8",
+        zh_cn: "同一玩家在较长时间窗口内重复使用同一来源 ID",
+        en_us: "Same player reused an origin id within the long window",
+    },
+    RuleDoc {
+        rule_id: "R9",
+        zh_cn: "背包快照中的关键物品数量超过阈值",
+        en_us: "Key item count in an inventory snapshot exceeded its threshold",
+    },
+    RuleDoc {
+        rule_id: "R10",
+        zh_cn: "短时间内从世界拾取的物品数量过大",
+        en_us: "Large volume of world pickups in a short window",
+    },
+    RuleDoc {
+        rule_id: "R12",
+        zh_cn: "容器扫描快照中的关键物品数量超过阈值",
+        en_us: "Key item count in a storage scan snapshot exceeded its threshold",
+    },
+    RuleDoc {
+        rule_id: "R13",
+        zh_cn: "短时间内同一类自定义事件数量过多",
+        en_us: "Burst of one custom event type in a short window",
+    },
+    RuleDoc {
+        rule_id: "R14",
+        zh_cn: "玩家当日获得的物品数量超过每日配额",
+        en_us: "Player acquired more of an item today than its daily quota allows",
+    },
+];
+
+pub fn is_alerting_rule(rule_id: &str) -> bool {
+    ALERTING_RULE_IDS.contains(&rule_id)
+}
+
+/// `R` followed by digits; user detection rules may not take these ids.
+pub fn is_builtin_rule_id(rule_id: &str) -> bool {
+    rule_id
+        .strip_prefix('R')
+        .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether an anomaly goes to the alert channel: the alerting built-in rules, plus user
+/// detection rules marked `alert: true`, which the analyzer records in the evidence.
+pub fn is_alerting_anomaly(row: &AnomalyRow) -> bool {
+    if is_builtin_rule_id(&row.rule_id) {
+        return is_alerting_rule(&row.rule_id);
+    }
+    serde_json::from_str::<serde_json::Value>(&row.evidence_json)
+        .ok()
+        .and_then(|evidence| evidence["explain"]["alert"].as_bool())
+        .unwrap_or(false)
+}
+
+/// Human-readable explanation of a rule; unknown languages fall back to `zh_cn`, unknown rules to an empty string.
+pub fn rule_description(rule_id: &str, lang: &str) -> &'static str {
+    let Some(doc) = RULE_DOCS.iter().find(|doc| doc.rule_id == rule_id) else {
+        return "";
+    };
+    match lang.trim().to_lowercase().replace('-', "_").as_str() {
+        "en_us" | "en" => doc.en_us,
+        _ => doc.zh_cn,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn description_falls_back_to_default_lang() {
+        assert_eq!(
+            rule_description("R4", "en-US"),
+            "Key item count exceeded its configured threshold"
+        );
+        assert_eq!(
+            rule_description("R4", "fr_fr"),
+            rule_description("R4", DEFAULT_RULE_LANG)
+        );
+        assert_eq!(rule_description("R99", "en_us"), "");
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde_json::{json, Value};
+
+use crate::entities::{DetectionRule, IngestEvent};
+use crate::services::{explain_window, is_builtin_rule_id, is_composite_rule_id};
+use crate::value_objects::{is_risk_level, RISK_LEVEL_NAMES};
+
+/// Event fields a user rule may keep its window per.
+pub const DETECTION_GROUP_KEYS: [&str; 6] = [
+    "player_uuid",
+    "item_id",
+    "server_id",
+    "origin_type",
+    "storage_id",
+    "dim",
+];
+
+/// Checks a loaded rule file, so a typo fails at load instead of silently never firing.
+pub fn validate_detection_rules(rules: &[DetectionRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        let id = rule.id.trim();
+        if id.is_empty() {
+            return Err("detection rule id must not be empty".to_string());
+        }
+        if is_builtin_rule_id(id) {
+            return Err(format!(
+                "detection rule {}: id is reserved for built-in rules",
+                id
+            ));
+        }
+        if is_composite_rule_id(id) {
+            return Err(format!(
+                "detection rule {}: id is reserved for composite rules",
+                id
+            ));
+        }
+        if !ids.insert(id) {
+            return Err(format!("detection rule {}: duplicate id", id));
+        }
+        if !is_risk_level(&rule.risk_level) {
+            return Err(format!(
+                "detection rule {}: risk_level must be {}",
+                id, RISK_LEVEL_NAMES
+            ));
+        }
+        if let Some(key) = rule
+            .group_by
+            .iter()
+            .find(|key| !DETECTION_GROUP_KEYS.contains(&key.as_str()))
+        {
+            return Err(format!(
+                "detection rule {}: group_by {} must be one of: {}",
+                id,
+                key,
+                DETECTION_GROUP_KEYS.join(", ")
+            ));
+        }
+        if (rule.window_seconds > 0) != (rule.threshold > 0) {
+            return Err(format!(
+                "detection rule {}: window_seconds and threshold must be set together",
+                id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A user rule that fired on one event; the analyzer turns it into an anomaly.
+#[derive(Debug, Clone)]
+pub struct DetectionHit {
+    pub rule_id: String,
+    pub risk_level: String,
+    pub reason: String,
+    pub explain: Value,
+}
+
+/// The enabled user rules and their sliding windows, keyed by rule id and `group_by` values.
+#[derive(Debug, Default)]
+pub struct DetectionRuleSet {
+    rules: Vec<DetectionRule>,
+    windows: HashMap<(String, String), VecDeque<(i64, i64)>>,
+}
+
+impl DetectionRuleSet {
+    /// Replaces the rules; windows survive unless the rules actually changed.
+    pub fn set_rules(&mut self, rules: Vec<DetectionRule>) {
+        let rules: Vec<DetectionRule> = rules.into_iter().filter(|rule| rule.enabled).collect();
+        if rules != self.rules {
+            self.rules = rules;
+            self.windows.clear();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn evaluate(&mut self, event: &IngestEvent) -> Vec<DetectionHit> {
+        let mut hits = Vec::new();
+        for rule in &self.rules {
+            if !matches_rule(rule, event) {
+                continue;
+            }
+            let reason = if rule.description.is_empty() {
+                format!("Detection rule {} matched", rule.id)
+            } else {
+                rule.description.clone()
+            };
+            let mut explain = if rule.window_seconds == 0 {
+                json!({ "count": event.count, "min_count": rule.min_count })
+            } else {
+                let window_ms = (rule.window_seconds * 1000) as i64;
+                let key = (rule.id.clone(), group_key(rule, event));
+                let window = self.windows.entry(key).or_default();
+                window.push_back((event.event_time, event.count));
+                while window
+                    .front()
+                    .is_some_and(|(time_ms, _)| event.event_time - time_ms > window_ms)
+                {
+                    window.pop_front();
+                }
+                let sum: i64 = window.iter().map(|(_, count)| count).sum();
+                if sum <= rule.threshold {
+                    continue;
+                }
+                let records: Vec<(i64, i64)> = window.iter().copied().collect();
+                window.clear();
+                let mut explain = explain_window(window_ms, rule.threshold as u64, &records);
+                explain["group_by"] = json!(group_fields(rule));
+                explain
+            };
+            explain["detection_rule"] = rule.id.clone().into();
+            explain["alert"] = rule.alert.into();
+            hits.push(DetectionHit {
+                rule_id: rule.id.clone(),
+                risk_level: rule.risk_level.clone(),
+                reason,
+                explain,
+            });
+        }
+        hits
+    }
+
+    /// Drops window records older than their rule's window at `now`.
+    pub fn cleanup(&mut self, now: i64) {
+        let window_ms: HashMap<&str, i64> = self
+            .rules
+            .iter()
+            .map(|rule| (rule.id.as_str(), (rule.window_seconds * 1000) as i64))
+            .collect();
+        self.windows.retain(|(rule_id, _), window| {
+            let Some(window_ms) = window_ms.get(rule_id.as_str()) else {
+                return false;
+            };
+            while window
+                .front()
+                .is_some_and(|(time_ms, _)| now - time_ms > *window_ms)
+            {
+                window.pop_front();
+            }
+            !window.is_empty()
+        });
+    }
+}
+
+fn matches_rule(rule: &DetectionRule, event: &IngestEvent) -> bool {
+    let event_type_matches = if rule.event_types.is_empty() {
+        event.event_type == "ACQUIRE"
+    } else {
+        rule.event_types
+            .iter()
+            .any(|event_type| event_type.eq_ignore_ascii_case(&event.event_type))
+    };
+    let origin_type = event.origin_type.as_deref().unwrap_or_default();
+    event_type_matches
+        && event.count >= rule.min_count
+        && (rule.origin_types.is_empty()
+            || rule.origin_types.iter().any(|item| item == origin_type))
+        && (rule.item_ids.is_empty()
+            || rule
+                .item_ids
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => event.item_id.starts_with(prefix),
+                    None => *pattern == event.item_id,
+                }))
+}
+
+fn group_fields(rule: &DetectionRule) -> Vec<String> {
+    if rule.group_by.is_empty() {
+        vec!["player_uuid".to_string()]
+    } else {
+        rule.group_by.clone()
+    }
+}
+
+fn group_key(rule: &DetectionRule, event: &IngestEvent) -> String {
+    group_fields(rule)
+        .iter()
+        .map(|field| match field.as_str() {
+            "item_id" => event.item_id.as_str(),
+            "server_id" => event.server_id.as_deref().unwrap_or_default(),
+            "origin_type" => event.origin_type.as_deref().unwrap_or_default(),
+            "storage_id" => event.storage_id.as_deref().unwrap_or_default(),
+            "dim" => event.dim.as_deref().unwrap_or_default(),
+            _ => event.player_uuid.as_deref().unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::is_alerting_anomaly;
+    use crate::testing::Scenario;
+
+    fn rule(value: Value) -> DetectionRule {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn rules_are_validated_on_load() {
+        let ok = rule(json!({
+            "id": "mek_burst",
+            "window_seconds": 60,
+            "threshold": 10,
+            "group_by": ["player_uuid", "item_id"],
+        }));
+        assert!(validate_detection_rules(std::slice::from_ref(&ok)).is_ok());
+        assert!(validate_detection_rules(&[ok.clone(), ok]).is_err());
+        assert!(validate_detection_rules(&[rule(json!({ "id": "R4" }))]).is_err());
+        assert!(
+            validate_detection_rules(&[rule(json!({ "id": "a", "risk_level": "SEVERE" }))])
+                .is_err()
+        );
+        assert!(
+            validate_detection_rules(&[rule(json!({ "id": "a", "group_by": ["nbt_hash"] }))])
+                .is_err()
+        );
+        assert!(
+            validate_detection_rules(&[rule(json!({ "id": "a", "window_seconds": 60 }))]).is_err()
+        );
+        assert!(
+            serde_json::from_value::<DetectionRule>(json!({ "id": "a", "thresold": 3 })).is_err()
+        );
+    }
+
+    #[test]
+    fn windowed_rule_fires_per_group_and_tags_the_anomaly() {
+        let outcome = Scenario::new()
+            .detection_rule(rule(json!({
+                "id": "mek_crafting_burst",
+                "risk_level": "HIGH",
+                "alert": true,
+                "origin_types": ["crafting"],
+                "item_ids": ["mekanism:*"],
+                "window_seconds": 60,
+                "threshold": 10,
+            })))
+            .at_secs(0)
+            .acquires("mekanism:ultimate_control_circuit", 6, "crafting")
+            .player("alex")
+            .acquires("mekanism:ultimate_control_circuit", 6, "crafting")
+            .player("steve")
+            .at_secs(30)
+            .acquires("minecraft:diamond", 6, "crafting")
+            .acquires("mekanism:atomic_disassembler", 5, "crafting")
+            .run();
+        outcome.assert_fired("mek_crafting_burst");
+        let hits = outcome.of_rule("mek_crafting_burst");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].risk_level, "HIGH");
+        assert_eq!(hits[0].item_id, "mekanism:atomic_disassembler");
+        assert!(is_alerting_anomaly(hits[0]));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::entities::{
+    ItemAnomalyStat, ItemCountDistribution, KeyItemRule, NoisyRule, RuleHygieneReport,
+    RuleThresholdFit,
+};
+use crate::services::ItemPatternSet;
+
+/// Key item rule hits per day from which a rule is listed as firing suspiciously often.
+pub const NOISY_RULE_HITS_PER_DAY: f64 = 20.0;
+/// Anomalies an item without a rule needs in the period before a rule is suggested for it.
+pub const UNCOVERED_MIN_ANOMALIES: u64 = 10;
+const UNCOVERED_LIMIT: usize = 20;
+
+/// Sorts a period's anomaly and acquisition statistics into the rule hygiene sections. Hits of
+/// items covered by a pattern rule count towards that rule.
+pub fn build_rule_hygiene_report(
+    from_date: &str,
+    to_date: &str,
+    days: u32,
+    rules: &HashMap<String, KeyItemRule>,
+    stats: Vec<ItemAnomalyStat>,
+    distributions: Vec<ItemCountDistribution>,
+) -> RuleHygieneReport {
+    let patterns = ItemPatternSet::from_rules(rules);
+    let mut hits: HashMap<&str, u64> = HashMap::new();
+    for stat in &stats {
+        if let Some(rule) = patterns.find(rules, &stat.item_id) {
+            *hits.entry(rule.item_id.as_str()).or_default() += stat.rule_hits;
+        }
+    }
+
+    let mut never_fired: Vec<String> = rules
+        .keys()
+        .filter(|item_id| hits.get(item_id.as_str()).copied().unwrap_or(0) == 0)
+        .cloned()
+        .collect();
+    never_fired.sort();
+
+    let mut noisy: Vec<NoisyRule> = rules
+        .keys()
+        .filter_map(|item_id| {
+            let rule_hits = hits.get(item_id.as_str()).copied().unwrap_or(0);
+            let per_day = rule_hits as f64 / f64::from(days.max(1));
+            (per_day >= NOISY_RULE_HITS_PER_DAY).then(|| NoisyRule {
+                item_id: item_id.clone(),
+                rule_hits,
+                per_day,
+            })
+        })
+        .collect();
+    noisy.sort_by(|a, b| {
+        b.rule_hits
+            .cmp(&a.rule_hits)
+            .then(a.item_id.cmp(&b.item_id))
+    });
+
+    let mut uncovered: Vec<ItemAnomalyStat> = stats
+        .into_iter()
+        .filter(|stat| {
+            patterns.find(rules, &stat.item_id).is_none()
+                && stat.anomalies >= UNCOVERED_MIN_ANOMALIES
+        })
+        .collect();
+    uncovered.sort_by(|a, b| {
+        b.anomalies
+            .cmp(&a.anomalies)
+            .then(a.item_id.cmp(&b.item_id))
+    });
+    uncovered.truncate(UNCOVERED_LIMIT);
+
+    let mut thresholds: Vec<RuleThresholdFit> = distributions
+        .into_iter()
+        .filter_map(|distribution| {
+            let rule = rules.get(&distribution.item_id)?;
+            Some(RuleThresholdFit {
+                item_id: distribution.item_id.clone(),
+                threshold: rule.effective_threshold(),
+                daily_quota: rule.daily_quota,
+                distribution,
+            })
+        })
+        .collect();
+    thresholds.sort_by(|a, b| a.item_id.cmp(&b.item_id));
+
+    RuleHygieneReport {
+        from_date: from_date.to_string(),
+        to_date: to_date.to_string(),
+        never_fired,
+        noisy,
+        uncovered,
+        thresholds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::KeyItemRuleApi;
+
+    fn rule(item_id: &str, threshold: u64) -> (String, KeyItemRule) {
+        (
+            item_id.to_string(),
+            KeyItemRule::from(KeyItemRuleApi {
+                item_id: item_id.to_string(),
+                threshold,
+                risk_level: "HIGH".to_string(),
+                daily_quota: None,
+                unit: None,
+                stack_size: None,
+                unit_value: None,
+            }),
+        )
+    }
+
+    fn stat(item_id: &str, anomalies: u64, rule_hits: u64) -> ItemAnomalyStat {
+        ItemAnomalyStat {
+            item_id: item_id.to_string(),
+            anomalies,
+            rule_hits,
+            players: 1,
+        }
+    }
+
+    #[test]
+    fn rules_are_sorted_into_hygiene_sections() {
+        let rules = HashMap::from([
+            rule("minecraft:beacon", 1),
+            rule("minecraft:diamond", 64),
+            rule("minecraft:elytra", 1),
+        ]);
+        let stats = vec![
+            stat("minecraft:diamond", 700, 650),
+            stat("minecraft:elytra", 3, 3),
+            stat("minecraft:netherite_ingot", 40, 0),
+            stat("minecraft:stone", 2, 0),
+        ];
+        let distributions = vec![ItemCountDistribution {
+            item_id: "minecraft:diamond".to_string(),
+            samples: 120,
+            p50: 8.0,
+            p90: 40.0,
+            p99: 90.0,
+            max: 300,
+        }];
+
+        let report =
+            build_rule_hygiene_report("2026-09-01", "2026-09-30", 30, &rules, stats, distributions);
+
+        assert_eq!(report.never_fired, ["minecraft:beacon"]);
+        assert_eq!(report.noisy.len(), 1);
+        assert_eq!(report.noisy[0].item_id, "minecraft:diamond");
+        let uncovered: Vec<&str> = report
+            .uncovered
+            .iter()
+            .map(|s| s.item_id.as_str())
+            .collect();
+        assert_eq!(uncovered, ["minecraft:netherite_ingot"]);
+        assert_eq!(report.thresholds.len(), 1);
+        assert_eq!(report.thresholds[0].threshold, 64);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::entities::{
+    KeyItemRule, KeyItemRuleApi, PresetConflictPolicy, RulePreset, RulePresetApplyResult,
+};
+use crate::value_objects::risk_rank;
+
+const PRESET_SOURCES: [&str; 3] = [
+    include_str!("../../presets/vanilla-rare-items.json"),
+    include_str!("../../presets/common-dupe-targets.json"),
+    include_str!("../../presets/creative-only-items.json"),
+];
+
+/// The presets embedded in the crate, in catalog order.
+pub fn rule_presets() -> Vec<RulePreset> {
+    PRESET_SOURCES
+        .iter()
+        .map(|source| serde_json::from_str(source).expect("embedded rule preset is valid JSON"))
+        .collect()
+}
+
+pub fn find_rule_preset(id: &str) -> Option<RulePreset> {
+    rule_presets().into_iter().find(|preset| preset.id == id)
+}
+
+/// Merges `preset` into `rules`; items without a rule are always added, conflicts follow `policy`.
+pub fn merge_rule_preset(
+    rules: &mut HashMap<String, KeyItemRule>,
+    preset: &RulePreset,
+    policy: PresetConflictPolicy,
+) -> RulePresetApplyResult {
+    let mut result = RulePresetApplyResult {
+        preset: preset.id.clone(),
+        ..Default::default()
+    };
+    for incoming in &preset.rules {
+        let incoming = incoming.normalized();
+        let item_id = incoming.item_id.clone();
+        let Some(existing) = rules.get(&item_id) else {
+            rules.insert(item_id.clone(), KeyItemRule::from(incoming));
+            result.added.push(item_id);
+            continue;
+        };
+        let merged = match policy {
+            PresetConflictPolicy::Keep => None,
+            PresetConflictPolicy::Replace => Some(incoming),
+            PresetConflictPolicy::Stricter => {
+                let existing = KeyItemRuleApi::from(existing);
+                Some(stricter(&existing, &incoming)).filter(|merged| !same_rule(&existing, merged))
+            }
+        };
+        match merged {
+            Some(merged) => {
+                rules.insert(item_id.clone(), KeyItemRule::from(merged));
+                result.replaced.push(item_id);
+            }
+            None => result.kept.push(item_id),
+        }
+    }
+    result
+}
+
+fn stricter(existing: &KeyItemRuleApi, incoming: &KeyItemRuleApi) -> KeyItemRuleApi {
+    // A threshold of 0 means the rule only carries a daily quota.
+    let threshold = match (existing.threshold, incoming.threshold) {
+        (0, other) | (other, 0) => other,
+        (a, b) => a.min(b),
+    };
+    let daily_quota = match (existing.daily_quota, incoming.daily_quota) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let risk_level = if risk_rank(&incoming.risk_level) > risk_rank(&existing.risk_level) {
+        incoming.risk_level.clone()
+    } else {
+        existing.risk_level.clone()
+    };
+    KeyItemRuleApi {
+        item_id: existing.item_id.clone(),
+        threshold,
+        risk_level,
+        daily_quota,
+        unit: existing.unit,
+        stack_size: existing.stack_size,
+        unit_value: existing.unit_value,
+    }
+}
+
+fn same_rule(a: &KeyItemRuleApi, b: &KeyItemRuleApi) -> bool {
+    a.threshold == b.threshold && a.risk_level == b.risk_level && a.daily_quota == b.daily_quota
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(
+        item_id: &str,
+        threshold: u64,
+        risk_level: &str,
+        daily_quota: Option<u64>,
+    ) -> KeyItemRule {
+        KeyItemRule::from(KeyItemRuleApi {
+            item_id: item_id.to_string(),
+            threshold,
+            risk_level: risk_level.to_string(),
+            daily_quota,
+            unit: None,
+            stack_size: None,
+            unit_value: None,
+        })
+    }
+
+    #[test]
+    fn embedded_presets_parse_with_unique_ids_and_valid_rules() {
+        let presets = rule_presets();
+        let ids: Vec<&str> = presets.iter().map(|preset| preset.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "vanilla-rare-items",
+                "common-dupe-targets",
+                "creative-only-items"
+            ]
+        );
+        for preset in &presets {
+            assert!(!preset.rules.is_empty(), "{} has no rules", preset.id);
+            for rule in &preset.rules {
+                assert_eq!(rule.normalized().item_id, rule.item_id);
+                assert!(rule.item_id.contains(':'));
+                assert!(rule.threshold > 0 || rule.daily_quota.is_some());
+                assert!(["LOW", "MEDIUM", "HIGH"].contains(&rule.risk_level.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn conflicts_follow_the_policy() {
+        let preset = RulePreset {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: String::new(),
+            rules: vec![
+                KeyItemRuleApi::from(&rule("minecraft:diamond", 32, "MEDIUM", Some(128))),
+                KeyItemRuleApi::from(&rule("minecraft:elytra", 2, "HIGH", None)),
+            ],
+        };
+        let current = HashMap::from([(
+            "minecraft:diamond".to_string(),
+            rule("minecraft:diamond", 64, "HIGH", None),
+        )]);
+
+        let mut keep = current.clone();
+        let result = merge_rule_preset(&mut keep, &preset, PresetConflictPolicy::Keep);
+        assert_eq!(result.added, ["minecraft:elytra"]);
+        assert_eq!(result.kept, ["minecraft:diamond"]);
+        assert_eq!(keep["minecraft:diamond"].effective_threshold(), 64);
+
+        let mut stricter = current.clone();
+        let result = merge_rule_preset(&mut stricter, &preset, PresetConflictPolicy::Stricter);
+        assert_eq!(result.replaced, ["minecraft:diamond"]);
+        let diamond = &stricter["minecraft:diamond"];
+        assert_eq!(diamond.effective_threshold(), 32);
+        assert_eq!(diamond.effective_risk_level(), "HIGH");
+        assert_eq!(diamond.daily_quota, Some(128));
+
+        let mut replace = current;
+        merge_rule_preset(&mut replace, &preset, PresetConflictPolicy::Replace);
+        assert_eq!(
+            replace["minecraft:diamond"].effective_risk_level(),
+            "MEDIUM"
+        );
+    }
+}
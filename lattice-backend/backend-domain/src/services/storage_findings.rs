@@ -0,0 +1,23 @@
+use crate::entities::AnomalyRow;
+
+pub const STORAGE_SCAN_RULE_ID: &str = "R12";
+pub const SCAN_STATUS_NEW: &str = "new";
+pub const SCAN_STATUS_GROWN: &str = "grown";
+pub const SCAN_STATUS_PERSISTING: &str = "persisting";
+
+/// `scan_status` stamped into an R12 anomaly's evidence once it is compared with earlier scans.
+pub fn storage_scan_status(row: &AnomalyRow) -> Option<String> {
+    if row.rule_id != STORAGE_SCAN_RULE_ID {
+        return None;
+    }
+    let evidence: serde_json::Value = serde_json::from_str(&row.evidence_json).ok()?;
+    evidence
+        .get("scan_status")
+        .and_then(|value| value.as_str())
+        .map(ToString::to_string)
+}
+
+/// Unchanged findings from an earlier scan: reported, but not alerted.
+pub fn is_persisting_finding(row: &AnomalyRow) -> bool {
+    storage_scan_status(row).as_deref() == Some(SCAN_STATUS_PERSISTING)
+}
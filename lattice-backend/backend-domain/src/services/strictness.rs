@@ -0,0 +1,154 @@
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+
+use crate::entities::{StrictProfile, StrictnessStatus};
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Parses a cron-like field (`*`, `n`, `a-b`, comma lists) into a bitmask over `0..=max`.
+/// Ranges may wrap, so `22-6` covers the night across midnight.
+fn parse_field(field: &str, max: u32, names: &[&str]) -> Result<u32, String> {
+    let field = field.trim();
+    if field.is_empty() || field == "*" {
+        return Ok(u32::MAX >> (31 - max));
+    }
+    let value = |raw: &str| -> Result<u32, String> {
+        let raw = raw.trim().to_ascii_lowercase();
+        if let Some(index) = names.iter().position(|name| *name == raw) {
+            return Ok(index as u32);
+        }
+        let value: u32 = raw
+            .parse()
+            .map_err(|_| format!("invalid value '{}'", raw))?;
+        // Cron allows 7 for Sunday as well as 0.
+        if !names.is_empty() && value == 7 {
+            return Ok(0);
+        }
+        if value > max {
+            return Err(format!("value {} out of range 0-{}", value, max));
+        }
+        Ok(value)
+    };
+    let mut mask = 0u32;
+    for part in field.split(',') {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (value(start)?, value(end)?);
+                let mut current = start;
+                loop {
+                    mask |= 1 << current;
+                    if current == end {
+                        break;
+                    }
+                    current = if current == max { 0 } else { current + 1 };
+                }
+            }
+            None => mask |= 1 << value(part)?,
+        }
+    }
+    Ok(mask)
+}
+
+/// Checks the `hours` and `days` fields of a profile; used by config validation.
+pub fn validate_strict_profile(profile: &StrictProfile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("strict profile name must not be empty".to_string());
+    }
+    parse_field(&profile.hours, 23, &[])
+        .map_err(|err| format!("strict profile '{}' hours: {}", profile.name, err))?;
+    parse_field(&profile.days, 6, &WEEKDAY_NAMES)
+        .map_err(|err| format!("strict profile '{}' days: {}", profile.name, err))?;
+    if profile.enabled && profile.pickup_window_seconds == Some(0) {
+        return Err(format!(
+            "strict profile '{}' pickup_window_seconds must be greater than 0",
+            profile.name
+        ));
+    }
+    Ok(())
+}
+
+pub fn strict_profile_matches<Tz: TimeZone>(profile: &StrictProfile, now: &DateTime<Tz>) -> bool {
+    let hours = parse_field(&profile.hours, 23, &[]).unwrap_or(0);
+    let days = parse_field(&profile.days, 6, &WEEKDAY_NAMES).unwrap_or(0);
+    hours & (1 << now.hour()) != 0 && days & (1 << now.weekday().num_days_from_sunday()) != 0
+}
+
+/// Strict pickup settings in effect at `now`: the first matching profile overrides the global
+/// `strict_*` values, unset profile fields fall back to them.
+pub fn resolve_strictness<Tz: TimeZone>(
+    enabled: bool,
+    pickup_window_seconds: u64,
+    pickup_threshold: u64,
+    profiles: &[StrictProfile],
+    now: &DateTime<Tz>,
+) -> StrictnessStatus {
+    let active = profiles
+        .iter()
+        .find(|profile| strict_profile_matches(profile, now));
+    match active {
+        Some(profile) => StrictnessStatus {
+            profile: Some(profile.name.clone()),
+            enabled: profile.enabled,
+            pickup_window_seconds: profile
+                .pickup_window_seconds
+                .unwrap_or(pickup_window_seconds),
+            pickup_threshold: profile.pickup_threshold.unwrap_or(pickup_threshold),
+        },
+        None => StrictnessStatus {
+            profile: None,
+            enabled,
+            pickup_window_seconds,
+            pickup_threshold,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn profile(name: &str, hours: &str, days: &str, threshold: Option<u64>) -> StrictProfile {
+        StrictProfile {
+            name: name.to_string(),
+            hours: hours.to_string(),
+            days: days.to_string(),
+            enabled: true,
+            pickup_window_seconds: None,
+            pickup_threshold: threshold,
+        }
+    }
+
+    #[test]
+    fn first_matching_profile_wins_and_ranges_wrap_midnight() {
+        let profiles = vec![
+            profile("event", "*", "sat", Some(1024)),
+            profile("night", "22-6", "*", Some(64)),
+        ];
+        // 2026-10-16 is a Friday.
+        let friday_night = Utc.with_ymd_and_hms(2026, 10, 16, 23, 0, 0).unwrap();
+        let status = resolve_strictness(false, 30, 256, &profiles, &friday_night);
+        assert_eq!(status.profile.as_deref(), Some("night"));
+        assert!(status.enabled);
+        assert_eq!(
+            (status.pickup_window_seconds, status.pickup_threshold),
+            (30, 64)
+        );
+
+        let saturday_night = Utc.with_ymd_and_hms(2026, 10, 17, 23, 0, 0).unwrap();
+        let status = resolve_strictness(false, 30, 256, &profiles, &saturday_night);
+        assert_eq!(status.profile.as_deref(), Some("event"));
+
+        let friday_noon = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let status = resolve_strictness(false, 30, 256, &profiles, &friday_noon);
+        assert!(status.profile.is_none());
+        assert!(!status.enabled);
+    }
+
+    #[test]
+    fn invalid_fields_are_rejected() {
+        assert!(validate_strict_profile(&profile("ok", "1,3-5", "mon-fri", None)).is_ok());
+        assert!(validate_strict_profile(&profile("bad", "24", "*", None)).is_err());
+        assert!(validate_strict_profile(&profile("bad", "*", "funday", None)).is_err());
+        assert!(validate_strict_profile(&profile(" ", "*", "*", None)).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use time::OffsetDateTime;
+
+use crate::entities::RuntimeConfig;
+
+pub const DEFAULT_DISPLAY_TIMEZONE: &str = "local";
+pub const DEFAULT_DISPLAY_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, Copy)]
+enum DisplayZone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+/// Renders event times for people: alert lines, report rows and the API's `display_time`, all
+/// in `display_timezone` with the `display_time_format` strftime pattern.
+#[derive(Debug, Clone)]
+pub struct TimeDisplay {
+    zone: DisplayZone,
+    pattern: String,
+}
+
+impl Default for TimeDisplay {
+    fn default() -> Self {
+        Self {
+            zone: DisplayZone::Local,
+            pattern: DEFAULT_DISPLAY_TIME_FORMAT.to_string(),
+        }
+    }
+}
+
+impl TimeDisplay {
+    /// `timezone` is `local`, `UTC`, a fixed offset such as `+08:00`, or an IANA name such as
+    /// `Asia/Shanghai`.
+    pub fn new(timezone: &str, pattern: &str) -> Result<Self, String> {
+        let timezone = timezone.trim();
+        let zone = if timezone.is_empty() || timezone.eq_ignore_ascii_case("local") {
+            DisplayZone::Local
+        } else if timezone.eq_ignore_ascii_case("utc") {
+            DisplayZone::Fixed(FixedOffset::east_opt(0).expect("zero offset"))
+        } else if timezone.starts_with(['+', '-']) {
+            DisplayZone::Fixed(parse_offset(timezone)?)
+        } else {
+            DisplayZone::Named(
+                timezone
+                    .parse::<Tz>()
+                    .map_err(|_| format!("unknown timezone: {}", timezone))?,
+            )
+        };
+        if pattern.trim().is_empty() {
+            return Err("time format must not be empty".to_string());
+        }
+        if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("invalid time format: {}", pattern));
+        }
+        Ok(Self {
+            zone,
+            pattern: pattern.to_string(),
+        })
+    }
+
+    /// The configured display; settings are validated at load, so a bad value falls back to the
+    /// defaults instead of failing a render.
+    pub fn from_config(config: &RuntimeConfig) -> Self {
+        Self::new(&config.display_timezone, &config.display_time_format).unwrap_or_default()
+    }
+
+    pub fn format_millis(&self, millis: i64) -> String {
+        let Some(utc) = Utc.timestamp_millis_opt(millis).single() else {
+            return millis.to_string();
+        };
+        self.format_utc(utc, &self.pattern)
+    }
+
+    pub fn format(&self, time: OffsetDateTime) -> String {
+        self.format_millis((time.unix_timestamp_nanos() / 1_000_000) as i64)
+    }
+
+    /// `millis` in the display timezone with another pattern, e.g. `%H:%M` for a marker.
+    pub fn format_millis_with(&self, millis: i64, pattern: &str) -> String {
+        match Utc.timestamp_millis_opt(millis).single() {
+            Some(utc) => self.format_utc(utc, pattern),
+            None => millis.to_string(),
+        }
+    }
+
+    fn format_utc(&self, utc: DateTime<Utc>, pattern: &str) -> String {
+        match self.zone {
+            DisplayZone::Local => utc.with_timezone(&Local).format(pattern).to_string(),
+            DisplayZone::Fixed(offset) => utc.with_timezone(&offset).format(pattern).to_string(),
+            DisplayZone::Named(tz) => utc.with_timezone(&tz).format(pattern).to_string(),
+        }
+    }
+}
+
+fn parse_offset(value: &str) -> Result<FixedOffset, String> {
+    let invalid = || format!("invalid timezone offset: {}", value);
+    let (sign, rest) = value.split_at(1);
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 14 || minutes > 59 {
+        return Err(invalid());
+    }
+    let seconds = (hours * 3600 + minutes * 60) * if sign == "-" { -1 } else { 1 };
+    FixedOffset::east_opt(seconds).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_render_in_the_configured_zone_and_pattern() {
+        // 2026-03-01T16:30:00Z
+        let millis = 1_772_382_600_000;
+        let shanghai = TimeDisplay::new("Asia/Shanghai", DEFAULT_DISPLAY_TIME_FORMAT).unwrap();
+        assert_eq!(shanghai.format_millis(millis), "2026-03-02 00:30:00");
+        let utc = TimeDisplay::new("UTC", "%m/%d %H:%M").unwrap();
+        assert_eq!(utc.format_millis(millis), "03/01 16:30");
+        let offset = TimeDisplay::new("-05:30", "%H:%M %:z").unwrap();
+        assert_eq!(offset.format_millis(millis), "11:00 -05:30");
+        assert_eq!(offset.format_millis_with(millis, "%H"), "11");
+
+        assert!(TimeDisplay::new("Mars/Olympus", DEFAULT_DISPLAY_TIME_FORMAT).is_err());
+        assert!(TimeDisplay::new("+25:00", DEFAULT_DISPLAY_TIME_FORMAT).is_err());
+        assert!(TimeDisplay::new("local", "%Q").is_err());
+        assert!(TimeDisplay::new("local", " ").is_err());
+    }
+}
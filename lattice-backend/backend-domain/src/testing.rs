@@ -0,0 +1,15 @@
+// Test support: an analyzer scenario builder, the published regression fixtures, a complete
+// runtime config, a sample anomaly row and in-memory implementations of the storage and alert
+// ports. Compiled for this crate's tests and, with the `test-support` feature, for other crates'
+// tests.
+pub mod config;
+pub mod fixtures;
+pub mod in_memory;
+pub mod rows;
+pub mod scenario;
+
+pub use config::*;
+pub use fixtures::*;
+pub use in_memory::*;
+pub use rows::*;
+pub use scenario::*;
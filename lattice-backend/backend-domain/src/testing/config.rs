@@ -0,0 +1,113 @@
+use crate::entities::RuntimeConfig;
+
+/// A complete `RuntimeConfig` with optional features off and no alert target, for tests that
+/// need a config but only care about a few keys; override those with struct update syntax.
+pub fn runtime_config() -> RuntimeConfig {
+    RuntimeConfig {
+        bind_addr: "127.0.0.1:3234".to_string(),
+        api_token: None,
+        op_token_admin_ids: Vec::new(),
+        op_token_allowed_group_ids: Vec::new(),
+        report_dir: "./reports".to_string(),
+        public_base_url: "http://127.0.0.1:3234".to_string(),
+        webhook_url: None,
+        webhook_template: None,
+        report_webhook_locale: "zh-CN".to_string(),
+        alert_webhook_url: None,
+        alert_webhook_template: None,
+        alert_webhook_token: None,
+        alert_group_id: None,
+        key_items_path: "./key_items.yaml".to_string(),
+        item_registry_path: "./item_registry.json".to_string(),
+        transfer_window_seconds: 2,
+        strict_transfer_fingerprint: false,
+        transfer_count_tolerance: 0,
+        transfer_count_tolerance_percent: 0.0,
+        transfer_aggregate: false,
+        key_item_window_minutes: 10,
+        strict_enabled: false,
+        strict_pickup_window_seconds: 30,
+        strict_pickup_threshold: 256,
+        max_body_bytes: 1024,
+        request_timeout_seconds: 15,
+        report_hour: 0,
+        report_minute: 5,
+        ingest_stale_after_minutes: 30,
+        task_stale_after_seconds: 600,
+        heartbeat_interval_seconds: 60,
+        heartbeat_missed_threshold: 3,
+        min_mod_version: None,
+        mod_version_enforce: false,
+        maintenance_enabled: true,
+        maintenance_hour: 4,
+        maintenance_optimize_min_rows: 1_000_000,
+        storage_alert_threshold_mb: 0,
+        response_compression_enabled: true,
+        response_compression_min_bytes: 1024,
+        response_compression_content_types: vec!["application/json".to_string()],
+        alert_group_by_player: false,
+        alert_group_window_seconds: 0,
+        anomaly_link_target: "off".to_string(),
+        custom_burst_types: Vec::new(),
+        custom_burst_threshold: 0,
+        custom_burst_window_seconds: 0,
+        bind_socket: String::new(),
+        tls_cert_path: String::new(),
+        tls_key_path: String::new(),
+        degraded_cache_size: 0,
+        dead_letter_max_events: 0,
+        degraded_recovery_seconds: 0,
+        clickhouse_watchdog_interval_seconds: 0,
+        clickhouse_watchdog_failures: 3,
+        strict_profiles: Vec::new(),
+        daily_quota_alert_enabled: true,
+        report_player_pages: 0,
+        report_retention_count: 0,
+        report_link_secret: None,
+        report_link_ttl_hours: 168,
+        ml_export_secret: None,
+        enrichers: Vec::new(),
+        cluster_mode: false,
+        cluster_state_url: String::new(),
+        grpc_bind_addr: String::new(),
+        mqtt_broker_url: String::new(),
+        mqtt_client_id: String::new(),
+        mqtt_username: None,
+        mqtt_password: None,
+        mqtt_anomaly_topic: String::new(),
+        mqtt_health_topic: String::new(),
+        mqtt_ingest_topic: String::new(),
+        redaction_rules: Vec::new(),
+        ingest_record_path: String::new(),
+        ingest_record_sample_rate: 1.0,
+        ingest_record_max_mb: 100,
+        alert_team_routes: Vec::new(),
+        config_change_alert_enabled: true,
+        slow_rule_budget_ms: 250,
+        rule_hygiene_report_day: 1,
+        origin_learning_days: 7,
+        server_keys: Vec::new(),
+        server_identity_required: false,
+        alert_max_lines: 8,
+        alert_page_ttl_minutes: 30,
+        rule_risk_overrides: std::collections::BTreeMap::new(),
+        alert_webhook_fallback_url: None,
+        meta_alert_spike_multiple: 5.0,
+        meta_alert_baseline_minutes: 60,
+        meta_alert_zero_ingest: true,
+        alert_webhook_proxy: None,
+        display_timezone: "local".to_string(),
+        display_time_format: "%Y-%m-%d %H:%M:%S".to_string(),
+        detection_rules_path: "./detection_rules.yaml".to_string(),
+        composite_rules_path: "./composite_rules.yaml".to_string(),
+        ingest_dedup_capacity: 100_000,
+        alert_server_groups: std::collections::BTreeMap::new(),
+        ingest_rate_limit_per_second: 0.0,
+        ingest_rate_limit_burst: 20,
+        trusted_proxies: Vec::new(),
+        replay_window_seconds: 0,
+        alert_routing: crate::entities::AlertRouting::default(),
+        config_path: None,
+        config_origins: Default::default(),
+    }
+}
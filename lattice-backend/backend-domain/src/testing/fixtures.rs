@@ -0,0 +1,470 @@
+use crate::testing::Scenario;
+
+/// A named scenario and the exact rule ids the analyzer must produce for it.
+pub struct Fixture {
+    pub name: &'static str,
+    pub expected: &'static [&'static str],
+    pub scenario: fn() -> Scenario,
+}
+
+/// Regression fixtures for the analyzer rules R0–R12 (there is no R11; R13 and R14 are decided
+/// outside the analyzer), plus scenarios that must stay quiet. A change to detection logic that
+/// breaks one of them changes what servers get alerted on.
+pub fn regression_fixtures() -> Vec<Fixture> {
+    vec![
+        Fixture {
+            name: "transfer_then_matching_acquire",
+            expected: &["R0"],
+            scenario: || {
+                Scenario::new()
+                    .transfers("minecraft:diamond", 64)
+                    .at_secs(1)
+                    .acquires_without_origin("minecraft:diamond", 64)
+            },
+        },
+        Fixture {
+            name: "acquire_without_origin",
+            expected: &["R1"],
+            scenario: || Scenario::new().acquires_without_origin("minecraft:diamond", 5),
+        },
+        Fixture {
+            name: "transfer_outside_window_does_not_explain_acquire",
+            expected: &["R1"],
+            scenario: || {
+                Scenario::new()
+                    .transfers("minecraft:diamond", 64)
+                    .at_secs(5)
+                    .acquires_without_origin("minecraft:diamond", 64)
+            },
+        },
+        Fixture {
+            name: "transfer_on_another_server_does_not_explain_acquire",
+            expected: &["R1"],
+            scenario: || {
+                Scenario::new()
+                    .server("survival")
+                    .transfers("minecraft:diamond", 64)
+                    .at_secs(1)
+                    .server("creative")
+                    .acquires_without_origin("minecraft:diamond", 64)
+            },
+        },
+        Fixture {
+            name: "origin_type_outside_whitelist",
+            expected: &["R2"],
+            scenario: || Scenario::new().acquires("botania:rune_fire", 1, "botania_rune_altar"),
+        },
+        Fixture {
+            name: "origin_id_on_two_players",
+            expected: &["R3"],
+            scenario: || {
+                Scenario::new()
+                    .player("steve")
+                    .acquires_from("minecraft:elytra", 1, "loot", "end-ship-1")
+                    .at_secs(2)
+                    .player("alex")
+                    .acquires_from("minecraft:elytra", 1, "loot", "end-ship-1")
+            },
+        },
+        Fixture {
+            name: "origin_id_on_two_servers_is_quiet",
+            expected: &[],
+            scenario: || {
+                Scenario::new()
+                    .server("survival")
+                    .acquires_from("minecraft:elytra", 1, "loot", "end-ship-1")
+                    .at_secs(2)
+                    .player("alex")
+                    .server("creative")
+                    .acquires_from("minecraft:elytra", 1, "loot", "end-ship-1")
+            },
+        },
+        Fixture {
+            name: "key_item_over_threshold_in_window",
+            expected: &["R4"],
+            scenario: || {
+                Scenario::new()
+                    .rule("minecraft:diamond", 64)
+                    .acquires("minecraft:diamond", 40, "craft")
+                    .at_secs(60)
+                    .acquires("minecraft:diamond", 40, "craft")
+            },
+        },
+        Fixture {
+            name: "key_item_window_expires",
+            expected: &[],
+            scenario: || {
+                Scenario::new()
+                    .rule("minecraft:diamond", 64)
+                    .acquires("minecraft:diamond", 40, "craft")
+                    .next_batch()
+                    .at_secs(11 * 60)
+                    .acquires("minecraft:diamond", 40, "craft")
+            },
+        },
+        Fixture {
+            name: "origin_reused_by_same_player_quickly",
+            expected: &["R5"],
+            scenario: || {
+                Scenario::new()
+                    .acquires_from("minecraft:diamond", 1, "world_pickup", "drop-1")
+                    .at_secs(20)
+                    .acquires_from("minecraft:diamond", 1, "world_pickup", "drop-1")
+            },
+        },
+        Fixture {
+            name: "identical_world_pickups",
+            expected: &["R6"],
+            scenario: || {
+                Scenario::new()
+                    .picks_up("minecraft:diamond", 1)
+                    .at_secs(5)
+                    .picks_up("minecraft:diamond", 1)
+            },
+        },
+        Fixture {
+            name: "inventory_audit_gain",
+            expected: &["R7"],
+            scenario: || {
+                Scenario::new()
+                    .audits("minecraft:iron_ingot", 10)
+                    .at_secs(10)
+                    .audits("minecraft:iron_ingot", 10)
+            },
+        },
+        Fixture {
+            name: "origin_reused_by_same_player_later",
+            expected: &["R8"],
+            scenario: || {
+                Scenario::new()
+                    .acquires_from("minecraft:diamond", 1, "world_pickup", "drop-1")
+                    .at_secs(60 * 60)
+                    .acquires_from("minecraft:diamond", 1, "world_pickup", "drop-1")
+            },
+        },
+        Fixture {
+            name: "inventory_snapshot_over_threshold",
+            expected: &["R9"],
+            scenario: || {
+                Scenario::new()
+                    .rule("minecraft:diamond", 64)
+                    .inventory_snapshot("minecraft:diamond", 100)
+            },
+        },
+        Fixture {
+            name: "world_pickup_volume",
+            expected: &["R10"],
+            scenario: || {
+                Scenario::new()
+                    .picks_up("minecraft:cobblestone", 128)
+                    .at_secs(16)
+                    .picks_up("minecraft:cobblestone", 128)
+            },
+        },
+        Fixture {
+            name: "storage_snapshot_over_threshold",
+            expected: &["R12"],
+            scenario: || {
+                Scenario::new()
+                    .rule("minecraft:diamond", 64)
+                    .storage_snapshot("minecraft:diamond", 100)
+            },
+        },
+        Fixture {
+            name: "whitelisted_craft_is_quiet",
+            expected: &[],
+            scenario: || Scenario::new().acquires("minecraft:diamond", 1, "craft"),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::entities::{
+        IngestEvent, KeyItemRule, OriginWhitelist, ThresholdUnit, TransferMatching,
+    };
+    use crate::services::Analyzer;
+    use crate::testing::{SCENARIO_SERVER_ID, SCENARIO_START_MS};
+
+    #[test]
+    fn every_regression_fixture_holds() {
+        for fixture in regression_fixtures() {
+            let outcome = (fixture.scenario)().run();
+            assert_eq!(
+                outcome.rule_ids(),
+                fixture.expected,
+                "fixture {}",
+                fixture.name
+            );
+        }
+    }
+
+    #[test]
+    fn key_item_anomaly_explains_its_window() {
+        let outcome = Scenario::new()
+            .rule("minecraft:diamond", 64)
+            .acquires("minecraft:diamond", 40, "craft")
+            .at_secs(60)
+            .acquires("minecraft:diamond", 40, "craft")
+            .run();
+        let row = outcome.of_rule("R4")[0];
+        let evidence: serde_json::Value =
+            serde_json::from_str(&row.evidence_json).expect("evidence");
+        let explain = &evidence["explain"];
+        assert_eq!(explain["threshold"], 64);
+        assert_eq!(explain["window_sum"], 80);
+        assert_eq!(explain["window_ms"], 600_000);
+        assert_eq!(explain["window_start_ms"], SCENARIO_START_MS);
+        assert_eq!(explain["rule_item_id"], "minecraft:diamond");
+        assert_eq!(
+            explain["matched"],
+            serde_json::json!([
+                { "time_ms": SCENARIO_START_MS, "count": 40 },
+                { "time_ms": SCENARIO_START_MS + 60_000, "count": 40 },
+            ])
+        );
+    }
+
+    #[test]
+    fn risk_override_reranks_a_rule_and_keeps_the_original() {
+        let outcome = Scenario::new()
+            .risk_override("R1", "MEDIUM")
+            .rule("minecraft:beacon", 1)
+            .acquires_without_origin("minecraft:beacon", 2)
+            .run();
+        outcome
+            .assert_risk("R1", "MEDIUM")
+            .assert_risk("R4", "HIGH");
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R1")[0].evidence_json).expect("evidence");
+        assert_eq!(
+            evidence["risk_override"],
+            serde_json::json!({ "from": "HIGH", "to": "MEDIUM", "source": "rule_risk_overrides" })
+        );
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R4")[0].evidence_json).expect("evidence");
+        assert!(evidence.get("risk_override").is_none());
+    }
+
+    #[test]
+    fn scenario_steps_shape_the_outcome() {
+        Scenario::new()
+            .rule_with_risk("minecraft:beacon", 1, "low")
+            .acquires_without_origin("minecraft:beacon", 2)
+            .run()
+            .assert_rules(&["R1", "R4"])
+            .assert_risk("R1", "HIGH")
+            .assert_risk("R4", "LOW");
+
+        Scenario::new()
+            .picks_up("minecraft:diamond", 1)
+            .at_secs(5)
+            .picks_up("minecraft:diamond", 1)
+            .with(|event| event.nbt_hash = Some("enchanted".to_string()))
+            .run()
+            .assert_not_fired("R6");
+
+        let learning = OriginWhitelist {
+            learning_until_ms: Some(i64::MAX),
+            ..OriginWhitelist::default()
+        };
+        Scenario::new()
+            .origin_whitelist(learning)
+            .acquires("botania:rune_fire", 1, "botania_rune_altar")
+            .run()
+            .assert_rules(&[]);
+    }
+
+    #[test]
+    fn weighted_rules_compare_stacks_and_value_units() {
+        let pearls = KeyItemRule {
+            item_id: "minecraft:ender_pearl".to_string(),
+            threshold: Some(2),
+            max_per_10m: None,
+            risk_level: Some("MEDIUM".to_string()),
+            weight: None,
+            daily_quota: None,
+            unit: Some(ThresholdUnit::Stacks),
+            stack_size: Some(16),
+            unit_value: None,
+        };
+        Scenario::new()
+            .key_item_rule(pearls.clone())
+            .acquires("minecraft:ender_pearl", 32, "craft")
+            .run()
+            .assert_rules(&[]);
+        let outcome = Scenario::new()
+            .key_item_rule(pearls)
+            .acquires("minecraft:ender_pearl", 40, "craft")
+            .run();
+        outcome.assert_rules(&["R4"]);
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R4")[0].evidence_json).expect("evidence");
+        assert_eq!(evidence["explain"]["unit"], "stacks");
+        assert_eq!(evidence["explain"]["weighted_sum"], 2.5);
+        assert_eq!(evidence["explain"]["stack_size"], 16);
+
+        let netherite = KeyItemRule {
+            item_id: "minecraft:netherite_block".to_string(),
+            threshold: None,
+            max_per_10m: None,
+            risk_level: None,
+            weight: None,
+            daily_quota: None,
+            unit: Some(ThresholdUnit::Value),
+            stack_size: None,
+            unit_value: Some(64.0),
+        };
+        let outcome = Scenario::new()
+            .key_item_rule(netherite)
+            .picks_up("minecraft:netherite_block", 4)
+            .run();
+        outcome.assert_rules(&["R10"]);
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R10")[0].evidence_json).expect("evidence");
+        assert_eq!(evidence["explain"]["item_weight"], 64.0);
+        assert_eq!(evidence["explain"]["weighted_sum"], 256.0);
+    }
+
+    #[test]
+    fn analyzer_status_reports_each_server_partition() {
+        let events: Vec<IngestEvent> = Scenario::new()
+            .transfers("minecraft:diamond", 64)
+            .server("creative")
+            .picks_up("minecraft:diamond", 1)
+            .picks_up("minecraft:diamond", 1)
+            .events()
+            .cloned()
+            .collect();
+        let mut analyzer = Analyzer::default();
+        analyzer.set_replay_clock(SCENARIO_START_MS);
+        analyzer.analyze_batch(&events, &HashMap::new(), 2_000, 600_000, 0, 0);
+
+        let status = analyzer.server_status();
+        let servers: Vec<&str> = status
+            .iter()
+            .map(|server| server.server_id.as_str())
+            .collect();
+        assert_eq!(servers, ["creative", SCENARIO_SERVER_ID]);
+        assert_eq!(status[0].events_analyzed, 2);
+        assert_eq!(status[0].origin_seen, 2);
+        assert_eq!(status[0].pickup_windows, 1);
+        assert_eq!(status[0].transfer_cache, 0);
+        assert_eq!(status[1].events_analyzed, 1);
+        assert_eq!(status[1].transfer_cache, 1);
+        assert_eq!(status[1].last_event_ms, Some(SCENARIO_START_MS));
+    }
+
+    #[test]
+    fn partitions_of_servers_gone_quiet_are_dropped() {
+        let events: Vec<IngestEvent> = Scenario::new()
+            .server("creative")
+            .picks_up("minecraft:diamond", 1)
+            .events()
+            .cloned()
+            .collect();
+        let mut analyzer = Analyzer::default();
+        analyzer.set_replay_clock(SCENARIO_START_MS);
+        analyzer.analyze_batch(&events, &HashMap::new(), 2_000, 600_000, 0, 0);
+
+        // Within the longest window (R8's six hours) the partition stays.
+        let later = SCENARIO_START_MS + 6 * 60 * 60 * 1000;
+        let events: Vec<IngestEvent> = Scenario::new()
+            .at_ms(later - SCENARIO_START_MS)
+            .picks_up("minecraft:diamond", 1)
+            .events()
+            .cloned()
+            .collect();
+        analyzer.set_replay_clock(later);
+        analyzer.analyze_batch(&events, &HashMap::new(), 2_000, 600_000, 0, 0);
+        assert_eq!(analyzer.server_status().len(), 2);
+
+        analyzer.set_replay_clock(later + 1);
+        analyzer.analyze_batch(&[], &HashMap::new(), 2_000, 600_000, 0, 0);
+        let status = analyzer.server_status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].server_id, SCENARIO_SERVER_ID);
+    }
+
+    #[test]
+    fn strict_transfer_fingerprint_refuses_synthesized_matches() {
+        let untagged = || {
+            Scenario::new()
+                .transfers("minecraft:diamond", 64)
+                .at_secs(1)
+                .acquires_without_origin("minecraft:diamond", 64)
+        };
+        untagged().run().assert_rules(&["R0"]);
+        let outcome = untagged().strict_transfer_fingerprint().run();
+        outcome.assert_rules(&["R1"]);
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R1")[0].evidence_json).expect("evidence");
+        assert_eq!(evidence["explain"]["fingerprint_refused"], true);
+
+        let tag = |event: &mut IngestEvent| event.item_fingerprint = Some("fp-1".to_string());
+        Scenario::new()
+            .strict_transfer_fingerprint()
+            .transfers("minecraft:diamond", 64)
+            .with(tag)
+            .at_secs(1)
+            .acquires_without_origin("minecraft:diamond", 64)
+            .with(tag)
+            .run()
+            .assert_rules(&["R0"]);
+    }
+
+    #[test]
+    fn transfer_matching_tolerates_partial_and_split_stacks() {
+        let partial = || {
+            Scenario::new()
+                .transfers("minecraft:diamond", 64)
+                .at_secs(1)
+                .acquires_without_origin("minecraft:diamond", 62)
+        };
+        partial().run().assert_rules(&["R1"]);
+        let outcome = partial()
+            .transfer_matching(TransferMatching {
+                count_tolerance: 2,
+                ..TransferMatching::default()
+            })
+            .run();
+        outcome.assert_rules(&["R0"]);
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R0")[0].evidence_json).expect("evidence");
+        assert_eq!(evidence["explain"]["strategy"], "tolerance");
+        assert_eq!(evidence["explain"]["count_delta"], -2);
+        partial()
+            .transfer_matching(TransferMatching {
+                count_tolerance_percent: 1.0,
+                ..TransferMatching::default()
+            })
+            .run()
+            .assert_rules(&["R1"]);
+
+        let split = || {
+            Scenario::new()
+                .transfers("minecraft:diamond", 32)
+                .transfers("minecraft:diamond", 32)
+                .at_secs(1)
+                .acquires_without_origin("minecraft:diamond", 64)
+        };
+        split().run().assert_rules(&["R1"]);
+        let outcome = split()
+            .transfer_matching(TransferMatching {
+                aggregate: true,
+                ..TransferMatching::default()
+            })
+            .run();
+        outcome.assert_rules(&["R0"]);
+        let evidence: serde_json::Value =
+            serde_json::from_str(&outcome.of_rule("R0")[0].evidence_json).expect("evidence");
+        assert_eq!(evidence["explain"]["strategy"], "aggregate");
+        assert_eq!(
+            evidence["explain"]["transfers"].as_array().map(Vec::len),
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,1375 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::entities::{
+    AlertDeliveryRecord, AlertDeliveryTotals, AlertPreview, AnomalyAckKey, AnomalyAckRequest,
+    AnomalyDailySummaryRow, AnomalyRow, AnomalySuppression, ApiToken, ClickhousePreflight,
+    CompositeRule, DeadLetterBatch, DetectionRule, FingerprintCollision, FingerprintUsage,
+    IngestEvent, ItemAnomalyStat, ItemCountDistribution, ItemEventFilter, ItemEventRow,
+    ItemRegistryEntry, KeyItemRule, ModConfigAck, ModConfigEnvelope, OriginWhitelist,
+    PartitionStat, PlayerAnomalyCount, PlayerBan, PlayerEventSpan, PlayerItemAcquired,
+    PlayerItemDailyTotal, PlayerQuarantine, PlayerTeam, RconConfig, ReportFile, ReportSummary,
+    RuleAnomalyCount, RuleRevision, RuntimeConfig, ShadowConsistency, StorageFinding,
+    StorageScanEventRow, StorageUsage,
+};
+use crate::ports::{
+    AlertService, AnomalyRepository, ConfigRepository, EventRepository, MaintenanceRepository,
+    ReportService,
+};
+use crate::services::anomaly_id_event_ms;
+use crate::utils::millis_to_utc;
+use crate::value_objects::FieldSelection;
+
+// In-memory implementations of the storage and alert ports, so an `AppState` can be composed
+// without ClickHouse, config files or an alert target. Days are the UTC dates of `event_time`,
+// field selections are ignored (rows come back whole) and nothing is persisted.
+
+/// `fetch_anomalies` reads at most this many rows, like the ClickHouse repository.
+const FETCH_ANOMALIES_LIMIT: usize = 500;
+
+fn day_of(time: OffsetDateTime) -> String {
+    time.date().to_string()
+}
+
+fn millis_of(time: OffsetDateTime) -> i64 {
+    (time.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+fn page<T>(rows: Vec<T>, offset: usize, limit: usize) -> Vec<T> {
+    rows.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Nearest-rank quantile of sorted `values`.
+fn quantile(values: &[i64], q: f64) -> f64 {
+    let rank = ((q * values.len() as f64).ceil() as usize).clamp(1, values.len());
+    values[rank - 1] as f64
+}
+
+fn item_event_row(event: &IngestEvent) -> ItemEventRow {
+    ItemEventRow {
+        event_time: millis_to_utc(event.event_time),
+        event_id: event.event_id.clone(),
+        server_id: event.server_id.clone().unwrap_or_default(),
+        event_type: event.event_type.clone(),
+        player_uuid: event.player_uuid.clone().unwrap_or_default(),
+        player_name: event.player_name.clone().unwrap_or_default(),
+        item_id: event.item_id.clone(),
+        count: event.count,
+        origin_id: event.origin_id.clone().unwrap_or_default(),
+        origin_type: event.origin_type.clone().unwrap_or_default(),
+        origin_ref: event.origin_ref.clone().unwrap_or_default(),
+        source_type: event.source_type.clone().unwrap_or_default(),
+        source_ref: event.source_ref.clone().unwrap_or_default(),
+        storage_mod: event.storage_mod.clone().unwrap_or_default(),
+        storage_id: event.storage_id.clone().unwrap_or_default(),
+        actor_type: event.actor_type.clone().unwrap_or_default(),
+        trace_id: event.trace_id.clone().unwrap_or_default(),
+        item_fingerprint: event.item_fingerprint.clone().unwrap_or_default(),
+        dim: event.dim.clone().unwrap_or_default(),
+        x: event.x,
+        y: event.y,
+        z: event.z,
+    }
+}
+
+fn matches_item_filter(row: &ItemEventRow, filter: &ItemEventFilter) -> bool {
+    let equals =
+        |value: &str, wanted: &Option<String>| wanted.as_deref().is_none_or(|w| value == w);
+    day_of(row.event_time) == filter.date
+        && filter
+            .player
+            .as_deref()
+            .is_none_or(|player| row.player_name == player || row.player_uuid == player)
+        && equals(&row.item_id, &filter.item_id)
+        && equals(&row.storage_id, &filter.storage_id)
+        && equals(&row.server_id, &filter.server_id)
+        && equals(&row.event_type, &filter.event_type)
+}
+
+/// Item events kept in insertion order; `custom` family events are kept apart, as they are in
+/// ClickHouse.
+#[derive(Default)]
+pub struct InMemoryEventRepository {
+    events: Mutex<Vec<ItemEventRow>>,
+    custom_events: Mutex<Vec<IngestEvent>>,
+}
+
+impl InMemoryEventRepository {
+    pub fn events(&self) -> Vec<ItemEventRow> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn custom_events(&self) -> Vec<IngestEvent> {
+        self.custom_events.lock().unwrap().clone()
+    }
+
+    /// Rows matching `filter`, newest first and then by event id.
+    fn filtered(&self, filter: &ItemEventFilter) -> Vec<ItemEventRow> {
+        let mut rows: Vec<ItemEventRow> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| matches_item_filter(row, filter))
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| {
+            b.event_time
+                .cmp(&a.event_time)
+                .then_with(|| a.event_id.cmp(&b.event_id))
+        });
+        rows
+    }
+
+    fn storage_scans(&self, date: &str, item: Option<&str>) -> Vec<StorageScanEventRow> {
+        let filter = ItemEventFilter {
+            date: date.to_string(),
+            player: None,
+            item_id: item.map(str::to_string),
+            storage_id: None,
+            server_id: None,
+            event_type: Some("STORAGE_SNAPSHOT".to_string()),
+        };
+        self.filtered(&filter)
+            .into_iter()
+            .map(|row| StorageScanEventRow {
+                event_time: row.event_time,
+                event_id: row.event_id,
+                item_id: row.item_id,
+                count: row.count,
+                storage_mod: row.storage_mod,
+                storage_id: row.storage_id,
+                dim: row.dim,
+                x: row.x,
+                y: row.y,
+                z: row.z,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventRepository for InMemoryEventRepository {
+    async fn ensure_schema(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn insert_events(&self, events: &[IngestEvent]) -> anyhow::Result<()> {
+        self.events
+            .lock()
+            .unwrap()
+            .extend(events.iter().map(item_event_row));
+        Ok(())
+    }
+
+    async fn insert_custom_events(&self, events: &[IngestEvent]) -> anyhow::Result<()> {
+        self.custom_events.lock().unwrap().extend_from_slice(events);
+        Ok(())
+    }
+
+    async fn fetch_storage_scan_events(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<StorageScanEventRow>> {
+        Ok(page(self.storage_scans(date, item), 0, limit))
+    }
+
+    async fn count_storage_scan_events(
+        &self,
+        date: &str,
+        item: Option<&str>,
+    ) -> anyhow::Result<u64> {
+        Ok(self.storage_scans(date, item).len() as u64)
+    }
+
+    async fn fetch_storage_scan_events_page(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        offset: usize,
+        limit: usize,
+        _fields: &FieldSelection,
+    ) -> anyhow::Result<Vec<StorageScanEventRow>> {
+        Ok(page(self.storage_scans(date, item), offset, limit))
+    }
+
+    async fn fetch_storage_scan_events_after(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        after: Option<(OffsetDateTime, &str)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<StorageScanEventRow>> {
+        let mut rows: Vec<StorageScanEventRow> = self
+            .storage_scans(date, item)
+            .into_iter()
+            .filter(|row| after.is_none_or(|after| (row.event_time, row.event_id.as_str()) < after))
+            .collect();
+        rows.sort_by(|a, b| (b.event_time, &b.event_id).cmp(&(a.event_time, &a.event_id)));
+        Ok(page(rows, 0, limit))
+    }
+
+    async fn count_item_events(&self, filter: &ItemEventFilter) -> anyhow::Result<u64> {
+        Ok(self.filtered(filter).len() as u64)
+    }
+
+    async fn fetch_item_events_page(
+        &self,
+        filter: &ItemEventFilter,
+        offset: usize,
+        limit: usize,
+        _fields: &FieldSelection,
+    ) -> anyhow::Result<Vec<ItemEventRow>> {
+        Ok(page(self.filtered(filter), offset, limit))
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn check_permissions(&self) -> anyhow::Result<ClickhousePreflight> {
+        Ok(ClickhousePreflight {
+            database: "in_memory".to_string(),
+            ok: true,
+            missing: Vec::new(),
+            checks: Vec::new(),
+        })
+    }
+
+    async fn round_trip_check(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_daily_acquired_totals(
+        &self,
+        date: &str,
+        player_uuids: &[String],
+        item_ids: &[String],
+    ) -> anyhow::Result<Vec<PlayerItemDailyTotal>> {
+        let mut totals: BTreeMap<(String, String), PlayerItemDailyTotal> = BTreeMap::new();
+        for row in self.events.lock().unwrap().iter() {
+            if row.event_type != "ACQUIRE"
+                || day_of(row.event_time) != date
+                || !player_uuids.contains(&row.player_uuid)
+                || !item_ids.contains(&row.item_id)
+            {
+                continue;
+            }
+            totals
+                .entry((row.player_uuid.clone(), row.item_id.clone()))
+                .or_insert_with(|| PlayerItemDailyTotal {
+                    player_uuid: row.player_uuid.clone(),
+                    player_name: row.player_name.clone(),
+                    item_id: row.item_id.clone(),
+                    total: 0,
+                })
+                .total += row.count;
+        }
+        Ok(totals.into_values().collect())
+    }
+
+    async fn fetch_item_ids_seen_since(&self, since_ms: i64) -> anyhow::Result<Vec<String>> {
+        let mut seen = Vec::new();
+        for row in self.events.lock().unwrap().iter() {
+            if millis_of(row.event_time) >= since_ms && !seen.contains(&row.item_id) {
+                seen.push(row.item_id.clone());
+            }
+        }
+        Ok(seen)
+    }
+
+    async fn fetch_item_count_distributions(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        item_ids: &[String],
+    ) -> anyhow::Result<Vec<ItemCountDistribution>> {
+        let mut daily: BTreeMap<(String, String, String), i64> = BTreeMap::new();
+        for row in self.events.lock().unwrap().iter() {
+            let day = day_of(row.event_time);
+            if row.event_type != "ACQUIRE"
+                || day.as_str() < from_date
+                || day.as_str() > to_date
+                || !item_ids.contains(&row.item_id)
+            {
+                continue;
+            }
+            *daily
+                .entry((row.item_id.clone(), row.player_uuid.clone(), day))
+                .or_default() += row.count;
+        }
+        let mut by_item: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+        for ((item_id, _, _), total) in daily {
+            by_item.entry(item_id).or_default().push(total);
+        }
+        Ok(by_item
+            .into_iter()
+            .map(|(item_id, mut totals)| {
+                totals.sort_unstable();
+                ItemCountDistribution {
+                    item_id,
+                    samples: totals.len() as u64,
+                    p50: quantile(&totals, 0.5),
+                    p90: quantile(&totals, 0.9),
+                    p99: quantile(&totals, 0.99),
+                    max: totals[totals.len() - 1],
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_player_event_span(
+        &self,
+        player_uuid: &str,
+    ) -> anyhow::Result<Option<PlayerEventSpan>> {
+        let mut span: Option<PlayerEventSpan> = None;
+        for row in self.events.lock().unwrap().iter() {
+            if row.player_uuid != player_uuid {
+                continue;
+            }
+            let time_ms = millis_of(row.event_time);
+            let span = span.get_or_insert_with(|| PlayerEventSpan {
+                player_name: row.player_name.clone(),
+                events: 0,
+                first_seen_ms: time_ms,
+                last_seen_ms: time_ms,
+            });
+            span.events += 1;
+            span.first_seen_ms = span.first_seen_ms.min(time_ms);
+            if time_ms >= span.last_seen_ms {
+                span.last_seen_ms = time_ms;
+                span.player_name = row.player_name.clone();
+            }
+        }
+        Ok(span)
+    }
+
+    async fn fetch_player_acquired_items(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<PlayerItemAcquired>> {
+        let mut items: BTreeMap<String, PlayerItemAcquired> = BTreeMap::new();
+        for row in self.events.lock().unwrap().iter() {
+            if row.player_uuid != player_uuid
+                || row.event_type != "ACQUIRE"
+                || millis_of(row.event_time) < since_ms
+            {
+                continue;
+            }
+            let item = items
+                .entry(row.item_id.clone())
+                .or_insert_with(|| PlayerItemAcquired {
+                    item_id: row.item_id.clone(),
+                    acquisitions: 0,
+                    total: 0,
+                });
+            item.acquisitions += 1;
+            item.total += row.count;
+        }
+        let mut items: Vec<PlayerItemAcquired> = items.into_values().collect();
+        items.sort_by_key(|item| Reverse(item.total));
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    async fn fetch_player_recent_events(
+        &self,
+        player_uuid: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ItemEventRow>> {
+        let mut rows: Vec<ItemEventRow> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| row.player_uuid == player_uuid)
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| Reverse(row.event_time));
+        rows.truncate(limit);
+        Ok(rows)
+    }
+
+    async fn fetch_events_by_trace(
+        &self,
+        trace_id: &str,
+        item_fingerprint: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ItemEventRow>> {
+        let mut rows: Vec<ItemEventRow> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| row.event_type == "TRANSFER" || row.event_type == "ACQUIRE")
+            .filter(|row| {
+                (!trace_id.is_empty() && row.trace_id == trace_id)
+                    || (!item_fingerprint.is_empty() && row.item_fingerprint == item_fingerprint)
+            })
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| {
+            a.event_time
+                .cmp(&b.event_time)
+                .then_with(|| a.event_id.cmp(&b.event_id))
+        });
+        rows.truncate(limit);
+        Ok(rows)
+    }
+
+    async fn fetch_fingerprint_usage(
+        &self,
+        since_ms: i64,
+    ) -> anyhow::Result<Vec<FingerprintUsage>> {
+        let mut usage: BTreeMap<String, FingerprintUsage> = BTreeMap::new();
+        for row in self.events.lock().unwrap().iter() {
+            if (row.event_type != "TRANSFER" && row.event_type != "ACQUIRE")
+                || millis_of(row.event_time) < since_ms
+            {
+                continue;
+            }
+            let entry = usage
+                .entry(row.server_id.clone())
+                .or_insert_with(|| FingerprintUsage {
+                    server_id: row.server_id.clone(),
+                    events: 0,
+                    missing: 0,
+                });
+            entry.events += 1;
+            entry.missing += u64::from(row.item_fingerprint.is_empty());
+        }
+        Ok(usage.into_values().collect())
+    }
+
+    async fn fetch_fingerprint_collisions(
+        &self,
+        since_ms: i64,
+        per_server: usize,
+    ) -> anyhow::Result<Vec<FingerprintCollision>> {
+        type Carriers = (u64, BTreeSet<String>, BTreeSet<String>);
+        let mut groups: BTreeMap<(String, String, bool), Carriers> = BTreeMap::new();
+        for row in self.events.lock().unwrap().iter() {
+            if (row.event_type != "TRANSFER" && row.event_type != "ACQUIRE")
+                || millis_of(row.event_time) < since_ms
+            {
+                continue;
+            }
+            let synthesized = row.item_fingerprint.is_empty();
+            let fingerprint = if synthesized {
+                format!("{}:*", row.item_id)
+            } else {
+                row.item_fingerprint.clone()
+            };
+            let (events, players, item_ids) = groups
+                .entry((row.server_id.clone(), fingerprint, synthesized))
+                .or_default();
+            *events += 1;
+            players.insert(row.player_uuid.clone());
+            item_ids.insert(row.item_id.clone());
+        }
+        let mut collisions: Vec<FingerprintCollision> = groups
+            .into_iter()
+            .filter(|(_, (_, players, item_ids))| players.len() > 1 || item_ids.len() > 1)
+            .map(
+                |((server_id, fingerprint, synthesized), (events, players, item_ids))| {
+                    FingerprintCollision {
+                        server_id,
+                        fingerprint,
+                        synthesized,
+                        events,
+                        players: players.len() as u64,
+                        item_ids: item_ids.len() as u64,
+                    }
+                },
+            )
+            .collect();
+        collisions.sort_by(|a, b| {
+            a.server_id
+                .cmp(&b.server_id)
+                .then_with(|| b.players.cmp(&a.players))
+                .then_with(|| b.events.cmp(&a.events))
+                .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+        });
+        let mut listed: BTreeMap<String, usize> = BTreeMap::new();
+        collisions.retain(|collision| {
+            let count = listed.entry(collision.server_id.clone()).or_default();
+            *count += 1;
+            *count <= per_server
+        });
+        Ok(collisions)
+    }
+}
+
+/// Seeded partition stats and no disk usage; `optimize_partition` and `drop_partition` calls
+/// are recorded.
+#[derive(Default)]
+pub struct InMemoryMaintenanceRepository {
+    partition_stats: Mutex<Vec<PartitionStat>>,
+    optimized: Mutex<Vec<(String, String)>>,
+    dropped: Mutex<Vec<(String, String)>>,
+}
+
+impl InMemoryMaintenanceRepository {
+    /// Seeds what `fetch_partition_stats` returns; dropped partitions leave it.
+    pub fn set_partition_stats(&self, stats: Vec<PartitionStat>) {
+        *self.partition_stats.lock().unwrap() = stats;
+    }
+
+    /// `(table, partition_id)` pairs passed to `optimize_partition`, in call order.
+    pub fn optimized(&self) -> Vec<(String, String)> {
+        self.optimized.lock().unwrap().clone()
+    }
+
+    /// `(table, partition_id)` pairs passed to `drop_partition`, in call order.
+    pub fn dropped(&self) -> Vec<(String, String)> {
+        self.dropped.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MaintenanceRepository for InMemoryMaintenanceRepository {
+    async fn fetch_partition_stats(&self) -> anyhow::Result<Vec<PartitionStat>> {
+        Ok(self.partition_stats.lock().unwrap().clone())
+    }
+
+    async fn optimize_partition(&self, table: &str, partition_id: &str) -> anyhow::Result<()> {
+        self.optimized
+            .lock()
+            .unwrap()
+            .push((table.to_string(), partition_id.to_string()));
+        Ok(())
+    }
+
+    async fn drop_partition(&self, table: &str, partition_id: &str) -> anyhow::Result<()> {
+        self.partition_stats
+            .lock()
+            .unwrap()
+            .retain(|stat| stat.table != table || stat.partition_id != partition_id);
+        self.dropped
+            .lock()
+            .unwrap()
+            .push((table.to_string(), partition_id.to_string()));
+        Ok(())
+    }
+
+    async fn fetch_storage_usage(&self) -> anyhow::Result<StorageUsage> {
+        Ok(StorageUsage::default())
+    }
+
+    async fn compare_shadow_counts(
+        &self,
+        _date: &str,
+    ) -> anyhow::Result<Option<ShadowConsistency>> {
+        Ok(None)
+    }
+}
+
+struct AckRecord {
+    key: AnomalyAckKey,
+    note: String,
+    acked_by: String,
+}
+
+/// Anomalies, their acks and read receipts and the daily rollup; `player` filters match the
+/// player name.
+#[derive(Default)]
+pub struct InMemoryAnomalyRepository {
+    anomalies: Mutex<Vec<AnomalyRow>>,
+    acks: Mutex<Vec<AckRecord>>,
+    /// `(anomaly id, seen_by)` in the order they were recorded.
+    seen: Mutex<Vec<(String, String)>>,
+    daily_summary: Mutex<Vec<AnomalyDailySummaryRow>>,
+}
+
+impl InMemoryAnomalyRepository {
+    pub fn anomalies(&self) -> Vec<AnomalyRow> {
+        self.anomalies.lock().unwrap().clone()
+    }
+
+    /// Every ack as `(key, note, acked_by)`, in the order they were made.
+    pub fn acks(&self) -> Vec<(AnomalyAckKey, String, String)> {
+        self.acks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|ack| (ack.key.clone(), ack.note.clone(), ack.acked_by.clone()))
+            .collect()
+    }
+
+    /// Every read receipt as `(anomaly id, seen_by)`, in the order they were recorded.
+    pub fn seen(&self) -> Vec<(String, String)> {
+        self.seen.lock().unwrap().clone()
+    }
+
+    /// Removes the anomalies of `date`, as dropping their ClickHouse partition would.
+    pub fn drop_date(&self, date: &str) {
+        self.anomalies
+            .lock()
+            .unwrap()
+            .retain(|row| day_of(row.event_time) != date);
+    }
+
+    /// Anomalies on `date`, newest first.
+    fn on_date(&self, date: &str, player: Option<&str>) -> Vec<AnomalyRow> {
+        let mut rows: Vec<AnomalyRow> = self
+            .anomalies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| day_of(row.event_time) == date)
+            .filter(|row| player.is_none_or(|player| row.player_name == player))
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| Reverse(row.event_time));
+        rows
+    }
+
+    fn push_ack(&self, row: &AnomalyRow, note: &str, acked_by: &str) {
+        self.acks.lock().unwrap().push(AckRecord {
+            key: AnomalyAckKey {
+                event_time: row.event_time,
+                player_uuid: row.player_uuid.clone(),
+                item_id: row.item_id.clone(),
+                rule_id: row.rule_id.clone(),
+            },
+            note: note.to_string(),
+            acked_by: acked_by.to_string(),
+        });
+    }
+}
+
+fn summarize<'a>(rows: impl Iterator<Item = &'a AnomalyRow>) -> ReportSummary {
+    let mut summary = ReportSummary::default();
+    for row in rows {
+        match row.risk_level.as_str() {
+            "CRITICAL" => summary.critical += 1,
+            "HIGH" => summary.high += 1,
+            "MEDIUM" => summary.medium += 1,
+            "LOW" => summary.low += 1,
+            _ => {}
+        }
+    }
+    summary
+}
+
+#[async_trait]
+impl AnomalyRepository for InMemoryAnomalyRepository {
+    async fn insert_anomalies(&self, anomalies: &[AnomalyRow]) -> anyhow::Result<()> {
+        self.anomalies.lock().unwrap().extend_from_slice(anomalies);
+        Ok(())
+    }
+
+    async fn fetch_anomalies(
+        &self,
+        date: &str,
+        player: Option<&str>,
+    ) -> anyhow::Result<Vec<AnomalyRow>> {
+        Ok(page(self.on_date(date, player), 0, FETCH_ANOMALIES_LIMIT))
+    }
+
+    async fn count_anomalies(&self, date: &str, player: Option<&str>) -> anyhow::Result<u64> {
+        Ok(self.on_date(date, player).len() as u64)
+    }
+
+    async fn fetch_anomalies_page(
+        &self,
+        date: &str,
+        player: Option<&str>,
+        offset: usize,
+        limit: usize,
+        _fields: &FieldSelection,
+    ) -> anyhow::Result<Vec<AnomalyRow>> {
+        Ok(page(self.on_date(date, player), offset, limit))
+    }
+
+    async fn fetch_anomalies_after(
+        &self,
+        date: &str,
+        player: Option<&str>,
+        after: Option<&AnomalyAckKey>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<AnomalyRow>> {
+        let key = |row: &AnomalyRow| AnomalyAckKey {
+            event_time: row.event_time,
+            player_uuid: row.player_uuid.clone(),
+            item_id: row.item_id.clone(),
+            rule_id: row.rule_id.clone(),
+        };
+        let sort_key = |key: &AnomalyAckKey| {
+            (
+                key.event_time,
+                key.player_uuid.clone(),
+                key.item_id.clone(),
+                key.rule_id.clone(),
+            )
+        };
+        let mut rows: Vec<AnomalyRow> = self
+            .on_date(date, player)
+            .into_iter()
+            .filter(|row| after.is_none_or(|after| sort_key(&key(row)) < sort_key(after)))
+            .collect();
+        rows.sort_by_key(|row| Reverse(sort_key(&key(row))));
+        Ok(page(rows, 0, limit))
+    }
+
+    async fn fetch_anomalies_at(&self, event_time_ms: i64) -> anyhow::Result<Vec<AnomalyRow>> {
+        Ok(self
+            .anomalies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| millis_of(row.event_time) == event_time_ms)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_summary(&self, date: &str) -> anyhow::Result<ReportSummary> {
+        Ok(summarize(self.on_date(date, None).iter()))
+    }
+
+    async fn fetch_summary_between(
+        &self,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> anyhow::Result<ReportSummary> {
+        let anomalies = self.anomalies.lock().unwrap();
+        Ok(summarize(anomalies.iter().filter(|row| {
+            let millis = millis_of(row.event_time);
+            millis >= from_ms && millis < to_ms
+        })))
+    }
+
+    async fn fetch_top_players(
+        &self,
+        date: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<PlayerAnomalyCount>> {
+        let mut players: BTreeMap<String, PlayerAnomalyCount> = BTreeMap::new();
+        for row in self.on_date(date, None) {
+            if row.player_uuid.is_empty() {
+                continue;
+            }
+            let entry =
+                players
+                    .entry(row.player_uuid.clone())
+                    .or_insert_with(|| PlayerAnomalyCount {
+                        player_uuid: row.player_uuid.clone(),
+                        player_name: row.player_name.clone(),
+                        anomalies: 0,
+                        critical: 0,
+                        high: 0,
+                    });
+            entry.anomalies += 1;
+            match row.risk_level.as_str() {
+                "CRITICAL" => entry.critical += 1,
+                "HIGH" => entry.high += 1,
+                _ => {}
+            }
+        }
+        let mut players: Vec<PlayerAnomalyCount> = players.into_values().collect();
+        players.sort_by(|a, b| {
+            b.critical
+                .cmp(&a.critical)
+                .then_with(|| b.high.cmp(&a.high))
+                .then_with(|| b.anomalies.cmp(&a.anomalies))
+                .then_with(|| a.player_name.cmp(&b.player_name))
+        });
+        players.truncate(limit);
+        Ok(players)
+    }
+
+    async fn rollup_daily_summary(&self, date: &str) -> anyhow::Result<()> {
+        let mut counts: BTreeMap<(String, String, String), u64> = BTreeMap::new();
+        for row in self.on_date(date, None) {
+            *counts
+                .entry((row.server_id, row.rule_id, row.risk_level))
+                .or_default() += 1;
+        }
+        let mut summary = self.daily_summary.lock().unwrap();
+        summary.retain(|row| row.date != date);
+        summary.extend(
+            counts
+                .into_iter()
+                .map(
+                    |((server_id, rule_id, risk_level), count)| AnomalyDailySummaryRow {
+                        date: date.to_string(),
+                        server_id,
+                        rule_id,
+                        risk_level,
+                        count,
+                    },
+                ),
+        );
+        Ok(())
+    }
+
+    async fn fetch_daily_summary(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        server_id: Option<&str>,
+        rule_id: Option<&str>,
+    ) -> anyhow::Result<Vec<AnomalyDailySummaryRow>> {
+        Ok(self
+            .daily_summary
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|row| row.date.as_str() >= from_date && row.date.as_str() <= to_date)
+            .filter(|row| server_id.is_none_or(|server_id| row.server_id == server_id))
+            .filter(|row| rule_id.is_none_or(|rule_id| row.rule_id == rule_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn ack_anomalies(
+        &self,
+        request: &AnomalyAckRequest,
+        _acked_at_ms: i64,
+    ) -> anyhow::Result<u64> {
+        let equals =
+            |value: &str, wanted: &Option<String>| wanted.as_deref().is_none_or(|w| value == w);
+        let matched: Vec<AnomalyRow> = self
+            .on_date(&request.date, None)
+            .into_iter()
+            .filter(|row| {
+                equals(&row.rule_id, &request.rule_id)
+                    && equals(&row.player_name, &request.player)
+                    && equals(&row.server_id, &request.server_id)
+                    && equals(&row.item_id, &request.item_id)
+            })
+            .collect();
+        let note = request.note.as_deref().unwrap_or_default();
+        let acked_by = request.acked_by.as_deref().unwrap_or_default();
+        for row in &matched {
+            self.push_ack(row, note, acked_by);
+        }
+        Ok(matched.len() as u64)
+    }
+
+    async fn ack_anomaly(
+        &self,
+        key: &AnomalyAckKey,
+        _acked_at_ms: i64,
+        note: &str,
+        acked_by: &str,
+    ) -> anyhow::Result<()> {
+        self.acks.lock().unwrap().push(AckRecord {
+            key: key.clone(),
+            note: note.to_string(),
+            acked_by: acked_by.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn fetch_acked_keys(&self, date: &str) -> anyhow::Result<Vec<AnomalyAckKey>> {
+        let mut keys = Vec::new();
+        for ack in self.acks.lock().unwrap().iter() {
+            if day_of(ack.key.event_time) == date && !keys.contains(&ack.key) {
+                keys.push(ack.key.clone());
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn mark_anomalies_seen(
+        &self,
+        ids: &[String],
+        _seen_at_ms: i64,
+        seen_by: &str,
+    ) -> anyhow::Result<()> {
+        let mut seen = self.seen.lock().unwrap();
+        seen.extend(ids.iter().map(|id| (id.clone(), seen_by.to_string())));
+        Ok(())
+    }
+
+    async fn fetch_seen_ids(&self, date: &str) -> anyhow::Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for (id, _) in self.seen.lock().unwrap().iter() {
+            let on_date = anomaly_id_event_ms(id)
+                .is_some_and(|event_ms| day_of(millis_to_utc(event_ms)) == date);
+            if on_date && !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        Ok(ids)
+    }
+
+    async fn fetch_item_anomaly_stats(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        rule_ids: &[String],
+    ) -> anyhow::Result<Vec<ItemAnomalyStat>> {
+        let mut stats: BTreeMap<String, (ItemAnomalyStat, HashSet<String>)> = BTreeMap::new();
+        for row in self.anomalies.lock().unwrap().iter() {
+            let day = day_of(row.event_time);
+            if day.as_str() < from_date || day.as_str() > to_date {
+                continue;
+            }
+            let (stat, players) = stats.entry(row.item_id.clone()).or_insert_with(|| {
+                let stat = ItemAnomalyStat {
+                    item_id: row.item_id.clone(),
+                    anomalies: 0,
+                    rule_hits: 0,
+                    players: 0,
+                };
+                (stat, HashSet::new())
+            });
+            stat.anomalies += 1;
+            if rule_ids.contains(&row.rule_id) {
+                stat.rule_hits += 1;
+            }
+            players.insert(row.player_uuid.clone());
+            stat.players = players.len() as u64;
+        }
+        Ok(stats.into_values().map(|(stat, _)| stat).collect())
+    }
+
+    async fn fetch_player_rule_counts(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+    ) -> anyhow::Result<Vec<RuleAnomalyCount>> {
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for row in self.anomalies.lock().unwrap().iter() {
+            if row.player_uuid == player_uuid && millis_of(row.event_time) >= since_ms {
+                *counts.entry(row.rule_id.clone()).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<RuleAnomalyCount> = counts
+            .into_iter()
+            .map(|(rule_id, anomalies)| RuleAnomalyCount { rule_id, anomalies })
+            .collect();
+        counts.sort_by_key(|count| Reverse(count.anomalies));
+        Ok(counts)
+    }
+}
+
+#[derive(Default)]
+struct ConfigStore {
+    key_items: HashMap<String, Vec<KeyItemRule>>,
+    detection_rules: HashMap<String, Vec<DetectionRule>>,
+    composite_rules: HashMap<String, Vec<CompositeRule>>,
+    item_registries: HashMap<String, Vec<ItemRegistryEntry>>,
+    rcon: Option<RconConfig>,
+    mod_configs: HashMap<String, ModConfigEnvelope>,
+    mod_config_acks: HashMap<String, ModConfigAck>,
+    storage_findings: Vec<StorageFinding>,
+    suppressions: Vec<AnomalySuppression>,
+    dead_letters: Vec<DeadLetterBatch>,
+    bans: Vec<PlayerBan>,
+    quarantines: Vec<PlayerQuarantine>,
+    api_tokens: Vec<ApiToken>,
+    player_teams: Vec<PlayerTeam>,
+    rule_revisions: Vec<RuleRevision>,
+    origin_whitelist: Option<OriginWhitelist>,
+    reports: Vec<ReportFile>,
+    report_files: HashMap<String, String>,
+}
+
+/// Config documents kept by path or server id. Anything never saved loads as the file
+/// repository's missing-file value: empty, `None` or the built-in default.
+#[derive(Default)]
+pub struct InMemoryConfigRepository {
+    store: Mutex<ConfigStore>,
+}
+
+impl InMemoryConfigRepository {
+    /// Adds a report for `list_reports`; reports are listed whatever the `report_dir`.
+    pub fn add_report(&self, report: ReportFile) {
+        self.store.lock().unwrap().reports.push(report);
+    }
+
+    /// Seeds the detection rules `load_detection_rules` returns for `path`.
+    pub fn set_detection_rules(&self, path: &str, rules: Vec<DetectionRule>) {
+        self.store
+            .lock()
+            .unwrap()
+            .detection_rules
+            .insert(path.to_string(), rules);
+    }
+
+    /// Seeds the composite rules `load_composite_rules` returns for `path`.
+    pub fn set_composite_rules(&self, path: &str, rules: Vec<CompositeRule>) {
+        self.store
+            .lock()
+            .unwrap()
+            .composite_rules
+            .insert(path.to_string(), rules);
+    }
+}
+
+#[async_trait]
+impl ConfigRepository for InMemoryConfigRepository {
+    async fn load_key_items(&self, path: &str) -> anyhow::Result<HashMap<String, KeyItemRule>> {
+        Ok(self
+            .store
+            .lock()
+            .unwrap()
+            .key_items
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|rule| (rule.item_id.clone(), rule.clone()))
+            .collect())
+    }
+
+    async fn save_key_items(&self, path: &str, rules: &[KeyItemRule]) -> anyhow::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .key_items
+            .insert(path.to_string(), rules.to_vec());
+        Ok(())
+    }
+
+    async fn load_detection_rules(&self, path: &str) -> anyhow::Result<Vec<DetectionRule>> {
+        Ok(self
+            .store
+            .lock()
+            .unwrap()
+            .detection_rules
+            .get(path)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn load_composite_rules(&self, path: &str) -> anyhow::Result<Vec<CompositeRule>> {
+        Ok(self
+            .store
+            .lock()
+            .unwrap()
+            .composite_rules
+            .get(path)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn load_item_registry(&self, path: &str) -> anyhow::Result<Vec<ItemRegistryEntry>> {
+        let store = self.store.lock().unwrap();
+        Ok(store.item_registries.get(path).cloned().unwrap_or_default())
+    }
+
+    async fn save_item_registry(
+        &self,
+        path: &str,
+        items: &[ItemRegistryEntry],
+    ) -> anyhow::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .item_registries
+            .insert(path.to_string(), items.to_vec());
+        Ok(())
+    }
+
+    async fn load_rcon_config(&self) -> anyhow::Result<RconConfig> {
+        Ok(self.store.lock().unwrap().rcon.clone().unwrap_or_default())
+    }
+
+    async fn save_rcon_config(&self, config: &RconConfig) -> anyhow::Result<()> {
+        self.store.lock().unwrap().rcon = Some(config.clone());
+        Ok(())
+    }
+
+    async fn load_mod_config(&self, server_id: &str) -> anyhow::Result<Option<ModConfigEnvelope>> {
+        Ok(self
+            .store
+            .lock()
+            .unwrap()
+            .mod_configs
+            .get(server_id)
+            .cloned())
+    }
+
+    async fn save_mod_config(&self, envelope: &ModConfigEnvelope) -> anyhow::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .mod_configs
+            .insert(envelope.server_id.clone(), envelope.clone());
+        Ok(())
+    }
+
+    async fn load_mod_config_ack(&self, server_id: &str) -> anyhow::Result<Option<ModConfigAck>> {
+        Ok(self
+            .store
+            .lock()
+            .unwrap()
+            .mod_config_acks
+            .get(server_id)
+            .cloned())
+    }
+
+    async fn save_mod_config_ack(&self, ack: &ModConfigAck) -> anyhow::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .mod_config_acks
+            .insert(ack.server_id.clone(), ack.clone());
+        Ok(())
+    }
+
+    async fn load_storage_findings(&self) -> anyhow::Result<Vec<StorageFinding>> {
+        Ok(self.store.lock().unwrap().storage_findings.clone())
+    }
+
+    async fn save_storage_findings(&self, findings: &[StorageFinding]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().storage_findings = findings.to_vec();
+        Ok(())
+    }
+
+    async fn load_suppressions(&self) -> anyhow::Result<Vec<AnomalySuppression>> {
+        Ok(self.store.lock().unwrap().suppressions.clone())
+    }
+
+    async fn save_suppressions(&self, suppressions: &[AnomalySuppression]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().suppressions = suppressions.to_vec();
+        Ok(())
+    }
+
+    async fn load_dead_letters(&self) -> anyhow::Result<Vec<DeadLetterBatch>> {
+        Ok(self.store.lock().unwrap().dead_letters.clone())
+    }
+
+    async fn append_dead_letters(&self, batch: &DeadLetterBatch) -> anyhow::Result<()> {
+        self.store.lock().unwrap().dead_letters.push(batch.clone());
+        Ok(())
+    }
+
+    async fn save_dead_letters(&self, batches: &[DeadLetterBatch]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().dead_letters = batches.to_vec();
+        Ok(())
+    }
+
+    async fn load_bans(&self) -> anyhow::Result<Vec<PlayerBan>> {
+        Ok(self.store.lock().unwrap().bans.clone())
+    }
+
+    async fn save_bans(&self, bans: &[PlayerBan]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().bans = bans.to_vec();
+        Ok(())
+    }
+
+    async fn load_quarantines(&self) -> anyhow::Result<Vec<PlayerQuarantine>> {
+        Ok(self.store.lock().unwrap().quarantines.clone())
+    }
+
+    async fn save_quarantines(&self, quarantines: &[PlayerQuarantine]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().quarantines = quarantines.to_vec();
+        Ok(())
+    }
+
+    async fn load_api_tokens(&self) -> anyhow::Result<Vec<ApiToken>> {
+        Ok(self.store.lock().unwrap().api_tokens.clone())
+    }
+
+    async fn save_api_tokens(&self, tokens: &[ApiToken]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().api_tokens = tokens.to_vec();
+        Ok(())
+    }
+
+    async fn load_player_teams(&self) -> anyhow::Result<Vec<PlayerTeam>> {
+        Ok(self.store.lock().unwrap().player_teams.clone())
+    }
+
+    async fn save_player_teams(&self, teams: &[PlayerTeam]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().player_teams = teams.to_vec();
+        Ok(())
+    }
+
+    async fn load_rule_revisions(&self) -> anyhow::Result<Vec<RuleRevision>> {
+        Ok(self.store.lock().unwrap().rule_revisions.clone())
+    }
+
+    async fn save_rule_revisions(&self, revisions: &[RuleRevision]) -> anyhow::Result<()> {
+        self.store.lock().unwrap().rule_revisions = revisions.to_vec();
+        Ok(())
+    }
+
+    async fn load_origin_whitelist(&self) -> anyhow::Result<OriginWhitelist> {
+        let store = self.store.lock().unwrap();
+        Ok(store.origin_whitelist.clone().unwrap_or_default())
+    }
+
+    async fn save_origin_whitelist(&self, whitelist: &OriginWhitelist) -> anyhow::Result<()> {
+        self.store.lock().unwrap().origin_whitelist = Some(whitelist.clone());
+        Ok(())
+    }
+
+    async fn round_trip_check(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn check_report_dir(&self, _report_dir: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn list_reports(&self, _report_dir: &str) -> anyhow::Result<Vec<ReportFile>> {
+        let mut reports = self.store.lock().unwrap().reports.clone();
+        reports.sort_by(|a, b| b.date.cmp(&a.date));
+        Ok(reports)
+    }
+
+    async fn delete_report(&self, _report_dir: &str, date: &str) -> anyhow::Result<bool> {
+        let mut store = self.store.lock().unwrap();
+        let before = store.reports.len();
+        store.reports.retain(|report| report.date != date);
+        Ok(store.reports.len() != before)
+    }
+
+    /// `{date}.html` of an added report is a stub page naming the date.
+    async fn read_report_file(
+        &self,
+        _report_dir: &str,
+        file: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let store = self.store.lock().unwrap();
+        if let Some(contents) = store.report_files.get(file) {
+            return Ok(Some(contents.clone()));
+        }
+        Ok(store
+            .reports
+            .iter()
+            .find(|report| file == format!("{}.html", report.date))
+            .map(|report| format!("<html><body>{}</body></html>", report.date)))
+    }
+
+    async fn write_report_file(
+        &self,
+        _report_dir: &str,
+        file: &str,
+        contents: &str,
+    ) -> anyhow::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .report_files
+            .insert(file.to_string(), contents.to_string());
+        Ok(())
+    }
+}
+
+/// Keeps every alert instead of sending it; the alert target always checks out and no
+/// delivery receipts are produced.
+#[derive(Default)]
+pub struct RecordingAlertService {
+    alerts: Mutex<Vec<AnomalyRow>>,
+    system_alerts: Mutex<Vec<String>>,
+    group_texts: Mutex<Vec<(i64, String)>>,
+}
+
+impl RecordingAlertService {
+    /// Anomalies handed to `spawn_alerts`, in call order.
+    pub fn alerts(&self) -> Vec<AnomalyRow> {
+        self.alerts.lock().unwrap().clone()
+    }
+
+    pub fn system_alerts(&self) -> Vec<String> {
+        self.system_alerts.lock().unwrap().clone()
+    }
+
+    pub fn group_texts(&self) -> Vec<(i64, String)> {
+        self.group_texts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AlertService for RecordingAlertService {
+    fn spawn_alerts(&self, _config: RuntimeConfig, anomalies: Vec<AnomalyRow>) {
+        self.alerts.lock().unwrap().extend(anomalies);
+    }
+
+    fn preview_alerts(&self, _config: &RuntimeConfig, anomalies: Vec<AnomalyRow>) -> AlertPreview {
+        let text = anomalies
+            .iter()
+            .map(|row| {
+                format!(
+                    "{} {} {} x{}",
+                    row.rule_id, row.player_name, row.item_id, row.count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        AlertPreview {
+            mode: "recording".to_string(),
+            sample_source: "request".to_string(),
+            alert_count: anomalies.len(),
+            filtered_count: 0,
+            payload: text.clone(),
+            text,
+            payload_valid_json: false,
+        }
+    }
+
+    async fn send_system_alert(
+        &self,
+        _config: &RuntimeConfig,
+        message: &str,
+    ) -> anyhow::Result<()> {
+        self.system_alerts.lock().unwrap().push(message.to_string());
+        Ok(())
+    }
+
+    async fn send_group_text(
+        &self,
+        _config: &RuntimeConfig,
+        group_id: i64,
+        message: &str,
+    ) -> anyhow::Result<()> {
+        self.group_texts
+            .lock()
+            .unwrap()
+            .push((group_id, message.to_string()));
+        Ok(())
+    }
+
+    async fn check_alert_target(&self, _config: &RuntimeConfig) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn next_alert_page(&self, _group_id: i64) -> Option<String> {
+        None
+    }
+
+    async fn list_alert_deliveries(&self, _limit: usize) -> Vec<AlertDeliveryRecord> {
+        Vec::new()
+    }
+
+    async fn last_alert_delivery(&self) -> Option<AlertDeliveryRecord> {
+        None
+    }
+
+    async fn alert_delivery_totals(&self) -> AlertDeliveryTotals {
+        AlertDeliveryTotals::default()
+    }
+}
+
+/// Records the dates it was asked to render instead of writing HTML.
+#[derive(Default)]
+pub struct RecordingReportService {
+    dates: Mutex<Vec<String>>,
+}
+
+impl RecordingReportService {
+    pub fn dates(&self) -> Vec<String> {
+        self.dates.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ReportService for RecordingReportService {
+    async fn generate_report(&self, date: &str) -> anyhow::Result<()> {
+        self.dates.lock().unwrap().push(date.to_string());
+        Ok(())
+    }
+}
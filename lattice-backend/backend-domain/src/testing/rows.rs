@@ -0,0 +1,19 @@
+use crate::entities::AnomalyRow;
+use crate::utils::millis_to_utc;
+
+/// A HIGH `R4` anomaly: Steve (`uuid-1`) picking up 64 diamonds on `server-01` at the epoch. For
+/// tests that only care about a few fields; override those with struct update syntax.
+pub fn anomaly_row() -> AnomalyRow {
+    AnomalyRow {
+        event_time: millis_to_utc(0),
+        server_id: "server-01".to_string(),
+        player_uuid: "uuid-1".to_string(),
+        player_name: "Steve".to_string(),
+        item_id: "minecraft:diamond".to_string(),
+        count: 64,
+        risk_level: "HIGH".to_string(),
+        rule_id: "R4".to_string(),
+        reason: String::new(),
+        evidence_json: "{}".to_string(),
+    }
+}
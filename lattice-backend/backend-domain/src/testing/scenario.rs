@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::entities::{
+    AnomalyRow, CompositeRule, DetectionRule, IngestEvent, KeyItemRule, KeyItemRuleApi,
+    OriginWhitelist, TransferMatching,
+};
+use crate::services::Analyzer;
+
+/// 2026-01-01T00:00:00Z; scenario offsets are relative to it.
+pub const SCENARIO_START_MS: i64 = 1_767_225_600_000;
+pub const SCENARIO_SERVER_ID: &str = "fixture";
+
+/// A fluent event sequence for the analyzer:
+///
+/// ```ignore
+/// use backend_domain::testing::Scenario;
+///
+/// Scenario::new()
+///     .player("steve")
+///     .at_secs(0)
+///     .transfers("minecraft:diamond", 64)
+///     .at_secs(1)
+///     .acquires_without_origin("minecraft:diamond", 64)
+///     .run()
+///     .assert_rules(&["R0"]);
+/// ```
+///
+/// Windows default to the `config.toml` defaults. Every event belongs to the current player and
+/// batch; `next_batch` starts a new `analyze_batch` call on the same analyzer.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    batches: Vec<Vec<IngestEvent>>,
+    rules: HashMap<String, KeyItemRule>,
+    whitelist: OriginWhitelist,
+    risk_overrides: BTreeMap<String, String>,
+    detection_rules: Vec<DetectionRule>,
+    composite_rules: Vec<CompositeRule>,
+    player: String,
+    server_id: String,
+    offset_ms: i64,
+    next_origin: u32,
+    transfer_window_ms: i64,
+    key_item_window_ms: i64,
+    strict_pickup_window_ms: i64,
+    strict_pickup_threshold: i64,
+    strict_transfer_fingerprint: bool,
+    transfer_matching: TransferMatching,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            batches: vec![Vec::new()],
+            rules: HashMap::new(),
+            whitelist: OriginWhitelist::default(),
+            risk_overrides: BTreeMap::new(),
+            detection_rules: Vec::new(),
+            composite_rules: Vec::new(),
+            player: "steve".to_string(),
+            server_id: SCENARIO_SERVER_ID.to_string(),
+            offset_ms: 0,
+            next_origin: 0,
+            transfer_window_ms: 2_000,
+            key_item_window_ms: 600_000,
+            strict_pickup_window_ms: 30_000,
+            strict_pickup_threshold: 256,
+            strict_transfer_fingerprint: false,
+            transfer_matching: TransferMatching::default(),
+        }
+    }
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key item rule with a `HIGH` risk level.
+    pub fn rule(self, item_id: &str, threshold: u64) -> Self {
+        self.rule_with_risk(item_id, threshold, "HIGH")
+    }
+
+    pub fn rule_with_risk(mut self, item_id: &str, threshold: u64, risk_level: &str) -> Self {
+        let rule = KeyItemRuleApi {
+            item_id: item_id.to_string(),
+            threshold,
+            risk_level: risk_level.to_string(),
+            daily_quota: None,
+            unit: None,
+            stack_size: None,
+            unit_value: None,
+        };
+        self.rules
+            .insert(item_id.to_string(), KeyItemRule::from(rule));
+        self
+    }
+
+    /// Forces `risk_level` on every anomaly of `rule_id`, as `rule_risk_overrides` does.
+    pub fn risk_override(mut self, rule_id: &str, risk_level: &str) -> Self {
+        self.risk_overrides
+            .insert(rule_id.to_string(), risk_level.to_string());
+        self
+    }
+
+    /// Adds a key item rule as given, for fields `rule` has no parameter for (e.g. `unit`).
+    pub fn key_item_rule(mut self, rule: KeyItemRule) -> Self {
+        self.rules.insert(rule.item_id.clone(), rule);
+        self
+    }
+
+    /// Adds a user detection rule, as if loaded from `detection_rules_path`.
+    pub fn detection_rule(mut self, rule: DetectionRule) -> Self {
+        self.detection_rules.push(rule);
+        self
+    }
+
+    /// Adds a composite rule, as if loaded from `composite_rules_path`.
+    pub fn composite_rule(mut self, rule: CompositeRule) -> Self {
+        self.composite_rules.push(rule);
+        self
+    }
+
+    /// Turns on `strict_transfer_fingerprint`.
+    pub fn strict_transfer_fingerprint(mut self) -> Self {
+        self.strict_transfer_fingerprint = true;
+        self
+    }
+
+    /// Sets the transfer count tolerance and aggregation.
+    pub fn transfer_matching(mut self, matching: TransferMatching) -> Self {
+        self.transfer_matching = matching;
+        self
+    }
+
+    pub fn origin_whitelist(mut self, whitelist: OriginWhitelist) -> Self {
+        self.whitelist = whitelist;
+        self
+    }
+
+    pub fn transfer_window_secs(mut self, seconds: i64) -> Self {
+        self.transfer_window_ms = seconds * 1000;
+        self
+    }
+
+    pub fn key_item_window_secs(mut self, seconds: i64) -> Self {
+        self.key_item_window_ms = seconds * 1000;
+        self
+    }
+
+    /// `strict_pickup_window_seconds` / `strict_pickup_threshold`; a 0 disables R10.
+    pub fn strict_pickup(mut self, window_seconds: i64, threshold: i64) -> Self {
+        self.strict_pickup_window_ms = window_seconds * 1000;
+        self.strict_pickup_threshold = threshold;
+        self
+    }
+
+    /// Makes `name` the player of the following events; its uuid is derived from the name.
+    pub fn player(mut self, name: &str) -> Self {
+        self.player = name.to_string();
+        self
+    }
+
+    /// Makes `server_id` the server of the following events; it starts as `SCENARIO_SERVER_ID`.
+    pub fn server(mut self, server_id: &str) -> Self {
+        self.server_id = server_id.to_string();
+        self
+    }
+
+    /// Moves the clock to `seconds` after the scenario start.
+    pub fn at_secs(self, seconds: i64) -> Self {
+        self.at_ms(seconds * 1000)
+    }
+
+    pub fn at_ms(mut self, offset_ms: i64) -> Self {
+        self.offset_ms = offset_ms;
+        self
+    }
+
+    /// Following events go to a new `analyze_batch` call.
+    pub fn next_batch(mut self) -> Self {
+        self.batches.push(Vec::new());
+        self
+    }
+
+    /// A `world_pickup` acquisition with a fresh origin id.
+    pub fn picks_up(self, item_id: &str, count: i64) -> Self {
+        self.acquires(item_id, count, "world_pickup")
+    }
+
+    /// An acquisition of `origin_type` with a fresh origin id.
+    pub fn acquires(mut self, item_id: &str, count: i64, origin_type: &str) -> Self {
+        self.next_origin += 1;
+        let origin_id = format!("origin-{}", self.next_origin);
+        self.acquires_from(item_id, count, origin_type, &origin_id)
+    }
+
+    /// An acquisition reusing a given origin id, for the R3 / R5 / R8 reuse rules.
+    pub fn acquires_from(
+        self,
+        item_id: &str,
+        count: i64,
+        origin_type: &str,
+        origin_id: &str,
+    ) -> Self {
+        self.push("ACQUIRE", item_id, count, |event| {
+            event.origin_type = Some(origin_type.to_string());
+            event.origin_id = Some(origin_id.to_string());
+        })
+    }
+
+    pub fn acquires_without_origin(self, item_id: &str, count: i64) -> Self {
+        self.push("ACQUIRE", item_id, count, |_| {})
+    }
+
+    /// An `inventory_audit` acquisition, the gain the mod infers between inventory scans.
+    pub fn audits(self, item_id: &str, count: i64) -> Self {
+        self.acquires(item_id, count, "inventory_audit")
+    }
+
+    /// A transfer out of a chest, matched by a later acquisition of the same count.
+    pub fn transfers(self, item_id: &str, count: i64) -> Self {
+        self.push("TRANSFER", item_id, count, |event| {
+            event.storage_mod = Some("minecraft".to_string());
+            event.storage_id = Some("minecraft:chest@0,64,0".to_string());
+        })
+    }
+
+    pub fn inventory_snapshot(self, item_id: &str, count: i64) -> Self {
+        self.push("INVENTORY_SNAPSHOT", item_id, count, |_| {})
+    }
+
+    pub fn storage_snapshot(self, item_id: &str, count: i64) -> Self {
+        self.push("STORAGE_SNAPSHOT", item_id, count, |event| {
+            event.storage_mod = Some("minecraft".to_string());
+            event.storage_id = Some("minecraft:chest@0,64,0".to_string());
+        })
+    }
+
+    /// Edits the last event, for fields the builder has no step for (e.g. `nbt_hash`).
+    pub fn with(mut self, edit: impl FnOnce(&mut IngestEvent)) -> Self {
+        if let Some(event) = self
+            .batches
+            .iter_mut()
+            .rev()
+            .find_map(|batch| batch.last_mut())
+        {
+            edit(event);
+        }
+        self
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &IngestEvent> {
+        self.batches.iter().flatten()
+    }
+
+    /// Runs every batch through one fresh analyzer, each with the replay clock at its latest
+    /// event.
+    pub fn run(&self) -> Outcome {
+        let mut analyzer = Analyzer::default();
+        analyzer.set_origin_whitelist(self.whitelist.clone());
+        analyzer.set_risk_overrides(self.risk_overrides.clone());
+        analyzer.set_detection_rules(self.detection_rules.clone());
+        analyzer.set_composite_rules(self.composite_rules.clone());
+        analyzer.set_strict_transfer_fingerprint(self.strict_transfer_fingerprint);
+        analyzer.set_transfer_matching(self.transfer_matching);
+        let mut anomalies = Vec::new();
+        for batch in self.batches.iter().filter(|batch| !batch.is_empty()) {
+            let now_ms = batch.iter().map(|event| event.event_time).max();
+            analyzer.set_replay_clock(now_ms.unwrap_or(SCENARIO_START_MS));
+            anomalies.extend(analyzer.analyze_batch(
+                batch,
+                &self.rules,
+                self.transfer_window_ms,
+                self.key_item_window_ms,
+                self.strict_pickup_window_ms,
+                self.strict_pickup_threshold,
+            ));
+        }
+        Outcome { anomalies }
+    }
+
+    fn push(
+        mut self,
+        event_type: &str,
+        item_id: &str,
+        count: i64,
+        edit: impl FnOnce(&mut IngestEvent),
+    ) -> Self {
+        let index = self.events().count() + 1;
+        let mut event = IngestEvent {
+            event_id: format!("evt-{}", index),
+            event_time: SCENARIO_START_MS + self.offset_ms,
+            server_id: Some(self.server_id.clone()),
+            event_type: event_type.to_string(),
+            player_uuid: Some(format!("uuid-{}", self.player)),
+            player_name: Some(self.player.clone()),
+            item_id: item_id.to_string(),
+            count,
+            nbt_hash: None,
+            origin_id: None,
+            origin_type: None,
+            origin_ref: None,
+            source_type: None,
+            source_ref: None,
+            storage_mod: None,
+            storage_id: None,
+            actor_type: Some("player".to_string()),
+            trace_id: Some(format!("trace-{}", index)),
+            item_fingerprint: None,
+            dim: Some("minecraft:overworld".to_string()),
+            x: Some(0),
+            y: Some(64),
+            z: Some(0),
+            family: None,
+            custom_type: None,
+            payload: None,
+            item_name: None,
+            rule_risk_level: None,
+        };
+        edit(&mut event);
+        if let Some(batch) = self.batches.last_mut() {
+            batch.push(event);
+        }
+        self
+    }
+}
+
+/// Anomalies a scenario produced, in the order the analyzer emitted them.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub anomalies: Vec<AnomalyRow>,
+}
+
+impl Outcome {
+    pub fn rule_ids(&self) -> Vec<&str> {
+        self.anomalies
+            .iter()
+            .map(|row| row.rule_id.as_str())
+            .collect()
+    }
+
+    pub fn of_rule(&self, rule_id: &str) -> Vec<&AnomalyRow> {
+        self.anomalies
+            .iter()
+            .filter(|row| row.rule_id == rule_id)
+            .collect()
+    }
+
+    /// Asserts the exact sequence of rule ids, e.g. `&["R1", "R4"]`; `&[]` for a quiet scenario.
+    #[track_caller]
+    pub fn assert_rules(&self, expected: &[&str]) -> &Self {
+        assert_eq!(
+            self.rule_ids(),
+            expected,
+            "rule ids of {:#?}",
+            self.anomalies
+        );
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_fired(&self, rule_id: &str) -> &Self {
+        assert!(
+            !self.of_rule(rule_id).is_empty(),
+            "expected {} among {:?}",
+            rule_id,
+            self.rule_ids()
+        );
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_not_fired(&self, rule_id: &str) -> &Self {
+        assert!(
+            self.of_rule(rule_id).is_empty(),
+            "expected no {} among {:?}",
+            rule_id,
+            self.rule_ids()
+        );
+        self
+    }
+
+    /// Asserts the risk level of every anomaly of `rule_id`.
+    #[track_caller]
+    pub fn assert_risk(&self, rule_id: &str, risk_level: &str) -> &Self {
+        self.assert_fired(rule_id);
+        for row in self.of_rule(rule_id) {
+            assert_eq!(row.risk_level, risk_level, "risk level of {}", rule_id);
+        }
+        self
+    }
+}
@@ -11,6 +11,44 @@ pub fn current_millis() -> i64 {
     OffsetDateTime::now_utc().unix_timestamp_nanos() as i64 / 1_000_000
 }
 
+/// Unix milliseconds at which the client sent a mutating request, see `replay_window_seconds`.
+pub const REPLAY_TIMESTAMP_HEADER: &str = "X-Lattice-Timestamp";
+/// Random per-request value, 16-128 visible ASCII characters.
+pub const REPLAY_NONCE_HEADER: &str = "X-Lattice-Nonce";
+
+/// Replay protection headers for a request the backend itself sends to another instance.
+pub fn replay_headers(now_ms: i64) -> [(&'static str, String); 2] {
+    [
+        (REPLAY_TIMESTAMP_HEADER, now_ms.to_string()),
+        (
+            REPLAY_NONCE_HEADER,
+            uuid::Uuid::new_v4().simple().to_string(),
+        ),
+    ]
+}
+
+/// Compares secrets without returning early at the first differing byte, so response timing
+/// does not reveal how much of a guess was right. Only a length mismatch returns at once.
+pub fn constant_time_eq(expected: &str, presented: &str) -> bool {
+    let expected = expected.as_bytes();
+    let presented = presented.as_bytes();
+    expected.len() == presented.len()
+        && expected
+            .iter()
+            .zip(presented)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// One CSV field, quoted when it holds a comma, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[allow(dead_code)]
 pub fn parse_date(date: &str) -> Result<NaiveDate> {
     NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| anyhow!(err))
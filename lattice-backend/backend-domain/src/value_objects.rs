@@ -1,8 +1,12 @@
 // Domain value objects
+pub mod field_selection;
 pub mod identifiers;
+pub mod mod_version;
 pub mod origin_type;
 pub mod risk_level;
 
+pub use field_selection::*;
 pub use identifiers::*;
+pub use mod_version::*;
 pub use origin_type::*;
 pub use risk_level::*;
@@ -0,0 +1,72 @@
+// Field selection value object
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+/// Fields named in a `fields=` query parameter; an empty selection keeps every field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSelection(BTreeSet<String>);
+
+impl FieldSelection {
+    /// Parses a comma-separated list whose names must all be in `allowed`.
+    pub fn parse(raw: Option<&str>, allowed: &[&str]) -> Result<Self, String> {
+        let mut fields = BTreeSet::new();
+        for field in raw.unwrap_or_default().split(',') {
+            let field = field.trim();
+            if field.is_empty() {
+                continue;
+            }
+            if !allowed.contains(&field) {
+                return Err(format!(
+                    "unknown field {}; allowed: {}",
+                    field,
+                    allowed.join(", ")
+                ));
+            }
+            fields.insert(field.to_string());
+        }
+        Ok(Self(fields))
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn includes(&self, field: &str) -> bool {
+        self.0.is_empty() || self.0.contains(field)
+    }
+
+    /// Drops the unselected keys of a JSON object; other values pass through.
+    pub fn project(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) if !self.is_all() => Value::Object(
+                map.into_iter()
+                    .filter(|(key, _)| self.0.contains(key))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_only_selected_keys() {
+        let allowed = ["id", "count", "reason"];
+        let fields = FieldSelection::parse(Some("id, count,"), &allowed).expect("fields");
+        let row = serde_json::json!({ "id": "a", "count": 3, "reason": "long" });
+        assert_eq!(
+            fields.project(row),
+            serde_json::json!({ "id": "a", "count": 3 })
+        );
+        assert!(!fields.includes("reason"));
+
+        let all = FieldSelection::parse(None, &allowed).expect("fields");
+        assert!(all.is_all() && all.includes("reason"));
+        assert!(FieldSelection::parse(Some("evidence_json"), &allowed).is_err());
+    }
+}
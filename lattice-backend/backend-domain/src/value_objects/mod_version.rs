@@ -0,0 +1,62 @@
+// Mod version value object
+
+use std::cmp::Ordering;
+
+/// Dotted numeric version as reported by the mod; pre-release/build suffixes are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModVersion(Vec<u64>);
+
+impl ModVersion {
+    pub fn parse(value: &str) -> Option<Self> {
+        let trimmed = value.trim();
+        let trimmed = trimmed
+            .strip_prefix('v')
+            .or_else(|| trimmed.strip_prefix('V'))
+            .unwrap_or(trimmed);
+        let core = trimmed.split(['-', '+']).next().unwrap_or_default();
+        if core.is_empty() {
+            return None;
+        }
+        let parts = core
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self(parts))
+    }
+}
+
+impl Ord for ModVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        for index in 0..len {
+            let left = self.0.get(index).copied().unwrap_or(0);
+            let right = other.0.get(index).copied().unwrap_or(0);
+            match left.cmp(&right) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for ModVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_numeric_parts_with_padding() {
+        let parse = |value| ModVersion::parse(value).expect("version");
+        assert!(parse("0.1.3") < parse("0.2.0"));
+        assert!(parse("0.10.0") > parse("0.9.9"));
+        assert_eq!(parse("1.2").cmp(&parse("1.2.0")), Ordering::Equal);
+        assert_eq!(parse("v1.2.0-beta.1"), parse("1.2.0"));
+        assert!(ModVersion::parse("dev").is_none());
+    }
+}
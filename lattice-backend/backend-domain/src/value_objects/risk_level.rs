@@ -2,19 +2,36 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Ordered lowest to highest, so levels compare by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RiskLevel {
     LOW,
     MEDIUM,
     HIGH,
+    CRITICAL,
 }
 
+/// The accepted level names, for validation messages.
+pub const RISK_LEVEL_NAMES: &str = "LOW, MEDIUM, HIGH or CRITICAL";
+
 impl RiskLevel {
     pub fn as_str(&self) -> &'static str {
         match self {
             RiskLevel::LOW => "LOW",
             RiskLevel::MEDIUM => "MEDIUM",
             RiskLevel::HIGH => "HIGH",
+            RiskLevel::CRITICAL => "CRITICAL",
+        }
+    }
+
+    /// Exact upper-case level names only, as stored in anomalies and accepted from rule files.
+    pub fn parse(s: &str) -> Option<RiskLevel> {
+        match s {
+            "LOW" => Some(RiskLevel::LOW),
+            "MEDIUM" => Some(RiskLevel::MEDIUM),
+            "HIGH" => Some(RiskLevel::HIGH),
+            "CRITICAL" => Some(RiskLevel::CRITICAL),
+            _ => None,
         }
     }
 }
@@ -24,7 +41,32 @@ impl From<&str> for RiskLevel {
         match s.to_uppercase().as_str() {
             "LOW" => RiskLevel::LOW,
             "HIGH" => RiskLevel::HIGH,
+            "CRITICAL" => RiskLevel::CRITICAL,
             _ => RiskLevel::MEDIUM,
         }
     }
 }
+
+pub fn is_risk_level(s: &str) -> bool {
+    RiskLevel::parse(s).is_some()
+}
+
+/// Severity order of a stored level; unknown levels rank below `LOW`.
+pub fn risk_rank(s: &str) -> u8 {
+    RiskLevel::parse(s).map_or(0, |level| level as u8 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critical_ranks_above_high() {
+        assert!(RiskLevel::CRITICAL > RiskLevel::HIGH);
+        assert!(risk_rank("CRITICAL") > risk_rank("HIGH"));
+        assert!(risk_rank("LOW") > risk_rank("SEVERE"));
+        assert_eq!(RiskLevel::parse("CRITICAL"), Some(RiskLevel::CRITICAL));
+        assert_eq!(RiskLevel::from("critical"), RiskLevel::CRITICAL);
+        assert!(!is_risk_level("critical"));
+    }
+}
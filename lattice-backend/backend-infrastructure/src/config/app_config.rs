@@ -1,14 +1,21 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::path::Path;
 
 use anyhow::{anyhow, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tracing::warn;
 
-use backend_domain::{DbConfig, RuntimeConfig};
+use crate::services::{parse_mqtt_broker_url, validate_proxy_url, Redactor};
+use backend_domain::{
+    builtin_enricher, is_risk_level, validate_strict_profile, AlertRouting, AlertTeamRoute,
+    ConfigOrigin, DbConfig, ModVersion, RedactionRule, RuntimeConfig, ServerKey, StrictProfile,
+    TimeDisplay, ANALYZER_RULE_IDS, BUILTIN_ENRICHERS, DEFAULT_ALERT_TARGET,
+    DEFAULT_DISPLAY_TIMEZONE, DEFAULT_DISPLAY_TIME_FORMAT, RISK_LEVEL_NAMES,
+};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct AppConfig {
     pub bind_addr: String,
@@ -19,10 +26,19 @@ pub struct AppConfig {
     pub clickhouse_database: String,
     pub clickhouse_user: Option<String>,
     pub clickhouse_password: Option<String>,
+    pub clickhouse_async_insert: bool,
+    pub clickhouse_wait_for_async_insert: bool,
+    pub clickhouse_max_insert_block_size: u64,
+    pub clickhouse_dedup_events: bool,
+    pub shadow_clickhouse_url: String,
+    pub shadow_clickhouse_database: String,
+    pub shadow_clickhouse_user: Option<String>,
+    pub shadow_clickhouse_password: Option<String>,
     pub report_dir: String,
     pub public_base_url: String,
     pub webhook_url: Option<String>,
     pub webhook_template: Option<String>,
+    pub report_webhook_locale: String,
     pub alert_webhook_url: Option<String>,
     pub alert_webhook_template: Option<String>,
     pub alert_webhook_token: Option<String>,
@@ -30,6 +46,10 @@ pub struct AppConfig {
     pub key_items_path: String,
     pub item_registry_path: String,
     pub transfer_window_seconds: u64,
+    pub strict_transfer_fingerprint: bool,
+    pub transfer_count_tolerance: u64,
+    pub transfer_count_tolerance_percent: f64,
+    pub transfer_aggregate: bool,
     pub key_item_window_minutes: u64,
     pub strict_enabled: bool,
     pub strict_pickup_window_seconds: u64,
@@ -38,6 +58,85 @@ pub struct AppConfig {
     pub request_timeout_seconds: u64,
     pub report_hour: u32,
     pub report_minute: u32,
+    pub ingest_stale_after_minutes: u64,
+    pub task_stale_after_seconds: u64,
+    pub heartbeat_interval_seconds: u64,
+    pub heartbeat_missed_threshold: u64,
+    pub min_mod_version: Option<String>,
+    pub mod_version_enforce: bool,
+    pub maintenance_enabled: bool,
+    pub maintenance_hour: u32,
+    pub maintenance_optimize_min_rows: u64,
+    pub storage_alert_threshold_mb: u64,
+    pub response_compression_enabled: bool,
+    pub response_compression_min_bytes: u16,
+    pub response_compression_content_types: Vec<String>,
+    pub alert_group_by_player: bool,
+    pub alert_group_window_seconds: u64,
+    pub anomaly_link_target: String,
+    pub custom_burst_types: Vec<String>,
+    pub custom_burst_threshold: u64,
+    pub custom_burst_window_seconds: u64,
+    pub bind_socket: String,
+    pub tls_cert_path: String,
+    pub tls_key_path: String,
+    pub degraded_cache_size: usize,
+    pub dead_letter_max_events: usize,
+    pub degraded_recovery_seconds: u64,
+    pub clickhouse_watchdog_interval_seconds: u64,
+    pub clickhouse_watchdog_failures: u32,
+    pub strict_profiles: Vec<StrictProfile>,
+    pub daily_quota_alert_enabled: bool,
+    pub report_player_pages: usize,
+    pub report_retention_count: usize,
+    pub report_link_secret: Option<String>,
+    pub report_link_ttl_hours: u64,
+    pub ml_export_secret: Option<String>,
+    pub enrichers: Vec<String>,
+    pub cluster_mode: bool,
+    pub cluster_state_url: String,
+    pub grpc_bind_addr: String,
+    pub mqtt_broker_url: String,
+    pub mqtt_client_id: String,
+    pub mqtt_username: Option<String>,
+    pub mqtt_password: Option<String>,
+    pub mqtt_anomaly_topic: String,
+    pub mqtt_health_topic: String,
+    pub mqtt_ingest_topic: String,
+    pub redaction_rules: Vec<RedactionRule>,
+    pub ingest_record_path: String,
+    pub ingest_record_sample_rate: f64,
+    pub ingest_record_max_mb: u64,
+    pub alert_team_routes: Vec<AlertTeamRoute>,
+    pub config_change_alert_enabled: bool,
+    pub slow_rule_budget_ms: u64,
+    pub rule_hygiene_report_day: u32,
+    pub origin_learning_days: u32,
+    pub server_keys: Vec<ServerKey>,
+    pub server_identity_required: bool,
+    pub alert_max_lines: usize,
+    pub alert_page_ttl_minutes: u64,
+    pub rule_risk_overrides: BTreeMap<String, String>,
+    pub alert_webhook_fallback_url: Option<String>,
+    pub meta_alert_spike_multiple: f64,
+    pub meta_alert_baseline_minutes: u64,
+    pub meta_alert_zero_ingest: bool,
+    pub alert_webhook_proxy: Option<String>,
+    pub display_timezone: String,
+    pub display_time_format: String,
+    pub detection_rules_path: String,
+    pub composite_rules_path: String,
+    pub ingest_dedup_capacity: usize,
+    pub alert_server_groups: BTreeMap<String, i64>,
+    pub ingest_rate_limit_per_second: f64,
+    pub ingest_rate_limit_burst: u64,
+    pub trusted_proxies: Vec<String>,
+    pub replay_window_seconds: u64,
+    pub alert_routing: AlertRouting,
+    #[serde(skip)]
+    pub config_path: Option<String>,
+    #[serde(skip)]
+    pub origins: BTreeMap<String, ConfigOrigin>,
 }
 
 impl Default for AppConfig {
@@ -51,10 +150,19 @@ impl Default for AppConfig {
             clickhouse_database: "lattice".to_string(),
             clickhouse_user: None,
             clickhouse_password: None,
+            clickhouse_async_insert: false,
+            clickhouse_wait_for_async_insert: true,
+            clickhouse_max_insert_block_size: 0,
+            clickhouse_dedup_events: false,
+            shadow_clickhouse_url: String::new(),
+            shadow_clickhouse_database: String::new(),
+            shadow_clickhouse_user: None,
+            shadow_clickhouse_password: None,
             report_dir: "./reports".to_string(),
             public_base_url: "http://127.0.0.1:3234".to_string(),
             webhook_url: None,
             webhook_template: None,
+            report_webhook_locale: "zh-CN".to_string(),
             alert_webhook_url: None,
             alert_webhook_template: None,
             alert_webhook_token: None,
@@ -62,6 +170,10 @@ impl Default for AppConfig {
             key_items_path: "./key_items.yaml".to_string(),
             item_registry_path: "./item_registry.json".to_string(),
             transfer_window_seconds: 2,
+            strict_transfer_fingerprint: false,
+            transfer_count_tolerance: 0,
+            transfer_count_tolerance_percent: 0.0,
+            transfer_aggregate: false,
             key_item_window_minutes: 10,
             strict_enabled: false,
             strict_pickup_window_seconds: 30,
@@ -70,18 +182,112 @@ impl Default for AppConfig {
             request_timeout_seconds: 15,
             report_hour: 0,
             report_minute: 5,
+            ingest_stale_after_minutes: 30,
+            task_stale_after_seconds: 600,
+            heartbeat_interval_seconds: 60,
+            heartbeat_missed_threshold: 3,
+            min_mod_version: None,
+            mod_version_enforce: false,
+            maintenance_enabled: true,
+            maintenance_hour: 4,
+            maintenance_optimize_min_rows: 1_000_000,
+            storage_alert_threshold_mb: 0,
+            response_compression_enabled: true,
+            response_compression_min_bytes: 1024,
+            response_compression_content_types: vec![
+                "application/json".to_string(),
+                "text/".to_string(),
+            ],
+            alert_group_by_player: true,
+            alert_group_window_seconds: 30,
+            anomaly_link_target: "desktop".to_string(),
+            custom_burst_types: Vec::new(),
+            custom_burst_threshold: 200,
+            custom_burst_window_seconds: 60,
+            bind_socket: String::new(),
+            tls_cert_path: String::new(),
+            tls_key_path: String::new(),
+            degraded_cache_size: 2000,
+            dead_letter_max_events: 100_000,
+            degraded_recovery_seconds: 60,
+            clickhouse_watchdog_interval_seconds: 15,
+            clickhouse_watchdog_failures: 3,
+            strict_profiles: Vec::new(),
+            daily_quota_alert_enabled: true,
+            report_player_pages: 10,
+            report_retention_count: 90,
+            report_link_secret: None,
+            report_link_ttl_hours: 168,
+            ml_export_secret: None,
+            enrichers: BUILTIN_ENRICHERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            cluster_mode: false,
+            cluster_state_url: String::new(),
+            grpc_bind_addr: String::new(),
+            mqtt_broker_url: String::new(),
+            mqtt_client_id: "lattice-backend".to_string(),
+            mqtt_username: None,
+            mqtt_password: None,
+            mqtt_anomaly_topic: "lattice/anomalies".to_string(),
+            mqtt_health_topic: "lattice/health".to_string(),
+            mqtt_ingest_topic: String::new(),
+            redaction_rules: Vec::new(),
+            ingest_record_path: String::new(),
+            ingest_record_sample_rate: 1.0,
+            ingest_record_max_mb: 100,
+            alert_team_routes: Vec::new(),
+            config_change_alert_enabled: true,
+            slow_rule_budget_ms: 250,
+            rule_hygiene_report_day: 1,
+            origin_learning_days: 7,
+            server_keys: Vec::new(),
+            server_identity_required: false,
+            alert_max_lines: 8,
+            alert_page_ttl_minutes: 30,
+            rule_risk_overrides: BTreeMap::new(),
+            alert_webhook_fallback_url: None,
+            meta_alert_spike_multiple: 5.0,
+            meta_alert_baseline_minutes: 60,
+            meta_alert_zero_ingest: true,
+            alert_webhook_proxy: None,
+            display_timezone: DEFAULT_DISPLAY_TIMEZONE.to_string(),
+            display_time_format: DEFAULT_DISPLAY_TIME_FORMAT.to_string(),
+            detection_rules_path: "./detection_rules.yaml".to_string(),
+            composite_rules_path: "./composite_rules.yaml".to_string(),
+            ingest_dedup_capacity: 100_000,
+            alert_server_groups: BTreeMap::new(),
+            ingest_rate_limit_per_second: 0.0,
+            ingest_rate_limit_burst: 20,
+            trusted_proxies: Vec::new(),
+            replay_window_seconds: 0,
+            alert_routing: AlertRouting::default(),
+            config_path: None,
+            origins: BTreeMap::new(),
         }
     }
 }
 
 impl AppConfig {
+    /// Loads the file named by `LATTICE_CONFIG` (default `./config.toml`).
     pub async fn load() -> Result<Self> {
         let path = env::var("LATTICE_CONFIG").unwrap_or_else(|_| "./config.toml".to_string());
-        let file_path = Path::new(&path);
+        Self::load_from(path).await
+    }
+
+    /// Loads an explicit config file without consulting `LATTICE_CONFIG`; a missing file falls back
+    /// to defaults. `LATTICE_*` value overrides still apply.
+    pub async fn load_from(path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = path.as_ref();
+        let path = file_path.to_string_lossy().to_string();
         let base_dir = file_path.parent();
         if !file_path.exists() {
             warn!("config.toml not found, using defaults");
-            let mut config = AppConfig::default();
+            let mut config = AppConfig {
+                origins: resolve_origins(&toml::Table::new()),
+                ..AppConfig::default()
+            };
             config.apply_env_overrides();
             config.resolve_paths(base_dir);
             config.normalize();
@@ -90,6 +296,8 @@ impl AppConfig {
         }
         let content = fs::read_to_string(file_path).await?;
         let mut config: AppConfig = toml::from_str(&content)?;
+        config.config_path = Some(path.clone());
+        config.origins = resolve_origins(&toml::from_str::<toml::Table>(&content)?);
         config.apply_env_overrides();
         config.resolve_paths(base_dir);
         config.normalize();
@@ -113,6 +321,18 @@ impl AppConfig {
                 self.clickhouse_password = None;
             }
         }
+        self.shadow_clickhouse_url = self.shadow_clickhouse_url.trim().to_string();
+        self.shadow_clickhouse_database = self.shadow_clickhouse_database.trim().to_string();
+        if let Some(user) = &self.shadow_clickhouse_user {
+            if user.trim().is_empty() {
+                self.shadow_clickhouse_user = None;
+            }
+        }
+        if let Some(password) = &self.shadow_clickhouse_password {
+            if password.trim().is_empty() {
+                self.shadow_clickhouse_password = None;
+            }
+        }
         if let Some(webhook_url) = &self.webhook_url {
             if webhook_url.trim().is_empty() {
                 self.webhook_url = None;
@@ -128,6 +348,16 @@ impl AppConfig {
                 self.alert_webhook_url = None;
             }
         }
+        self.alert_webhook_fallback_url = self
+            .alert_webhook_fallback_url
+            .take()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        self.alert_webhook_proxy = self
+            .alert_webhook_proxy
+            .take()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
         if let Some(template) = &self.alert_webhook_template {
             if template.trim().is_empty() {
                 self.alert_webhook_template = None;
@@ -143,11 +373,209 @@ impl AppConfig {
                 self.alert_group_id = None;
             }
         }
+        if let Some(version) = &self.min_mod_version {
+            if version.trim().is_empty() {
+                self.min_mod_version = None;
+            } else {
+                self.min_mod_version = Some(version.trim().to_string());
+            }
+        }
+        self.response_compression_content_types = normalize_id_list(
+            std::mem::take(&mut self.response_compression_content_types)
+                .into_iter()
+                .map(|item| item.to_ascii_lowercase())
+                .collect(),
+        );
+        self.anomaly_link_target = self.anomaly_link_target.trim().to_ascii_lowercase();
+        self.report_webhook_locale = self.report_webhook_locale.trim().to_string();
+        self.bind_socket = self.bind_socket.trim().to_string();
+        self.tls_cert_path = self.tls_cert_path.trim().to_string();
+        self.tls_key_path = self.tls_key_path.trim().to_string();
+        for profile in &mut self.strict_profiles {
+            profile.name = profile.name.trim().to_string();
+        }
+        self.custom_burst_types = normalize_id_list(
+            std::mem::take(&mut self.custom_burst_types)
+                .into_iter()
+                .map(|item| item.to_ascii_lowercase())
+                .collect(),
+        );
+        // Order matters for enrichers, so duplicates are dropped without sorting.
+        let mut enrichers: Vec<String> = Vec::new();
+        for name in std::mem::take(&mut self.enrichers) {
+            let name = name.trim().to_ascii_lowercase();
+            if !name.is_empty() && !enrichers.contains(&name) {
+                enrichers.push(name);
+            }
+        }
+        self.enrichers = enrichers;
+        self.cluster_state_url = self
+            .cluster_state_url
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        self.grpc_bind_addr = self.grpc_bind_addr.trim().to_string();
+        self.mqtt_broker_url = self.mqtt_broker_url.trim().to_string();
+        self.mqtt_client_id = self.mqtt_client_id.trim().to_string();
+        if let Some(username) = &self.mqtt_username {
+            if username.trim().is_empty() {
+                self.mqtt_username = None;
+            }
+        }
+        if let Some(password) = &self.mqtt_password {
+            if password.is_empty() {
+                self.mqtt_password = None;
+            }
+        }
+        if let Some(secret) = &self.report_link_secret {
+            if secret.trim().is_empty() {
+                self.report_link_secret = None;
+            }
+        }
+        if let Some(secret) = &self.ml_export_secret {
+            if secret.trim().is_empty() {
+                self.ml_export_secret = None;
+            }
+        }
+        self.mqtt_anomaly_topic = self.mqtt_anomaly_topic.trim().to_string();
+        self.mqtt_health_topic = self.mqtt_health_topic.trim().to_string();
+        self.mqtt_ingest_topic = self.mqtt_ingest_topic.trim().to_string();
+        self.ingest_record_path = self.ingest_record_path.trim().to_string();
+        for rule in &mut self.redaction_rules {
+            rule.field = rule
+                .field
+                .take()
+                .map(|field| field.trim().to_string())
+                .filter(|field| !field.is_empty());
+            rule.pattern = rule.pattern.take().filter(|pattern| !pattern.is_empty());
+            rule.targets = normalize_id_list(
+                std::mem::take(&mut rule.targets)
+                    .into_iter()
+                    .map(|target| target.to_ascii_lowercase())
+                    .collect(),
+            );
+        }
+        for server_key in &mut self.server_keys {
+            server_key.server_id = server_key.server_id.trim().to_lowercase();
+            server_key.key = server_key.key.trim().to_string();
+        }
+        for route in &mut self.alert_team_routes {
+            route.team = route.team.trim().to_string();
+            route.webhook_url = route
+                .webhook_url
+                .take()
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty());
+            route.proxy = route
+                .proxy
+                .take()
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty());
+        }
+        self.rule_risk_overrides = std::mem::take(&mut self.rule_risk_overrides)
+            .into_iter()
+            .map(|(rule_id, level)| (rule_id.trim().to_uppercase(), level.trim().to_uppercase()))
+            .collect();
+        self.alert_server_groups = std::mem::take(&mut self.alert_server_groups)
+            .into_iter()
+            .map(|(server_id, group_id)| (server_id.trim().to_lowercase(), group_id))
+            .collect();
+        self.alert_routing.targets = std::mem::take(&mut self.alert_routing.targets)
+            .into_iter()
+            .map(|(name, mut target)| {
+                target.webhook_url = target
+                    .webhook_url
+                    .take()
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty());
+                target.proxy = target
+                    .proxy
+                    .take()
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty());
+                (name.trim().to_lowercase(), target)
+            })
+            .collect();
+        for route in &mut self.alert_routing.routes {
+            let upper = |values: Vec<String>| {
+                normalize_id_list(values.into_iter().map(|id| id.to_uppercase()).collect())
+            };
+            route.rule_ids = upper(std::mem::take(&mut route.rule_ids));
+            route.risk_levels = upper(std::mem::take(&mut route.risk_levels));
+            route.targets = normalize_id_list(
+                std::mem::take(&mut route.targets)
+                    .into_iter()
+                    .map(|target| target.to_lowercase())
+                    .collect(),
+            );
+        }
+        self.trusted_proxies = normalize_id_list(std::mem::take(&mut self.trusted_proxies));
         self.op_token_admin_ids = normalize_id_list(std::mem::take(&mut self.op_token_admin_ids));
         self.op_token_allowed_group_ids =
             normalize_id_list(std::mem::take(&mut self.op_token_allowed_group_ids));
     }
 
+    fn validate_alert_routing(&self) -> Result<()> {
+        let routing = &self.alert_routing;
+        for (name, target) in &routing.targets {
+            if name.is_empty() || name == DEFAULT_ALERT_TARGET {
+                return Err(anyhow!(
+                    "alert_routing.targets: {:?} is not a usable target name",
+                    name
+                ));
+            }
+            if target.group_id.is_none() && target.webhook_url.is_none() {
+                return Err(anyhow!(
+                    "alert routing target {} needs a group_id or a webhook_url",
+                    name
+                ));
+            }
+            if target.group_id.is_some_and(|group_id| group_id <= 0) {
+                return Err(anyhow!(
+                    "alert routing target {} group_id must be positive",
+                    name
+                ));
+            }
+            if let Some(proxy) = &target.proxy {
+                validate_proxy_url(proxy)
+                    .map_err(|err| anyhow!("alert routing target {} proxy: {}", name, err))?;
+            }
+        }
+        for (index, route) in routing.routes.iter().enumerate() {
+            let entry = index + 1;
+            if route.rule_ids.is_empty() && route.risk_levels.is_empty() {
+                return Err(anyhow!(
+                    "alert_routing.routes entry {} needs rule_ids or risk_levels",
+                    entry
+                ));
+            }
+            if let Some(level) = route.risk_levels.iter().find(|level| !is_risk_level(level)) {
+                return Err(anyhow!(
+                    "alert_routing.routes entry {}: risk level {} must be {}",
+                    entry,
+                    level,
+                    RISK_LEVEL_NAMES
+                ));
+            }
+            if route.targets.is_empty() {
+                return Err(anyhow!(
+                    "alert_routing.routes entry {} has no targets",
+                    entry
+                ));
+            }
+            if let Some(target) = route.targets.iter().find(|target| {
+                target.as_str() != DEFAULT_ALERT_TARGET && !routing.targets.contains_key(*target)
+            }) {
+                return Err(anyhow!(
+                    "alert_routing.routes entry {}: unknown target {}",
+                    entry,
+                    target
+                ));
+            }
+        }
+        Ok(())
+    }
+
     fn resolve_paths(&mut self, base_dir: Option<&Path>) {
         let Some(base) = base_dir else {
             return;
@@ -155,6 +583,8 @@ impl AppConfig {
         self.report_dir = resolve_path(base, &self.report_dir);
         self.key_items_path = resolve_path(base, &self.key_items_path);
         self.item_registry_path = resolve_path(base, &self.item_registry_path);
+        self.detection_rules_path = resolve_path(base, &self.detection_rules_path);
+        self.composite_rules_path = resolve_path(base, &self.composite_rules_path);
     }
 
     pub fn validate(&self) -> Result<()> {
@@ -170,6 +600,250 @@ impl AppConfig {
         if self.report_hour > 23 || self.report_minute > 59 {
             return Err(anyhow!("report_hour or report_minute out of range"));
         }
+        if self.rule_hygiene_report_day > 28 {
+            return Err(anyhow!("rule_hygiene_report_day must be between 0 and 28"));
+        }
+        if self.origin_learning_days == 0 || self.origin_learning_days > 90 {
+            return Err(anyhow!("origin_learning_days must be between 1 and 90"));
+        }
+        if !(1..=50).contains(&self.alert_max_lines) {
+            return Err(anyhow!("alert_max_lines must be between 1 and 50"));
+        }
+        for (rule_id, level) in &self.rule_risk_overrides {
+            if !ANALYZER_RULE_IDS.contains(&rule_id.as_str()) {
+                return Err(anyhow!(
+                    "rule_risk_overrides: {} is not an analyzer rule (R0-R12)",
+                    rule_id
+                ));
+            }
+            if !is_risk_level(level) {
+                return Err(anyhow!(
+                    "rule_risk_overrides: {} must be {}",
+                    rule_id,
+                    RISK_LEVEL_NAMES
+                ));
+            }
+        }
+        if self.alert_webhook_fallback_url.is_some()
+            && self.alert_webhook_fallback_url == self.alert_webhook_url
+        {
+            return Err(anyhow!(
+                "alert_webhook_fallback_url must differ from alert_webhook_url"
+            ));
+        }
+        if !self.meta_alert_spike_multiple.is_finite() || self.meta_alert_spike_multiple < 0.0 {
+            anyhow::bail!("meta_alert_spike_multiple must be a non-negative number");
+        }
+        if !(0.0..=100.0).contains(&self.transfer_count_tolerance_percent) {
+            anyhow::bail!("transfer_count_tolerance_percent must be between 0 and 100");
+        }
+        if !self.ingest_rate_limit_per_second.is_finite() || self.ingest_rate_limit_per_second < 0.0
+        {
+            anyhow::bail!("ingest_rate_limit_per_second must be a non-negative number");
+        }
+        if self.ingest_rate_limit_per_second > 0.0 && self.ingest_rate_limit_burst == 0 {
+            anyhow::bail!("ingest_rate_limit_burst must be at least 1");
+        }
+        if let Some(proxy) = self
+            .trusted_proxies
+            .iter()
+            .find(|proxy| proxy.parse::<std::net::IpAddr>().is_err())
+        {
+            return Err(anyhow!("trusted_proxies: {} is not an IP address", proxy));
+        }
+        if self.replay_window_seconds > 3600 {
+            anyhow::bail!("replay_window_seconds must be at most 3600");
+        }
+        if self.meta_alert_baseline_minutes == 0 || self.meta_alert_baseline_minutes > 1440 {
+            anyhow::bail!("meta_alert_baseline_minutes must be between 1 and 1440");
+        }
+        if let Some(proxy) = &self.alert_webhook_proxy {
+            validate_proxy_url(proxy).map_err(|err| anyhow!("alert_webhook_proxy: {}", err))?;
+        }
+        TimeDisplay::new(&self.display_timezone, &self.display_time_format)
+            .map_err(|err| anyhow!("display_timezone / display_time_format: {}", err))?;
+        for (server_id, group_id) in &self.alert_server_groups {
+            if server_id.is_empty() || *group_id <= 0 {
+                return Err(anyhow!(
+                    "alert_server_groups: {:?} needs a server_id and a positive group id",
+                    server_id
+                ));
+            }
+        }
+        if let Some(version) = &self.min_mod_version {
+            if ModVersion::parse(version).is_none() {
+                return Err(anyhow!("invalid min_mod_version: {}", version));
+            }
+        }
+        if self.maintenance_hour > 23 {
+            return Err(anyhow!("maintenance_hour out of range"));
+        }
+        if self.heartbeat_interval_seconds == 0 {
+            return Err(anyhow!("heartbeat_interval_seconds must be greater than 0"));
+        }
+        if self.alert_group_window_seconds > 600 {
+            return Err(anyhow!("alert_group_window_seconds must be at most 600"));
+        }
+        if !matches!(self.anomaly_link_target.as_str(), "desktop" | "web" | "off") {
+            return Err(anyhow!(
+                "anomaly_link_target must be one of desktop, web, off"
+            ));
+        }
+        if !matches!(self.report_webhook_locale.as_str(), "zh-CN" | "en") {
+            return Err(anyhow!("report_webhook_locale must be one of zh-CN, en"));
+        }
+        if !self.custom_burst_types.is_empty()
+            && (self.custom_burst_threshold == 0 || self.custom_burst_window_seconds == 0)
+        {
+            return Err(anyhow!(
+                "custom_burst_threshold and custom_burst_window_seconds must be greater than 0"
+            ));
+        }
+        if cfg!(windows)
+            && !self.bind_socket.is_empty()
+            && !self.bind_socket.starts_with(r"\\.\pipe\")
+        {
+            return Err(anyhow!(
+                r"bind_socket must be a named pipe like \\.\pipe\lattice"
+            ));
+        }
+        if self.tls_cert_path.is_empty() != self.tls_key_path.is_empty() {
+            return Err(anyhow!(
+                "tls_cert_path and tls_key_path must be set together"
+            ));
+        }
+        if self.clickhouse_watchdog_failures == 0 {
+            return Err(anyhow!(
+                "clickhouse_watchdog_failures must be greater than 0"
+            ));
+        }
+        for (index, profile) in self.strict_profiles.iter().enumerate() {
+            validate_strict_profile(profile).map_err(|err| anyhow!(err))?;
+            if self.strict_profiles[..index]
+                .iter()
+                .any(|other| other.name == profile.name)
+            {
+                return Err(anyhow!("duplicate strict profile name: {}", profile.name));
+            }
+        }
+        for name in &self.enrichers {
+            builtin_enricher(name).map_err(|err| anyhow!(err))?;
+        }
+        if self.report_player_pages > 200 {
+            return Err(anyhow!("report_player_pages must be at most 200"));
+        }
+        if self
+            .report_link_secret
+            .as_ref()
+            .is_some_and(|secret| secret.trim().len() < 16)
+        {
+            return Err(anyhow!("report_link_secret must be at least 16 characters"));
+        }
+        if self.report_link_ttl_hours == 0 {
+            return Err(anyhow!("report_link_ttl_hours must be greater than 0"));
+        }
+        if self
+            .ml_export_secret
+            .as_ref()
+            .is_some_and(|secret| secret.trim().len() < 16)
+        {
+            return Err(anyhow!("ml_export_secret must be at least 16 characters"));
+        }
+        if !self.cluster_state_url.is_empty()
+            && !self.cluster_state_url.starts_with("http://")
+            && !self.cluster_state_url.starts_with("https://")
+        {
+            return Err(anyhow!("cluster_state_url must be an http(s) URL"));
+        }
+        if let Some(shadow) = self.to_shadow_db_config() {
+            if !shadow.clickhouse_url.starts_with("http://")
+                && !shadow.clickhouse_url.starts_with("https://")
+            {
+                return Err(anyhow!("shadow_clickhouse_url must be an http(s) URL"));
+            }
+            let same_server = shadow.clickhouse_url.trim_end_matches('/')
+                == self.clickhouse_url.trim_end_matches('/');
+            if same_server && shadow.clickhouse_database == self.clickhouse_database {
+                return Err(anyhow!(
+                    "shadow_clickhouse_url must point at another database than clickhouse_url"
+                ));
+            }
+        }
+        if !self.grpc_bind_addr.is_empty()
+            && self.grpc_bind_addr.parse::<std::net::SocketAddr>().is_err()
+        {
+            return Err(anyhow!("grpc_bind_addr must be host:port"));
+        }
+        Redactor::new(&self.redaction_rules).map_err(|err| anyhow!(err))?;
+        if !self.ingest_record_path.is_empty() {
+            if !(0.0..=1.0).contains(&self.ingest_record_sample_rate) {
+                return Err(anyhow!("ingest_record_sample_rate must be between 0 and 1"));
+            }
+            if self.ingest_record_max_mb == 0 {
+                return Err(anyhow!("ingest_record_max_mb must be greater than 0"));
+            }
+        }
+        for (index, server_key) in self.server_keys.iter().enumerate() {
+            if server_key.server_id.is_empty() || server_key.key.is_empty() {
+                return Err(anyhow!(
+                    "server_keys entry {} needs a server_id and a key",
+                    index + 1
+                ));
+            }
+            if self.server_keys[..index].iter().any(|other| {
+                other.server_id == server_key.server_id || other.key == server_key.key
+            }) {
+                return Err(anyhow!(
+                    "duplicate server_id or key in server_keys: {}",
+                    server_key.server_id
+                ));
+            }
+        }
+        if self.server_identity_required && self.server_keys.is_empty() {
+            return Err(anyhow!("server_identity_required needs server_keys"));
+        }
+        for (index, route) in self.alert_team_routes.iter().enumerate() {
+            if route.team.is_empty() {
+                return Err(anyhow!("alert_team_routes entry {} has no team", index + 1));
+            }
+            if self.alert_team_routes[..index]
+                .iter()
+                .any(|other| other.team.eq_ignore_ascii_case(&route.team))
+            {
+                return Err(anyhow!("duplicate alert team route: {}", route.team));
+            }
+            if route.group_id.is_none() && route.webhook_url.is_none() {
+                return Err(anyhow!(
+                    "alert team route {} needs a group_id or a webhook_url",
+                    route.team
+                ));
+            }
+            if route.group_id.is_some_and(|group_id| group_id <= 0) {
+                return Err(anyhow!(
+                    "alert team route {} group_id must be positive",
+                    route.team
+                ));
+            }
+            if let Some(proxy) = &route.proxy {
+                validate_proxy_url(proxy)
+                    .map_err(|err| anyhow!("alert team route {} proxy: {}", route.team, err))?;
+            }
+        }
+        self.validate_alert_routing()?;
+        if !self.mqtt_broker_url.is_empty() {
+            parse_mqtt_broker_url(&self.mqtt_broker_url).map_err(|err| anyhow!(err))?;
+            if self.mqtt_client_id.is_empty() {
+                return Err(anyhow!("mqtt_client_id must not be empty"));
+            }
+            for (key, topic) in [
+                ("mqtt_anomaly_topic", &self.mqtt_anomaly_topic),
+                ("mqtt_health_topic", &self.mqtt_health_topic),
+            ] {
+                if topic.contains(['+', '#']) {
+                    return Err(anyhow!("{} must not contain wildcards", key));
+                }
+            }
+        }
         Ok(())
     }
 
@@ -183,6 +857,7 @@ impl AppConfig {
             public_base_url: self.public_base_url.clone(),
             webhook_url: self.webhook_url.clone(),
             webhook_template: self.webhook_template.clone(),
+            report_webhook_locale: self.report_webhook_locale.clone(),
             alert_webhook_url: self.alert_webhook_url.clone(),
             alert_webhook_template: self.alert_webhook_template.clone(),
             alert_webhook_token: self.alert_webhook_token.clone(),
@@ -190,6 +865,10 @@ impl AppConfig {
             key_items_path: self.key_items_path.clone(),
             item_registry_path: self.item_registry_path.clone(),
             transfer_window_seconds: self.transfer_window_seconds,
+            strict_transfer_fingerprint: self.strict_transfer_fingerprint,
+            transfer_count_tolerance: self.transfer_count_tolerance,
+            transfer_count_tolerance_percent: self.transfer_count_tolerance_percent,
+            transfer_aggregate: self.transfer_aggregate,
             key_item_window_minutes: self.key_item_window_minutes,
             strict_enabled: self.strict_enabled,
             strict_pickup_window_seconds: self.strict_pickup_window_seconds,
@@ -198,6 +877,83 @@ impl AppConfig {
             request_timeout_seconds: self.request_timeout_seconds,
             report_hour: self.report_hour,
             report_minute: self.report_minute,
+            ingest_stale_after_minutes: self.ingest_stale_after_minutes,
+            task_stale_after_seconds: self.task_stale_after_seconds,
+            heartbeat_interval_seconds: self.heartbeat_interval_seconds,
+            heartbeat_missed_threshold: self.heartbeat_missed_threshold,
+            min_mod_version: self.min_mod_version.clone(),
+            mod_version_enforce: self.mod_version_enforce,
+            maintenance_enabled: self.maintenance_enabled,
+            maintenance_hour: self.maintenance_hour,
+            maintenance_optimize_min_rows: self.maintenance_optimize_min_rows,
+            storage_alert_threshold_mb: self.storage_alert_threshold_mb,
+            response_compression_enabled: self.response_compression_enabled,
+            response_compression_min_bytes: self.response_compression_min_bytes,
+            response_compression_content_types: self.response_compression_content_types.clone(),
+            alert_group_by_player: self.alert_group_by_player,
+            alert_group_window_seconds: self.alert_group_window_seconds,
+            anomaly_link_target: self.anomaly_link_target.clone(),
+            custom_burst_types: self.custom_burst_types.clone(),
+            custom_burst_threshold: self.custom_burst_threshold,
+            custom_burst_window_seconds: self.custom_burst_window_seconds,
+            bind_socket: self.bind_socket.clone(),
+            tls_cert_path: self.tls_cert_path.clone(),
+            tls_key_path: self.tls_key_path.clone(),
+            degraded_cache_size: self.degraded_cache_size,
+            dead_letter_max_events: self.dead_letter_max_events,
+            degraded_recovery_seconds: self.degraded_recovery_seconds,
+            clickhouse_watchdog_interval_seconds: self.clickhouse_watchdog_interval_seconds,
+            clickhouse_watchdog_failures: self.clickhouse_watchdog_failures,
+            strict_profiles: self.strict_profiles.clone(),
+            daily_quota_alert_enabled: self.daily_quota_alert_enabled,
+            report_player_pages: self.report_player_pages,
+            report_retention_count: self.report_retention_count,
+            report_link_secret: self.report_link_secret.clone(),
+            report_link_ttl_hours: self.report_link_ttl_hours,
+            ml_export_secret: self.ml_export_secret.clone(),
+            enrichers: self.enrichers.clone(),
+            cluster_mode: self.cluster_mode,
+            cluster_state_url: self.cluster_state_url.clone(),
+            grpc_bind_addr: self.grpc_bind_addr.clone(),
+            mqtt_broker_url: self.mqtt_broker_url.clone(),
+            mqtt_client_id: self.mqtt_client_id.clone(),
+            mqtt_username: self.mqtt_username.clone(),
+            mqtt_password: self.mqtt_password.clone(),
+            mqtt_anomaly_topic: self.mqtt_anomaly_topic.clone(),
+            mqtt_health_topic: self.mqtt_health_topic.clone(),
+            mqtt_ingest_topic: self.mqtt_ingest_topic.clone(),
+            redaction_rules: self.redaction_rules.clone(),
+            ingest_record_path: self.ingest_record_path.clone(),
+            ingest_record_sample_rate: self.ingest_record_sample_rate,
+            ingest_record_max_mb: self.ingest_record_max_mb,
+            alert_team_routes: self.alert_team_routes.clone(),
+            config_change_alert_enabled: self.config_change_alert_enabled,
+            slow_rule_budget_ms: self.slow_rule_budget_ms,
+            rule_hygiene_report_day: self.rule_hygiene_report_day,
+            origin_learning_days: self.origin_learning_days,
+            server_keys: self.server_keys.clone(),
+            server_identity_required: self.server_identity_required,
+            alert_max_lines: self.alert_max_lines,
+            alert_page_ttl_minutes: self.alert_page_ttl_minutes,
+            rule_risk_overrides: self.rule_risk_overrides.clone(),
+            alert_webhook_fallback_url: self.alert_webhook_fallback_url.clone(),
+            meta_alert_spike_multiple: self.meta_alert_spike_multiple,
+            meta_alert_baseline_minutes: self.meta_alert_baseline_minutes,
+            meta_alert_zero_ingest: self.meta_alert_zero_ingest,
+            alert_webhook_proxy: self.alert_webhook_proxy.clone(),
+            display_timezone: self.display_timezone.clone(),
+            display_time_format: self.display_time_format.clone(),
+            detection_rules_path: self.detection_rules_path.clone(),
+            composite_rules_path: self.composite_rules_path.clone(),
+            ingest_dedup_capacity: self.ingest_dedup_capacity,
+            alert_server_groups: self.alert_server_groups.clone(),
+            ingest_rate_limit_per_second: self.ingest_rate_limit_per_second,
+            ingest_rate_limit_burst: self.ingest_rate_limit_burst,
+            trusted_proxies: self.trusted_proxies.clone(),
+            replay_window_seconds: self.replay_window_seconds,
+            alert_routing: self.alert_routing.clone(),
+            config_path: self.config_path.clone(),
+            config_origins: self.origins.clone(),
         }
     }
 
@@ -207,7 +963,31 @@ impl AppConfig {
             clickhouse_database: self.clickhouse_database.clone(),
             clickhouse_user: self.clickhouse_user.clone(),
             clickhouse_password: self.clickhouse_password.clone(),
+            clickhouse_async_insert: self.clickhouse_async_insert,
+            clickhouse_wait_for_async_insert: self.clickhouse_wait_for_async_insert,
+            clickhouse_max_insert_block_size: self.clickhouse_max_insert_block_size,
+            clickhouse_dedup_events: self.clickhouse_dedup_events,
+        }
+    }
+
+    /// The dual-write target of a ClickHouse migration; insert settings and the table engine
+    /// follow the primary. `None` unless `shadow_clickhouse_url` is set.
+    pub fn to_shadow_db_config(&self) -> Option<DbConfig> {
+        if self.shadow_clickhouse_url.is_empty() {
+            return None;
         }
+        let database = if self.shadow_clickhouse_database.is_empty() {
+            self.clickhouse_database.clone()
+        } else {
+            self.shadow_clickhouse_database.clone()
+        };
+        Some(DbConfig {
+            clickhouse_url: self.shadow_clickhouse_url.clone(),
+            clickhouse_database: database,
+            clickhouse_user: self.shadow_clickhouse_user.clone(),
+            clickhouse_password: self.shadow_clickhouse_password.clone(),
+            ..self.to_db_config()
+        })
     }
 
     fn apply_env_overrides(&mut self) {
@@ -235,6 +1015,34 @@ impl AppConfig {
         if let Ok(value) = env::var("LATTICE_CLICKHOUSE_PASSWORD") {
             self.clickhouse_password = Some(value);
         }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_ASYNC_INSERT") {
+            self.clickhouse_async_insert = value.parse().unwrap_or(self.clickhouse_async_insert);
+        }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_WAIT_FOR_ASYNC_INSERT") {
+            self.clickhouse_wait_for_async_insert = value
+                .parse()
+                .unwrap_or(self.clickhouse_wait_for_async_insert);
+        }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_MAX_INSERT_BLOCK_SIZE") {
+            self.clickhouse_max_insert_block_size = value
+                .parse()
+                .unwrap_or(self.clickhouse_max_insert_block_size);
+        }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_DEDUP_EVENTS") {
+            self.clickhouse_dedup_events = value.parse().unwrap_or(self.clickhouse_dedup_events);
+        }
+        if let Ok(value) = env::var("LATTICE_SHADOW_CLICKHOUSE_URL") {
+            self.shadow_clickhouse_url = value;
+        }
+        if let Ok(value) = env::var("LATTICE_SHADOW_CLICKHOUSE_DATABASE") {
+            self.shadow_clickhouse_database = value;
+        }
+        if let Ok(value) = env::var("LATTICE_SHADOW_CLICKHOUSE_USER") {
+            self.shadow_clickhouse_user = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_SHADOW_CLICKHOUSE_PASSWORD") {
+            self.shadow_clickhouse_password = Some(value);
+        }
         if let Ok(value) = env::var("LATTICE_REPORT_DIR") {
             self.report_dir = value;
         }
@@ -247,6 +1055,9 @@ impl AppConfig {
         if let Ok(value) = env::var("LATTICE_WEBHOOK_TEMPLATE") {
             self.webhook_template = Some(value);
         }
+        if let Ok(value) = env::var("LATTICE_REPORT_WEBHOOK_LOCALE") {
+            self.report_webhook_locale = value;
+        }
         if let Ok(value) = env::var("LATTICE_ALERT_WEBHOOK_URL") {
             self.alert_webhook_url = Some(value);
         }
@@ -268,6 +1079,21 @@ impl AppConfig {
         if let Ok(value) = env::var("LATTICE_TRANSFER_WINDOW_SECONDS") {
             self.transfer_window_seconds = value.parse().unwrap_or(self.transfer_window_seconds);
         }
+        if let Ok(value) = env::var("LATTICE_STRICT_TRANSFER_FINGERPRINT") {
+            self.strict_transfer_fingerprint =
+                value.parse().unwrap_or(self.strict_transfer_fingerprint);
+        }
+        if let Ok(value) = env::var("LATTICE_TRANSFER_COUNT_TOLERANCE") {
+            self.transfer_count_tolerance = value.parse().unwrap_or(self.transfer_count_tolerance);
+        }
+        if let Ok(value) = env::var("LATTICE_TRANSFER_COUNT_TOLERANCE_PERCENT") {
+            self.transfer_count_tolerance_percent = value
+                .parse()
+                .unwrap_or(self.transfer_count_tolerance_percent);
+        }
+        if let Ok(value) = env::var("LATTICE_TRANSFER_AGGREGATE") {
+            self.transfer_aggregate = value.parse().unwrap_or(self.transfer_aggregate);
+        }
         if let Ok(value) = env::var("LATTICE_KEY_ITEM_WINDOW_MINUTES") {
             self.key_item_window_minutes = value.parse().unwrap_or(self.key_item_window_minutes);
         }
@@ -293,9 +1119,296 @@ impl AppConfig {
         if let Ok(value) = env::var("LATTICE_REPORT_MINUTE") {
             self.report_minute = value.parse().unwrap_or(self.report_minute);
         }
+        if let Ok(value) = env::var("LATTICE_INGEST_STALE_AFTER_MINUTES") {
+            self.ingest_stale_after_minutes =
+                value.parse().unwrap_or(self.ingest_stale_after_minutes);
+        }
+        if let Ok(value) = env::var("LATTICE_TASK_STALE_AFTER_SECONDS") {
+            self.task_stale_after_seconds = value.parse().unwrap_or(self.task_stale_after_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_HEARTBEAT_INTERVAL_SECONDS") {
+            self.heartbeat_interval_seconds =
+                value.parse().unwrap_or(self.heartbeat_interval_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_HEARTBEAT_MISSED_THRESHOLD") {
+            self.heartbeat_missed_threshold =
+                value.parse().unwrap_or(self.heartbeat_missed_threshold);
+        }
+        if let Ok(value) = env::var("LATTICE_MIN_MOD_VERSION") {
+            self.min_mod_version = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_MOD_VERSION_ENFORCE") {
+            self.mod_version_enforce = value.parse().unwrap_or(self.mod_version_enforce);
+        }
+        if let Ok(value) = env::var("LATTICE_MAINTENANCE_ENABLED") {
+            self.maintenance_enabled = value.parse().unwrap_or(self.maintenance_enabled);
+        }
+        if let Ok(value) = env::var("LATTICE_MAINTENANCE_HOUR") {
+            self.maintenance_hour = value.parse().unwrap_or(self.maintenance_hour);
+        }
+        if let Ok(value) = env::var("LATTICE_MAINTENANCE_OPTIMIZE_MIN_ROWS") {
+            self.maintenance_optimize_min_rows =
+                value.parse().unwrap_or(self.maintenance_optimize_min_rows);
+        }
+        if let Ok(value) = env::var("LATTICE_STORAGE_ALERT_THRESHOLD_MB") {
+            self.storage_alert_threshold_mb =
+                value.parse().unwrap_or(self.storage_alert_threshold_mb);
+        }
+        if let Ok(value) = env::var("LATTICE_RESPONSE_COMPRESSION_ENABLED") {
+            self.response_compression_enabled =
+                value.parse().unwrap_or(self.response_compression_enabled);
+        }
+        if let Ok(value) = env::var("LATTICE_RESPONSE_COMPRESSION_MIN_BYTES") {
+            self.response_compression_min_bytes =
+                value.parse().unwrap_or(self.response_compression_min_bytes);
+        }
+        if let Ok(value) = env::var("LATTICE_RESPONSE_COMPRESSION_CONTENT_TYPES") {
+            self.response_compression_content_types = parse_env_id_list(&value);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_GROUP_BY_PLAYER") {
+            self.alert_group_by_player = value.parse().unwrap_or(self.alert_group_by_player);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_GROUP_WINDOW_SECONDS") {
+            self.alert_group_window_seconds =
+                value.parse().unwrap_or(self.alert_group_window_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_ANOMALY_LINK_TARGET") {
+            self.anomaly_link_target = value;
+        }
+        if let Ok(value) = env::var("LATTICE_CUSTOM_BURST_TYPES") {
+            self.custom_burst_types = parse_env_id_list(&value);
+        }
+        if let Ok(value) = env::var("LATTICE_CUSTOM_BURST_THRESHOLD") {
+            self.custom_burst_threshold = value.parse().unwrap_or(self.custom_burst_threshold);
+        }
+        if let Ok(value) = env::var("LATTICE_CUSTOM_BURST_WINDOW_SECONDS") {
+            self.custom_burst_window_seconds =
+                value.parse().unwrap_or(self.custom_burst_window_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_BIND_SOCKET") {
+            self.bind_socket = value;
+        }
+        if let Ok(value) = env::var("LATTICE_TLS_CERT_PATH") {
+            self.tls_cert_path = value;
+        }
+        if let Ok(value) = env::var("LATTICE_TLS_KEY_PATH") {
+            self.tls_key_path = value;
+        }
+        if let Ok(value) = env::var("LATTICE_DEGRADED_CACHE_SIZE") {
+            self.degraded_cache_size = value.parse().unwrap_or(self.degraded_cache_size);
+        }
+        if let Ok(value) = env::var("LATTICE_DEAD_LETTER_MAX_EVENTS") {
+            self.dead_letter_max_events = value.parse().unwrap_or(self.dead_letter_max_events);
+        }
+        if let Ok(value) = env::var("LATTICE_DEGRADED_RECOVERY_SECONDS") {
+            self.degraded_recovery_seconds =
+                value.parse().unwrap_or(self.degraded_recovery_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_WATCHDOG_INTERVAL_SECONDS") {
+            self.clickhouse_watchdog_interval_seconds = value
+                .parse()
+                .unwrap_or(self.clickhouse_watchdog_interval_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_CLICKHOUSE_WATCHDOG_FAILURES") {
+            self.clickhouse_watchdog_failures =
+                value.parse().unwrap_or(self.clickhouse_watchdog_failures);
+        }
+        if let Ok(value) = env::var("LATTICE_STRICT_PROFILES") {
+            match serde_json::from_str(&value) {
+                Ok(profiles) => self.strict_profiles = profiles,
+                Err(err) => warn!("ignoring invalid LATTICE_STRICT_PROFILES: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_DAILY_QUOTA_ALERT_ENABLED") {
+            self.daily_quota_alert_enabled =
+                value.parse().unwrap_or(self.daily_quota_alert_enabled);
+        }
+        if let Ok(value) = env::var("LATTICE_REPORT_PLAYER_PAGES") {
+            self.report_player_pages = value.parse().unwrap_or(self.report_player_pages);
+        }
+        if let Ok(value) = env::var("LATTICE_REPORT_RETENTION_COUNT") {
+            self.report_retention_count = value.parse().unwrap_or(self.report_retention_count);
+        }
+        if let Ok(value) = env::var("LATTICE_REPORT_LINK_SECRET") {
+            self.report_link_secret = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_REPORT_LINK_TTL_HOURS") {
+            self.report_link_ttl_hours = value.parse().unwrap_or(self.report_link_ttl_hours);
+        }
+        if let Ok(value) = env::var("LATTICE_ML_EXPORT_SECRET") {
+            self.ml_export_secret = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_ENRICHERS") {
+            self.enrichers = parse_env_id_list(&value);
+        }
+        if let Ok(value) = env::var("LATTICE_CLUSTER_MODE") {
+            self.cluster_mode = value.parse().unwrap_or(self.cluster_mode);
+        }
+        if let Ok(value) = env::var("LATTICE_CLUSTER_STATE_URL") {
+            self.cluster_state_url = value;
+        }
+        if let Ok(value) = env::var("LATTICE_GRPC_BIND_ADDR") {
+            self.grpc_bind_addr = value;
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_BROKER_URL") {
+            self.mqtt_broker_url = value;
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_CLIENT_ID") {
+            self.mqtt_client_id = value;
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_USERNAME") {
+            self.mqtt_username = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_PASSWORD") {
+            self.mqtt_password = Some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_ANOMALY_TOPIC") {
+            self.mqtt_anomaly_topic = value;
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_HEALTH_TOPIC") {
+            self.mqtt_health_topic = value;
+        }
+        if let Ok(value) = env::var("LATTICE_MQTT_INGEST_TOPIC") {
+            self.mqtt_ingest_topic = value;
+        }
+        if let Ok(value) = env::var("LATTICE_REDACTION_RULES") {
+            match serde_json::from_str(&value) {
+                Ok(rules) => self.redaction_rules = rules,
+                Err(err) => warn!("ignoring invalid LATTICE_REDACTION_RULES: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_RECORD_PATH") {
+            self.ingest_record_path = value;
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_RECORD_SAMPLE_RATE") {
+            self.ingest_record_sample_rate =
+                value.parse().unwrap_or(self.ingest_record_sample_rate);
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_RECORD_MAX_MB") {
+            self.ingest_record_max_mb = value.parse().unwrap_or(self.ingest_record_max_mb);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_TEAM_ROUTES") {
+            match serde_json::from_str(&value) {
+                Ok(routes) => self.alert_team_routes = routes,
+                Err(err) => warn!("ignoring invalid LATTICE_ALERT_TEAM_ROUTES: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_CONFIG_CHANGE_ALERT_ENABLED") {
+            self.config_change_alert_enabled =
+                value.parse().unwrap_or(self.config_change_alert_enabled);
+        }
+        if let Ok(value) = env::var("LATTICE_SLOW_RULE_BUDGET_MS") {
+            self.slow_rule_budget_ms = value.parse().unwrap_or(self.slow_rule_budget_ms);
+        }
+        if let Ok(value) = env::var("LATTICE_RULE_HYGIENE_REPORT_DAY") {
+            self.rule_hygiene_report_day = value.parse().unwrap_or(self.rule_hygiene_report_day);
+        }
+        if let Ok(value) = env::var("LATTICE_ORIGIN_LEARNING_DAYS") {
+            self.origin_learning_days = value.parse().unwrap_or(self.origin_learning_days);
+        }
+        if let Ok(value) = env::var("LATTICE_SERVER_KEYS") {
+            match serde_json::from_str(&value) {
+                Ok(keys) => self.server_keys = keys,
+                Err(err) => warn!("ignoring invalid LATTICE_SERVER_KEYS: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_SERVER_IDENTITY_REQUIRED") {
+            self.server_identity_required = value.parse().unwrap_or(self.server_identity_required);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_MAX_LINES") {
+            self.alert_max_lines = value.parse().unwrap_or(self.alert_max_lines);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_PAGE_TTL_MINUTES") {
+            self.alert_page_ttl_minutes = value.parse().unwrap_or(self.alert_page_ttl_minutes);
+        }
+        if let Ok(value) = env::var("LATTICE_RULE_RISK_OVERRIDES") {
+            match serde_json::from_str(&value) {
+                Ok(overrides) => self.rule_risk_overrides = overrides,
+                Err(err) => warn!("ignoring invalid LATTICE_RULE_RISK_OVERRIDES: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_WEBHOOK_FALLBACK_URL") {
+            let value = value.trim().to_string();
+            self.alert_webhook_fallback_url = (!value.is_empty()).then_some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_META_ALERT_SPIKE_MULTIPLE") {
+            self.meta_alert_spike_multiple =
+                value.parse().unwrap_or(self.meta_alert_spike_multiple);
+        }
+        if let Ok(value) = env::var("LATTICE_META_ALERT_BASELINE_MINUTES") {
+            self.meta_alert_baseline_minutes =
+                value.parse().unwrap_or(self.meta_alert_baseline_minutes);
+        }
+        if let Ok(value) = env::var("LATTICE_META_ALERT_ZERO_INGEST") {
+            self.meta_alert_zero_ingest = value.parse().unwrap_or(self.meta_alert_zero_ingest);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_WEBHOOK_PROXY") {
+            let value = value.trim().to_string();
+            self.alert_webhook_proxy = (!value.is_empty()).then_some(value);
+        }
+        if let Ok(value) = env::var("LATTICE_DISPLAY_TIMEZONE") {
+            self.display_timezone = value;
+        }
+        if let Ok(value) = env::var("LATTICE_DISPLAY_TIME_FORMAT") {
+            self.display_time_format = value;
+        }
+        if let Ok(value) = env::var("LATTICE_DETECTION_RULES_PATH") {
+            self.detection_rules_path = value;
+        }
+        if let Ok(value) = env::var("LATTICE_COMPOSITE_RULES_PATH") {
+            self.composite_rules_path = value;
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_DEDUP_CAPACITY") {
+            self.ingest_dedup_capacity = value.parse().unwrap_or(self.ingest_dedup_capacity);
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_RATE_LIMIT_PER_SECOND") {
+            self.ingest_rate_limit_per_second =
+                value.parse().unwrap_or(self.ingest_rate_limit_per_second);
+        }
+        if let Ok(value) = env::var("LATTICE_INGEST_RATE_LIMIT_BURST") {
+            self.ingest_rate_limit_burst = value.parse().unwrap_or(self.ingest_rate_limit_burst);
+        }
+        if let Ok(value) = env::var("LATTICE_TRUSTED_PROXIES") {
+            self.trusted_proxies = parse_env_id_list(&value);
+        }
+        if let Ok(value) = env::var("LATTICE_REPLAY_WINDOW_SECONDS") {
+            self.replay_window_seconds = value.parse().unwrap_or(self.replay_window_seconds);
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_SERVER_GROUPS") {
+            match serde_json::from_str(&value) {
+                Ok(groups) => self.alert_server_groups = groups,
+                Err(err) => warn!("ignoring invalid LATTICE_ALERT_SERVER_GROUPS: {}", err),
+            }
+        }
+        if let Ok(value) = env::var("LATTICE_ALERT_ROUTING") {
+            match serde_json::from_str(&value) {
+                Ok(routing) => self.alert_routing = routing,
+                Err(err) => warn!("ignoring invalid LATTICE_ALERT_ROUTING: {}", err),
+            }
+        }
     }
 }
 
+/// Env overrides are always named `LATTICE_<KEY>`, so a key's origin is env, then file, then default.
+fn resolve_origins(file: &toml::Table) -> BTreeMap<String, ConfigOrigin> {
+    let keys: Vec<String> = match serde_json::to_value(AppConfig::default()) {
+        Ok(serde_json::Value::Object(map)) => map.into_iter().map(|(key, _)| key).collect(),
+        _ => Vec::new(),
+    };
+    keys.into_iter()
+        .map(|key| {
+            let origin = if env::var(format!("LATTICE_{}", key.to_uppercase())).is_ok() {
+                ConfigOrigin::Env
+            } else if file.contains_key(&key) {
+                ConfigOrigin::File
+            } else {
+                ConfigOrigin::Default
+            };
+            (key, origin)
+        })
+        .collect()
+}
+
 fn resolve_path(base: &Path, value: &str) -> String {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -1,30 +1,234 @@
-use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use clickhouse::Client;
+use time::OffsetDateTime;
+use tracing::warn;
 
 use backend_domain::{
-    AnomalyRepository, AnomalyRow, EventRepository, IngestEvent, ItemEventRow, ReportSummary,
-    StorageScanEventRow,
+    anomaly_id_event_ms, custom_type, AnomalyAckKey, AnomalyAckRequest, AnomalyDailySummaryRow, AnomalyRepository,
+    AnomalyRow, ClickhousePreflight, CustomEventRow, DbConfig, EventRepository, FieldSelection,
+    FingerprintCollision, FingerprintUsage, IngestEvent,
+    ItemAnomalyStat, ItemCountDistribution, ItemEventFilter, ItemEventRow, ITEM_EVENT_FIELDS, MaintenanceRepository, PartitionStat, PermissionCheck, PlayerAnomalyCount,
+    PlayerEventSpan, PlayerItemAcquired, PlayerItemDailyTotal, ReportSummary, RuleAnomalyCount,
+    ShadowConsistency, ShadowTableCount, StorageScanEventRow, StorageUsage,
 };
 
 use crate::utils::millis_to_utc;
 
+/// Daily-partitioned tables that maintenance may OPTIMIZE; anything else is rejected.
+const MAINTAINED_TABLES: [&str; 3] = ["item_events", "custom_events", "anomalies"];
+
+/// Scratch table the permission preflight writes to; its rows expire after a day.
+const PREFLIGHT_TABLE: &str = "lattice_preflight";
+
+/// `AnomalyRow` columns that only matter when a listing asks for them; the rest feed the
+/// anomaly id and ack key and are always read.
+const ANOMALY_OPTIONAL_COLUMNS: [&str; 4] = ["player_name", "risk_level", "reason", "evidence_json"];
+
+/// SELECT list for `AnomalyRow`, reading unrequested optional columns as ''.
+fn anomaly_columns(fields: &FieldSelection) -> String {
+    [
+        "event_time", "server_id", "player_uuid", "player_name", "item_id", "count", "risk_level",
+        "rule_id", "reason", "evidence_json",
+    ]
+    .iter()
+    .map(|column| {
+        if ANOMALY_OPTIONAL_COLUMNS.contains(column) && !fields.includes(column) {
+            format!("'' AS {}", column)
+        } else {
+            column.to_string()
+        }
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// SELECT list for `ItemEventRow`, reading unrequested columns as ''/0/NULL; the time and id
+/// keep the ordering stable.
+fn item_event_columns(fields: &FieldSelection) -> String {
+    ITEM_EVENT_FIELDS
+        .iter()
+        .map(|column| match *column {
+            "event_time" | "event_id" => column.to_string(),
+            _ if fields.includes(column) => column.to_string(),
+            "count" => "toInt64(0) AS count".to_string(),
+            "x" | "y" | "z" => format!("CAST(NULL, 'Nullable(Int32)') AS {}", column),
+            _ => format!("'' AS {}", column),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// WHERE clause and its bind values for an `ItemEventFilter`; `player` matches name or UUID.
+fn item_event_filter(filter: &ItemEventFilter) -> (String, Vec<&str>) {
+    let mut clause = "toDate(event_time) = toDate(?)".to_string();
+    let mut values = vec![filter.date.as_str()];
+    if let Some(player) = &filter.player {
+        clause.push_str(" AND (player_name = ? OR player_uuid = ?)");
+        values.push(player.as_str());
+        values.push(player.as_str());
+    }
+    for (column, value) in [
+        ("item_id", &filter.item_id),
+        ("storage_id", &filter.storage_id),
+        ("server_id", &filter.server_id),
+        ("event_type", &filter.event_type),
+    ] {
+        if let Some(value) = value {
+            clause.push_str(&format!(" AND {} = ?", column));
+            values.push(value.as_str());
+        }
+    }
+    (clause, values)
+}
+
+/// SELECT list for `StorageScanEventRow`, reading unrequested location columns as ''/NULL.
+fn storage_scan_columns(fields: &FieldSelection) -> String {
+    [
+        "event_time", "event_id", "item_id", "count", "storage_mod", "storage_id", "dim", "x", "y",
+        "z",
+    ]
+    .iter()
+    .map(|column| match *column {
+        "storage_mod" | "storage_id" | "dim" if !fields.includes(column) => {
+            format!("'' AS {}", column)
+        }
+        "x" | "y" | "z" if !fields.includes(column) => {
+            format!("CAST(NULL, 'Nullable(Int32)') AS {}", column)
+        }
+        _ => column.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn risk_summary(rows: Vec<(String, u64)>) -> ReportSummary {
+    let mut summary = ReportSummary::default();
+    for (risk, count) in rows {
+        match risk.as_str() {
+            "CRITICAL" => summary.critical = count,
+            "HIGH" => summary.high = count,
+            "MEDIUM" => summary.medium = count,
+            "LOW" => summary.low = count,
+            _ => {}
+        }
+    }
+    summary
+}
+
+/// ClickHouse settings sent with every row insert, from the `clickhouse_*insert*` keys.
+fn insert_settings(db_config: &DbConfig) -> Vec<(&'static str, String)> {
+    let mut settings = Vec::new();
+    if db_config.clickhouse_async_insert {
+        settings.push(("async_insert", "1".to_string()));
+        let wait = if db_config.clickhouse_wait_for_async_insert {
+            "1"
+        } else {
+            "0"
+        };
+        settings.push(("wait_for_async_insert", wait.to_string()));
+    }
+    if db_config.clickhouse_max_insert_block_size > 0 {
+        settings.push((
+            "max_insert_block_size",
+            db_config.clickhouse_max_insert_block_size.to_string(),
+        ));
+    }
+    settings
+}
+
+/// The second ClickHouse of a migration window. Row inserts are mirrored to it once the
+/// primary stored them; its failures are only counted and logged, so they never reach the
+/// dead-letter queue or degraded mode of the primary.
+struct ShadowTarget {
+    repo: ClickhouseRepo,
+    failed_batches: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl ShadowTarget {
+    fn record(&self, table: &str, result: Result<()>) {
+        if let Err(err) = result {
+            self.failed_batches.fetch_add(1, Ordering::Relaxed);
+            warn!("shadow clickhouse insert into {} failed: {}", table, err);
+            if let Ok(mut last_error) = self.last_error.lock() {
+                *last_error = Some(err.to_string());
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ClickhouseRepo {
     client: Client,
+    /// `client` plus the insert settings; used for row inserts only, so queries and
+    /// `INSERT ... SELECT` statements keep the server defaults.
+    insert_client: Client,
     database: String,
+    /// `clickhouse_dedup_events`: event tables are created as `ReplacingMergeTree`.
+    dedup_events: bool,
+    /// `shadow_clickhouse_url`: dual-write target during a migration.
+    shadow: Option<Arc<ShadowTarget>>,
 }
 
 impl ClickhouseRepo {
     pub fn new(client: Client, database: String) -> Self {
-        Self { client, database }
+        Self {
+            insert_client: client.clone(),
+            client,
+            database,
+            dedup_events: false,
+            shadow: None,
+        }
+    }
+
+    pub fn with_insert_settings(mut self, db_config: &DbConfig) -> Self {
+        let mut insert_client = self.client.clone();
+        for (name, value) in insert_settings(db_config) {
+            insert_client = insert_client.with_option(name, value);
+        }
+        self.insert_client = insert_client;
+        self
+    }
+
+    pub fn with_dedup_events(mut self, enabled: bool) -> Self {
+        self.dedup_events = enabled;
+        self
+    }
+
+    /// Mirrors `item_events`, `custom_events` and `anomalies` inserts to `shadow`.
+    pub fn with_shadow(mut self, shadow: ClickhouseRepo) -> Self {
+        self.shadow = Some(Arc::new(ShadowTarget {
+            repo: shadow,
+            failed_batches: AtomicU64::new(0),
+            last_error: Mutex::new(None),
+        }));
+        self
+    }
+
+    /// Engine, partitioning and sorting key of an event table; the dedup engine appends
+    /// `event_id` to the key so only rows of the same event collapse.
+    fn event_table_engine(&self, order_by: &str) -> String {
+        let (engine, order_by) = if self.dedup_events {
+            ("ReplacingMergeTree", format!("{}, event_id", order_by))
+        } else {
+            ("MergeTree", order_by.to_string())
+        };
+        format!(
+            "{}\nPARTITION BY toDate(event_time)\nORDER BY ({})",
+            engine, order_by
+        )
     }
 
     pub async fn ensure_schema(&self) -> Result<()> {
         let create_db = format!("CREATE DATABASE IF NOT EXISTS {}", self.database);
         self.client.query(&create_db).execute().await?;
 
-        let create_events = r#"
+        let create_events = format!(
+            r#"
 CREATE TABLE IF NOT EXISTS item_events (
     event_time DateTime64(3),
     event_id String,
@@ -48,13 +252,36 @@ CREATE TABLE IF NOT EXISTS item_events (
     x Nullable(Int32),
     y Nullable(Int32),
     z Nullable(Int32)
-) ENGINE = MergeTree
-PARTITION BY toDate(event_time)
-ORDER BY (event_time, player_uuid, item_id)
+) ENGINE = {}
 TTL toDateTime(event_time) + INTERVAL 7 DAY
-"#;
+"#,
+            self.event_table_engine("event_time, player_uuid, item_id")
+        );
+
+        self.client.query(&create_events).execute().await?;
+
+        let create_custom_events = format!(
+            r#"
+CREATE TABLE IF NOT EXISTS custom_events (
+    event_time DateTime64(3),
+    event_id String,
+    server_id String,
+    custom_type LowCardinality(String),
+    player_uuid String,
+    player_name String,
+    count Int64,
+    payload_json String,
+    dim String,
+    x Nullable(Int32),
+    y Nullable(Int32),
+    z Nullable(Int32)
+) ENGINE = {}
+TTL toDateTime(event_time) + INTERVAL 7 DAY
+"#,
+            self.event_table_engine("custom_type, event_time, player_uuid")
+        );
 
-        self.client.query(create_events).execute().await?;
+        self.client.query(&create_custom_events).execute().await?;
 
         let create_anomalies = r#"
 CREATE TABLE IF NOT EXISTS anomalies (
@@ -75,11 +302,99 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
 "#;
 
         self.client.query(create_anomalies).execute().await?;
+
+        // Daily rollup outlives the raw anomalies TTL so long-range trend charts stay cheap.
+        let create_daily_summary = r#"
+CREATE TABLE IF NOT EXISTS anomaly_daily_summary (
+    date Date,
+    server_id String,
+    rule_id String,
+    risk_level String,
+    count UInt64,
+    updated_at DateTime64(3)
+) ENGINE = ReplacingMergeTree(updated_at)
+PARTITION BY toYYYYMM(date)
+ORDER BY (date, server_id, rule_id, risk_level)
+"#;
+
+        self.client.query(create_daily_summary).execute().await?;
+
+        // Acks live beside the raw anomalies (same TTL) so bulk triage never rewrites anomaly parts.
+        let create_anomaly_acks = r#"
+CREATE TABLE IF NOT EXISTS anomaly_acks (
+    event_time DateTime64(3),
+    player_uuid String,
+    item_id String,
+    rule_id String,
+    acked_at DateTime64(3),
+    note String,
+    acked_by String
+) ENGINE = ReplacingMergeTree(acked_at)
+PARTITION BY toDate(event_time)
+ORDER BY (event_time, player_uuid, item_id, rule_id)
+TTL toDateTime(event_time) + INTERVAL 30 DAY
+"#;
+
+        self.client.query(create_anomaly_acks).execute().await?;
+        self.client
+            .query("ALTER TABLE anomaly_acks ADD COLUMN IF NOT EXISTS acked_by String")
+            .execute()
+            .await?;
+
+        // Read receipts from the desktop, keyed by anomaly id; one row per id survives merges.
+        let create_anomaly_seen = r#"
+CREATE TABLE IF NOT EXISTS anomaly_seen (
+    event_time DateTime64(3),
+    anomaly_id String,
+    seen_at DateTime64(3),
+    seen_by String
+) ENGINE = ReplacingMergeTree(seen_at)
+PARTITION BY toDate(event_time)
+ORDER BY (event_time, anomaly_id)
+TTL toDateTime(event_time) + INTERVAL 30 DAY
+"#;
+
+        self.client.query(create_anomaly_seen).execute().await?;
+        self.check_event_table_engines().await?;
+        Ok(())
+    }
+
+    /// The event tables are only ever created `IF NOT EXISTS`, so switching
+    /// `clickhouse_dedup_events` leaves existing ones on their old engine; warns at startup
+    /// rather than silently keeping (or not keeping) duplicates.
+    async fn check_event_table_engines(&self) -> Result<()> {
+        let expected = if self.dedup_events {
+            "ReplacingMergeTree"
+        } else {
+            "MergeTree"
+        };
+        let tables = self
+            .client
+            .query("SELECT name, engine FROM system.tables WHERE database = currentDatabase() AND name IN ('item_events', 'custom_events') ORDER BY name")
+            .fetch_all::<(String, String)>()
+            .await?;
+        for (table, engine) in tables {
+            if engine != expected {
+                warn!(
+                    "{} uses {} but clickhouse_dedup_events = {} expects {}; the existing table is not converted, so rebuild it (CREATE TABLE {}_new with the new engine, INSERT INTO {}_new SELECT * FROM {}, EXCHANGE TABLES {} AND {}_new) or set clickhouse_dedup_events back",
+                    table, engine, self.dedup_events, expected, table, table, table, table, table
+                );
+            }
+        }
         Ok(())
     }
 
     pub async fn insert_events(&self, events: &[IngestEvent]) -> Result<()> {
-        let mut insert = self.client.insert("item_events")?;
+        self.write_events(events).await?;
+        if let Some(shadow) = &self.shadow {
+            let result = shadow.repo.write_events(events).await;
+            shadow.record("item_events", result);
+        }
+        Ok(())
+    }
+
+    async fn write_events(&self, events: &[IngestEvent]) -> Result<()> {
+        let mut insert = self.insert_client.insert("item_events")?;
         for event in events {
             insert
                 .write(&ItemEventRow {
@@ -112,8 +427,54 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
         Ok(())
     }
 
+    pub async fn insert_custom_events(&self, events: &[IngestEvent]) -> Result<()> {
+        self.write_custom_events(events).await?;
+        if let Some(shadow) = &self.shadow {
+            let result = shadow.repo.write_custom_events(events).await;
+            shadow.record("custom_events", result);
+        }
+        Ok(())
+    }
+
+    async fn write_custom_events(&self, events: &[IngestEvent]) -> Result<()> {
+        let mut insert = self.insert_client.insert("custom_events")?;
+        for event in events {
+            insert
+                .write(&CustomEventRow {
+                    event_time: millis_to_utc(event.event_time),
+                    event_id: event.event_id.clone(),
+                    server_id: event.server_id.clone().unwrap_or_default(),
+                    custom_type: custom_type(event),
+                    player_uuid: event.player_uuid.clone().unwrap_or_default(),
+                    player_name: event.player_name.clone().unwrap_or_default(),
+                    count: event.count,
+                    payload_json: event
+                        .payload
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| "{}".to_string()),
+                    dim: event.dim.clone().unwrap_or_default(),
+                    x: event.x,
+                    y: event.y,
+                    z: event.z,
+                })
+                .await?;
+        }
+        insert.end().await?;
+        Ok(())
+    }
+
     pub async fn insert_anomalies(&self, anomalies: &[AnomalyRow]) -> Result<()> {
-        let mut insert = self.client.insert("anomalies")?;
+        self.write_anomalies(anomalies).await?;
+        if let Some(shadow) = &self.shadow {
+            let result = shadow.repo.write_anomalies(anomalies).await;
+            shadow.record("anomalies", result);
+        }
+        Ok(())
+    }
+
+    async fn write_anomalies(&self, anomalies: &[AnomalyRow]) -> Result<()> {
+        let mut insert = self.insert_client.insert("anomalies")?;
         for anomaly in anomalies {
             insert.write(anomaly).await?;
         }
@@ -122,7 +483,8 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
     }
 
     pub async fn fetch_anomalies(&self, date: &str, player: Option<&str>) -> Result<Vec<AnomalyRow>> {
-        self.fetch_anomalies_page(date, player, 0, 500).await
+        self.fetch_anomalies_page(date, player, 0, 500, &FieldSelection::default())
+            .await
     }
 
     pub async fn count_anomalies(&self, date: &str, player: Option<&str>) -> Result<u64> {
@@ -150,13 +512,15 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
         player: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
     ) -> Result<Vec<AnomalyRow>> {
         let safe_limit = limit.clamp(1, 2000) as u64;
         let safe_offset = offset as u64;
+        let columns = anomaly_columns(fields);
         if let Some(player_name) = player {
             return self
                 .client
-                .query("SELECT event_time, server_id, player_uuid, player_name, item_id, count, risk_level, rule_id, reason, evidence_json FROM anomalies WHERE toDate(event_time) = toDate(?) AND player_name = ? ORDER BY event_time DESC LIMIT ? OFFSET ?")
+                .query(&format!("SELECT {} FROM anomalies WHERE toDate(event_time) = toDate(?) AND player_name = ? ORDER BY event_time DESC LIMIT ? OFFSET ?", columns))
                 .bind(date)
                 .bind(player_name)
                 .bind(safe_limit)
@@ -166,7 +530,7 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
                 .map_err(Into::into);
         }
         self.client
-            .query("SELECT event_time, server_id, player_uuid, player_name, item_id, count, risk_level, rule_id, reason, evidence_json FROM anomalies WHERE toDate(event_time) = toDate(?) ORDER BY event_time DESC LIMIT ? OFFSET ?")
+            .query(&format!("SELECT {} FROM anomalies WHERE toDate(event_time) = toDate(?) ORDER BY event_time DESC LIMIT ? OFFSET ?", columns))
             .bind(date)
             .bind(safe_limit)
             .bind(safe_offset)
@@ -175,6 +539,52 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
             .map_err(Into::into)
     }
 
+    pub async fn fetch_anomalies_after(
+        &self,
+        date: &str,
+        player: Option<&str>,
+        after: Option<&AnomalyAckKey>,
+        limit: usize,
+    ) -> Result<Vec<AnomalyRow>> {
+        let mut clause = "toDate(event_time) = toDate(?)".to_string();
+        if player.is_some() {
+            clause.push_str(" AND player_name = ?");
+        }
+        if after.is_some() {
+            clause.push_str(" AND (event_time, player_uuid, item_id, rule_id) < (fromUnixTimestamp64Milli(toInt64(?)), ?, ?, ?)");
+        }
+        let mut query = self.client.query(&format!(
+            "SELECT {} FROM anomalies WHERE {} ORDER BY event_time DESC, player_uuid DESC, item_id DESC, rule_id DESC LIMIT ?",
+            anomaly_columns(&FieldSelection::default()),
+            clause
+        ));
+        query = query.bind(date);
+        if let Some(player_name) = player {
+            query = query.bind(player_name);
+        }
+        if let Some(key) = after {
+            query = query
+                .bind((key.event_time.unix_timestamp_nanos() / 1_000_000) as i64)
+                .bind(key.player_uuid.as_str())
+                .bind(key.item_id.as_str())
+                .bind(key.rule_id.as_str());
+        }
+        query
+            .bind(limit.clamp(1, 2000) as u64)
+            .fetch_all::<AnomalyRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_anomalies_at(&self, event_time_ms: i64) -> Result<Vec<AnomalyRow>> {
+        self.client
+            .query("SELECT event_time, server_id, player_uuid, player_name, item_id, count, risk_level, rule_id, reason, evidence_json FROM anomalies WHERE event_time = fromUnixTimestamp64Milli(?)")
+            .bind(event_time_ms)
+            .fetch_all::<AnomalyRow>()
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn fetch_summary(&self, date: &str) -> Result<ReportSummary> {
         let rows = self
             .client
@@ -182,16 +592,221 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
             .bind(date)
             .fetch_all::<(String, u64)>()
             .await?;
-        let mut summary = ReportSummary::default();
-        for (risk, count) in rows {
-            match risk.as_str() {
-                "HIGH" => summary.high = count,
-                "MEDIUM" => summary.medium = count,
-                "LOW" => summary.low = count,
-                _ => {}
+        Ok(risk_summary(rows))
+    }
+
+    pub async fn fetch_summary_between(&self, from_ms: i64, to_ms: i64) -> Result<ReportSummary> {
+        let rows = self
+            .client
+            .query("SELECT risk_level, count() as cnt FROM anomalies WHERE event_time >= fromUnixTimestamp64Milli(toInt64(?)) AND event_time < fromUnixTimestamp64Milli(toInt64(?)) GROUP BY risk_level")
+            .bind(from_ms)
+            .bind(to_ms)
+            .fetch_all::<(String, u64)>()
+            .await?;
+        Ok(risk_summary(rows))
+    }
+
+    pub async fn fetch_top_players(
+        &self,
+        date: &str,
+        limit: usize,
+    ) -> Result<Vec<PlayerAnomalyCount>> {
+        self.client
+            .query("SELECT player_uuid, any(player_name) AS player_name, count() AS anomalies, countIf(risk_level = 'CRITICAL') AS critical, countIf(risk_level = 'HIGH') AS high FROM anomalies WHERE toDate(event_time) = toDate(?) AND player_uuid != '' GROUP BY player_uuid ORDER BY critical DESC, high DESC, anomalies DESC, player_name LIMIT ?")
+            .bind(date)
+            .bind(limit.clamp(1, 200) as u64)
+            .fetch_all::<PlayerAnomalyCount>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Replaces `date`'s rows: a combination that no longer occurs (after a partition drop or a
+    /// rule change) would otherwise keep its old count, since replacing only merges equal keys.
+    pub async fn rollup_daily_summary(&self, date: &str) -> Result<()> {
+        self.client
+            .clone()
+            .with_option("mutations_sync", "1")
+            .query("ALTER TABLE anomaly_daily_summary DELETE WHERE date = toDate(?)")
+            .bind(date)
+            .execute()
+            .await?;
+        self.client
+            .query("INSERT INTO anomaly_daily_summary (date, server_id, rule_id, risk_level, count, updated_at) SELECT toDate(event_time) AS day, server_id, rule_id, risk_level, count(), now64(3) FROM anomalies WHERE toDate(event_time) = toDate(?) GROUP BY day, server_id, rule_id, risk_level")
+            .bind(date)
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn fetch_daily_summary(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        server_id: Option<&str>,
+        rule_id: Option<&str>,
+    ) -> Result<Vec<AnomalyDailySummaryRow>> {
+        let mut sql = "SELECT toString(date), server_id, rule_id, risk_level, count FROM anomaly_daily_summary FINAL WHERE date >= toDate(?) AND date <= toDate(?)".to_string();
+        if server_id.is_some() {
+            sql.push_str(" AND server_id = ?");
+        }
+        if rule_id.is_some() {
+            sql.push_str(" AND rule_id = ?");
+        }
+        sql.push_str(" ORDER BY date, server_id, rule_id, risk_level");
+
+        let mut query = self.client.query(&sql).bind(from_date).bind(to_date);
+        if let Some(value) = server_id {
+            query = query.bind(value);
+        }
+        if let Some(value) = rule_id {
+            query = query.bind(value);
+        }
+        query
+            .fetch_all::<AnomalyDailySummaryRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn ack_anomalies(
+        &self,
+        request: &AnomalyAckRequest,
+        acked_at_ms: i64,
+    ) -> Result<u64> {
+        let mut filter = "toDate(event_time) = toDate(?)".to_string();
+        let mut values = vec![request.date.as_str()];
+        for (column, value) in [
+            ("rule_id", &request.rule_id),
+            ("player_name", &request.player),
+            ("server_id", &request.server_id),
+            ("item_id", &request.item_id),
+        ] {
+            if let Some(value) = value {
+                filter.push_str(&format!(" AND {} = ?", column));
+                values.push(value.as_str());
             }
         }
-        Ok(summary)
+
+        let count_sql = format!("SELECT count() FROM anomalies WHERE {}", filter);
+        let mut count_query = self.client.query(&count_sql);
+        for value in &values {
+            count_query = count_query.bind(*value);
+        }
+        let matched = count_query.fetch_one::<u64>().await?;
+        if matched == 0 {
+            return Ok(0);
+        }
+
+        let insert_sql = format!(
+            "INSERT INTO anomaly_acks (event_time, player_uuid, item_id, rule_id, acked_at, note, acked_by) SELECT event_time, player_uuid, item_id, rule_id, fromUnixTimestamp64Milli(toInt64(?)), ?, ? FROM anomalies WHERE {}",
+            filter
+        );
+        let mut insert_query = self
+            .client
+            .query(&insert_sql)
+            .bind(acked_at_ms)
+            .bind(request.note.as_deref().unwrap_or_default())
+            .bind(request.acked_by.as_deref().unwrap_or_default());
+        for value in &values {
+            insert_query = insert_query.bind(*value);
+        }
+        insert_query.execute().await?;
+        Ok(matched)
+    }
+
+    pub async fn fetch_item_anomaly_stats(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        rule_ids: &[String],
+    ) -> Result<Vec<ItemAnomalyStat>> {
+        self.client
+            .query("SELECT item_id, count(), countIf(has(?, rule_id)), uniqExact(player_uuid) FROM anomalies WHERE toDate(event_time) BETWEEN toDate(?) AND toDate(?) GROUP BY item_id")
+            .bind(rule_ids)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_all::<ItemAnomalyStat>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_player_rule_counts(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+    ) -> Result<Vec<RuleAnomalyCount>> {
+        self.client
+            .query("SELECT rule_id, count() AS anomalies FROM anomalies WHERE player_uuid = ? AND event_time >= fromUnixTimestamp64Milli(toInt64(?)) GROUP BY rule_id ORDER BY anomalies DESC, rule_id")
+            .bind(player_uuid)
+            .bind(since_ms)
+            .fetch_all::<RuleAnomalyCount>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn ack_anomaly(
+        &self,
+        key: &AnomalyAckKey,
+        acked_at_ms: i64,
+        note: &str,
+        acked_by: &str,
+    ) -> Result<()> {
+        let event_time_ms = (key.event_time.unix_timestamp_nanos() / 1_000_000) as i64;
+        self.client
+            .query("INSERT INTO anomaly_acks (event_time, player_uuid, item_id, rule_id, acked_at, note, acked_by) VALUES (fromUnixTimestamp64Milli(toInt64(?)), ?, ?, ?, fromUnixTimestamp64Milli(toInt64(?)), ?, ?)")
+            .bind(event_time_ms)
+            .bind(&key.player_uuid)
+            .bind(&key.item_id)
+            .bind(&key.rule_id)
+            .bind(acked_at_ms)
+            .bind(note)
+            .bind(acked_by)
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_acked_keys(&self, date: &str) -> Result<Vec<AnomalyAckKey>> {
+        self.client
+            .query("SELECT DISTINCT event_time, player_uuid, item_id, rule_id FROM anomaly_acks WHERE toDate(event_time) = toDate(?)")
+            .bind(date)
+            .fetch_all::<AnomalyAckKey>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn mark_anomalies_seen(
+        &self,
+        ids: &[String],
+        seen_at_ms: i64,
+        seen_by: &str,
+    ) -> Result<()> {
+        let (event_times, ids): (Vec<i64>, Vec<String>) = ids
+            .iter()
+            .filter_map(|id| Some((anomaly_id_event_ms(id)?, id.clone())))
+            .unzip();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.client
+            .query("INSERT INTO anomaly_seen (event_time, anomaly_id, seen_at, seen_by) SELECT fromUnixTimestamp64Milli(receipt.1), receipt.2, fromUnixTimestamp64Milli(toInt64(?)), ? FROM (SELECT arrayJoin(arrayZip(?, ?)) AS receipt)")
+            .bind(seen_at_ms)
+            .bind(seen_by)
+            .bind(event_times)
+            .bind(ids)
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_seen_ids(&self, date: &str) -> Result<Vec<String>> {
+        self.client
+            .query(
+                "SELECT DISTINCT anomaly_id FROM anomaly_seen WHERE toDate(event_time) = toDate(?)",
+            )
+            .bind(date)
+            .fetch_all::<String>()
+            .await
+            .map_err(Into::into)
     }
 
     pub async fn fetch_storage_scan_events(
@@ -200,7 +815,8 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
         item: Option<&str>,
         limit: usize,
     ) -> Result<Vec<StorageScanEventRow>> {
-        self.fetch_storage_scan_events_page(date, item, 0, limit).await
+        self.fetch_storage_scan_events_page(date, item, 0, limit, &FieldSelection::default())
+            .await
     }
 
     pub async fn count_storage_scan_events(
@@ -233,13 +849,15 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
         item: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
     ) -> Result<Vec<StorageScanEventRow>> {
         let safe_limit = limit.clamp(1, 2000) as u64;
         let safe_offset = offset as u64;
+        let columns = storage_scan_columns(fields);
         if let Some(item_id) = item {
             return self
                 .client
-                .query("SELECT event_time, item_id, count, storage_mod, storage_id, dim, x, y, z FROM item_events WHERE event_type = 'STORAGE_SNAPSHOT' AND toDate(event_time) = toDate(?) AND item_id = ? ORDER BY event_time DESC LIMIT ? OFFSET ?")
+                .query(&format!("SELECT {} FROM item_events WHERE event_type = 'STORAGE_SNAPSHOT' AND toDate(event_time) = toDate(?) AND item_id = ? ORDER BY event_time DESC LIMIT ? OFFSET ?", columns))
                 .bind(date)
                 .bind(item_id)
                 .bind(safe_limit)
@@ -250,7 +868,7 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
         }
 
         self.client
-            .query("SELECT event_time, item_id, count, storage_mod, storage_id, dim, x, y, z FROM item_events WHERE event_type = 'STORAGE_SNAPSHOT' AND toDate(event_time) = toDate(?) ORDER BY event_time DESC LIMIT ? OFFSET ?")
+            .query(&format!("SELECT {} FROM item_events WHERE event_type = 'STORAGE_SNAPSHOT' AND toDate(event_time) = toDate(?) ORDER BY event_time DESC LIMIT ? OFFSET ?", columns))
             .bind(date)
             .bind(safe_limit)
             .bind(safe_offset)
@@ -259,10 +877,387 @@ TTL toDateTime(event_time) + INTERVAL 30 DAY
             .map_err(Into::into)
     }
 
+    pub async fn fetch_storage_scan_events_after(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        after: Option<(OffsetDateTime, &str)>,
+        limit: usize,
+    ) -> Result<Vec<StorageScanEventRow>> {
+        let mut clause =
+            "event_type = 'STORAGE_SNAPSHOT' AND toDate(event_time) = toDate(?)".to_string();
+        if item.is_some() {
+            clause.push_str(" AND item_id = ?");
+        }
+        if after.is_some() {
+            clause.push_str(
+                " AND (event_time, event_id) < (fromUnixTimestamp64Milli(toInt64(?)), ?)",
+            );
+        }
+        let mut query = self.client.query(&format!(
+            "SELECT {} FROM item_events WHERE {} ORDER BY event_time DESC, event_id DESC LIMIT ?",
+            storage_scan_columns(&FieldSelection::default()),
+            clause
+        ));
+        query = query.bind(date);
+        if let Some(item_id) = item {
+            query = query.bind(item_id);
+        }
+        if let Some((event_time, event_id)) = after {
+            query = query
+                .bind((event_time.unix_timestamp_nanos() / 1_000_000) as i64)
+                .bind(event_id);
+        }
+        query
+            .bind(limit.clamp(1, 2000) as u64)
+            .fetch_all::<StorageScanEventRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn count_item_events(&self, filter: &ItemEventFilter) -> Result<u64> {
+        let (clause, values) = item_event_filter(filter);
+        let mut query = self
+            .client
+            .query(&format!("SELECT count() FROM item_events WHERE {}", clause));
+        for value in values {
+            query = query.bind(value);
+        }
+        query.fetch_one::<u64>().await.map_err(Into::into)
+    }
+
+    pub async fn fetch_item_events_page(
+        &self,
+        filter: &ItemEventFilter,
+        offset: usize,
+        limit: usize,
+        fields: &FieldSelection,
+    ) -> Result<Vec<ItemEventRow>> {
+        let (clause, values) = item_event_filter(filter);
+        let mut query = self.client.query(&format!(
+            "SELECT {} FROM item_events WHERE {} ORDER BY event_time DESC, event_id LIMIT ? OFFSET ?",
+            item_event_columns(fields),
+            clause
+        ));
+        for value in values {
+            query = query.bind(value);
+        }
+        query
+            .bind(limit.clamp(1, 2000) as u64)
+            .bind(offset as u64)
+            .fetch_all::<ItemEventRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_daily_acquired_totals(
+        &self,
+        date: &str,
+        player_uuids: &[String],
+        item_ids: &[String],
+    ) -> Result<Vec<PlayerItemDailyTotal>> {
+        if player_uuids.is_empty() || item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.client
+            .query("SELECT player_uuid, any(player_name), item_id, sum(count) FROM item_events WHERE event_type = 'ACQUIRE' AND toDate(event_time) = toDate(?) AND has(?, player_uuid) AND has(?, item_id) GROUP BY player_uuid, item_id")
+            .bind(date)
+            .bind(player_uuids)
+            .bind(item_ids)
+            .fetch_all::<PlayerItemDailyTotal>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_player_event_span(&self, player_uuid: &str) -> Result<Option<PlayerEventSpan>> {
+        self.client
+            .query("SELECT argMax(player_name, event_time), count(), toInt64(toUnixTimestamp64Milli(min(event_time))), toInt64(toUnixTimestamp64Milli(max(event_time))) FROM item_events WHERE player_uuid = ? GROUP BY player_uuid")
+            .bind(player_uuid)
+            .fetch_optional::<PlayerEventSpan>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_player_acquired_items(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+        limit: usize,
+    ) -> Result<Vec<PlayerItemAcquired>> {
+        self.client
+            .query("SELECT item_id, count(), sum(count) AS total FROM item_events WHERE player_uuid = ? AND event_type = 'ACQUIRE' AND event_time >= fromUnixTimestamp64Milli(toInt64(?)) GROUP BY item_id ORDER BY total DESC, item_id LIMIT ?")
+            .bind(player_uuid)
+            .bind(since_ms)
+            .bind(limit as u64)
+            .fetch_all::<PlayerItemAcquired>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_player_recent_events(
+        &self,
+        player_uuid: &str,
+        limit: usize,
+    ) -> Result<Vec<ItemEventRow>> {
+        self.client
+            .query(&format!(
+                "SELECT {} FROM item_events WHERE player_uuid = ? ORDER BY event_time DESC, event_id LIMIT ?",
+                item_event_columns(&FieldSelection::default())
+            ))
+            .bind(player_uuid)
+            .bind(limit as u64)
+            .fetch_all::<ItemEventRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_events_by_trace(
+        &self,
+        trace_id: &str,
+        item_fingerprint: &str,
+        limit: usize,
+    ) -> Result<Vec<ItemEventRow>> {
+        let mut matches = Vec::new();
+        if !trace_id.is_empty() {
+            matches.push("trace_id = ?");
+        }
+        if !item_fingerprint.is_empty() {
+            matches.push("item_fingerprint = ?");
+        }
+        if matches.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut query = self.client.query(&format!(
+            "SELECT {} FROM item_events WHERE event_type IN ('TRANSFER', 'ACQUIRE') AND ({}) ORDER BY event_time, event_id LIMIT ?",
+            item_event_columns(&FieldSelection::default()),
+            matches.join(" OR ")
+        ));
+        if !trace_id.is_empty() {
+            query = query.bind(trace_id);
+        }
+        if !item_fingerprint.is_empty() {
+            query = query.bind(item_fingerprint);
+        }
+        query
+            .bind(limit as u64)
+            .fetch_all::<ItemEventRow>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_fingerprint_usage(&self, since_ms: i64) -> Result<Vec<FingerprintUsage>> {
+        self.client
+            .query("SELECT server_id, count() AS events, countIf(item_fingerprint = '') AS missing FROM item_events WHERE event_type IN ('TRANSFER', 'ACQUIRE') AND event_time >= fromUnixTimestamp64Milli(toInt64(?)) GROUP BY server_id ORDER BY server_id")
+            .bind(since_ms)
+            .fetch_all::<FingerprintUsage>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_fingerprint_collisions(
+        &self,
+        since_ms: i64,
+        per_server: usize,
+    ) -> Result<Vec<FingerprintCollision>> {
+        self.client
+            .query("SELECT server_id, if(item_fingerprint = '', concat(item_id, ':*'), item_fingerprint) AS fingerprint, item_fingerprint = '' AS synthesized, count() AS events, uniqExact(player_uuid) AS players, uniqExact(item_id) AS item_ids FROM item_events WHERE event_type IN ('TRANSFER', 'ACQUIRE') AND event_time >= fromUnixTimestamp64Milli(toInt64(?)) GROUP BY server_id, fingerprint, synthesized HAVING players > 1 OR item_ids > 1 ORDER BY server_id, players DESC, events DESC, fingerprint LIMIT ? BY server_id")
+            .bind(since_ms)
+            .bind(per_server as u64)
+            .fetch_all::<FingerprintCollision>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_item_ids_seen_since(&self, since_ms: i64) -> Result<Vec<String>> {
+        self.client
+            .query("SELECT DISTINCT item_id FROM item_events WHERE event_time >= fromUnixTimestamp64Milli(?)")
+            .bind(since_ms)
+            .fetch_all::<String>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn fetch_item_count_distributions(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        item_ids: &[String],
+    ) -> Result<Vec<ItemCountDistribution>> {
+        if item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.client
+            .query("SELECT item_id, count(), quantile(0.5)(total), quantile(0.9)(total), quantile(0.99)(total), max(total) FROM (SELECT item_id, player_uuid, toDate(event_time) AS day, sum(count) AS total FROM item_events WHERE event_type = 'ACQUIRE' AND toDate(event_time) BETWEEN toDate(?) AND toDate(?) AND has(?, item_id) GROUP BY item_id, player_uuid, day) GROUP BY item_id")
+            .bind(from_date)
+            .bind(to_date)
+            .bind(item_ids)
+            .fetch_all::<ItemCountDistribution>()
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn ping(&self) -> Result<()> {
         let _: u8 = self.client.query("SELECT toUInt8(1)").fetch_one().await?;
         Ok(())
     }
+
+    pub async fn check_permissions(&self) -> Result<ClickhousePreflight> {
+        self.ping().await?;
+        let steps = [
+            (
+                "CREATE",
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {} (checked_at DateTime64(3), note String) ENGINE = MergeTree ORDER BY checked_at TTL toDateTime(checked_at) + INTERVAL 1 DAY",
+                    PREFLIGHT_TABLE
+                ),
+            ),
+            (
+                "INSERT",
+                format!("INSERT INTO {} VALUES (now64(3), 'preflight')", PREFLIGHT_TABLE),
+            ),
+            ("SELECT", format!("SELECT count() FROM {}", PREFLIGHT_TABLE)),
+            (
+                "ALTER",
+                format!(
+                    "ALTER TABLE {} COMMENT COLUMN note 'lattice permission preflight'",
+                    PREFLIGHT_TABLE
+                ),
+            ),
+        ];
+        let mut checks = Vec::with_capacity(steps.len());
+        for (privilege, sql) in steps {
+            let (status, error) = match self.client.query(&sql).execute().await {
+                Ok(()) => ("granted", None),
+                Err(err) => {
+                    let message = err.to_string();
+                    let denied = message.contains("ACCESS_DENIED")
+                        || message.contains("Not enough privileges");
+                    (if denied { "missing" } else { "unknown" }, Some(message))
+                }
+            };
+            checks.push(PermissionCheck {
+                privilege: privilege.to_string(),
+                status: status.to_string(),
+                error,
+            });
+        }
+        let missing: Vec<String> = checks
+            .iter()
+            .filter(|check| check.status == "missing")
+            .map(|check| check.privilege.clone())
+            .collect();
+        Ok(ClickhousePreflight {
+            database: self.database.clone(),
+            ok: checks.iter().all(|check| check.status == "granted"),
+            missing,
+            checks,
+        })
+    }
+
+    pub async fn round_trip_check(&self) -> Result<()> {
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {} (checked_at DateTime64(3), note String) ENGINE = MergeTree ORDER BY checked_at TTL toDateTime(checked_at) + INTERVAL 1 DAY",
+            PREFLIGHT_TABLE
+        );
+        self.client.query(&create).execute().await?;
+        let marker = format!("selftest-{}", uuid::Uuid::new_v4());
+        let insert = format!("INSERT INTO {} VALUES (now64(3), ?)", PREFLIGHT_TABLE);
+        self.client.query(&insert).bind(&marker).execute().await?;
+        let select = format!("SELECT count() FROM {} WHERE note = ?", PREFLIGHT_TABLE);
+        let found: u64 = self.client.query(&select).bind(&marker).fetch_one().await?;
+        if found == 0 {
+            return Err(anyhow!("selftest row was written but not read back"));
+        }
+        Ok(())
+    }
+
+    pub async fn fetch_partition_stats(&self) -> Result<Vec<PartitionStat>> {
+        self.client
+            .query("SELECT table, partition_id, count() AS parts, sum(rows) AS rows, sum(bytes_on_disk) AS bytes_on_disk FROM system.parts WHERE database = ? AND active AND table IN ('item_events', 'custom_events', 'anomalies') GROUP BY table, partition_id ORDER BY table, partition_id")
+            .bind(&self.database)
+            .fetch_all::<PartitionStat>()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn optimize_partition(&self, table: &str, partition_id: &str) -> Result<()> {
+        if !MAINTAINED_TABLES.contains(&table) {
+            return Err(anyhow!("table {} is not eligible for maintenance", table));
+        }
+        let sql = format!("OPTIMIZE TABLE {} PARTITION ID ? FINAL", table);
+        self.client.query(&sql).bind(partition_id).execute().await?;
+        Ok(())
+    }
+
+    pub async fn drop_partition(&self, table: &str, partition_id: &str) -> Result<()> {
+        if !MAINTAINED_TABLES.contains(&table) {
+            return Err(anyhow!("table {} is not eligible for maintenance", table));
+        }
+        let sql = format!("ALTER TABLE {} DROP PARTITION ID ?", table);
+        self.client.query(&sql).bind(partition_id).execute().await?;
+        Ok(())
+    }
+
+    pub async fn fetch_storage_usage(&self) -> Result<StorageUsage> {
+        let database_bytes = self
+            .client
+            .query("SELECT sum(bytes_on_disk) FROM system.parts WHERE database = ? AND active")
+            .bind(&self.database)
+            .fetch_one::<u64>()
+            .await?;
+        let (disk_free_bytes, disk_total_bytes) = self
+            .client
+            .query("SELECT sum(free_space), sum(total_space) FROM system.disks")
+            .fetch_one::<(u64, u64)>()
+            .await?;
+        Ok(StorageUsage {
+            database_bytes,
+            disk_free_bytes,
+            disk_total_bytes,
+        })
+    }
+
+    /// Rows per mirrored table on `date`; both sides are counted without `FINAL`, so
+    /// `clickhouse_dedup_events` tables may differ until their parts are merged.
+    async fn count_daily_rows(&self, date: &str) -> Result<Vec<u64>> {
+        let mut counts = Vec::with_capacity(MAINTAINED_TABLES.len());
+        for table in MAINTAINED_TABLES {
+            let sql = format!(
+                "SELECT count() FROM {} WHERE toDate(event_time) = toDate(?)",
+                table
+            );
+            let count = self.client.query(&sql).bind(date).fetch_one::<u64>().await?;
+            counts.push(count);
+        }
+        Ok(counts)
+    }
+
+    pub async fn compare_shadow_counts(&self, date: &str) -> Result<Option<ShadowConsistency>> {
+        let Some(shadow) = &self.shadow else {
+            return Ok(None);
+        };
+        let primary = self.count_daily_rows(date).await?;
+        let mirrored = shadow
+            .repo
+            .count_daily_rows(date)
+            .await
+            .map_err(|err| anyhow!("shadow clickhouse: {}", err))?;
+        let tables = MAINTAINED_TABLES
+            .iter()
+            .zip(primary.into_iter().zip(mirrored))
+            .map(|(table, (primary, shadow))| ShadowTableCount {
+                table: table.to_string(),
+                primary,
+                shadow,
+            })
+            .collect();
+        Ok(Some(ShadowConsistency {
+            date: date.to_string(),
+            tables,
+            failed_batches: shadow.failed_batches.load(Ordering::Relaxed),
+            last_error: shadow.last_error.lock().ok().and_then(|err| err.clone()),
+        }))
+    }
 }
 
 #[async_trait]
@@ -275,6 +1270,10 @@ impl EventRepository for ClickhouseRepo {
         ClickhouseRepo::insert_events(self, events).await
     }
 
+    async fn insert_custom_events(&self, events: &[IngestEvent]) -> Result<()> {
+        ClickhouseRepo::insert_custom_events(self, events).await
+    }
+
     async fn fetch_storage_scan_events(
         &self,
         date: &str,
@@ -298,13 +1297,111 @@ impl EventRepository for ClickhouseRepo {
         item: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
+    ) -> Result<Vec<StorageScanEventRow>> {
+        ClickhouseRepo::fetch_storage_scan_events_page(self, date, item, offset, limit, fields)
+            .await
+    }
+
+    async fn fetch_storage_scan_events_after(
+        &self,
+        date: &str,
+        item: Option<&str>,
+        after: Option<(OffsetDateTime, &str)>,
+        limit: usize,
     ) -> Result<Vec<StorageScanEventRow>> {
-        ClickhouseRepo::fetch_storage_scan_events_page(self, date, item, offset, limit).await
+        ClickhouseRepo::fetch_storage_scan_events_after(self, date, item, after, limit).await
+    }
+
+    async fn count_item_events(&self, filter: &ItemEventFilter) -> Result<u64> {
+        ClickhouseRepo::count_item_events(self, filter).await
+    }
+
+    async fn fetch_item_events_page(
+        &self,
+        filter: &ItemEventFilter,
+        offset: usize,
+        limit: usize,
+        fields: &FieldSelection,
+    ) -> Result<Vec<ItemEventRow>> {
+        ClickhouseRepo::fetch_item_events_page(self, filter, offset, limit, fields).await
     }
 
     async fn ping(&self) -> Result<()> {
         ClickhouseRepo::ping(self).await
     }
+
+    async fn check_permissions(&self) -> Result<ClickhousePreflight> {
+        ClickhouseRepo::check_permissions(self).await
+    }
+
+    async fn round_trip_check(&self) -> Result<()> {
+        ClickhouseRepo::round_trip_check(self).await
+    }
+
+    async fn fetch_daily_acquired_totals(
+        &self,
+        date: &str,
+        player_uuids: &[String],
+        item_ids: &[String],
+    ) -> Result<Vec<PlayerItemDailyTotal>> {
+        ClickhouseRepo::fetch_daily_acquired_totals(self, date, player_uuids, item_ids).await
+    }
+
+    async fn fetch_item_ids_seen_since(&self, since_ms: i64) -> Result<Vec<String>> {
+        ClickhouseRepo::fetch_item_ids_seen_since(self, since_ms).await
+    }
+
+    async fn fetch_item_count_distributions(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        item_ids: &[String],
+    ) -> Result<Vec<ItemCountDistribution>> {
+        ClickhouseRepo::fetch_item_count_distributions(self, from_date, to_date, item_ids).await
+    }
+
+    async fn fetch_player_event_span(&self, player_uuid: &str) -> Result<Option<PlayerEventSpan>> {
+        ClickhouseRepo::fetch_player_event_span(self, player_uuid).await
+    }
+
+    async fn fetch_player_acquired_items(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+        limit: usize,
+    ) -> Result<Vec<PlayerItemAcquired>> {
+        ClickhouseRepo::fetch_player_acquired_items(self, player_uuid, since_ms, limit).await
+    }
+
+    async fn fetch_player_recent_events(
+        &self,
+        player_uuid: &str,
+        limit: usize,
+    ) -> Result<Vec<ItemEventRow>> {
+        ClickhouseRepo::fetch_player_recent_events(self, player_uuid, limit).await
+    }
+
+    async fn fetch_events_by_trace(
+        &self,
+        trace_id: &str,
+        item_fingerprint: &str,
+        limit: usize,
+    ) -> Result<Vec<ItemEventRow>> {
+        ClickhouseRepo::fetch_events_by_trace(self, trace_id, item_fingerprint, limit).await
+    }
+
+    async fn fetch_fingerprint_usage(&self, since_ms: i64) -> Result<Vec<FingerprintUsage>> {
+        ClickhouseRepo::fetch_fingerprint_usage(self, since_ms).await
+    }
+
+    async fn fetch_fingerprint_collisions(
+        &self,
+        since_ms: i64,
+        per_server: usize,
+    ) -> Result<Vec<FingerprintCollision>> {
+        ClickhouseRepo::fetch_fingerprint_collisions(self, since_ms, per_server).await
+    }
 }
 
 #[async_trait]
@@ -317,6 +1414,10 @@ impl AnomalyRepository for ClickhouseRepo {
         ClickhouseRepo::fetch_anomalies(self, date, player).await
     }
 
+    async fn fetch_summary_between(&self, from_ms: i64, to_ms: i64) -> Result<ReportSummary> {
+        ClickhouseRepo::fetch_summary_between(self, from_ms, to_ms).await
+    }
+
     async fn count_anomalies(&self, date: &str, player: Option<&str>) -> Result<u64> {
         ClickhouseRepo::count_anomalies(self, date, player).await
     }
@@ -327,11 +1428,119 @@ impl AnomalyRepository for ClickhouseRepo {
         player: Option<&str>,
         offset: usize,
         limit: usize,
+        fields: &FieldSelection,
+    ) -> Result<Vec<AnomalyRow>> {
+        ClickhouseRepo::fetch_anomalies_page(self, date, player, offset, limit, fields).await
+    }
+
+    async fn fetch_anomalies_after(
+        &self,
+        date: &str,
+        player: Option<&str>,
+        after: Option<&AnomalyAckKey>,
+        limit: usize,
     ) -> Result<Vec<AnomalyRow>> {
-        ClickhouseRepo::fetch_anomalies_page(self, date, player, offset, limit).await
+        ClickhouseRepo::fetch_anomalies_after(self, date, player, after, limit).await
+    }
+
+    async fn fetch_anomalies_at(&self, event_time_ms: i64) -> Result<Vec<AnomalyRow>> {
+        ClickhouseRepo::fetch_anomalies_at(self, event_time_ms).await
     }
 
     async fn fetch_summary(&self, date: &str) -> Result<ReportSummary> {
         ClickhouseRepo::fetch_summary(self, date).await
     }
+
+    async fn fetch_top_players(
+        &self,
+        date: &str,
+        limit: usize,
+    ) -> Result<Vec<PlayerAnomalyCount>> {
+        ClickhouseRepo::fetch_top_players(self, date, limit).await
+    }
+
+    async fn rollup_daily_summary(&self, date: &str) -> Result<()> {
+        ClickhouseRepo::rollup_daily_summary(self, date).await
+    }
+
+    async fn fetch_daily_summary(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        server_id: Option<&str>,
+        rule_id: Option<&str>,
+    ) -> Result<Vec<AnomalyDailySummaryRow>> {
+        ClickhouseRepo::fetch_daily_summary(self, from_date, to_date, server_id, rule_id).await
+    }
+
+    async fn ack_anomalies(&self, request: &AnomalyAckRequest, acked_at_ms: i64) -> Result<u64> {
+        ClickhouseRepo::ack_anomalies(self, request, acked_at_ms).await
+    }
+
+    async fn ack_anomaly(
+        &self,
+        key: &AnomalyAckKey,
+        acked_at_ms: i64,
+        note: &str,
+        acked_by: &str,
+    ) -> Result<()> {
+        ClickhouseRepo::ack_anomaly(self, key, acked_at_ms, note, acked_by).await
+    }
+
+    async fn fetch_acked_keys(&self, date: &str) -> Result<Vec<AnomalyAckKey>> {
+        ClickhouseRepo::fetch_acked_keys(self, date).await
+    }
+
+    async fn mark_anomalies_seen(
+        &self,
+        ids: &[String],
+        seen_at_ms: i64,
+        seen_by: &str,
+    ) -> Result<()> {
+        ClickhouseRepo::mark_anomalies_seen(self, ids, seen_at_ms, seen_by).await
+    }
+
+    async fn fetch_seen_ids(&self, date: &str) -> Result<Vec<String>> {
+        ClickhouseRepo::fetch_seen_ids(self, date).await
+    }
+
+    async fn fetch_item_anomaly_stats(
+        &self,
+        from_date: &str,
+        to_date: &str,
+        rule_ids: &[String],
+    ) -> Result<Vec<ItemAnomalyStat>> {
+        ClickhouseRepo::fetch_item_anomaly_stats(self, from_date, to_date, rule_ids).await
+    }
+
+    async fn fetch_player_rule_counts(
+        &self,
+        player_uuid: &str,
+        since_ms: i64,
+    ) -> Result<Vec<RuleAnomalyCount>> {
+        ClickhouseRepo::fetch_player_rule_counts(self, player_uuid, since_ms).await
+    }
+}
+
+#[async_trait]
+impl MaintenanceRepository for ClickhouseRepo {
+    async fn fetch_partition_stats(&self) -> Result<Vec<PartitionStat>> {
+        ClickhouseRepo::fetch_partition_stats(self).await
+    }
+
+    async fn optimize_partition(&self, table: &str, partition_id: &str) -> Result<()> {
+        ClickhouseRepo::optimize_partition(self, table, partition_id).await
+    }
+
+    async fn drop_partition(&self, table: &str, partition_id: &str) -> Result<()> {
+        ClickhouseRepo::drop_partition(self, table, partition_id).await
+    }
+
+    async fn fetch_storage_usage(&self) -> Result<StorageUsage> {
+        ClickhouseRepo::fetch_storage_usage(self).await
+    }
+
+    async fn compare_shadow_counts(&self, date: &str) -> Result<Option<ShadowConsistency>> {
+        ClickhouseRepo::compare_shadow_counts(self, date).await
+    }
 }
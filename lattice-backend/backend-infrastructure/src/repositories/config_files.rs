@@ -1,23 +1,119 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use chrono::NaiveDate;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 
 use backend_domain::{
+    AnomalySuppression,
+    ApiToken,
+    CompositeRule,
     ConfigRepository,
+    DeadLetterBatch,
+    DetectionRule,
     ItemRegistryEntry,
     KeyItemRule,
     ModConfigAck,
     ModConfigEnvelope,
+    OriginWhitelist,
+    PlayerBan,
+    PlayerQuarantine,
+    PlayerTeam,
     RconConfig,
+    ReportFile,
+    RuleRevision,
+    StorageFinding,
 };
 
-pub struct ConfigFileRepository;
+/// Stores rcon, mod-config, storage-finding, suppression, dead-letter, ban, quarantine and rule
+/// revision files next to the config file, and manages the generated reports in `report_dir`.
+pub struct ConfigFileRepository {
+    config_dir: PathBuf,
+}
 
 impl ConfigFileRepository {
+    /// Uses the directory of `LATTICE_CONFIG` (default `./config.toml`).
     pub fn new() -> Self {
-        Self
+        Self::with_config_dir(resolve_config_dir())
+    }
+
+    pub fn with_config_dir(config_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            config_dir: config_dir.into(),
+        }
+    }
+
+    /// Directory holding the config file `config_path`; `.` when it has no parent.
+    pub fn for_config_path(config_path: impl AsRef<Path>) -> Self {
+        Self::with_config_dir(config_dir_of(config_path.as_ref()))
+    }
+
+    fn rcon_path(&self) -> PathBuf {
+        self.config_dir.join("rcon.toml")
+    }
+
+    fn storage_findings_path(&self) -> PathBuf {
+        self.config_dir.join("storage_findings.json")
+    }
+
+    fn suppressions_path(&self) -> PathBuf {
+        self.config_dir.join("suppressions.json")
+    }
+
+    /// Write-ahead log of the dead-letter queue: one JSON batch per line, appended as writes
+    /// fail and rewritten whole after a replay or when the oldest rows are dropped.
+    fn dead_letters_path(&self) -> PathBuf {
+        self.config_dir.join("dead_letters.wal")
+    }
+
+    /// Whole-file queue written before the log existed; read once and folded into the log.
+    fn legacy_dead_letters_path(&self) -> PathBuf {
+        self.config_dir.join("dead_letters.json")
+    }
+
+    fn bans_path(&self) -> PathBuf {
+        self.config_dir.join("bans.json")
+    }
+
+    fn quarantines_path(&self) -> PathBuf {
+        self.config_dir.join("quarantines.json")
+    }
+
+    fn api_tokens_path(&self) -> PathBuf {
+        self.config_dir.join("api_tokens.json")
+    }
+
+    fn player_teams_path(&self) -> PathBuf {
+        self.config_dir.join("player_teams.json")
+    }
+
+    fn rule_revisions_path(&self) -> PathBuf {
+        self.config_dir.join("rule_revisions.json")
+    }
+
+    fn origin_whitelist_path(&self) -> PathBuf {
+        self.config_dir.join("origin_whitelist.json")
+    }
+
+    fn selftest_path(&self) -> PathBuf {
+        self.config_dir.join("selftest.json")
+    }
+
+    fn mod_config_dir(&self) -> PathBuf {
+        self.config_dir.join("mod-config")
+    }
+
+    fn mod_config_path(&self, server_id: &str) -> PathBuf {
+        self.mod_config_dir()
+            .join(format!("{}.json", sanitize_server_id(server_id)))
+    }
+
+    fn mod_config_ack_path(&self, server_id: &str) -> PathBuf {
+        self.mod_config_dir()
+            .join("acks")
+            .join(format!("{}.json", sanitize_server_id(server_id)))
     }
 }
 
@@ -27,17 +123,16 @@ impl Default for ConfigFileRepository {
     }
 }
 
-fn resolve_config_dir() -> std::path::PathBuf {
+fn resolve_config_dir() -> PathBuf {
     let path = std::env::var("LATTICE_CONFIG").unwrap_or_else(|_| "./config.toml".to_string());
-    let file_path = Path::new(&path);
-    file_path
-        .parent()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
+    config_dir_of(Path::new(&path))
 }
 
-fn resolve_rcon_path() -> std::path::PathBuf {
-    resolve_config_dir().join("rcon.toml")
+fn config_dir_of(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
 }
 
 fn sanitize_server_id(server_id: &str) -> String {
@@ -57,18 +152,39 @@ fn sanitize_server_id(server_id: &str) -> String {
         .collect()
 }
 
-fn resolve_mod_config_dir() -> std::path::PathBuf {
-    resolve_config_dir().join("mod-config")
+fn is_report_date(value: &str) -> bool {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
 }
 
-fn resolve_mod_config_path(server_id: &str) -> std::path::PathBuf {
-    resolve_mod_config_dir().join(format!("{}.json", sanitize_server_id(server_id)))
+fn modified_ms(metadata: &std::fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
 }
 
-fn resolve_mod_config_ack_path(server_id: &str) -> std::path::PathBuf {
-    resolve_mod_config_dir()
-        .join("acks")
-        .join(format!("{}.json", sanitize_server_id(server_id)))
+/// Total size and number of player pages under a report's `{date}/` directory.
+async fn report_dir_usage(dir: &Path) -> anyhow::Result<(u64, usize)> {
+    let mut size = 0;
+    let mut pages = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries = fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            size += metadata.len();
+            if current.ends_with("players") {
+                pages += 1;
+            }
+        }
+    }
+    Ok((size, pages))
 }
 
 #[async_trait]
@@ -93,6 +209,32 @@ impl ConfigRepository for ConfigFileRepository {
         Ok(())
     }
 
+    async fn load_detection_rules(&self, path: &str) -> anyhow::Result<Vec<DetectionRule>> {
+        if !Path::new(path).exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(path).await?;
+        let rules: Vec<DetectionRule> = if path.ends_with(".json") {
+            serde_json::from_str(&content)?
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+        Ok(rules)
+    }
+
+    async fn load_composite_rules(&self, path: &str) -> anyhow::Result<Vec<CompositeRule>> {
+        if !Path::new(path).exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(path).await?;
+        let rules: Vec<CompositeRule> = if path.ends_with(".json") {
+            serde_json::from_str(&content)?
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+        Ok(rules)
+    }
+
     async fn load_item_registry(&self, path: &str) -> anyhow::Result<Vec<ItemRegistryEntry>> {
         if !Path::new(path).exists() {
             return Ok(Vec::new());
@@ -114,7 +256,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn load_rcon_config(&self) -> anyhow::Result<RconConfig> {
-        let path = resolve_rcon_path();
+        let path = self.rcon_path();
         if !path.exists() {
             return Ok(RconConfig::default());
         }
@@ -124,7 +266,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn save_rcon_config(&self, config: &RconConfig) -> anyhow::Result<()> {
-        let path = resolve_rcon_path();
+        let path = self.rcon_path();
         if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
                 fs::create_dir_all(parent).await?;
@@ -136,7 +278,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn load_mod_config(&self, server_id: &str) -> anyhow::Result<Option<ModConfigEnvelope>> {
-        let path = resolve_mod_config_path(server_id);
+        let path = self.mod_config_path(server_id);
         if !path.exists() {
             return Ok(None);
         }
@@ -146,7 +288,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn save_mod_config(&self, envelope: &ModConfigEnvelope) -> anyhow::Result<()> {
-        let path = resolve_mod_config_path(&envelope.server_id);
+        let path = self.mod_config_path(&envelope.server_id);
         if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
                 fs::create_dir_all(parent).await?;
@@ -158,7 +300,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn load_mod_config_ack(&self, server_id: &str) -> anyhow::Result<Option<ModConfigAck>> {
-        let path = resolve_mod_config_ack_path(server_id);
+        let path = self.mod_config_ack_path(server_id);
         if !path.exists() {
             return Ok(None);
         }
@@ -168,7 +310,7 @@ impl ConfigRepository for ConfigFileRepository {
     }
 
     async fn save_mod_config_ack(&self, ack: &ModConfigAck) -> anyhow::Result<()> {
-        let path = resolve_mod_config_ack_path(&ack.server_id);
+        let path = self.mod_config_ack_path(&ack.server_id);
         if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
                 fs::create_dir_all(parent).await?;
@@ -178,4 +320,488 @@ impl ConfigRepository for ConfigFileRepository {
         fs::write(path, content).await?;
         Ok(())
     }
+
+    async fn load_storage_findings(&self) -> anyhow::Result<Vec<StorageFinding>> {
+        let path = self.storage_findings_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let findings: Vec<StorageFinding> = serde_json::from_str(&content)?;
+        Ok(findings)
+    }
+
+    async fn save_storage_findings(&self, findings: &[StorageFinding]) -> anyhow::Result<()> {
+        let path = self.storage_findings_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string(findings)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_suppressions(&self) -> anyhow::Result<Vec<AnomalySuppression>> {
+        let path = self.suppressions_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let suppressions: Vec<AnomalySuppression> = serde_json::from_str(&content)?;
+        Ok(suppressions)
+    }
+
+    async fn save_suppressions(&self, suppressions: &[AnomalySuppression]) -> anyhow::Result<()> {
+        let path = self.suppressions_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(suppressions)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_dead_letters(&self) -> anyhow::Result<Vec<DeadLetterBatch>> {
+        let mut batches = Vec::new();
+        let legacy = self.legacy_dead_letters_path();
+        if legacy.exists() {
+            let content = fs::read_to_string(&legacy).await?;
+            batches = serde_json::from_str::<Vec<DeadLetterBatch>>(&content)?;
+        }
+        let path = self.dead_letters_path();
+        if path.exists() {
+            let content = fs::read_to_string(&path).await?;
+            let mut lines = content.split_inclusive('\n').peekable();
+            while let Some(line) = lines.next() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<DeadLetterBatch>(line) {
+                    Ok(batch) => batches.push(batch),
+                    // A crash mid-append leaves a last line without its newline; that batch
+                    // was never acknowledged as queued.
+                    Err(_) if lines.peek().is_none() && !line.ends_with('\n') => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(batches)
+    }
+
+    async fn append_dead_letters(&self, batch: &DeadLetterBatch) -> anyhow::Result<()> {
+        let path = self.dead_letters_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let mut line = serde_json::to_string(batch)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    async fn save_dead_letters(&self, batches: &[DeadLetterBatch]) -> anyhow::Result<()> {
+        let path = self.dead_letters_path();
+        let legacy = self.legacy_dead_letters_path();
+        if batches.is_empty() {
+            for path in [path, legacy] {
+                if path.exists() {
+                    fs::remove_file(path).await?;
+                }
+            }
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let mut content = String::new();
+        for batch in batches {
+            content.push_str(&serde_json::to_string(batch)?);
+            content.push('\n');
+        }
+        // Write beside the log and rename over it, so a crash keeps either the old or new queue.
+        let staging = path.with_extension("wal.tmp");
+        let mut file = fs::File::create(&staging).await?;
+        file.write_all(content.as_bytes()).await?;
+        file.sync_data().await?;
+        fs::rename(&staging, &path).await?;
+        if legacy.exists() {
+            fs::remove_file(legacy).await?;
+        }
+        Ok(())
+    }
+
+    async fn load_bans(&self) -> anyhow::Result<Vec<PlayerBan>> {
+        let path = self.bans_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let bans: Vec<PlayerBan> = serde_json::from_str(&content)?;
+        Ok(bans)
+    }
+
+    async fn save_bans(&self, bans: &[PlayerBan]) -> anyhow::Result<()> {
+        let path = self.bans_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(bans)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_quarantines(&self) -> anyhow::Result<Vec<PlayerQuarantine>> {
+        let path = self.quarantines_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let quarantines: Vec<PlayerQuarantine> = serde_json::from_str(&content)?;
+        Ok(quarantines)
+    }
+
+    async fn save_quarantines(&self, quarantines: &[PlayerQuarantine]) -> anyhow::Result<()> {
+        let path = self.quarantines_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(quarantines)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_api_tokens(&self) -> anyhow::Result<Vec<ApiToken>> {
+        let path = self.api_tokens_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let tokens: Vec<ApiToken> = serde_json::from_str(&content)?;
+        Ok(tokens)
+    }
+
+    async fn save_api_tokens(&self, tokens: &[ApiToken]) -> anyhow::Result<()> {
+        let path = self.api_tokens_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(tokens)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_player_teams(&self) -> anyhow::Result<Vec<PlayerTeam>> {
+        let path = self.player_teams_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let teams: Vec<PlayerTeam> = serde_json::from_str(&content)?;
+        Ok(teams)
+    }
+
+    async fn save_player_teams(&self, teams: &[PlayerTeam]) -> anyhow::Result<()> {
+        let path = self.player_teams_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(teams)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_rule_revisions(&self) -> anyhow::Result<Vec<RuleRevision>> {
+        let path = self.rule_revisions_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let revisions: Vec<RuleRevision> = serde_json::from_str(&content)?;
+        Ok(revisions)
+    }
+
+    async fn save_rule_revisions(&self, revisions: &[RuleRevision]) -> anyhow::Result<()> {
+        let path = self.rule_revisions_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(revisions)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn load_origin_whitelist(&self) -> anyhow::Result<OriginWhitelist> {
+        let path = self.origin_whitelist_path();
+        if !path.exists() {
+            return Ok(OriginWhitelist::default());
+        }
+        let content = fs::read_to_string(&path).await?;
+        let whitelist: OriginWhitelist = serde_json::from_str(&content)?;
+        Ok(whitelist)
+    }
+
+    async fn save_origin_whitelist(&self, whitelist: &OriginWhitelist) -> anyhow::Result<()> {
+        let path = self.origin_whitelist_path();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let content = serde_json::to_string_pretty(whitelist)?;
+        fs::write(path, content).await?;
+        Ok(())
+    }
+
+    async fn round_trip_check(&self) -> anyhow::Result<()> {
+        let path = self.selftest_path();
+        let marker = serde_json::json!({ "selftest": uuid::Uuid::new_v4().to_string() });
+        fs::write(&path, serde_json::to_string(&marker)?).await?;
+        let content = fs::read_to_string(&path).await;
+        fs::remove_file(&path).await?;
+        let loaded: serde_json::Value = serde_json::from_str(&content?)?;
+        if loaded != marker {
+            return Err(anyhow::anyhow!("{} read back different content", path.display()));
+        }
+        Ok(())
+    }
+
+    async fn check_report_dir(&self, report_dir: &str) -> anyhow::Result<()> {
+        let dir = Path::new(report_dir);
+        fs::create_dir_all(dir).await?;
+        let probe = dir.join(format!(".lattice-write-check-{}", uuid::Uuid::new_v4()));
+        fs::write(&probe, b"ok").await?;
+        fs::remove_file(&probe).await?;
+        Ok(())
+    }
+
+    async fn list_reports(&self, report_dir: &str) -> anyhow::Result<Vec<ReportFile>> {
+        let dir = Path::new(report_dir);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut reports: BTreeMap<String, ReportFile> = BTreeMap::new();
+        let mut entries = fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let metadata = entry.metadata().await?;
+            let (date, size, pages) = if metadata.is_dir() {
+                let (size, pages) = report_dir_usage(&entry.path()).await?;
+                (name, size, pages)
+            } else {
+                match name.strip_suffix(".html") {
+                    Some(date) => (date.to_string(), metadata.len(), 0),
+                    None => continue,
+                }
+            };
+            if !is_report_date(&date) {
+                continue;
+            }
+            let report = reports.entry(date.clone()).or_insert_with(|| ReportFile {
+                date,
+                size_bytes: 0,
+                player_pages: 0,
+                modified_ms: 0,
+            });
+            report.size_bytes += size;
+            report.player_pages += pages;
+            report.modified_ms = report.modified_ms.max(modified_ms(&metadata));
+        }
+        Ok(reports.into_values().rev().collect())
+    }
+
+    async fn delete_report(&self, report_dir: &str, date: &str) -> anyhow::Result<bool> {
+        if !is_report_date(date) {
+            return Ok(false);
+        }
+        let dir = Path::new(report_dir);
+        let page = dir.join(format!("{}.html", date));
+        let pages_dir = dir.join(date);
+        let mut deleted = false;
+        if page.exists() {
+            fs::remove_file(&page).await?;
+            deleted = true;
+        }
+        if pages_dir.is_dir() {
+            fs::remove_dir_all(&pages_dir).await?;
+            deleted = true;
+        }
+        Ok(deleted)
+    }
+
+    async fn read_report_file(
+        &self,
+        report_dir: &str,
+        file: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let path = Path::new(report_dir).join(file);
+        match fs::read_to_string(&path).await {
+            Ok(html) => Ok(Some(html)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn write_report_file(
+        &self,
+        report_dir: &str,
+        file: &str,
+        contents: &str,
+    ) -> anyhow::Result<()> {
+        let path = Path::new(report_dir).join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, contents).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::Scenario;
+
+    fn scratch_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("lattice-config-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn batch(item_id: &str) -> DeadLetterBatch {
+        DeadLetterBatch::Events(
+            Scenario::new()
+                .acquires_without_origin(item_id, 1)
+                .events()
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn items(batches: &[DeadLetterBatch]) -> Vec<String> {
+        batches
+            .iter()
+            .flat_map(|batch| match batch {
+                DeadLetterBatch::Events(rows) | DeadLetterBatch::CustomEvents(rows) => rows
+                    .iter()
+                    .map(|row| row.item_id.clone())
+                    .collect::<Vec<_>>(),
+                DeadLetterBatch::Anomalies(rows) => {
+                    rows.iter().map(|row| row.item_id.clone()).collect()
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_torn_last_line_is_skipped_but_a_torn_middle_line_is_an_error() {
+        let dir = scratch_dir();
+        let repo = ConfigFileRepository::with_config_dir(&dir);
+        repo.append_dead_letters(&batch("minecraft:diamond"))
+            .await
+            .unwrap();
+        let torn = serde_json::to_string(&batch("minecraft:emerald")).unwrap();
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(repo.dead_letters_path())
+            .await
+            .unwrap();
+        file.write_all(&torn.as_bytes()[..torn.len() / 2])
+            .await
+            .unwrap();
+        drop(file);
+        assert_eq!(
+            items(&repo.load_dead_letters().await.unwrap()),
+            ["minecraft:diamond"]
+        );
+
+        // Appending after the torn line glues the next batch onto it; that line is corrupt.
+        fs::write(
+            repo.dead_letters_path(),
+            format!("{}\n{}\n", &torn[..torn.len() / 2], torn),
+        )
+        .await
+        .unwrap();
+        assert!(repo.load_dead_letters().await.is_err());
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_legacy_queue_is_read_first_and_folded_into_the_log() {
+        let dir = scratch_dir();
+        let repo = ConfigFileRepository::with_config_dir(&dir);
+        fs::create_dir_all(&dir).await.unwrap();
+        fs::write(
+            repo.legacy_dead_letters_path(),
+            serde_json::to_string(&[batch("minecraft:diamond")]).unwrap(),
+        )
+        .await
+        .unwrap();
+        repo.append_dead_letters(&batch("minecraft:emerald"))
+            .await
+            .unwrap();
+
+        let loaded = repo.load_dead_letters().await.unwrap();
+        assert_eq!(items(&loaded), ["minecraft:diamond", "minecraft:emerald"]);
+        repo.save_dead_letters(&loaded).await.unwrap();
+        assert!(!repo.legacy_dead_letters_path().exists());
+        assert_eq!(
+            items(&repo.load_dead_letters().await.unwrap()),
+            ["minecraft:diamond", "minecraft:emerald"]
+        );
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rewrites_go_through_a_staging_file_renamed_over_the_log() {
+        let dir = scratch_dir();
+        let repo = ConfigFileRepository::with_config_dir(&dir);
+        repo.append_dead_letters(&batch("minecraft:diamond"))
+            .await
+            .unwrap();
+        // A crash before the rename leaves the staging file behind; the log is still the old one.
+        let staging = repo.dead_letters_path().with_extension("wal.tmp");
+        fs::write(&staging, "{\"Events\":").await.unwrap();
+        assert_eq!(
+            items(&repo.load_dead_letters().await.unwrap()),
+            ["minecraft:diamond"]
+        );
+
+        repo.save_dead_letters(&[batch("minecraft:emerald")])
+            .await
+            .unwrap();
+        assert!(!staging.exists());
+        let content = fs::read_to_string(repo.dead_letters_path()).await.unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert_eq!(
+            items(&repo.load_dead_letters().await.unwrap()),
+            ["minecraft:emerald"]
+        );
+
+        repo.save_dead_letters(&[]).await.unwrap();
+        assert!(!repo.dead_letters_path().exists());
+        assert!(repo.load_dead_letters().await.unwrap().is_empty());
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
 }
@@ -1,7 +1,33 @@
+pub mod alert_proxy;
 pub mod alert_service;
+pub mod cluster_state_service;
+pub mod config_watch_service;
+pub mod dead_letter_service;
 pub mod health_service;
+pub mod ingest_recorder;
+pub mod ingest_monitor_service;
+pub mod maintenance_service;
+pub mod mqtt_service;
+pub mod redaction;
+pub mod report_player_pages;
 pub mod report_service;
+pub mod rule_hygiene_service;
+pub mod shadow_check_service;
+pub mod suppression_monitor_service;
 
+pub use alert_proxy::*;
 pub use alert_service::*;
+pub use cluster_state_service::*;
+pub use config_watch_service::*;
+pub use dead_letter_service::*;
 pub use health_service::*;
+pub use ingest_recorder::*;
+pub use ingest_monitor_service::*;
+pub use maintenance_service::*;
+pub use mqtt_service::*;
+pub use redaction::*;
+pub use report_player_pages::*;
 pub use report_service::*;
+pub use rule_hygiene_service::*;
+pub use shadow_check_service::*;
+pub use suppression_monitor_service::*;
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::{Client, Proxy, Url};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::tungstenite::handshake::client::{Request, Response};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use backend_domain::RuntimeConfig;
+
+/// Proxy schemes `alert_webhook_proxy` and team route `proxy` accept. `socks5` resolves the
+/// target locally, `socks5h` lets the proxy resolve it.
+const PROXY_SCHEMES: [&str; 3] = ["http", "socks5", "socks5h"];
+
+pub type AlertWebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Checks a proxy url before it is used, so a typo fails config loading instead of every alert.
+pub fn validate_proxy_url(proxy: &str) -> Result<(), String> {
+    let url = Url::parse(proxy).map_err(|err| format!("invalid proxy url: {}", err))?;
+    if !PROXY_SCHEMES.contains(&url.scheme()) {
+        return Err(format!(
+            "proxy url must use one of: {}",
+            PROXY_SCHEMES.join(", ")
+        ));
+    }
+    if url.host_str().is_none() || url.port_or_known_default().is_none() {
+        return Err("proxy url needs a host and port".to_string());
+    }
+    Ok(())
+}
+
+/// HTTP client for the alert target. Without `alert_webhook_proxy`, reqwest's usual environment
+/// proxies apply.
+pub fn alert_http_client(config: &RuntimeConfig) -> Result<Client> {
+    let mut builder =
+        Client::builder().timeout(Duration::from_secs(config.request_timeout_seconds.max(3)));
+    if let Some(proxy) = &config.alert_webhook_proxy {
+        builder = builder.proxy(Proxy::all(proxy.as_str())?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Opens a WebSocket to the alert target, tunnelled through `proxy` when one is set.
+pub async fn connect_alert_ws(
+    request: Request,
+    proxy: Option<&str>,
+) -> Result<(AlertWebSocket, Response)> {
+    let Some(proxy) = proxy else {
+        return Ok(tokio_tungstenite::connect_async(request).await?);
+    };
+    let uri = request.uri();
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow!("ws url has no host"))?
+        .trim_matches(['[', ']'])
+        .to_string();
+    let default_port = if uri.scheme_str() == Some("wss") {
+        443
+    } else {
+        80
+    };
+    let port = uri.port_u16().unwrap_or(default_port);
+    let stream = open_tunnel(proxy, &host, port).await?;
+    Ok(tokio_tungstenite::client_async_tls(request, stream).await?)
+}
+
+async fn open_tunnel(proxy: &str, host: &str, port: u16) -> Result<TcpStream> {
+    let url = Url::parse(proxy).context("invalid proxy url")?;
+    let proxy_host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("proxy url has no host"))?
+        .trim_matches(['[', ']']);
+    let proxy_port = url
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("proxy url has no port"))?;
+    let user = (!url.username().is_empty()).then(|| url.username().to_string());
+    let password = url.password().unwrap_or_default();
+    match url.scheme() {
+        "http" => {
+            let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+            http_connect(&mut stream, host, port, user.as_deref(), password).await?;
+            Ok(stream)
+        }
+        "socks5" | "socks5h" => {
+            let proxy_addr = (proxy_host, proxy_port);
+            let stream = if url.scheme() == "socks5" {
+                let target = tokio::net::lookup_host((host, port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| anyhow!("could not resolve {}", host))?;
+                match &user {
+                    Some(user) => {
+                        Socks5Stream::connect_with_password(proxy_addr, target, user, password)
+                            .await?
+                    }
+                    None => Socks5Stream::connect(proxy_addr, target).await?,
+                }
+            } else {
+                match &user {
+                    Some(user) => {
+                        Socks5Stream::connect_with_password(
+                            proxy_addr,
+                            (host, port),
+                            user,
+                            password,
+                        )
+                        .await?
+                    }
+                    None => Socks5Stream::connect(proxy_addr, (host, port)).await?,
+                }
+            };
+            Ok(stream.into_inner())
+        }
+        scheme => bail!("unsupported proxy scheme: {}", scheme),
+    }
+}
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    user: Option<&str>,
+    password: &str,
+) -> Result<()> {
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let mut head = format!(
+        "CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n",
+        authority = authority
+    );
+    if let Some(user) = user {
+        let credentials = STANDARD.encode(format!("{}:{}", user, password));
+        head.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status = String::new();
+    reader.read_line(&mut status).await?;
+    let code = status.split_whitespace().nth(1).unwrap_or_default();
+    if code != "200" {
+        bail!("proxy CONNECT to {} failed: {}", authority, status.trim());
+    }
+    // Headers end with an empty line; the tunnel starts right after it.
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+    }
+    if !reader.buffer().is_empty() {
+        bail!("proxy sent data before the tunnel was ready");
+    }
+    Ok(())
+}
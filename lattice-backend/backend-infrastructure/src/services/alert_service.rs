@@ -1,30 +1,64 @@
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use reqwest::header::AUTHORIZATION;
-use reqwest::Client;
 use serde_json::{json, Value};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::{sleep, timeout};
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
 use tracing::warn;
 
 use backend_domain::ports::AlertService;
-use backend_domain::{AlertDeliveryRecord, AnomalyRow, RuntimeConfig};
+use backend_domain::{
+    anomaly_link, is_alerting_anomaly, risk_rank, rule_description, AlertDeliveryRecord,
+    AlertDeliveryTotals, AlertPreview, AnomalyRow, RiskLevel, RuntimeConfig, TimeDisplay,
+    DEFAULT_ALERT_TARGET, DEFAULT_RULE_LANG, MORE_ALERTS_COMMAND,
+};
+
+use super::alert_proxy::{alert_http_client, connect_alert_ws};
+use super::redaction::{Redactor, REDACT_ALERT};
 
 const DELIVERY_HISTORY_LIMIT: usize = 200;
 const ALERT_RETRY_ATTEMPTS: u8 = 3;
 const ALERT_RETRY_BASE_MS: u64 = 400;
+/// How often a failed-over primary target is retried while no alerts arrive.
+const PRIMARY_RECOVERY_INTERVAL: Duration = Duration::from_secs(60);
+type AlertTarget = (Option<i64>, Option<String>);
+
+const PRIMARY_RECOVERY_MESSAGE: &str =
+    "[Lattice 告警通道恢复] 主告警通道已恢复，后续告警不再发往备用通道";
+const DEFAULT_ALERT_TEMPLATE: &str = r#"{"message":"[Lattice 稀有物资告警] {summary}\n{lines}"}"#;
 
 #[derive(Clone)]
 pub struct DefaultAlertService {
-    deliveries: Arc<RwLock<VecDeque<AlertDeliveryRecord>>>,
+    deliveries: Arc<RwLock<DeliveryLog>>,
     history_limit: usize,
+    /// Alerts held back while a player-grouping window is open, per target (group id and webhook
+    /// url, which team and alert routing may override); the first one in schedules the flush.
+    pending: Arc<Mutex<HashMap<AlertTarget, Vec<AnomalyRow>>>>,
+    /// Lines of each group's latest alert batch that did not fit its message, for `/更多`.
+    pages: Arc<Mutex<HashMap<i64, AlertPages>>>,
+    /// Primary webhook urls currently failed over to `alert_webhook_fallback_url`.
+    failed_over: Arc<Mutex<HashSet<String>>>,
+}
+
+/// The latest `history_limit` delivery records, plus totals that outlive the trimmed ones.
+#[derive(Default)]
+struct DeliveryLog {
+    records: VecDeque<AlertDeliveryRecord>,
+    totals: AlertDeliveryTotals,
+}
+
+struct AlertPages {
+    lines: Vec<String>,
+    shown: usize,
+    per_page: usize,
+    expires_at_ms: i64,
 }
 
 impl Default for DefaultAlertService {
@@ -40,56 +74,127 @@ impl DefaultAlertService {
 
     pub fn with_history_limit(history_limit: usize) -> Self {
         Self {
-            deliveries: Arc::new(RwLock::new(VecDeque::new())),
+            deliveries: Arc::new(RwLock::new(DeliveryLog::default())),
             history_limit: history_limit.max(1),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            pages: Arc::new(Mutex::new(HashMap::new())),
+            failed_over: Arc::new(Mutex::new(HashSet::new())),
         }
     }
-}
 
-#[async_trait]
-impl AlertService for DefaultAlertService {
-    fn spawn_alerts(&self, config: RuntimeConfig, anomalies: Vec<AnomalyRow>) {
-        let alerts = anomalies
+    /// Delivers to `config`'s target, or queues for its player-grouping window. `CRITICAL` rows
+    /// never wait for the window and go out in a batch of their own.
+    fn spawn_delivery(&self, config: RuntimeConfig, alerts: Vec<AnomalyRow>) {
+        if !config.alert_group_by_player || config.alert_group_window_seconds == 0 {
+            self.spawn_immediate(config, alerts);
+            return;
+        }
+        let (critical, alerts): (Vec<_>, Vec<_>) = alerts
             .into_iter()
-            .filter(|row| should_emit_alert(&row.rule_id))
-            .collect::<Vec<_>>();
+            .partition(|row| RiskLevel::parse(&row.risk_level) == Some(RiskLevel::CRITICAL));
+        if !critical.is_empty() {
+            self.spawn_immediate(config.clone(), critical);
+        }
         if alerts.is_empty() {
             return;
         }
 
         let deliveries = self.deliveries.clone();
         let history_limit = self.history_limit;
+        let pages = self.pages.clone();
+        let failed_over = self.failed_over.clone();
+        let pending = self.pending.clone();
+        let target = (config.alert_group_id, config.alert_webhook_url.clone());
         tokio::spawn(async move {
-            let mode = resolve_alert_mode(&config);
-            let (attempts, error) =
-                send_alerts_with_retry(&config, &alerts, ALERT_RETRY_ATTEMPTS).await;
-            let status = if error.is_none() {
-                "success".to_string()
-            } else {
-                "failed".to_string()
-            };
-
-            let mut rule_ids = BTreeSet::new();
-            for row in &alerts {
-                rule_ids.insert(row.rule_id.clone());
+            {
+                let mut guard = pending.lock().await;
+                let queued = guard.entry(target.clone()).or_default();
+                let window_open = !queued.is_empty();
+                queued.extend(alerts);
+                if window_open {
+                    return;
+                }
             }
+            sleep(Duration::from_secs(config.alert_group_window_seconds)).await;
+            let alerts = pending.lock().await.remove(&target).unwrap_or_default();
+            deliver_alerts(
+                &config,
+                alerts,
+                deliveries,
+                history_limit,
+                &pages,
+                &failed_over,
+            )
+            .await;
+        });
+    }
 
-            let record = AlertDeliveryRecord {
-                timestamp_ms: chrono::Utc::now().timestamp_millis(),
-                status,
-                mode,
-                attempts,
-                alert_count: alerts.len(),
-                rule_ids: rule_ids.into_iter().collect(),
-                error: error.clone(),
-            };
-            push_delivery(deliveries, history_limit, record).await;
-
-            if let Some(err) = error {
-                warn!("alert webhook failed after {attempts} attempts: {err}");
-            }
+    fn spawn_immediate(&self, config: RuntimeConfig, alerts: Vec<AnomalyRow>) {
+        let deliveries = self.deliveries.clone();
+        let history_limit = self.history_limit;
+        let pages = self.pages.clone();
+        let failed_over = self.failed_over.clone();
+        tokio::spawn(async move {
+            deliver_alerts(
+                &config,
+                alerts,
+                deliveries,
+                history_limit,
+                &pages,
+                &failed_over,
+            )
+            .await;
         });
     }
+}
+
+#[async_trait]
+impl AlertService for DefaultAlertService {
+    fn spawn_alerts(&self, config: RuntimeConfig, anomalies: Vec<AnomalyRow>) {
+        let mut alerts = anomalies
+            .into_iter()
+            .filter(|row| should_emit_alert(&config, row))
+            .collect::<Vec<_>>();
+        if alerts.is_empty() {
+            return;
+        }
+        Redactor::from_config(&config).redact_rows(REDACT_ALERT, &mut alerts);
+        for (config, alerts) in route_by_rule(config, alerts) {
+            for (config, alerts) in route_by_server(config, alerts) {
+                self.spawn_delivery(config, alerts);
+            }
+        }
+    }
+
+    fn preview_alerts(&self, config: &RuntimeConfig, anomalies: Vec<AnomalyRow>) -> AlertPreview {
+        let total = anomalies.len();
+        let mut alerts = anomalies
+            .into_iter()
+            .filter(|row| should_emit_alert(config, row))
+            .collect::<Vec<_>>();
+        Redactor::from_config(config).redact_rows(REDACT_ALERT, &mut alerts);
+        let mode = resolve_alert_mode(config);
+        let text = build_message(&alerts, config);
+        let payload = if mode == "ws" {
+            build_ws_payload(
+                config.alert_group_id.unwrap_or_default(),
+                &text,
+                "lattice-preview",
+            )
+        } else {
+            build_payload(&alerts, resolve_alert_template(config), config)
+        };
+        let payload_valid_json = serde_json::from_str::<Value>(&payload).is_ok();
+        AlertPreview {
+            mode,
+            sample_source: String::new(),
+            alert_count: alerts.len(),
+            filtered_count: total - alerts.len(),
+            text,
+            payload,
+            payload_valid_json,
+        }
+    }
 
     async fn send_system_alert(&self, config: &RuntimeConfig, message: &str) -> Result<()> {
         send_system_alert(config, message).await
@@ -108,14 +213,46 @@ impl AlertService for DefaultAlertService {
         check_alert_target(config).await
     }
 
+    async fn next_alert_page(&self, group_id: i64) -> Option<String> {
+        let mut pages = self.pages.lock().await;
+        let entry = pages.get_mut(&group_id)?;
+        if entry.expires_at_ms <= chrono::Utc::now().timestamp_millis() {
+            pages.remove(&group_id);
+            return None;
+        }
+        let total_pages = entry.lines.len().div_ceil(entry.per_page);
+        let page = entry.shown / entry.per_page + 1;
+        let end = (entry.shown + entry.per_page).min(entry.lines.len());
+        let mut lines = vec![format!("[Lattice 告警续页 {}/{}]", page, total_pages)];
+        lines.extend(entry.lines[entry.shown..end].iter().cloned());
+        entry.shown = end;
+        let hidden = entry.lines.len() - end;
+        if hidden > 0 {
+            lines.push(more_note(hidden, true));
+        } else {
+            pages.remove(&group_id);
+        }
+        Some(lines.join("\n"))
+    }
+
     async fn list_alert_deliveries(&self, limit: usize) -> Vec<AlertDeliveryRecord> {
         let limit = limit.max(1).min(self.history_limit);
         let deliveries = self.deliveries.read().await;
-        deliveries.iter().rev().take(limit).cloned().collect()
+        deliveries
+            .records
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
     }
 
     async fn last_alert_delivery(&self) -> Option<AlertDeliveryRecord> {
-        self.deliveries.read().await.back().cloned()
+        self.deliveries.read().await.records.back().cloned()
+    }
+
+    async fn alert_delivery_totals(&self) -> AlertDeliveryTotals {
+        self.deliveries.read().await.totals.clone()
     }
 }
 
@@ -128,8 +265,9 @@ pub async fn check_alert_target(config: &RuntimeConfig) -> Result<()> {
     }
 }
 
-fn should_emit_alert(rule_id: &str) -> bool {
-    matches!(rule_id, "R4" | "R10" | "R12")
+/// Alerting rules, plus any anomaly an `[alert_routing]` route matches.
+fn should_emit_alert(config: &RuntimeConfig, row: &AnomalyRow) -> bool {
+    is_alerting_anomaly(row) || !config.alert_routing.targets_for(row).is_empty()
 }
 
 fn resolve_alert_mode(config: &RuntimeConfig) -> String {
@@ -140,6 +278,236 @@ fn resolve_alert_mode(config: &RuntimeConfig) -> String {
     }
 }
 
+async fn deliver_alerts(
+    config: &RuntimeConfig,
+    alerts: Vec<AnomalyRow>,
+    deliveries: Arc<RwLock<DeliveryLog>>,
+    history_limit: usize,
+    pages: &Mutex<HashMap<i64, AlertPages>>,
+    failed_over: &Arc<Mutex<HashSet<String>>>,
+) {
+    let started = Instant::now();
+    let mut mode = resolve_alert_mode(config);
+    let primary = resolve_alert_url(config).ok();
+    let fallback = config.alert_webhook_fallback_url.clone();
+    let primary_down = match &primary {
+        Some(url) => failed_over.lock().await.contains(url),
+        None => false,
+    };
+    // A primary already known to be down gets a single try, so alerts are not held up by its
+    // retries while it stays down.
+    let primary_attempts = if primary_down && fallback.is_some() {
+        1
+    } else {
+        ALERT_RETRY_ATTEMPTS
+    };
+    let (mut attempts, mut error) = send_alerts_with_retry(config, &alerts, primary_attempts).await;
+    let mut failover = false;
+    let mut primary_error = None;
+    match (&primary, &fallback, error.clone()) {
+        (Some(url), _, None) if primary_down => {
+            failed_over.lock().await.remove(url);
+            announce_primary_recovery(config).await;
+        }
+        (Some(url), Some(fallback), Some(err)) => {
+            let mut fallback_config = config.clone();
+            fallback_config.alert_webhook_url = Some(fallback.clone());
+            mode = resolve_alert_mode(&fallback_config);
+            let (fallback_attempts, fallback_error) =
+                send_alerts_with_retry(&fallback_config, &alerts, ALERT_RETRY_ATTEMPTS).await;
+            attempts = attempts.saturating_add(fallback_attempts);
+            failover = true;
+            primary_error = Some(err.clone());
+            error = fallback_error;
+            if failed_over.lock().await.insert(url.clone()) {
+                warn!("alert webhook primary failed, failing over to alert_webhook_fallback_url: {err}");
+                spawn_recovery_watch(config.clone(), url.clone(), failed_over.clone());
+            }
+        }
+        _ => {}
+    }
+    if error.is_none() {
+        keep_alert_pages(config, &alerts, pages).await;
+    }
+    let status = if error.is_none() {
+        "success".to_string()
+    } else {
+        "failed".to_string()
+    };
+
+    let mut rule_ids = BTreeSet::new();
+    for row in &alerts {
+        rule_ids.insert(row.rule_id.clone());
+    }
+
+    let record = AlertDeliveryRecord {
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+        status,
+        mode,
+        attempts,
+        alert_count: alerts.len(),
+        rule_ids: rule_ids.into_iter().collect(),
+        error: error.clone(),
+        failover,
+        primary_error,
+    };
+    push_delivery(deliveries, history_limit, record, started.elapsed()).await;
+
+    if let Some(err) = error {
+        warn!("alert webhook failed after {attempts} attempts: {err}");
+    }
+}
+
+/// Retries a failed-over primary target until it takes the recovery notice, for when no alert
+/// batch comes along to try it; stops once a batch finds it healthy first.
+fn spawn_recovery_watch(
+    config: RuntimeConfig,
+    url: String,
+    failed_over: Arc<Mutex<HashSet<String>>>,
+) {
+    tokio::spawn(async move {
+        loop {
+            sleep(PRIMARY_RECOVERY_INTERVAL).await;
+            if !failed_over.lock().await.contains(&url) {
+                return;
+            }
+            if send_system_alert(&config, PRIMARY_RECOVERY_MESSAGE)
+                .await
+                .is_ok()
+            {
+                failed_over.lock().await.remove(&url);
+                warn!("alert webhook primary recovered, leaving alert_webhook_fallback_url");
+                return;
+            }
+        }
+    });
+}
+
+async fn announce_primary_recovery(config: &RuntimeConfig) {
+    warn!("alert webhook primary recovered, leaving alert_webhook_fallback_url");
+    if let Err(err) = send_system_alert(config, PRIMARY_RECOVERY_MESSAGE).await {
+        warn!("failed to send alert recovery notice: {err}");
+    }
+}
+
+/// Splits alerts by their `[alert_routing]` targets; an anomaly matching routes to several
+/// targets goes to each, and unmatched ones stay on `config`'s target.
+fn route_by_rule(
+    config: RuntimeConfig,
+    alerts: Vec<AnomalyRow>,
+) -> Vec<(RuntimeConfig, Vec<AnomalyRow>)> {
+    if config.alert_routing.routes.is_empty() {
+        return vec![(config, alerts)];
+    }
+    let mut by_target: BTreeMap<String, Vec<AnomalyRow>> = BTreeMap::new();
+    for row in alerts {
+        let targets = config.alert_routing.targets_for(&row);
+        if targets.is_empty() {
+            by_target
+                .entry(DEFAULT_ALERT_TARGET.to_string())
+                .or_default()
+                .push(row);
+            continue;
+        }
+        for target in targets {
+            by_target
+                .entry(target.to_string())
+                .or_default()
+                .push(row.clone());
+        }
+    }
+    by_target
+        .into_iter()
+        .map(|(name, rows)| {
+            let mut routed = config.clone();
+            if let Some(target) = config.alert_routing.targets.get(&name) {
+                if let Some(group_id) = target.group_id {
+                    routed.alert_group_id = Some(group_id);
+                    routed.alert_server_groups.clear();
+                }
+                if let Some(url) = &target.webhook_url {
+                    routed.alert_webhook_url = Some(url.clone());
+                }
+                if let Some(proxy) = &target.proxy {
+                    routed.alert_webhook_proxy = Some(proxy.clone());
+                }
+            }
+            (routed, rows)
+        })
+        .collect()
+}
+
+/// Splits alerts by the `alert_server_groups` entry of their `server_id`; the others keep
+/// `config`'s group.
+fn route_by_server(
+    config: RuntimeConfig,
+    alerts: Vec<AnomalyRow>,
+) -> Vec<(RuntimeConfig, Vec<AnomalyRow>)> {
+    if config.alert_server_groups.is_empty() {
+        return vec![(config, alerts)];
+    }
+    let mut by_group: BTreeMap<Option<i64>, Vec<AnomalyRow>> = BTreeMap::new();
+    for row in alerts {
+        let group_id = config
+            .alert_server_groups
+            .get(&row.server_id.to_lowercase())
+            .copied()
+            .or(config.alert_group_id);
+        by_group.entry(group_id).or_default().push(row);
+    }
+    by_group
+        .into_iter()
+        .map(|(group_id, rows)| {
+            let mut config = config.clone();
+            config.alert_group_id = group_id;
+            (config, rows)
+        })
+        .collect()
+}
+
+/// Keeps what the message left out for `/更多`; a batch that fit replaces older pages too, since
+/// `/更多` always continues the group's latest batch.
+async fn keep_alert_pages(
+    config: &RuntimeConfig,
+    alerts: &[AnomalyRow],
+    pages: &Mutex<HashMap<i64, AlertPages>>,
+) {
+    let Some(group_id) = config.alert_group_id else {
+        return;
+    };
+    let mut pages = pages.lock().await;
+    let lines = alert_lines(alerts, config);
+    if !paging_enabled(config) || lines.len() <= config.alert_max_lines {
+        pages.remove(&group_id);
+        return;
+    }
+    let ttl_ms = (config.alert_page_ttl_minutes * 60_000) as i64;
+    pages.insert(
+        group_id,
+        AlertPages {
+            lines,
+            shown: config.alert_max_lines,
+            per_page: config.alert_max_lines,
+            expires_at_ms: chrono::Utc::now().timestamp_millis() + ttl_ms,
+        },
+    );
+}
+
+fn paging_enabled(config: &RuntimeConfig) -> bool {
+    config.alert_page_ttl_minutes > 0 && config.alert_group_id.is_some()
+}
+
+fn more_note(hidden: usize, paging: bool) -> String {
+    if paging {
+        format!(
+            "...还有 {} 条未展示，发送 {} 查看",
+            hidden, MORE_ALERTS_COMMAND
+        )
+    } else {
+        format!("...还有 {} 条未展示", hidden)
+    }
+}
+
 async fn send_alerts_with_retry(
     config: &RuntimeConfig,
     alerts: &[AnomalyRow],
@@ -166,14 +534,21 @@ async fn send_alerts_with_retry(
 }
 
 async fn push_delivery(
-    deliveries: Arc<RwLock<VecDeque<AlertDeliveryRecord>>>,
+    deliveries: Arc<RwLock<DeliveryLog>>,
     history_limit: usize,
     record: AlertDeliveryRecord,
+    elapsed: Duration,
 ) {
     let mut guard = deliveries.write().await;
-    guard.push_back(record);
-    while guard.len() > history_limit.max(1) {
-        guard.pop_front();
+    guard.totals.record(
+        &record.mode,
+        record.status == "success",
+        record.attempts,
+        elapsed.as_secs_f64(),
+    );
+    guard.records.push_back(record);
+    while guard.records.len() > history_limit.max(1) {
+        guard.records.pop_front();
     }
 }
 
@@ -216,15 +591,8 @@ async fn send_group_text(config: &RuntimeConfig, group_id: i64, message: &str) -
 }
 
 async fn send_http_alerts(config: &RuntimeConfig, url: &str, alerts: &[AnomalyRow]) -> Result<()> {
-    let template = config
-        .alert_webhook_template
-        .as_deref()
-        .unwrap_or(r#"{"message":"[Lattice 稀有物资告警] {summary}\n{lines}"}"#);
-
-    let payload = build_payload(alerts, template);
-    let client = Client::builder()
-        .timeout(Duration::from_secs(config.request_timeout_seconds.max(3)))
-        .build()?;
+    let payload = build_payload(alerts, resolve_alert_template(config), config);
+    let client = alert_http_client(config)?;
 
     client
         .post(url)
@@ -237,9 +605,7 @@ async fn send_http_alerts(config: &RuntimeConfig, url: &str, alerts: &[AnomalyRo
 }
 
 async fn send_http_text_alert(config: &RuntimeConfig, url: &str, message: &str) -> Result<()> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(config.request_timeout_seconds.max(3)))
-        .build()?;
+    let client = alert_http_client(config)?;
     let payload = json!({ "message": message }).to_string();
     client
         .post(url)
@@ -257,9 +623,7 @@ async fn send_http_group_text_alert(
     group_id: i64,
     message: &str,
 ) -> Result<()> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(config.request_timeout_seconds.max(3)))
-        .build()?;
+    let client = alert_http_client(config)?;
     let payload = json!({ "group_id": group_id, "message": message }).to_string();
     client
         .post(url)
@@ -272,9 +636,7 @@ async fn send_http_group_text_alert(
 }
 
 async fn check_http_target(config: &RuntimeConfig, url: &str) -> Result<()> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(config.request_timeout_seconds.max(3)))
-        .build()?;
+    let client = alert_http_client(config)?;
     let response = client.get(url).send().await?;
     if !response.status().is_success() {
         anyhow::bail!("alert webhook responded {}", response.status());
@@ -284,9 +646,10 @@ async fn check_http_target(config: &RuntimeConfig, url: &str) -> Result<()> {
 
 async fn check_ws_target(config: &RuntimeConfig, url: &str) -> Result<()> {
     let token = config.alert_webhook_token.clone();
-    if let Err(err) = try_ws_check(url, token.as_deref(), false).await {
+    let proxy = config.alert_webhook_proxy.as_deref();
+    if let Err(err) = try_ws_check(url, token.as_deref(), proxy, false).await {
         if token.as_ref().is_some() {
-            return try_ws_check(url, token.as_deref(), true).await;
+            return try_ws_check(url, token.as_deref(), proxy, true).await;
         }
         return Err(err);
     }
@@ -297,22 +660,15 @@ async fn send_ws_alerts(config: &RuntimeConfig, url: &str, alerts: &[AnomalyRow]
     let group_id = config
         .alert_group_id
         .ok_or_else(|| anyhow::anyhow!("alert_group_id not configured"))?;
-    let message = build_message(alerts);
+    let message = build_message(alerts, config);
     let echo = format!("lattice-{}", chrono::Utc::now().timestamp_millis());
-    let payload = json!({
-        "action": "send_group_msg",
-        "params": {
-            "group_id": group_id,
-            "message": message,
-        },
-        "echo": echo,
-    })
-    .to_string();
+    let payload = build_ws_payload(group_id, &message, &echo);
 
     let token = config.alert_webhook_token.clone();
-    if let Err(err) = try_ws_send(url, token.as_deref(), &payload, &echo, false).await {
+    let proxy = config.alert_webhook_proxy.as_deref();
+    if let Err(err) = try_ws_send(url, token.as_deref(), proxy, &payload, &echo, false).await {
         if token.as_ref().is_some() {
-            try_ws_send(url, token.as_deref(), &payload, &echo, true).await?;
+            try_ws_send(url, token.as_deref(), proxy, &payload, &echo, true).await?;
         } else {
             return Err(err);
         }
@@ -334,20 +690,13 @@ async fn send_ws_group_text_alert(
     message: &str,
 ) -> Result<()> {
     let echo = format!("lattice-system-{}", chrono::Utc::now().timestamp_millis());
-    let payload = json!({
-        "action": "send_group_msg",
-        "params": {
-            "group_id": group_id,
-            "message": message,
-        },
-        "echo": echo,
-    })
-    .to_string();
+    let payload = build_ws_payload(group_id, message, &echo);
 
     let token = config.alert_webhook_token.clone();
-    if let Err(err) = try_ws_send(url, token.as_deref(), &payload, &echo, false).await {
+    let proxy = config.alert_webhook_proxy.as_deref();
+    if let Err(err) = try_ws_send(url, token.as_deref(), proxy, &payload, &echo, false).await {
         if token.as_ref().is_some() {
-            try_ws_send(url, token.as_deref(), &payload, &echo, true).await?;
+            try_ws_send(url, token.as_deref(), proxy, &payload, &echo, true).await?;
         } else {
             return Err(err);
         }
@@ -355,7 +704,12 @@ async fn send_ws_group_text_alert(
     Ok(())
 }
 
-async fn try_ws_check(url: &str, token: Option<&str>, use_query: bool) -> Result<()> {
+async fn try_ws_check(
+    url: &str,
+    token: Option<&str>,
+    proxy: Option<&str>,
+    use_query: bool,
+) -> Result<()> {
     let mut request = if use_query {
         add_access_token_query(url, token).into_client_request()?
     } else {
@@ -370,7 +724,7 @@ async fn try_ws_check(url: &str, token: Option<&str>, use_query: bool) -> Result
         }
     }
 
-    let (mut ws, _) = tokio_tungstenite::connect_async(request).await?;
+    let (mut ws, _) = connect_alert_ws(request, proxy).await?;
     let echo = format!("lattice-check-{}", chrono::Utc::now().timestamp_millis());
     let payload = json!({
         "action": "get_status",
@@ -405,7 +759,10 @@ async fn try_ws_check(url: &str, token: Option<&str>, use_query: bool) -> Result
                     ws.send(Message::Pong(bytes)).await?;
                 }
                 Some(Ok(Message::Close(frame))) => {
-                    return Err(anyhow::anyhow!("ws closed before check response: {:?}", frame));
+                    return Err(anyhow::anyhow!(
+                        "ws closed before check response: {:?}",
+                        frame
+                    ));
                 }
                 Some(Ok(_)) => {}
                 Some(Err(err)) => return Err(anyhow::anyhow!("ws check receive failed: {err}")),
@@ -425,6 +782,7 @@ async fn try_ws_check(url: &str, token: Option<&str>, use_query: bool) -> Result
 async fn try_ws_send(
     url: &str,
     token: Option<&str>,
+    proxy: Option<&str>,
     payload: &str,
     expected_echo: &str,
     use_query: bool,
@@ -443,7 +801,7 @@ async fn try_ws_send(
         }
     }
 
-    let (mut ws, _) = tokio_tungstenite::connect_async(request).await?;
+    let (mut ws, _) = connect_alert_ws(request, proxy).await?;
     ws.send(Message::Text(payload.to_string().into())).await?;
 
     let result = timeout(Duration::from_secs(8), async {
@@ -471,7 +829,10 @@ async fn try_ws_send(
                     ws.send(Message::Pong(bytes)).await?;
                 }
                 Some(Ok(Message::Close(frame))) => {
-                    return Err(anyhow::anyhow!("ws closed before action response: {:?}", frame));
+                    return Err(anyhow::anyhow!(
+                        "ws closed before action response: {:?}",
+                        frame
+                    ));
                 }
                 Some(Ok(_)) => {}
                 Some(Err(err)) => return Err(anyhow::anyhow!("ws action receive failed: {err}")),
@@ -554,40 +915,245 @@ fn resolve_alert_url(config: &RuntimeConfig) -> Result<String> {
     anyhow::bail!("alert webhook url not configured")
 }
 
-fn build_message(alerts: &[AnomalyRow]) -> String {
+fn resolve_alert_template(config: &RuntimeConfig) -> &str {
+    config
+        .alert_webhook_template
+        .as_deref()
+        .unwrap_or(DEFAULT_ALERT_TEMPLATE)
+}
+
+fn build_ws_payload(group_id: i64, message: &str, echo: &str) -> String {
+    json!({
+        "action": "send_group_msg",
+        "params": {
+            "group_id": group_id,
+            "message": message,
+        },
+        "echo": echo,
+    })
+    .to_string()
+}
+
+fn format_alert_line(row: &AnomalyRow, config: &RuntimeConfig, display: &TimeDisplay) -> String {
+    let mut line = format!(
+        "{} | {} | {} x{} | {}",
+        display.format(row.event_time),
+        row.player_name,
+        row.item_id,
+        row.count,
+        row.risk_level
+    );
+    match rule_description(&row.rule_id, DEFAULT_RULE_LANG) {
+        "" => {}
+        description => line = format!("{} | {}", line, description),
+    }
+    with_link(line, row, config)
+}
+
+fn with_link(line: String, row: &AnomalyRow, config: &RuntimeConfig) -> String {
+    match anomaly_link(config, row) {
+        Some(link) => format!("{} {}", line, link),
+        None => line,
+    }
+}
+
+/// One line per player: every rule they tripped plus item counts (summed within a rule, max across
+/// rules, since R4 and R12 usually describe the same stack). The link points at the riskiest row.
+fn group_alert_lines(
+    alerts: &[AnomalyRow],
+    config: &RuntimeConfig,
+    display: &TimeDisplay,
+) -> Vec<String> {
+    struct PlayerGroup<'a> {
+        key: &'a str,
+        player_name: &'a str,
+        rule_ids: Vec<&'a str>,
+        items: Vec<(&'a str, HashMap<&'a str, i64>)>,
+        top: &'a AnomalyRow,
+    }
+
+    let mut groups: Vec<PlayerGroup> = Vec::new();
+    for row in alerts {
+        let key = if row.player_uuid.is_empty() {
+            row.player_name.as_str()
+        } else {
+            row.player_uuid.as_str()
+        };
+        let index = match groups.iter().position(|group| group.key == key) {
+            Some(index) => index,
+            None => {
+                groups.push(PlayerGroup {
+                    key,
+                    player_name: &row.player_name,
+                    rule_ids: Vec::new(),
+                    items: Vec::new(),
+                    top: row,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        if !group.rule_ids.contains(&row.rule_id.as_str()) {
+            group.rule_ids.push(&row.rule_id);
+        }
+        match group
+            .items
+            .iter_mut()
+            .find(|(item_id, _)| *item_id == row.item_id)
+        {
+            Some((_, per_rule)) => *per_rule.entry(&row.rule_id).or_default() += row.count,
+            None => group.items.push((
+                &row.item_id,
+                HashMap::from([(row.rule_id.as_str(), row.count)]),
+            )),
+        }
+        if risk_rank(&row.risk_level) > risk_rank(&group.top.risk_level) {
+            group.top = row;
+        }
+    }
+    groups
+        .into_iter()
+        .map(|group| {
+            let items = group
+                .items
+                .iter()
+                .map(|(item_id, per_rule)| {
+                    format!(
+                        "{} x{}",
+                        item_id,
+                        per_rule.values().copied().max().unwrap_or_default()
+                    )
+                })
+                .collect::<Vec<_>>();
+            let line = format!(
+                "{} | {}: {}, {} | {}",
+                display.format(group.top.event_time),
+                group.player_name,
+                group.rule_ids.join("+"),
+                items.join(", "),
+                group.top.risk_level
+            );
+            with_link(line, group.top, config)
+        })
+        .collect()
+}
+
+/// Alert lines with event times in `display_timezone` / `display_time_format`.
+fn alert_lines(alerts: &[AnomalyRow], config: &RuntimeConfig) -> Vec<String> {
+    let display = TimeDisplay::from_config(config);
+    if config.alert_group_by_player {
+        group_alert_lines(alerts, config, &display)
+    } else {
+        alerts
+            .iter()
+            .map(|row| format_alert_line(row, config, &display))
+            .collect()
+    }
+}
+
+fn build_message(alerts: &[AnomalyRow], config: &RuntimeConfig) -> String {
     let summary = format!("共 {} 条", alerts.len());
+    let alert_lines = alert_lines(alerts, config);
     let mut lines = Vec::new();
     lines.push(format!("[Lattice 稀有物资告警] {}", summary));
-    for row in alerts.iter().take(8) {
-        lines.push(format!(
-            "{} | {} x{} | {}",
-            row.player_name, row.item_id, row.count, row.risk_level
+    lines.extend(alert_lines.iter().take(config.alert_max_lines).cloned());
+    if alert_lines.len() > config.alert_max_lines {
+        lines.push(more_note(
+            alert_lines.len() - config.alert_max_lines,
+            paging_enabled(config),
         ));
     }
-    if alerts.len() > 8 {
-        lines.push(format!("...还有 {} 条未展示", alerts.len() - 8));
-    }
     lines.join("\n")
 }
 
-fn build_payload(alerts: &[AnomalyRow], template: &str) -> String {
+fn build_payload(alerts: &[AnomalyRow], template: &str, config: &RuntimeConfig) -> String {
     let summary = format!("共 {} 条", alerts.len());
-    let lines = alerts
+    let lines = alert_lines(alerts, config);
+    let mut line_text = lines
         .iter()
-        .take(8)
-        .map(|row| {
-            format!(
-                "{} | {} x{} | {}",
-                row.player_name, row.item_id, row.count, row.risk_level
-            )
-        })
-        .collect::<Vec<_>>();
-    let mut line_text = lines.join("\\n");
-    if alerts.len() > 8 {
-        line_text.push_str(&format!("\\n...还有 {} 条未展示", alerts.len() - 8));
+        .take(config.alert_max_lines)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\\n");
+    if lines.len() > config.alert_max_lines {
+        line_text.push_str("\\n");
+        line_text.push_str(&more_note(
+            lines.len() - config.alert_max_lines,
+            paging_enabled(config),
+        ));
     }
     template
         .replace("{total}", &alerts.len().to_string())
         .replace("{summary}", &summary)
         .replace("{lines}", &line_text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::{anomaly_row, runtime_config};
+    use backend_domain::{AlertRoute, AlertRoutingTarget};
+
+    fn row(server_id: &str, risk_level: &str) -> AnomalyRow {
+        AnomalyRow {
+            server_id: server_id.to_string(),
+            risk_level: risk_level.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[test]
+    fn routed_alerts_fan_out_and_unmatched_ones_keep_the_usual_target() {
+        let mut config = runtime_config();
+        config.alert_group_id = Some(100);
+        config.alert_webhook_url = Some("http://usual.example/hook".to_string());
+        config.alert_server_groups.insert("lobby".to_string(), 300);
+        config.alert_routing.targets.insert(
+            "oncall".to_string(),
+            AlertRoutingTarget {
+                group_id: Some(200),
+                webhook_url: Some("http://oncall.example/hook".to_string()),
+                proxy: None,
+            },
+        );
+        config.alert_routing.routes.push(AlertRoute {
+            rule_ids: Vec::new(),
+            risk_levels: vec!["HIGH".to_string()],
+            targets: vec!["oncall".to_string(), DEFAULT_ALERT_TARGET.to_string()],
+        });
+        let alerts = vec![row("survival", "HIGH"), row("lobby", "MEDIUM")];
+
+        // Deliveries as `spawn_alerts` makes them: by rule, then by server.
+        let mut deliveries: Vec<(Option<i64>, Option<String>, Vec<String>)> =
+            route_by_rule(config, alerts)
+                .into_iter()
+                .flat_map(|(config, alerts)| route_by_server(config, alerts))
+                .map(|(config, alerts)| {
+                    let servers = alerts.into_iter().map(|row| row.server_id).collect();
+                    (config.alert_group_id, config.alert_webhook_url, servers)
+                })
+                .collect();
+        deliveries.sort();
+        let usual = Some("http://usual.example/hook".to_string());
+        assert_eq!(
+            deliveries,
+            vec![
+                (Some(100), usual.clone(), vec!["survival".to_string()]),
+                (
+                    Some(200),
+                    Some("http://oncall.example/hook".to_string()),
+                    vec!["survival".to_string()]
+                ),
+                (Some(300), usual, vec!["lobby".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn without_routes_alerts_stay_in_one_batch() {
+        let alerts = vec![row("survival", "HIGH"), row("lobby", "LOW")];
+        let routed = route_by_rule(runtime_config(), alerts);
+        assert_eq!(routed.len(), 1);
+        assert_eq!(routed[0].1.len(), 2);
+    }
+}
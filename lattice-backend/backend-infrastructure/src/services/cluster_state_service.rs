@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::header::AUTHORIZATION;
+use reqwest::Client;
+
+use backend_domain::ports::AnalyzerStateService;
+use backend_domain::{current_millis, replay_headers, AnomalyRow, ClusterAnalyzeRequest};
+
+/// Path of the analyze endpoint served by the instance that owns the shared analyzer state.
+pub const CLUSTER_ANALYZE_PATH: &str = "/v2/cluster/analyze";
+
+/// Forwards batches to the analyzer state instance at `cluster_state_url` over HTTP.
+#[derive(Clone)]
+pub struct HttpAnalyzerStateService {
+    client: Client,
+    endpoint: String,
+    api_token: Option<String>,
+}
+
+impl HttpAnalyzerStateService {
+    pub fn new(state_url: &str, api_token: Option<String>, timeout_seconds: u64) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(timeout_seconds.max(1)))
+            .build()?;
+        Ok(Self {
+            client,
+            endpoint: format!(
+                "{}{}",
+                state_url.trim_end_matches('/'),
+                CLUSTER_ANALYZE_PATH
+            ),
+            api_token,
+        })
+    }
+}
+
+#[async_trait]
+impl AnalyzerStateService for HttpAnalyzerStateService {
+    async fn analyze(&self, request: &ClusterAnalyzeRequest) -> Result<Vec<AnomalyRow>> {
+        let mut builder = self.client.post(&self.endpoint).json(request);
+        if let Some(token) = &self.api_token {
+            builder = builder.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        // Always sent, so the state instance may turn on `replay_window_seconds`.
+        for (name, value) in replay_headers(current_millis()) {
+            builder = builder.header(name, value);
+        }
+        let response = builder.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "analyzer state service returned {}: {}",
+                status,
+                body.trim()
+            ));
+        }
+        Ok(response.json().await?)
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use tokio::fs;
+use tracing::warn;
+
+use backend_application::commands::config_change_commands::{
+    describe_rule_changes, notify_config_change,
+};
+use backend_application::AppState;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const FILE_ACTOR: &str = "文件修改";
+
+/// Announces hand edits of `config.toml` and the key item rule file. Both are only read at
+/// startup, so the notification says the change applies after a restart. Rule files written by
+/// the API match the rules in memory and are not announced twice.
+pub async fn monitor_config_files(state: AppState) {
+    if !state.config.config_change_alert_enabled {
+        return;
+    }
+    let config_path = state.config.config_path.clone();
+    let mut config_table = match &config_path {
+        Some(path) => read_config_table(path).await,
+        None => None,
+    };
+    let mut rules = state
+        .config_repo
+        .load_key_items(&state.config.key_items_path)
+        .await
+        .ok();
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Some(path) = &config_path {
+            let current = read_config_table(path).await;
+            if let (Some(before), Some(after)) = (&config_table, &current) {
+                let keys = changed_keys(before, after);
+                if !keys.is_empty() {
+                    notify_config_change(
+                        &state,
+                        FILE_ACTOR,
+                        &format!("config.toml 已修改：{}（重启后生效）", keys.join(", ")),
+                    );
+                }
+            }
+            if current.is_some() {
+                config_table = current;
+            }
+        }
+
+        let Ok(current) = state
+            .config_repo
+            .load_key_items(&state.config.key_items_path)
+            .await
+        else {
+            continue;
+        };
+        if let Some(before) = &rules {
+            let written_by_api =
+                describe_rule_changes(&*state.key_rules.read().await, &current).is_none();
+            if !written_by_api {
+                if let Some(changes) = describe_rule_changes(before, &current) {
+                    notify_config_change(
+                        &state,
+                        FILE_ACTOR,
+                        &format!("关键物品规则文件已修改：{}（重启后生效）", changes),
+                    );
+                }
+            }
+        }
+        rules = Some(current);
+    }
+}
+
+async fn read_config_table(path: &str) -> Option<toml::Table> {
+    let content = fs::read_to_string(path).await.ok()?;
+    match content.parse::<toml::Table>() {
+        Ok(table) => Some(table),
+        Err(err) => {
+            warn!("config watch: {} is not valid TOML: {}", path, err);
+            None
+        }
+    }
+}
+
+/// Top-level keys added, removed or changed; values are left out since some are secrets.
+fn changed_keys(before: &toml::Table, after: &toml::Table) -> Vec<String> {
+    let mut keys: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .chain(
+            before
+                .keys()
+                .filter(|key| !after.contains_key(*key))
+                .cloned(),
+        )
+        .collect();
+    keys.sort();
+    keys
+}
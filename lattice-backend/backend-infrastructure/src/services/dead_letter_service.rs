@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use backend_application::commands::dead_letter_commands;
+use backend_application::AppState;
+
+const MIN_REPLAY_DELAY: Duration = Duration::from_secs(5);
+const MAX_REPLAY_DELAY: Duration = Duration::from_secs(300);
+
+/// Drains writes queued during a ClickHouse outage once it answers again. Failed attempts back
+/// off from 5 seconds up to 5 minutes, so a long outage is not hammered with retries.
+pub async fn monitor_dead_letters(state: AppState) {
+    if !state.dead_letters.enabled() {
+        return;
+    }
+    let mut delay = MIN_REPLAY_DELAY;
+    loop {
+        tokio::time::sleep(delay).await;
+        dead_letter_commands::replay_dead_letters(&state).await;
+        delay = if state.dead_letters.len_events().await == 0 {
+            MIN_REPLAY_DELAY
+        } else {
+            (delay * 2).min(MAX_REPLAY_DELAY)
+        };
+    }
+}
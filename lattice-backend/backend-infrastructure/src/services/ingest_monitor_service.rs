@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use tracing::{error, warn};
+
+use backend_application::queries::task_progress_queries;
+use backend_application::AppState;
+use backend_domain::{current_millis, ServerStatus, StaleServerStatus};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn monitor_ingest_staleness(state: AppState) {
+    let stale_after_minutes = state.config.ingest_stale_after_minutes;
+    if stale_after_minutes == 0 {
+        return;
+    }
+    let stale_after_ms = (stale_after_minutes * 60_000) as i64;
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let transitions = state
+            .ingest_tracker
+            .take_stale_transitions(current_millis(), stale_after_ms)
+            .await;
+        for status in &transitions.newly_stale {
+            warn!(
+                "server {} has not ingested for {}s",
+                status.server_id, status.stale_for_seconds
+            );
+            let message = format_stale_message(status);
+            send_monitor_alert(&state, &message).await;
+        }
+        for server_id in &transitions.recovered {
+            let message = format!("[Lattice 采集恢复] server={} 已恢复上报", server_id);
+            send_monitor_alert(&state, &message).await;
+        }
+    }
+}
+
+pub async fn monitor_server_heartbeats(state: AppState) {
+    let missed_threshold = state.config.heartbeat_missed_threshold;
+    if missed_threshold == 0 {
+        return;
+    }
+    let interval_ms = (state.config.heartbeat_interval_seconds * 1000) as i64;
+    let mut interval = tokio::time::interval(Duration::from_millis(interval_ms.max(1000) as u64));
+    loop {
+        interval.tick().await;
+        let transitions = state
+            .heartbeats
+            .take_transitions(current_millis(), interval_ms, missed_threshold)
+            .await;
+        for status in &transitions.missed {
+            warn!(
+                "server {} missed {} heartbeats",
+                status.server_id, status.missed_heartbeats
+            );
+            let message = format!(
+                "[Lattice 心跳告警] server={} 已连续 {} 次未发送心跳，采集端可能已离线",
+                status.server_id, status.missed_heartbeats
+            );
+            send_monitor_alert(&state, &message).await;
+        }
+        for status in &transitions.recovered {
+            let message = format_heartbeat_recovered_message(status);
+            send_monitor_alert(&state, &message).await;
+        }
+    }
+}
+
+/// Sends one system alert per stall of a task that stopped reporting progress while RUNNING;
+/// the task has to report again before it can stall, and alert, a second time.
+pub async fn monitor_task_progress(state: AppState) {
+    let stale_after_seconds = state.config.task_stale_after_seconds;
+    if stale_after_seconds == 0 {
+        return;
+    }
+    let mut interval =
+        tokio::time::interval(CHECK_INTERVAL.min(Duration::from_secs(stale_after_seconds)));
+    let mut alerted: HashMap<&'static str, i64> = HashMap::new();
+    loop {
+        interval.tick().await;
+        let status = state.task_status.read().await.clone();
+        let now_ms = current_millis();
+        let stalled = task_progress_queries::stalled_tasks(&status, now_ms, stale_after_seconds);
+        alerted.retain(|task, _| stalled.iter().any(|(name, _)| name == task));
+        for (task, progress) in stalled {
+            if alerted.insert(task, progress.updated_at) == Some(progress.updated_at) {
+                continue;
+            }
+            let silent_seconds = (now_ms - progress.updated_at) / 1000;
+            warn!(
+                "{} task is RUNNING without progress for {}s",
+                task, silent_seconds
+            );
+            let message = format!(
+                "[Lattice 任务告警] {} 任务已 {} 秒未上报进度，状态标记为 STALLED，mod 可能已崩溃",
+                task, silent_seconds
+            );
+            send_monitor_alert(&state, &message).await;
+        }
+    }
+}
+
+/// Watches the backend's own counters: a minute with far more anomalies than the trailing
+/// baseline, or a full clock hour without ingest when the same hour a day earlier had some.
+/// Each condition alerts once and re-arms when it clears.
+pub async fn monitor_system_rates(state: AppState) {
+    let multiple = state.config.meta_alert_spike_multiple;
+    let baseline_minutes = state.config.meta_alert_baseline_minutes;
+    if multiple <= 0.0 && !state.config.meta_alert_zero_ingest {
+        return;
+    }
+    let started_ms = current_millis();
+    let mut spiking = false;
+    let mut last_silent_hour = None;
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now_ms = current_millis();
+
+        // Until a whole baseline was observed, any burst would look like a spike.
+        if now_ms - started_ms >= (baseline_minutes * 60_000) as i64 {
+            match state
+                .metrics
+                .anomaly_spike(now_ms, baseline_minutes, multiple)
+            {
+                Some(spike) if !spiking => {
+                    spiking = true;
+                    warn!(
+                        "anomaly spike: {} in the last minute, baseline {:.1}/min",
+                        spike.last_minute, spike.baseline_per_minute
+                    );
+                    let message = format!(
+                        "[Lattice 自监控告警] 最近一分钟产生 {} 条异常，过去 {} 分钟平均每分钟 {:.1} 条，超过 {} 倍阈值",
+                        spike.last_minute, baseline_minutes, spike.baseline_per_minute, multiple
+                    );
+                    send_monitor_alert(&state, &message).await;
+                }
+                Some(_) => {}
+                None => spiking = false,
+            }
+        }
+
+        if state.config.meta_alert_zero_ingest {
+            let hour = now_ms.div_euclid(3_600_000) - 1;
+            let started_hour = started_ms.div_euclid(3_600_000);
+            // Both hours must have been observed from their start.
+            if hour - 24 > started_hour && last_silent_hour != Some(hour) {
+                let previous = state.metrics.ingest_events_in_hour(hour - 24);
+                if previous > 0 && state.metrics.ingest_events_in_hour(hour) == 0 {
+                    last_silent_hour = Some(hour);
+                    warn!("no events ingested during hour {}", hour);
+                    let message = format!(
+                        "[Lattice 自监控告警] {} 起一小时内未收到任何事件，昨天同一时段收到 {} 条，请检查采集端与网络",
+                        format_hour(hour),
+                        previous
+                    );
+                    send_monitor_alert(&state, &message).await;
+                }
+            }
+        }
+    }
+}
+
+fn format_hour(hour: i64) -> String {
+    Local
+        .timestamp_millis_opt(hour * 3_600_000)
+        .single()
+        .map(|time| time.format("%m-%d %H:00").to_string())
+        .unwrap_or_else(|| hour.to_string())
+}
+
+async fn send_monitor_alert(state: &AppState, message: &str) {
+    if let Err(err) = state
+        .alert_service
+        .send_system_alert(&state.config, message)
+        .await
+    {
+        error!("monitor alert failed: {}", err);
+    }
+}
+
+fn format_heartbeat_recovered_message(status: &ServerStatus) -> String {
+    let mut message = format!("[Lattice 心跳恢复] server={} 心跳已恢复", status.server_id);
+    if let Some(version) = &status.mod_version {
+        message.push_str(&format!(" (mod {})", version));
+    }
+    message
+}
+
+fn format_stale_message(status: &StaleServerStatus) -> String {
+    let mut message = format!(
+        "[Lattice 采集告警] server={} 已 {} 分钟未成功上报",
+        status.server_id,
+        status.stale_for_seconds / 60
+    );
+    if status.auth_failures > 0 {
+        message.push_str(&format!(
+            "\n期间鉴权失败 {} 次，最近来源 {}，请检查 api_token 配置",
+            status.auth_failures,
+            status
+                .last_auth_failure_source
+                .as_deref()
+                .unwrap_or("unknown")
+        ));
+    }
+    message
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use backend_domain::ports::IngestRecorder;
+use backend_domain::{IngestEvent, RuntimeConfig};
+
+/// Lines queued for the writer; batches beyond this are dropped rather than awaited.
+const RECORD_BUFFER: usize = 256;
+
+/// Appends sampled ingest batches to `ingest_record_path` as JSON lines until the file reaches
+/// `ingest_record_max_mb`.
+pub struct FileIngestRecorder {
+    sender: mpsc::Sender<String>,
+    sample_rate: f64,
+    seen: AtomicU64,
+}
+
+impl FileIngestRecorder {
+    /// Opens the recording and starts its writer; `None` when recording is off. Must be called
+    /// inside a tokio runtime.
+    pub async fn start(config: &RuntimeConfig) -> Result<Option<Self>> {
+        if config.ingest_record_path.is_empty() || config.ingest_record_sample_rate <= 0.0 {
+            return Ok(None);
+        }
+        let path = config.ingest_record_path.clone();
+        if let Some(parent) = std::path::Path::new(&path).parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).await?;
+            }
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        let mut size = file.metadata().await?.len();
+        let max_bytes = config.ingest_record_max_mb.saturating_mul(1024 * 1024);
+        info!(
+            "recording {}% of ingest batches to {}",
+            config.ingest_record_sample_rate * 100.0,
+            path
+        );
+
+        let (sender, mut receiver) = mpsc::channel::<String>(RECORD_BUFFER);
+        tokio::spawn(async move {
+            while let Some(line) = receiver.recv().await {
+                if size + line.len() as u64 > max_bytes {
+                    warn!(
+                        "ingest recording {} reached its size cap, recording stopped",
+                        path
+                    );
+                    break;
+                }
+                if let Err(err) = file.write_all(line.as_bytes()).await {
+                    warn!("ingest recording {} failed, recording stopped: {}", path, err);
+                    break;
+                }
+                size += line.len() as u64;
+            }
+            let _ = file.flush().await;
+        });
+        Ok(Some(Self {
+            sender,
+            sample_rate: config.ingest_record_sample_rate,
+            seen: AtomicU64::new(0),
+        }))
+    }
+
+    /// Evenly spread sampling: batch `n` is kept when it crosses the next multiple of 1/rate.
+    fn sampled(&self) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.sample_rate).floor() > (n * self.sample_rate).floor()
+    }
+}
+
+impl IngestRecorder for FileIngestRecorder {
+    fn record(&self, recorded_at_ms: i64, events: &[IngestEvent]) {
+        if self.sender.is_closed() || !self.sampled() {
+            return;
+        }
+        let line = serde_json::json!({
+            "recorded_at_ms": recorded_at_ms,
+            "events": events,
+        });
+        if let Err(err) = self.sender.try_send(format!("{}\n", line)) {
+            if matches!(err, mpsc::error::TrySendError::Full(_)) {
+                warn!("ingest recording is behind, batch dropped");
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Local, TimeZone};
+use tracing::info;
+
+use backend_application::commands::maintenance_commands;
+use backend_application::AppState;
+
+pub async fn schedule_maintenance(state: AppState) {
+    if !state.config.maintenance_enabled {
+        return;
+    }
+    loop {
+        let next = next_maintenance_time(state.config.maintenance_hour);
+        let duration = next.signed_duration_since(Local::now());
+        let sleep_ms = duration.num_milliseconds().max(0) as u64;
+        tokio::time::sleep(std::time::Duration::from_millis(sleep_ms)).await;
+
+        let run = maintenance_commands::run_maintenance(&state).await;
+        info!(
+            "maintenance finished: status={} optimized={} errors={}",
+            run.status,
+            run.optimized.len(),
+            run.errors.len()
+        );
+    }
+}
+
+pub(crate) fn next_maintenance_time(hour: u32) -> DateTime<Local> {
+    let now = Local::now();
+    let today = now.date_naive();
+    let target = today.and_hms_opt(hour, 0, 0).unwrap();
+    let mut dt = Local.from_local_datetime(&target).unwrap();
+    if dt <= now {
+        let next_target = today.succ_opt().unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        dt = Local.from_local_datetime(&next_target).unwrap();
+    }
+    dt
+}
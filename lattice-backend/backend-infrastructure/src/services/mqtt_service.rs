@@ -0,0 +1,199 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use backend_domain::ports::{EventPublisher, EventSource};
+use backend_domain::{AnomalyRow, HealthTransition, IngestEnvelope, IngestEvent, RuntimeConfig};
+
+use super::redaction::{Redactor, REDACT_ALERT};
+
+const DEFAULT_MQTT_PORT: u16 = 1883;
+const RECONNECT_DELAY_SECONDS: u64 = 5;
+/// Requests queued for the broker; publishes beyond this are dropped rather than awaited.
+const CLIENT_CAPACITY: usize = 256;
+const INGEST_BUFFER: usize = 64;
+
+/// Splits `mqtt://host:port` (or `tcp://`, or a bare `host:port`) into host and port.
+pub fn parse_mqtt_broker_url(url: &str) -> Result<(String, u16), String> {
+    let rest = url
+        .strip_prefix("mqtt://")
+        .or_else(|| url.strip_prefix("tcp://"))
+        .unwrap_or(url);
+    if rest.contains("://") {
+        return Err(format!(
+            "mqtt_broker_url '{}' must use mqtt:// or tcp://",
+            url
+        ));
+    }
+    let rest = rest.trim_end_matches('/');
+    let (host, port) = match rest.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| format!("mqtt_broker_url '{}' has an invalid port", url))?,
+        ),
+        None => (rest, DEFAULT_MQTT_PORT),
+    };
+    if host.is_empty() {
+        return Err(format!("mqtt_broker_url '{}' has no host", url));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// The publisher and ingest source of one broker connection; either is `None` when its topics
+/// are not configured.
+pub struct MqttBridge {
+    pub publisher: Option<Arc<dyn EventPublisher>>,
+    pub source: Option<MqttEventSource>,
+}
+
+impl MqttBridge {
+    /// Connects to `mqtt_broker_url` and keeps the connection alive in the background; `None`
+    /// when MQTT is not configured. Must be called inside a tokio runtime.
+    pub fn start(config: &RuntimeConfig) -> Result<Option<Self>> {
+        if config.mqtt_broker_url.is_empty() {
+            return Ok(None);
+        }
+        let publishes =
+            !config.mqtt_anomaly_topic.is_empty() || !config.mqtt_health_topic.is_empty();
+        let ingest_topic = config.mqtt_ingest_topic.clone();
+        if !publishes && ingest_topic.is_empty() {
+            return Ok(None);
+        }
+        let (host, port) =
+            parse_mqtt_broker_url(&config.mqtt_broker_url).map_err(|err| anyhow!(err))?;
+        let mut options = MqttOptions::new(config.mqtt_client_id.clone(), host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &config.mqtt_username {
+            options.set_credentials(
+                username.clone(),
+                config.mqtt_password.clone().unwrap_or_default(),
+            );
+        }
+        let (client, mut event_loop) = AsyncClient::new(options, CLIENT_CAPACITY);
+
+        let (ingest_tx, ingest_rx) = mpsc::channel(INGEST_BUFFER);
+        let subscriber = client.clone();
+        let broker = config.mqtt_broker_url.clone();
+        tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("mqtt connected: {}", broker);
+                        // Subscriptions do not survive a reconnect with a clean session.
+                        if !ingest_topic.is_empty() {
+                            if let Err(err) =
+                                subscriber.try_subscribe(&ingest_topic, QoS::AtLeastOnce)
+                            {
+                                warn!("mqtt subscribe to {} failed: {}", ingest_topic, err);
+                            }
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        match parse_envelope(&publish.payload) {
+                            Ok(events) => {
+                                if ingest_tx.send(events).await.is_err() {
+                                    warn!("mqtt ingest consumer stopped, message dropped");
+                                }
+                            }
+                            Err(err) => {
+                                warn!("mqtt ingest ignored message on {}: {}", publish.topic, err)
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        warn!("mqtt connection to {} failed: {}", broker, err);
+                        sleep(Duration::from_secs(RECONNECT_DELAY_SECONDS)).await;
+                    }
+                }
+            }
+        });
+
+        let publisher: Option<Arc<dyn EventPublisher>> = if publishes {
+            Some(Arc::new(MqttPublisher {
+                client,
+                anomaly_topic: config.mqtt_anomaly_topic.clone(),
+                health_topic: config.mqtt_health_topic.clone(),
+                redactor: Redactor::from_config(config),
+            }))
+        } else {
+            None
+        };
+        let source = (!config.mqtt_ingest_topic.is_empty()).then(|| MqttEventSource {
+            receiver: ingest_rx,
+        });
+        Ok(Some(Self { publisher, source }))
+    }
+}
+
+/// Publishes one JSON message per anomaly and per health transition, QoS 1, not retained.
+/// Anomalies leave the backend here as they do in alerts, so the `alert` redaction rules apply.
+pub struct MqttPublisher {
+    client: AsyncClient,
+    anomaly_topic: String,
+    health_topic: String,
+    redactor: Redactor,
+}
+
+impl MqttPublisher {
+    fn publish_json(&self, topic: &str, payload: Result<Vec<u8>, serde_json::Error>) {
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("mqtt payload for {} not serializable: {}", topic, err);
+                return;
+            }
+        };
+        if let Err(err) = self
+            .client
+            .try_publish(topic, QoS::AtLeastOnce, false, payload)
+        {
+            warn!("mqtt publish to {} dropped: {}", topic, err);
+        }
+    }
+}
+
+impl EventPublisher for MqttPublisher {
+    fn publish_anomalies(&self, anomalies: &[AnomalyRow]) {
+        if self.anomaly_topic.is_empty() {
+            return;
+        }
+        let mut anomalies = anomalies.to_vec();
+        self.redactor.redact_rows(REDACT_ALERT, &mut anomalies);
+        for anomaly in &anomalies {
+            self.publish_json(&self.anomaly_topic, serde_json::to_vec(anomaly));
+        }
+    }
+
+    fn publish_health(&self, transition: &HealthTransition) {
+        if self.health_topic.is_empty() {
+            return;
+        }
+        self.publish_json(&self.health_topic, serde_json::to_vec(transition));
+    }
+}
+
+/// Ingest envelopes received on `mqtt_ingest_topic`, already parsed.
+pub struct MqttEventSource {
+    receiver: mpsc::Receiver<Vec<IngestEvent>>,
+}
+
+#[async_trait]
+impl EventSource for MqttEventSource {
+    async fn next_batch(&mut self) -> Option<Vec<IngestEvent>> {
+        self.receiver.recv().await
+    }
+}
+
+/// Same `IngestEnvelope` as `POST /v2/ingest/events`, uncompressed.
+fn parse_envelope(payload: &[u8]) -> Result<Vec<IngestEvent>> {
+    let envelope: IngestEnvelope = serde_json::from_slice(payload)?;
+    envelope.into_events().map_err(|err| anyhow!(err))
+}
@@ -0,0 +1,258 @@
+use regex::Regex;
+use serde_json::Value;
+use tracing::warn;
+
+use backend_domain::{AnomalyRow, ItemEventRow, RedactionRule, RuntimeConfig};
+
+pub const REDACT_REPORT: &str = "report";
+pub const REDACT_ALERT: &str = "alert";
+const REDACT_TARGETS: [&str; 2] = [REDACT_REPORT, REDACT_ALERT];
+
+struct CompiledRule {
+    field: Option<String>,
+    pattern: Option<Regex>,
+    replacement: String,
+    targets: Vec<String>,
+}
+
+impl CompiledRule {
+    fn applies_to(&self, target: &str) -> bool {
+        self.targets.is_empty() || self.targets.iter().any(|item| item == target)
+    }
+
+    fn replace(&self, value: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern
+                .replace_all(value, self.replacement.as_str())
+                .into_owned(),
+            None => self.replacement.clone(),
+        }
+    }
+}
+
+/// Sanitizer shared by reports, alerts and MQTT messages, built from `redaction_rules`.
+#[derive(Default)]
+pub struct Redactor {
+    rules: Vec<CompiledRule>,
+}
+
+impl Redactor {
+    pub fn new(rules: &[RedactionRule]) -> Result<Self, String> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.field.is_none() && rule.pattern.is_none() {
+                return Err(format!(
+                    "redaction rule {} needs a field or a pattern",
+                    index + 1
+                ));
+            }
+            if let Some(target) = rule
+                .targets
+                .iter()
+                .find(|target| !REDACT_TARGETS.contains(&target.as_str()))
+            {
+                return Err(format!(
+                    "redaction rule {}: unknown target '{}', expected report or alert",
+                    index + 1,
+                    target
+                ));
+            }
+            let pattern = rule
+                .pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|err| format!("redaction rule {}: {}", index + 1, err))?;
+            compiled.push(CompiledRule {
+                field: rule.field.clone(),
+                pattern,
+                replacement: rule.replacement.clone(),
+                targets: rule.targets.clone(),
+            });
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    /// Rules were validated at startup, so a failure here only drops redaction with a warning.
+    pub fn from_config(config: &RuntimeConfig) -> Self {
+        Self::new(&config.redaction_rules).unwrap_or_else(|err| {
+            warn!("redaction disabled: {}", err);
+            Self::default()
+        })
+    }
+
+    pub fn redact_rows(&self, target: &str, rows: &mut [AnomalyRow]) {
+        if self.rules.iter().all(|rule| !rule.applies_to(target)) {
+            return;
+        }
+        for row in rows {
+            row.player_name = self.redact_field(target, "player_name", &row.player_name);
+            row.reason = self.redact_field(target, "reason", &row.reason);
+            row.evidence_json = self.redact_evidence(target, &row.evidence_json);
+        }
+    }
+
+    /// The free-text columns of item events, by column name like the anomaly fields.
+    pub fn redact_events(&self, target: &str, rows: &mut [ItemEventRow]) {
+        if self.rules.iter().all(|rule| !rule.applies_to(target)) {
+            return;
+        }
+        for row in rows {
+            row.player_name = self.redact_field(target, "player_name", &row.player_name);
+            row.origin_ref = self.redact_field(target, "origin_ref", &row.origin_ref);
+            row.source_ref = self.redact_field(target, "source_ref", &row.source_ref);
+            row.storage_id = self.redact_field(target, "storage_id", &row.storage_id);
+        }
+    }
+
+    /// `value` of the top-level `field` after every applicable rule; pattern-only rules cover
+    /// `reason` but not names.
+    pub fn redact_field(&self, target: &str, field: &str, value: &str) -> String {
+        let mut value = value.to_string();
+        for rule in self.rules.iter().filter(|rule| rule.applies_to(target)) {
+            let matches = match rule.field.as_deref() {
+                Some(rule_field) => rule_field == field,
+                None => field == "reason",
+            };
+            if matches {
+                value = rule.replace(&value);
+            }
+        }
+        value
+    }
+
+    fn redact_evidence(&self, target: &str, evidence_json: &str) -> String {
+        let Ok(mut evidence) = serde_json::from_str::<Value>(evidence_json) else {
+            return evidence_json.to_string();
+        };
+        for rule in self.rules.iter().filter(|rule| rule.applies_to(target)) {
+            redact_value(rule, &mut evidence, false);
+        }
+        evidence.to_string()
+    }
+}
+
+/// Walks the evidence; `inside` is true below a key named by the rule's field.
+fn redact_value(rule: &CompiledRule, value: &mut Value, inside: bool) {
+    let applies = inside || rule.field.is_none();
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let inside = inside || rule.field.as_deref() == Some(key.as_str());
+                redact_value(rule, child, inside);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_value(rule, item, inside);
+            }
+        }
+        Value::String(text) if applies => *text = rule.replace(text),
+        Value::Null => {}
+        _ if inside && rule.pattern.is_none() => *value = Value::String(rule.replacement.clone()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::anomaly_row;
+
+    fn rule(field: Option<&str>, pattern: Option<&str>, targets: &[&str]) -> RedactionRule {
+        RedactionRule {
+            field: field.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+            targets: targets.iter().map(|target| target.to_string()).collect(),
+            ..RedactionRule::default()
+        }
+    }
+
+    fn row() -> AnomalyRow {
+        AnomalyRow {
+            player_name: "Steve".to_string(),
+            reason: "joined from 10.0.0.7".to_string(),
+            evidence_json: r#"{"ip":"10.0.0.7","pos":{"x":12,"y":64},"note":"seen at 10.0.0.7"}"#
+                .to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    fn evidence(row: &AnomalyRow) -> Value {
+        serde_json::from_str(&row.evidence_json).expect("evidence json")
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(Redactor::new(&[rule(None, None, &[])]).is_err());
+        assert!(Redactor::new(&[rule(Some("pos"), None, &["mqtt"])]).is_err());
+        assert!(Redactor::new(&[rule(None, Some("("), &[])]).is_err());
+        assert!(Redactor::new(&[rule(Some("pos"), None, &[REDACT_REPORT, REDACT_ALERT])]).is_ok());
+    }
+
+    #[test]
+    fn field_rules_replace_the_whole_value_below_their_key() {
+        let redactor = Redactor::new(&[
+            rule(Some("player_name"), None, &[]),
+            rule(Some("pos"), None, &[]),
+        ])
+        .expect("rules");
+        let mut rows = vec![row()];
+        redactor.redact_rows(REDACT_ALERT, &mut rows);
+
+        assert_eq!(rows[0].player_name, "[redacted]");
+        assert_eq!(rows[0].reason, "joined from 10.0.0.7");
+        let evidence = evidence(&rows[0]);
+        assert_eq!(evidence["pos"]["x"], "[redacted]");
+        assert_eq!(evidence["pos"]["y"], "[redacted]");
+        assert_eq!(evidence["ip"], "10.0.0.7");
+    }
+
+    #[test]
+    fn patterns_replace_matches_in_reason_and_evidence_but_not_names() {
+        let ip = RedactionRule {
+            replacement: "[ip]".to_string(),
+            ..rule(None, Some(r"\d+\.\d+\.\d+\.\d+"), &[])
+        };
+        let named = RedactionRule {
+            replacement: "S***".to_string(),
+            ..rule(Some("player_name"), Some("^St.*"), &[])
+        };
+        let redactor = Redactor::new(&[ip, named]).expect("rules");
+        let mut rows = vec![AnomalyRow {
+            player_name: "Steve 10.0.0.7".to_string(),
+            ..row()
+        }];
+        redactor.redact_rows(REDACT_REPORT, &mut rows);
+
+        assert_eq!(rows[0].player_name, "S***");
+        assert_eq!(rows[0].reason, "joined from [ip]");
+        let evidence = evidence(&rows[0]);
+        assert_eq!(evidence["ip"], "[ip]");
+        assert_eq!(evidence["note"], "seen at [ip]");
+        assert_eq!(evidence["pos"]["x"], 12);
+    }
+
+    #[test]
+    fn rules_only_touch_the_channels_they_target() {
+        let redactor = Redactor::new(&[
+            rule(Some("player_name"), None, &[REDACT_REPORT]),
+            RedactionRule {
+                replacement: "[hidden]".to_string(),
+                ..rule(Some("reason"), None, &[REDACT_ALERT])
+            },
+        ])
+        .expect("rules");
+
+        let mut report = vec![row()];
+        redactor.redact_rows(REDACT_REPORT, &mut report);
+        assert_eq!(report[0].player_name, "[redacted]");
+        assert_eq!(report[0].reason, "joined from 10.0.0.7");
+
+        let mut alert = vec![row()];
+        redactor.redact_rows(REDACT_ALERT, &mut alert);
+        assert_eq!(alert[0].player_name, "Steve");
+        assert_eq!(alert[0].reason, "[hidden]");
+        assert_eq!(evidence(&alert[0]), evidence(&row()));
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use backend_domain::{
+    anomaly_id, anomaly_link, rule_description, AnomalyRow, ItemEventRow, PlayerAnomalyCount,
+    RuntimeConfig, TimeDisplay, DEFAULT_RULE_LANG,
+};
+
+/// File name of a player's drill-down page under `{report_dir}/{date}/players/`. UUIDs are
+/// already hex and dashes; anything else is replaced so the UUID stays a single path segment.
+pub fn player_page_file(player_uuid: &str) -> String {
+    let slug: String = player_uuid
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if slug.is_empty() {
+        "unknown.html".to_string()
+    } else {
+        format!("{}.html", slug)
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Timeline, rule breakdown and evidence for one player; `rows` are that player's anomalies of
+/// the day in any order, `events` the latest of their item events, at most `event_limit`.
+pub fn render_player_page(
+    date: &str,
+    player: &PlayerAnomalyCount,
+    rows: &[AnomalyRow],
+    events: &[ItemEventRow],
+    event_limit: usize,
+    config: &RuntimeConfig,
+) -> String {
+    let mut timeline: Vec<&AnomalyRow> = rows.iter().collect();
+    timeline.sort_by_key(|row| row.event_time);
+
+    let mut rules: BTreeMap<&str, (u64, i64)> = BTreeMap::new();
+    for row in &timeline {
+        let entry = rules.entry(row.rule_id.as_str()).or_default();
+        entry.0 += 1;
+        entry.1 += row.count;
+    }
+    let rule_rows: String = rules
+        .iter()
+        .map(|(rule_id, (anomalies, items))| {
+            format!(
+                "<tr><td>{rule}</td><td>{description}</td><td class=\"count\">{anomalies}</td><td class=\"count\">{items}</td></tr>",
+                rule = escape_html(rule_id),
+                description = escape_html(rule_description(rule_id, DEFAULT_RULE_LANG)),
+                anomalies = anomalies,
+                items = items,
+            )
+        })
+        .collect();
+
+    let display = TimeDisplay::from_config(config);
+    let timeline_rows: String = timeline
+        .iter()
+        .map(|row| {
+            let time = match anomaly_link(config, row) {
+                Some(link) => format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(&link),
+                    escape_html(&display.format(row.event_time))
+                ),
+                None => escape_html(&display.format(row.event_time)),
+            };
+            format!(
+                "<tr id=\"{id}\"><td>{time}</td><td>{server}</td><td class=\"item\">{item}</td><td class=\"count\">{count}</td><td>{risk}</td><td>{rule}</td><td>{reason}<details><summary>evidence</summary><pre>{evidence}</pre></details></td></tr>",
+                id = anomaly_id(row),
+                time = time,
+                server = escape_html(&row.server_id),
+                item = escape_html(&row.item_id),
+                count = row.count,
+                risk = escape_html(&row.risk_level),
+                rule = escape_html(&row.rule_id),
+                reason = escape_html(&row.reason),
+                evidence = escape_html(&pretty_evidence(&row.evidence_json)),
+            )
+        })
+        .collect();
+
+    let mut event_timeline: Vec<&ItemEventRow> = events.iter().collect();
+    event_timeline.sort_by_key(|event| event.event_time);
+    let event_rows: String = event_timeline
+        .iter()
+        .map(|event| {
+            format!(
+                "<tr><td>{time}</td><td>{server}</td><td>{event_type}</td><td class=\"item\">{item}</td><td class=\"count\">{count}</td><td>{origin}</td><td>{source}</td><td class=\"item\">{storage}</td></tr>",
+                time = escape_html(&display.format(event.event_time)),
+                server = escape_html(&event.server_id),
+                event_type = escape_html(&event.event_type),
+                item = escape_html(&event.item_id),
+                count = event.count,
+                origin = escape_html(&joined(&event.origin_type, &event.origin_ref)),
+                source = escape_html(&joined(&event.source_type, &event.source_ref)),
+                storage = escape_html(&joined(&event.storage_mod, &event.storage_id)),
+            )
+        })
+        .collect();
+    let event_note = if events.len() >= event_limit {
+        format!("latest {} events of the day", event_limit)
+    } else {
+        format!("{} events of the day", events.len())
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8" />
+<meta name="viewport" content="width=device-width, initial-scale=1" />
+<title>{player} · Lattice Report {date}</title>
+<style>
+body {{ margin: 0; font-family: "IBM Plex Sans", "Source Sans 3", "Noto Sans SC", sans-serif; background: #0f172a; color: #e2e8f0; }}
+.page {{ max-width: 1200px; margin: 0 auto; padding: 32px 20px 48px; }}
+a {{ color: #93c5fd; }}
+h1 {{ margin: 8px 0 4px; font-size: 26px; }}
+h2 {{ margin: 28px 0 10px; font-size: 18px; }}
+.meta {{ color: #94a3b8; font-size: 14px; }}
+table {{ width: 100%; border-collapse: collapse; font-size: 14px; background: #ffffff; color: #0f172a; border-radius: 12px; overflow: hidden; }}
+th {{ text-align: left; font-size: 11px; letter-spacing: 0.12em; text-transform: uppercase; color: #64748b; background: #f1f5f9; padding: 10px 12px; }}
+td {{ padding: 10px 12px; border-bottom: 1px solid #e2e8f0; vertical-align: top; }}
+td a {{ color: inherit; }}
+.count {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.item, pre {{ font-family: "IBM Plex Mono", "JetBrains Mono", "SFMono-Regular", monospace; font-size: 12px; }}
+pre {{ white-space: pre-wrap; margin: 6px 0 0; }}
+</style>
+</head>
+<body>
+<div class="page">
+  <a href="../../{date}.html">&larr; {date}</a>
+  <h1>{player}</h1>
+  <div class="meta">{anomalies} anomalies · {critical} CRITICAL · {high} HIGH</div>
+
+  <h2>Rules</h2>
+  <table>
+    <thead><tr><th>Rule</th><th>Description</th><th>Anomalies</th><th>Items</th></tr></thead>
+    <tbody>{rule_rows}</tbody>
+  </table>
+
+  <h2>Timeline</h2>
+  <table>
+    <thead><tr><th>Time</th><th>Server</th><th>Item</th><th>Count</th><th>Risk</th><th>Rule</th><th>Reason</th></tr></thead>
+    <tbody>{timeline_rows}</tbody>
+  </table>
+
+  <h2>Events</h2>
+  <div class="meta">{event_note}</div>
+  <table>
+    <thead><tr><th>Time</th><th>Server</th><th>Type</th><th>Item</th><th>Count</th><th>Origin</th><th>Source</th><th>Storage</th></tr></thead>
+    <tbody>{event_rows}</tbody>
+  </table>
+</div>
+</body>
+</html>"#,
+        date = date,
+        player = escape_html(&player.player_name),
+        anomalies = player.anomalies,
+        critical = player.critical,
+        high = player.high,
+        rule_rows = rule_rows,
+        timeline_rows = timeline_rows,
+        event_note = event_note,
+        event_rows = event_rows,
+    )
+}
+
+/// `kind:reference`, or whichever of the two is set.
+fn joined(kind: &str, reference: &str) -> String {
+    match (kind.is_empty(), reference.is_empty()) {
+        (false, false) => format!("{}:{}", kind, reference),
+        (false, true) => kind.to_string(),
+        _ => reference.to_string(),
+    }
+}
+
+fn pretty_evidence(evidence_json: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(evidence_json)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or_else(|_| evidence_json.to_string())
+}
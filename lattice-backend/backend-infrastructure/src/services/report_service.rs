@@ -1,12 +1,44 @@
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::Result;
-use chrono::{DateTime, Local, TimeZone};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
 use tokio::fs;
 use tracing::error;
 
+use backend_application::commands::report_commands;
+use backend_application::ops::RuleRevisionLog;
+use backend_application::queries::report_queries;
 use backend_application::AppState;
-use backend_domain::{AnomalyRow, ReportSummary, RuntimeConfig};
+use backend_domain::ports::{AnomalyRepository, EventRepository, ReportService};
+use backend_domain::{
+    anomaly_id, anomaly_link, current_millis, is_persisting_finding, millis_to_utc, AnomalyRow,
+    FieldSelection, ItemEventFilter, PlayerAnomalyCount, ReportSummary, RuleRevision,
+    RuntimeConfig, TimeDisplay,
+};
+
+use super::redaction::{Redactor, REDACT_REPORT};
+use super::report_player_pages::{escape_html, player_page_file, render_player_page};
+use super::rule_hygiene_service::generate_rule_hygiene_report;
+
+/// Built-in `webhook_template`s by `report_webhook_locale`.
+const REPORT_TEMPLATE_ZH_CN: &str = r#"{"message":"[Lattice 日报] {date}\n总异常 {total}（严重{critical} / 高{high} / 中{medium} / 低{low}），较昨日 {total_delta}\n规则: {rule_totals}\n主要玩家: {top_players}\n报告: {link}"}"#;
+const REPORT_TEMPLATE_EN: &str = r#"{"message":"[Lattice daily report] {date}\n{total} anomalies (critical {critical} / high {high} / medium {medium} / low {low}), {total_delta} vs yesterday\nRules: {rule_totals}\nTop players: {top_players}\nReport: {link}"}"#;
+/// Offenders named by `{top_players}`.
+const REPORT_WEBHOOK_TOP_PLAYERS: usize = 3;
+/// Latest item events of the day shown on a player page.
+const PLAYER_PAGE_EVENTS: usize = 500;
+
+/// What the report webhook template can refer to.
+struct ReportDigest {
+    summary: ReportSummary,
+    yesterday_total: u64,
+    /// Most anomalies first.
+    rule_totals: Vec<(String, u64)>,
+    top_players: Vec<String>,
+}
 
 pub async fn schedule_reports(state: AppState) {
     loop {
@@ -18,56 +50,442 @@ pub async fn schedule_reports(state: AppState) {
         if let Err(err) = generate_daily_report(&state).await {
             error!("report generation failed: {}", err);
         }
+        let today = Local::now().date_naive();
+        if today.day() == state.config.rule_hygiene_report_day {
+            if let Err(err) = generate_rule_hygiene_report(&state, today).await {
+                error!("rule hygiene report failed: {}", err);
+            }
+        }
     }
 }
 
 pub async fn generate_daily_report(state: &AppState) -> Result<()> {
-    let date = Local::now().format("%Y-%m-%d").to_string();
-    let summary = state.anomaly_repo.fetch_summary(&date).await?;
-    let detail = state.anomaly_repo.fetch_anomalies(&date, None).await?;
+    let today = Local::now().date_naive();
+    let date = today.format("%Y-%m-%d").to_string();
+    rollup_daily_summaries(state, today).await;
+    let summary = write_report(
+        &state.config,
+        state.anomaly_repo.as_ref(),
+        state.event_repo.as_ref(),
+        &state.rule_revisions,
+        &date,
+    )
+    .await?;
+    if let Err(err) = report_commands::prune_reports(state).await {
+        error!("report pruning failed: {}", err);
+    }
+
+    if let Some(url) = &state.config.webhook_url {
+        let report_link = report_queries::report_url(&state.config, &date, current_millis());
+        let digest = report_digest(state, today, summary).await?;
+        let default_template = match state.config.report_webhook_locale.as_str() {
+            "en" => REPORT_TEMPLATE_EN,
+            _ => REPORT_TEMPLATE_ZH_CN,
+        };
+        let template = state
+            .config
+            .webhook_template
+            .as_deref()
+            .unwrap_or(default_template);
+        send_webhook(url, template, &date, &digest, &report_link).await?;
+    }
+
+    Ok(())
+}
 
-    let report_dir = Path::new(&state.config.report_dir);
+/// Per-rule totals and yesterday's total from the daily summary rollup, and the day's top
+/// offenders with report redaction applied.
+async fn report_digest(
+    state: &AppState,
+    today: NaiveDate,
+    summary: ReportSummary,
+) -> Result<ReportDigest> {
+    let date = today.format("%Y-%m-%d").to_string();
+    let yesterday = today
+        .pred_opt()
+        .map(|day| day.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| date.clone());
+    let rows = state
+        .anomaly_repo
+        .fetch_daily_summary(&yesterday, &date, None, None)
+        .await?;
+    let mut yesterday_total = 0;
+    let mut per_rule: BTreeMap<String, u64> = BTreeMap::new();
+    for row in rows {
+        if row.date == date {
+            *per_rule.entry(row.rule_id).or_default() += row.count;
+        } else if row.date == yesterday {
+            yesterday_total += row.count;
+        }
+    }
+    let mut rule_totals: Vec<(String, u64)> = per_rule.into_iter().collect();
+    rule_totals.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let redactor = Redactor::from_config(&state.config);
+    let top_players = state
+        .anomaly_repo
+        .fetch_top_players(&date, REPORT_WEBHOOK_TOP_PLAYERS)
+        .await?
+        .into_iter()
+        .map(|player| redactor.redact_field(REDACT_REPORT, "player_name", &player.player_name))
+        .collect();
+    Ok(ReportDigest {
+        summary,
+        yesterday_total,
+        rule_totals,
+        top_players,
+    })
+}
+
+/// Anomaly counts of the report day between two key item rule changes (or the day's start/end).
+pub struct RulePeriod {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub summary: ReportSummary,
+}
+
+/// Who the report links to: its "Top players" list, and each player's page by UUID, relative to
+/// the main report.
+#[derive(Default)]
+pub struct ReportPlayers {
+    pub top: Vec<PlayerAnomalyCount>,
+    pub pages: HashMap<String, String>,
+}
+
+/// Renders `{report_dir}/{date}.html` and its player pages from what ClickHouse holds for `date`,
+/// replacing an existing report. Rule changes made that day are marked and split the summary.
+pub async fn write_report(
+    config: &RuntimeConfig,
+    anomaly_repo: &dyn AnomalyRepository,
+    event_repo: &dyn EventRepository,
+    rule_revisions: &RuleRevisionLog,
+    date: &str,
+) -> Result<ReportSummary> {
+    let summary = anomaly_repo.fetch_summary(date).await?;
+    let mut detail = anomaly_repo.fetch_anomalies(date, None).await?;
+    let mut top_players = if config.report_player_pages == 0 {
+        Vec::new()
+    } else {
+        anomaly_repo
+            .fetch_top_players(date, config.report_player_pages)
+            .await?
+    };
+    let mut players: BTreeMap<String, String> = BTreeMap::new();
+    for (uuid, name) in detail
+        .iter()
+        .map(|row| (&row.player_uuid, &row.player_name))
+        .chain(
+            top_players
+                .iter()
+                .map(|player| (&player.player_uuid, &player.player_name)),
+        )
+    {
+        if !uuid.is_empty() {
+            players.entry(uuid.clone()).or_insert_with(|| name.clone());
+        }
+    }
+    let redactor = Redactor::from_config(config);
+    redactor.redact_rows(REDACT_REPORT, &mut detail);
+    for player in &mut top_players {
+        player.player_name =
+            redactor.redact_field(REDACT_REPORT, "player_name", &player.player_name);
+    }
+
+    let report_dir = Path::new(&config.report_dir);
     fs::create_dir_all(report_dir).await?;
     let path = report_dir.join(format!("{}.html", date));
 
-    let html = render_report(&date, &summary, &detail);
+    let pages = write_player_pages(
+        config,
+        anomaly_repo,
+        event_repo,
+        date,
+        report_dir,
+        &redactor,
+        &players,
+    )
+    .await?;
+    let (revisions, periods) = rule_periods(anomaly_repo, rule_revisions, date).await?;
+    let html = render_report(
+        date,
+        &summary,
+        &detail,
+        &ReportPlayers {
+            top: top_players,
+            pages,
+        },
+        &revisions,
+        &periods,
+        config,
+    );
     fs::write(&path, html).await?;
+    Ok(summary)
+}
 
-    if let Some(url) = &state.config.webhook_url {
-        let report_link = format!("{}/reports/{}", state.config.public_base_url, date);
-        send_webhook(url, state.config.webhook_template.as_deref(), &date, &summary, &report_link).await?;
+/// On-demand generation for `POST /v2/ops/reports/{date}`; unlike the daily schedule it neither
+/// prunes nor sends the report webhook.
+pub struct HtmlReportService {
+    config: RuntimeConfig,
+    anomaly_repo: Arc<dyn AnomalyRepository>,
+    event_repo: Arc<dyn EventRepository>,
+    rule_revisions: Arc<RuleRevisionLog>,
+}
+
+impl HtmlReportService {
+    pub fn new(
+        config: RuntimeConfig,
+        anomaly_repo: Arc<dyn AnomalyRepository>,
+        event_repo: Arc<dyn EventRepository>,
+        rule_revisions: Arc<RuleRevisionLog>,
+    ) -> Self {
+        Self {
+            config,
+            anomaly_repo,
+            event_repo,
+            rule_revisions,
+        }
     }
+}
 
-    Ok(())
+#[async_trait]
+impl ReportService for HtmlReportService {
+    async fn generate_report(&self, date: &str) -> Result<()> {
+        if let Err(err) = self.anomaly_repo.rollup_daily_summary(date).await {
+            error!("daily summary rollup failed for {}: {}", date, err);
+        }
+        write_report(
+            &self.config,
+            self.anomaly_repo.as_ref(),
+            self.event_repo.as_ref(),
+            &self.rule_revisions,
+            date,
+        )
+        .await?;
+        Ok(())
+    }
 }
 
-pub fn render_report(date: &str, summary: &ReportSummary, detail: &[AnomalyRow]) -> String {
-    let mut rows = String::new();
-    for item in detail.iter().take(500) {
-        let risk_class = match item.risk_level.as_str() {
-            "HIGH" => "risk-high",
-            "MEDIUM" => "risk-medium",
-            "LOW" => "risk-low",
-            _ => "risk-unknown",
+/// Writes `{report_dir}/{date}/players/{uuid}.html` for each of `players` (UUID to name) and
+/// returns the page paths relative to the main report by UUID. Each page holds the player's
+/// anomalies and latest item events of the day. A redacted name gets a numbered file so neither
+/// it nor the UUID shows up in the URL.
+async fn write_player_pages(
+    config: &RuntimeConfig,
+    anomaly_repo: &dyn AnomalyRepository,
+    event_repo: &dyn EventRepository,
+    date: &str,
+    report_dir: &Path,
+    redactor: &Redactor,
+    players: &BTreeMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut pages = HashMap::with_capacity(players.len());
+    if players.is_empty() {
+        return Ok(pages);
+    }
+    let pages_dir = report_dir.join(date).join("players");
+    fs::create_dir_all(&pages_dir).await?;
+    for (index, (uuid, player_name)) in players.iter().enumerate() {
+        let mut rows = anomaly_repo
+            .fetch_anomalies(date, Some(player_name))
+            .await?;
+        rows.retain(|row| &row.player_uuid == uuid);
+        redactor.redact_rows(REDACT_REPORT, &mut rows);
+        let filter = ItemEventFilter {
+            date: date.to_string(),
+            player: Some(player_name.clone()),
+            item_id: None,
+            storage_id: None,
+            server_id: None,
+            event_type: None,
         };
-        rows.push_str(&format!(
-            "<tr data-risk=\"{risk}\" data-player=\"{player}\" data-item=\"{item}\">\
-            <td class=\"time\">{time}</td>\
-            <td class=\"player\">{player}</td>\
-            <td class=\"item\">{item}</td>\
-            <td class=\"count\">{count}</td>\
-            <td class=\"risk\"><span class=\"badge {risk_class}\">{risk}</span></td>\
-            <td class=\"reason\">{reason}</td>\
-            </tr>",
-            time = item.event_time,
-            player = item.player_name,
-            item = item.item_id,
-            count = item.count,
-            risk = item.risk_level,
-            risk_class = risk_class,
-            reason = item.reason
+        let mut events = event_repo
+            .fetch_item_events_page(&filter, 0, PLAYER_PAGE_EVENTS, &FieldSelection::default())
+            .await?;
+        events.retain(|event| &event.player_uuid == uuid);
+        redactor.redact_events(REDACT_REPORT, &mut events);
+        let name = redactor.redact_field(REDACT_REPORT, "player_name", player_name);
+        let file = if &name == player_name {
+            player_page_file(uuid)
+        } else {
+            format!("player-{}.html", index + 1)
+        };
+        let player = PlayerAnomalyCount {
+            player_uuid: uuid.clone(),
+            player_name: name,
+            anomalies: rows.len() as u64,
+            critical: rows
+                .iter()
+                .filter(|row| row.risk_level == "CRITICAL")
+                .count() as u64,
+            high: rows.iter().filter(|row| row.risk_level == "HIGH").count() as u64,
+        };
+        let html = render_player_page(date, &player, &rows, &events, PLAYER_PAGE_EVENTS, config);
+        fs::write(pages_dir.join(&file), html).await?;
+        pages.insert(uuid.clone(), format!("{}/players/{}", date, file));
+    }
+    Ok(pages)
+}
+
+/// Rule revisions of `date` (local time) and the summary of each period they split the day into;
+/// both empty when the rules did not change that day.
+async fn rule_periods(
+    anomaly_repo: &dyn AnomalyRepository,
+    rule_revisions: &RuleRevisionLog,
+    date: &str,
+) -> Result<(Vec<RuleRevision>, Vec<RulePeriod>)> {
+    let Some((day_start_ms, day_end_ms)) = local_day_bounds(date) else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let revisions = rule_revisions.between(day_start_ms, day_end_ms).await;
+    if revisions.is_empty() {
+        return Ok((revisions, Vec::new()));
+    }
+    let mut bounds = vec![day_start_ms];
+    bounds.extend(revisions.iter().map(|revision| revision.changed_at_ms));
+    bounds.push(day_end_ms);
+    let mut periods = Vec::with_capacity(bounds.len() - 1);
+    for pair in bounds.windows(2) {
+        periods.push(RulePeriod {
+            from_ms: pair[0],
+            to_ms: pair[1],
+            summary: anomaly_repo.fetch_summary_between(pair[0], pair[1]).await?,
+        });
+    }
+    Ok((revisions, periods))
+}
+
+fn local_day_bounds(date: &str) -> Option<(i64, i64)> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let start = |day: NaiveDate| {
+        Local
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+            .earliest()
+            .map(|time| time.timestamp_millis())
+    };
+    Some((start(day)?, start(day.succ_opt()?)?))
+}
+
+/// `HH:MM` in local time; the end of the day reads `24:00`.
+fn local_clock(ms: i64, day_end_ms: i64) -> String {
+    if ms >= day_end_ms {
+        return "24:00".to_string();
+    }
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn rule_change_marker(revision: &RuleRevision) -> String {
+    let time = local_clock(revision.changed_at_ms, i64::MAX);
+    format!(
+        "<tr class=\"rule-change\"><td colspan=\"6\"><span data-i18n=\"rule_change_marker\" data-time=\"{time}\">Configuration changed at {time}</span> · {summary}</td></tr>",
+        time = time,
+        summary = escape_html(&revision.summary),
+    )
+}
+
+async fn rollup_daily_summaries(state: &AppState, today: NaiveDate) {
+    // The previous day is re-rolled as well so late inserts after its last report are counted.
+    let days = [today.pred_opt(), Some(today)];
+    for day in days.into_iter().flatten() {
+        let date = day.format("%Y-%m-%d").to_string();
+        if let Err(err) = state.anomaly_repo.rollup_daily_summary(&date).await {
+            error!("daily summary rollup failed for {}: {}", date, err);
+        }
+    }
+}
+
+pub fn render_report(
+    date: &str,
+    summary: &ReportSummary,
+    detail: &[AnomalyRow],
+    players: &ReportPlayers,
+    revisions: &[RuleRevision],
+    periods: &[RulePeriod],
+    config: &RuntimeConfig,
+) -> String {
+    let (persisting, active): (Vec<&AnomalyRow>, Vec<&AnomalyRow>) =
+        detail.iter().partition(|row| is_persisting_finding(row));
+    let player_pages = &players.pages;
+    let shown: Vec<&AnomalyRow> = active.iter().copied().take(500).collect();
+    // Rows are newest first, so markers go in from the latest change backwards.
+    let mut rows = String::new();
+    let mut rest = shown.as_slice();
+    for revision in revisions.iter().rev() {
+        let changed_at = millis_to_utc(revision.changed_at_ms);
+        let split = rest
+            .iter()
+            .position(|row| row.event_time < changed_at)
+            .unwrap_or(rest.len());
+        rows.push_str(&render_rows(
+            rest[..split].iter().copied(),
+            player_pages,
+            config,
         ));
+        rows.push_str(&rule_change_marker(revision));
+        rest = &rest[split..];
     }
+    rows.push_str(&render_rows(rest.iter().copied(), player_pages, config));
+    let rule_changes_section = render_rule_changes(revisions, periods);
+    let top_players_section = if players.top.is_empty() {
+        String::new()
+    } else {
+        let items: String = players
+            .top
+            .iter()
+            .map(|player| {
+                let name = match player_pages.get(&player.player_uuid) {
+                    Some(href) => format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(href),
+                        escape_html(&player.player_name)
+                    ),
+                    None => escape_html(&player.player_name),
+                };
+                format!(
+                    "<li>{name} <span>{anomalies} · {critical} CRITICAL · {high} HIGH</span></li>",
+                    name = name,
+                    anomalies = player.anomalies,
+                    critical = player.critical,
+                    high = player.high,
+                )
+            })
+            .collect();
+        format!(
+            r#"<section class="top-players">
+    <h2 data-i18n="top_players_title">Top players</h2>
+    <ol>{items}</ol>
+  </section>"#,
+            items = items
+        )
+    };
+    let persisting_section = if persisting.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#"<section class="persisting">
+    <h2 data-i18n="persisting_title">Persisting storage findings</h2>
+    <p data-i18n="persisting_hint">Flagged by an earlier scan with the same or a lower count; not alerted again.</p>
+    <div class="table-wrap">
+      <table class="table">
+        <thead><tr>
+          <th data-i18n="th_time">Time</th>
+          <th data-i18n="th_player">Player</th>
+          <th data-i18n="th_item">Item</th>
+          <th data-i18n="th_count">Count</th>
+          <th data-i18n="th_risk">Risk</th>
+          <th data-i18n="th_reason">Reason</th>
+        </tr></thead>
+        <tbody>
+        {rows}
+        </tbody>
+      </table>
+    </div>
+  </section>"#,
+            rows = render_rows(persisting.iter().copied().take(500), player_pages, config)
+        )
+    };
 
     format!(
         r#"<!DOCTYPE html>
@@ -87,6 +505,7 @@ pub fn render_report(date: &str, summary: &ReportSummary, detail: &[AnomalyRow])
   --border: #e2e8f0;
   --shadow: rgba(15, 23, 42, 0.14);
   --accent: #2563eb;
+  --critical: #7f1d1d;
   --high: #dc2626;
   --medium: #f59e0b;
   --low: #16a34a;
@@ -218,6 +637,7 @@ body {{
   vertical-align: middle;
 }}
 .table tbody tr:nth-child(even) {{ background: #f8fafc; }}
+.table td.time a {{ color: inherit; text-decoration: underline dotted; }}
 .table tbody tr:hover {{ background: #eef2ff; }}
 .table .count {{
   text-align: right;
@@ -238,6 +658,7 @@ body {{
   font-weight: 600;
   color: white;
 }}
+.risk-critical {{ background: var(--critical); }}
 .risk-high {{ background: var(--high); }}
 .risk-medium {{ background: var(--medium); }}
 .risk-low {{ background: var(--low); }}
@@ -247,6 +668,21 @@ body {{
   text-align: center;
   color: var(--muted);
 }}
+.persisting {{ margin-top: 28px; }}
+.persisting h2 {{ margin: 0 0 4px; font-size: 18px; }}
+.persisting p {{ margin: 0 0 12px; color: var(--muted); font-size: 13px; }}
+.rule-changes {{ margin-top: 24px; }}
+.rule-changes h2 {{ margin: 0 0 4px; font-size: 18px; }}
+.rule-changes p {{ margin: 0 0 8px; color: var(--muted); font-size: 13px; }}
+.rule-changes ul {{ margin: 0 0 12px; padding-left: 20px; font-size: 14px; }}
+.rule-changes li span {{ color: #94a3b8; font-size: 12px; }}
+.table tbody tr.rule-change td {{ background: #fef3c7; color: #92400e; font-size: 13px; font-weight: 600; }}
+.top-players {{ margin-top: 24px; }}
+.top-players h2 {{ margin: 0 0 8px; font-size: 18px; }}
+.top-players ol {{ margin: 0; padding-left: 20px; columns: 2; font-size: 14px; }}
+.top-players a {{ color: #93c5fd; }}
+.top-players span {{ color: #94a3b8; font-size: 12px; }}
+.player a {{ color: inherit; }}
 .footer {{
   margin-top: 16px;
   color: var(--muted);
@@ -268,6 +704,7 @@ body {{
     <h1 data-i18n="title">Item Anomaly Daily Report</h1>
     <p data-i18n="subtitle" data-date="{date}" data-limit="500">Date: {date} · Showing the latest 500 events</p>
     <div class="summary">
+      <div class="card"><div class="label" data-i18n="summary_critical">Critical Risk</div><div class="value">{critical}</div></div>
       <div class="card"><div class="label" data-i18n="summary_high">High Risk</div><div class="value">{high}</div></div>
       <div class="card"><div class="label" data-i18n="summary_medium">Medium Risk</div><div class="value">{medium}</div></div>
       <div class="card"><div class="label" data-i18n="summary_low">Low Risk</div><div class="value">{low}</div></div>
@@ -275,6 +712,10 @@ body {{
     </div>
   </section>
 
+  {rule_changes_section}
+
+  {top_players_section}
+
   <section class="controls">
     <div class="search">
       <span data-i18n="search_label">Search</span>
@@ -282,6 +723,7 @@ body {{
     </div>
     <div class="segmented" id="risk">
       <button type="button" data-risk-filter="ALL" class="active" data-i18n="filter_all">All</button>
+      <button type="button" data-risk-filter="CRITICAL" data-i18n="filter_critical">Critical</button>
       <button type="button" data-risk-filter="HIGH" data-i18n="filter_high">High</button>
       <button type="button" data-risk-filter="MEDIUM" data-i18n="filter_medium">Medium</button>
       <button type="button" data-risk-filter="LOW" data-i18n="filter_low">Low</button>
@@ -306,12 +748,14 @@ body {{
     <div class="empty" id="empty" style="display:none;" data-i18n="empty">No rows match the current filters.</div>
   </div>
 
+  {persisting_section}
+
   <div class="footer" data-i18n="footer">Low risk rows usually indicate a matched transfer chain for audit reference.</div>
 </div>
 <script>
   const search = document.getElementById('search');
   const risk = document.getElementById('risk');
-  const rows = Array.from(document.querySelectorAll('#rows tr'));
+  const rows = Array.from(document.querySelectorAll('#rows tr[data-risk]'));
   const count = document.getElementById('visible-count');
   const empty = document.getElementById('empty');
   let currentRisk = 'ALL';
@@ -319,6 +763,7 @@ body {{
   const fallbackDict = {{
     title: 'Item Anomaly Daily Report',
     subtitle: 'Date: {{date}} · Showing the latest {{limit}} events',
+    summary_critical: 'Critical Risk',
     summary_high: 'High Risk',
     summary_medium: 'Medium Risk',
     summary_low: 'Low Risk',
@@ -326,6 +771,7 @@ body {{
     search_label: 'Search',
     search_placeholder: 'Player, item, reason',
     filter_all: 'All',
+    filter_critical: 'Critical',
     filter_high: 'High',
     filter_medium: 'Medium',
     filter_low: 'Low',
@@ -337,6 +783,13 @@ body {{
     th_reason: 'Reason',
     empty: 'No rows match the current filters.',
     footer: 'Low risk rows usually indicate a matched transfer chain for audit reference.',
+    persisting_title: 'Persisting storage findings',
+    persisting_hint: 'Flagged by an earlier scan with the same or a lower count; not alerted again.',
+    top_players_title: 'Top players',
+    rule_changes_title: 'Rules changed during the day',
+    rule_changes_hint: 'Counts are split at each change so the rule sets can be compared.',
+    rule_change_marker: 'Configuration changed at {{time}}',
+    th_period: 'Period',
     showing: 'Showing {{visible}} / {{total}}'
   }};
 
@@ -419,32 +872,177 @@ body {{
 </body>
 </html>"#,
         date = date,
+        critical = summary.critical,
         high = summary.high,
         medium = summary.medium,
         low = summary.low,
-        total = summary.high + summary.medium + summary.low,
+        total = summary.total(),
         rows = rows,
+        persisting_section = persisting_section,
+        top_players_section = top_players_section,
+        rule_changes_section = rule_changes_section,
     )
 }
 
+/// The list of the day's rule changes and a summary per period between them; empty when the
+/// rules did not change.
+fn render_rule_changes(revisions: &[RuleRevision], periods: &[RulePeriod]) -> String {
+    if revisions.is_empty() {
+        return String::new();
+    }
+    let day_end_ms = periods.last().map(|period| period.to_ms).unwrap_or(i64::MAX);
+    let changes: String = revisions
+        .iter()
+        .map(|revision| {
+            let time = local_clock(revision.changed_at_ms, day_end_ms);
+            format!(
+                "<li><strong data-i18n=\"rule_change_marker\" data-time=\"{time}\">Configuration changed at {time}</strong> <span>{summary} · {actor}</span></li>",
+                time = time,
+                summary = escape_html(&revision.summary),
+                actor = escape_html(&revision.actor),
+            )
+        })
+        .collect();
+    let period_rows: String = periods
+        .iter()
+        .map(|period| {
+            format!(
+                "<tr><td>{from} – {to}</td><td class=\"count\">{critical}</td><td class=\"count\">{high}</td><td class=\"count\">{medium}</td><td class=\"count\">{low}</td><td class=\"count\">{total}</td></tr>",
+                from = local_clock(period.from_ms, day_end_ms),
+                to = local_clock(period.to_ms, day_end_ms),
+                critical = period.summary.critical,
+                high = period.summary.high,
+                medium = period.summary.medium,
+                low = period.summary.low,
+                total = period.summary.total(),
+            )
+        })
+        .collect();
+    format!(
+        r#"<section class="rule-changes">
+    <h2 data-i18n="rule_changes_title">Rules changed during the day</h2>
+    <p data-i18n="rule_changes_hint">Counts are split at each change so the rule sets can be compared.</p>
+    <ul>{changes}</ul>
+    <div class="table-wrap">
+      <table class="table">
+        <thead><tr>
+          <th data-i18n="th_period">Period</th>
+          <th data-i18n="summary_critical">Critical Risk</th>
+          <th data-i18n="summary_high">High Risk</th>
+          <th data-i18n="summary_medium">Medium Risk</th>
+          <th data-i18n="summary_low">Low Risk</th>
+          <th data-i18n="summary_total">Total</th>
+        </tr></thead>
+        <tbody>{period_rows}</tbody>
+      </table>
+    </div>
+  </section>"#,
+        changes = changes,
+        period_rows = period_rows,
+    )
+}
+
+fn render_rows<'a>(
+    items: impl Iterator<Item = &'a AnomalyRow>,
+    player_pages: &HashMap<String, String>,
+    config: &RuntimeConfig,
+) -> String {
+    let display = TimeDisplay::from_config(config);
+    let mut rows = String::new();
+    for item in items {
+        let risk_class = match item.risk_level.as_str() {
+            "CRITICAL" => "risk-critical",
+            "HIGH" => "risk-high",
+            "MEDIUM" => "risk-medium",
+            "LOW" => "risk-low",
+            _ => "risk-unknown",
+        };
+        let time = match anomaly_link(config, item) {
+            Some(link) => format!(
+                "<a href=\"{}\">{}</a>",
+                link,
+                escape_html(&display.format(item.event_time))
+            ),
+            None => escape_html(&display.format(item.event_time)),
+        };
+        let player = match player_pages.get(&item.player_uuid) {
+            Some(href) => format!("<a href=\"{}\">{}</a>", href, item.player_name),
+            None => item.player_name.clone(),
+        };
+        rows.push_str(&format!(
+            "<tr id=\"{id}\" data-risk=\"{risk}\" data-player=\"{player}\" data-item=\"{item}\">\
+            <td class=\"time\">{time}</td>\
+            <td class=\"player\">{player_cell}</td>\
+            <td class=\"item\">{item}</td>\
+            <td class=\"count\">{count}</td>\
+            <td class=\"risk\"><span class=\"badge {risk_class}\">{risk}</span></td>\
+            <td class=\"reason\">{reason}</td>\
+            </tr>",
+            id = anomaly_id(item),
+            time = time,
+            player = item.player_name,
+            player_cell = player,
+            item = item.item_id,
+            count = item.count,
+            risk = item.risk_level,
+            risk_class = risk_class,
+            reason = item.reason
+        ));
+    }
+    rows
+}
+
+/// Fills `{name}` placeholders; values are escaped for a JSON string, since templates are JSON.
+fn render_report_template(template: &str, vars: &[(&str, String)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in vars {
+        let quoted = serde_json::Value::from(value.as_str()).to_string();
+        text = text.replace(&format!("{{{}}}", name), &quoted[1..quoted.len() - 1]);
+    }
+    text
+}
+
 async fn send_webhook(
     url: &str,
-    template: Option<&str>,
+    template: &str,
     date: &str,
-    summary: &ReportSummary,
+    digest: &ReportDigest,
     link: &str,
 ) -> Result<()> {
-    let template = template.unwrap_or(
-        r#"{"message":"[Lattice 日报] {date}\n总异常 {total}（高{high} / 中{medium} / 低{low}）\n报告: {link}"}"#,
+    let summary = &digest.summary;
+    let total = summary.total();
+    let delta = total as i64 - digest.yesterday_total as i64;
+    let rule_totals = if digest.rule_totals.is_empty() {
+        "-".to_string()
+    } else {
+        digest
+            .rule_totals
+            .iter()
+            .map(|(rule_id, count)| format!("{} {}", rule_id, count))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    };
+    let top_players = if digest.top_players.is_empty() {
+        "-".to_string()
+    } else {
+        digest.top_players.join(", ")
+    };
+    let payload = render_report_template(
+        template,
+        &[
+            ("date", date.to_string()),
+            ("total", total.to_string()),
+            ("critical", summary.critical.to_string()),
+            ("high", summary.high.to_string()),
+            ("medium", summary.medium.to_string()),
+            ("low", summary.low.to_string()),
+            ("yesterday_total", digest.yesterday_total.to_string()),
+            ("total_delta", format!("{:+}", delta)),
+            ("rule_totals", rule_totals),
+            ("top_players", top_players),
+            ("link", link.to_string()),
+        ],
     );
-    let total = summary.high + summary.medium + summary.low;
-    let payload = template
-        .replace("{date}", date)
-        .replace("{total}", &total.to_string())
-        .replace("{high}", &summary.high.to_string())
-        .replace("{medium}", &summary.medium.to_string())
-        .replace("{low}", &summary.low.to_string())
-        .replace("{link}", link);
 
     let client = reqwest::Client::new();
     client
@@ -473,3 +1071,76 @@ fn next_report_time(config: &RuntimeConfig) -> DateTime<Local> {
     }
     dt
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_domain::testing::{
+        anomaly_row, runtime_config, InMemoryAnomalyRepository, InMemoryEventRepository,
+    };
+    use backend_domain::{millis_to_utc, AnomalyRepository};
+
+    fn anomaly(event_ms: i64, uuid: &str, name: &str, risk_level: &str) -> AnomalyRow {
+        AnomalyRow {
+            event_time: millis_to_utc(event_ms),
+            player_uuid: uuid.to_string(),
+            player_name: name.to_string(),
+            risk_level: risk_level.to_string(),
+            ..anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn every_player_in_the_report_gets_a_page_keyed_by_uuid() {
+        let report_dir =
+            std::env::temp_dir().join(format!("lattice-report-{}", uuid::Uuid::new_v4()));
+        let mut config = runtime_config();
+        config.report_dir = report_dir.to_string_lossy().into_owned();
+        config.report_player_pages = 1;
+        let event_ms = 1_790_000_000_000;
+        let date = millis_to_utc(event_ms).date().to_string();
+        let anomalies = InMemoryAnomalyRepository::default();
+        // Two accounts that went by the same name that day each keep their own page.
+        anomalies
+            .insert_anomalies(&[
+                anomaly(event_ms, "uuid-steve", "steve", "CRITICAL"),
+                anomaly(event_ms + 1, "uuid-alex", "alex", "HIGH"),
+                anomaly(event_ms + 2, "uuid-steve-2", "steve", "LOW"),
+            ])
+            .await
+            .unwrap();
+
+        write_report(
+            &config,
+            &anomalies,
+            &InMemoryEventRepository::default(),
+            &RuleRevisionLog::new(Vec::new()),
+            &date,
+        )
+        .await
+        .unwrap();
+
+        let html = fs::read_to_string(report_dir.join(format!("{}.html", date)))
+            .await
+            .unwrap();
+        for (uuid, name) in [
+            ("uuid-steve", "steve"),
+            ("uuid-alex", "alex"),
+            ("uuid-steve-2", "steve"),
+        ] {
+            let href = format!("{}/players/{}.html", date, uuid);
+            assert!(html.contains(&format!(
+                "<td class=\"player\"><a href=\"{}\">{}</a></td>",
+                href, name
+            )));
+            let page = fs::read_to_string(report_dir.join(&href)).await.unwrap();
+            assert!(page.contains(name));
+        }
+        let steve_page =
+            fs::read_to_string(report_dir.join(format!("{}/players/uuid-steve.html", date)))
+                .await
+                .unwrap();
+        assert!(steve_page.contains("CRITICAL") && !steve_page.contains(">LOW<"));
+        fs::remove_dir_all(&report_dir).await.unwrap();
+    }
+}
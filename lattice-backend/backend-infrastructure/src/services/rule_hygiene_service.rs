@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate};
+use tokio::fs;
+use tracing::info;
+
+use backend_application::queries::report_queries;
+use backend_application::AppState;
+use backend_domain::{
+    build_rule_hygiene_report, current_millis, is_item_pattern, RuleHygieneReport,
+    RuleThresholdFit, KEY_ITEM_RULE_IDS,
+};
+
+use super::report_player_pages::escape_html;
+
+/// Reviews the key item rules against the calendar month before `today`, writes
+/// `{report_dir}/rule-hygiene-{YYYY-MM}.html` and sends a summary to `webhook_url`.
+pub async fn generate_rule_hygiene_report(state: &AppState, today: NaiveDate) -> Result<()> {
+    let to = today
+        .with_day(1)
+        .and_then(|first| first.pred_opt())
+        .ok_or_else(|| anyhow!("no previous month for {}", today))?;
+    let from = to.with_day(1).unwrap_or(to);
+    let from_date = from.format("%Y-%m-%d").to_string();
+    let to_date = to.format("%Y-%m-%d").to_string();
+    let month = from.format("%Y-%m").to_string();
+
+    let rules = state.key_rules.read().await.clone();
+    let rule_ids: Vec<String> = KEY_ITEM_RULE_IDS.iter().map(|id| id.to_string()).collect();
+    let stats = state
+        .anomaly_repo
+        .fetch_item_anomaly_stats(&from_date, &to_date, &rule_ids)
+        .await?;
+    let mut item_ids: Vec<String> = rules
+        .keys()
+        .filter(|item_id| !is_item_pattern(item_id))
+        .cloned()
+        .collect();
+    item_ids.sort();
+    let distributions = state
+        .event_repo
+        .fetch_item_count_distributions(&from_date, &to_date, &item_ids)
+        .await?;
+    let report =
+        build_rule_hygiene_report(&from_date, &to_date, to.day(), &rules, stats, distributions);
+
+    let report_dir = Path::new(&state.config.report_dir);
+    fs::create_dir_all(report_dir).await?;
+    let file = format!("rule-hygiene-{}", month);
+    fs::write(
+        report_dir.join(format!("{}.html", file)),
+        render_rule_hygiene(&month, &report),
+    )
+    .await?;
+    info!(
+        "rule hygiene report {}: {} never fired, {} noisy, {} uncovered",
+        month,
+        report.never_fired.len(),
+        report.noisy.len(),
+        report.uncovered.len()
+    );
+
+    if let Some(url) = &state.config.webhook_url {
+        let link = report_queries::report_url(&state.config, &file, current_millis());
+        let message = format!(
+            "[Lattice 规则体检] {}\n{} 条规则从未触发；{} 条规则触发过于频繁；{} 个物品频繁异常但没有规则\n报告: {}",
+            month,
+            report.never_fired.len(),
+            report.noisy.len(),
+            report.uncovered.len(),
+            link
+        );
+        reqwest::Client::new()
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(serde_json::json!({ "message": message }).to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+    }
+    Ok(())
+}
+
+fn render_rule_hygiene(month: &str, report: &RuleHygieneReport) -> String {
+    let never_fired = if report.never_fired.is_empty() {
+        "<p class=\"meta\">None.</p>".to_string()
+    } else {
+        let items: String = report
+            .never_fired
+            .iter()
+            .map(|item_id| format!("<li class=\"item\">{}</li>", escape_html(item_id)))
+            .collect();
+        format!("<ul>{}</ul>", items)
+    };
+    let noisy_rows: String = report
+        .noisy
+        .iter()
+        .map(|rule| {
+            format!(
+                "<tr><td class=\"item\">{item}</td><td class=\"count\">{hits}</td><td class=\"count\">{per_day:.1}</td></tr>",
+                item = escape_html(&rule.item_id),
+                hits = rule.rule_hits,
+                per_day = rule.per_day,
+            )
+        })
+        .collect();
+    let uncovered_rows: String = report
+        .uncovered
+        .iter()
+        .map(|stat| {
+            format!(
+                "<tr><td class=\"item\">{item}</td><td class=\"count\">{anomalies}</td><td class=\"count\">{players}</td></tr>",
+                item = escape_html(&stat.item_id),
+                anomalies = stat.anomalies,
+                players = stat.players,
+            )
+        })
+        .collect();
+    let threshold_rows: String = report.thresholds.iter().map(render_threshold_row).collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8" />
+<meta name="viewport" content="width=device-width, initial-scale=1" />
+<title>Lattice Rule Hygiene {month}</title>
+<style>
+body {{ margin: 0; font-family: "IBM Plex Sans", "Source Sans 3", "Noto Sans SC", sans-serif; background: #0f172a; color: #e2e8f0; }}
+.page {{ max-width: 1200px; margin: 0 auto; padding: 32px 20px 48px; }}
+h1 {{ margin: 8px 0 4px; font-size: 26px; }}
+h2 {{ margin: 28px 0 10px; font-size: 18px; }}
+.meta {{ color: #94a3b8; font-size: 14px; }}
+table {{ width: 100%; border-collapse: collapse; font-size: 14px; background: #ffffff; color: #0f172a; border-radius: 12px; overflow: hidden; }}
+th {{ text-align: left; font-size: 11px; letter-spacing: 0.12em; text-transform: uppercase; color: #64748b; background: #f1f5f9; padding: 10px 12px; }}
+td {{ padding: 10px 12px; border-bottom: 1px solid #e2e8f0; vertical-align: middle; }}
+.count {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.item {{ font-family: "IBM Plex Mono", "JetBrains Mono", "SFMono-Regular", monospace; font-size: 12px; }}
+.chart {{ position: relative; height: 18px; min-width: 240px; background: #f1f5f9; border-radius: 4px; }}
+.chart span {{ position: absolute; top: 0; bottom: 0; left: 0; border-radius: 4px; }}
+.chart .p99 {{ background: #bfdbfe; }}
+.chart .p90 {{ background: #60a5fa; }}
+.chart .p50 {{ background: #2563eb; }}
+.chart .threshold {{ width: 2px; background: #dc2626; border-radius: 0; }}
+.chart .quota {{ width: 2px; background: #f59e0b; border-radius: 0; }}
+</style>
+</head>
+<body>
+<div class="page">
+  <h1>Rule hygiene · {month}</h1>
+  <div class="meta">{from_date} – {to_date} · key item rules R4 / R9 / R12 / R14</div>
+
+  <h2>Never fired ({never_fired_count})</h2>
+  {never_fired}
+
+  <h2>Firing often ({noisy_count})</h2>
+  <table>
+    <thead><tr><th>Item</th><th>Hits</th><th>Per day</th></tr></thead>
+    <tbody>{noisy_rows}</tbody>
+  </table>
+
+  <h2>Anomalous items without a rule ({uncovered_count})</h2>
+  <table>
+    <thead><tr><th>Item</th><th>Anomalies</th><th>Players</th></tr></thead>
+    <tbody>{uncovered_rows}</tbody>
+  </table>
+
+  <h2>Threshold vs observed daily totals per player</h2>
+  <div class="meta">Bars: p50 / p90 / p99 of each player's daily acquisitions; red line: threshold; amber line: daily quota.</div>
+  <table>
+    <thead><tr><th>Item</th><th>Threshold</th><th>Quota</th><th>p50</th><th>p90</th><th>p99</th><th>Max</th><th>Samples</th><th>Distribution</th></tr></thead>
+    <tbody>{threshold_rows}</tbody>
+  </table>
+</div>
+</body>
+</html>"#,
+        month = escape_html(month),
+        from_date = report.from_date,
+        to_date = report.to_date,
+        never_fired_count = report.never_fired.len(),
+        never_fired = never_fired,
+        noisy_count = report.noisy.len(),
+        noisy_rows = noisy_rows,
+        uncovered_count = report.uncovered.len(),
+        uncovered_rows = uncovered_rows,
+        threshold_rows = threshold_rows,
+    )
+}
+
+fn render_threshold_row(fit: &RuleThresholdFit) -> String {
+    let distribution = &fit.distribution;
+    let scale = [
+        fit.threshold as f64,
+        fit.daily_quota.unwrap_or(0) as f64,
+        distribution.p99,
+    ]
+    .into_iter()
+    .fold(1.0_f64, f64::max);
+    let percent = |value: f64| (value / scale * 100.0).clamp(0.0, 100.0);
+    let mut chart = format!(
+        "<span class=\"p99\" style=\"width:{:.1}%\"></span><span class=\"p90\" style=\"width:{:.1}%\"></span><span class=\"p50\" style=\"width:{:.1}%\"></span>",
+        percent(distribution.p99),
+        percent(distribution.p90),
+        percent(distribution.p50),
+    );
+    if fit.threshold > 0 {
+        chart.push_str(&format!(
+            "<span class=\"threshold\" style=\"left:{:.1}%\"></span>",
+            percent(fit.threshold as f64)
+        ));
+    }
+    if let Some(quota) = fit.daily_quota {
+        chart.push_str(&format!(
+            "<span class=\"quota\" style=\"left:{:.1}%\"></span>",
+            percent(quota as f64)
+        ));
+    }
+    format!(
+        "<tr><td class=\"item\">{item}</td><td class=\"count\">{threshold}</td><td class=\"count\">{quota}</td><td class=\"count\">{p50:.0}</td><td class=\"count\">{p90:.0}</td><td class=\"count\">{p99:.0}</td><td class=\"count\">{max}</td><td class=\"count\">{samples}</td><td><div class=\"chart\">{chart}</div></td></tr>",
+        item = escape_html(&fit.item_id),
+        threshold = fit.threshold,
+        quota = fit
+            .daily_quota
+            .map(|quota| quota.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        p50 = distribution.p50,
+        p90 = distribution.p90,
+        p99 = distribution.p99,
+        max = distribution.max,
+        samples = distribution.samples,
+        chart = chart,
+    )
+}
@@ -0,0 +1,64 @@
+use chrono::{Duration, Local};
+use tracing::{error, info, warn};
+
+use backend_application::AppState;
+use backend_domain::ShadowConsistency;
+
+use crate::services::maintenance_service::next_maintenance_time;
+
+/// While dual-writing to `shadow_clickhouse_url`, compares the previous day's row counts of the
+/// primary and the shadow at startup and then daily at `maintenance_hour`, and sends a system
+/// alert when they differ. Returns at once when no shadow is configured.
+pub async fn monitor_shadow_consistency(state: AppState) {
+    loop {
+        let date = (Local::now().date_naive() - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        match state.maintenance_repo.compare_shadow_counts(&date).await {
+            Ok(None) => return,
+            Ok(Some(report)) if report.mismatched().next().is_none() => {
+                info!(
+                    "shadow clickhouse consistent for {}; {} mirror inserts failed since start",
+                    date, report.failed_batches
+                );
+            }
+            Ok(Some(report)) => {
+                let message = format_mismatch_message(&report);
+                warn!("{}", message);
+                if let Err(err) = state
+                    .alert_service
+                    .send_system_alert(&state.config, &message)
+                    .await
+                {
+                    error!("failed to send shadow consistency alert: {}", err);
+                }
+            }
+            Err(err) => warn!("shadow consistency check for {} failed: {}", date, err),
+        }
+        let next = next_maintenance_time(state.config.maintenance_hour);
+        let duration = next.signed_duration_since(Local::now());
+        let sleep_ms = duration.num_milliseconds().max(0) as u64;
+        tokio::time::sleep(std::time::Duration::from_millis(sleep_ms)).await;
+    }
+}
+
+fn format_mismatch_message(report: &ShadowConsistency) -> String {
+    let tables = report
+        .mismatched()
+        .map(|count| {
+            format!(
+                "{} 主库 {} / 影子库 {}",
+                count.table, count.primary, count.shadow
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("，");
+    let mut message = format!(
+        "[Lattice 双写校验] {} 主库与影子库行数不一致：{}；影子库写入失败 {} 批",
+        report.date, tables, report.failed_batches
+    );
+    if let Some(err) = &report.last_error {
+        message.push_str(&format!("，最近错误：{}", err));
+    }
+    message
+}
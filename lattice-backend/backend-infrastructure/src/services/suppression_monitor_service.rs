@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use backend_application::commands::suppression_commands;
+use backend_application::AppState;
+use backend_domain::{current_millis, AnomalySuppression};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Announces each suppression once when it expires, so temporary exceptions are neither
+/// forgotten nor silently dropped.
+pub async fn monitor_suppression_expiry(state: AppState) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let (expired, changed) = state.suppressions.take_expired(current_millis()).await;
+        for suppression in &expired {
+            info!("suppression {} expired", suppression.id);
+            let message = format_expired_message(suppression);
+            if let Err(err) = state
+                .alert_service
+                .send_system_alert(&state.config, &message)
+                .await
+            {
+                error!("suppression expiry alert failed: {}", err);
+            }
+        }
+        if changed {
+            suppression_commands::persist_suppressions(&state).await;
+        }
+    }
+}
+
+fn format_expired_message(suppression: &AnomalySuppression) -> String {
+    let mut scope = Vec::new();
+    if let Some(rule_id) = &suppression.rule_id {
+        scope.push(format!("rule={}", rule_id));
+    }
+    if let Some(player) = &suppression.player {
+        scope.push(format!("player={}", player));
+    }
+    if let Some(server_id) = &suppression.server_id {
+        scope.push(format!("server={}", server_id));
+    }
+    if let Some(item_id) = &suppression.item_id {
+        scope.push(format!("item={}", item_id));
+    }
+    let mut message = format!(
+        "[Lattice 屏蔽到期] {} 的临时屏蔽已到期，相关异常恢复告警",
+        scope.join(" ")
+    );
+    if let Some(reason) = &suppression.reason {
+        message.push_str(&format!("\n原因: {}", reason));
+    }
+    message
+}
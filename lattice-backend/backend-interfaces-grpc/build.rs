@@ -0,0 +1,34 @@
+// Generates the `LatticeIngest` server from the hand-written prost messages in src/proto.rs, so
+// the build does not need protoc. Keep it in sync with proto/lattice_ingest.proto.
+fn main() {
+    let codec = "tonic::codec::ProstCodec";
+    let service = tonic_build::manual::Service::builder()
+        .name("LatticeIngest")
+        .package("lattice.v2")
+        .method(
+            tonic_build::manual::Method::builder()
+                .name("stream_events")
+                .route_name("StreamEvents")
+                .input_type("crate::proto::IngestBatch")
+                .output_type("crate::proto::IngestAck")
+                .codec_path(codec)
+                .client_streaming()
+                .server_streaming()
+                .build(),
+        )
+        .method(
+            tonic_build::manual::Method::builder()
+                .name("watch_mod_config")
+                .route_name("WatchModConfig")
+                .input_type("crate::proto::WatchModConfigRequest")
+                .output_type("crate::proto::ModConfigUpdate")
+                .codec_path(codec)
+                .server_streaming()
+                .build(),
+        )
+        .build();
+    println!("cargo:rerun-if-changed=build.rs");
+    tonic_build::manual::Builder::new()
+        .build_client(false)
+        .compile(&[service]);
+}
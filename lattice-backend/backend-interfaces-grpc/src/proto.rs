@@ -0,0 +1,172 @@
+//! Messages of proto/lattice_ingest.proto, written out with prost derives so the build does not
+//! need protoc. Tags must match the .proto file.
+
+use backend_domain::{IngestEvent as DomainIngestEvent, ModConfigEnvelope};
+
+include!(concat!(env!("OUT_DIR"), "/lattice.v2.LatticeIngest.rs"));
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IngestEvent {
+    #[prost(string, tag = "1")]
+    pub event_id: String,
+    #[prost(int64, tag = "2")]
+    pub event_time: i64,
+    #[prost(string, optional, tag = "3")]
+    pub server_id: Option<String>,
+    #[prost(string, tag = "4")]
+    pub event_type: String,
+    #[prost(string, optional, tag = "5")]
+    pub player_uuid: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub player_name: Option<String>,
+    #[prost(string, tag = "7")]
+    pub item_id: String,
+    #[prost(int64, tag = "8")]
+    pub count: i64,
+    #[prost(string, optional, tag = "9")]
+    pub nbt_hash: Option<String>,
+    #[prost(string, optional, tag = "10")]
+    pub origin_id: Option<String>,
+    #[prost(string, optional, tag = "11")]
+    pub origin_type: Option<String>,
+    #[prost(string, optional, tag = "12")]
+    pub origin_ref: Option<String>,
+    #[prost(string, optional, tag = "13")]
+    pub source_type: Option<String>,
+    #[prost(string, optional, tag = "14")]
+    pub source_ref: Option<String>,
+    #[prost(string, optional, tag = "15")]
+    pub storage_mod: Option<String>,
+    #[prost(string, optional, tag = "16")]
+    pub storage_id: Option<String>,
+    #[prost(string, optional, tag = "17")]
+    pub actor_type: Option<String>,
+    #[prost(string, optional, tag = "18")]
+    pub trace_id: Option<String>,
+    #[prost(string, optional, tag = "19")]
+    pub item_fingerprint: Option<String>,
+    #[prost(string, optional, tag = "20")]
+    pub dim: Option<String>,
+    #[prost(int32, optional, tag = "21")]
+    pub x: Option<i32>,
+    #[prost(int32, optional, tag = "22")]
+    pub y: Option<i32>,
+    #[prost(int32, optional, tag = "23")]
+    pub z: Option<i32>,
+    #[prost(string, optional, tag = "24")]
+    pub family: Option<String>,
+    #[prost(string, optional, tag = "25")]
+    pub custom_type: Option<String>,
+    #[prost(string, optional, tag = "26")]
+    pub payload_json: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IngestBatch {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(string, optional, tag = "2")]
+    pub server_id: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub mod_version: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub events: Vec<IngestEvent>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IngestAck {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    #[prost(uint32, tag = "2")]
+    pub accepted: u32,
+    #[prost(string, tag = "3")]
+    pub code: String,
+    #[prost(string, tag = "4")]
+    pub message: String,
+    #[prost(bool, tag = "5")]
+    pub mod_version_outdated: bool,
+    #[prost(string, optional, tag = "6")]
+    pub min_mod_version: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct WatchModConfigRequest {
+    #[prost(string, tag = "1")]
+    pub server_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ModConfigUpdate {
+    #[prost(string, tag = "1")]
+    pub server_id: String,
+    #[prost(uint64, tag = "2")]
+    pub revision: u64,
+    #[prost(int64, tag = "3")]
+    pub updated_at_ms: i64,
+    #[prost(string, tag = "4")]
+    pub updated_by: String,
+    #[prost(string, tag = "5")]
+    pub checksum_sha256: String,
+    #[prost(string, tag = "6")]
+    pub config_json: String,
+}
+
+impl IngestEvent {
+    /// Converts to the domain event; `batch_server_id` fills a missing `server_id` like the HTTP
+    /// envelope does. Fails only on a `payload_json` that is not JSON.
+    pub fn into_domain(
+        self,
+        batch_server_id: Option<&str>,
+    ) -> Result<DomainIngestEvent, serde_json::Error> {
+        let payload = self
+            .payload_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?;
+        Ok(DomainIngestEvent {
+            event_id: self.event_id,
+            event_time: self.event_time,
+            server_id: self
+                .server_id
+                .or_else(|| batch_server_id.map(ToString::to_string)),
+            event_type: self.event_type,
+            player_uuid: self.player_uuid,
+            player_name: self.player_name,
+            item_id: self.item_id,
+            count: self.count,
+            nbt_hash: self.nbt_hash,
+            origin_id: self.origin_id,
+            origin_type: self.origin_type,
+            origin_ref: self.origin_ref,
+            source_type: self.source_type,
+            source_ref: self.source_ref,
+            storage_mod: self.storage_mod,
+            storage_id: self.storage_id,
+            actor_type: self.actor_type,
+            trace_id: self.trace_id,
+            item_fingerprint: self.item_fingerprint,
+            dim: self.dim,
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            family: self.family,
+            custom_type: self.custom_type,
+            payload,
+            item_name: None,
+            rule_risk_level: None,
+        })
+    }
+}
+
+impl From<ModConfigEnvelope> for ModConfigUpdate {
+    fn from(envelope: ModConfigEnvelope) -> Self {
+        Self {
+            server_id: envelope.server_id,
+            revision: envelope.revision,
+            updated_at_ms: envelope.updated_at_ms,
+            updated_by: envelope.updated_by,
+            checksum_sha256: envelope.checksum_sha256,
+            config_json: envelope.config.to_string(),
+        }
+    }
+}
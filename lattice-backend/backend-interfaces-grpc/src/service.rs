@@ -0,0 +1,264 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{info, warn};
+
+use backend_application::commands::{ingest_commands, server_identity_commands};
+use backend_application::ops::ModVersionCheck;
+use backend_application::queries::mod_config_queries;
+use backend_application::{AppError, AppState, ErrorCode};
+use backend_domain::{current_millis, ApiTokenScope};
+
+use crate::proto::lattice_ingest_server::{LatticeIngest, LatticeIngestServer};
+use crate::proto::{IngestAck, IngestBatch, ModConfigUpdate, WatchModConfigRequest};
+
+/// Metadata carrying the enrollment key a mod's `server_id` is bound to (`server_keys`).
+const SERVER_KEY_METADATA: &str = "x-lattice-server-key";
+
+/// Acks buffered per stream before the server stops reading further batches.
+const ACK_BUFFER: usize = 16;
+
+/// Serves `LatticeIngest` on `addr` until the runtime shuts down.
+pub async fn serve_grpc(state: AppState, addr: SocketAddr) -> anyhow::Result<()> {
+    info!("grpc listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(LatticeIngestServer::new(IngestGrpcService::new(state)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// gRPC front of the same ingest and mod-config commands the HTTP handlers call.
+#[derive(Clone)]
+pub struct IngestGrpcService {
+    state: AppState,
+}
+
+impl IngestGrpcService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl LatticeIngest for IngestGrpcService {
+    type StreamEventsStream = ReceiverStream<Result<IngestAck, Status>>;
+    type WatchModConfigStream =
+        Pin<Box<dyn Stream<Item = Result<ModConfigUpdate, Status>> + Send + 'static>>;
+
+    async fn stream_events(
+        &self,
+        request: Request<Streaming<IngestBatch>>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        authorize(&self.state, request.metadata()).await?;
+        let source = request
+            .remote_addr()
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let server_key = request
+            .metadata()
+            .get(SERVER_KEY_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        let mut batches = request.into_inner();
+        let (tx, rx) = mpsc::channel(ACK_BUFFER);
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            while let Some(batch) = batches.next().await {
+                let batch = match batch {
+                    Ok(batch) => batch,
+                    Err(status) => {
+                        warn!("grpc ingest stream closed: {}", status);
+                        break;
+                    }
+                };
+                let ack = ingest_batch(&state, batch, &source, server_key.as_deref()).await;
+                if tx.send(Ok(ack)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn watch_mod_config(
+        &self,
+        request: Request<WatchModConfigRequest>,
+    ) -> Result<Response<Self::WatchModConfigStream>, Status> {
+        authorize(&self.state, request.metadata()).await?;
+        let server_id = resolve_server_id(&request.into_inner().server_id);
+        let receiver = self.state.mod_config_stream_hub.subscribe(&server_id).await;
+        let initial = mod_config_queries::get_mod_config(&self.state, &server_id)
+            .await
+            .map_err(status_from_app_error)?;
+        let updates = BroadcastStream::new(receiver).filter_map(|update| match update {
+            Ok(envelope) => Some(Ok(ModConfigUpdate::from(envelope))),
+            Err(err) => {
+                warn!("grpc mod-config stream skipped updates: {}", err);
+                None
+            }
+        });
+        let initial = initial.map(ModConfigUpdate::from).map(Ok);
+        let stream = tokio_stream::iter(initial).chain(updates);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Validates and processes one batch like `POST /v2/ingest/events`; failures become an ack with
+/// the HTTP error code so the stream stays usable.
+async fn ingest_batch(
+    state: &AppState,
+    batch: IngestBatch,
+    source: &str,
+    server_key: Option<&str>,
+) -> IngestAck {
+    let mut ack = IngestAck {
+        sequence: batch.sequence,
+        ..IngestAck::default()
+    };
+    let batch_server_id = batch.server_id.as_deref();
+    let mut events = Vec::with_capacity(batch.events.len());
+    for event in batch.events {
+        match event.into_domain(batch_server_id) {
+            Ok(event) => events.push(event),
+            Err(err) => {
+                ack.code = code_name(ErrorCode::BadRequest);
+                ack.message = format!("invalid payload_json: {}", err);
+                return ack;
+            }
+        }
+    }
+    if let Err(mismatch) = server_identity_commands::check_server_identity(
+        &state.config.server_keys,
+        state.config.server_identity_required,
+        server_key,
+        &mut events,
+    ) {
+        let err = server_identity_commands::report_identity_mismatch(state, source, mismatch).await;
+        ack.code = code_name(err.code());
+        ack.message = err.to_string();
+        return ack;
+    }
+    let server_id = batch_server_id
+        .or_else(|| events.iter().find_map(|event| event.server_id.as_deref()))
+        .map(ToString::to_string);
+    match ingest_commands::check_mod_version(
+        state,
+        server_id.as_deref(),
+        batch.mod_version.as_deref(),
+    )
+    .await
+    {
+        Ok(ModVersionCheck::Outdated { minimum }) => {
+            ack.mod_version_outdated = true;
+            ack.min_mod_version = Some(minimum);
+        }
+        Ok(ModVersionCheck::Accepted) => {}
+        Err(err) => {
+            ack.code = code_name(err.code());
+            ack.message = err.to_string();
+            return ack;
+        }
+    }
+    let original_len = events.len();
+    events.retain(ingest_commands::is_valid_event);
+    if events.len() != original_len {
+        warn!(
+            "dropped {} invalid events (empty item_id/air/<=0 count/no custom_type)",
+            original_len - events.len()
+        );
+    }
+    if events.is_empty() {
+        return ack;
+    }
+    let accepted = events.len() as u32;
+    match ingest_commands::process_ingest_events(state, events).await {
+        Ok(()) => ack.accepted = accepted,
+        Err(err) => {
+            ack.code = code_name(err.code());
+            ack.message = err.to_string();
+        }
+    }
+    ack
+}
+
+/// Same bearer tokens as the HTTP API, sent as `authorization` metadata; both RPCs need the
+/// `ingest` scope.
+async fn authorize(state: &AppState, metadata: &MetadataMap) -> Result<(), Status> {
+    let presented = metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("Bearer "));
+    state
+        .api_tokens
+        .check(
+            state.config.api_token.as_deref(),
+            presented,
+            ApiTokenScope::Ingest,
+            current_millis(),
+        )
+        .await
+        .map_err(status_from_app_error)
+}
+
+fn resolve_server_id(server_id: &str) -> String {
+    let trimmed = server_id.trim();
+    if trimmed.is_empty() {
+        "server-01".to_string()
+    } else {
+        trimmed.to_lowercase()
+    }
+}
+
+fn code_name(code: ErrorCode) -> String {
+    match serde_json::to_value(code) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn status_from_app_error(err: AppError) -> Status {
+    match err {
+        AppError::Unauthorized => Status::unauthenticated(err.to_string()),
+        AppError::Forbidden(_) => Status::permission_denied(err.to_string()),
+        AppError::BadRequest(_) | AppError::Invalid(..) => {
+            Status::invalid_argument(err.to_string())
+        }
+        AppError::Unavailable(_) => Status::unavailable(err.to_string()),
+        AppError::Internal(_) => Status::internal(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::IngestEvent;
+
+    #[test]
+    fn proto_events_map_to_domain_events() {
+        let event = IngestEvent {
+            event_id: "evt-1".to_string(),
+            event_type: "ACQUIRE".to_string(),
+            item_id: "minecraft:diamond".to_string(),
+            count: 3,
+            payload_json: Some(r#"{"zone":"spawn"}"#.to_string()),
+            ..IngestEvent::default()
+        };
+        let domain = event.clone().into_domain(Some("server-02")).expect("event");
+        assert_eq!(domain.server_id.as_deref(), Some("server-02"));
+        assert_eq!(domain.payload, Some(serde_json::json!({ "zone": "spawn" })));
+        assert!(ingest_commands::is_valid_event(&domain));
+
+        let broken = IngestEvent {
+            payload_json: Some("{".to_string()),
+            ..event
+        };
+        assert!(broken.into_domain(None).is_err());
+        assert_eq!(code_name(ErrorCode::InvalidDate), "INVALID_DATE");
+    }
+}
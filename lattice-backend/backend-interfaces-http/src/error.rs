@@ -1,21 +1,49 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Serialize;
 
+use backend_application::ErrorCode;
+
 #[derive(Debug)]
 pub enum HttpError {
     Unauthorized,
+    Forbidden(String),
     BadRequest(String),
+    /// `400` with a specific error code instead of the generic `BAD_REQUEST`.
+    Invalid(ErrorCode, String),
     NotFound,
+    Unavailable(String),
     Internal(String),
+    /// `429` with a `Retry-After` of this many seconds.
+    RateLimited(u64),
+}
+
+impl HttpError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            HttpError::Unauthorized => ErrorCode::Unauthorized,
+            HttpError::Forbidden(_) => ErrorCode::Forbidden,
+            HttpError::BadRequest(_) => ErrorCode::BadRequest,
+            HttpError::Invalid(code, _) => *code,
+            HttpError::NotFound => ErrorCode::NotFound,
+            HttpError::Unavailable(_) => ErrorCode::ClickhouseUnavailable,
+            HttpError::Internal(_) => ErrorCode::Internal,
+            HttpError::RateLimited(_) => ErrorCode::RateLimited,
+        }
+    }
 }
 
 impl From<backend_application::AppError> for HttpError {
     fn from(value: backend_application::AppError) -> Self {
         match value {
             backend_application::AppError::Unauthorized => HttpError::Unauthorized,
+            backend_application::AppError::Forbidden(msg) => HttpError::Forbidden(msg),
             backend_application::AppError::BadRequest(msg) => HttpError::BadRequest(msg),
+            backend_application::AppError::Invalid(code, msg) => HttpError::Invalid(code, msg),
+            backend_application::AppError::Unavailable(err) => {
+                HttpError::Unavailable(err.to_string())
+            }
             backend_application::AppError::Internal(err) => HttpError::Internal(err.to_string()),
         }
     }
@@ -24,16 +52,75 @@ impl From<backend_application::AppError> for HttpError {
 #[derive(Serialize)]
 struct ErrorBody {
     error: String,
+    code: ErrorCode,
 }
 
 impl IntoResponse for HttpError {
     fn into_response(self) -> Response {
+        let code = self.code();
+        let retry_after = match &self {
+            HttpError::RateLimited(seconds) => Some(*seconds),
+            _ => None,
+        };
         let (status, message) = match self {
             HttpError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized".to_string()),
-            HttpError::BadRequest(msg) => (StatusCode::BAD_REQUEST, format!("bad request: {}", msg)),
+            HttpError::Forbidden(msg) => (StatusCode::FORBIDDEN, format!("forbidden: {}", msg)),
+            HttpError::BadRequest(msg) | HttpError::Invalid(_, msg) => {
+                (StatusCode::BAD_REQUEST, format!("bad request: {}", msg))
+            }
             HttpError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            HttpError::Unavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("clickhouse unavailable: {}", msg),
+            ),
             HttpError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            HttpError::RateLimited(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("rate limited: retry in {} s", seconds),
+            ),
         };
-        (status, Json(ErrorBody { error: message })).into_response()
+        let mut response = (
+            status,
+            Json(ErrorBody {
+                error: message,
+                code,
+            }),
+        )
+            .into_response();
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend_application::AppError;
+
+    #[tokio::test]
+    async fn app_error_codes_reach_the_body() {
+        let err = HttpError::from(AppError::Invalid(
+            ErrorCode::InvalidDate,
+            "invalid date: 2026-13-01".to_string(),
+        ));
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        let body: serde_json::Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(body["code"], "INVALID_DATE");
+        assert_eq!(body["error"], "bad request: invalid date: 2026-13-01");
+
+        let unavailable = HttpError::from(AppError::Unavailable(anyhow::anyhow!("timeout")));
+        assert_eq!(unavailable.code(), ErrorCode::ClickhouseUnavailable);
+        assert_eq!(
+            unavailable.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
     }
 }
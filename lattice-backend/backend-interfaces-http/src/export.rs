@@ -0,0 +1,526 @@
+//! `format=csv|xlsx` downloads of list endpoints: every row of the query as one attachment
+//! instead of a JSON page.
+
+use std::io::Write;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use futures_util::{future, stream, StreamExt};
+use tracing::warn;
+
+use backend_domain::csv_field;
+
+use crate::error::HttpError;
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Rows a worksheet holds, its header row included.
+const XLSX_MAX_ROWS: usize = 1_048_576;
+
+/// `format` query parameter of exportable list endpoints.
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// `json` (default) for the paged listing, `csv` or `xlsx` for the whole day as a download.
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportQuery {
+    /// None for the usual JSON listing.
+    pub fn format(&self) -> Result<Option<ExportFormat>, HttpError> {
+        match self.format.as_deref().map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(value) if value.eq_ignore_ascii_case("json") => Ok(None),
+            Some(value) if value.eq_ignore_ascii_case("csv") => Ok(Some(ExportFormat::Csv)),
+            Some(value) if value.eq_ignore_ascii_case("xlsx") => Ok(Some(ExportFormat::Xlsx)),
+            Some(_) => Err(HttpError::BadRequest(
+                "format must be one of: json, csv, xlsx".to_string(),
+            )),
+        }
+    }
+}
+
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<u64> for Cell {
+    fn from(value: u64) -> Self {
+        Cell::Number(value as f64)
+    }
+}
+
+impl From<Option<i32>> for Cell {
+    fn from(value: Option<i32>) -> Self {
+        value.map_or(Cell::Text(String::new()), |value| {
+            Cell::Number(f64::from(value))
+        })
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(value: bool) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+/// The rows of a download, a chunk at a time.
+#[async_trait]
+pub trait RowSource: Send + 'static {
+    /// The next rows; empty once every row was read.
+    async fn next_rows(&mut self) -> Result<Vec<Vec<Cell>>, HttpError>;
+}
+
+/// Every row of `rows` as `{name}.csv` or `{name}.xlsx`; `name` must be safe in a file name.
+/// CSV streams a chunk at a time. Its first chunk is read before answering, so a storage outage
+/// still gets its status code, while a later failure cuts the download short. XLSX is assembled
+/// whole and refused past what one sheet holds.
+pub async fn export_response(
+    format: ExportFormat,
+    name: &str,
+    columns: &'static [&'static str],
+    mut rows: impl RowSource,
+) -> Result<Response, HttpError> {
+    let (content_type, extension, body) = match format {
+        ExportFormat::Csv => {
+            let first = rows.next_rows().await?;
+            (
+                "text/csv; charset=utf-8",
+                "csv",
+                csv_body(columns, first, rows),
+            )
+        }
+        ExportFormat::Xlsx => {
+            let mut table = Vec::new();
+            loop {
+                let chunk = rows.next_rows().await?;
+                if chunk.is_empty() {
+                    break;
+                }
+                if table.len() + chunk.len() >= XLSX_MAX_ROWS {
+                    return Err(HttpError::BadRequest(format!(
+                        "more than {} rows do not fit in an xlsx sheet, use format=csv",
+                        XLSX_MAX_ROWS - 1
+                    )));
+                }
+                table.extend(chunk);
+            }
+            let workbook = render_xlsx(columns, &table, name)?;
+            (XLSX_CONTENT_TYPE, "xlsx", Body::from(workbook))
+        }
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.{}\"", name, extension),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// RFC 4180 lines behind a byte order mark, so spreadsheet programs read the text as UTF-8:
+/// the header and `first`, then each further chunk of `rows` as it is read.
+fn csv_body(columns: &'static [&'static str], first: Vec<Vec<Cell>>, rows: impl RowSource) -> Body {
+    let mut head = String::from("\u{feff}");
+    head.push_str(&columns.join(","));
+    head.push_str("\r\n");
+    head.push_str(&csv_lines(&first));
+    let rest = stream::unfold((rows, first.is_empty()), |(mut rows, done)| async move {
+        if done {
+            return None;
+        }
+        match rows.next_rows().await {
+            Ok(chunk) if chunk.is_empty() => None,
+            Ok(chunk) => Some((Ok(csv_lines(&chunk)), (rows, false))),
+            Err(err) => {
+                warn!("csv export cut short: {:?}", err);
+                let err = std::io::Error::other(format!("{:?}", err));
+                Some((Err(err), (rows, true)))
+            }
+        }
+    });
+    Body::from_stream(stream::once(future::ready(Ok(head))).chain(rest))
+}
+
+fn csv_lines(rows: &[Vec<Cell>]) -> String {
+    let mut csv = String::new();
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|cell| match cell {
+                Cell::Text(text) => csv_field(&formula_safe(text)),
+                Cell::Number(number) => number.to_string(),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Text a spreadsheet program would run as a formula gets a leading `'`, which keeps it text.
+/// XLSX needs none of this: its cells are typed strings, never formulas.
+fn formula_safe(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text).into()
+    } else {
+        text.into()
+    }
+}
+
+/// A workbook with one sheet of inline strings, the least a spreadsheet program needs.
+fn render_xlsx(
+    columns: &[&str],
+    rows: &[Vec<Cell>],
+    sheet_name: &str,
+) -> Result<Vec<u8>, HttpError> {
+    let mut sheet = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    );
+    let header = columns.iter().map(|column| Cell::from(*column));
+    push_row(&mut sheet, header.collect::<Vec<_>>().iter());
+    for row in rows {
+        push_row(&mut sheet, row.iter());
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let sheet_name: String = sheet_name
+        .chars()
+        .filter(|ch| !matches!(ch, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        xml_escape(&sheet_name)
+    );
+    let parts: [(&str, &str); 5] = [
+        (
+            "[Content_Types].xml",
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#,
+        ),
+        (
+            "_rels/.rels",
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#,
+        ),
+        ("xl/workbook.xml", &workbook),
+        (
+            "xl/_rels/workbook.xml.rels",
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+        ),
+        ("xl/worksheets/sheet1.xml", &sheet),
+    ];
+    zip(&parts).map_err(|err| HttpError::Internal(format!("failed to build xlsx: {}", err)))
+}
+
+fn push_row<'a>(sheet: &mut String, cells: impl Iterator<Item = &'a Cell>) {
+    sheet.push_str("<row>");
+    for cell in cells {
+        match cell {
+            Cell::Text(text) => {
+                sheet.push_str(r#"<c t="inlineStr"><is><t xml:space="preserve">"#);
+                sheet.push_str(&xml_escape(text));
+                sheet.push_str("</t></is></c>");
+            }
+            Cell::Number(number) => {
+                sheet.push_str("<c><v>");
+                sheet.push_str(&number.to_string());
+                sheet.push_str("</v></c>");
+            }
+        }
+    }
+    sheet.push_str("</row>");
+}
+
+/// Escapes markup and drops the control characters XML 1.0 cannot carry.
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            ch if ch.is_control() => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// `value` as a ZIP header field, or an error where the format would need zip64.
+fn zip_field<T: TryFrom<usize>>(value: usize, what: &str) -> std::io::Result<T> {
+    T::try_from(value).map_err(|_| {
+        std::io::Error::other(format!(
+            "{} of {} does not fit a zip without zip64",
+            what, value
+        ))
+    })
+}
+
+/// A deflated ZIP archive of `(path, contents)` entries.
+fn zip(entries: &[(&str, &str)]) -> std::io::Result<Vec<u8>> {
+    // 1980-01-01 00:00, the earliest DOS date.
+    const DOS_TIME: u16 = 0;
+    const DOS_DATE: u16 = (1 << 5) | 1;
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (path, contents) in entries {
+        let mut crc = Crc::new();
+        crc.update(contents.as_bytes());
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents.as_bytes())?;
+        let compressed = encoder.finish()?;
+        let offset: u32 = zip_field(archive.len(), "entry offset")?;
+        let compressed_size: u32 = zip_field(compressed.len(), "compressed size")?;
+        let size: u32 = zip_field(contents.len(), "entry size")?;
+        let path_len: u16 = zip_field(path.len(), "path length")?;
+        let fields = |buffer: &mut Vec<u8>| {
+            buffer.extend_from_slice(&20u16.to_le_bytes()); // version needed: 2.0
+            buffer.extend_from_slice(&0u16.to_le_bytes()); // flags
+            buffer.extend_from_slice(&8u16.to_le_bytes()); // deflate
+            buffer.extend_from_slice(&DOS_TIME.to_le_bytes());
+            buffer.extend_from_slice(&DOS_DATE.to_le_bytes());
+            buffer.extend_from_slice(&crc.sum().to_le_bytes());
+            buffer.extend_from_slice(&compressed_size.to_le_bytes());
+            buffer.extend_from_slice(&size.to_le_bytes());
+            buffer.extend_from_slice(&path_len.to_le_bytes());
+            buffer.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        };
+
+        archive.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        fields(&mut archive);
+        archive.extend_from_slice(path.as_bytes());
+        archive.extend_from_slice(&compressed);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        fields(&mut directory);
+        directory.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        directory.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(path.as_bytes());
+    }
+    let directory_offset: u32 = zip_field(archive.len(), "directory offset")?;
+    let directory_size: u32 = zip_field(directory.len(), "directory size")?;
+    let count: u16 = zip_field(entries.len(), "entry count")?;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]); // disk numbers
+    archive.extend_from_slice(&count.to_le_bytes());
+    archive.extend_from_slice(&count.to_le_bytes());
+    archive.extend_from_slice(&directory_size.to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    fn u16_at(bytes: &[u8], at: usize) -> usize {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> usize {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+    }
+
+    /// Follows the central directory to each entry and inflates it.
+    fn unzip(archive: &[u8]) -> Vec<(String, String)> {
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(archive, end), 0x0605_4b50);
+        let mut at = u32_at(archive, end + 16);
+        let mut entries = Vec::new();
+        for _ in 0..u16_at(archive, end + 10) {
+            assert_eq!(u32_at(archive, at), 0x0201_4b50);
+            let compressed = u32_at(archive, at + 20);
+            let name_len = u16_at(archive, at + 28);
+            let local = u32_at(archive, at + 42);
+            let name = String::from_utf8(archive[at + 46..at + 46 + name_len].to_vec()).unwrap();
+            let data = local + 30 + u16_at(archive, local + 26);
+            let mut contents = String::new();
+            DeflateDecoder::new(&archive[data..data + compressed])
+                .read_to_string(&mut contents)
+                .unwrap();
+            let mut crc = Crc::new();
+            crc.update(contents.as_bytes());
+            assert_eq!(crc.sum() as usize, u32_at(archive, at + 16));
+            entries.push((name, contents));
+            at += 46 + name_len;
+        }
+        entries
+    }
+
+    const COLUMNS: [&str; 3] = ["player_name", "count", "x"];
+
+    fn rows() -> Vec<Vec<Cell>> {
+        vec![
+            vec![
+                Cell::from("Steve, \"the\" <duper>"),
+                Cell::from(64i64),
+                Cell::from(Some(-3)),
+            ],
+            vec![Cell::from("alex"), Cell::from(1u64), Cell::from(None)],
+        ]
+    }
+
+    /// Hands out `chunks` in order, then fails if `fail` or ends.
+    struct Chunks {
+        chunks: Vec<Vec<Vec<Cell>>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl RowSource for Chunks {
+        async fn next_rows(&mut self) -> Result<Vec<Vec<Cell>>, HttpError> {
+            if !self.chunks.is_empty() {
+                return Ok(self.chunks.remove(0));
+            }
+            if self.fail {
+                return Err(HttpError::Unavailable("clickhouse went away".to_string()));
+            }
+            Ok(Vec::new())
+        }
+    }
+
+    async fn download(
+        format: ExportFormat,
+        source: Chunks,
+    ) -> Result<Result<Vec<u8>, axum::Error>, HttpError> {
+        let response = export_response(format, "anomalies-2026-10-15", &COLUMNS, source).await?;
+        Ok(axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map(|bytes| bytes.to_vec()))
+    }
+
+    #[tokio::test]
+    async fn exports_render_as_csv_and_as_a_readable_xlsx_workbook() {
+        let source = Chunks {
+            chunks: vec![rows(), rows()],
+            fail: false,
+        };
+        let csv = download(ExportFormat::Csv, source).await.unwrap().unwrap();
+        let line = "\"Steve, \"\"the\"\" <duper>\",64,-3\r\nalex,1,\r\n";
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!("\u{feff}player_name,count,x\r\n{}{}", line, line)
+        );
+
+        let source = Chunks {
+            chunks: vec![rows()],
+            fail: false,
+        };
+        let xlsx = download(ExportFormat::Xlsx, source).await.unwrap().unwrap();
+        let entries = unzip(&xlsx);
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/worksheets/sheet1.xml"
+            ]
+        );
+        assert!(entries[2].1.contains(r#"name="anomalies-2026-10-15""#));
+        let sheet = &entries[4].1;
+        assert_eq!(sheet.matches("<row>").count(), 3);
+        assert!(sheet.contains("Steve, &quot;the&quot; &lt;duper&gt;"));
+        assert!(sheet.contains("<c><v>64</v></c><c><v>-3</v></c>"));
+
+        let query = |format: &str| ExportQuery {
+            format: Some(format.to_string()),
+        };
+        assert_eq!(query("XLSX").format().unwrap(), Some(ExportFormat::Xlsx));
+        assert_eq!(query("json").format().unwrap(), None);
+        assert!(query("parquet").format().is_err());
+    }
+
+    #[tokio::test]
+    async fn a_failure_after_the_first_chunk_cuts_the_csv_short() {
+        let unreachable = Chunks {
+            chunks: Vec::new(),
+            fail: true,
+        };
+        assert!(matches!(
+            download(ExportFormat::Csv, unreachable).await,
+            Err(HttpError::Unavailable(_))
+        ));
+        let midway = Chunks {
+            chunks: vec![rows()],
+            fail: true,
+        };
+        assert!(download(ExportFormat::Csv, midway).await.unwrap().is_err());
+    }
+
+    #[test]
+    fn csv_text_that_reads_as_a_formula_stays_text() {
+        let row = vec![vec![
+            Cell::from("=HYPERLINK(\"http://evil\",\"x\")"),
+            Cell::from("@SUM(A1)"),
+            Cell::from("+1"),
+            Cell::from("-1"),
+            Cell::from(-1i64),
+            Cell::from("a=b"),
+        ]];
+        assert_eq!(
+            csv_lines(&row),
+            "\"'=HYPERLINK(\"\"http://evil\"\",\"\"x\"\")\",'@SUM(A1),'+1,'-1,-1,a=b\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn sizes_past_the_zip_and_sheet_limits_are_refused() {
+        assert!(zip_field::<u16>(u16::MAX as usize, "entry count").is_ok());
+        assert!(zip_field::<u16>(u16::MAX as usize + 1, "entry count").is_err());
+        assert!(zip_field::<u32>(u32::MAX as usize + 1, "entry size").is_err());
+
+        let too_many = Chunks {
+            chunks: vec![(0..XLSX_MAX_ROWS).map(|_| Vec::new()).collect()],
+            fail: false,
+        };
+        assert!(matches!(
+            download(ExportFormat::Xlsx, too_many).await,
+            Err(HttpError::BadRequest(_))
+        ));
+    }
+}
@@ -1,63 +1,891 @@
-use axum::extract::{Query, State};
-use axum::http::{HeaderMap, StatusCode};
+use std::convert::Infallible;
+
+use async_trait::async_trait;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use futures_util::Stream;
+use serde::Serialize;
 
-use backend_application::commands::key_item_commands;
-use backend_application::queries::{anomaly_queries, key_item_queries, storage_scan_queries};
+use backend_application::commands::{
+    anomaly_commands, key_item_commands, origin_whitelist_commands, suppression_commands,
+};
+use backend_application::queries::anomaly_queries::{AnomalyExport, AnomalyStreamItem};
+use backend_application::queries::storage_scan_queries::StorageScanExport;
+use backend_application::queries::{
+    analyzer_queries, anomaly_queries, key_item_queries, origin_whitelist_queries,
+    storage_scan_queries, suppression_queries,
+};
 use backend_application::AppState;
-use backend_domain::{AnomalyQuery, AnomalyRow, KeyItemRuleApi, PagedResult, StorageScanQuery, StorageScanRow};
+use backend_domain::{
+    AnalyzerStatus, AnomalyAckRequest, AnomalyAckResult, AnomalyDailySummaryRow,
+    AnomalyLookupQuery, AnomalyQuery, AnomalySeenRequest, AnomalySeenResult, AnomalyStreamQuery,
+    AnomalySuppression, AnomalyTrendQuery, AnomalyView, ApiTokenScope, CompositeRule,
+    DetectionRule, ExpiredSuppressionQuery, FieldSelection, KeyItemRuleApi, OriginLearningRequest,
+    OriginWhitelist, OriginWhitelistUpdate, PagedResult, RulePreset, RulePresetApplyRequest,
+    RulePresetApplyResult, StorageScanQuery, StorageScanRow, SuppressionRequest, TimeDisplay,
+    ANOMALY_FIELDS, STORAGE_SCAN_FIELDS,
+};
 
 use crate::error::HttpError;
-use crate::middleware::authorize;
+use crate::export::{export_response, Cell, ExportQuery, RowSource};
+use crate::middleware::{authorize, authorize_admin, json_with_etag, request_actor};
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct KeyItemRulesPayload {
     pub rules: Vec<KeyItemRuleApi>,
 }
 
+/// `envelope` query flag of list endpoints that moved to `PagedResult`.
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListShapeQuery {
+    pub envelope: Option<String>,
+}
+
+/// `fields` query parameter: comma-separated item keys to keep, for clients that only need counts.
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+impl FieldsQuery {
+    pub(crate) fn selection(&self, allowed: &[&str]) -> Result<FieldSelection, HttpError> {
+        FieldSelection::parse(self.fields.as_deref(), allowed).map_err(HttpError::BadRequest)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListEnvelope {
+    Paged,
+    /// Bare item array for older mod dashboards; paging moves to `X-*` headers. Deprecated.
+    Flat,
+}
+
+impl ListEnvelope {
+    fn parse(value: Option<&str>) -> Result<Self, HttpError> {
+        match value
+            .map(|value| value.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("") | Some("paged") => Ok(ListEnvelope::Paged),
+            Some("flat") => Ok(ListEnvelope::Flat),
+            Some(other) => Err(HttpError::BadRequest(format!(
+                "envelope must be flat or paged, got {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Serializes the page, keeping only the selected keys of each item.
+pub(crate) fn project_page<T: Serialize>(
+    result: PagedResult<T>,
+    fields: &FieldSelection,
+) -> Result<PagedResult<serde_json::Value>, HttpError> {
+    let items = result
+        .items
+        .into_iter()
+        .map(|item| serde_json::to_value(item).map(|value| fields.project(value)))
+        .collect::<Result<_, _>>()
+        .map_err(|err| HttpError::Internal(err.to_string()))?;
+    Ok(PagedResult {
+        items,
+        page: result.page,
+        page_size: result.page_size,
+        total_items: result.total_items,
+        total_pages: result.total_pages,
+        degraded: result.degraded,
+    })
+}
+
+fn paged_response<T: Serialize>(result: PagedResult<T>, envelope: ListEnvelope) -> Response {
+    if envelope == ListEnvelope::Paged {
+        return Json(result).into_response();
+    }
+    let mut response = Json(result.items).into_response();
+    let headers = response.headers_mut();
+    for (name, value) in [
+        ("x-total-count", result.total_items),
+        ("x-page", result.page),
+        ("x-page-size", result.page_size),
+        ("x-total-pages", result.total_pages),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
+    }
+    if result.degraded {
+        headers.insert("x-lattice-degraded", HeaderValue::from_static("true"));
+    }
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/anomalies",
+    tag = "detect",
+    summary = "List anomalies of one day",
+    params(AnomalyQuery, ListShapeQuery, FieldsQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of anomalies, or with `format` every anomaly of the day as a CSV or XLSX attachment"),
+        (status = 503, description = "ClickHouse is unreachable during a `format` download")
+    )
+)]
 pub async fn list_anomalies(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<AnomalyQuery>,
-) -> Result<Json<PagedResult<AnomalyRow>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
+    Query(shape): Query<ListShapeQuery>,
+    Query(select): Query<FieldsQuery>,
+    Query(export): Query<ExportQuery>,
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    if let Some(format) = export.format()? {
+        let export = anomaly_queries::export_anomalies(&state, query).await?;
+        let name = format!("anomalies-{}", export.date());
+        return export_response(format, &name, &ANOMALY_EXPORT_COLUMNS, AnomalyRows(export)).await;
+    }
+    let envelope = ListEnvelope::parse(shape.envelope.as_deref())?;
+    let fields = select.selection(&ANOMALY_FIELDS)?;
+    let rows = anomaly_queries::list_anomalies(&state, query, &fields).await?;
+    if fields.is_all() {
+        return Ok(paged_response(rows, envelope));
+    }
+    Ok(paged_response(project_page(rows, &fields)?, envelope))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/anomalies/lookup",
+    tag = "detect",
+    summary = "Look up one anomaly by id",
+    params(AnomalyLookupQuery),
+    responses(
+        (status = 200, description = "The anomaly"),
+        (status = 404, description = "Unknown id")
+    )
+)]
+pub async fn get_anomaly(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnomalyLookupQuery>,
+) -> Result<Json<AnomalyView>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let row = anomaly_queries::get_anomaly(&state, query)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok(Json(row))
+}
+
+/// Server-sent events of new anomalies: `anomaly` events carry an `AnomalyView`, `lagged`
+/// events the number of anomalies a slow connection skipped.
+#[utoipa::path(
+    get,
+    path = "/v2/detect/anomalies/stream",
+    tag = "detect",
+    summary = "Server-sent events of new anomalies",
+    params(AnomalyStreamQuery),
+    responses(
+        (status = 200, description = "`text/event-stream` of `anomaly` and `lagged` events")
+    )
+)]
+pub async fn stream_anomalies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnomalyStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let subscription = anomaly_queries::subscribe_anomalies(&state, query)?;
+    let events = futures_util::stream::unfold(subscription, |mut subscription| async move {
+        let event = match subscription.next().await? {
+            AnomalyStreamItem::Anomaly(view) => Event::default()
+                .event("anomaly")
+                .id(view.id.clone())
+                .json_data(&view)
+                .unwrap_or_else(|err| Event::default().comment(err.to_string())),
+            AnomalyStreamItem::Lagged(skipped) => {
+                Event::default().event("lagged").data(skipped.to_string())
+            }
+        };
+        Some((Ok(event), subscription))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/anomalies/bulk-ack",
+    tag = "detect",
+    summary = "Acknowledge the anomalies matching a filter",
+    responses(
+        (status = 200, description = "Acknowledged anomalies")
+    )
+)]
+pub async fn bulk_ack_anomalies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<AnomalyAckRequest>,
+) -> Result<Json<AnomalyAckResult>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    payload.acked_by = Some(request_actor(&headers));
+    let result = anomaly_commands::bulk_ack_anomalies(&state, payload).await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/anomalies/seen",
+    tag = "detect",
+    summary = "Mark anomalies as seen",
+    responses(
+        (status = 200, description = "Seen anomalies")
+    )
+)]
+pub async fn mark_anomalies_seen(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<AnomalySeenRequest>,
+) -> Result<Json<AnomalySeenResult>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    payload.seen_by = Some(request_actor(&headers));
+    let result = anomaly_commands::mark_anomalies_seen(&state, payload).await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/suppressions",
+    tag = "detect",
+    summary = "List active suppressions",
+    responses(
+        (status = 200, description = "Active suppressions")
+    )
+)]
+pub async fn list_suppressions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AnomalySuppression>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(suppression_queries::list_suppressions(&state).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/suppressions",
+    tag = "detect",
+    summary = "Suppress matching anomalies for a while",
+    responses(
+        (status = 200, description = "The stored suppression")
+    )
+)]
+pub async fn create_suppression(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SuppressionRequest>,
+) -> Result<Json<AnomalySuppression>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
     }
-    let rows = anomaly_queries::list_anomalies(&state, query).await?;
+    let suppression = suppression_commands::create_suppression(&state, payload).await?;
+    Ok(Json(suppression))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/suppressions/expired",
+    tag = "detect",
+    summary = "List recently expired suppressions",
+    params(ExpiredSuppressionQuery),
+    responses(
+        (status = 200, description = "Expired suppressions")
+    )
+)]
+pub async fn list_expired_suppressions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ExpiredSuppressionQuery>,
+) -> Result<Json<Vec<AnomalySuppression>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let items = suppression_queries::list_expired_suppressions(&state, query).await?;
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/anomalies/trend",
+    tag = "detect",
+    summary = "Daily anomaly counts",
+    params(AnomalyTrendQuery),
+    responses(
+        (status = 200, description = "One row per day and rule")
+    )
+)]
+pub async fn anomaly_trend(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AnomalyTrendQuery>,
+) -> Result<Json<Vec<AnomalyDailySummaryRow>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let rows = anomaly_queries::anomaly_trend(&state, query).await?;
     Ok(Json(rows))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/detect/storage-scan",
+    tag = "detect",
+    summary = "List storage scan findings",
+    params(StorageScanQuery, FieldsQuery, ExportQuery),
+    responses(
+        (status = 200, description = "A page of findings, or with `format` every finding of the day as a CSV or XLSX attachment")
+    )
+)]
 pub async fn list_storage_scan(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<StorageScanQuery>,
-) -> Result<Json<PagedResult<StorageScanRow>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
+    Query(select): Query<FieldsQuery>,
+    Query(export): Query<ExportQuery>,
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    if let Some(format) = export.format()? {
+        let export = storage_scan_queries::export_storage_scan(&state, query).await?;
+        let name = format!("storage-scan-{}", export.date());
+        let rows = StorageScanRows {
+            export,
+            display: TimeDisplay::from_config(&state.config),
+        };
+        return export_response(format, &name, &STORAGE_SCAN_EXPORT_COLUMNS, rows).await;
     }
-    let rows = storage_scan_queries::list_storage_scan(&state, query).await?;
-    Ok(Json(rows))
+    let fields = select.selection(&STORAGE_SCAN_FIELDS)?;
+    let rows = storage_scan_queries::list_storage_scan(&state, query, &fields).await?;
+    Ok(Json(project_page(rows, &fields)?).into_response())
+}
+
+const ANOMALY_EXPORT_COLUMNS: [&str; 14] = [
+    "id",
+    "time",
+    "server_id",
+    "player_name",
+    "player_uuid",
+    "item_id",
+    "count",
+    "rule_id",
+    "rule_description",
+    "risk_level",
+    "reason",
+    "acknowledged",
+    "seen",
+    "evidence_json",
+];
+
+struct AnomalyRows(AnomalyExport);
+
+#[async_trait]
+impl RowSource for AnomalyRows {
+    async fn next_rows(&mut self) -> Result<Vec<Vec<Cell>>, HttpError> {
+        let views = self.0.next_chunk().await?;
+        Ok(views.into_iter().map(anomaly_export_row).collect())
+    }
+}
+
+struct StorageScanRows {
+    export: StorageScanExport,
+    display: TimeDisplay,
+}
+
+#[async_trait]
+impl RowSource for StorageScanRows {
+    async fn next_rows(&mut self) -> Result<Vec<Vec<Cell>>, HttpError> {
+        let rows = self.export.next_chunk().await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| storage_scan_export_row(row, &self.display))
+            .collect())
+    }
+}
+
+fn anomaly_export_row(view: AnomalyView) -> Vec<Cell> {
+    let row = view.row;
+    vec![
+        view.id.into(),
+        view.display_time.into(),
+        row.server_id.into(),
+        row.player_name.into(),
+        row.player_uuid.into(),
+        row.item_id.into(),
+        row.count.into(),
+        row.rule_id.into(),
+        view.rule_description.into(),
+        row.risk_level.into(),
+        row.reason.into(),
+        view.acknowledged.into(),
+        view.seen.into(),
+        row.evidence_json.into(),
+    ]
 }
 
+const STORAGE_SCAN_EXPORT_COLUMNS: [&str; 14] = [
+    "time",
+    "item_id",
+    "count",
+    "threshold",
+    "risk_level",
+    "rule_id",
+    "rule_description",
+    "storage_mod",
+    "storage_id",
+    "dim",
+    "x",
+    "y",
+    "z",
+    "reason",
+];
+
+fn storage_scan_export_row(row: StorageScanRow, display: &TimeDisplay) -> Vec<Cell> {
+    vec![
+        display.format(row.event_time).into(),
+        row.item_id.into(),
+        row.count.into(),
+        row.threshold.into(),
+        row.risk_level.into(),
+        row.rule_id.into(),
+        row.rule_description.into(),
+        row.storage_mod.into(),
+        row.storage_id.into(),
+        row.dim.into(),
+        row.x.into(),
+        row.y.into(),
+        row.z.into(),
+        row.reason.into(),
+    ]
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/rules",
+    tag = "detect",
+    summary = "List key item rules",
+    responses(
+        (status = 200, description = "Key item rules", body = Vec<KeyItemRuleApi>),
+        (status = 304, description = "Unchanged since `If-None-Match`")
+    )
+)]
 pub async fn list_key_items(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<Json<Vec<KeyItemRuleApi>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let list = key_item_queries::list_key_items(&state).await?;
-    Ok(Json(list))
+    json_with_etag(&headers, &list)
 }
 
+#[utoipa::path(
+    put,
+    path = "/v2/detect/rules",
+    tag = "detect",
+    summary = "Replace the key item rules",
+    request_body = KeyItemRulesPayload,
+    responses(
+        (status = 204, description = "Saved"),
+        (status = 400, description = "Invalid rule")
+    )
+)]
 pub async fn update_key_items(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<KeyItemRulesPayload>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
     }
-    key_item_commands::update_key_items(&state, payload.rules).await?;
+    let actor = request_actor(&headers);
+    key_item_commands::update_key_items(&state, payload.rules, &actor).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/rules/presets",
+    tag = "detect",
+    summary = "List the rule presets shipped with the backend",
+    responses(
+        (status = 200, description = "Presets")
+    )
+)]
+pub async fn list_rule_presets(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RulePreset>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(key_item_queries::list_rule_presets()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/rules/custom",
+    tag = "detect",
+    summary = "List user detection rules",
+    responses(
+        (status = 200, description = "Rules from `detection_rules_path`", body = Vec<DetectionRule>)
+    )
+)]
+pub async fn list_detection_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<DetectionRule>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(key_item_queries::list_detection_rules(&state).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/rules/composite",
+    tag = "detect",
+    summary = "List composite rules",
+    responses(
+        (status = 200, description = "Rules from `composite_rules_path`", body = Vec<CompositeRule>)
+    )
+)]
+pub async fn list_composite_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<CompositeRule>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(key_item_queries::list_composite_rules(&state).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/rules/presets/{id}/apply",
+    tag = "detect",
+    summary = "Merge a preset into the key item rules",
+    params(("id" = String, Path, description = "Preset id")),
+    responses(
+        (status = 200, description = "Items added, replaced and kept"),
+        (status = 404, description = "Unknown preset")
+    )
+)]
+pub async fn apply_rule_preset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(request): Json<RulePresetApplyRequest>,
+) -> Result<Json<RulePresetApplyResult>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    let result = key_item_commands::apply_rule_preset(&state, &id, request, &actor)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/analyzer/status",
+    tag = "detect",
+    summary = "Analyzer partitions per server",
+    responses(
+        (status = 200, description = "Analyzer status")
+    )
+)]
+pub async fn analyzer_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<AnalyzerStatus>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(analyzer_queries::analyzer_status(&state).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/detect/origin-whitelist",
+    tag = "detect",
+    summary = "Origin types that do not raise R2",
+    responses(
+        (status = 200, description = "The whitelist")
+    )
+)]
+pub async fn get_origin_whitelist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<OriginWhitelist>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(
+        origin_whitelist_queries::get_origin_whitelist(&state).await,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/origin-whitelist",
+    tag = "detect",
+    summary = "Add or remove whitelisted origin types",
+    responses(
+        (status = 200, description = "The updated whitelist")
+    )
+)]
+pub async fn update_origin_whitelist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(update): Json<OriginWhitelistUpdate>,
+) -> Result<Json<OriginWhitelist>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    let whitelist =
+        origin_whitelist_commands::update_origin_whitelist(&state, update, &actor).await?;
+    Ok(Json(whitelist))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/detect/origin-whitelist/learning",
+    tag = "detect",
+    summary = "Start or end origin learning",
+    responses(
+        (status = 200, description = "The updated whitelist")
+    )
+)]
+pub async fn set_origin_learning(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<OriginLearningRequest>,
+) -> Result<Json<OriginWhitelist>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    let whitelist = origin_whitelist_commands::set_origin_learning(&state, request, &actor).await?;
+    Ok(Json(whitelist))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page() -> PagedResult<&'static str> {
+        PagedResult {
+            items: vec!["minecraft:diamond", "minecraft:elytra"],
+            page: 2,
+            page_size: 2,
+            total_items: 5,
+            total_pages: 3,
+            degraded: false,
+        }
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        serde_json::from_slice(&bytes).expect("json")
+    }
+
+    #[tokio::test]
+    async fn paged_envelope_keeps_the_paged_result() {
+        let envelope = ListEnvelope::parse(None).expect("default");
+        let response = paged_response(page(), envelope);
+        assert!(response.headers().get("x-total-count").is_none());
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!({
+                "items": ["minecraft:diamond", "minecraft:elytra"],
+                "page": 2,
+                "page_size": 2,
+                "total_items": 5,
+                "total_pages": 3
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn flat_envelope_returns_a_bare_array_with_paging_headers() {
+        let envelope = ListEnvelope::parse(Some("FLAT")).expect("flat");
+        let response = paged_response(page(), envelope);
+        let headers = response.headers();
+        assert_eq!(headers["x-total-count"], "5");
+        assert_eq!(headers["x-page"], "2");
+        assert_eq!(headers["x-total-pages"], "3");
+        assert_eq!(headers["deprecation"], "true");
+        assert_eq!(
+            body_json(response).await,
+            serde_json::json!(["minecraft:diamond", "minecraft:elytra"])
+        );
+        assert!(ListEnvelope::parse(Some("nested")).is_err());
+    }
+
+    #[test]
+    fn field_selection_trims_items_and_keeps_paging() {
+        let select = FieldsQuery {
+            fields: Some("id,count".to_string()),
+        };
+        let fields = select.selection(&ANOMALY_FIELDS).expect("fields");
+        let result = PagedResult {
+            items: vec![serde_json::json!({
+                "id": "a1",
+                "count": 64,
+                "evidence_json": "{\"slots\":[]}",
+                "reason": "picked up 64"
+            })],
+            page: 1,
+            page_size: 50,
+            total_items: 1,
+            total_pages: 1,
+            degraded: false,
+        };
+        let projected = project_page(result, &fields).expect("project");
+        assert_eq!(
+            projected.items,
+            [serde_json::json!({ "id": "a1", "count": 64 })]
+        );
+        assert_eq!(projected.total_items, 1);
+
+        let unknown = FieldsQuery {
+            fields: Some("id,storage_mod".to_string()),
+        };
+        assert!(unknown.selection(&ANOMALY_FIELDS).is_err());
+    }
+
+    fn anomaly(player_name: &str, risk_level: &str, rule_id: &str) -> backend_domain::AnomalyRow {
+        backend_domain::AnomalyRow {
+            // 2026-03-01T16:30:00Z
+            event_time: backend_domain::millis_to_utc(1_772_382_600_000),
+            server_id: "survival-01".to_string(),
+            player_uuid: format!("uuid-{}", player_name),
+            player_name: player_name.to_string(),
+            risk_level: risk_level.to_string(),
+            rule_id: rule_id.to_string(),
+            ..backend_domain::testing::anomaly_row()
+        }
+    }
+
+    #[tokio::test]
+    async fn anomalies_are_listed_and_acked_without_clickhouse() {
+        use backend_application::testing::InMemoryApp;
+        use backend_domain::AnomalyRepository;
+
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        app.anomalies
+            .insert_anomalies(&[anomaly("Steve", "HIGH", "R4"), anomaly("Alex", "LOW", "R1")])
+            .await
+            .expect("insert");
+
+        let query = AnomalyQuery {
+            date: Some("2026-03-01".to_string()),
+            player: None,
+            page: None,
+            page_size: None,
+            lang: None,
+        };
+        let response = list_anomalies(
+            State(app.state.clone()),
+            HeaderMap::new(),
+            Query(query),
+            Query(ListShapeQuery { envelope: None }),
+            Query(FieldsQuery { fields: None }),
+            Query(ExportQuery { format: None }),
+        )
+        .await
+        .expect("list");
+        let body = body_json(response).await;
+        assert_eq!(body["total_items"], 2);
+        assert_eq!(body["items"].as_array().map(Vec::len), Some(2));
+
+        let query = AnomalyQuery {
+            date: Some("2026-03-01".to_string()),
+            player: Some("Steve".to_string()),
+            page: Some(2),
+            page_size: None,
+            lang: None,
+        };
+        let response = list_anomalies(
+            State(app.state.clone()),
+            HeaderMap::new(),
+            Query(query),
+            Query(ListShapeQuery { envelope: None }),
+            Query(FieldsQuery { fields: None }),
+            Query(ExportQuery {
+                format: Some("csv".to_string()),
+            }),
+        )
+        .await
+        .expect("export");
+        assert_eq!(
+            response.headers()["content-disposition"],
+            "attachment; filename=\"anomalies-2026-03-01.csv\""
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        let csv = String::from_utf8(bytes.to_vec()).expect("utf-8");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\u{feff}id,time,server_id,player_name"));
+        assert!(lines[1].contains(",Steve,uuid-Steve,minecraft:diamond,64,R4,"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Lattice-Actor", HeaderValue::from_static("reviewer"));
+        let request = AnomalyAckRequest {
+            date: "2026-03-01".to_string(),
+            rule_id: Some("R4".to_string()),
+            player: None,
+            server_id: None,
+            item_id: None,
+            note: None,
+            acked_by: None,
+        };
+        let Json(result) = bulk_ack_anomalies(State(app.state.clone()), headers, Json(request))
+            .await
+            .expect("ack");
+        assert_eq!(result.matched, 1);
+        let acks = app.anomalies.acks();
+        assert_eq!(acks.len(), 1);
+        assert_eq!(acks[0].0.player_uuid, "uuid-Steve");
+        assert_eq!(acks[0].2, "reviewer");
+    }
+
+    #[tokio::test]
+    async fn suppressions_need_the_admin_secret_of_an_embedded_backend() {
+        use backend_application::ops::AdminSecret;
+        use backend_application::testing::InMemoryApp;
+
+        let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+        let mut state = app.state.clone();
+        let secret = AdminSecret::generate();
+        state.admin_secret = Some(secret.clone());
+        let request = || SuppressionRequest {
+            rule_id: Some("R4".to_string()),
+            player: None,
+            server_id: None,
+            item_id: None,
+            reason: None,
+            duration_minutes: 30,
+        };
+
+        let rejected =
+            create_suppression(State(state.clone()), HeaderMap::new(), Json(request())).await;
+        assert!(matches!(rejected, Err(HttpError::Forbidden(_))));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::middleware::ADMIN_SECRET_HEADER,
+            HeaderValue::from_str(secret.as_str()).expect("secret"),
+        );
+        let Json(stored) = create_suppression(State(state), headers, Json(request()))
+            .await
+            .expect("suppression");
+        assert_eq!(stored.rule_id.as_deref(), Some("R4"));
+    }
+}
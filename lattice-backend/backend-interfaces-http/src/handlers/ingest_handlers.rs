@@ -1,49 +1,238 @@
-use axum::extract::State;
-use axum::http::{HeaderMap, StatusCode};
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::Json;
 use tracing::{error, warn};
 
-use backend_application::commands::ingest_commands;
+use backend_application::commands::{cluster_commands, ingest_commands, server_identity_commands};
+use backend_application::ops::ModVersionCheck;
 use backend_application::AppState;
+use backend_domain::{
+    current_millis, ingest_contract_samples, AnomalyRow, ApiTokenScope, ClusterAnalyzeRequest,
+    IngestContractSamples, ServerHeartbeat,
+};
 
 use crate::error::HttpError;
-use crate::middleware::{authorize, parse_events};
+use crate::middleware::{authorize, parse_events, request_source, trusted_proxy_ips};
+
+const MOD_VERSION_HEADER: &str = "X-Lattice-Mod-Version";
+const MIN_MOD_VERSION_HEADER: &str = "X-Lattice-Min-Mod-Version";
+const MOD_VERSION_STATUS_HEADER: &str = "X-Lattice-Mod-Version-Status";
+const SERVER_KEY_HEADER: &str = "X-Lattice-Server-Key";
 
+#[utoipa::path(
+    post,
+    path = "/v2/ingest/events",
+    tag = "ingest",
+    summary = "Ingest a batch of item and custom events",
+    request_body(
+        content = backend_domain::IngestEnvelope,
+        description = "`schema_version` must be `v2`; the body may be sent with `Content-Encoding: gzip`"
+    ),
+    responses(
+        (status = 200, description = "Accepted"),
+        (status = 204, description = "Every event was filtered as invalid"),
+        (status = 400, description = "Invalid payload or schema version"),
+        (status = 403, description = "Claimed `server_id` does not match `X-Lattice-Server-Key`"),
+        (status = 429, description = "Ingest rate limit reached; retry after `Retry-After` seconds")
+    )
+)]
 pub async fn ingest_items(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
     body: axum::body::Bytes,
-) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
+) -> Result<(HeaderMap, StatusCode), HttpError> {
+    let source = request_source(
+        &headers,
+        connect_info.map(|ConnectInfo(addr)| addr),
+        &trusted_proxy_ips(&state.config.trusted_proxies),
+    );
+    if let Err(err) = authorize(&state, &headers, ApiTokenScope::Ingest).await {
+        warn!("ingest auth failed: source={}", source);
+        state
+            .ingest_tracker
+            .record_auth_failure(&source, current_millis())
+            .await;
+        return Err(err);
     }
 
-    let events = parse_events(&headers, &body).map_err(|err| {
+    let mut events = parse_events(&headers, &body).map_err(|err| {
         error!("failed to parse ingest body: {}", err);
         HttpError::BadRequest(err.to_string())
     })?;
+    let server_key = headers
+        .get(SERVER_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if let Err(mismatch) = server_identity_commands::check_server_identity(
+        &state.config.server_keys,
+        state.config.server_identity_required,
+        server_key,
+        &mut events,
+    ) {
+        return Err(
+            server_identity_commands::report_identity_mismatch(&state, &source, mismatch)
+                .await
+                .into(),
+        );
+    }
+    let server_id = events.iter().find_map(|event| event.server_id.as_deref());
+    let check = ingest_commands::check_mod_version(
+        &state,
+        server_id,
+        header_mod_version(&headers).as_deref(),
+    )
+    .await?;
+    let response_headers = mod_version_headers(&check);
     let original_len = events.len();
     let events = events
         .into_iter()
-        .filter(|event| {
-            !(event.item_id.trim().is_empty() || event.item_id == "minecraft:air" || event.count <= 0)
-        })
+        .filter(ingest_commands::is_valid_event)
         .collect::<Vec<_>>();
     if events.is_empty() {
         if original_len > 0 {
             warn!(
-                "dropped {} invalid events (empty item_id/air/<=0 count)",
+                "dropped {} invalid events (empty item_id/air/<=0 count/no custom_type)",
                 original_len
             );
         }
-        return Ok(StatusCode::NO_CONTENT);
+        return Ok((response_headers, StatusCode::NO_CONTENT));
     }
     if events.len() != original_len {
         warn!(
-            "dropped {} invalid events (empty item_id/air/<=0 count)",
+            "dropped {} invalid events (empty item_id/air/<=0 count/no custom_type)",
             original_len - events.len()
         );
     }
 
+    let server_ids: BTreeSet<String> = events
+        .iter()
+        .filter_map(|event| event.server_id.clone())
+        .collect();
     ingest_commands::process_ingest_events(&state, events).await?;
-    Ok(StatusCode::OK)
+    state
+        .ingest_tracker
+        .record_source(server_ids.iter().map(String::as_str), &source)
+        .await;
+    Ok((response_headers, StatusCode::OK))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/ingest/heartbeat",
+    tag = "ingest",
+    summary = "Record a server heartbeat",
+    request_body = ServerHeartbeat,
+    responses(
+        (status = 204, description = "Recorded")
+    )
+)]
+pub async fn ingest_heartbeat(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut payload): Json<ServerHeartbeat>,
+) -> Result<(HeaderMap, StatusCode), HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
+    if payload.mod_version.is_none() {
+        payload.mod_version = header_mod_version(&headers);
+    }
+    let server_id = payload.server_id.trim().to_lowercase();
+    let check = ingest_commands::check_mod_version(
+        &state,
+        Some(server_id.as_str()),
+        payload.mod_version.as_deref(),
+    )
+    .await?;
+    ingest_commands::record_heartbeat(&state, payload).await?;
+    Ok((mod_version_headers(&check), StatusCode::NO_CONTENT))
+}
+
+/// Example envelopes with the status `POST /v2/ingest/events` answers each with, for mod CI.
+#[utoipa::path(
+    get,
+    path = "/v2/ingest/contract-samples",
+    tag = "ingest",
+    summary = "Canonical ingest envelopes with the status each is answered with",
+    responses(
+        (status = 200, description = "Samples, also checked in as `contracts/ingest-v2.json`")
+    )
+)]
+pub async fn contract_samples(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<IngestContractSamples>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
+    Ok(Json(ingest_contract_samples()))
+}
+
+/// Analyzes a batch forwarded by a `cluster_mode` replica against the shared windows.
+#[utoipa::path(
+    post,
+    path = "/v2/cluster/analyze",
+    tag = "ingest",
+    summary = "Analyze a batch forwarded by a cluster replica",
+    responses(
+        (status = 200, description = "Anomalies raised for the batch")
+    )
+)]
+pub async fn cluster_analyze(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ClusterAnalyzeRequest>,
+) -> Result<Json<Vec<AnomalyRow>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
+    let anomalies = cluster_commands::serve_analyze(&state, request).await?;
+    Ok(Json(anomalies))
+}
+
+fn header_mod_version(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(MOD_VERSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToString::to_string)
+}
+
+fn mod_version_headers(check: &ModVersionCheck) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let ModVersionCheck::Outdated { minimum } = check {
+        headers.insert(
+            MOD_VERSION_STATUS_HEADER,
+            HeaderValue::from_static("outdated"),
+        );
+        if let Ok(value) = HeaderValue::from_str(minimum) {
+            headers.insert(MIN_MOD_VERSION_HEADER, value);
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use backend_application::testing::InMemoryApp;
+
+    #[tokio::test]
+    async fn contract_samples_get_the_status_they_advertise() {
+        for sample in ingest_contract_samples().samples {
+            let app = InMemoryApp::new(backend_domain::testing::runtime_config());
+            let body = serde_json::to_vec(&sample.envelope).expect("json");
+            let status = match ingest_items(
+                State(app.state.clone()),
+                None,
+                HeaderMap::new(),
+                body.into(),
+            )
+            .await
+            {
+                Ok((_, status)) => status,
+                Err(err) => err.into_response().status(),
+            };
+            assert_eq!(status.as_u16(), sample.status, "{}", sample.name);
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use axum::extract::{
     ws::{Message, WebSocket, WebSocketUpgrade},
-    Query, State,
+    Path, Query, State,
 };
 use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
@@ -11,17 +11,34 @@ use tokio::time::{timeout, Duration};
 use tracing::{error, warn};
 
 use backend_application::commands::{
-    mod_config_commands, op_token_commands, task_progress_commands,
+    alert_page_commands, api_token_commands, ban_commands, chat_ack_commands,
+    dead_letter_commands, maintenance_commands, ml_export_commands, mod_config_commands,
+    op_token_commands, player_team_commands, quarantine_commands, replay_commands,
+    report_commands, selftest_commands, task_progress_commands,
+};
+use backend_application::queries::{
+    alert_queries, api_token_queries, ban_queries, config_queries, ingest_queries, job_queries,
+    maintenance_queries, mod_config_queries, overview_queries, player_team_queries,
+    preflight_queries, quarantine_queries, report_queries, task_progress_queries,
 };
-use backend_application::queries::{mod_config_queries, task_progress_queries};
 use backend_application::AppState;
 use backend_domain::{
-    AlertDeliveryRecord, ModConfigAck, ModConfigEnvelope, ModConfigPutRequest, OpTokenIssueRequest,
-    OpTokenIssueResponse, OpTokenMisuseAlertRequest, RconConfig, TaskProgressUpdate, TaskStatus,
+    AlertDeliveryRecord, AlertPreview, AlertPreviewRequest, ApiTokenIssueRequest, ApiTokenIssued,
+    ApiTokenScope, ApiTokenView, BanEventRequest, ClickhousePreflight,
+    ConfigWarning, DataDropQuery, DataDropResult, EffectiveConfig, FingerprintStatsQuery,
+    FingerprintStatsReport, IngestStaleReport, Job, MaintenanceStatus, MlExportRequest, ModConfigAck, ModConfigEnvelope,
+    ModConfigPutRequest, OpTokenIssueRequest, OpTokenIssueResponse, OpTokenMisuseAlertRequest,
+    OpsOverview, PlayerBan, PlayerQuarantine, PlayerTeam, QuarantineRequest, RconConfig, ReadyStatus, ReplayReport, ReportFile, SelftestReport,
+    ServerStatusReport, StrictnessStatus, TaskProgressUpdate, TaskStatus,
 };
 
 use crate::error::HttpError;
-use crate::middleware::authorize;
+use crate::middleware::{authorize, authorize_admin, request_actor};
+
+#[derive(serde::Deserialize)]
+pub struct PlayerTeamsPayload {
+    pub players: Vec<PlayerTeam>,
+}
 
 #[derive(serde::Serialize)]
 struct AlertStatus {
@@ -29,17 +46,20 @@ struct AlertStatus {
     mode: String,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AlertDeliveryQuery {
     pub limit: Option<usize>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ServerIdQuery {
     pub server_id: Option<String>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ModConfigPullQuery {
     pub server_id: Option<String>,
     pub after_revision: Option<u64>,
@@ -61,13 +81,20 @@ pub struct NapcatGroupMessageEvent {
     pub message: Option<Value>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/rcon-config",
+    tag = "ops",
+    summary = "RCON settings",
+    responses(
+        (status = 200, description = "RCON settings")
+    )
+)]
 pub async fn get_rcon_config(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Result<Json<RconConfig>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
     let config = state
         .config_repo
         .load_rcon_config()
@@ -76,14 +103,21 @@ pub async fn get_rcon_config(
     Ok(Json(config))
 }
 
+#[utoipa::path(
+    put,
+    path = "/v2/ops/rcon-config",
+    tag = "ops",
+    summary = "Save RCON settings",
+    responses(
+        (status = 204, description = "Saved")
+    )
+)]
 pub async fn update_rcon_config(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<RconConfig>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     state
         .config_repo
         .save_rcon_config(&payload)
@@ -92,74 +126,128 @@ pub async fn update_rcon_config(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/task-progress",
+    tag = "ops",
+    summary = "Progress of background tasks",
+    responses(
+        (status = 200, description = "Task status")
+    )
+)]
 pub async fn get_task_progress(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Result<Json<TaskStatus>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let status = task_progress_queries::get_task_progress(&state).await;
     Ok(Json(status))
 }
 
+#[utoipa::path(
+    put,
+    path = "/v2/ops/task-progress",
+    tag = "ops",
+    summary = "Report task progress",
+    responses(
+        (status = 204, description = "Saved")
+    )
+)]
 pub async fn update_task_progress(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<TaskProgressUpdate>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     task_progress_commands::update_task_progress(&state, payload).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/v2/ops/op-token/issue",
+    tag = "ops",
+    summary = "Issue a one-time op token",
+    responses(
+        (status = 200, description = "The token")
+    )
+)]
 pub async fn issue_op_token(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<OpTokenIssueRequest>,
 ) -> Result<Json<OpTokenIssueResponse>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
     let issued = op_token_commands::issue_op_token(&state, payload).await?;
     Ok(Json(issued))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v2/ops/op-token/misuse-alert",
+    tag = "ops",
+    summary = "Report a misused op token",
+    responses(
+        (status = 204, description = "Alert sent")
+    )
+)]
 pub async fn report_op_token_misuse(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<OpTokenMisuseAlertRequest>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     op_token_commands::report_op_token_misuse(&state, payload).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/v2/ops/napcat/group-event",
+    tag = "ops",
+    summary = "NapCat group message webhook",
+    responses(
+        (status = 204, description = "Handled")
+    )
+)]
 pub async fn handle_napcat_group_event(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<NapcatGroupMessageEvent>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
     if !is_group_message_event(&payload) {
         return Ok(StatusCode::NO_CONTENT);
     }
 
     let command_text = normalize_command_text(payload.raw_message.as_deref(), payload.message.as_ref());
-    if !is_issue_token_command(&command_text) {
-        return Ok(StatusCode::NO_CONTENT);
-    }
-
     let group_id = match payload.group_id {
         Some(value) if value > 0 => value,
         _ => return Ok(StatusCode::NO_CONTENT),
     };
+    if alert_page_commands::is_more_alerts_command(&command_text) {
+        let page = alert_page_commands::next_alert_page_reply(&state, group_id).await;
+        state
+            .alert_service
+            .send_group_text(&state.config, group_id, &page)
+            .await
+            .map_err(|err| HttpError::Internal(err.to_string()))?;
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    if let Some(target) = chat_ack_commands::parse_ack_command(&command_text) {
+        let user_id = payload.user_id.filter(|value| *value > 0);
+        let reply = chat_ack_commands::chat_ack_reply(&state, group_id, user_id, target).await;
+        state
+            .alert_service
+            .send_group_text(&state.config, group_id, &reply)
+            .await
+            .map_err(|err| HttpError::Internal(err.to_string()))?;
+        return Ok(StatusCode::NO_CONTENT);
+    }
+    if !is_issue_token_command(&command_text) {
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
     let operator_id = payload
         .user_id
         .filter(|value| *value > 0)
@@ -185,80 +273,130 @@ pub async fn handle_napcat_group_event(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/mod-config/current",
+    tag = "ops",
+    summary = "Current mod config",
+    params(ServerIdQuery),
+    responses(
+        (status = 200, description = "The config, or null")
+    )
+)]
 pub async fn get_mod_config_current(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ServerIdQuery>,
 ) -> Result<Json<Option<ModConfigEnvelope>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
     let server_id = resolve_server_id(query.server_id);
     let value = mod_config_queries::get_mod_config(&state, &server_id).await?;
     Ok(Json(value))
 }
 
+#[utoipa::path(
+    put,
+    path = "/v2/ops/mod-config/current",
+    tag = "ops",
+    summary = "Publish a mod config",
+    params(ServerIdQuery),
+    responses(
+        (status = 200, description = "The published config")
+    )
+)]
 pub async fn put_mod_config_current(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ServerIdQuery>,
     Json(payload): Json<ModConfigPutRequest>,
 ) -> Result<Json<ModConfigEnvelope>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
     }
     let envelope = mod_config_commands::put_mod_config(&state, query.server_id, payload).await?;
     Ok(Json(envelope))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/mod-config/pull",
+    tag = "ops",
+    summary = "Long-poll for a newer mod config",
+    params(ModConfigPullQuery),
+    responses(
+        (status = 200, description = "A newer config, or null on timeout")
+    )
+)]
 pub async fn pull_mod_config(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ModConfigPullQuery>,
 ) -> Result<Json<Option<ModConfigEnvelope>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     let server_id = resolve_server_id(query.server_id);
     let value =
         mod_config_queries::pull_mod_config(&state, &server_id, query.after_revision).await?;
     Ok(Json(value))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v2/ops/mod-config/ack",
+    tag = "ops",
+    summary = "Acknowledge an applied mod config",
+    responses(
+        (status = 204, description = "Recorded")
+    )
+)]
 pub async fn update_mod_config_ack(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<ModConfigAck>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     mod_config_commands::save_mod_config_ack(&state, payload).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/mod-config/ack/last",
+    tag = "ops",
+    summary = "Last mod config acknowledgement",
+    params(ServerIdQuery),
+    responses(
+        (status = 200, description = "The acknowledgement, or null")
+    )
+)]
 pub async fn get_mod_config_ack_last(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ServerIdQuery>,
 ) -> Result<Json<Option<ModConfigAck>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let server_id = resolve_server_id(query.server_id);
     let ack = mod_config_queries::get_mod_config_ack(&state, &server_id).await?;
     Ok(Json(ack))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/mod-config/stream",
+    tag = "ops",
+    summary = "WebSocket pushing mod config changes",
+    params(ServerIdQuery),
+    responses(
+        (status = 101, description = "Switching to WebSocket")
+    )
+)]
 pub async fn stream_mod_config(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ServerIdQuery>,
     ws: WebSocketUpgrade,
 ) -> Result<Response, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     let server_id = resolve_server_id(query.server_id);
     let receiver = state.mod_config_stream_hub.subscribe(&server_id).await;
     let initial = mod_config_queries::get_mod_config(&state, &server_id).await?;
@@ -268,19 +406,32 @@ pub async fn stream_mod_config(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/alert-target/check",
+    tag = "ops",
+    summary = "Check the alert target is reachable",
+    responses(
+        (status = 200, description = "Check result")
+    )
+)]
 pub async fn alert_target_check(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if !authorize(&state.config, &headers) {
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(AlertStatus {
-                status: "unauthorized".to_string(),
-                mode: "unset".to_string(),
-            }),
-        )
-            .into_response();
+    match authorize(&state, &headers, ApiTokenScope::Read).await {
+        Ok(()) => {}
+        Err(HttpError::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(AlertStatus {
+                    status: "unauthorized".to_string(),
+                    mode: "unset".to_string(),
+                }),
+            )
+                .into_response();
+        }
+        Err(err) => return err.into_response(),
     }
 
     let timeout_secs = state.config.request_timeout_seconds.max(1);
@@ -340,58 +491,714 @@ pub async fn alert_target_check(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/alert-deliveries",
+    tag = "ops",
+    summary = "Recent alert deliveries",
+    params(AlertDeliveryQuery),
+    responses(
+        (status = 200, description = "Deliveries, newest first")
+    )
+)]
 pub async fn list_alert_deliveries(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<AlertDeliveryQuery>,
 ) -> Result<Json<Vec<AlertDeliveryRecord>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let limit = query.limit.unwrap_or(50).clamp(1, 200);
     let deliveries = state.alert_service.list_alert_deliveries(limit).await;
     Ok(Json(deliveries))
 }
 
+#[utoipa::path(
+    post,
+    path = "/v2/ops/alerts/preview",
+    tag = "ops",
+    summary = "Render alerts without sending them",
+    responses(
+        (status = 200, description = "Rendered messages")
+    )
+)]
+pub async fn preview_alerts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<AlertPreviewRequest>,
+) -> Result<Json<AlertPreview>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let preview = alert_queries::preview_alerts(&state, payload).await?;
+    Ok(Json(preview))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/alert-deliveries/last",
+    tag = "ops",
+    summary = "Last alert delivery",
+    responses(
+        (status = 200, description = "The delivery, or null")
+    )
+)]
 pub async fn get_last_alert_delivery(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Result<Json<Option<AlertDeliveryRecord>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let last = state.alert_service.last_alert_delivery().await;
     Ok(Json(last))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/ingest/stale-servers",
+    tag = "ops",
+    summary = "Servers that stopped sending events",
+    responses(
+        (status = 200, description = "Stale servers")
+    )
+)]
+pub async fn list_stale_ingest_servers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<IngestStaleReport>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let report = ingest_queries::get_stale_servers(&state).await;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/ingest/fingerprint-stats",
+    tag = "ops",
+    summary = "Missing and shared item fingerprints per server",
+    params(FingerprintStatsQuery),
+    responses(
+        (status = 200, description = "Fingerprint quality per server"),
+        (status = 400, description = "Invalid hours or top")
+    )
+)]
+pub async fn get_fingerprint_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FingerprintStatsQuery>,
+) -> Result<Json<FingerprintStatsReport>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let report = ingest_queries::fingerprint_stats(&state, query).await?;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/overview",
+    tag = "ops",
+    summary = "Dashboard overview",
+    responses(
+        (status = 200, description = "Overview")
+    )
+)]
+pub async fn get_ops_overview(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<OpsOverview>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let overview = overview_queries::ops_overview(&state).await;
+    Ok(Json(overview))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/servers/status",
+    tag = "ops",
+    summary = "Heartbeat status per server",
+    responses(
+        (status = 200, description = "Server status")
+    )
+)]
+pub async fn list_server_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ServerStatusReport>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let report = ingest_queries::list_server_status(&state).await;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/maintenance",
+    tag = "ops",
+    summary = "ClickHouse maintenance status",
+    responses(
+        (status = 200, description = "Maintenance status")
+    )
+)]
+pub async fn get_maintenance_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<MaintenanceStatus>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let status = maintenance_queries::get_maintenance_status(&state).await;
+    Ok(Json(status))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v2/ops/data",
+    tag = "ops",
+    summary = "Drop one daily partition, confirmed by a second call",
+    params(DataDropQuery),
+    responses(
+        (status = 200, description = "The partition to drop or dropped")
+    )
+)]
+pub async fn drop_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DataDropQuery>,
+) -> Result<Json<DataDropResult>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    let result = maintenance_commands::drop_data_partition(&state, &query, &actor)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/config/effective",
+    tag = "ops",
+    summary = "Effective configuration with secrets masked",
+    responses(
+        (status = 200, description = "Effective config")
+    )
+)]
+pub async fn get_effective_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<EffectiveConfig>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let config = config_queries::effective_config(&state)?;
+    Ok(Json(config))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/config/warnings",
+    tag = "ops",
+    summary = "Configuration sanity warnings",
+    responses(
+        (status = 200, description = "Warnings")
+    )
+)]
+pub async fn get_config_warnings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ConfigWarning>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(config_queries::config_warnings(&state).await))
+}
+
+/// Inbound webhook for external ban systems: `ban` answers the stored ban, `unban` answers 204.
+#[utoipa::path(
+    post,
+    path = "/v2/ops/integrations/ban-events",
+    tag = "ops",
+    summary = "Ban or unban webhook for external ban systems",
+    responses(
+        (status = 200, description = "The stored ban"),
+        (status = 204, description = "Unbanned")
+    )
+)]
+pub async fn record_ban_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<BanEventRequest>,
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    match ban_commands::record_ban_event(&state, payload).await? {
+        Some(ban) => Ok(Json(ban).into_response()),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/integrations/bans",
+    tag = "ops",
+    summary = "Recorded bans",
+    responses(
+        (status = 200, description = "Bans")
+    )
+)]
+pub async fn list_bans(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PlayerBan>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(ban_queries::list_bans(&state).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/quarantine",
+    tag = "ops",
+    summary = "Quarantined players",
+    responses(
+        (status = 200, description = "Quarantined players, newest first")
+    )
+)]
+pub async fn list_quarantines(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PlayerQuarantine>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(quarantine_queries::list_quarantines(&state).await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/ops/quarantine",
+    tag = "ops",
+    summary = "Quarantine a player",
+    responses(
+        (status = 200, description = "Quarantined"),
+        (status = 400, description = "Neither player_uuid nor player_name given")
+    )
+)]
+pub async fn quarantine_player(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<QuarantineRequest>,
+) -> Result<Json<PlayerQuarantine>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    let actor = request_actor(&headers);
+    let entry = quarantine_commands::quarantine_player(&state, payload, &actor).await?;
+    Ok(Json(entry))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v2/ops/quarantine/{player}",
+    tag = "ops",
+    summary = "Release a quarantined player",
+    params(("player" = String, Path, description = "Player UUID or name")),
+    responses(
+        (status = 204, description = "Released"),
+        (status = 404, description = "Player is not quarantined")
+    )
+)]
+pub async fn release_player(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(player): Path<String>,
+) -> Result<StatusCode, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    let actor = request_actor(&headers);
+    if quarantine_commands::release_player(&state, &player, &actor).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(HttpError::NotFound)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/jobs",
+    tag = "ops",
+    summary = "Background jobs",
+    responses(
+        (status = 200, description = "Jobs, newest first")
+    )
+)]
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Job>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(job_queries::list_jobs(&state).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/jobs/{id}",
+    tag = "ops",
+    summary = "One background job",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "The job"),
+        (status = 404, description = "Unknown or forgotten job")
+    )
+)]
+pub async fn get_job(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let job = job_queries::get_job(&state, &id)
+        .await
+        .ok_or(HttpError::NotFound)?;
+    Ok(Json(job))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/ops/jobs/ml-export",
+    tag = "ops",
+    summary = "Queue an anonymized ML training export",
+    responses(
+        (status = 202, description = "Queued job"),
+        (status = 400, description = "Invalid date range, sampling ratio or format")
+    )
+)]
+pub async fn submit_ml_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<MlExportRequest>,
+) -> Result<(StatusCode, Json<Job>), HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    let actor = request_actor(&headers);
+    let job = ml_export_commands::submit_ml_export(&state, payload, &actor).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/jobs/{id}/download",
+    tag = "ops",
+    summary = "Download the file of a finished export job",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "CSV attachment", content_type = "text/csv"),
+        (status = 404, description = "Unknown, unfinished or failed job")
+    )
+)]
+pub async fn download_job_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    let (name, csv) = job_queries::read_job_export(&state, &id)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", name),
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/player-teams",
+    tag = "ops",
+    summary = "Player team assignments",
+    responses(
+        (status = 200, description = "Teams")
+    )
+)]
+pub async fn list_player_teams(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PlayerTeam>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(player_team_queries::list_player_teams(&state).await))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v2/ops/player-teams",
+    tag = "ops",
+    summary = "Replace player team assignments",
+    responses(
+        (status = 204, description = "Saved")
+    )
+)]
+pub async fn update_player_teams(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<PlayerTeamsPayload>,
+) -> Result<StatusCode, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    player_team_commands::update_player_teams(&state, payload.players).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/api-tokens",
+    tag = "ops",
+    summary = "Issued API tokens, without their secrets",
+    responses(
+        (status = 200, description = "Tokens", body = [ApiTokenView])
+    )
+)]
+pub async fn list_api_tokens(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ApiTokenView>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    Ok(Json(api_token_queries::list_api_tokens(&state).await))
+}
+
+/// Answers the new token's secret; it is not shown again.
+#[utoipa::path(
+    post,
+    path = "/v2/ops/api-tokens",
+    tag = "ops",
+    summary = "Issue a named API token with a scope",
+    request_body = ApiTokenIssueRequest,
+    responses(
+        (status = 200, description = "The token and its secret", body = ApiTokenIssued)
+    )
+)]
+pub async fn issue_api_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<ApiTokenIssueRequest>,
+) -> Result<Json<ApiTokenIssued>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    let issued = api_token_commands::issue_api_token(&state, payload, &actor).await?;
+    Ok(Json(issued))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v2/ops/api-tokens/{name}",
+    tag = "ops",
+    summary = "Revoke an API token",
+    params(("name" = String, Path, description = "Token name")),
+    responses(
+        (status = 204, description = "Revoked"),
+        (status = 404, description = "No active token with that name")
+    )
+)]
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Result<StatusCode, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let actor = request_actor(&headers);
+    if api_token_commands::revoke_api_token(&state, &name, &actor).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(HttpError::NotFound)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/reports",
+    tag = "ops",
+    summary = "Generated daily reports",
+    responses(
+        (status = 200, description = "Reports")
+    )
+)]
+pub async fn list_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ReportFile>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(report_queries::list_reports(&state).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/ops/reports/{date}",
+    tag = "ops",
+    summary = "Generate the report of one day",
+    params(("date" = String, Path, description = "Local day, `YYYY-MM-DD`")),
+    responses(
+        (status = 200, description = "The report file")
+    )
+)]
+pub async fn generate_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(date): Path<String>,
+) -> Result<Json<ReportFile>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    Ok(Json(report_commands::generate_report(&state, &date).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v2/ops/reports/{date}",
+    tag = "ops",
+    summary = "Delete the report of one day",
+    params(("date" = String, Path, description = "Local day, `YYYY-MM-DD`")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No report for that day")
+    )
+)]
+pub async fn delete_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(date): Path<String>,
+) -> Result<StatusCode, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    if report_commands::delete_report(&state, &date).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(HttpError::NotFound)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/strictness",
+    tag = "ops",
+    summary = "Detection strictness in effect",
+    responses(
+        (status = 200, description = "Strictness")
+    )
+)]
+pub async fn get_strictness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<StrictnessStatus>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(config_queries::current_strictness(&state)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v2/ops/selftest",
+    tag = "ops",
+    summary = "Run the end-to-end self test",
+    responses(
+        (status = 200, description = "Self test report")
+    )
+)]
+pub async fn selftest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SelftestReport>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    Ok(Json(selftest_commands::run_selftest(&state).await))
+}
+
+/// Body: an ingest recording as written to `ingest_record_path` (JSON lines).
+#[utoipa::path(
+    post,
+    path = "/v2/ops/replay",
+    tag = "ops",
+    summary = "Replay an ingest recording through a fresh analyzer",
+    responses(
+        (status = 200, description = "Anomalies the recording raises")
+    )
+)]
+pub async fn replay(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<ReplayReport>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    Ok(Json(replay_commands::replay(&state, &body).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/clickhouse/preflight",
+    tag = "ops",
+    summary = "Check ClickHouse permissions",
+    responses(
+        (status = 200, description = "Permission checks")
+    )
+)]
+pub async fn clickhouse_preflight(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ClickhousePreflight>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(preflight_queries::clickhouse_preflight(&state).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/ops/health/live",
+    tag = "ops",
+    summary = "Liveness probe",
+    security(()),
+    responses(
+        (status = 200, description = "The process is up")
+    )
+)]
 pub async fn health_live() -> StatusCode {
     StatusCode::OK
 }
 
-pub async fn health_ready(State(state): State<AppState>) -> StatusCode {
-    let timeout_secs = state.config.request_timeout_seconds.max(1);
-    let timeout_duration = Duration::from_secs(timeout_secs);
-    match timeout(timeout_duration, state.event_repo.ping()).await {
-        Ok(Ok(_)) => StatusCode::OK,
-        Ok(Err(err)) => {
-            error!("ready check failed: {}", err);
-            StatusCode::SERVICE_UNAVAILABLE
-        }
-        Err(_) => {
-            error!("ready check timeout after {}s", timeout_secs);
-            StatusCode::SERVICE_UNAVAILABLE
-        }
+/// `degraded` still answers 200 so load balancers keep routing ingest into the dead-letter queue.
+#[utoipa::path(
+    get,
+    path = "/v2/ops/health/ready",
+    tag = "ops",
+    summary = "Readiness probe",
+    security(()),
+    responses(
+        (status = 200, description = "Ready or degraded"),
+        (status = 503, description = "ClickHouse is down")
+    )
+)]
+pub async fn health_ready(State(state): State<AppState>) -> (StatusCode, Json<ReadyStatus>) {
+    let status = dead_letter_commands::check_readiness(&state).await;
+    if status.status == "down" {
+        error!("ready check failed: {:?}", status.last_error);
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(status));
     }
+    (StatusCode::OK, Json(status))
 }
 
+#[utoipa::path(
+    get,
+    path = "/v2/ops/metrics/prometheus",
+    tag = "ops",
+    summary = "Prometheus metrics",
+    responses(
+        (status = 200, description = "Prometheus text format")
+    )
+)]
 pub async fn metrics_prometheus(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if !authorize(&state.config, &headers) {
-        return (StatusCode::UNAUTHORIZED, "unauthorized".to_string()).into_response();
+    match authorize(&state, &headers, ApiTokenScope::Read).await {
+        Ok(()) => {}
+        Err(HttpError::Unauthorized) => {
+            return (StatusCode::UNAUTHORIZED, "unauthorized".to_string()).into_response();
+        }
+        Err(err) => return err.into_response(),
     }
-    let payload = state.metrics.render_prometheus();
+    let alerts = state.alert_service.alert_delivery_totals().await;
+    let payload = state.metrics.render_prometheus(alerts);
     let mut headers = HeaderMap::new();
     headers.insert(
         header::CONTENT_TYPE,
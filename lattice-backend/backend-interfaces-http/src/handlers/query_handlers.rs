@@ -1,36 +1,215 @@
-use axum::extract::{Query, State};
-use axum::http::{HeaderMap, StatusCode};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 
 use backend_application::commands::item_registry_commands;
-use backend_application::queries::item_registry_queries;
+use backend_application::queries::{
+    event_queries, item_registry_queries, item_trace_queries, player_profile_queries,
+    report_queries,
+};
 use backend_application::AppState;
-use backend_domain::{ItemRegistryEntry, ItemRegistryPayload, ItemRegistryQuery, ItemRegistryUpdateQuery};
+use backend_domain::{
+    current_millis, ApiTokenScope, ItemEventQuery, ItemRegistryDeleteQuery,
+    ItemRegistryDeleteResult, ItemRegistryPayload, ItemRegistryQuery, ItemRegistryUpdateQuery,
+    ItemTrace, ItemTraceQuery, PagedResult, PlayerProfile, PlayerProfileQuery, ReportArchiveEntry,
+    ITEM_EVENT_FIELDS,
+};
 
 use crate::error::HttpError;
-use crate::middleware::authorize;
+use crate::handlers::detect_handlers::{project_page, FieldsQuery};
+use crate::middleware::{authorize, authorize_admin, json_with_etag};
 
+#[utoipa::path(
+    get,
+    path = "/v2/query/events",
+    tag = "query",
+    summary = "Raw item events of one day",
+    params(ItemEventQuery, FieldsQuery),
+    responses(
+        (status = 200, description = "A page of events")
+    )
+)]
+pub async fn query_item_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ItemEventQuery>,
+    Query(select): Query<FieldsQuery>,
+) -> Result<Json<PagedResult<serde_json::Value>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let fields = select.selection(&ITEM_EVENT_FIELDS)?;
+    let rows = event_queries::query_item_events(&state, query, &fields).await?;
+    Ok(Json(project_page(rows, &fields)?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/query/players/{uuid}/profile",
+    tag = "query",
+    summary = "Aggregates and recent events of one player",
+    params(("uuid" = String, Path, description = "Player UUID")),
+    responses(
+        (status = 200, description = "The profile")
+    )
+)]
+pub async fn player_profile(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(player_uuid): Path<String>,
+    Query(query): Query<PlayerProfileQuery>,
+) -> Result<Json<PlayerProfile>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    let profile = player_profile_queries::player_profile(&state, &player_uuid, query)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok(Json(profile))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/query/item-trace",
+    tag = "query",
+    summary = "Transfers and acquisitions of one item as a graph",
+    params(ItemTraceQuery),
+    responses(
+        (status = 200, description = "Nodes and hops, oldest first", body = ItemTrace)
+    )
+)]
+pub async fn item_trace(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ItemTraceQuery>,
+) -> Result<Json<ItemTrace>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(item_trace_queries::item_trace(&state, query).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/query/reports",
+    tag = "query",
+    summary = "Generated daily reports with links and anomaly counts",
+    responses(
+        (status = 200, description = "Reports, newest first")
+    )
+)]
+pub async fn list_report_archive(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ReportArchiveEntry>>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
+    Ok(Json(report_queries::list_report_archive(&state).await?))
+}
+
+/// Serves a report, rule hygiene report or player page from `report_dir`. A signed link needs
+/// no token; anything else is authorized like `GET /v2/query/reports`.
+#[utoipa::path(
+    get,
+    path = "/reports/{path}",
+    tag = "query",
+    summary = "A generated report page",
+    params(("path" = String, Path, description = "`<date>`, `rule-hygiene-<month>` or `<date>/players/<file>.html`, optionally behind a signed `s/<expires>/<signature>/` prefix")),
+    responses(
+        (status = 200, description = "The HTML page"),
+        (status = 401, description = "No token and no valid, unexpired signature"),
+        (status = 404, description = "No such report")
+    )
+)]
+pub async fn serve_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+) -> Result<Response, HttpError> {
+    let (page, signature) = report_queries::parse_report_path(&path).ok_or(HttpError::NotFound)?;
+    match signature {
+        Some(signature) => {
+            let valid = report_queries::verify_report_signature(
+                &state.config,
+                &page.report,
+                &signature,
+                current_millis(),
+            );
+            if !valid {
+                return Err(HttpError::Unauthorized);
+            }
+        }
+        None => authorize(&state, &headers, ApiTokenScope::Read).await?,
+    }
+    let html = report_queries::read_report_page(&state, &page)
+        .await?
+        .ok_or(HttpError::NotFound)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-cache"),
+            // Keeps a signed link out of the Referer of links followed from the page.
+            (header::REFERRER_POLICY, "no-referrer"),
+        ],
+        html,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v2/query/item-registry",
+    tag = "query",
+    summary = "Search the item registry",
+    params(ItemRegistryQuery),
+    responses(
+        (status = 200, description = "Matching entries", body = Vec<backend_domain::ItemRegistryEntry>)
+    )
+)]
 pub async fn list_item_registry(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ItemRegistryQuery>,
-) -> Result<Json<Vec<ItemRegistryEntry>>, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+) -> Result<Response, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Read).await?;
     let results = item_registry_queries::list_item_registry(&state, query).await?;
-    Ok(Json(results))
+    json_with_etag(&headers, &results)
 }
 
+#[utoipa::path(
+    put,
+    path = "/v2/query/item-registry",
+    tag = "query",
+    summary = "Replace or merge registry entries",
+    params(ItemRegistryUpdateQuery),
+    responses(
+        (status = 204, description = "Saved")
+    )
+)]
 pub async fn update_item_registry(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(query): Query<ItemRegistryUpdateQuery>,
     Json(payload): Json<ItemRegistryPayload>,
 ) -> Result<StatusCode, HttpError> {
-    if !authorize(&state.config, &headers) {
-        return Err(HttpError::Unauthorized);
-    }
+    authorize(&state, &headers, ApiTokenScope::Ingest).await?;
     item_registry_commands::update_item_registry(&state, query, payload).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    delete,
+    path = "/v2/query/item-registry",
+    tag = "query",
+    summary = "Remove registry entries matching a filter",
+    params(ItemRegistryDeleteQuery),
+    responses(
+        (status = 200, description = "Removed entries")
+    )
+)]
+pub async fn delete_item_registry(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ItemRegistryDeleteQuery>,
+) -> Result<Json<ItemRegistryDeleteResult>, HttpError> {
+    authorize(&state, &headers, ApiTokenScope::Admin).await?;
+    if !authorize_admin(state.admin_secret.as_ref(), &headers) {
+        return Err(HttpError::Forbidden("admin secret required".to_string()));
+    }
+    let result = item_registry_commands::delete_item_registry(&state, query).await?;
+    Ok(Json(result))
+}
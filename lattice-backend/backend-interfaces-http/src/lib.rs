@@ -1,9 +1,13 @@
 pub mod error;
+pub mod export;
 pub mod handlers;
 pub mod middleware;
+pub mod openapi;
 pub mod routes;
 
 pub use error::*;
+pub use export::*;
 pub use handlers::*;
 pub use middleware::*;
+pub use openapi::*;
 pub use routes::*;
@@ -1,4 +1,12 @@
 pub mod auth;
+pub mod envelope;
+pub mod etag;
 pub mod logging;
+pub mod rate_limit;
+pub mod replay;
 
 pub use auth::*;
+pub use envelope::*;
+pub use etag::*;
+pub use rate_limit::*;
+pub use replay::*;
@@ -1,36 +1,127 @@
 use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
 
 use anyhow::{anyhow, Result};
 use axum::http::HeaderMap;
 use flate2::read::GzDecoder;
 
-use backend_domain::{IngestEnvelope, IngestEvent, RuntimeConfig};
+use backend_application::ops::AdminSecret;
+use backend_application::AppState;
+use backend_domain::{current_millis, ApiTokenScope, IngestEnvelope, IngestEvent};
 
-pub fn authorize(config: &RuntimeConfig, headers: &HeaderMap) -> bool {
-    if let Some(api_token) = &config.api_token {
-        return extract_bearer(headers)
-            .map(|v| v == *api_token)
-            .unwrap_or(false);
-    }
-    true
+use crate::error::HttpError;
+
+/// Checks the bearer token against `api_token` and the issued API tokens: `401` without a valid
+/// token, `403` when the token's scope does not cover `scope`.
+pub async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    scope: ApiTokenScope,
+) -> Result<(), HttpError> {
+    state
+        .api_tokens
+        .check(
+            state.config.api_token.as_deref(),
+            extract_bearer(headers).as_deref(),
+            scope,
+            current_millis(),
+        )
+        .await
+        .map_err(HttpError::from)
 }
 
-pub fn parse_events(headers: &HeaderMap, body: &[u8]) -> Result<Vec<IngestEvent>> {
-    let content = maybe_gunzip(headers, body)?;
-    let mut envelope: IngestEnvelope = serde_json::from_str(&content)?;
-    if envelope.schema_version.trim() != "v2" {
-        return Err(anyhow!(
-            "unsupported schema_version '{}', expected 'v2'",
-            envelope.schema_version
-        ));
+/// Header carrying the embedded backend's admin secret, see `AdminSecret`.
+pub const ADMIN_SECRET_HEADER: &str = "X-Lattice-Admin-Secret";
+
+/// Second check of config-mutating endpoints: with an admin secret (embedded backend) the
+/// request must present it; a standalone backend has none and relies on the API token alone.
+pub fn authorize_admin(admin_secret: Option<&AdminSecret>, headers: &HeaderMap) -> bool {
+    let Some(secret) = admin_secret else {
+        return true;
+    };
+    headers
+        .get(ADMIN_SECRET_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| secret.matches(value))
+}
+
+/// Entries of `trusted_proxies` that are IP addresses; the config rejects the others.
+pub fn trusted_proxy_ips(trusted_proxies: &[String]) -> Vec<IpAddr> {
+    trusted_proxies
+        .iter()
+        .filter_map(|proxy| proxy.parse().ok())
+        .collect()
+}
+
+/// The address a request came from: the peer, or when the peer is a trusted proxy, the nearest
+/// `X-Forwarded-For` hop that is not one. Hops a client wrote itself sit further left and are
+/// never reached.
+pub fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut client = peer?;
+    if !trusted_proxies.contains(&client) {
+        return Some(client);
     }
-    let inherited_server_id = envelope.server_id.clone();
-    for event in &mut envelope.events {
-        if event.server_id.is_none() {
-            event.server_id = inherited_server_id.clone();
+    let hops: Vec<&str> = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    for hop in hops.into_iter().rev() {
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !trusted_proxies.contains(&ip) {
+            break;
         }
     }
-    Ok(envelope.events)
+    Some(client)
+}
+
+/// Identifies the caller for diagnostics and security alerts by its `client_ip`.
+pub fn request_source(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trusted_proxies: &[IpAddr],
+) -> String {
+    client_ip(headers, peer.map(|addr| addr.ip()), trusted_proxies)
+        .map_or_else(|| "unknown".to_string(), |ip| ip.to_string())
+}
+
+/// Who made a change, for config change notifications: `X-Lattice-Actor` (e.g. `desktop`), else
+/// the first `X-Forwarded-For` hop. Both are the caller's own word, fine for a label but not for
+/// anything keyed or alerted on, which goes by `request_source`.
+pub fn request_actor(headers: &HeaderMap) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    header("X-Lattice-Actor")
+        .or_else(|| {
+            header("X-Forwarded-For")
+                .and_then(|value| value.split(',').next())
+                .map(str::trim)
+        })
+        .filter(|value| !value.is_empty())
+        .map_or_else(
+            || "unknown".to_string(),
+            |value| value.chars().take(64).collect(),
+        )
+}
+
+pub fn parse_events(headers: &HeaderMap, body: &[u8]) -> Result<Vec<IngestEvent>> {
+    let content = maybe_gunzip(headers, body)?;
+    let envelope: IngestEnvelope = serde_json::from_str(&content)?;
+    envelope.into_events().map_err(|err| anyhow!(err))
 }
 
 fn maybe_gunzip(headers: &HeaderMap, body: &[u8]) -> Result<String> {
@@ -45,7 +136,7 @@ fn maybe_gunzip(headers: &HeaderMap, body: &[u8]) -> Result<String> {
     Ok(String::from_utf8(body.to_vec())?)
 }
 
-fn extract_bearer(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn extract_bearer(headers: &HeaderMap) -> Option<String> {
     let value = headers.get("Authorization")?.to_str().ok()?.trim();
     let prefix = "Bearer ";
     if !value.starts_with(prefix) {
@@ -0,0 +1,109 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+
+use backend_domain::{ApiEnvelope, API_VERSION};
+
+use crate::error::HttpError;
+
+/// Media type that opts a detect/query request into the versioned `ApiEnvelope` body.
+pub const ENVELOPE_MEDIA_TYPE: &str = "application/vnd.lattice.v2.1+json";
+
+const ENVELOPED_PREFIXES: [&str; 2] = ["/v2/detect/", "/v2/query/"];
+
+pub fn wants_envelope(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|entry| entry.split(';').next())
+        .any(|media_type| media_type.trim().eq_ignore_ascii_case(ENVELOPE_MEDIA_TYPE))
+}
+
+/// Wraps successful JSON detect/query responses in `{ "api_version", "data" }` when the client
+/// asks for it; everything else passes through unchanged, so existing clients keep the bare shape.
+pub async fn version_envelope(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let negotiated = ENVELOPED_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix));
+    let enveloped = negotiated && wants_envelope(request.headers());
+    let mut response = next.run(request).await;
+    if negotiated {
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+    }
+    if !enveloped {
+        return response;
+    }
+    match wrap_response(response).await {
+        Ok(response) => response,
+        Err(err) => err.into_response(),
+    }
+}
+
+async fn wrap_response(response: Response) -> Result<Response, HttpError> {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !response.status().is_success() || !is_json {
+        return Ok(response);
+    }
+    let (mut parts, body) = response.into_parts();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|err| HttpError::Internal(err.to_string()))?;
+    let data: Value =
+        serde_json::from_slice(&bytes).map_err(|err| HttpError::Internal(err.to_string()))?;
+    let envelope = ApiEnvelope {
+        api_version: API_VERSION.to_string(),
+        data,
+    };
+    let body = serde_json::to_vec(&envelope).map_err(|err| HttpError::Internal(err.to_string()))?;
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(ENVELOPE_MEDIA_TYPE),
+    );
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+
+    #[tokio::test]
+    async fn envelope_wraps_json_only_when_accepted() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_envelope(&headers));
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/json, application/vnd.lattice.v2.1+json;q=0.9"),
+        );
+        assert!(wants_envelope(&headers));
+
+        let response = wrap_response(Json(vec!["minecraft:diamond"]).into_response())
+            .await
+            .expect("wrapped");
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            ENVELOPE_MEDIA_TYPE
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        let value: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(
+            value,
+            serde_json::json!({ "api_version": "2.1", "data": ["minecraft:diamond"] })
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::HttpError;
+
+/// Serializes `value` as JSON with a content-hash ETag and answers `304` when the client already has it.
+pub fn json_with_etag<T: Serialize>(
+    request_headers: &HeaderMap,
+    value: &T,
+) -> Result<Response, HttpError> {
+    let body = serde_json::to_vec(value).map_err(|err| HttpError::Internal(err.to_string()))?;
+    let etag = compute_etag(&body);
+    let etag_value =
+        HeaderValue::from_str(&etag).map_err(|err| HttpError::Internal(err.to_string()))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ETAG, etag_value);
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    if if_none_match(request_headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+    }
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    Ok((StatusCode::OK, headers, body).into_response())
+}
+
+fn compute_etag(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let mut out = String::with_capacity(34);
+    out.push('"');
+    for byte in digest.iter().take(16) {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out.push('"');
+    out
+}
+
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| {
+            candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_if_none_match_returns_not_modified() {
+        let value = vec!["minecraft:diamond"];
+        let first = json_with_etag(&HeaderMap::new(), &value).expect("response");
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers().get(header::ETAG).cloned().expect("etag");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag);
+        let second = json_with_etag(&headers, &value).expect("response");
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+
+        let mut stale = HeaderMap::new();
+        stale.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"deadbeef\""),
+        );
+        let third = json_with_etag(&stale, &value).expect("response");
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+}
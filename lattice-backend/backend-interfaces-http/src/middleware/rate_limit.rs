@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use backend_application::AppState;
+use backend_domain::current_millis;
+
+use crate::error::HttpError;
+use crate::middleware::{client_ip, extract_bearer, trusted_proxy_ips};
+
+/// Past this many buckets, full ones are dropped, since a bucket that refilled is the same as
+/// none; if that is not enough, the least recently used go too.
+const MAX_BUCKETS: usize = 4096;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_ms: i64,
+}
+
+/// Token buckets for `/v2/ingest/events`, one per source address and one per authenticated API
+/// token, each refilling at `per_second` up to `burst`. A request needs a token from every bucket
+/// it falls in, so a mod cannot dodge the limit by rotating either.
+#[derive(Debug)]
+pub struct IngestRateLimiter {
+    per_second: f64,
+    burst: f64,
+    trusted_proxies: Vec<IpAddr>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+/// Which bucket ran out, and in how many whole seconds it holds a token again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttled {
+    pub by_token: bool,
+    pub retry_after_seconds: u64,
+}
+
+impl IngestRateLimiter {
+    /// `per_second` of 0 lets every request through. Entries of `trusted_proxies` that are not
+    /// IP addresses are ignored; the config rejects them.
+    pub fn new(per_second: f64, burst: u64, trusted_proxies: &[String]) -> Self {
+        Self {
+            per_second,
+            burst: burst.max(1) as f64,
+            trusted_proxies: trusted_proxy_ips(trusted_proxies),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The address a request is limited by, see `client_ip`.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        client_ip(headers, peer, &self.trusted_proxies)
+    }
+
+    /// Takes a token for `source` and, when given, `token`; nothing is taken when either is out.
+    /// Only pass a `token` that has authenticated, or every made-up bearer gets a bucket.
+    pub fn acquire(&self, source: &str, token: Option<&str>, now_ms: i64) -> Result<(), Throttled> {
+        if self.per_second <= 0.0 {
+            return Ok(());
+        }
+        let mut keys = vec![(format!("source:{}", source), false)];
+        if let Some(token) = token {
+            keys.push((format!("token:{}", token_key(token)), true));
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        if buckets.len() > MAX_BUCKETS {
+            buckets.retain(|_, bucket| self.refilled(*bucket, now_ms) < self.burst);
+        }
+        if buckets.len() > MAX_BUCKETS {
+            let mut by_age: Vec<(i64, String)> = buckets
+                .iter()
+                .map(|(key, bucket)| (bucket.updated_ms, key.clone()))
+                .collect();
+            by_age.sort_unstable();
+            let excess = buckets.len() - MAX_BUCKETS;
+            for (_, key) in by_age.into_iter().take(excess) {
+                buckets.remove(&key);
+            }
+        }
+        for (key, by_token) in &keys {
+            let tokens = buckets
+                .get(key)
+                .map_or(self.burst, |bucket| self.refilled(*bucket, now_ms));
+            if tokens < 1.0 {
+                return Err(Throttled {
+                    by_token: *by_token,
+                    retry_after_seconds: ((1.0 - tokens) / self.per_second).ceil().max(1.0) as u64,
+                });
+            }
+        }
+        for (key, _) in keys {
+            let tokens = buckets
+                .get(&key)
+                .map_or(self.burst, |bucket| self.refilled(*bucket, now_ms));
+            buckets.insert(
+                key,
+                Bucket {
+                    tokens: tokens - 1.0,
+                    updated_ms: now_ms,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn refilled(&self, bucket: Bucket, now_ms: i64) -> f64 {
+        let elapsed_seconds = (now_ms - bucket.updated_ms).max(0) as f64 / 1000.0;
+        (bucket.tokens + elapsed_seconds * self.per_second).min(self.burst)
+    }
+}
+
+/// Buckets are keyed by a digest, so the limiter holds no usable token.
+fn token_key(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Answers `429` with `Retry-After` once the caller's source or token bucket is empty, before
+/// the batch is read. A bearer that does not authenticate only counts against its source.
+pub async fn ingest_rate_limit(
+    State((state, limiter)): State<(AppState, Arc<IngestRateLimiter>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let now_ms = current_millis();
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let source = limiter
+        .client_ip(request.headers(), peer)
+        .map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+    let mut token = extract_bearer(request.headers());
+    if let Some(presented) = token.as_deref() {
+        let authenticated = state
+            .api_tokens
+            .authenticates(state.config.api_token.as_deref(), presented, now_ms)
+            .await;
+        if !authenticated {
+            token = None;
+        }
+    }
+    if let Err(throttled) = limiter.acquire(&source, token.as_deref(), now_ms) {
+        state.metrics.record_ingest_throttled(throttled.by_token);
+        warn!(
+            "ingest throttled: source={} by_token={} retry_after={}s",
+            source, throttled.by_token, throttled.retry_after_seconds
+        );
+        return HttpError::RateLimited(throttled.retry_after_seconds).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, StatusCode};
+
+    #[test]
+    fn buckets_refill_and_limit_sources_and_tokens_separately() {
+        let limiter = IngestRateLimiter::new(2.0, 3, &[]);
+        for _ in 0..3 {
+            assert!(limiter.acquire("10.0.0.1", Some("mod-a"), 0).is_ok());
+        }
+        assert_eq!(
+            limiter.acquire("10.0.0.1", None, 0),
+            Err(Throttled {
+                by_token: false,
+                retry_after_seconds: 1
+            })
+        );
+        // Another address with the same token still runs into the token's bucket.
+        assert_eq!(
+            limiter
+                .acquire("10.0.0.2", Some("mod-a"), 0)
+                .map_err(|t| t.by_token),
+            Err(true)
+        );
+        assert!(limiter.acquire("10.0.0.2", Some("mod-b"), 0).is_ok());
+        // Half a second refills one token at 2 per second.
+        assert!(limiter.acquire("10.0.0.1", Some("mod-a"), 500).is_ok());
+        assert!(limiter.acquire("10.0.0.1", Some("mod-a"), 500).is_err());
+
+        let off = IngestRateLimiter::new(0.0, 1, &[]);
+        for _ in 0..10 {
+            assert!(off.acquire("10.0.0.1", None, 0).is_ok());
+        }
+    }
+
+    #[test]
+    fn forwarded_for_counts_only_behind_a_trusted_proxy() {
+        let limiter = IngestRateLimiter::new(1.0, 1, &["10.0.0.9".to_string()]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-For",
+            "6.6.6.6, 203.0.113.7, 10.0.0.9".parse().unwrap(),
+        );
+        let ip = |addr: &str| addr.parse::<IpAddr>().ok();
+        // A client talking to us directly cannot pick its bucket.
+        assert_eq!(
+            limiter.client_ip(&headers, ip("198.51.100.1")),
+            ip("198.51.100.1")
+        );
+        // Behind the proxy, the first untrusted hop from the right is the client; the spoofed
+        // leftmost hop is never reached.
+        assert_eq!(
+            limiter.client_ip(&headers, ip("10.0.0.9")),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            limiter.client_ip(&HeaderMap::new(), ip("10.0.0.9")),
+            ip("10.0.0.9")
+        );
+        assert_eq!(limiter.client_ip(&headers, None), None);
+    }
+
+    #[test]
+    fn bucket_count_stays_bounded() {
+        let limiter = IngestRateLimiter::new(0.001, 5, &[]);
+        for i in 0..(MAX_BUCKETS + 100) {
+            assert!(limiter
+                .acquire(&format!("source-{}", i), None, i as i64)
+                .is_ok());
+        }
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.len() <= MAX_BUCKETS + 1);
+        assert!(buckets.contains_key(&format!("source:source-{}", MAX_BUCKETS + 99)));
+        assert!(!buckets.contains_key("source:source-0"));
+    }
+
+    #[test]
+    fn throttled_requests_get_retry_after() {
+        let response = HttpError::RateLimited(3).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+    }
+}
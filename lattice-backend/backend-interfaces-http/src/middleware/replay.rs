@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, Method};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use tracing::warn;
+
+use backend_application::{AppState, ErrorCode};
+use backend_domain::current_millis;
+pub use backend_domain::{REPLAY_NONCE_HEADER, REPLAY_TIMESTAMP_HEADER};
+
+use crate::error::HttpError;
+use crate::middleware::auth::extract_bearer;
+
+/// Callbacks of third-party systems, which cannot send the replay headers: the ban sync webhook
+/// and NapCat group events. Both still authenticate as usual.
+pub const REPLAY_EXEMPT_PATHS: [&str; 2] = [
+    "/v2/ops/integrations/ban-events",
+    "/v2/ops/napcat/group-event",
+];
+
+/// Past this many live nonces, mutating requests are turned away until some expire, so a flood
+/// of fresh nonces cannot grow memory or evict the nonces a replay would reuse. Only
+/// authenticated requests record nonces, so the flood has to come from a token holder.
+const MAX_NONCES: usize = 100_000;
+
+/// Why a mutating request was taken for a replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayRejection {
+    Missing,
+    InvalidNonce,
+    InvalidTimestamp,
+    Expired,
+    Reused,
+    Overloaded,
+}
+
+impl ReplayRejection {
+    pub fn message(self) -> &'static str {
+        match self {
+            ReplayRejection::Missing => "X-Lattice-Timestamp and X-Lattice-Nonce are required",
+            ReplayRejection::InvalidNonce => {
+                "X-Lattice-Nonce must be 16-128 visible ASCII characters"
+            }
+            ReplayRejection::InvalidTimestamp => "X-Lattice-Timestamp must be unix milliseconds",
+            ReplayRejection::Expired => "X-Lattice-Timestamp is outside the replay window",
+            ReplayRejection::Reused => "X-Lattice-Nonce was already used",
+            ReplayRejection::Overloaded => "too many requests in the replay window",
+        }
+    }
+}
+
+/// Nonces of accepted mutating requests, each kept until its timestamp leaves the window and
+/// the request would be refused as expired anyway.
+#[derive(Debug)]
+pub struct ReplayGuard {
+    window_ms: i64,
+    capacity: usize,
+    nonces: Mutex<HashMap<String, i64>>,
+}
+
+impl ReplayGuard {
+    /// `window_seconds` of 0 lets every request through.
+    pub fn new(window_seconds: u64) -> Self {
+        Self::with_capacity(window_seconds, MAX_NONCES)
+    }
+
+    fn with_capacity(window_seconds: u64, capacity: usize) -> Self {
+        Self {
+            window_ms: window_seconds.saturating_mul(1000) as i64,
+            capacity,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.window_ms > 0
+    }
+
+    /// Accepts a timestamp at most the window away from `now_ms` with a nonce not seen in it.
+    pub fn check(
+        &self,
+        timestamp: Option<&str>,
+        nonce: Option<&str>,
+        now_ms: i64,
+    ) -> Result<(), ReplayRejection> {
+        let (Some(timestamp), Some(nonce)) = (timestamp, nonce) else {
+            return Err(ReplayRejection::Missing);
+        };
+        if !(16..=128).contains(&nonce.len()) || !nonce.bytes().all(|byte| byte.is_ascii_graphic())
+        {
+            return Err(ReplayRejection::InvalidNonce);
+        }
+        let timestamp_ms: i64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| ReplayRejection::InvalidTimestamp)?;
+        if now_ms.abs_diff(timestamp_ms) > self.window_ms as u64 {
+            return Err(ReplayRejection::Expired);
+        }
+        let mut nonces = self.nonces.lock().unwrap_or_else(|err| err.into_inner());
+        if nonces.len() >= self.capacity {
+            nonces.retain(|_, expires_ms| *expires_ms >= now_ms);
+            if nonces.len() >= self.capacity {
+                return Err(ReplayRejection::Overloaded);
+            }
+        }
+        if nonces
+            .get(nonce)
+            .is_some_and(|expires_ms| *expires_ms >= now_ms)
+        {
+            return Err(ReplayRejection::Reused);
+        }
+        nonces.insert(nonce.to_string(), timestamp_ms + self.window_ms);
+        Ok(())
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Checks the replay headers of a request that authenticates: it presents `api_token` or an
+/// active issued token, or the API is open. Other requests are let through unchecked for the
+/// handler to answer `401`, so they never spend a slot of the nonce table.
+async fn admit(
+    state: &AppState,
+    guard: &ReplayGuard,
+    headers: &HeaderMap,
+    now_ms: i64,
+) -> Result<(), ReplayRejection> {
+    let api_token = state.config.api_token.as_deref();
+    let authenticated = match extract_bearer(headers) {
+        Some(presented) => {
+            state
+                .api_tokens
+                .authenticates(api_token, &presented, now_ms)
+                .await
+        }
+        None => false,
+    };
+    if !authenticated && !state.api_tokens.is_open(api_token).await {
+        return Ok(());
+    }
+    guard.check(
+        header(headers, REPLAY_TIMESTAMP_HEADER),
+        header(headers, REPLAY_NONCE_HEADER),
+        now_ms,
+    )
+}
+
+/// Answers `400` `REPLAY_REJECTED` to an authenticated mutating request whose timestamp is stale
+/// or whose nonce was used before; reads and `REPLAY_EXEMPT_PATHS` pass through.
+pub async fn replay_protection(
+    State((state, guard)): State<(AppState, Arc<ReplayGuard>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let safe = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    let exempt = REPLAY_EXEMPT_PATHS.contains(&request.uri().path());
+    if safe || exempt || !guard.enabled() {
+        return next.run(request).await;
+    }
+    let checked = admit(&state, &guard, request.headers(), current_millis()).await;
+    if let Err(rejection) = checked {
+        warn!(
+            "request rejected as a possible replay: {} {}: {}",
+            request.method(),
+            request.uri().path(),
+            rejection.message()
+        );
+        return HttpError::Invalid(ErrorCode::ReplayRejected, rejection.message().to_string())
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use backend_application::testing::InMemoryApp;
+
+    const NONCE: &str = "0f8c2d4e-6a1b-4c3d";
+
+    #[test]
+    fn stale_timestamps_and_reused_nonces_are_rejected() {
+        let guard = ReplayGuard::new(300);
+        let now = 1_700_000_000_000;
+        let sent = now.to_string();
+        assert_eq!(
+            guard.check(None, Some(NONCE), now),
+            Err(ReplayRejection::Missing)
+        );
+        assert_eq!(
+            guard.check(Some(&sent), Some("short"), now),
+            Err(ReplayRejection::InvalidNonce)
+        );
+        let stale = (now - 301_000).to_string();
+        assert_eq!(
+            guard.check(Some(&stale), Some(NONCE), now),
+            Err(ReplayRejection::Expired)
+        );
+
+        assert_eq!(guard.check(Some(&sent), Some(NONCE), now), Ok(()));
+        assert_eq!(
+            guard.check(Some(&sent), Some(NONCE), now + 1_000),
+            Err(ReplayRejection::Reused)
+        );
+        // Once the window has passed, the stale timestamp alone rejects the replay.
+        assert_eq!(
+            guard.check(Some(&sent), Some(NONCE), now + 301_000),
+            Err(ReplayRejection::Expired)
+        );
+        let later = (now + 301_000).to_string();
+        assert_eq!(
+            guard.check(Some(&later), Some(NONCE), now + 301_000),
+            Ok(())
+        );
+    }
+
+    fn signed(nonce: &str, now: i64, token: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(REPLAY_TIMESTAMP_HEADER, HeaderValue::from(now));
+        headers.insert(
+            REPLAY_NONCE_HEADER,
+            HeaderValue::from_str(nonce).expect("nonce"),
+        );
+        if let Some(token) = token {
+            let bearer = format!("Bearer {token}");
+            headers.insert(
+                "Authorization",
+                HeaderValue::from_str(&bearer).expect("bearer"),
+            );
+        }
+        headers
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_requests_do_not_consume_nonce_capacity() {
+        let mut config = backend_domain::testing::runtime_config();
+        config.api_token = Some("secret-token".to_string());
+        let app = InMemoryApp::new(config);
+        let guard = ReplayGuard::with_capacity(300, 2);
+        let now = 1_700_000_000_000;
+
+        for index in 0..3 {
+            let nonce = format!("anonymous-nonce-{index:04}");
+            let forged = signed(&nonce, now, Some("wrong-token"));
+            assert_eq!(admit(&app.state, &guard, &forged, now).await, Ok(()));
+            let anonymous = signed(&nonce, now, None);
+            assert_eq!(admit(&app.state, &guard, &anonymous, now).await, Ok(()));
+        }
+
+        let first = signed("authorized-nonce-0001", now, Some("secret-token"));
+        assert_eq!(admit(&app.state, &guard, &first, now).await, Ok(()));
+        assert_eq!(
+            admit(&app.state, &guard, &first, now).await,
+            Err(ReplayRejection::Reused)
+        );
+        let second = signed("authorized-nonce-0002", now, Some("secret-token"));
+        assert_eq!(admit(&app.state, &guard, &second, now).await, Ok(()));
+        let third = signed("authorized-nonce-0003", now, Some("secret-token"));
+        assert_eq!(
+            admit(&app.state, &guard, &third, now).await,
+            Err(ReplayRejection::Overloaded)
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::handlers::{detect_handlers, ingest_handlers, ops_handlers, query_handlers};
+
+/// The v2 API as served at `/v2/openapi.json`. Each handler carries its own `#[utoipa::path]`;
+/// a route added to `build_router` must be listed here too.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Lattice backend API",
+        description = "Field-level semantics are described in `docs/http-v2-contract.md`. With \
+                       `api_token` set every operation except the health probes needs \
+                       `Authorization: Bearer <api_token>`."
+    ),
+    paths(
+        ingest_handlers::ingest_items,
+        ingest_handlers::ingest_heartbeat,
+        ingest_handlers::contract_samples,
+        ingest_handlers::cluster_analyze,
+        detect_handlers::list_anomalies,
+        detect_handlers::stream_anomalies,
+        detect_handlers::get_anomaly,
+        detect_handlers::bulk_ack_anomalies,
+        detect_handlers::mark_anomalies_seen,
+        detect_handlers::list_suppressions,
+        detect_handlers::create_suppression,
+        detect_handlers::list_expired_suppressions,
+        detect_handlers::anomaly_trend,
+        detect_handlers::list_storage_scan,
+        detect_handlers::list_key_items,
+        detect_handlers::update_key_items,
+        detect_handlers::list_rule_presets,
+        detect_handlers::list_detection_rules,
+        detect_handlers::list_composite_rules,
+        detect_handlers::apply_rule_preset,
+        detect_handlers::analyzer_status,
+        detect_handlers::get_origin_whitelist,
+        detect_handlers::update_origin_whitelist,
+        detect_handlers::set_origin_learning,
+        query_handlers::query_item_events,
+        query_handlers::player_profile,
+        query_handlers::item_trace,
+        query_handlers::list_report_archive,
+        query_handlers::serve_report,
+        query_handlers::list_item_registry,
+        query_handlers::update_item_registry,
+        query_handlers::delete_item_registry,
+        ops_handlers::get_rcon_config,
+        ops_handlers::update_rcon_config,
+        ops_handlers::get_task_progress,
+        ops_handlers::update_task_progress,
+        ops_handlers::issue_op_token,
+        ops_handlers::report_op_token_misuse,
+        ops_handlers::handle_napcat_group_event,
+        ops_handlers::get_mod_config_current,
+        ops_handlers::put_mod_config_current,
+        ops_handlers::stream_mod_config,
+        ops_handlers::pull_mod_config,
+        ops_handlers::update_mod_config_ack,
+        ops_handlers::get_mod_config_ack_last,
+        ops_handlers::alert_target_check,
+        ops_handlers::list_alert_deliveries,
+        ops_handlers::get_last_alert_delivery,
+        ops_handlers::preview_alerts,
+        ops_handlers::list_stale_ingest_servers,
+        ops_handlers::get_fingerprint_stats,
+        ops_handlers::get_ops_overview,
+        ops_handlers::list_server_status,
+        ops_handlers::get_maintenance_status,
+        ops_handlers::drop_data,
+        ops_handlers::get_effective_config,
+        ops_handlers::get_config_warnings,
+        ops_handlers::get_strictness,
+        ops_handlers::record_ban_event,
+        ops_handlers::list_bans,
+        ops_handlers::list_quarantines,
+        ops_handlers::quarantine_player,
+        ops_handlers::release_player,
+        ops_handlers::list_jobs,
+        ops_handlers::get_job,
+        ops_handlers::submit_ml_export,
+        ops_handlers::download_job_export,
+        ops_handlers::list_api_tokens,
+        ops_handlers::issue_api_token,
+        ops_handlers::revoke_api_token,
+        ops_handlers::list_player_teams,
+        ops_handlers::update_player_teams,
+        ops_handlers::list_reports,
+        ops_handlers::generate_report,
+        ops_handlers::delete_report,
+        ops_handlers::selftest,
+        ops_handlers::replay,
+        ops_handlers::clickhouse_preflight,
+        ops_handlers::health_live,
+        ops_handlers::health_ready,
+        ops_handlers::metrics_prometheus,
+    ),
+    modifiers(&ApiTokenAuth),
+    security(("api_token" = [])),
+    tags(
+        (name = "ingest", description = "Events and heartbeats sent by the game mod"),
+        (name = "detect", description = "Anomalies, suppressions and detection rules"),
+        (name = "query", description = "Raw events, players, item traces and the item registry"),
+        (name = "ops", description = "Configuration, alerts, reports and health")
+    )
+)]
+pub struct ApiDoc;
+
+struct ApiTokenAuth;
+
+impl Modify for ApiTokenAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_route_is_documented() {
+        let spec = ApiDoc::openapi();
+        let routes: Vec<String> = include_str!("routes/v2.rs")
+            .split(".route(")
+            .skip(1)
+            .filter_map(|chunk| chunk.split('"').nth(1))
+            .map(|path| {
+                path.split('/')
+                    .map(|segment| match segment.strip_prefix([':', '*']) {
+                        Some(name) => format!("{{{}}}", name),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        assert!(routes.len() > 50);
+        let missing: Vec<&String> = routes
+            .iter()
+            .filter(|path| !spec.paths.paths.contains_key(path.as_str()))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "routes missing from ApiDoc: {:?}",
+            missing
+        );
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["components"]["securitySchemes"]["api_token"]["scheme"],
+            "bearer"
+        );
+        assert_eq!(
+            json["paths"]["/v2/ops/health/live"]["get"]["security"],
+            serde_json::json!([{}])
+        );
+        assert!(json["components"]["schemas"]["IngestEvent"].is_object());
+    }
+}
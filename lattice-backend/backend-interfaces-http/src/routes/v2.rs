@@ -1,28 +1,140 @@
+use std::sync::Arc;
+
 use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 use backend_application::AppState;
 
 use crate::handlers::{detect_handlers, ingest_handlers, ops_handlers, query_handlers};
+use crate::middleware::{
+    ingest_rate_limit, replay_protection, version_envelope, IngestRateLimiter, ReplayGuard,
+};
+use crate::openapi::ApiDoc;
 
 pub fn build_router(state: AppState) -> Router {
+    // A recording may be as large as `ingest_record_max_mb`, well beyond the default body limit.
+    let replay_limit = state.config.ingest_record_max_mb.max(1) as usize * 1024 * 1024;
+    let ingest_limiter = Arc::new(IngestRateLimiter::new(
+        state.config.ingest_rate_limit_per_second,
+        state.config.ingest_rate_limit_burst,
+        &state.config.trusted_proxies,
+    ));
+    let replay_guard = Arc::new(ReplayGuard::new(state.config.replay_window_seconds));
     Router::new()
         .route(
             "/v2/ingest/events",
-            axum::routing::post(ingest_handlers::ingest_items),
+            axum::routing::post(ingest_handlers::ingest_items).layer(
+                axum::middleware::from_fn_with_state(
+                    (state.clone(), ingest_limiter),
+                    ingest_rate_limit,
+                ),
+            ),
+        )
+        .route(
+            "/v2/ingest/heartbeat",
+            axum::routing::post(ingest_handlers::ingest_heartbeat),
+        )
+        .route(
+            "/v2/ingest/contract-samples",
+            axum::routing::get(ingest_handlers::contract_samples),
+        )
+        .route(
+            "/v2/cluster/analyze",
+            axum::routing::post(ingest_handlers::cluster_analyze),
         )
         .route(
             "/v2/detect/anomalies",
             axum::routing::get(detect_handlers::list_anomalies),
         )
+        .route(
+            "/v2/detect/anomalies/stream",
+            axum::routing::get(detect_handlers::stream_anomalies),
+        )
+        .route(
+            "/v2/detect/anomalies/lookup",
+            axum::routing::get(detect_handlers::get_anomaly),
+        )
+        .route(
+            "/v2/detect/anomalies/bulk-ack",
+            axum::routing::post(detect_handlers::bulk_ack_anomalies),
+        )
+        .route(
+            "/v2/detect/anomalies/seen",
+            axum::routing::post(detect_handlers::mark_anomalies_seen),
+        )
+        .route(
+            "/v2/detect/suppressions",
+            axum::routing::get(detect_handlers::list_suppressions)
+                .post(detect_handlers::create_suppression),
+        )
+        .route(
+            "/v2/detect/suppressions/expired",
+            axum::routing::get(detect_handlers::list_expired_suppressions),
+        )
+        .route(
+            "/v2/detect/anomalies/trend",
+            axum::routing::get(detect_handlers::anomaly_trend),
+        )
         .route(
             "/v2/detect/rules",
             axum::routing::get(detect_handlers::list_key_items)
                 .put(detect_handlers::update_key_items),
         )
+        .route(
+            "/v2/detect/rules/presets",
+            axum::routing::get(detect_handlers::list_rule_presets),
+        )
+        .route(
+            "/v2/detect/rules/custom",
+            axum::routing::get(detect_handlers::list_detection_rules),
+        )
+        .route(
+            "/v2/detect/rules/composite",
+            axum::routing::get(detect_handlers::list_composite_rules),
+        )
+        .route(
+            "/v2/detect/rules/presets/:id/apply",
+            axum::routing::post(detect_handlers::apply_rule_preset),
+        )
+        .route(
+            "/v2/detect/analyzer/status",
+            axum::routing::get(detect_handlers::analyzer_status),
+        )
+        .route(
+            "/v2/detect/origin-whitelist",
+            axum::routing::get(detect_handlers::get_origin_whitelist)
+                .post(detect_handlers::update_origin_whitelist),
+        )
+        .route(
+            "/v2/detect/origin-whitelist/learning",
+            axum::routing::post(detect_handlers::set_origin_learning),
+        )
+        .route(
+            "/v2/query/events",
+            axum::routing::get(query_handlers::query_item_events),
+        )
+        .route(
+            "/v2/query/players/:uuid/profile",
+            axum::routing::get(query_handlers::player_profile),
+        )
+        .route(
+            "/v2/query/item-trace",
+            axum::routing::get(query_handlers::item_trace),
+        )
+        .route(
+            "/v2/query/reports",
+            axum::routing::get(query_handlers::list_report_archive),
+        )
+        .route(
+            "/reports/*path",
+            axum::routing::get(query_handlers::serve_report),
+        )
         .route(
             "/v2/query/item-registry",
             axum::routing::get(query_handlers::list_item_registry)
-                .put(query_handlers::update_item_registry),
+                .put(query_handlers::update_item_registry)
+                .delete(query_handlers::delete_item_registry),
         )
         .route(
             "/v2/ops/rcon-config",
@@ -82,6 +194,111 @@ pub fn build_router(state: AppState) -> Router {
             "/v2/ops/alert-deliveries/last",
             axum::routing::get(ops_handlers::get_last_alert_delivery),
         )
+        .route(
+            "/v2/ops/alerts/preview",
+            axum::routing::post(ops_handlers::preview_alerts),
+        )
+        .route(
+            "/v2/ops/ingest/stale-servers",
+            axum::routing::get(ops_handlers::list_stale_ingest_servers),
+        )
+        .route(
+            "/v2/ops/ingest/fingerprint-stats",
+            axum::routing::get(ops_handlers::get_fingerprint_stats),
+        )
+        .route(
+            "/v2/ops/overview",
+            axum::routing::get(ops_handlers::get_ops_overview),
+        )
+        .route(
+            "/v2/ops/servers/status",
+            axum::routing::get(ops_handlers::list_server_status),
+        )
+        .route(
+            "/v2/ops/maintenance",
+            axum::routing::get(ops_handlers::get_maintenance_status),
+        )
+        .route(
+            "/v2/ops/data",
+            axum::routing::delete(ops_handlers::drop_data),
+        )
+        .route(
+            "/v2/ops/config/effective",
+            axum::routing::get(ops_handlers::get_effective_config),
+        )
+        .route(
+            "/v2/ops/config/warnings",
+            axum::routing::get(ops_handlers::get_config_warnings),
+        )
+        .route(
+            "/v2/ops/strictness",
+            axum::routing::get(ops_handlers::get_strictness),
+        )
+        .route(
+            "/v2/ops/integrations/ban-events",
+            axum::routing::post(ops_handlers::record_ban_event),
+        )
+        .route(
+            "/v2/ops/integrations/bans",
+            axum::routing::get(ops_handlers::list_bans),
+        )
+        .route(
+            "/v2/ops/quarantine",
+            axum::routing::get(ops_handlers::list_quarantines)
+                .post(ops_handlers::quarantine_player),
+        )
+        .route(
+            "/v2/ops/quarantine/:player",
+            axum::routing::delete(ops_handlers::release_player),
+        )
+        .route("/v2/ops/jobs", axum::routing::get(ops_handlers::list_jobs))
+        .route(
+            "/v2/ops/jobs/ml-export",
+            axum::routing::post(ops_handlers::submit_ml_export),
+        )
+        .route(
+            "/v2/ops/jobs/:id",
+            axum::routing::get(ops_handlers::get_job),
+        )
+        .route(
+            "/v2/ops/jobs/:id/download",
+            axum::routing::get(ops_handlers::download_job_export),
+        )
+        .route(
+            "/v2/ops/player-teams",
+            axum::routing::get(ops_handlers::list_player_teams)
+                .put(ops_handlers::update_player_teams),
+        )
+        .route(
+            "/v2/ops/api-tokens",
+            axum::routing::get(ops_handlers::list_api_tokens).post(ops_handlers::issue_api_token),
+        )
+        .route(
+            "/v2/ops/api-tokens/:name",
+            axum::routing::delete(ops_handlers::revoke_api_token),
+        )
+        .route(
+            "/v2/ops/reports",
+            axum::routing::get(ops_handlers::list_reports),
+        )
+        .route(
+            "/v2/ops/reports/:date",
+            axum::routing::post(ops_handlers::generate_report)
+                .delete(ops_handlers::delete_report),
+        )
+        .route(
+            "/v2/ops/selftest",
+            axum::routing::post(ops_handlers::selftest),
+        )
+        .route(
+            "/v2/ops/replay",
+            axum::routing::post(ops_handlers::replay)
+                .layer(axum::extract::DefaultBodyLimit::max(replay_limit)),
+        )
+        .route(
+            "/v2/ops/clickhouse/preflight",
+            axum::routing::get(ops_handlers::clickhouse_preflight),
+        )
         .route(
             "/v2/ops/health/live",
             axum::routing::get(ops_handlers::health_live),
@@ -94,5 +311,11 @@ pub fn build_router(state: AppState) -> Router {
             "/v2/ops/metrics/prometheus",
             axum::routing::get(ops_handlers::metrics_prometheus),
         )
+        .merge(SwaggerUi::new("/v2/docs").url("/v2/openapi.json", ApiDoc::openapi()))
+        .layer(axum::middleware::from_fn_with_state(
+            (state.clone(), replay_guard),
+            replay_protection,
+        ))
+        .layer(axum::middleware::from_fn(version_envelope))
         .with_state(state)
 }
@@ -1,18 +1,15 @@
-mod analyzer;
-mod app;
-mod config;
-mod db;
-mod error;
-mod ingest;
-mod metrics;
-mod model;
-mod alert;
-mod report;
-mod utils;
-mod web;
+//! Deprecated entry point kept for callers that still depend on `lattice_backend`.
+//!
+//! Everything here forwards to `backend-bootstrap`, so this binary and the desktop embed the
+//! same layered backend. The legacy modules next to this file are no longer compiled and only
+//! remain as a migration reference.
 
-pub use config::AppConfig;
+pub use backend_bootstrap::{
+    run_standalone, start_embedded, AlertService, AppConfig, BackendBuilder, BackendEndpoint,
+    BackendHandle, ConfigRepository,
+};
 
+#[deprecated(note = "depend on backend-bootstrap and call backend_bootstrap::run instead")]
 pub async fn run() -> anyhow::Result<()> {
-    app::run().await
+    backend_bootstrap::run().await
 }
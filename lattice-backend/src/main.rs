@@ -1,4 +1,5 @@
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)]
     lattice_backend::run().await
 }
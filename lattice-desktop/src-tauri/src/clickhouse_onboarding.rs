@@ -0,0 +1,222 @@
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    append_debug_log, ensure_config, epoch_millis, parse_config_string, parse_target_from_url,
+    probe_tcp, spawn_backend, stop_backend, BackendState,
+};
+
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+const CLICKHOUSE_MISSING_EVENT: &str = "clickhouse-missing";
+const CLICKHOUSE_READY_EVENT: &str = "clickhouse-ready";
+const INSTALL_DOCS_URL: &str = "https://clickhouse.com/docs/en/install";
+const DOCKER_COMMAND: &str = "docker run -d --name lattice-clickhouse --restart unless-stopped -p 8123:8123 -p 9000:9000 -e CLICKHOUSE_SKIP_USER_SETUP=1 clickhouse/clickhouse-server";
+
+/// Set while nothing listens on the configured ClickHouse port; `watching` keeps a second
+/// backend start from polling the port twice.
+#[derive(Default)]
+pub struct OnboardingState {
+    missing: Mutex<Option<ClickhouseMissing>>,
+    watching: AtomicBool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ClickhouseMissing {
+    /// `host:port` taken from `clickhouse_url`.
+    target: String,
+    error: String,
+    detected_at_ms: u64,
+}
+
+#[derive(Serialize)]
+pub struct InstallOption {
+    label: &'static str,
+    detail: &'static str,
+    /// Shell command to copy; `None` for options that only link to docs.
+    command: Option<&'static str>,
+    url: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct ClickhouseOnboarding {
+    missing: Option<ClickhouseMissing>,
+    /// `windows`, `macos` or `linux`, picking the install options.
+    platform: &'static str,
+    install_options: Vec<InstallOption>,
+}
+
+/// Errors meaning nothing listens on the port, as opposed to a running server rejecting the
+/// credentials or a slow network, which the guided install would not fix.
+fn is_not_listening(error: &str) -> bool {
+    let error = error.to_ascii_lowercase();
+    [
+        "connection refused",
+        "actively refused",
+        "os error 111",
+        "os error 61",
+        "os error 10061",
+    ]
+    .iter()
+    .any(|pattern| error.contains(pattern))
+}
+
+fn clickhouse_target(app: &AppHandle) -> Option<String> {
+    let config_path = ensure_config(app)?;
+    let parsed = fs::read_to_string(config_path)
+        .ok()?
+        .parse::<toml::Value>()
+        .ok()?;
+    let url = parse_config_string(&parsed, "clickhouse_url")?;
+    parse_target_from_url(&url)
+}
+
+fn install_options(platform: &str) -> Vec<InstallOption> {
+    let docker = InstallOption {
+        label: "Docker 容器（推荐）",
+        detail: "已安装 Docker Desktop 时最省事，数据保存在容器内，端口与默认配置一致",
+        command: Some(DOCKER_COMMAND),
+        url: None,
+    };
+    let native = match platform {
+        "windows" => InstallOption {
+            label: "WSL2 安装",
+            detail: "ClickHouse 没有原生 Windows 版本；在 WSL2 的 Ubuntu 中执行后运行 ./clickhouse server",
+            command: Some("curl https://clickhouse.com/ | sh"),
+            url: None,
+        },
+        "macos" => InstallOption {
+            label: "Homebrew 安装",
+            detail: "安装后执行 clickhouse server 启动",
+            command: Some("brew install --cask clickhouse"),
+            url: None,
+        },
+        _ => InstallOption {
+            label: "官方安装脚本",
+            detail: "下载后执行 sudo ./clickhouse install，再用 sudo clickhouse start 启动",
+            command: Some("curl https://clickhouse.com/ | sh"),
+            url: None,
+        },
+    };
+    let docs = InstallOption {
+        label: "安装文档",
+        detail: "其他安装方式与系统要求",
+        command: None,
+        url: Some(INSTALL_DOCS_URL),
+    };
+    vec![docker, native, docs]
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str) {
+    if let Err(err) = app.notification().builder().title(title).body(body).show() {
+        append_debug_log(app, "WARN", &format!("notification failed: {}", err));
+    }
+}
+
+/// Checks the configured ClickHouse port after a backend start. The embedded backend starts
+/// without ClickHouse and only reports degraded reads, so the port itself is probed; when
+/// nothing listens, the frontend shows the install guide and the port is polled until
+/// ClickHouse is up, then the backend is restarted to create its schema.
+pub fn check_after_start(app: &AppHandle) {
+    let state = app.state::<OnboardingState>();
+    if state.watching.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        watch_clickhouse(&app).await;
+        app.state::<OnboardingState>()
+            .watching
+            .store(false, Ordering::SeqCst);
+    });
+}
+
+async fn watch_clickhouse(app: &AppHandle) {
+    let Some(target) = clickhouse_target(app) else {
+        return;
+    };
+    let probe = probe_tcp(Some(&target), "missing clickhouse_url").await;
+    let backend_error = app
+        .state::<BackendState>()
+        .last_error
+        .lock()
+        .unwrap()
+        .clone();
+    let error = match (probe.ok, probe.error, backend_error) {
+        (true, _, _) => return,
+        (false, Some(error), _) if is_not_listening(&error) => error,
+        (false, _, Some(error)) if is_not_listening(&error) => error,
+        _ => return,
+    };
+    append_debug_log(
+        app,
+        "WARN",
+        &format!("clickhouse not reachable at {}: {}", target, error),
+    );
+    let missing = ClickhouseMissing {
+        target: target.clone(),
+        error,
+        detected_at_ms: epoch_millis(),
+    };
+    *app.state::<OnboardingState>().missing.lock().unwrap() = Some(missing.clone());
+    let _ = app.emit(CLICKHOUSE_MISSING_EVENT, missing);
+    notify(
+        app,
+        "未检测到 ClickHouse",
+        &format!(
+            "{} 无法连接，打开 Lattice 查看安装指引；安装后会自动重试。",
+            target
+        ),
+    );
+
+    loop {
+        tokio::time::sleep(RETRY_INTERVAL).await;
+        // The user may point clickhouse_url elsewhere while the guide is open.
+        let Some(target) = clickhouse_target(app) else {
+            return;
+        };
+        if !probe_tcp(Some(&target), "missing clickhouse_url").await.ok {
+            continue;
+        }
+        append_debug_log(
+            app,
+            "INFO",
+            &format!("clickhouse reachable at {}, restarting backend", target),
+        );
+        *app.state::<OnboardingState>().missing.lock().unwrap() = None;
+        // Starting the backend blocks for up to its startup timeout.
+        let restart_app = app.clone();
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            let backend = restart_app.state::<BackendState>();
+            stop_backend(&restart_app, &backend);
+            spawn_backend(&restart_app, &backend);
+        })
+        .await;
+        let _ = app.emit(CLICKHOUSE_READY_EVENT, target.clone());
+        notify(
+            app,
+            "ClickHouse 已连接",
+            &format!("{} 已可用，后端已自动重启。", target),
+        );
+        return;
+    }
+}
+
+#[tauri::command]
+pub fn clickhouse_onboarding_status(state: State<OnboardingState>) -> ClickhouseOnboarding {
+    let platform = match std::env::consts::OS {
+        "windows" => "windows",
+        "macos" => "macos",
+        _ => "linux",
+    };
+    ClickhouseOnboarding {
+        missing: state.missing.lock().unwrap().clone(),
+        platform,
+        install_options: install_options(platform),
+    }
+}
@@ -0,0 +1,305 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{append_debug_log, ensure_config, epoch_millis, read_debug_log_tail};
+
+/// Oldest reports are dropped once the local queue holds this many.
+const MAX_QUEUED_REPORTS: usize = 20;
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Settings in `crash_reporting.toml`; nothing is captured until the user opts in.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CrashReportConfig {
+    enabled: bool,
+    /// Receives each queued report as a JSON `POST` when the user sends them.
+    endpoint: String,
+    /// Lines of `desktop.log` attached to a report; `0` attaches none.
+    log_tail_lines: usize,
+}
+
+impl Default for CrashReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: String::new(),
+            log_tail_lines: 200,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CrashReport {
+    id: String,
+    /// `panic` or `backend_error`.
+    kind: String,
+    created_at_ms: u64,
+    app_version: String,
+    os: String,
+    arch: String,
+    message: String,
+    #[serde(default)]
+    backtrace: String,
+    #[serde(default)]
+    log_tail: String,
+}
+
+#[derive(Serialize)]
+pub struct CrashReportSummary {
+    id: String,
+    kind: String,
+    created_at_ms: u64,
+    message: String,
+}
+
+#[derive(Serialize)]
+pub struct CrashReportSendResult {
+    sent: usize,
+    remaining: usize,
+    error: Option<String>,
+}
+
+fn config_path(app: &AppHandle) -> Option<PathBuf> {
+    let config_path = ensure_config(app)?;
+    config_path
+        .parent()
+        .map(|dir| dir.join("crash_reporting.toml"))
+}
+
+fn queue_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join("crash-reports"))
+}
+
+fn load_config(app: &AppHandle) -> CrashReportConfig {
+    config_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Queues reports for panics in the desktop shell; the previous hook still runs afterwards.
+pub fn install_panic_hook(app: &AppHandle) {
+    let app = app.clone();
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|text| text.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        let message = match info.location() {
+            Some(location) => format!("{payload} at {}:{}", location.file(), location.line()),
+            None => payload,
+        };
+        capture(
+            &app,
+            "panic",
+            &message,
+            &Backtrace::force_capture().to_string(),
+        );
+        previous(info);
+    }));
+}
+
+/// Queues an anonymized report when crash reporting is enabled.
+pub fn capture(app: &AppHandle, kind: &str, message: &str, backtrace: &str) {
+    let config = load_config(app);
+    if !config.enabled {
+        return;
+    }
+    let Some(dir) = queue_dir(app) else {
+        return;
+    };
+    let created_at_ms = epoch_millis();
+    let log_tail = if config.log_tail_lines == 0 {
+        String::new()
+    } else {
+        read_debug_log_tail(app, config.log_tail_lines)
+    };
+    let report = CrashReport {
+        id: format!("{created_at_ms}-{kind}"),
+        kind: kind.to_string(),
+        created_at_ms,
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        message: anonymize(message),
+        backtrace: anonymize(backtrace),
+        log_tail: anonymize(&log_tail),
+    };
+    let _ = fs::create_dir_all(&dir);
+    let Ok(content) = serde_json::to_string_pretty(&report) else {
+        return;
+    };
+    if fs::write(dir.join(format!("{}.json", report.id)), content).is_ok() {
+        append_debug_log(app, "INFO", &format!("crash report queued: {}", report.id));
+    }
+    let queued = load_queue(&dir);
+    for stale in queued
+        .iter()
+        .take(queued.len().saturating_sub(MAX_QUEUED_REPORTS))
+    {
+        let _ = fs::remove_file(dir.join(format!("{}.json", stale.id)));
+    }
+}
+
+/// Queued reports, oldest first.
+fn load_queue(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str::<CrashReport>(&content).ok())
+        .collect::<Vec<_>>();
+    reports.sort_by(|a, b| a.created_at_ms.cmp(&b.created_at_ms).then(a.id.cmp(&b.id)));
+    reports
+}
+
+/// Strips the home directory, the user name and bearer tokens.
+fn anonymize(text: &str) -> String {
+    let mut text = text.to_string();
+    for var in ["HOME", "USERPROFILE"] {
+        if let Ok(home) = std::env::var(var) {
+            if home.len() > 1 {
+                text = text
+                    .replace(&home, "~")
+                    .replace(&home.replace('\\', "/"), "~");
+            }
+        }
+    }
+    for var in ["USER", "USERNAME"] {
+        if let Ok(user) = std::env::var(var) {
+            if user.len() >= 3 {
+                text = text.replace(&user, "<user>");
+            }
+        }
+    }
+    redact_bearer_tokens(&text)
+}
+
+fn redact_bearer_tokens(text: &str) -> String {
+    const PREFIX: &str = "Bearer ";
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(PREFIX) {
+        let (head, tail) = rest.split_at(index + PREFIX.len());
+        redacted.push_str(head);
+        redacted.push_str("<redacted>");
+        rest = tail.trim_start_matches(|c: char| !c.is_whitespace() && c != '"');
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[tauri::command]
+pub fn crash_report_config_get(app: AppHandle) -> Result<CrashReportConfig, String> {
+    Ok(load_config(&app))
+}
+
+#[tauri::command]
+pub fn crash_report_config_set(app: AppHandle, config: CrashReportConfig) -> Result<(), String> {
+    let path = config_path(&app).ok_or("config path unavailable")?;
+    append_debug_log(
+        &app,
+        "INFO",
+        &format!("crash reporting enabled={}", config.enabled),
+    );
+    let content = toml::to_string(&config).map_err(|err| err.to_string())?;
+    fs::write(path, content).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn crash_report_list(app: AppHandle) -> Result<Vec<CrashReportSummary>, String> {
+    let dir = queue_dir(&app).ok_or("crash report path unavailable")?;
+    Ok(load_queue(&dir)
+        .into_iter()
+        .map(|report| CrashReportSummary {
+            id: report.id,
+            kind: report.kind,
+            created_at_ms: report.created_at_ms,
+            message: report.message,
+        })
+        .collect())
+}
+
+/// Removes one queued report, or all of them without an id; returns how many were removed.
+#[tauri::command]
+pub fn crash_report_discard(app: AppHandle, id: Option<String>) -> Result<usize, String> {
+    let dir = queue_dir(&app).ok_or("crash report path unavailable")?;
+    let mut removed = 0;
+    for report in load_queue(&dir) {
+        if id.as_deref().is_some_and(|id| id != report.id) {
+            continue;
+        }
+        if fs::remove_file(dir.join(format!("{}.json", report.id))).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Sends queued reports oldest first and stops at the first failure; sent reports are removed.
+#[tauri::command]
+pub async fn crash_report_send(app: AppHandle) -> Result<CrashReportSendResult, String> {
+    let config = load_config(&app);
+    let endpoint = config.endpoint.trim();
+    if endpoint.is_empty() {
+        return Err("crash report endpoint is not configured".to_string());
+    }
+    let dir = queue_dir(&app).ok_or("crash report path unavailable")?;
+    let client = Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let queued = load_queue(&dir);
+    let mut sent = 0;
+    let mut error = None;
+    for report in &queued {
+        let body = serde_json::to_vec(report).map_err(|err| err.to_string())?;
+        let result = client
+            .post(endpoint)
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => {
+                let _ = fs::remove_file(dir.join(format!("{}.json", report.id)));
+                sent += 1;
+            }
+            Ok(response) => {
+                error = Some(format!("endpoint returned {}", response.status()));
+                break;
+            }
+            Err(err) => {
+                error = Some(err.to_string());
+                break;
+            }
+        }
+    }
+    append_debug_log(
+        &app,
+        if error.is_some() { "WARN" } else { "INFO" },
+        &format!("crash reports sent: {sent}/{}", queued.len()),
+    );
+    Ok(CrashReportSendResult {
+        sent,
+        remaining: queued.len() - sent,
+        error,
+    })
+}
@@ -7,14 +7,25 @@ use std::sync::Mutex;
 #[cfg(target_os = "macos")]
 use std::time::Duration;
 
-use lattice_backend::BackendHandle;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper_util::rt::TokioIo;
+use lattice_backend::{
+    BackendEndpoint, BackendHandle, ADMIN_SECRET_HEADER, REPLAY_NONCE_HEADER,
+    REPLAY_TIMESTAMP_HEADER,
+};
 use rcon::Connection;
 use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager, State, WindowEvent};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_opener::OpenerExt;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex as AsyncMutex;
 
+mod clickhouse_onboarding;
+mod crash_reporter;
+
 const DEFAULT_CONFIG_TOML_TEMPLATE: &str = r#"
 bind_addr = "127.0.0.1:3234"
 api_token = ""
@@ -22,10 +33,19 @@ clickhouse_url = "http://127.0.0.1:8123"
 clickhouse_database = "lattice"
 clickhouse_user = ""
 clickhouse_password = ""
+clickhouse_async_insert = false
+clickhouse_wait_for_async_insert = true
+clickhouse_max_insert_block_size = 0
+clickhouse_dedup_events = false
+shadow_clickhouse_url = ""
+shadow_clickhouse_database = ""
+shadow_clickhouse_user = ""
+shadow_clickhouse_password = ""
 report_dir = "__REPORT_DIR__"
 public_base_url = "http://127.0.0.1:3234"
 webhook_url = ""
-webhook_template = "{\"message\":\"{date} anomalies: high {high} medium {medium} low {low} {link}\"}"
+webhook_template = ""
+report_webhook_locale = "en"
 alert_webhook_url = ""
 alert_webhook_template = "{\"message\":\"rare item alert {total} lines\\n{lines}\"}"
 alert_webhook_token = ""
@@ -33,6 +53,10 @@ alert_group_id = 0
 key_items_path = "__KEY_ITEMS_PATH__"
 item_registry_path = "__ITEM_REGISTRY_PATH__"
 transfer_window_seconds = 2
+strict_transfer_fingerprint = false
+transfer_count_tolerance = 0
+transfer_count_tolerance_percent = 0.0
+transfer_aggregate = false
 key_item_window_minutes = 10
 strict_enabled = true
 strict_pickup_window_seconds = 30
@@ -41,15 +65,95 @@ max_body_bytes = 8388608
 request_timeout_seconds = 15
 report_hour = 0
 report_minute = 5
+ingest_stale_after_minutes = 30
+task_stale_after_seconds = 600
+heartbeat_interval_seconds = 60
+heartbeat_missed_threshold = 3
+min_mod_version = ""
+mod_version_enforce = false
+maintenance_enabled = true
+maintenance_hour = 4
+maintenance_optimize_min_rows = 1000000
+storage_alert_threshold_mb = 0
+response_compression_enabled = true
+response_compression_min_bytes = 1024
+response_compression_content_types = ["application/json", "text/"]
+alert_group_by_player = true
+alert_group_window_seconds = 30
+anomaly_link_target = "desktop"
+custom_burst_types = []
+custom_burst_threshold = 200
+custom_burst_window_seconds = 60
+bind_socket = ""
+tls_cert_path = ""
+tls_key_path = ""
+degraded_cache_size = 2000
+dead_letter_max_events = 100000
+degraded_recovery_seconds = 60
+clickhouse_watchdog_interval_seconds = 15
+clickhouse_watchdog_failures = 3
+strict_profiles = []
+daily_quota_alert_enabled = true
+report_player_pages = 10
+report_retention_count = 90
+report_link_secret = ""
+report_link_ttl_hours = 168
+ml_export_secret = ""
+enrichers = ["player_name", "item_name", "rule_metadata"]
+cluster_mode = false
+cluster_state_url = ""
+grpc_bind_addr = ""
+mqtt_broker_url = ""
+mqtt_client_id = "lattice-backend"
+mqtt_username = ""
+mqtt_password = ""
+mqtt_anomaly_topic = "lattice/anomalies"
+mqtt_health_topic = "lattice/health"
+mqtt_ingest_topic = ""
+redaction_rules = []
+ingest_record_path = ""
+ingest_record_sample_rate = 1.0
+ingest_record_max_mb = 100
+alert_team_routes = []
+config_change_alert_enabled = true
+slow_rule_budget_ms = 250
+rule_hygiene_report_day = 1
+origin_learning_days = 7
+server_keys = []
+server_identity_required = false
+alert_max_lines = 8
+alert_page_ttl_minutes = 30
+rule_risk_overrides = {}
+alert_webhook_fallback_url = ""
+meta_alert_spike_multiple = 5.0
+meta_alert_baseline_minutes = 60
+meta_alert_zero_ingest = true
+alert_webhook_proxy = ""
+display_timezone = "local"
+display_time_format = "%Y-%m-%d %H:%M:%S"
+detection_rules_path = "__DETECTION_RULES_PATH__"
+ingest_dedup_capacity = 100000
+alert_server_groups = {}
+ingest_rate_limit_per_second = 0.0
+ingest_rate_limit_burst = 20
+trusted_proxies = []
+replay_window_seconds = 0
+
+[alert_routing]
+targets = {}
+routes = []
 "#;
 
 const DEFAULT_ITEM_REGISTRY_JSON: &str = include_str!("../item_registry.json");
+const DEEP_LINK_PREFIX: &str = "lattice://";
+const DEEP_LINK_EVENT: &str = "deep-link";
 
 struct RuntimePaths {
     config_path: PathBuf,
     report_dir: PathBuf,
     key_items_path: PathBuf,
     item_registry_path: PathBuf,
+    detection_rules_path: PathBuf,
 }
 
 struct BackendState {
@@ -88,9 +192,29 @@ impl Default for RconConfig {
     }
 }
 
+/// Shell preferences in `desktop.toml`, kept apart from the backend's own `config.toml`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct DesktopSettings {
+    /// Start the embedded backend when the app launches; when off it only starts on request.
+    auto_start_backend: bool,
+}
+
+impl Default for DesktopSettings {
+    fn default() -> Self {
+        Self {
+            auto_start_backend: true,
+        }
+    }
+}
+
 #[derive(Default)]
 struct RconState(AsyncMutex<Option<Connection<TcpStream>>>);
 
+/// Last `lattice://` link not yet picked up by the frontend, e.g. the one that launched the app.
+#[derive(Default)]
+struct DeepLinkState(Mutex<Option<String>>);
+
 #[derive(Serialize)]
 struct RconStatus {
     connected: bool,
@@ -100,6 +224,103 @@ struct RconStatus {
 struct BackendRuntimeStatus {
     running: bool,
     last_error: Option<String>,
+    /// `http://host:port`, or `unix:<path>` / `pipe:<name>` when the backend serves `bind_socket`.
+    base_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LocalSocketResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// Transport the debug probes use to reach the backend.
+enum ProbeTarget {
+    Http { client: Client, base_url: String },
+    LocalSocket(String),
+}
+
+impl ProbeTarget {
+    fn url(&self, path: &str) -> String {
+        match self {
+            ProbeTarget::Http { base_url, .. } => format!("{base_url}{path}"),
+            ProbeTarget::LocalSocket(socket) => {
+                format!("{}{path}", BackendEndpoint::LocalSocket(socket.clone()).base_url())
+            }
+        }
+    }
+
+    async fn get(&self, path: &str, token: Option<&str>) -> Result<(u16, String), String> {
+        self.request("GET", path, token).await
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+    ) -> Result<(u16, String), String> {
+        let token = token.map(|v| v.trim()).filter(|v| !v.is_empty());
+        match self {
+            ProbeTarget::Http { client, base_url } => {
+                let method = reqwest::Method::from_bytes(method.as_bytes())
+                    .map_err(|err| err.to_string())?;
+                let mut request = client.request(method, format!("{base_url}{path}"));
+                if let Some(value) = token {
+                    request = request.bearer_auth(value);
+                }
+                for (name, value) in replay_headers() {
+                    request = request.header(name, value);
+                }
+                let response = request.send().await.map_err(|err| err.to_string())?;
+                let status = response.status().as_u16();
+                let body = match response.text().await {
+                    Ok(text) => text,
+                    Err(err) => format!("read body failed: {err}"),
+                };
+                Ok((status, body))
+            }
+            ProbeTarget::LocalSocket(socket) => {
+                let mut headers = replay_headers();
+                if let Some(value) = token {
+                    headers.push(("authorization".to_string(), format!("Bearer {value}")));
+                }
+                let response = local_socket_request(socket, method, path, &headers, None).await?;
+                Ok((response.status, response.body))
+            }
+        }
+    }
+}
+
+/// Timestamp and nonce a backend with `replay_window_seconds` requires on mutating requests.
+fn replay_headers() -> Vec<(String, String)> {
+    use std::hash::{BuildHasher, Hasher};
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    // A freshly seeded hasher is random per call, which is all a nonce has to be.
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(now.as_nanos());
+    vec![
+        (
+            REPLAY_TIMESTAMP_HEADER.to_string(),
+            now.as_millis().to_string(),
+        ),
+        (
+            REPLAY_NONCE_HEADER.to_string(),
+            format!("{:016x}{:08x}", hasher.finish(), now.subsec_nanos()),
+        ),
+    ]
+}
+
+/// Entry of `GET /v2/ops/reports`.
+#[derive(Serialize, Deserialize)]
+struct ReportFile {
+    date: String,
+    size_bytes: u64,
+    player_pages: usize,
+    modified_ms: i64,
 }
 
 #[derive(Serialize)]
@@ -132,6 +353,10 @@ struct BackendDebugReport {
     health_live: HttpProbeStatus,
     health_ready: HttpProbeStatus,
     alert_check: HttpProbeStatus,
+    clickhouse_preflight: HttpProbeStatus,
+    selftest: HttpProbeStatus,
+    effective_config: Option<serde_json::Value>,
+    effective_config_error: Option<String>,
 }
 
 fn epoch_millis() -> u64 {
@@ -156,6 +381,7 @@ fn resolve_runtime_paths(app: &AppHandle) -> Option<RuntimePaths> {
         report_dir: app_data_dir.join("reports"),
         key_items_path: app_data_dir.join("key_items.yaml"),
         item_registry_path: app_data_dir.join("item_registry.json"),
+        detection_rules_path: app_data_dir.join("detection_rules.yaml"),
     })
 }
 
@@ -171,6 +397,10 @@ fn build_default_config_toml(paths: &RuntimePaths) -> String {
             "__ITEM_REGISTRY_PATH__",
             &to_toml_path(&paths.item_registry_path),
         )
+        .replace(
+            "__DETECTION_RULES_PATH__",
+            &to_toml_path(&paths.detection_rules_path),
+        )
 }
 
 fn ensure_runtime_files(paths: &RuntimePaths) {
@@ -251,7 +481,22 @@ fn save_rcon_config(path: &PathBuf, config: &RconConfig) -> Result<(), String> {
     fs::write(path, content).map_err(|err| err.to_string())
 }
 
-fn spawn_backend(app: &AppHandle, state: &BackendState) {
+fn desktop_settings_path(app: &AppHandle) -> Option<PathBuf> {
+    let config_path = ensure_config(app)?;
+    config_path.parent().map(|dir| dir.join("desktop.toml"))
+}
+
+fn load_desktop_settings(path: &PathBuf) -> Result<DesktopSettings, String> {
+    if !path.exists() {
+        return Ok(DesktopSettings::default());
+    }
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    toml::from_str(&content).map_err(|err| err.to_string())
+}
+
+/// Launch-time start, gated by `auto_start_backend`; `LATTICE_BACKEND_DISABLE=1` still wins so
+/// existing scripts keep working.
+fn auto_start_backend(app: &AppHandle, state: &BackendState) {
     if std::env::var("LATTICE_BACKEND_DISABLE").ok().as_deref() == Some("1") {
         append_debug_log(
             app,
@@ -260,6 +505,29 @@ fn spawn_backend(app: &AppHandle, state: &BackendState) {
         );
         return;
     }
+    let settings = match desktop_settings_path(app) {
+        Some(path) => load_desktop_settings(&path).unwrap_or_else(|err| {
+            append_debug_log(
+                app,
+                "WARN",
+                &format!("desktop settings unreadable, using defaults: {}", err),
+            );
+            DesktopSettings::default()
+        }),
+        None => DesktopSettings::default(),
+    };
+    if !settings.auto_start_backend {
+        append_debug_log(
+            app,
+            "INFO",
+            "backend spawn skipped by auto_start_backend = false",
+        );
+        return;
+    }
+    spawn_backend(app, state);
+}
+
+fn spawn_backend(app: &AppHandle, state: &BackendState) {
     if state.handle.lock().unwrap().is_some() {
         append_debug_log(app, "INFO", "backend spawn skipped: already running");
         return;
@@ -294,6 +562,7 @@ fn spawn_backend(app: &AppHandle, state: &BackendState) {
         Err(err) => {
             *state.last_error.lock().unwrap() = Some(err.to_string());
             append_debug_log(app, "ERROR", &format!("backend spawn failed: {}", err));
+            crash_reporter::capture(app, "backend_error", &format!("{err:#}"), "");
             eprintln!("backend start failed: {err}");
         }
     }
@@ -364,43 +633,192 @@ async fn probe_tcp(target: Option<&str>, default_error: &str) -> TcpProbeStatus
 }
 
 async fn probe_http(
-    client: &Client,
-    url: String,
+    target: &ProbeTarget,
+    method: &str,
+    path: &str,
     token: Option<&str>,
     with_auth: bool,
 ) -> HttpProbeStatus {
-    let mut request = client.get(&url);
-    if with_auth {
-        if let Some(value) = token.map(|v| v.trim()).filter(|v| !v.is_empty()) {
-            request = request.bearer_auth(value);
-        }
-    }
-
-    match request.send().await {
-        Ok(response) => {
-            let status = response.status();
-            let body = match response.text().await {
-                Ok(text) => truncate_body(text),
-                Err(err) => format!("read body failed: {err}"),
-            };
-            HttpProbeStatus {
-                url,
-                ok: status.is_success(),
-                status: Some(status.as_u16()),
-                body,
-                error: None,
-            }
-        }
+    let url = target.url(path);
+    let token = if with_auth { token } else { None };
+    match target.request(method, path, token).await {
+        Ok((status, body)) => HttpProbeStatus {
+            url,
+            ok: (200..300).contains(&status),
+            status: Some(status),
+            body: truncate_body(body),
+            error: None,
+        },
         Err(err) => HttpProbeStatus {
             url,
             ok: false,
             status: None,
             body: String::new(),
-            error: Some(err.to_string()),
+            error: Some(err),
         },
     }
 }
 
+async fn fetch_effective_config(
+    target: &ProbeTarget,
+    token: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    let (status, body) = target.get("/v2/ops/config/effective", token).await?;
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {status}"));
+    }
+    serde_json::from_str(&body).map_err(|err| err.to_string())
+}
+
+/// Backend the report commands talk to, resolved from the desktop config like the debug probe.
+struct ReportBackend {
+    target: ProbeTarget,
+    api_token: Option<String>,
+    report_dir: PathBuf,
+}
+
+fn resolve_report_backend(app: &AppHandle) -> Result<ReportBackend, String> {
+    let config_path = ensure_config(app).ok_or("config path unavailable".to_string())?;
+    let content = fs::read_to_string(&config_path).map_err(|err| err.to_string())?;
+    let parsed = content
+        .parse::<toml::Value>()
+        .map_err(|err| err.to_string())?;
+    let report_dir = parse_config_string(&parsed, "report_dir")
+        .map(PathBuf::from)
+        .or_else(|| resolve_runtime_paths(app).map(|paths| paths.report_dir))
+        .ok_or("report_dir unavailable".to_string())?;
+    let target = match parse_config_string(&parsed, "bind_socket") {
+        Some(socket) => ProbeTarget::LocalSocket(socket),
+        None => {
+            let base_url = parse_config_string(&parsed, "public_base_url")
+                .or_else(|| {
+                    parse_config_string(&parsed, "bind_addr").map(|value| format!("http://{value}"))
+                })
+                .ok_or("missing bind_addr".to_string())?;
+            let client = Client::builder()
+                .no_proxy()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()
+                .map_err(|err| err.to_string())?;
+            ProbeTarget::Http {
+                client,
+                base_url: base_url.trim_end_matches('/').to_string(),
+            }
+        }
+    };
+    Ok(ReportBackend {
+        target,
+        api_token: parse_config_string(&parsed, "api_token"),
+        report_dir,
+    })
+}
+
+/// `YYYY-MM-DD` only, so the date is safe to join onto `report_dir`.
+fn is_report_date(date: &str) -> bool {
+    date.len() == 10
+        && date.char_indices().all(|(index, ch)| match index {
+            4 | 7 => ch == '-',
+            _ => ch.is_ascii_digit(),
+        })
+}
+
+#[cfg(unix)]
+async fn connect_local_socket(path: &str) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn connect_local_socket(
+    name: &str,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+    const ERROR_PIPE_BUSY: i32 = 231;
+    loop {
+        match ClientOptions::new().open(name) {
+            Err(err) if err.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn probe_local_socket(socket: &str) -> TcpProbeStatus {
+    let connect_fut = connect_local_socket(socket);
+    let error = match tokio::time::timeout(tokio::time::Duration::from_secs(3), connect_fut).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some("connect timeout".to_string()),
+    };
+    TcpProbeStatus {
+        target: socket.to_string(),
+        ok: error.is_none(),
+        error,
+    }
+}
+
+/// One HTTP/1.1 exchange with a backend serving `bind_socket`.
+async fn local_socket_request(
+    socket: &str,
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<String>,
+) -> Result<LocalSocketResponse, String> {
+    let exchange = async {
+        let stream = connect_local_socket(socket)
+            .await
+            .map_err(|err| err.to_string())?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|err| err.to_string())?;
+        tauri::async_runtime::spawn(async move {
+            let _ = connection.await;
+        });
+
+        let mut request = hyper::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(hyper::header::HOST, "localhost");
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let request = request
+            .body(Full::new(Bytes::from(body.unwrap_or_default())))
+            .map_err(|err| err.to_string())?;
+        let response = sender
+            .send_request(request)
+            .await
+            .map_err(|err| err.to_string())?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| (name.to_string(), value.to_string()))
+            })
+            .collect();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|err| err.to_string())?
+            .to_bytes();
+        Ok(LocalSocketResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).to_string(),
+        })
+    };
+    match tokio::time::timeout(tokio::time::Duration::from_secs(30), exchange).await {
+        Ok(result) => result,
+        Err(_) => Err("local socket request timeout".to_string()),
+    }
+}
+
 fn missing_http_probe(path: &str, reason: &str) -> HttpProbeStatus {
     HttpProbeStatus {
         url: path.to_string(),
@@ -411,14 +829,117 @@ fn missing_http_probe(path: &str, reason: &str) -> HttpProbeStatus {
     }
 }
 
+fn queue_deep_link(app: &AppHandle, url: &str) {
+    if !url.starts_with(DEEP_LINK_PREFIX) {
+        return;
+    }
+    append_debug_log(app, "INFO", &format!("deep link opened: {}", url));
+    if let Ok(mut pending) = app.state::<DeepLinkState>().0.lock() {
+        *pending = Some(url.to_string());
+    }
+    if let Err(err) = app.emit(DEEP_LINK_EVENT, url) {
+        append_debug_log(app, "WARN", &format!("deep link emit failed: {}", err));
+    }
+}
+
+#[tauri::command]
+fn deep_link_take(state: State<DeepLinkState>) -> Option<String> {
+    state.0.lock().ok().and_then(|mut pending| pending.take())
+}
+
 #[tauri::command]
 fn backend_runtime_status(state: State<BackendState>) -> BackendRuntimeStatus {
-    let running = state.handle.lock().unwrap().is_some();
+    let handle = state.handle.lock().unwrap();
     let last_error = state.last_error.lock().unwrap().clone();
     BackendRuntimeStatus {
-        running,
+        running: handle.is_some(),
         last_error,
+        base_url: handle.as_ref().map(|handle| handle.endpoint().base_url()),
+    }
+}
+
+/// Sends a config-mutating API call, adding the admin secret when `url` points at the embedded
+/// backend; the secret itself never reaches the webview. Calls to any other backend go out
+/// unchanged, since only the embedded one issued a secret to this process.
+#[tauri::command]
+async fn backend_admin_request(
+    state: State<'_, BackendState>,
+    url: String,
+    method: String,
+    mut headers: Vec<(String, String)>,
+    body: Option<String>,
+) -> Result<LocalSocketResponse, String> {
+    let embedded = state
+        .handle
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|handle| (handle.endpoint().clone(), handle.admin_secret().to_string()));
+    if let Some((endpoint, secret)) = embedded {
+        let base_url = endpoint.base_url();
+        if let Some(path) = url.strip_prefix(&base_url).filter(|path| path.starts_with('/')) {
+            headers.push((ADMIN_SECRET_HEADER.to_string(), secret));
+            if let BackendEndpoint::LocalSocket(socket) = &endpoint {
+                return local_socket_request(socket, &method, path, &headers, body).await;
+            }
+        }
+    }
+
+    let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|err| err.to_string())?;
+    let mut request = reqwest::Client::new().request(method, &url);
+    for (name, value) in &headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+    let response = request.send().await.map_err(|err| err.to_string())?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.to_string(), value.to_string()))
+        })
+        .collect();
+    let body = response.text().await.map_err(|err| err.to_string())?;
+    Ok(LocalSocketResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Forwards a frontend API call to the embedded backend when it serves `bind_socket`, since the
+/// webview can only fetch HTTP URLs.
+#[tauri::command]
+async fn backend_socket_request(
+    state: State<'_, BackendState>,
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+) -> Result<LocalSocketResponse, String> {
+    let endpoint = state
+        .handle
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|handle| handle.endpoint().clone());
+    let socket = match endpoint {
+        Some(BackendEndpoint::LocalSocket(socket)) => socket,
+        Some(BackendEndpoint::Tcp(_) | BackendEndpoint::Https(_)) => {
+            return Err("embedded backend is not bound to a local socket".to_string())
+        }
+        None => return Err("embedded backend is not running".to_string()),
+    };
+    if !path.starts_with('/') {
+        return Err("path must start with /".to_string());
     }
+    local_socket_request(&socket, &method, &path, &headers, body).await
 }
 
 #[tauri::command]
@@ -426,9 +947,13 @@ async fn backend_debug_probe(
     app: AppHandle,
     state: State<'_, BackendState>,
 ) -> Result<BackendDebugReport, String> {
-    let runtime = BackendRuntimeStatus {
-        running: state.handle.lock().unwrap().is_some(),
-        last_error: state.last_error.lock().unwrap().clone(),
+    let runtime = {
+        let handle = state.handle.lock().unwrap();
+        BackendRuntimeStatus {
+            running: handle.is_some(),
+            last_error: state.last_error.lock().unwrap().clone(),
+            base_url: handle.as_ref().map(|handle| handle.endpoint().base_url()),
+        }
     };
 
     let config_path = ensure_config(&app).ok_or("config path unavailable".to_string())?;
@@ -438,6 +963,7 @@ async fn backend_debug_probe(
         .map_err(|err| err.to_string())?;
 
     let bind_addr = parse_config_string(&parsed, "bind_addr");
+    let bind_socket = parse_config_string(&parsed, "bind_socket");
     let clickhouse_url = parse_config_string(&parsed, "clickhouse_url");
     let public_base_url = parse_config_string(&parsed, "public_base_url");
     let api_token = parse_config_string(&parsed, "api_token");
@@ -446,13 +972,19 @@ async fn backend_debug_probe(
         .map(|value| !value.trim().is_empty())
         .unwrap_or(false);
 
-    let backend_tcp = probe_tcp(bind_addr.as_deref(), "missing bind_addr").await;
+    let backend_tcp = match &bind_socket {
+        Some(socket) => probe_local_socket(socket).await,
+        None => probe_tcp(bind_addr.as_deref(), "missing bind_addr").await,
+    };
     let clickhouse_target = clickhouse_url.as_deref().and_then(parse_target_from_url);
     let clickhouse_tcp = probe_tcp(clickhouse_target.as_deref(), "missing clickhouse_url").await;
 
-    let probe_base_url = public_base_url
-        .or_else(|| bind_addr.as_ref().map(|value| format!("http://{value}")))
-        .map(|value| value.trim_end_matches('/').to_string());
+    let probe_base_url = match &bind_socket {
+        Some(socket) => Some(BackendEndpoint::LocalSocket(socket.clone()).base_url()),
+        None => public_base_url
+            .or_else(|| bind_addr.as_ref().map(|value| format!("http://{value}")))
+            .map(|value| value.trim_end_matches('/').to_string()),
+    };
 
     let client = Client::builder()
         .no_proxy()
@@ -460,35 +992,74 @@ async fn backend_debug_probe(
         .build()
         .map_err(|err| err.to_string())?;
 
-    let (health_live, health_ready, alert_check) = if let Some(base_url) = &probe_base_url {
-        let live = probe_http(
-            &client,
-            format!("{base_url}/v2/ops/health/live"),
-            api_token.as_deref(),
-            false,
-        )
-        .await;
-        let ready = probe_http(
-            &client,
-            format!("{base_url}/v2/ops/health/ready"),
-            api_token.as_deref(),
-            false,
-        )
-        .await;
-        let alert = probe_http(
-            &client,
-            format!("{base_url}/v2/ops/alert-target/check"),
-            api_token.as_deref(),
-            true,
-        )
-        .await;
-        (live, ready, alert)
-    } else {
-        (
-            missing_http_probe("/v2/ops/health/live", "missing probe base url"),
-            missing_http_probe("/v2/ops/health/ready", "missing probe base url"),
-            missing_http_probe("/v2/ops/alert-target/check", "missing probe base url"),
-        )
+    let probe_target = match (&bind_socket, &probe_base_url) {
+        (Some(socket), _) => Some(ProbeTarget::LocalSocket(socket.clone())),
+        (None, Some(base_url)) => Some(ProbeTarget::Http {
+            client,
+            base_url: base_url.clone(),
+        }),
+        (None, None) => None,
+    };
+
+    let (health_live, health_ready, alert_check, clickhouse_preflight, selftest) =
+        if let Some(target) = &probe_target {
+            let live = probe_http(
+                target,
+                "GET",
+                "/v2/ops/health/live",
+                api_token.as_deref(),
+                false,
+            )
+            .await;
+            let ready = probe_http(
+                target,
+                "GET",
+                "/v2/ops/health/ready",
+                api_token.as_deref(),
+                false,
+            )
+            .await;
+            let alert = probe_http(
+                target,
+                "GET",
+                "/v2/ops/alert-target/check",
+                api_token.as_deref(),
+                true,
+            )
+            .await;
+            let preflight = probe_http(
+                target,
+                "GET",
+                "/v2/ops/clickhouse/preflight",
+                api_token.as_deref(),
+                true,
+            )
+            .await;
+            let selftest = probe_http(
+                target,
+                "POST",
+                "/v2/ops/selftest",
+                api_token.as_deref(),
+                true,
+            )
+            .await;
+            (live, ready, alert, preflight, selftest)
+        } else {
+            (
+                missing_http_probe("/v2/ops/health/live", "missing probe base url"),
+                missing_http_probe("/v2/ops/health/ready", "missing probe base url"),
+                missing_http_probe("/v2/ops/alert-target/check", "missing probe base url"),
+                missing_http_probe("/v2/ops/clickhouse/preflight", "missing probe base url"),
+                missing_http_probe("/v2/ops/selftest", "missing probe base url"),
+            )
+        };
+    let effective = match &probe_target {
+        Some(target) => fetch_effective_config(target, api_token.as_deref()).await,
+        None => Err("missing probe base url".to_string()),
+    };
+    let (effective_config, effective_config_error) = match effective {
+        Ok(value) => (Some(value), None),
+        Err(err) => (None, Some(err)),
     };
 
     let timestamp_ms = epoch_millis();
@@ -515,9 +1086,78 @@ async fn backend_debug_probe(
         health_live,
         health_ready,
         alert_check,
+        clickhouse_preflight,
+        selftest,
+        effective_config,
+        effective_config_error,
     })
 }
 
+#[tauri::command]
+async fn report_list(app: AppHandle) -> Result<Vec<ReportFile>, String> {
+    let backend = resolve_report_backend(&app)?;
+    let (status, body) = backend
+        .target
+        .get("/v2/ops/reports", backend.api_token.as_deref())
+        .await?;
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {status}: {}", truncate_body(body)));
+    }
+    serde_json::from_str(&body).map_err(|err| err.to_string())
+}
+
+/// Opens `report_dir/<date>.html`, asking the backend to generate it first when it is missing.
+/// `in_app` shows it in a desktop window instead of the system browser.
+#[tauri::command]
+async fn report_open(app: AppHandle, date: String, in_app: Option<bool>) -> Result<(), String> {
+    let date = date.trim().to_string();
+    if !is_report_date(&date) {
+        return Err(format!("invalid report date: {date}"));
+    }
+    let backend = resolve_report_backend(&app)?;
+    let path = backend.report_dir.join(format!("{date}.html"));
+    if !path.exists() {
+        append_debug_log(&app, "INFO", &format!("report {date} missing, generating"));
+        let (status, body) = backend
+            .target
+            .request(
+                "POST",
+                &format!("/v2/ops/reports/{date}"),
+                backend.api_token.as_deref(),
+            )
+            .await?;
+        if !(200..300).contains(&status) {
+            let message = format!(
+                "report generation failed: HTTP {status}: {}",
+                truncate_body(body)
+            );
+            append_debug_log(&app, "ERROR", &message);
+            return Err(message);
+        }
+        if !path.exists() {
+            return Err(format!("report not found at {}", path.display()));
+        }
+    }
+
+    if in_app.unwrap_or(false) {
+        let label = format!("report-{date}");
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.reload();
+            return window.set_focus().map_err(|err| err.to_string());
+        }
+        let url = Url::from_file_path(&path).map_err(|_| "invalid report path".to_string())?;
+        WebviewWindowBuilder::new(&app, label, WebviewUrl::External(url))
+            .title(format!("Lattice report {date}"))
+            .inner_size(1100.0, 800.0)
+            .build()
+            .map_err(|err| err.to_string())?;
+        return Ok(());
+    }
+    app.opener()
+        .open_path(path.to_string_lossy().to_string(), None::<&str>)
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn debug_log_path(app: AppHandle) -> Result<String, String> {
     let path = resolve_debug_log_path(&app).ok_or("log path unavailable".to_string())?;
@@ -564,9 +1204,39 @@ fn backend_restart(app: AppHandle, state: State<BackendState>) -> Result<(), Str
     append_debug_log(&app, "INFO", "backend restart requested");
     stop_backend(&app, &state);
     spawn_backend(&app, &state);
+    clickhouse_onboarding::check_after_start(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn backend_start(app: AppHandle, state: State<BackendState>) -> Result<(), String> {
+    append_debug_log(&app, "INFO", "backend start requested");
+    spawn_backend(&app, &state);
+    if let Some(err) = state.last_error.lock().unwrap().clone() {
+        return Err(err);
+    }
+    clickhouse_onboarding::check_after_start(&app);
     Ok(())
 }
 
+#[tauri::command]
+fn desktop_settings_get(app: AppHandle) -> Result<DesktopSettings, String> {
+    let path = desktop_settings_path(&app).ok_or("config path unavailable")?;
+    load_desktop_settings(&path)
+}
+
+#[tauri::command]
+fn desktop_settings_set(app: AppHandle, settings: DesktopSettings) -> Result<(), String> {
+    let path = desktop_settings_path(&app).ok_or("config path unavailable")?;
+    append_debug_log(
+        &app,
+        "INFO",
+        &format!("desktop auto_start_backend={}", settings.auto_start_backend),
+    );
+    let content = toml::to_string(&settings).map_err(|err| err.to_string())?;
+    fs::write(path, content).map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn rcon_config_get(app: AppHandle) -> Result<RconConfig, String> {
     let path = rcon_config_path(&app).ok_or("config path unavailable")?;
@@ -650,11 +1320,32 @@ pub fn run() {
     tauri::Builder::default()
         .manage(BackendState::default())
         .manage(RconState::default())
+        .manage(DeepLinkState::default())
+        .manage(clickhouse_onboarding::OnboardingState::default())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             let handle = app.handle();
             let state = app.state::<BackendState>();
             append_debug_log(&handle, "INFO", "desktop setup start");
-            spawn_backend(&handle, &state);
+            crash_reporter::install_panic_hook(&handle);
+            auto_start_backend(&handle, &state);
+            clickhouse_onboarding::check_after_start(&handle);
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(err) = app.deep_link().register_all() {
+                append_debug_log(&handle, "WARN", &format!("deep link register failed: {}", err));
+            }
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    queue_deep_link(&handle, url.as_str());
+                }
+            }
+            let link_handle = handle.clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    queue_deep_link(&link_handle, url.as_str());
+                }
+            });
             #[cfg(target_os = "macos")]
             refresh_macos_window_shadow(&handle);
             append_debug_log(&handle, "INFO", "desktop setup done");
@@ -669,19 +1360,33 @@ pub fn run() {
         })
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
+            backend_admin_request,
             backend_config_get,
             backend_config_set,
             backend_restart,
             backend_runtime_status,
+            backend_start,
             backend_debug_probe,
+            backend_socket_request,
+            clickhouse_onboarding::clickhouse_onboarding_status,
+            crash_reporter::crash_report_config_get,
+            crash_reporter::crash_report_config_set,
+            crash_reporter::crash_report_discard,
+            crash_reporter::crash_report_list,
+            crash_reporter::crash_report_send,
+            deep_link_take,
             debug_log_path,
             debug_log_tail,
+            desktop_settings_get,
+            desktop_settings_set,
             rcon_config_get,
             rcon_config_set,
             rcon_connect,
             rcon_disconnect,
             rcon_status,
-            rcon_send
+            rcon_send,
+            report_list,
+            report_open
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,170 @@
+import * as React from "react";
+import { invoke, isTauri } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { openUrl } from "@tauri-apps/plugin-opener";
+import { Copy, Database, ExternalLink, Loader2 } from "lucide-react";
+import { toast } from "sonner";
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogClose,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+
+type ClickhouseMissing = {
+  target: string;
+  error: string;
+  detected_at_ms: number;
+};
+
+type InstallOption = {
+  label: string;
+  detail: string;
+  command: string | null;
+  url: string | null;
+};
+
+type ClickhouseOnboarding = {
+  missing: ClickhouseMissing | null;
+  platform: "windows" | "macos" | "linux";
+  install_options: InstallOption[];
+};
+
+const platformLabel: Record<ClickhouseOnboarding["platform"], string> = {
+  windows: "Windows",
+  macos: "macOS",
+  linux: "Linux",
+};
+
+/**
+ * Guided install shown when the embedded backend's ClickHouse port refuses connections. The app
+ * shell keeps polling the port and restarts the backend on its own once ClickHouse is up.
+ */
+export function ClickhouseOnboardingDialog() {
+  const [status, setStatus] = React.useState<ClickhouseOnboarding | null>(null);
+  const [open, setOpen] = React.useState(false);
+
+  React.useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
+    let disposed = false;
+    async function load() {
+      try {
+        const data = await invoke<ClickhouseOnboarding>("clickhouse_onboarding_status");
+        if (disposed) {
+          return;
+        }
+        setStatus(data);
+        if (data.missing) {
+          setOpen(true);
+        }
+      } catch {
+        // Older shells without the command simply never show the guide.
+      }
+    }
+    void load();
+    const unlistenMissing = listen<ClickhouseMissing>("clickhouse-missing", () => {
+      void load();
+    });
+    const unlistenReady = listen<string>("clickhouse-ready", (event) => {
+      setStatus((prev) => (prev ? { ...prev, missing: null } : prev));
+      setOpen(false);
+      toast.success(`ClickHouse 已连接（${event.payload}），后端已重启`);
+    });
+    return () => {
+      disposed = true;
+      void unlistenMissing.then((off) => off());
+      void unlistenReady.then((off) => off());
+    };
+  }, []);
+
+  async function copyCommand(command: string) {
+    try {
+      await navigator.clipboard.writeText(command);
+      toast.success("命令已复制");
+    } catch {
+      toast.error("复制失败，请手动复制");
+    }
+  }
+
+  async function handleOpen(url: string) {
+    try {
+      await openUrl(url);
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "打开失败");
+    }
+  }
+
+  const missing = status?.missing;
+  if (!status || !missing) {
+    return null;
+  }
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <DialogContent className="w-[min(92vw,40rem)] gap-5">
+        <DialogHeader>
+          <div className="mb-1 flex items-center gap-3">
+            <div className="rounded-lg border hairline bg-muted/36 p-2 text-foreground">
+              <Database className="size-5" />
+            </div>
+            <div>
+              <DialogTitle>未检测到 ClickHouse</DialogTitle>
+              <DialogDescription>
+                后端已启动，但 {missing.target} 无法连接，事件暂时无法保存。
+              </DialogDescription>
+            </div>
+          </div>
+        </DialogHeader>
+
+        <div className="grid gap-3 text-sm">
+          <p className="text-muted-foreground">
+            按 {platformLabel[status.platform]} 选择一种方式安装并启动 ClickHouse；如果 ClickHouse
+            在其他地址运行，请在配置页修改 clickhouse_url。
+          </p>
+          {status.install_options.map((option) => (
+            <div key={option.label} className="grid gap-2 rounded-lg border hairline p-3">
+              <div className="flex items-center justify-between gap-3">
+                <span className="font-medium text-foreground">{option.label}</span>
+                {option.url ? (
+                  <Button size="sm" variant="ghost" onClick={() => handleOpen(option.url!)}>
+                    <ExternalLink className="h-4 w-4" />
+                    打开
+                  </Button>
+                ) : null}
+              </div>
+              <span className="text-muted-foreground">{option.detail}</span>
+              {option.command ? (
+                <div className="flex items-center gap-2">
+                  <code className="min-w-0 flex-1 truncate rounded bg-muted/48 px-2 py-1 font-mono text-xs">
+                    {option.command}
+                  </code>
+                  <Button size="sm" variant="secondary" onClick={() => copyCommand(option.command!)}>
+                    <Copy className="h-4 w-4" />
+                    复制
+                  </Button>
+                </div>
+              ) : null}
+            </div>
+          ))}
+          <div className="flex items-center gap-2 text-muted-foreground">
+            <Loader2 className="h-4 w-4 animate-spin" />
+            正在等待 {missing.target} 可用，连接成功后会自动重启后端。
+          </div>
+          <p className="break-all font-mono text-xs text-muted-foreground">{missing.error}</p>
+        </div>
+
+        <DialogFooter>
+          <DialogClose asChild>
+            <Button variant="ghost">稍后处理</Button>
+          </DialogClose>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -49,6 +49,22 @@ type BackendDebugReport = {
   health_live: ProbeStatus;
   health_ready: ProbeStatus;
   alert_check: ProbeStatus;
+  clickhouse_preflight: ProbeStatus;
+  selftest: ProbeStatus;
+  effective_config?: EffectiveConfig | null;
+  effective_config_error?: string | null;
+};
+
+type EffectiveConfigEntry = {
+  key: string;
+  value: unknown;
+  origin: "file" | "env" | "default";
+  secret: boolean;
+};
+
+type EffectiveConfig = {
+  config_path?: string;
+  entries: EffectiveConfigEntry[];
 };
 
 type RconConfig = {
@@ -63,6 +79,25 @@ type RconStatus = {
   connected: boolean;
 };
 
+type CrashReportConfig = {
+  enabled: boolean;
+  endpoint: string;
+  log_tail_lines: number;
+};
+
+type CrashReportSummary = {
+  id: string;
+  kind: "panic" | "backend_error";
+  created_at_ms: number;
+  message: string;
+};
+
+type CrashReportSendResult = {
+  sent: number;
+  remaining: number;
+  error?: string | null;
+};
+
 type ConsoleEntry = {
   id: string;
   kind: "command" | "response" | "error";
@@ -120,6 +155,23 @@ function saveRconHistory(entries: ConsoleEntry[]) {
   }
 }
 
+function formatEffectiveConfig(report: BackendDebugReport | null) {
+  if (!report) {
+    return "尚未运行自检。点击“运行自检”读取后端生效配置。";
+  }
+  if (!report.effective_config) {
+    return `读取生效配置失败: ${report.effective_config_error ?? "未知错误"}`;
+  }
+  const { config_path, entries } = report.effective_config;
+  const lines = entries.map(
+    (entry) =>
+      `${entry.key} = ${JSON.stringify(entry.value)}  [${entry.origin}]`,
+  );
+  return [`# ${config_path ?? "未找到 config.toml，使用默认值"}`, ...lines].join(
+    "\n",
+  );
+}
+
 function DebugPanel({ visible }: { visible: boolean }) {
   const [debugLoading, setDebugLoading] = React.useState(false);
   const [debugLogLoading, setDebugLogLoading] = React.useState(false);
@@ -225,6 +277,8 @@ function DebugPanel({ visible }: { visible: boolean }) {
         <TabsList className="w-full justify-start">
           <TabsTrigger value="logs">运行日志</TabsTrigger>
           <TabsTrigger value="probe">自检结果</TabsTrigger>
+          <TabsTrigger value="config">生效配置</TabsTrigger>
+          <TabsTrigger value="crash">崩溃报告</TabsTrigger>
         </TabsList>
         <TabsContent value="logs" className="mt-2">
           <Label className="mb-2 block">最近 500 行</Label>
@@ -246,11 +300,160 @@ function DebugPanel({ visible }: { visible: boolean }) {
             }
           />
         </TabsContent>
+        <TabsContent value="config" className="mt-2">
+          <Label className="mb-2 block">来源: file / env / default</Label>
+          <Textarea
+            className="min-h-[42vh] font-mono text-xs"
+            readOnly
+            value={formatEffectiveConfig(debugReport)}
+          />
+        </TabsContent>
+        <TabsContent value="crash" className="mt-2">
+          <CrashReportPanel visible={visible} />
+        </TabsContent>
       </Tabs>
     </div>
   );
 }
 
+// Crash reports stay on this machine until the user opts in and sends them explicitly.
+function CrashReportPanel({ visible }: { visible: boolean }) {
+  const [config, setConfig] = React.useState<CrashReportConfig>({
+    enabled: false,
+    endpoint: "",
+    log_tail_lines: 200,
+  });
+  const [reports, setReports] = React.useState<CrashReportSummary[]>([]);
+  const [busy, setBusy] = React.useState(false);
+
+  const loadCrashReports = React.useCallback(async () => {
+    if (!tauriReady) {
+      return;
+    }
+    try {
+      const [loaded, queued] = await Promise.all([
+        invoke<CrashReportConfig>("crash_report_config_get"),
+        invoke<CrashReportSummary[]>("crash_report_list"),
+      ]);
+      setConfig(loaded);
+      setReports(queued);
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "读取崩溃报告失败");
+    }
+  }, []);
+
+  React.useEffect(() => {
+    if (visible) {
+      void loadCrashReports();
+    }
+  }, [loadCrashReports, visible]);
+
+  async function saveCrashConfig(next: CrashReportConfig) {
+    try {
+      setBusy(true);
+      await invoke("crash_report_config_set", { config: next });
+      setConfig(next);
+      toast.success(next.enabled ? "已开启崩溃报告" : "已关闭崩溃报告");
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "保存配置失败");
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  async function sendCrashReports() {
+    try {
+      setBusy(true);
+      await invoke("crash_report_config_set", { config });
+      const result = await invoke<CrashReportSendResult>("crash_report_send");
+      if (result.error) {
+        toast.error(
+          `已发送 ${result.sent} 份，剩余 ${result.remaining} 份: ${result.error}`,
+        );
+      } else {
+        toast.success(`已发送 ${result.sent} 份崩溃报告`);
+      }
+      await loadCrashReports();
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "发送失败");
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  async function discardCrashReports() {
+    try {
+      setBusy(true);
+      await invoke<number>("crash_report_discard", { id: null });
+      await loadCrashReports();
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "清空失败");
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  return (
+    <div className="grid gap-3">
+      <div className="text-xs text-muted-foreground">
+        开启后，桌面端崩溃和后端启动失败会在本地生成报告（已去除用户目录、用户名和令牌，附最近日志），只有点击“发送报告”才会上传。
+      </div>
+      <div className="grid gap-1.5">
+        <Label>上报地址</Label>
+        <Input
+          value={config.endpoint}
+          onChange={(event) =>
+            setConfig((prev) => ({ ...prev, endpoint: event.target.value }))
+          }
+          placeholder="https://example.com/lattice/crash-reports"
+          disabled={busy}
+        />
+      </div>
+      <div className="flex flex-wrap gap-2">
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={() =>
+            saveCrashConfig({ ...config, enabled: !config.enabled })
+          }
+          disabled={busy || !tauriReady}
+        >
+          {config.enabled ? "关闭崩溃报告" : "开启崩溃报告"}
+        </Button>
+        <Button
+          size="sm"
+          onClick={sendCrashReports}
+          disabled={busy || reports.length === 0 || !config.endpoint.trim()}
+        >
+          发送报告 ({reports.length})
+        </Button>
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={discardCrashReports}
+          disabled={busy || reports.length === 0}
+        >
+          清空队列
+        </Button>
+      </div>
+      <Textarea
+        className="min-h-[30vh] font-mono text-xs"
+        readOnly
+        value={
+          reports.length
+            ? reports
+                .map(
+                  (report) =>
+                    `[${new Date(report.created_at_ms).toLocaleString()}] ${report.kind}: ${report.message}`,
+                )
+                .join("\n")
+            : "暂无待发送的崩溃报告。"
+        }
+      />
+    </div>
+  );
+}
+
 function RconPanel({ visible }: { visible: boolean }) {
   const [config, setConfig] = React.useState<RconConfig>({
     host: "127.0.0.1",
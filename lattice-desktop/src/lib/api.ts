@@ -2,21 +2,41 @@ import type {
   AlertDeliveryRecord,
   AlertStatus,
   AnomalyRow,
+  AnomalySeenResult,
+  ConfigWarning,
   ItemRegistryEntry,
   KeyItemRule,
   ModConfigAck,
   ModConfigEnvelope,
   ModConfigPutRequest,
   PagedResult,
+  PresetConflictPolicy,
+  ReportFile,
+  RulePreset,
+  RulePresetApplyResult,
   StorageScanRow,
   TaskStatus,
 } from "@/lib/types";
+import { invoke } from "@tauri-apps/api/core";
+
+// Embedded backend served over `bind_socket`; the webview cannot fetch these, so requests go
+// through the Tauri shell.
+const LOCAL_SOCKET_BASE = /^(unix|pipe):/i;
+
+type LocalSocketResponse = {
+  status: number;
+  headers: [string, string][];
+  body: string;
+};
 
 function normalizeBaseUrl(baseUrl: string) {
   const trimmed = baseUrl.trim();
   if (!trimmed) {
     return "http://127.0.0.1:3234";
   }
+  if (LOCAL_SOCKET_BASE.test(trimmed)) {
+    return trimmed;
+  }
   if (!/^https?:\/\//i.test(trimmed)) {
     return `http://${trimmed.replace(/^\/+/, "")}`;
   }
@@ -30,8 +50,52 @@ function buildUrl(baseUrl: string, path: string) {
   return `${normalizeBaseUrl(baseUrl)}${path}`;
 }
 
+async function backendFetch(url: string, init: RequestInit = {}) {
+  if (!LOCAL_SOCKET_BASE.test(url)) {
+    return fetch(url, init);
+  }
+  // API paths all live under /v2/, which never appears in a socket path or pipe name.
+  const pathStart = url.indexOf("/v2/");
+  const path = pathStart >= 0 ? url.slice(pathStart) : "/";
+  const headers = Object.entries((init.headers ?? {}) as Record<string, string>);
+  const res = await invoke<LocalSocketResponse>("backend_socket_request", {
+    method: init.method ?? "GET",
+    path,
+    headers,
+    body: typeof init.body === "string" ? init.body : null,
+  });
+  const nullBody = res.status === 204 || res.status === 304;
+  return new Response(nullBody ? null : res.body, {
+    status: res.status,
+    headers: res.headers,
+  });
+}
+
+// Config-mutating calls go through the Tauri shell, which adds the embedded backend's admin
+// secret (`X-Lattice-Admin-Secret`) without handing it to the webview.
+async function adminFetch(url: string, init: RequestInit = {}) {
+  const headers = Object.entries((init.headers ?? {}) as Record<string, string>);
+  const res = await invoke<LocalSocketResponse>("backend_admin_request", {
+    url,
+    method: init.method ?? "GET",
+    headers,
+    body: typeof init.body === "string" ? init.body : null,
+  });
+  const nullBody = res.status === 204 || res.status === 304;
+  return new Response(nullBody ? null : res.body, {
+    status: res.status,
+    headers: res.headers,
+  });
+}
+
 function buildHeaders(apiToken: string, isJson = false) {
-  const headers: Record<string, string> = {};
+  // Names the desktop as the actor in config change notifications.
+  // A fresh timestamp and nonce satisfy a backend with `replay_window_seconds` set.
+  const headers: Record<string, string> = {
+    "X-Lattice-Actor": "desktop",
+    "X-Lattice-Timestamp": String(Date.now()),
+    "X-Lattice-Nonce": crypto.randomUUID(),
+  };
   const trimmed = apiToken.trim();
   if (trimmed) {
     headers.Authorization = `Bearer ${trimmed}`;
@@ -77,6 +141,7 @@ function normalizeTaskProgress(raw: unknown) {
     } | null;
     trace_id?: string | null;
     throughput_per_sec?: number | null;
+    stale?: boolean;
   }>;
   const sourceTotals = next?.counters?.targets_total_by_source;
   const doneBySource = next?.counters?.done_by_source;
@@ -124,6 +189,7 @@ function normalizeTaskProgress(raw: unknown) {
         ? next.trace_id.trim()
         : null,
     throughput_per_sec: throughput,
+    stale: next?.stale === true,
   };
 }
 
@@ -152,7 +218,7 @@ function normalizePagedResult<T>(raw: unknown): PagedResult<T> {
 }
 
 export async function fetchKeyItems(baseUrl: string, apiToken: string) {
-  const res = await fetch(buildUrl(baseUrl, "/v2/detect/rules"), {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/detect/rules"), {
     headers: buildHeaders(apiToken, false),
   });
   return jsonOrThrow<KeyItemRule[]>(res);
@@ -163,7 +229,7 @@ export async function saveKeyItems(
   apiToken: string,
   rules: KeyItemRule[],
 ) {
-  const res = await fetch(buildUrl(baseUrl, "/v2/detect/rules"), {
+  const res = await adminFetch(buildUrl(baseUrl, "/v2/detect/rules"), {
     method: "PUT",
     headers: buildHeaders(apiToken, true),
     body: JSON.stringify({ rules }),
@@ -174,6 +240,30 @@ export async function saveKeyItems(
   }
 }
 
+export async function fetchRulePresets(baseUrl: string, apiToken: string) {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/detect/rules/presets"), {
+    headers: buildHeaders(apiToken, false),
+  });
+  return jsonOrThrow<RulePreset[]>(res);
+}
+
+export async function applyRulePreset(
+  baseUrl: string,
+  apiToken: string,
+  presetId: string,
+  onConflict: PresetConflictPolicy,
+) {
+  const res = await adminFetch(
+    buildUrl(baseUrl, `/v2/detect/rules/presets/${encodeURIComponent(presetId)}/apply`),
+    {
+      method: "POST",
+      headers: buildHeaders(apiToken, true),
+      body: JSON.stringify({ on_conflict: onConflict }),
+    },
+  );
+  return jsonOrThrow<RulePresetApplyResult>(res);
+}
+
 export async function searchRegistry(
   baseUrl: string,
   apiToken: string,
@@ -185,7 +275,7 @@ export async function searchRegistry(
     baseUrl,
     `/v2/query/item-registry?query=${encodeURIComponent(query)}&limit=${limit}&lang=${encodeURIComponent(lang)}`,
   );
-  const res = await fetch(url, {
+  const res = await backendFetch(url, {
     headers: buildHeaders(apiToken, false),
   });
   return jsonOrThrow<ItemRegistryEntry[]>(res);
@@ -206,13 +296,33 @@ export async function fetchAnomalies(
   if (player) {
     query.set("player", player);
   }
-  const res = await fetch(buildUrl(baseUrl, `/v2/detect/anomalies?${query.toString()}`), {
-    headers: buildHeaders(apiToken, false),
-  });
+  const res = await backendFetch(
+    buildUrl(baseUrl, `/v2/detect/anomalies?${query.toString()}`),
+    {
+      headers: buildHeaders(apiToken, false),
+    },
+  );
   const raw = await jsonOrThrow<unknown>(res);
   return normalizePagedResult<AnomalyRow>(raw);
 }
 
+export async function fetchAnomaly(baseUrl: string, apiToken: string, id: string) {
+  const url = buildUrl(baseUrl, `/v2/detect/anomalies/lookup?id=${encodeURIComponent(id)}`);
+  const res = await backendFetch(url, {
+    headers: buildHeaders(apiToken, false),
+  });
+  return jsonOrThrow<AnomalyRow>(res);
+}
+
+export async function markAnomaliesSeen(baseUrl: string, apiToken: string, ids: string[]) {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/detect/anomalies/seen"), {
+    method: "POST",
+    headers: buildHeaders(apiToken, true),
+    body: JSON.stringify({ ids }),
+  });
+  return jsonOrThrow<AnomalySeenResult>(res);
+}
+
 export async function fetchStorageScan(
   baseUrl: string,
   apiToken: string,
@@ -228,9 +338,12 @@ export async function fetchStorageScan(
   if (item) {
     query.set("item", item);
   }
-  const res = await fetch(buildUrl(baseUrl, `/v2/detect/storage-scan?${query.toString()}`), {
-    headers: buildHeaders(apiToken, false),
-  });
+  const res = await backendFetch(
+    buildUrl(baseUrl, `/v2/detect/storage-scan?${query.toString()}`),
+    {
+      headers: buildHeaders(apiToken, false),
+    },
+  );
   const raw = await jsonOrThrow<unknown>(res);
   return normalizePagedResult<StorageScanRow>(raw);
 }
@@ -239,7 +352,7 @@ export async function fetchAlertStatus(
   baseUrl: string,
   apiToken: string,
 ): Promise<AlertStatus> {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/alert-target/check"), {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/alert-target/check"), {
     headers: buildHeaders(apiToken, false),
   });
   let payload: AlertStatus | null = null;
@@ -263,11 +376,18 @@ export async function fetchAlertStatus(
   throw new Error("Alert check returned invalid payload");
 }
 
+export async function fetchConfigWarnings(baseUrl: string, apiToken: string) {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/config/warnings"), {
+    headers: buildHeaders(apiToken, false),
+  });
+  return jsonOrThrow<ConfigWarning[]>(res);
+}
+
 export async function fetchTaskProgress(
   baseUrl: string,
   apiToken: string,
 ): Promise<TaskStatus> {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/task-progress"), {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/task-progress"), {
     headers: buildHeaders(apiToken, false),
   });
   const raw = await jsonOrThrow<unknown>(res);
@@ -332,7 +452,7 @@ export async function fetchModConfigCurrent(
 ): Promise<ModConfigEnvelope | null> {
   const query = new URLSearchParams();
   query.set("server_id", serverId.trim() || "server-01");
-  const res = await fetch(
+  const res = await backendFetch(
     buildUrl(baseUrl, `/v2/ops/mod-config/current?${query.toString()}`),
     {
       headers: buildHeaders(apiToken, false),
@@ -353,7 +473,7 @@ export async function updateModConfigCurrent(
 ): Promise<ModConfigEnvelope> {
   const query = new URLSearchParams();
   query.set("server_id", serverId.trim() || "server-01");
-  const res = await fetch(
+  const res = await adminFetch(
     buildUrl(baseUrl, `/v2/ops/mod-config/current?${query.toString()}`),
     {
       method: "PUT",
@@ -376,7 +496,7 @@ export async function fetchModConfigAckLast(
 ): Promise<ModConfigAck | null> {
   const query = new URLSearchParams();
   query.set("server_id", serverId.trim() || "server-01");
-  const res = await fetch(
+  const res = await backendFetch(
     buildUrl(baseUrl, `/v2/ops/mod-config/ack/last?${query.toString()}`),
     {
       headers: buildHeaders(apiToken, false),
@@ -402,6 +522,8 @@ function normalizeAlertDelivery(raw: unknown): AlertDeliveryRecord {
     alert_count: Number(next.alert_count || 0),
     rule_ids: rules,
     error: typeof next.error === "string" ? next.error : null,
+    failover: next.failover === true,
+    primary_error: typeof next.primary_error === "string" ? next.primary_error : null,
   };
 }
 
@@ -411,7 +533,7 @@ export async function fetchAlertDeliveries(
   limit = 50,
 ): Promise<AlertDeliveryRecord[]> {
   const size = Math.max(1, Math.min(200, Math.floor(limit)));
-  const res = await fetch(
+  const res = await backendFetch(
     buildUrl(baseUrl, `/v2/ops/alert-deliveries?limit=${size}`),
     {
       headers: buildHeaders(apiToken, false),
@@ -428,7 +550,7 @@ export async function fetchLastAlertDelivery(
   baseUrl: string,
   apiToken: string,
 ): Promise<AlertDeliveryRecord | null> {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/alert-deliveries/last"), {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/alert-deliveries/last"), {
     headers: buildHeaders(apiToken, false),
   });
   const raw = await jsonOrThrow<unknown | null>(res);
@@ -439,17 +561,17 @@ export async function fetchLastAlertDelivery(
 }
 
 export async function pingHealth(baseUrl: string) {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/health/live"));
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/health/live"));
   return res.ok;
 }
 
 export async function pingReady(baseUrl: string) {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/health/ready"));
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/health/ready"));
   return res.ok;
 }
 
 export async function fetchMetrics(baseUrl: string, apiToken: string) {
-  const res = await fetch(buildUrl(baseUrl, "/v2/ops/metrics/prometheus"), {
+  const res = await backendFetch(buildUrl(baseUrl, "/v2/ops/metrics/prometheus"), {
     headers: buildHeaders(apiToken, false),
   });
   if (!res.ok) {
@@ -458,3 +580,13 @@ export async function fetchMetrics(baseUrl: string, apiToken: string) {
   }
   return res.text();
 }
+
+// Daily reports go through the Tauri shell, which reads report_dir and the backend address from
+// the desktop config rather than the UI settings.
+export async function listReports() {
+  return invoke<ReportFile[]>("report_list");
+}
+
+export async function openReport(date: string, inApp = false) {
+  await invoke("report_open", { date, inApp });
+}
@@ -25,8 +25,9 @@ export function parsePrometheusMetrics(payload: string): ParsedMetrics {
     .map((line) => line.trim())
     .filter((line) => line.length > 0 && !line.startsWith("#"))
     .forEach((line) => {
-      const [name, value] = line.split(/\s+/);
-      const key = METRIC_MAP[name];
+      const [series, value] = line.split(/\s+/);
+      // Labelled series (e.g. anomalies per rule_id) are summed into one figure.
+      const key = METRIC_MAP[series.split("{")[0]];
       if (!key) {
         return;
       }
@@ -34,7 +35,7 @@ export function parsePrometheusMetrics(payload: string): ParsedMetrics {
       if (!Number.isFinite(parsed)) {
         return;
       }
-      metrics[key] = parsed;
+      metrics[key] += parsed;
     });
 
   return metrics;
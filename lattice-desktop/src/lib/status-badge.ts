@@ -1,4 +1,5 @@
 export const statusBadgeClass = {
+  critical: "border-foreground/70 bg-foreground/22 text-foreground font-semibold",
   high: "border-border/60 bg-foreground/14 text-foreground",
   medium: "border-border/60 bg-foreground/10 text-foreground/92",
   low: "border-border/60 bg-foreground/6 text-foreground/86",
@@ -8,6 +9,7 @@ export const statusBadgeClass = {
 } as const;
 
 export const riskBadgeClass: Record<string, string> = {
+  CRITICAL: statusBadgeClass.critical,
   HIGH: statusBadgeClass.high,
   MEDIUM: statusBadgeClass.medium,
   LOW: statusBadgeClass.low,
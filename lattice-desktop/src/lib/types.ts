@@ -1,9 +1,29 @@
-export type RiskLevel = "LOW" | "MEDIUM" | "HIGH";
+export type RiskLevel = "LOW" | "MEDIUM" | "HIGH" | "CRITICAL";
 
 export type KeyItemRule = {
   item_id: string;
   threshold: number;
   risk_level: RiskLevel;
+  daily_quota?: number | null;
+  unit?: "items" | "stacks" | "value" | null;
+  stack_size?: number | null;
+  unit_value?: number | null;
+};
+
+export type RulePreset = {
+  id: string;
+  name: string;
+  description: string;
+  rules: KeyItemRule[];
+};
+
+export type PresetConflictPolicy = "keep" | "replace" | "stricter";
+
+export type RulePresetApplyResult = {
+  preset: string;
+  added: string[];
+  replaced: string[];
+  kept: string[];
 };
 
 export type ItemRegistryEntry = {
@@ -12,10 +32,13 @@ export type ItemRegistryEntry = {
   names?: Record<string, string> | null;
   namespace?: string | null;
   path?: string | null;
+  max_stack_size?: number | null;
 };
 
 export type AnomalyRow = {
+  id?: string;
   event_time: string;
+  display_time?: string;
   server_id: string;
   player_uuid: string;
   player_name: string;
@@ -23,8 +46,16 @@ export type AnomalyRow = {
   count: number;
   risk_level: RiskLevel | string;
   rule_id: string;
+  rule_description?: string;
   reason: string;
   evidence_json: string;
+  acknowledged?: boolean;
+  seen?: boolean;
+};
+
+export type AnomalySeenResult = {
+  recorded: number;
+  seen_at_ms: number;
 };
 
 export type StorageScanRow = {
@@ -38,6 +69,7 @@ export type StorageScanRow = {
   y: number | null;
   z: number | null;
   rule_id: string;
+  rule_description?: string;
   threshold: number;
   risk_level: RiskLevel | string;
   reason: string;
@@ -48,14 +80,21 @@ export type AlertStatus = {
   mode: string;
 };
 
+export type ConfigWarning = {
+  code: string;
+  key: string;
+  message: string;
+};
+
 export type TaskProgress = {
-  state: "IDLE" | "RUNNING" | "SUCCEEDED" | "FAILED" | string;
+  state: "IDLE" | "RUNNING" | "STALLED" | "SUCCEEDED" | "FAILED" | string;
   stage?: "INDEXING" | "OFFLINE_WORLD" | "OFFLINE_SB" | "OFFLINE_RS2" | "RUNTIME" | string | null;
   counters: TaskCounters;
   updated_at: number;
   failure?: TaskFailure | null;
   trace_id?: string | null;
   throughput_per_sec?: number | null;
+  stale: boolean;
 };
 
 export type TaskCounters = {
@@ -105,6 +144,8 @@ export type AlertDeliveryRecord = {
   alert_count: number;
   rule_ids: string[];
   error?: string | null;
+  failover?: boolean;
+  primary_error?: string | null;
 };
 
 export type ModConfigEnvelope = {
@@ -131,6 +172,13 @@ export type ModConfigAck = {
   changed_keys: string[];
 };
 
+export type ReportFile = {
+  date: string;
+  size_bytes: number;
+  player_pages: number;
+  modified_ms: number;
+};
+
 export type HealthStatus = {
   ok: boolean;
 };
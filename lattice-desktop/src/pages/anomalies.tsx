@@ -24,7 +24,7 @@ import {
   TableRow,
 } from "@/components/ui/table";
 import { Badge } from "@/components/ui/badge";
-import { fetchAnomalies } from "@/lib/api";
+import { fetchAnomalies, markAnomaliesSeen } from "@/lib/api";
 import { formatDateTime } from "@/lib/datetime";
 import { riskBadgeClass, statusBadgeClass } from "@/lib/status-badge";
 import { useSettings } from "@/lib/settings";
@@ -102,6 +102,19 @@ export function Anomalies() {
 
   const data = anomaliesQuery.data?.items || [];
 
+  const selectAnomaly = React.useCallback(
+    (row: AnomalyRow) => {
+      setSelected(row);
+      // Receipts only tell other moderators what was reviewed; a failed one is not worth a toast.
+      if (row.id && row.seen === false) {
+        markAnomaliesSeen(settings.baseUrl, settings.apiToken, [row.id])
+          .then(() => anomaliesQuery.refetch())
+          .catch(() => undefined);
+      }
+    },
+    [settings.baseUrl, settings.apiToken, anomaliesQuery],
+  );
+
   const columns = React.useMemo<ColumnDef<AnomalyRow>[]>(
     () => [
       {
@@ -117,7 +130,14 @@ export function Anomalies() {
         accessorKey: "player_name",
         header: "玩家",
         cell: ({ row }) => (
-          <span className="text-sm text-foreground">{row.original.player_name}</span>
+          <span
+            className={cn(
+              "text-sm text-foreground",
+              row.original.seen === false && "font-semibold",
+            )}
+          >
+            {row.original.player_name}
+          </span>
         ),
       },
       {
@@ -305,7 +325,7 @@ export function Anomalies() {
                         "cursor-pointer border-border",
                         active && "bg-muted/40",
                       )}
-                      onClick={() => setSelected(row.original)}
+                      onClick={() => selectAnomaly(row.original)}
                     >
                       {row.getVisibleCells().map((cell) => (
                         <TableCell key={cell.id}>
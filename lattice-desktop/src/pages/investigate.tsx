@@ -1,5 +1,6 @@
 import * as React from "react";
 import { useQuery } from "@tanstack/react-query";
+import { useSearch } from "@tanstack/react-router";
 import {
   type ColumnDef,
   type PaginationState,
@@ -26,7 +27,7 @@ import {
   TableHeader,
   TableRow,
 } from "@/components/ui/table";
-import { fetchAnomalies, fetchStorageScan } from "@/lib/api";
+import { fetchAnomalies, fetchAnomaly, fetchStorageScan } from "@/lib/api";
 import { formatDateTime } from "@/lib/datetime";
 import { useMotionPresets } from "@/lib/motion";
 import { riskBadgeClass, statusBadgeClass } from "@/lib/status-badge";
@@ -34,14 +35,23 @@ import { useSettings } from "@/lib/settings";
 import type { AnomalyRow, StorageScanRow } from "@/lib/types";
 import { cn } from "@/lib/utils";
 
-function today() {
-  const now = new Date();
-  const year = now.getFullYear();
-  const month = String(now.getMonth() + 1).padStart(2, "0");
-  const day = String(now.getDate()).padStart(2, "0");
+function toDateValue(value: Date) {
+  const year = value.getFullYear();
+  const month = String(value.getMonth() + 1).padStart(2, "0");
+  const day = String(value.getDate()).padStart(2, "0");
   return `${year}-${month}-${day}`;
 }
 
+function today() {
+  return toDateValue(new Date());
+}
+
+/** Anomaly ids start with the event time in epoch millis. */
+function anomalyIdDate(id?: string) {
+  const millis = Number(id?.split("-")[0]);
+  return Number.isFinite(millis) && millis > 0 ? toDateValue(new Date(millis)) : null;
+}
+
 function escapeCsv(value: string | number | null | undefined) {
   const text = String(value ?? "");
   if (/[",\n]/.test(text)) {
@@ -96,6 +106,8 @@ function isDateValue(value: string) {
 export function Investigate() {
   const { settings } = useSettings();
   const { variants } = useMotionPresets();
+  const search = useSearch({ strict: false });
+  const linkedAnomalyId = typeof search.anomaly === "string" ? search.anomaly : undefined;
 
   const anomaliesRef = React.useRef<HTMLElement | null>(null);
   const storageRef = React.useRef<HTMLElement | null>(null);
@@ -150,6 +162,12 @@ export function Investigate() {
 
   const anomaliesData = anomaliesQuery.data?.items || [];
 
+  const linkedAnomalyQuery = useQuery({
+    queryKey: ["anomaly", settings.baseUrl, settings.apiToken, linkedAnomalyId],
+    enabled: Boolean(linkedAnomalyId),
+    queryFn: () => fetchAnomaly(settings.baseUrl, settings.apiToken, linkedAnomalyId ?? ""),
+  });
+
   const scanQuery = useQuery({
     queryKey: [
       "storage-scan",
@@ -312,14 +330,34 @@ export function Investigate() {
     }
   }, [scanMaxPageIndex, scanPageState.pageIndex]);
 
+  React.useEffect(() => {
+    const linked = linkedAnomalyQuery.data;
+    if (!linked) {
+      return;
+    }
+    const date = anomalyIdDate(linked.id);
+    if (date) {
+      setAnomalyDate(date);
+    }
+    setSelectedAnomaly(linked);
+    anomaliesRef.current?.scrollIntoView({ behavior: "smooth", block: "start" });
+  }, [linkedAnomalyQuery.data]);
+
+  React.useEffect(() => {
+    if (linkedAnomalyQuery.isError) {
+      toast.error(`无法打开异常链接：${(linkedAnomalyQuery.error as Error).message}`);
+    }
+  }, [linkedAnomalyQuery.isError, linkedAnomalyQuery.error]);
+
   React.useEffect(() => {
     if (selectedAnomaly) {
       const exists = anomaliesData.some((row) => anomalyRowKey(row) === anomalyRowKey(selectedAnomaly));
-      if (!exists) {
+      const linked = Boolean(selectedAnomaly.id) && selectedAnomaly.id === linkedAnomalyId;
+      if (!exists && !linked) {
         setSelectedAnomaly(null);
       }
     }
-  }, [anomaliesData, selectedAnomaly]);
+  }, [anomaliesData, selectedAnomaly, linkedAnomalyId]);
 
   React.useEffect(() => {
     if (selectedStorage) {
@@ -544,7 +582,10 @@ export function Investigate() {
                 </div>
                 <div>
                   <div className="text-xs text-muted-foreground">规则 / 原因</div>
-                  <div className="mt-1">{selectedAnomaly.rule_id}</div>
+                  <div className="mt-1">
+                    {selectedAnomaly.rule_id}
+                    {selectedAnomaly.rule_description ? ` · ${selectedAnomaly.rule_description}` : ""}
+                  </div>
                   <div className="mt-2 whitespace-pre-wrap">{selectedAnomaly.reason}</div>
                 </div>
                 <div>
@@ -705,6 +746,9 @@ export function Investigate() {
                 </div>
                 <div>
                   <div className="text-xs text-muted-foreground">原因</div>
+                  {selectedStorage.rule_description ? (
+                    <div className="mt-1">{selectedStorage.rule_description}</div>
+                  ) : null}
                   <div className="mt-2 whitespace-pre-wrap">{selectedStorage.reason}</div>
                 </div>
               </div>
@@ -329,6 +329,9 @@ export function Operations() {
     if (progress.state === "RUNNING") {
       return "运行中";
     }
+    if (progress.stale) {
+      return "进度停滞";
+    }
     const waitingAck =
       queuedAt !== null && !hasProgressAckAfterQueue(progress, queuedAt);
     if (waitingAck) {
@@ -897,6 +900,7 @@ export function Operations() {
                       {item.status === "success" ? "成功" : "失败"} ·{" "}
                       {item.mode.toUpperCase()} · {item.alert_count} 条 ·{" "}
                       {item.rule_ids.join(", ")}
+                      {item.failover ? " · 备用通道" : ""}
                     </span>
                     <span className="text-[11px]">
                       {formatElapsed(Math.max(0, Date.now() - item.timestamp_ms))}
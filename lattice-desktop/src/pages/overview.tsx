@@ -2,7 +2,13 @@ import { useQuery } from "@tanstack/react-query";
 import { motion } from "motion/react";
 import { EmptyState, ErrorState, LoadingState } from "@/components/page-state";
 import { StatusPill } from "@/components/status-pill";
-import { fetchAlertStatus, fetchMetrics, pingHealth, pingReady } from "@/lib/api";
+import {
+  fetchAlertStatus,
+  fetchConfigWarnings,
+  fetchMetrics,
+  pingHealth,
+  pingReady,
+} from "@/lib/api";
 import { parsePrometheusMetrics } from "@/lib/metrics";
 import { useMotionPresets } from "@/lib/motion";
 import { useSettings } from "@/lib/settings";
@@ -35,6 +41,12 @@ export function Overview() {
     refetchInterval: 15_000,
   });
 
+  const warningsQuery = useQuery({
+    queryKey: ["config-warnings", settings.baseUrl, settings.apiToken],
+    queryFn: () => fetchConfigWarnings(settings.baseUrl, settings.apiToken),
+    refetchInterval: 60_000,
+  });
+
   const metrics = metricsQuery.data ? parsePrometheusMetrics(metricsQuery.data) : null;
   const hasError =
     healthQuery.isError || readyQuery.isError || alertQuery.isError || metricsQuery.isError;
@@ -60,6 +72,25 @@ export function Overview() {
         {!hasError && isLoading && <LoadingState className="mt-4" message="状态刷新中..." />}
       </motion.section>
 
+      {warningsQuery.data && warningsQuery.data.length > 0 && (
+        <motion.section className="section" variants={variants.sectionReveal}>
+          <div className="section-header">
+            <div>
+              <div className="section-title">启动检查</div>
+              <div className="section-meta">以下配置可用但可能不是预期，建议逐项确认</div>
+            </div>
+          </div>
+          <div className="space-y-2 text-sm">
+            {warningsQuery.data.map((warning) => (
+              <div key={warning.code} className="flex items-start gap-3">
+                <span className="font-mono text-[11px] text-foreground">{warning.key}</span>
+                <span className="text-muted-foreground">{warning.message}</span>
+              </div>
+            ))}
+          </div>
+        </motion.section>
+      )}
+
       <motion.section className="section" variants={variants.sectionReveal}>
         <div className="section-header">
           <div>
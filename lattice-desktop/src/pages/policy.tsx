@@ -15,7 +15,7 @@ import { statusBadgeClass } from "@/lib/status-badge";
 import { useSettings } from "@/lib/settings";
 import type { ItemRegistryEntry, KeyItemRule, RiskLevel } from "@/lib/types";
 
-const riskOptions: RiskLevel[] = ["HIGH", "MEDIUM", "LOW"];
+const riskOptions: RiskLevel[] = ["CRITICAL", "HIGH", "MEDIUM", "LOW"];
 
 export function Policy() {
   const { settings } = useSettings();
@@ -244,6 +244,7 @@ export function Policy() {
                     ...rule,
                     item_id: rule.item_id.trim().toLowerCase(),
                     threshold: Number(rule.threshold) || 1,
+                    daily_quota: Number(rule.daily_quota) > 0 ? Number(rule.daily_quota) : null,
                   }))
                   .filter((rule) => rule.item_id.length > 0);
                 saveMutation.mutate(cleaned);
@@ -260,6 +261,7 @@ export function Policy() {
             <TableRow>
               <TableHead>Item ID</TableHead>
               <TableHead>阈值</TableHead>
+              <TableHead>每日配额</TableHead>
               <TableHead>风险</TableHead>
               <TableHead>操作</TableHead>
             </TableRow>
@@ -267,10 +269,10 @@ export function Policy() {
           <TableBody>
             {rules.map((rule, index) => (
               <TableRow key={`${rule.item_id}-${index}`}>
-                <TableCell className="w-[45%]">
+                <TableCell className="w-[35%]">
                   <Input value={rule.item_id} onChange={(event) => updateRule(index, { item_id: event.target.value })} />
                 </TableCell>
-                <TableCell className="w-[20%]">
+                <TableCell className="w-[15%]">
                   <Input
                     type="number"
                     min={1}
@@ -278,6 +280,17 @@ export function Policy() {
                     onChange={(event) => updateRule(index, { threshold: Number(event.target.value || 1) })}
                   />
                 </TableCell>
+                <TableCell className="w-[15%]">
+                  <Input
+                    type="number"
+                    min={1}
+                    placeholder="不限"
+                    value={rule.daily_quota ?? ""}
+                    onChange={(event) =>
+                      updateRule(index, { daily_quota: event.target.value ? Number(event.target.value) : null })
+                    }
+                  />
+                </TableCell>
                 <TableCell className="w-[20%]">
                   <Select
                     value={rule.risk_level}
@@ -304,7 +317,7 @@ export function Policy() {
             ))}
             {rules.length === 0 && (
               <TableRow>
-                <TableCell colSpan={4} className="text-center text-muted-foreground">
+                <TableCell colSpan={5} className="text-center text-muted-foreground">
                   暂无规则
                 </TableCell>
               </TableRow>
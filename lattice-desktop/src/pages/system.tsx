@@ -23,6 +23,11 @@ import { useSettings } from "@/lib/settings";
 type BackendRuntimeStatus = {
   running: boolean;
   last_error?: string | null;
+  base_url?: string | null;
+};
+
+type DesktopSettings = {
+  auto_start_backend: boolean;
 };
 
 type UiLang = "zh_cn" | "en_us";
@@ -397,6 +402,8 @@ export function System() {
   const [saving, setSaving] = React.useState(false);
   const [backendRuntime, setBackendRuntime] =
     React.useState<BackendRuntimeStatus | null>(null);
+  const [desktopSettings, setDesktopSettings] =
+    React.useState<DesktopSettings | null>(null);
   const [modServerId, setModServerId] = React.useState("server-01");
   const [modConfigForm, setModConfigForm] = React.useState<ModConfigForm>(
     MOD_CONFIG_DEFAULTS,
@@ -486,10 +493,20 @@ export function System() {
     }
   }, []);
 
+  const loadDesktopSettings = React.useCallback(async () => {
+    try {
+      const data = await invoke<DesktopSettings>("desktop_settings_get");
+      setDesktopSettings(data);
+    } catch {
+      setDesktopSettings(null);
+    }
+  }, []);
+
   React.useEffect(() => {
     loadConfig();
     loadRuntimeStatus();
-  }, [loadConfig, loadRuntimeStatus]);
+    loadDesktopSettings();
+  }, [loadConfig, loadDesktopSettings, loadRuntimeStatus]);
 
   function saveConnection() {
     updateSettings({
@@ -538,6 +555,30 @@ export function System() {
     }
   }
 
+  async function onAutoStartChange(value: string) {
+    const next = { auto_start_backend: value === "on" };
+    try {
+      await invoke("desktop_settings_set", { settings: next });
+      setDesktopSettings(next);
+      toast.success(next.auto_start_backend ? "启动时自动运行后端" : "后端改为手动启动");
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "保存失败");
+    }
+  }
+
+  async function startBackend() {
+    try {
+      setSaving(true);
+      await invoke("backend_start");
+      toast.success("后端已启动");
+    } catch (error) {
+      toast.error(error instanceof Error ? error.message : "启动失败");
+    } finally {
+      await loadRuntimeStatus();
+      setSaving(false);
+    }
+  }
+
   async function restartBackend() {
     try {
       setSaving(true);
@@ -604,9 +645,20 @@ export function System() {
         </div>
         <TableStateBanner
           message={`嵌入后端: ${backendRuntime?.running ? "运行中" : "未运行"}${
-            backendRuntime?.last_error ? `（${backendRuntime.last_error}）` : ""
-          }`}
+            backendRuntime?.base_url ? ` @ ${backendRuntime.base_url}` : ""
+          }${backendRuntime?.last_error ? `（${backendRuntime.last_error}）` : ""}`}
         />
+        {backendRuntime && !backendRuntime.running ? (
+          <Button
+            variant="secondary"
+            size="sm"
+            className="justify-self-start"
+            disabled={saving}
+            onClick={startBackend}
+          >
+            启动嵌入后端
+          </Button>
+        ) : null}
 
         <div className="grid gap-4 lg:grid-cols-2">
           <div className="grid gap-2">
@@ -617,6 +669,16 @@ export function System() {
               onChange={(event) => setBaseUrl(event.target.value)}
               placeholder="http://127.0.0.1:3234"
             />
+            {backendRuntime?.base_url && backendRuntime.base_url !== baseUrl.trim() ? (
+              <Button
+                variant="secondary"
+                size="sm"
+                className="justify-self-start"
+                onClick={() => setBaseUrl(backendRuntime.base_url ?? "")}
+              >
+                使用嵌入后端地址
+              </Button>
+            ) : null}
           </div>
 
           <div className="grid gap-2">
@@ -657,6 +719,23 @@ export function System() {
             </Select>
           </div>
 
+          <div className="grid gap-2">
+            <Label>启动时运行后端</Label>
+            <Select
+              value={desktopSettings?.auto_start_backend === false ? "off" : "on"}
+              onValueChange={onAutoStartChange}
+              disabled={!desktopSettings}
+            >
+              <SelectTrigger>
+                <SelectValue placeholder="选择模式" />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="on">自动启动</SelectItem>
+                <SelectItem value="off">手动启动</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+
           <div className="grid gap-2">
             <Label>调试模式</Label>
             <Select
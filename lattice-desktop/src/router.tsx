@@ -9,7 +9,8 @@ import {
   useRouter,
   useRouterState,
 } from "@tanstack/react-router";
-import { isTauri } from "@tauri-apps/api/core";
+import { invoke, isTauri } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { AnimatePresence, motion } from "motion/react";
 import {
   Compass,
@@ -20,6 +21,7 @@ import {
   Wrench,
 } from "lucide-react";
 import { AboutDialog } from "@/components/about-dialog";
+import { ClickhouseOnboardingDialog } from "@/components/clickhouse-onboarding-dialog";
 import { ItemMarkIcon } from "@/components/item-mark";
 import { useMotionPresets } from "@/lib/motion";
 import { cn } from "@/lib/utils";
@@ -80,6 +82,16 @@ const commandItemsSeed: CommandItem[] = [
 
 const tauriReady = isTauri();
 
+export type InvestigateSearch = {
+  anomaly?: string;
+};
+
+/** `lattice://anomaly/{id}` -> anomaly id, as generated by backend alerts and reports. */
+function parseAnomalyLink(url: string) {
+  const match = /^lattice:\/\/anomaly\/([^/?#]+)/.exec(url.trim());
+  return match ? decodeURIComponent(match[1]) : null;
+}
+
 function NavButton({ item }: { item: NavItem }) {
   const pathname = useRouterState({
     select: (state) => state.location.pathname,
@@ -155,6 +167,28 @@ function Layout() {
     return () => document.removeEventListener("keydown", handleKey, true);
   }, [router]);
 
+  React.useEffect(() => {
+    if (!tauriReady) {
+      return;
+    }
+    let disposed = false;
+    async function openPending() {
+      const url = await invoke<string | null>("deep_link_take");
+      const anomaly = url ? parseAnomalyLink(url) : null;
+      if (anomaly && !disposed) {
+        router.navigate({ to: "/investigate", search: { anomaly } });
+      }
+    }
+    void openPending();
+    const unlisten = listen<string>("deep-link", () => {
+      void openPending();
+    });
+    return () => {
+      disposed = true;
+      void unlisten.then((off) => off());
+    };
+  }, [router]);
+
   React.useEffect(() => {
     if (commandOpen) {
       requestAnimationFrame(() => {
@@ -340,6 +374,7 @@ function Layout() {
       </AnimatePresence>
 
       <AboutDialog open={aboutOpen} onOpenChange={setAboutOpen} />
+      <ClickhouseOnboardingDialog />
     </div>
   );
 }
@@ -368,6 +403,9 @@ const investigateRoute = createRoute({
   getParentRoute: () => rootRoute,
   path: "investigate",
   component: Investigate,
+  validateSearch: (search: Record<string, unknown>): InvestigateSearch => ({
+    anomaly: typeof search.anomaly === "string" && search.anomaly ? search.anomaly : undefined,
+  }),
 });
 
 const policyRoute = createRoute({
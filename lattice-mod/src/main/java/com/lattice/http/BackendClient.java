@@ -1,16 +1,23 @@
 package com.lattice.http;
 
+import com.lattice.Lattice;
 import com.lattice.config.LatticeConfig;
+import net.fabricmc.loader.api.FabricLoader;
 
 import java.net.URI;
 import java.net.http.HttpClient;
 import java.net.http.HttpRequest;
 import java.time.Duration;
+import java.util.UUID;
 
 public final class BackendClient {
     private static final HttpClient CLIENT = HttpClient.newBuilder()
         .connectTimeout(Duration.ofSeconds(5))
         .build();
+    private static final String MOD_VERSION = FabricLoader.getInstance()
+        .getModContainer(Lattice.MOD_ID)
+        .map(container -> container.getMetadata().getVersion().getFriendlyString())
+        .orElse("");
 
     private BackendClient() {
     }
@@ -31,6 +38,12 @@ public final class BackendClient {
         if (!token.isEmpty()) {
             builder.header("Authorization", "Bearer " + token);
         }
+        if (!MOD_VERSION.isEmpty()) {
+            builder.header("X-Lattice-Mod-Version", MOD_VERSION);
+        }
+        // Every request is built afresh, so a retry carries a new nonce and passes replay checks.
+        builder.header("X-Lattice-Timestamp", Long.toString(System.currentTimeMillis()));
+        builder.header("X-Lattice-Nonce", UUID.randomUUID().toString());
         return builder;
     }
 